@@ -0,0 +1,453 @@
+use std::{io::Write, path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Context, Error, Result};
+use clap::{Parser, Subcommand};
+use keri_core::{
+    database::redb::RedbDatabase,
+    event_message::signed_event_message::{Message, Notice},
+    oobi_manager::OobiManager,
+    prefix::{IdentifierPrefix, SelfSigningPrefix},
+};
+use keri_sdk::{
+    Acdc, Controller, EncryptedFileKeyStore, HttpOobiFetcher, HttpTransport,
+    Identifier, OobiResolver, TransportAdapter, WitnessPublisher,
+    KEL_QUERY_PAGE_SIZE,
+};
+use said::SelfAddressingIdentifier;
+use teliox::database::redb::RedbTelDatabase;
+
+type CliController = Controller<RedbDatabase, RedbTelDatabase>;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Cli {
+    /// Directory of controller databases.
+    #[arg(short = 'd', long, default_value = "keriox-db")]
+    db_path: PathBuf,
+
+    /// Key store file, `keys.json` in database directory by default.
+    #[arg(short = 'k', long)]
+    keystore: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Identifiers are given by prefix or by name set on inception.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Incepts identifier with keys kept in key store.
+    Incept {
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Rotates identifier to its next keys.
+    Rotate { id: String },
+    /// Anchors SAIDs in identifier's KEL.
+    Anchor {
+        id: String,
+        #[arg(required = true)]
+        saids: Vec<String>,
+    },
+    /// Resolves OOBI URL.
+    ResolveOobi { url: String },
+    /// Queries witness for KEL of other identifier. Location of the
+    /// witness has to be known, e.g. from resolved OOBI.
+    Query {
+        id: String,
+        #[arg(long)]
+        of: String,
+        #[arg(long)]
+        witness: String,
+    },
+    /// Incepts credential registry managed by identifier.
+    Registry { id: String },
+    /// Issues ACDC and prints it signed by identifier.
+    Issue {
+        id: String,
+        #[arg(long)]
+        registry: String,
+        /// SAID of credential schema.
+        #[arg(long)]
+        schema: String,
+        /// Attributes as JSON object.
+        #[arg(long)]
+        attributes: String,
+    },
+    /// Revokes credential issued in registry.
+    Revoke {
+        id: String,
+        #[arg(long)]
+        registry: String,
+        #[arg(long)]
+        said: String,
+    },
+    /// Verifies signed data or credential read from file. Prints signer.
+    Verify { file: PathBuf },
+    /// Prints KEL of identifier with its receipts as CESR stream.
+    Export { id: String },
+    /// Lists managed identifiers with their names.
+    List,
+}
+
+/// Environment variable with passphrase of key store. It's prompted for if
+/// the variable isn't set.
+const PASSPHRASE_VAR: &str = "KERIOX_PASSPHRASE";
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    run(cli, &passphrase()?, &mut std::io::stdout())
+}
+
+/// Reads key store passphrase, which isn't taken as argument so it doesn't
+/// show up in shell history or process list.
+fn passphrase() -> Result<String> {
+    match std::env::var(PASSPHRASE_VAR) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => dialoguer::Password::new()
+            .with_prompt("Key store passphrase")
+            .interact()
+            .context("Can't read passphrase"),
+    }
+}
+
+fn run(cli: Cli, passphrase: &str, out: &mut dyn Write) -> Result<()> {
+    let keystore = cli
+        .keystore
+        .unwrap_or_else(|| cli.db_path.join("keys.json"));
+    let controller = Controller::builder()
+        .with_db_path(&cli.db_path)
+        .with_keystore(Arc::new(EncryptedFileKeyStore::new(keystore)))
+        .with_oobi_fetcher(Arc::new(HttpOobiFetcher::new()))
+        .build()
+        .map_err(Error::msg)?;
+    controller.unlock(passphrase).map_err(Error::msg)?;
+
+    match cli.command {
+        Command::Incept { name } => {
+            let identifier =
+                controller.incept_with_keystore().map_err(Error::msg)?;
+            let identifier =
+                controller.manage(identifier).map_err(Error::msg)?;
+            if let Some(name) = name {
+                controller
+                    .set_identifier_name(&identifier.id, &name)
+                    .map_err(Error::msg)?;
+            }
+            writeln!(out, "{}", identifier.id)?;
+        }
+        Command::Rotate { id } => {
+            let identifier = identifier(&controller, &id)?;
+            let keystore = controller
+                .keystore()
+                .ok_or(anyhow!("No keystore configured"))?;
+            let rot = identifier.rotate(keystore).map_err(Error::msg)?;
+            publish(&controller, &identifier, Notice::Event(rot))?;
+            writeln!(out, "{}", identifier.id)?;
+        }
+        Command::Anchor { id, saids } => {
+            let identifier = identifier(&controller, &id)?;
+            let saids = saids
+                .iter()
+                .map(|said| parse_said(said))
+                .collect::<Result<Vec<_>>>()?;
+            let ixn = identifier.anchor(&saids).map_err(Error::msg)?;
+            let sig = sign(&controller, &identifier, ixn.as_bytes())?;
+            let ixn = identifier
+                .finalize_anchor(ixn.as_bytes(), sig)
+                .map_err(Error::msg)?;
+            let digest = ixn.event_message.digest()?;
+            publish(&controller, &identifier, Notice::Event(ixn))?;
+            writeln!(out, "{}", digest)?;
+        }
+        Command::ResolveOobi { url } => {
+            let id = oobi_resolver(&controller)
+                .resolve_oobi(&url)
+                .map_err(Error::msg)?;
+            writeln!(out, "{}", id)?;
+        }
+        Command::Query { id, of, witness } => {
+            let identifier = identifier(&controller, &id)?;
+            let witness = parse_prefix(&witness)?;
+            let signer = |query: &[u8]| {
+                controller.sign_with_keystore(&identifier.id, query)
+            };
+            let received = identifier
+                .query_kel(
+                    &parse_prefix(&of)?,
+                    0,
+                    &witness,
+                    KEL_QUERY_PAGE_SIZE,
+                    &signer,
+                    &transport(&controller, std::slice::from_ref(&witness))?,
+                )
+                .map_err(Error::msg)?;
+            writeln!(out, "{}", received)?;
+        }
+        Command::Registry { id } => {
+            let identifier = identifier(&controller, &id)?;
+            let (ixn, vcp) = controller
+                .incept_registry(&identifier)
+                .map_err(Error::msg)?;
+            let sig = sign(&controller, &identifier, ixn.as_bytes())?;
+            let registry = controller
+                .finalize_incept_registry(
+                    &identifier,
+                    ixn.as_bytes(),
+                    sig,
+                    vcp.as_bytes(),
+                    &witness_transport(&controller, &identifier)?,
+                )
+                .map_err(Error::msg)?;
+            writeln!(out, "{}", registry)?;
+        }
+        Command::Issue {
+            id,
+            registry,
+            schema,
+            attributes,
+        } => {
+            let identifier = identifier(&controller, &id)?;
+            let attributes = serde_json::from_str(&attributes)
+                .context("Attributes are not a JSON object")?;
+            let acdc = Acdc::new(
+                identifier.id.clone(),
+                Some(parse_prefix(&registry)?),
+                parse_said(&schema)?,
+                attributes,
+            )
+            .map_err(Error::msg)?;
+            let (ixn, iss) = controller
+                .issue_acdc(&identifier, &acdc)
+                .map_err(Error::msg)?;
+            let sig = sign(&controller, &identifier, ixn.as_bytes())?;
+            controller
+                .finalize_issue_credential(
+                    &identifier,
+                    ixn.as_bytes(),
+                    sig,
+                    iss.as_bytes(),
+                    &witness_transport(&controller, &identifier)?,
+                )
+                .map_err(Error::msg)?;
+            let data = acdc.encode().map_err(Error::msg)?;
+            let sig = sign(&controller, &identifier, &data)?;
+            let signed =
+                identifier.sign_data(&data, sig).map_err(Error::msg)?;
+            out.write_all(&signed)?;
+            writeln!(out)?;
+        }
+        Command::Revoke { id, registry, said } => {
+            let identifier = identifier(&controller, &id)?;
+            let said = parse_said(&said)?;
+            let (ixn, rev) = controller
+                .revoke_credential(
+                    &identifier,
+                    &parse_prefix(&registry)?,
+                    &said,
+                )
+                .map_err(Error::msg)?;
+            let sig = sign(&controller, &identifier, ixn.as_bytes())?;
+            controller
+                .finalize_revoke_credential(
+                    &identifier,
+                    ixn.as_bytes(),
+                    sig,
+                    rev.as_bytes(),
+                    &witness_transport(&controller, &identifier)?,
+                )
+                .map_err(Error::msg)?;
+            writeln!(out, "{}", said)?;
+        }
+        Command::Verify { file } => {
+            let stream = std::fs::read(&file)
+                .with_context(|| format!("Can't read {}", file.display()))?;
+            let stream = stream.trim_ascii_end();
+            let (signer, data) =
+                controller.verify_signed_data(stream).map_err(Error::msg)?;
+            // Credentials are verified along with their TEL status.
+            if Acdc::parse(&data).is_ok() {
+                controller.verify_credential(stream).map_err(Error::msg)?;
+            }
+            writeln!(out, "{}", signer)?;
+        }
+        Command::Export { id } => {
+            let id = parse_id(&controller, &id)?;
+            let kel = controller
+                .kel
+                .storage
+                .get_kel_messages_with_receipts_all(&id)?
+                .ok_or(anyhow!("No KEL of {}", id))?;
+            for notice in kel {
+                out.write_all(&Message::Notice(notice).to_cesr()?)?;
+            }
+            writeln!(out)?;
+        }
+        Command::List => {
+            for id in controller.identifiers().map_err(Error::msg)? {
+                let metadata =
+                    controller.identifier_metadata(&id).map_err(Error::msg)?;
+                writeln!(out, "{}\t{}", id, metadata.name.unwrap_or_default())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns managed identifier of given name, or given prefix.
+fn parse_id(controller: &CliController, id: &str) -> Result<IdentifierPrefix> {
+    match controller.identifier_by_name(id).map_err(Error::msg)? {
+        Some(id) => Ok(id),
+        None => parse_prefix(id),
+    }
+}
+
+fn parse_prefix(id: &str) -> Result<IdentifierPrefix> {
+    id.parse().map_err(|_| anyhow!("Invalid identifier {}", id))
+}
+
+fn parse_said(said: &str) -> Result<SelfAddressingIdentifier> {
+    said.parse().map_err(|_| anyhow!("Invalid SAID {}", said))
+}
+
+fn identifier(
+    controller: &CliController,
+    id: &str,
+) -> Result<Arc<Identifier<RedbDatabase>>> {
+    controller
+        .identifier(&parse_id(controller, id)?)
+        .map_err(Error::msg)
+}
+
+fn sign(
+    controller: &CliController,
+    identifier: &Identifier<RedbDatabase>,
+    data: &[u8],
+) -> Result<SelfSigningPrefix> {
+    controller
+        .sign_with_keystore(&identifier.id, data)
+        .map_err(Error::msg)
+}
+
+fn oobi_resolver(controller: &CliController) -> OobiResolver<RedbDatabase> {
+    controller.kel.oobi_resolver(
+        Arc::new(OobiManager::new(controller.kel.storage.events_db.clone())),
+        Arc::new(HttpOobiFetcher::new()),
+    )
+}
+
+/// HTTP transport to `recipients`, whose locations are taken from
+/// resolved OOBIs.
+fn transport(
+    controller: &CliController,
+    recipients: &[IdentifierPrefix],
+) -> Result<TransportAdapter> {
+    let resolver = oobi_resolver(controller);
+    let transport = HttpTransport::new();
+    for recipient in recipients {
+        for location in
+            resolver.get_loc_schemes(recipient).map_err(Error::msg)?
+        {
+            transport.add_location(&location).map_err(Error::msg)?;
+        }
+    }
+    Ok(TransportAdapter(Arc::new(transport)))
+}
+
+fn witness_transport(
+    controller: &CliController,
+    identifier: &Identifier<RedbDatabase>,
+) -> Result<TransportAdapter> {
+    let witnesses = controller
+        .identifier_witnesses(&identifier.id)
+        .map_err(Error::msg)?
+        .into_iter()
+        .map(IdentifierPrefix::Basic)
+        .collect::<Vec<_>>();
+    transport(controller, &witnesses)
+}
+
+/// Sends event to witnesses of identifier.
+fn publish(
+    controller: &CliController,
+    identifier: &Identifier<RedbDatabase>,
+    notice: Notice,
+) -> Result<()> {
+    let witnesses = controller
+        .identifier_witnesses(&identifier.id)
+        .map_err(Error::msg)?;
+    if witnesses.is_empty() {
+        return Ok(());
+    }
+    let publisher = witness_transport(controller, identifier)?;
+    let stream = Message::Notice(notice).to_cesr()?;
+    for witness in witnesses {
+        publisher.publish(&witness, &stream).map_err(Error::msg)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use said::derivation::{HashFunction, HashFunctionCode};
+    use serde_json::Value;
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_cli() -> Result<()> {
+        let root = Builder::new().prefix("test-db").tempdir()?;
+        let db = root.path().join("db");
+        let keriox = |args: &[&str]| -> Result<String> {
+            let cli = Cli::try_parse_from(
+                ["keriox", "--db-path", &db.to_string_lossy()]
+                    .into_iter()
+                    .chain(args.iter().copied()),
+            )?;
+            let mut out = vec![];
+            run(cli, "passphrase", &mut out)?;
+            Ok(String::from_utf8(out)?.trim_end().to_string())
+        };
+
+        let id = keriox(&["incept", "--name", "alice"])?;
+        assert_eq!(keriox(&["list"])?, format!("{}\talice", id));
+        assert_eq!(keriox(&["rotate", "alice"])?, id);
+        let document =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"doc");
+        keriox(&["anchor", "alice", &document.to_string()])?;
+
+        let registry = keriox(&["registry", &id])?;
+        let schema =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"schema");
+        let credential = keriox(&[
+            "issue",
+            "alice",
+            "--registry",
+            &registry,
+            "--schema",
+            &schema.to_string(),
+            "--attributes",
+            r#"{"name":"Bob"}"#,
+        ])?;
+        let file = root.path().join("credential.cesr");
+        std::fs::write(&file, &credential)?;
+        let file = file.to_string_lossy();
+        assert_eq!(keriox(&["verify", &file])?, id);
+
+        // Credential is followed by its signature in the stream.
+        let acdc = serde_json::Deserializer::from_str(&credential)
+            .into_iter::<Value>()
+            .next()
+            .unwrap()?;
+        let said = acdc["d"].as_str().unwrap();
+        keriox(&["revoke", "alice", "--registry", &registry, "--said", said])?;
+        assert!(keriox(&["verify", &file]).is_err());
+
+        let kel = keriox(&["export", "alice"])?;
+        // Inception, rotation and interactions anchoring document, registry,
+        // issuance and revocation.
+        assert_eq!(kel.matches("\"t\":").count(), 6);
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use keri_core::{
         signed_event_message::{Message, Op},
         EventTypeTag,
     },
-    mailbox::exchange::{Exchange, ForwardTopic, SignedExchange},
+    mailbox::exchange::{ForwardTopic, SignedExchange},
     prefix::{BasicPrefix, IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
 };
 
@@ -171,10 +171,12 @@ impl Identifier {
         let parsed_exn =
             parse_event_type(exchange).map_err(|_e| MechanicsError::EventFormatError)?;
         if let EventType::Exn(exn) = parsed_exn {
-            let Exchange::Fwd {
-                args: _,
-                to_forward,
-            } = exn.data.data.clone();
+            let to_forward = exn
+                .data
+                .data
+                .event()
+                .ok_or(MechanicsError::WrongEventTypeError)?
+                .clone();
 
             let sigs: Vec<_> = if let Some(receipts) = self.known_events.find_receipt(
                 &to_forward.data.get_prefix(),
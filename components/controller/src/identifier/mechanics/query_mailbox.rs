@@ -57,15 +57,15 @@ impl Identifier {
 
                 Ok(MailboxQuery::new_query(
                     MailboxRoute::Mbx {
-                        args: QueryArgsMbx {
-                            // about who
-                            i: identifier.clone(),
+                        args: QueryArgsMbx::new(
                             // who is asking
-                            pre: self.id.clone(),
+                            self.id.clone(),
+                            // about who
+                            identifier.clone(),
                             // who will get the query
-                            src: recipient,
-                            topics: reminder.to_query_topics(),
-                        },
+                            recipient,
+                            reminder.to_query_topics(),
+                        ),
                         reply_route: "".to_string(),
                     },
                     SerializationFormats::JSON,
@@ -43,10 +43,7 @@ impl Identifier {
             digest: ixn.digest()?,
         };
 
-        let verifiable_event = VerifiableEvent {
-            event: vcp,
-            seal: AttachedSourceSeal { seal: source_seal },
-        };
+        let verifiable_event = VerifiableEvent::new(vcp, AttachedSourceSeal { seal: source_seal });
 
         tel.processor.process(verifiable_event)?;
         self.registry_id = Some(id.clone());
@@ -40,10 +40,8 @@ impl Identifier {
                     digest: ixn.digest()?,
                 };
 
-                let verifiable_event = VerifiableEvent {
-                    event: iss,
-                    seal: AttachedSourceSeal { seal: source_seal },
-                };
+                let verifiable_event =
+                    VerifiableEvent::new(iss, AttachedSourceSeal { seal: source_seal });
                 tel.processor.process(verifiable_event)?;
 
                 Ok((vc_hash, ixn))
@@ -77,10 +75,8 @@ impl Identifier {
                 };
                 let encoded_ixn = ixn.encode()?;
 
-                let verifiable_event = VerifiableEvent {
-                    event: rev,
-                    seal: AttachedSourceSeal { seal: source_seal },
-                };
+                let verifiable_event =
+                    VerifiableEvent::new(rev, AttachedSourceSeal { seal: source_seal });
                 tel.processor.process(verifiable_event)?;
 
                 Ok(encoded_ixn)
@@ -83,7 +83,7 @@ impl KnownEvents {
             path.push("escrow");
             EscrowDatabase::new(&path).map_err(|e| ControllerError::OtherError(e.to_string()))?
         };
-        let (tel_bus, missing_issuer, _out_of_order, _missing_registy) =
+        let (tel_bus, missing_issuer, _out_of_order, _missing_registy, _partially_receipted) =
             tel_escrow_bus(tel_events_db.clone(), kel_storage.clone(), tel_escrow_db)?;
 
         let tel_storage = Arc::new(TelEventStorage::new(tel_events_db.clone()));
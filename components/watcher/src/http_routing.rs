@@ -30,6 +30,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         "/resolve",
         actix_web::web::post().to(http_handlers::resolve_oobi),
     )
+    .route(
+        "/duplicity/{id}",
+        actix_web::web::get().to(http_handlers::get_duplicity),
+    )
     .route(
         "/query/tel",
         actix_web::web::post().to(http_handlers::process_tel_query),
@@ -259,3 +259,83 @@ pub async fn watcher_forward_ksn() -> Result<(), ActorError> {
 
     Ok(())
 }
+
+#[test]
+fn test_duplicity_detection() -> Result<(), ActorError> {
+    use keri_core::{
+        actor::event_generator,
+        event::sections::seal::{DigestSeal, Seal},
+        prefix::IndexedSignature,
+        signer::{CryptoBox, KeyManager},
+    };
+
+    let key_manager = Arc::new(Mutex::new(CryptoBox::new().unwrap()));
+    let mut controller = {
+        let events_db_path = Builder::new().tempfile().unwrap();
+        let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+        SimpleController::new(events_db, key_manager.clone(), EscrowConfig::default()).unwrap()
+    };
+    let icp = controller.incept(None, None, None).unwrap();
+    let inception_state = controller.get_state().unwrap();
+    let ixn = controller.anchor(&[]).unwrap();
+
+    // Interaction event of the same sn, signed with controller's keys.
+    let conflicting_ixn = event_generator::anchor_with_seal(
+        inception_state.clone(),
+        &[Seal::Digest(DigestSeal::new(icp.event_message.digest()?))],
+    )?;
+    let signature = key_manager
+        .lock()
+        .unwrap()
+        .sign(&conflicting_ixn.encode()?)?;
+    let conflicting_ixn = conflicting_ixn.sign(
+        vec![IndexedSignature::new_both_same(
+            SelfSigningPrefix::Ed25519Sha512(signature),
+            0,
+        )],
+        None,
+        None,
+    );
+
+    let root = Builder::new().prefix("cont-test-db").tempdir().unwrap();
+    let watcher_tel_dir = Builder::new().prefix("cont-test-tel-db").tempdir().unwrap();
+    let watcher = Watcher::new(WatcherConfig {
+        public_address: url::Url::parse("http://some/dummy/url").unwrap(),
+        db_path: root.path().to_owned(),
+        tel_storage_path: watcher_tel_dir.path().join("tel_storage"),
+        ..Default::default()
+    })?;
+
+    for event in [&icp, &ixn] {
+        watcher.parse_and_process_notices(&event.encode()?)?;
+    }
+    // Receiving the same event again isn't duplicity.
+    watcher.parse_and_process_notices(&ixn.encode()?)?;
+    assert!(watcher.duplicitous_events(controller.prefix())?.is_empty());
+
+    // Unsigned conflicting event isn't evidence of duplicity either.
+    let unsigned_ixn = event_generator::anchor_with_seal(
+        inception_state,
+        &[Seal::Digest(DigestSeal::new(ixn.event_message.digest()?))],
+    )?
+    .sign(vec![], None, None);
+    let _ = watcher.parse_and_process_notices(&unsigned_ixn.encode()?);
+    assert!(watcher.duplicitous_events(controller.prefix())?.is_empty());
+
+    watcher.parse_and_process_notices(&conflicting_ixn.encode()?)?;
+    let duplicitous = watcher.duplicitous_events(controller.prefix())?;
+    assert_eq!(duplicitous.len(), 1);
+    assert_eq!(
+        duplicitous[0].event_message.digest()?,
+        conflicting_ixn.event_message.digest()?
+    );
+    // Accepted KEL is left intact.
+    assert_eq!(
+        watcher
+            .watcher_data
+            .get_state_for_prefix(controller.prefix()),
+        controller.get_state()
+    );
+
+    Ok(())
+}
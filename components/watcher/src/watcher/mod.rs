@@ -14,7 +14,7 @@ use keri_core::{
     },
     database::redb::RedbDatabase,
     error::Error,
-    event_message::signed_event_message::Message,
+    event_message::signed_event_message::{Message, SignedEventMessage},
     oobi::{error::OobiError, EndRole, LocationScheme},
     prefix::{BasicPrefix, IdentifierPrefix},
     query::reply_event::{ReplyRoute, SignedReply},
@@ -24,7 +24,7 @@ use teliox::{database::redb::RedbTelDatabase, event::parse_tel_query_stream};
 use teliox::{
     event::verifiable_event::VerifiableEvent,
     processor::{validator::TelEventValidator, TelReplyType},
-    query::TelQueryRoute,
+    query::{TelQueryRoute, TelStateNotice},
 };
 use tokio::sync::mpsc::{channel, Receiver};
 use watcher_data::WatcherData;
@@ -69,6 +69,15 @@ impl Watcher {
         self.watcher_data.get_loc_scheme_for_id(eid)
     }
 
+    /// Returns conflicting versions of `id` events, if any were received.
+    /// Non-empty result means that identifier's controller is duplicitous.
+    pub fn duplicitous_events(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Vec<SignedEventMessage>, ActorError> {
+        Ok(self.watcher_data.get_duplicitous_events(id)?)
+    }
+
     pub async fn process_update_requests(&self) {
         let mut recv = self.recv.lock().unwrap();
 
@@ -236,18 +245,17 @@ impl Watcher {
         let mut out = vec![];
         for qry in tel_queries {
             // TODO Verify signature
-            let (ri, vc_id) = match qry.query.data.data {
-                TelQueryRoute::Tels {
-                    reply_route: _,
-                    args,
-                } => match (args.ri, args.i) {
-                    (Some(ri), Some(i)) => (ri, i),
-                    _ => {
-                        return Err(ActorError::GeneralError(
-                            "Wrong TEL query format. `ri` and `i` field required".to_string(),
-                        ))
-                    }
-                },
+            let (args, state_requested) = match qry.query.data.data {
+                TelQueryRoute::Tels { args, .. } => (args, false),
+                TelQueryRoute::Tsn { args, .. } => (args, true),
+            };
+            let (ri, vc_id) = match (args.ri, args.i) {
+                (Some(ri), Some(i)) => (ri, i),
+                _ => {
+                    return Err(ActorError::GeneralError(
+                        "Wrong TEL query format. `ri` and `i` field required".to_string(),
+                    ))
+                }
             };
             // Query witness about new tel events
             self.watcher_data
@@ -265,7 +273,16 @@ impl Watcher {
                 .get(&ri, &vc_id)
                 .map_err(|e| ActorError::GeneralError(e.to_string()))?
             {
-                out.push(TelReplyType::Tel(tel.clone().as_bytes().to_vec()))
+                if state_requested {
+                    // State is computed from forwarded TEL, which watcher
+                    // doesn't verify.
+                    let notice = VerifiableEvent::parse(tel.as_bytes())
+                        .and_then(|events| TelStateNotice::from_events(&ri, Some(&vc_id), events))
+                        .map_err(|e| ActorError::GeneralError(e.to_string()))?;
+                    out.push(TelReplyType::State(Box::new(notice)))
+                } else {
+                    out.push(TelReplyType::Tel(tel.clone().as_bytes().to_vec()))
+                }
             };
         }
         Ok(out)
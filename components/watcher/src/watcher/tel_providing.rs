@@ -191,6 +191,9 @@ impl RegistryMapping {
     }
 }
 
+#[cfg(test)]
+const FULL_TEL: &str = r#"{"v":"KERI10JSON0000e0_","t":"vcp","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn","i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","ii":"EL2KqdbeSkemPII22qQ9dNglhBYa2YaQL7ePjN-3aTGg","c":["NB"],"bt":"0","b":[]}-GAB0AAAAAAAAAAAAAAAAAAAAAABEKK6lIEGR6MEuweYBwtiQb6giHxj-nvX4uwQY0tOj1KQ{"v":"KERI10JSON000162_","t":"bis","d":"EDoj2ic6WlRrszgT3Hm67-fdxjr-ZxRZMy9O9MXURZDF","i":"ENdJge-nCgyIC42MGYXQddvL9nm5ml-ZFOWq-WuDGp4k","s":"0","ii":"EL2KqdbeSkemPII22qQ9dNglhBYa2YaQL7ePjN-3aTGg","ra":{"i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn"},"dt":"2024-08-01T11:55:26.509238+00:00"}-GAB0AAAAAAAAAAAAAAAAAAAAAACEHUKCdWrjC14xuBc2wMgVlF0zmyhx5XXTtjZC7GVtQ2f{"v":"KERI10JSON000161_","t":"brv","d":"EI6P1iV3bSFI5Y_TEhep-uvrGXxwFVdixPjvLKLRP7Oy","i":"ENdJge-nCgyIC42MGYXQddvL9nm5ml-ZFOWq-WuDGp4k","s":"1","p":"EDoj2ic6WlRrszgT3Hm67-fdxjr-ZxRZMy9O9MXURZDF","ra":{"i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn"},"dt":"2024-08-01T11:55:28.579999+00:00"}-GAB0AAAAAAAAAAAAAAAAAAAAAADEO0smZufH8Yrtl6ac_iqF2QeBCJmT031wYqlEMKbeROV"#;
+
 #[test]
 fn test_tel_to_forward() {
     let tmp_file = tempfile::NamedTempFile::new().unwrap();
@@ -203,18 +206,94 @@ fn test_tel_to_forward() {
     let vc_id: IdentifierPrefix = "ENdJge-nCgyIC42MGYXQddvL9nm5ml-ZFOWq-WuDGp4k"
         .parse()
         .unwrap();
-    let full_tel = r#"{"v":"KERI10JSON0000e0_","t":"vcp","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn","i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","ii":"EL2KqdbeSkemPII22qQ9dNglhBYa2YaQL7ePjN-3aTGg","c":["NB"],"bt":"0","b":[]}-GAB0AAAAAAAAAAAAAAAAAAAAAABEKK6lIEGR6MEuweYBwtiQb6giHxj-nvX4uwQY0tOj1KQ{"v":"KERI10JSON000162_","t":"bis","d":"EDoj2ic6WlRrszgT3Hm67-fdxjr-ZxRZMy9O9MXURZDF","i":"ENdJge-nCgyIC42MGYXQddvL9nm5ml-ZFOWq-WuDGp4k","s":"0","ii":"EL2KqdbeSkemPII22qQ9dNglhBYa2YaQL7ePjN-3aTGg","ra":{"i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn"},"dt":"2024-08-01T11:55:26.509238+00:00"}-GAB0AAAAAAAAAAAAAAAAAAAAAACEHUKCdWrjC14xuBc2wMgVlF0zmyhx5XXTtjZC7GVtQ2f{"v":"KERI10JSON000161_","t":"brv","d":"EI6P1iV3bSFI5Y_TEhep-uvrGXxwFVdixPjvLKLRP7Oy","i":"ENdJge-nCgyIC42MGYXQddvL9nm5ml-ZFOWq-WuDGp4k","s":"1","p":"EDoj2ic6WlRrszgT3Hm67-fdxjr-ZxRZMy9O9MXURZDF","ra":{"i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn"},"dt":"2024-08-01T11:55:28.579999+00:00"}-GAB0AAAAAAAAAAAAAAAAAAAAAADEO0smZufH8Yrtl6ac_iqF2QeBCJmT031wYqlEMKbeROV"#;
     let not_full_tel = r#"{"v":"KERI10JSON0000e0_","t":"vcp","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn","i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","ii":"EL2KqdbeSkemPII22qQ9dNglhBYa2YaQL7ePjN-3aTGg","c":["NB"],"bt":"0","b":[]}-GAB0AAAAAAAAAAAAAAAAAAAAAABEKK6lIEGR6MEuweYBwtiQb6giHxj-nvX4uwQY0tOj1KQ{"v":"KERI10JSON000162_","t":"bis","d":"EDoj2ic6WlRrszgT3Hm67-fdxjr-ZxRZMy9O9MXURZDF","i":"ENdJge-nCgyIC42MGYXQddvL9nm5ml-ZFOWq-WuDGp4k","s":"0","ii":"EL2KqdbeSkemPII22qQ9dNglhBYa2YaQL7ePjN-3aTGg","ra":{"i":"EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH","s":"0","d":"ELKXqawms-uIvL74BTnYHFOPJrjD8N9DaC2-Yljl_OOn"},"dt":"2024-08-01T11:55:26.509238+00:00"}-GAB0AAAAAAAAAAAAAAAAAAAAAACEHUKCdWrjC14xuBc2wMgVlF0zmyhx5XXTtjZC7GVtQ2f"#;
 
     tel_to_forward
-        .save(&registry_id, &vc_id, full_tel.to_string())
+        .save(&registry_id, &vc_id, FULL_TEL.to_string())
         .unwrap();
     let saved = tel_to_forward.get(&registry_id, &vc_id).unwrap();
-    assert_eq!(saved.as_ref(), Some(full_tel.to_string()).as_ref());
+    assert_eq!(saved.as_ref(), Some(FULL_TEL.to_string()).as_ref());
 
     // Try to save only subset of TEL events. Should not be replaced.
     tel_to_forward
         .save(&registry_id, &vc_id, not_full_tel.to_string())
         .unwrap();
-    assert_eq!(saved.as_ref(), Some(full_tel.to_string()).as_ref());
+    assert_eq!(saved.as_ref(), Some(FULL_TEL.to_string()).as_ref());
+}
+
+#[actix_web::test]
+async fn test_tel_state_from_forwarded_tel() {
+    use keri_core::{
+        actor::prelude::{HashFunctionCode, SerializationFormats},
+        event_message::{msg::KeriEvent, timestamped::Timestamped},
+        prefix::{BasicPrefix, SelfSigningPrefix},
+        signer::Signer,
+    };
+    use teliox::{
+        processor::TelReplyType,
+        query::{SignedTelQuery, TelQueryArgs, TelQueryRoute},
+        state::vc_state::TelState,
+    };
+
+    let root = tempfile::Builder::new().tempdir().unwrap();
+    let tel_dir = tempfile::Builder::new().tempdir().unwrap();
+    let watcher = crate::Watcher::new(crate::WatcherConfig {
+        public_address: url::Url::parse("http://some/dummy/url").unwrap(),
+        db_path: root.path().to_owned(),
+        tel_storage_path: tel_dir.path().join("tel_storage"),
+        ..Default::default()
+    })
+    .unwrap();
+    let registry_id: IdentifierPrefix = "EEJeOc0HPZScDMKD-L9RsJ9K5-j73IZkMA2tui5gYEpH"
+        .parse()
+        .unwrap();
+    let vc_id: IdentifierPrefix = "ENdJge-nCgyIC42MGYXQddvL9nm5ml-ZFOWq-WuDGp4k"
+        .parse()
+        .unwrap();
+    watcher
+        .watcher_data
+        .tel_to_forward
+        .save(&registry_id, &vc_id, FULL_TEL.to_string())
+        .unwrap();
+
+    let signer = Signer::new();
+    let query = |i: Option<IdentifierPrefix>| {
+        let route = TelQueryRoute::Tsn {
+            reply_route: "".into(),
+            args: TelQueryArgs {
+                i,
+                ri: Some(registry_id.clone()),
+            },
+        };
+        let qry = KeriEvent::new(
+            SerializationFormats::JSON,
+            HashFunctionCode::Blake3_256.into(),
+            Timestamped::new(route),
+        );
+        let signature =
+            SelfSigningPrefix::Ed25519Sha512(signer.sign(qry.encode().unwrap()).unwrap());
+        SignedTelQuery::new_nontrans(qry, BasicPrefix::Ed25519NT(signer.public_key()), signature)
+            .to_cesr()
+            .unwrap()
+    };
+
+    let mut replies = watcher
+        .parse_and_process_tel_queries(&query(Some(vc_id.clone())))
+        .await
+        .unwrap();
+    assert_eq!(replies.len(), 1);
+    let notice = match replies.remove(0) {
+        TelReplyType::State(notice) => *notice,
+        TelReplyType::Tel(_) => panic!("Expected TEL state notice"),
+    };
+    assert_eq!(notice.registry_id, registry_id);
+    assert_eq!(notice.sn, 0);
+    assert_eq!(notice.vc_id, Some(vc_id));
+    assert_eq!(notice.vc_state, Some(TelState::Revoked));
+
+    // Watcher collects TEL per credential, so registry-only state is unknown.
+    assert!(watcher
+        .parse_and_process_tel_queries(&query(None))
+        .await
+        .is_err());
 }
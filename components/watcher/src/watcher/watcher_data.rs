@@ -8,12 +8,13 @@ use keri_core::error::Error;
 use keri_core::oobi::LocationScheme;
 use keri_core::prefix::{BasicPrefix, IdentifierPrefix, SelfSigningPrefix};
 use keri_core::processor::escrow::default_escrow_bus;
+use keri_core::processor::escrow::duplicitous_events::DuplicitousEvents;
 use keri_core::processor::escrow::reply_escrow::ReplyEscrow;
 use keri_core::query::{
     reply_event::{ReplyEvent, ReplyRoute, SignedReply},
     ReplyType,
 };
-use keri_core::state::IdentifierState;
+use keri_core::state::{EventSemantics, IdentifierState};
 use keri_core::{
     actor::{
         error::ActorError,
@@ -26,7 +27,7 @@ use keri_core::{
     database::redb::RedbDatabase,
     event_message::{
         msg::KeriEvent,
-        signed_event_message::{Message, Notice, Op},
+        signed_event_message::{Message, Notice, Op, SignedEventMessage},
         timestamped::Timestamped,
     },
 };
@@ -64,6 +65,8 @@ pub struct WatcherData {
     pub tel_tx: Sender<(IdentifierPrefix, IdentifierPrefix)>,
     pub(super) tel_to_forward: Arc<TelToForward>,
     reply_escrow: Arc<ReplyEscrow<RedbDatabase>>,
+    /// Events conflicting with already accepted ones.
+    duplicitous: Arc<DuplicitousEvents<RedbDatabase>>,
 }
 
 impl WatcherData {
@@ -99,7 +102,8 @@ impl WatcherData {
 
         let oobi_manager = OobiManager::new(events_db.clone());
 
-        let (notification_bus, _escrows) = default_escrow_bus(events_db.clone(), escrow_config, None);
+        let (notification_bus, escrows) =
+            default_escrow_bus(events_db.clone(), escrow_config, None);
         let reply_escrow = Arc::new(ReplyEscrow::new(events_db.clone()));
         notification_bus.register_observer(
             reply_escrow.clone(),
@@ -150,6 +154,7 @@ impl WatcherData {
             tel_tx,
             tel_transport,
             reply_escrow,
+            duplicitous: escrows.duplicitous,
         });
         Ok(watcher.clone())
     }
@@ -214,6 +219,42 @@ impl WatcherData {
         self.event_storage.get_state(id)
     }
 
+    /// Returns evidence of duplicity of `id`: properly signed events of
+    /// already accepted sn, but with digest different than accepted one.
+    pub fn get_duplicitous_events(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Vec<SignedEventMessage>, Error> {
+        let mut duplicitous = vec![];
+        for event in self.duplicitous.get(id)? {
+            let sn = event.event_message.data.get_sn();
+            let accepted = match self.event_storage.get_event_at_sn(id, sn) {
+                Some(accepted) => accepted.signed_event_message,
+                None => continue,
+            };
+            if accepted.event_message.digest()? == event.event_message.digest()? {
+                // The same event was received again.
+                continue;
+            }
+            let prior_state = match sn.checked_sub(1) {
+                Some(prior_sn) => self
+                    .event_storage
+                    .compute_state_at_sn(id, prior_sn)?
+                    .unwrap_or_default(),
+                None => IdentifierState::default(),
+            };
+            let verified = event.apply_to(prior_state).and_then(|state| {
+                Ok(state
+                    .current
+                    .verify(&event.event_message.encode()?, &event.signatures)?)
+            });
+            if let Ok(true) = verified {
+                duplicitous.push(event);
+            }
+        }
+        Ok(duplicitous)
+    }
+
     pub fn process_notice(&self, notice: Notice) -> Result<(), Error> {
         process_notice(notice, &self.processor)
     }
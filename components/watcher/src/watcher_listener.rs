@@ -70,7 +70,7 @@ pub mod http_handlers {
     use itertools::Itertools;
     use keri_core::{
         actor::{error::ActorError, prelude::Message},
-        event_message::signed_event_message::Op,
+        event_message::signed_event_message::{Notice, Op},
         oobi::{error::OobiError, EndRole, LocationScheme, Role},
         prefix::IdentifierPrefix,
     };
@@ -209,6 +209,23 @@ pub mod http_handlers {
             .body(String::from_utf8(oobis).unwrap()))
     }
 
+    pub async fn get_duplicity(
+        id: web::Path<IdentifierPrefix>,
+        data: web::Data<Arc<Watcher>>,
+    ) -> Result<HttpResponse, ApiError> {
+        let events = data
+            .duplicitous_events(&id)?
+            .into_iter()
+            .map(|event| Message::Notice(Notice::Event(event)).to_cesr())
+            .flatten_ok()
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| ApiError(ActorError::GeneralError(e.to_string())))?;
+
+        Ok(HttpResponse::Ok()
+            .content_type(ContentType::plaintext())
+            .body(String::from_utf8(events).unwrap()))
+    }
+
     pub async fn process_tel_query(
         post_data: String,
         data: web::Data<Arc<Watcher>>,
@@ -218,10 +235,14 @@ pub mod http_handlers {
             .parse_and_process_tel_queries(post_data.as_bytes())
             .await?
             .iter()
-            .map(|msg| msg.to_string())
-            .collect::<Vec<_>>()
-            .join("");
-        println!("\nWatcher responds with: {}", resp);
+            .map(|msg| msg.encode())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ApiError(ActorError::GeneralError(e.to_string())))?
+            .concat();
+        println!(
+            "\nWatcher responds with: {}",
+            String::from_utf8_lossy(&resp)
+        );
         Ok(HttpResponse::Ok()
             .content_type(ContentType::plaintext())
             .body(resp))
@@ -1415,3 +1415,94 @@ pub fn test_delegating_multisig() -> Result<(), ActorError> {
 
     Ok(())
 }
+
+#[test]
+fn test_get_kel() -> Result<(), Error> {
+    let mut controller = {
+        let events_root = Builder::new().tempfile().unwrap();
+        let events_db = Arc::new(RedbDatabase::new(events_root.path()).unwrap());
+        let key_manager = Arc::new(Mutex::new(CryptoBox::new()?));
+        SimpleController::new(Arc::clone(&events_db), key_manager, EscrowConfig::default())?
+    };
+
+    let witness = {
+        let root = Builder::new().prefix("test-witness").tempdir().unwrap();
+        std::fs::create_dir_all(root.path()).unwrap();
+        Witness::setup(
+            Url::parse("http://example.com").unwrap(),
+            root.path(),
+            None,
+            WitnessEscrowConfig::default(),
+        )
+        .unwrap()
+    };
+
+    let icp = controller.incept(Some(vec![witness.prefix.clone()]), Some(1), None)?;
+    assert!(witness.get_kel(controller.prefix())?.is_empty());
+
+    witness.process_notice(Notice::Event(icp.clone()))?;
+    let kel = witness.get_kel(controller.prefix())?;
+    assert!(
+        matches!(kel.first(), Some(Notice::Event(event)) if event.event_message == icp.event_message)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_mailbox_pagination() -> Result<(), Error> {
+    use keri_core::query::mailbox::QueryTopics;
+
+    let mut controller = {
+        let events_root = Builder::new().tempfile().unwrap();
+        let events_db = Arc::new(RedbDatabase::new(events_root.path()).unwrap());
+        let key_manager = Arc::new(Mutex::new(CryptoBox::new()?));
+        SimpleController::new(Arc::clone(&events_db), key_manager, EscrowConfig::default())?
+    };
+
+    let witness = {
+        let root = Builder::new().prefix("test-witness").tempdir().unwrap();
+        std::fs::create_dir_all(root.path()).unwrap();
+        Witness::setup(
+            Url::parse("http://example.com").unwrap(),
+            root.path(),
+            None,
+            WitnessEscrowConfig::default(),
+        )
+        .unwrap()
+    };
+
+    // Witness generates receipt of each of 3 events.
+    let icp = controller.incept(Some(vec![witness.prefix.clone()]), Some(0), None)?;
+    witness.process_notice(Notice::Event(icp))?;
+    for _ in 0..2 {
+        let ixn = controller.anchor(&[])?;
+        witness.process_notice(Notice::Event(ixn))?;
+    }
+    assert_eq!(
+        witness
+            .get_mailbox_messages(controller.prefix())?
+            .receipt
+            .len(),
+        3
+    );
+
+    let topics = QueryTopics::default();
+    let page = witness.get_mailbox_page(controller.prefix(), topics.clone(), Some(2))?;
+    assert_eq!(page.receipt.len(), 2);
+    assert_eq!(page.receipt[0].body.sn, 0);
+
+    let topics = page.next_topics(&topics);
+    assert_eq!(topics.receipt, 2);
+    let page = witness.get_mailbox_page(controller.prefix(), topics.clone(), Some(2))?;
+    assert_eq!(page.receipt.len(), 1);
+    assert_eq!(page.receipt[0].body.sn, 2);
+
+    // Cursor stays in place after the last page.
+    let topics = page.next_topics(&topics);
+    let page = witness.get_mailbox_page(controller.prefix(), topics.clone(), Some(2))?;
+    assert!(page.is_empty());
+    assert_eq!(page.next_topics(&topics), topics);
+
+    Ok(())
+}
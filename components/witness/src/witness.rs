@@ -5,7 +5,7 @@ use std::{
 
 use keri_core::{
     actor::{
-        error::ActorError, parse_exchange_stream, parse_notice_stream, parse_query_stream,
+        error::ActorError, parse_exchange_stream, parse_new_notices, parse_query_stream,
         parse_reply_stream, possible_response::PossibleResponse, prelude::*, process_reply,
         process_signed_exn, process_signed_query,
     },
@@ -21,11 +21,14 @@ use keri_core::{
         signature::Nontransferable,
         signed_event_message::{Notice, SignedNontransferableReceipt},
     },
-    mailbox::MailboxResponse,
+    mailbox::{dispatch::ExchangeDispatcher, MailboxResponse},
     oobi::LocationScheme,
     oobi_manager::OobiManager,
     prefix::{BasicPrefix, IdentifierPrefix, SelfSigningPrefix},
-    processor::notification::{Notification, NotificationBus, Notifier},
+    processor::{
+        notification::{Notification, NotificationBus, Notifier},
+        notify_accepted,
+    },
     query::{
         mailbox::{QueryArgsMbx, QueryTopics},
         reply_event::{ReplyEvent, ReplyRoute, SignedReply},
@@ -69,7 +72,7 @@ impl Notifier for WitnessReceiptGenerator {
                 self.storage
                     .events_db
                     .add_kel_finalized_event(prt.clone(), &prt.event_message.data.get_prefix())?;
-                bus.notify(&Notification::KeyEventAdded(prt.clone()))?;
+                notify_accepted(self.storage.events_db.as_ref(), bus, prt.clone(), None)?;
                 let non_trans_receipt =
                     self.respond_to_key_event(&prt.event_message, self.signer.clone())?;
                 let prefix = &non_trans_receipt.body.prefix.clone();
@@ -142,6 +145,7 @@ pub struct Witness {
     pub prefix: BasicPrefix,
     pub processor: WitnessProcessor,
     pub event_storage: Arc<EventStorage<RedbDatabase>>,
+    pub exchange_dispatcher: ExchangeDispatcher<RedbDatabase>,
     pub oobi_manager: OobiManager,
     pub signer: Arc<Signer>,
     pub receipt_generator: Arc<WitnessReceiptGenerator>,
@@ -200,7 +204,7 @@ impl Witness {
             EscrowDatabase::new(&tel_path)
                 .map_err(|e| WitnessError::DatabaseError(e.to_string()))?
         };
-        let (tel_bus, _missing_issuer, _out_of_order, _missing_registy) =
+        let (tel_bus, _missing_issuer, _out_of_order, _missing_registy, _partially_receipted) =
             default_escrow_bus(tel_events_db.clone(), event_storage.clone(), tel_escrow_db)
                 .unwrap();
 
@@ -210,12 +214,15 @@ impl Witness {
             Some(tel_bus),
         ));
 
+        let exchange_dispatcher = ExchangeDispatcher::with_mailbox(event_storage.clone())?;
+
         Ok(Self {
             address,
             prefix,
             processor: witness_processor,
             signer,
             event_storage,
+            exchange_dispatcher,
             receipt_generator,
             oobi_manager: OobiManager::new(events_db.clone()),
             tel,
@@ -282,6 +289,15 @@ impl Witness {
             .collect()
     }
 
+    /// Returns KEL of `id` with receipts collected by witness, in order of
+    /// acceptance. Empty if identifier is unknown.
+    pub fn get_kel(&self, id: &IdentifierPrefix) -> Result<Vec<Notice>, Error> {
+        Ok(self
+            .event_storage
+            .get_kel_messages_with_receipts_all(id)?
+            .unwrap_or_default())
+    }
+
     pub fn get_signed_ksn_for_prefix(
         &self,
         prefix: &IdentifierPrefix,
@@ -322,7 +338,7 @@ impl Witness {
         &self,
         exn: keri_core::mailbox::exchange::SignedExchange,
     ) -> Result<(), ActorError> {
-        process_signed_exn(exn, &self.event_storage)?;
+        process_signed_exn(exn, &self.exchange_dispatcher)?;
         Ok(())
     }
 
@@ -360,8 +376,10 @@ impl Witness {
         }
     }
 
+    /// Processes notices of stream. Events witness already accepted are
+    /// dropped without being decoded.
     pub fn parse_and_process_notices(&self, input_stream: &[u8]) -> Result<(), Error> {
-        parse_notice_stream(input_stream)?
+        parse_new_notices(input_stream, &self.event_storage)?
             .into_iter()
             .try_for_each(|notice| self.process_notice(notice))
     }
@@ -414,11 +432,9 @@ impl Witness {
     }
 
     pub fn get_mailbox_messages(&self, id: &IdentifierPrefix) -> Result<MailboxResponse, Error> {
-        self.event_storage.get_mailbox_messages(&QueryArgsMbx {
-            pre: IdentifierPrefix::Basic(self.prefix.clone()),
-            i: id.clone(),
-            src: IdentifierPrefix::Basic(self.prefix.clone()),
-            topics: QueryTopics {
+        self.get_mailbox_page(
+            id,
+            QueryTopics {
                 credential: 0,
                 receipt: 0,
                 replay: 0,
@@ -426,6 +442,27 @@ impl Witness {
                 delegate: 0,
                 reply: 0,
             },
-        })
+            None,
+        )
+    }
+
+    /// Returns at most `limit` messages of each topic addressed to `id`,
+    /// starting from indexes provided in `topics`. Topics of the next page
+    /// can be computed with [`MailboxResponse::next_topics`].
+    pub fn get_mailbox_page(
+        &self,
+        id: &IdentifierPrefix,
+        topics: QueryTopics,
+        limit: Option<usize>,
+    ) -> Result<MailboxResponse, Error> {
+        self.event_storage.get_mailbox_messages(
+            &QueryArgsMbx::new(
+                IdentifierPrefix::Basic(self.prefix.clone()),
+                id.clone(),
+                IdentifierPrefix::Basic(self.prefix.clone()),
+                topics,
+            )
+            .with_limit(limit),
+        )
     }
 }
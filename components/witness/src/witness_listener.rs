@@ -54,6 +54,10 @@ impl WitnessListener {
                     "/oobi/{cid}/{role}/{eid}",
                     actix_web::web::get().to(http_handlers::resolve_role),
                 )
+                .route(
+                    "/kel/{id}",
+                    actix_web::web::get().to(http_handlers::get_kel),
+                )
                 .route(
                     "/process",
                     actix_web::web::post().to(http_handlers::process_notice),
@@ -253,6 +257,28 @@ pub mod http_handlers {
             .body(String::from_utf8(oobis).unwrap()))
     }
 
+    pub async fn get_kel(
+        id: web::Path<IdentifierPrefix>,
+        data: web::Data<Arc<Witness>>,
+    ) -> Result<HttpResponse, ApiError> {
+        let kel: Vec<u8> = data
+            .get_kel(&id)
+            .map_err(ActorError::KeriError)?
+            .into_iter()
+            .map(|notice| {
+                Message::Notice(notice)
+                    .to_cesr()
+                    .map_err(|_| Error::CesrError)
+            })
+            .flatten_ok()
+            .try_collect()
+            .map_err(ActorError::KeriError)?;
+
+        Ok(HttpResponse::Ok()
+            .content_type(ContentType::plaintext())
+            .body(String::from_utf8(kel).unwrap()))
+    }
+
     pub async fn resolve_role(
         path: web::Path<(IdentifierPrefix, Role, IdentifierPrefix)>,
         data: web::Data<Arc<Witness>>,
@@ -377,10 +403,14 @@ pub mod http_handlers {
         let resp = data
             .parse_and_process_tel_queries(post_data.as_bytes())?
             .iter()
-            .map(|msg| msg.to_string())
-            .collect::<Vec<_>>()
-            .join("");
-        println!("\nWitness responds with: {}", resp);
+            .map(|msg| msg.encode())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ApiError(ActorError::GeneralError(e.to_string())))?
+            .concat();
+        println!(
+            "\nWitness responds with: {}",
+            String::from_utf8_lossy(&resp)
+        );
         Ok(HttpResponse::Ok()
             .content_type(ContentType::plaintext())
             .body(resp))
@@ -7,7 +7,9 @@ use keri_core::{
             delegation_escrow::DelegationEscrow, maybe_out_of_order_escrow::MaybeOutOfOrderEscrow,
             partially_signed_escrow::PartiallySignedEscrow, EscrowConfig,
         },
+        metrics::ProcessorMetrics,
         notification::{JustNotification, Notification, NotificationBus, Notifier},
+        notify_accepted,
         validator::EventValidator,
         EventProcessor, Processor,
     },
@@ -97,6 +99,11 @@ impl WitnessProcessor {
         Self { processor }
     }
 
+    /// Starts collecting processing statistics into provided metrics.
+    pub fn register_metrics(&self, metrics: Arc<dyn ProcessorMetrics>) -> Result<(), Error> {
+        self.processor.register_metrics(metrics)
+    }
+
     /// Witness processing strategy
     ///
     /// Ignore not fully witness error and accept not fully witnessed events.
@@ -111,7 +118,7 @@ impl WitnessProcessor {
             Ok(_) => {
                 db.add_kel_finalized_event(signed_event.clone(), id)
                     .map_err(|_| Error::DbError)?;
-                publisher.notify(&Notification::KeyEventAdded(signed_event))
+                notify_accepted(db.as_ref(), publisher, signed_event, None)
             }
             Err(Error::EventOutOfOrderError) => {
                 publisher.notify(&Notification::OutOfOrder(signed_event))
@@ -122,7 +129,7 @@ impl WitnessProcessor {
             Err(Error::NotEnoughReceiptsError) => {
                 db.add_kel_finalized_event(signed_event.clone(), id)
                     .map_err(|_| Error::DbError)?;
-                publisher.notify(&Notification::KeyEventAdded(signed_event))
+                notify_accepted(db.as_ref(), publisher, signed_event, None)
             }
             Err(Error::NotEnoughSigsError) => {
                 publisher.notify(&Notification::PartiallySigned(signed_event))
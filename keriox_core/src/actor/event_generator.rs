@@ -1,10 +1,18 @@
 use said::SelfAddressingIdentifier;
+#[cfg(feature = "oobi")]
+use url::Url;
 
 #[cfg(feature = "mailbox")]
-use crate::mailbox::exchange::{Exchange, ExchangeMessage, ForwardTopic, FwdArgs};
-#[cfg(feature = "oobi")]
-use crate::oobi::{EndRole, Role};
+use crate::mailbox::exchange::{
+    Exchange, ExchangeMessage, ExchangeRoute, ForwardTopic, FwdArgs, IpexData,
+};
 #[cfg(feature = "oobi")]
+use crate::oobi::{EndRole, LocationScheme, Role, Scheme};
+#[cfg(feature = "mailbox")]
+use crate::query::mailbox::{MailboxQuery, MailboxRoute, QueryArgsMbx, QueryTopics};
+#[cfg(feature = "query")]
+use crate::query::query_event::{LogsQueryArgs, QueryEvent, QueryRoute};
+#[cfg(feature = "query")]
 use crate::query::reply_event::{ReplyEvent, ReplyRoute};
 use crate::{
     error::Error,
@@ -12,6 +20,7 @@ use crate::{
         sections::{
             seal::{DigestSeal, Seal},
             threshold::{SignatureThreshold, WeightedThreshold},
+            RotationWitnessConfig,
         },
         KeyEvent,
     },
@@ -131,7 +140,8 @@ fn make_rotation(
     witness_to_remove: Vec<BasicPrefix>,
     witness_threshold: u64,
 ) -> Result<KeriEvent<KeyEvent>, Error> {
-    EventMsgBuilder::new(EventTypeTag::Rot)
+    check_not_abandoned(&state)?;
+    EventMsgBuilder::new(rotation_type(&state))
         .with_prefix(&state.prefix)
         .with_sn(state.sn + 1)
         .with_previous_event(&state.last_event_digest.into())
@@ -145,6 +155,118 @@ fn make_rotation(
         .map_err(|e| Error::EventGenerationError(e.to_string()))
 }
 
+/// Generates rotation event that exposes only a subset of pre-rotated keys.
+/// Keys in `current_keys` which weren't committed in previous next keys list
+/// are new keys. Commitments of unexposed keys can be carried over in
+/// `next_keys_hashes` to keep them in reserve. Witnesses are left unchanged.
+pub fn partial_rotate(
+    state: IdentifierState,
+    current_keys: Vec<BasicPrefix>,
+    signature_threshold: &SignatureThreshold,
+    next_keys_hashes: Vec<SelfAddressingIdentifier>,
+    next_threshold: &SignatureThreshold,
+) -> Result<KeriEvent<KeyEvent>, Error> {
+    let witness_config = RotationWitnessConfig {
+        tally: state.witness_config.tally.clone(),
+        prune: vec![],
+        graft: vec![],
+    };
+    partial_rotate_with_witnesses(
+        state,
+        current_keys,
+        signature_threshold,
+        next_keys_hashes,
+        next_threshold,
+        witness_config,
+    )
+}
+
+/// Same as `partial_rotate`, but also changes witnesses: removes `prune`
+/// list, adds `graft` list and sets new witness threshold.
+pub fn partial_rotate_with_witnesses(
+    state: IdentifierState,
+    current_keys: Vec<BasicPrefix>,
+    signature_threshold: &SignatureThreshold,
+    next_keys_hashes: Vec<SelfAddressingIdentifier>,
+    next_threshold: &SignatureThreshold,
+    witness_config: RotationWitnessConfig,
+) -> Result<KeriEvent<KeyEvent>, Error> {
+    check_not_abandoned(&state)?;
+    // Check if exposed keys satisfy previous next threshold
+    let exposed: Vec<usize> = current_keys
+        .iter()
+        .filter_map(|key| state.current.next_keys_data.key_position(key))
+        .collect();
+    if !state
+        .current
+        .next_keys_data
+        .threshold
+        .is_satisfied(&exposed)
+    {
+        return Err(Error::EventGenerationError(
+            "Exposed keys don't satisfy previous next threshold".into(),
+        ));
+    }
+
+    EventMsgBuilder::new(rotation_type(&state))
+        .with_prefix(&state.prefix)
+        .with_sn(state.sn + 1)
+        .with_previous_event(&state.last_event_digest.into())
+        .with_keys(current_keys)
+        .with_threshold(signature_threshold)
+        .with_next_keys_hashes(next_keys_hashes)
+        .with_next_threshold(next_threshold)
+        .with_witness_to_add(&witness_config.graft)
+        .with_witness_to_remove(&witness_config.prune)
+        .with_witness_threshold(&witness_config.tally)
+        .build()
+        .map_err(|e| Error::EventGenerationError(e.to_string()))
+}
+
+/// Generates abandonment rotation, which exposes `current_keys` and
+/// commits to no next keys. Once it is accepted, identifier can't be
+/// rotated and no further events of it are accepted.
+pub fn abandon(
+    state: IdentifierState,
+    current_keys: Vec<BasicPrefix>,
+    signature_threshold: &SignatureThreshold,
+) -> Result<KeriEvent<KeyEvent>, Error> {
+    let witness_config = RotationWitnessConfig {
+        tally: state.witness_config.tally.clone(),
+        prune: vec![],
+        graft: vec![],
+    };
+    partial_rotate_with_witnesses(
+        state,
+        current_keys,
+        signature_threshold,
+        vec![],
+        &SignatureThreshold::Simple(0),
+        witness_config,
+    )
+}
+
+/// Abandoned identifier has no next keys, so it can't be rotated again.
+fn check_not_abandoned(state: &IdentifierState) -> Result<(), Error> {
+    if state.is_abandoned() {
+        Err(Error::EventGenerationError(
+            "Identifier is already abandoned".into(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Delegated identifiers rotate with `drt` events, which need delegator's
+/// approval.
+fn rotation_type(state: &IdentifierState) -> EventTypeTag {
+    if state.delegator.is_some() {
+        EventTypeTag::Drt
+    } else {
+        EventTypeTag::Rot
+    }
+}
+
 pub fn anchor(
     state: IdentifierState,
     payload: &[SelfAddressingIdentifier],
@@ -187,7 +309,7 @@ pub fn generate_end_role(
     role: Role,
     enabled: bool,
 ) -> ReplyEvent {
-    use said::derivation::HashFunctionCode;
+    use crate::event_message::digest::DEFAULT_DERIVATION;
     use said::version::format::SerializationFormats;
 
     let end_role = EndRole {
@@ -202,33 +324,181 @@ pub fn generate_end_role(
     };
     ReplyEvent::new_reply(
         reply_route,
-        // TODO set algo and serialization
-        HashFunctionCode::Blake3_256,
+        // TODO set serialization
+        DEFAULT_DERIVATION,
         SerializationFormats::JSON,
     )
 }
+
+/// Generates query of `about` identifier's KEL kept by `witness`. Events
+/// are returned starting from `from_sn`, at most `limit` of them.
+#[cfg(feature = "query")]
+pub fn logs_query(
+    about: &IdentifierPrefix,
+    witness: &IdentifierPrefix,
+    from_sn: Option<u64>,
+    limit: Option<u64>,
+) -> QueryEvent {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
+
+    QueryEvent::new_query(
+        QueryRoute::Logs {
+            reply_route: "".to_string(),
+            args: LogsQueryArgs {
+                s: from_sn,
+                limit,
+                i: about.clone(),
+                src: Some(witness.clone()),
+            },
+        },
+        SerializationFormats::JSON,
+        DEFAULT_DERIVATION,
+    )
+}
+
+/// Generates query of `about` identifier's key state notice.
+#[cfg(feature = "query")]
+pub fn ksn_query(about: &IdentifierPrefix, witness: &IdentifierPrefix) -> QueryEvent {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
+
+    QueryEvent::new_query(
+        QueryRoute::Ksn {
+            reply_route: "".to_string(),
+            args: LogsQueryArgs {
+                s: None,
+                limit: None,
+                i: about.clone(),
+                src: Some(witness.clone()),
+            },
+        },
+        SerializationFormats::JSON,
+        DEFAULT_DERIVATION,
+    )
+}
+
+/// Generates reply with key state notice of `state`, made by `signer`.
+#[cfg(feature = "query")]
+pub fn generate_ksn_reply(signer: &IdentifierPrefix, state: IdentifierState) -> ReplyEvent {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
+
+    use crate::query::key_state_notice::KeyStateNotice;
+
+    ReplyEvent::new_reply(
+        ReplyRoute::Ksn(
+            signer.clone(),
+            KeyStateNotice::new_ksn(state, SerializationFormats::JSON),
+        ),
+        DEFAULT_DERIVATION,
+        SerializationFormats::JSON,
+    )
+}
+
+#[cfg(feature = "oobi")]
+/// Generate reply event used to advertise location of `eid` endpoint.
+pub fn generate_loc_scheme(eid: &IdentifierPrefix, scheme: Scheme, url: Url) -> ReplyEvent {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
+
+    ReplyEvent::new_reply(
+        ReplyRoute::LocScheme(LocationScheme {
+            eid: eid.clone(),
+            scheme,
+            url,
+        }),
+        DEFAULT_DERIVATION,
+        SerializationFormats::JSON,
+    )
+}
+
 #[cfg(feature = "mailbox")]
 pub fn exchange(
     receipient: &IdentifierPrefix,
     data: &KeriEvent<KeyEvent>,
     topic: ForwardTopic,
 ) -> ExchangeMessage {
-    use said::derivation::HashFunctionCode;
+    use crate::event_message::digest::DEFAULT_DERIVATION;
     use said::version::format::SerializationFormats;
 
-    use crate::event_message::timestamped::Timestamped;
-
-    let event = Timestamped::new(Exchange::Fwd {
+    Exchange::Fwd {
         args: FwdArgs {
             recipient_id: receipient.clone(),
             topic,
         },
         to_forward: data.clone(),
-    });
+    }
+    .to_message(SerializationFormats::JSON, DEFAULT_DERIVATION)
+}
+
+/// Generates exchange message of given route, carrying `data` to
+/// `receipient`.
+#[cfg(feature = "mailbox")]
+pub fn routed_exchange(
+    receipient: &IdentifierPrefix,
+    data: &KeriEvent<KeyEvent>,
+    route: ExchangeRoute,
+) -> Result<ExchangeMessage, Error> {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
+
+    Ok(Exchange::new(route, receipient.clone(), data.clone())?
+        .to_message(SerializationFormats::JSON, DEFAULT_DERIVATION))
+}
+
+/// Generates response to `receipient`'s challenge, in which `signer`
+/// repeats challenge `words`.
+#[cfg(feature = "mailbox")]
+pub fn challenge_response(
+    receipient: &IdentifierPrefix,
+    signer: &IdentifierPrefix,
+    words: Vec<String>,
+) -> ExchangeMessage {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
+
+    Exchange::challenge_response(receipient.clone(), signer.clone(), words)
+        .to_message(SerializationFormats::JSON, DEFAULT_DERIVATION)
+}
+
+/// Generates IPEX exchange message of given route to `receipient`,
+/// responding to `prior` exchange message.
+#[cfg(feature = "mailbox")]
+pub fn ipex_exchange(
+    receipient: &IdentifierPrefix,
+    route: ExchangeRoute,
+    prior: Option<SelfAddressingIdentifier>,
+    data: IpexData,
+) -> Result<ExchangeMessage, Error> {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
 
-    KeriEvent::new(
+    Ok(Exchange::ipex(route, receipient.clone(), prior, data)?
+        .to_message(SerializationFormats::JSON, DEFAULT_DERIVATION))
+}
+
+/// Generates query of `about` identifier's mailbox kept by `witness`.
+/// Messages of each topic are returned starting from index set in `topics`,
+/// at most `limit` of them.
+#[cfg(feature = "mailbox")]
+pub fn mailbox_query(
+    asking: &IdentifierPrefix,
+    about: &IdentifierPrefix,
+    witness: &IdentifierPrefix,
+    topics: QueryTopics,
+    limit: Option<usize>,
+) -> MailboxQuery {
+    use crate::event_message::digest::DEFAULT_DERIVATION;
+    use said::version::format::SerializationFormats;
+
+    MailboxQuery::new_query(
+        MailboxRoute::Mbx {
+            reply_route: "".to_string(),
+            args: QueryArgsMbx::new(asking.clone(), about.clone(), witness.clone(), topics)
+                .with_limit(limit),
+        },
         SerializationFormats::JSON,
-        HashFunctionCode::Blake3_256.into(),
-        event,
+        DEFAULT_DERIVATION,
     )
 }
@@ -1,14 +1,13 @@
 use std::convert::TryFrom;
 
+use cesrox::group::Group;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "oobi-manager")]
 use crate::oobi_manager::OobiManager;
 #[cfg(feature = "query")]
 use crate::{
-    database::EventDatabase,
     event_message::{signature::Signature, signed_event_message::Op},
-    processor::event_storage::EventStorage,
     query::{
         key_state_notice::KeyStateNotice,
         query_event::QueryRoute,
@@ -18,22 +17,24 @@ use crate::{
     },
 };
 use crate::{
+    database::EventDatabase,
     error::Error,
     event_message::{
-        cesr_adapter::ParseError,
+        cesr_adapter::{parse_many_with_policy, ParseError},
+        protocol_version::VersionPolicy,
         signed_event_message::{Message, Notice},
+        strict_parser::StrictParser,
     },
+    processor::event_storage::EventStorage,
     prefix::IdentifierPrefix,
     processor::Processor,
 };
 #[cfg(feature = "mailbox")]
 use crate::{
-    event_message::signed_event_message::SignedEventMessage,
-    mailbox::exchange::{Exchange, ExchangeMessage, ForwardTopic, SignedExchange},
+    mailbox::{dispatch::ExchangeDispatcher, exchange::SignedExchange},
     query::mailbox::MailboxRoute,
 };
 pub use cesrox::cesr_proof::MaterialPath;
-use cesrox::parse_many;
 #[cfg(feature = "query")]
 use said::version::format::SerializationFormats;
 
@@ -46,24 +47,56 @@ pub mod possible_response;
 pub mod simple_controller;
 
 pub fn parse_event_stream(stream: &[u8]) -> Result<Vec<Message>, ParseError> {
-    let (_rest, events) = parse_many(stream).map_err(|e| ParseError::CesrError(e.to_string()))?;
+    let events = parse_many_with_policy(stream, VersionPolicy::default())?;
     events.into_iter().map(Message::try_from).collect()
 }
 
 pub fn parse_notice_stream(stream: &[u8]) -> Result<Vec<Notice>, ParseError> {
-    let (_rest, notices) = parse_many(stream).map_err(|e| ParseError::CesrError(e.to_string()))?;
+    let notices = parse_many_with_policy(stream, VersionPolicy::default())?;
     notices.into_iter().map(Notice::try_from).collect()
 }
 
+/// Parses notices of untrusted stream, leaving out key events already
+/// accepted into KEL. Messages are framed with `StrictParser` without
+/// copying the stream. Fields of each message are read through `EventRef`,
+/// so known events are dropped before they are decoded and validated, and
+/// the rest is decoded straight from the stream. Known events with
+/// attached witness receipts are kept, so that the receipts are stored.
+pub fn parse_new_notices<D: EventDatabase>(
+    stream: &[u8],
+    storage: &EventStorage<D>,
+) -> Result<Vec<Notice>, Error> {
+    let messages = StrictParser::new()
+        .parse_borrowed(stream)
+        .map_err(|e| ParseError::CesrError(e.to_string()))?;
+    let mut notices = vec![];
+    for message in messages {
+        if has_witness_receipts(&message.attachments)
+            || !storage.is_accepted(&message.event()?)?
+        {
+            notices.push(Notice::try_from(message)?);
+        }
+    }
+    Ok(notices)
+}
+
+fn has_witness_receipts(attachments: &[Group]) -> bool {
+    attachments.iter().any(|group| match group {
+        Group::IndexedWitnessSignatures(_) | Group::NontransReceiptCouples(_) => true,
+        Group::Frame(groups) => has_witness_receipts(groups),
+        _ => false,
+    })
+}
+
 #[cfg(any(feature = "query", feature = "oobi-manager"))]
 pub fn parse_op_stream(stream: &[u8]) -> Result<Vec<Op>, ParseError> {
-    let (_rest, ops) = parse_many(stream).map_err(|e| ParseError::CesrError(e.to_string()))?;
+    let ops = parse_many_with_policy(stream, VersionPolicy::default())?;
     ops.into_iter().map(Op::try_from).collect()
 }
 
 #[cfg(any(feature = "query", feature = "oobi-manager"))]
 pub fn parse_query_stream(stream: &[u8]) -> Result<Vec<SignedQueryMessage>, ParseError> {
-    let (_rest, queries) = parse_many(stream).map_err(|e| ParseError::CesrError(e.to_string()))?;
+    let queries = parse_many_with_policy(stream, VersionPolicy::default())?;
     queries
         .into_iter()
         .map(SignedQueryMessage::try_from)
@@ -72,14 +105,13 @@ pub fn parse_query_stream(stream: &[u8]) -> Result<Vec<SignedQueryMessage>, Pars
 
 #[cfg(feature = "query")]
 pub fn parse_reply_stream(stream: &[u8]) -> Result<Vec<SignedReply>, ParseError> {
-    let (_rest, replies) = parse_many(stream).map_err(|e| ParseError::CesrError(e.to_string()))?;
+    let replies = parse_many_with_policy(stream, VersionPolicy::default())?;
     replies.into_iter().map(SignedReply::try_from).collect()
 }
 
 #[cfg(feature = "mailbox")]
 pub fn parse_exchange_stream(stream: &[u8]) -> Result<Vec<SignedExchange>, ParseError> {
-    let (_rest, exchanges) =
-        parse_many(stream).map_err(|e| ParseError::CesrError(e.to_string()))?;
+    let exchanges = parse_many_with_policy(stream, VersionPolicy::default())?;
     exchanges
         .into_iter()
         .map(SignedExchange::try_from)
@@ -128,65 +160,14 @@ pub fn process_signed_oobi<D: EventDatabase + 'static>(
     Ok(())
 }
 
+/// Verifies exchange message and passes it to handlers registered in
+/// `dispatcher` for its route.
 #[cfg(feature = "mailbox")]
 pub fn process_signed_exn<D: EventDatabase>(
     exn: SignedExchange,
-    storage: &EventStorage<D>,
-) -> Result<(), Error> {
-    let exn_message = &exn.exchange_message;
-    let verification_result =
-        exn.signature
-            .iter()
-            .try_fold(true, |acc, signature| -> Result<bool, Error> {
-                Ok(acc && signature.verify(&exn_message.encode()?, storage)?)
-            });
-    if verification_result? {
-        process_exn(exn_message, exn.data_signature, storage)
-    } else {
-        Err(Error::SignatureVerificationError)
-    }
-}
-
-#[cfg(feature = "mailbox")]
-fn process_exn<D: EventDatabase>(
-    exn: &ExchangeMessage,
-    attachment: (MaterialPath, Vec<Signature>),
-    storage: &EventStorage<D>,
+    dispatcher: &ExchangeDispatcher<D>,
 ) -> Result<(), Error> {
-    let (recipient, to_forward, topic) = match &exn.data.data {
-        Exchange::Fwd { args, to_forward } => (&args.recipient_id, to_forward, &args.topic),
-    };
-    let (sigs, witness_receipts) = attachment.1.into_iter().fold(
-        (vec![], vec![]),
-        |(mut signatures, mut witness_receipts), s| {
-            match s {
-                Signature::Transferable(_sd, mut sigs) => signatures.append(&mut sigs),
-                Signature::NonTransferable(receipts) => witness_receipts.push(receipts),
-            }
-            (signatures, witness_receipts)
-        },
-    );
-
-    let signed_to_forward = SignedEventMessage {
-        event_message: to_forward.clone(),
-        signatures: sigs,
-        witness_receipts: if witness_receipts.is_empty() {
-            None
-        } else {
-            Some(witness_receipts)
-        },
-        delegator_seal: None,
-    };
-
-    match topic {
-        ForwardTopic::Multisig => {
-            storage.add_mailbox_multisig(recipient, signed_to_forward)?;
-        }
-        ForwardTopic::Delegate => {
-            storage.add_mailbox_delegate(recipient, signed_to_forward)?;
-        }
-    };
-    Ok(())
+    dispatcher.dispatch(&exn)
 }
 
 #[cfg(feature = "query")]
@@ -14,14 +14,17 @@ use crate::{
     query::{mailbox::SignedMailboxQuery, query_event::LogsQueryArgs},
 };
 use cesrox::{cesr_proof::MaterialPath, parse, primitives::CesrPrimitive};
-use said::derivation::{HashFunction, HashFunctionCode};
+use said::derivation::HashFunction;
 use said::version::format::SerializationFormats;
 
 use super::{
     event_generator, prelude::Message, process_notice, process_signed_exn, process_signed_oobi,
 };
 #[cfg(feature = "mailbox")]
-use crate::mailbox::exchange::{Exchange, ForwardTopic, FwdArgs, SignedExchange};
+use crate::mailbox::{
+    dispatch::ExchangeDispatcher,
+    exchange::{Exchange, ForwardTopic, FwdArgs, SignedExchange},
+};
 use crate::{
     error::Error,
     event::{
@@ -34,6 +37,7 @@ use crate::{
     },
     event_message::{
         cesr_adapter::EventType,
+        digest::DEFAULT_DERIVATION,
         signature::{Signature, SignerData},
         signed_event_message::{Notice, Op, SignedEventMessage, SignedNontransferableReceipt},
     },
@@ -67,6 +71,7 @@ pub struct SimpleController<K: KeyManager + 'static, D: EventDatabase + EscrowCr
     processor: BasicProcessor<D>,
     oobi_manager: OobiManager,
     pub storage: EventStorage<D>,
+    exchange_dispatcher: ExchangeDispatcher<D>,
     pub groups: Vec<IdentifierPrefix>,
     pub not_fully_witnessed_escrow: Arc<PartiallyWitnessedEscrow<D>>,
     pub ooo_escrow: Arc<MaybeOutOfOrderEscrow<D>>,
@@ -92,6 +97,9 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
             oobi_manager: OobiManager::new(event_db.clone()),
             processor,
             storage: EventStorage::new(event_db.clone()),
+            exchange_dispatcher: ExchangeDispatcher::with_mailbox(Arc::new(
+                EventStorage::new_redb(event_db.clone()),
+            ))?,
             groups: vec![],
             not_fully_witnessed_escrow: escrows.partially_witnessed,
             ooo_escrow: escrows.out_of_order,
@@ -173,7 +181,7 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
                 reply_route: String::from(""),
             },
             SerializationFormats::JSON,
-            HashFunctionCode::Blake3_256,
+            DEFAULT_DERIVATION,
         );
 
         // sign message by bob
@@ -334,7 +342,7 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
             .map(|message| match message {
                 Message::Notice(notice) => process_notice(notice.to_owned(), &self.processor),
                 Message::Op(op) => match op {
-                    Op::Exchange(exn) => process_signed_exn(exn.to_owned(), &self.storage),
+                    Op::Exchange(exn) => process_signed_exn(exn.to_owned(), &self.exchange_dispatcher),
                     Op::Reply(rpy) => process_signed_oobi(&rpy, &self.oobi_manager, &self.storage),
                     Op::Query(_) => todo!(),
                 },
@@ -446,7 +454,7 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
     ) -> Result<(SignedEventMessage, Vec<SignedExchange>), Error> {
         let signed = {
             let km = self.key_manager.lock().map_err(|_| Error::MutexPoisoned)?;
-            let next_key_hash = HashFunction::from(HashFunctionCode::Blake3_256).derive(
+            let next_key_hash = HashFunction::from(DEFAULT_DERIVATION).derive(
                 BasicPrefix::Ed25519(km.next_public_key())
                     .to_str()
                     .as_bytes(),
@@ -522,7 +530,7 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
             },
             to_forward: data.event_message.clone(),
         }
-        .to_message(SerializationFormats::JSON, HashFunctionCode::Blake3_256);
+        .to_message(SerializationFormats::JSON, DEFAULT_DERIVATION);
 
         let sigs = vec![Signature::Transferable(
             SignerData::JustSignatures,
@@ -566,11 +574,11 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
 
         let qry_msg = MailboxQuery::new_query(
             MailboxRoute::Mbx {
-                args: QueryArgsMbx {
-                    i: self.prefix.clone(),
-                    pre: self.prefix.clone(),
-                    src: IdentifierPrefix::Basic(witness.clone()),
-                    topics: QueryTopics {
+                args: QueryArgsMbx::new(
+                    self.prefix.clone(),
+                    self.prefix.clone(),
+                    IdentifierPrefix::Basic(witness.clone()),
+                    QueryTopics {
                         credential: 0,
                         receipt: 0,
                         replay: 0,
@@ -578,11 +586,11 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
                         delegate: 0,
                         reply: 0,
                     },
-                },
+                ),
                 reply_route: "".to_string(),
             },
             SerializationFormats::JSON,
-            HashFunctionCode::Blake3_256,
+            DEFAULT_DERIVATION,
         );
         let signature = self
             .key_manager
@@ -608,11 +616,11 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
             .map(|id| {
                 let qry_msg = MailboxQuery::new_query(
                     MailboxRoute::Mbx {
-                        args: QueryArgsMbx {
-                            i: id.clone(),
-                            pre: self.prefix.clone(),
-                            src: IdentifierPrefix::Basic(witness.clone()),
-                            topics: QueryTopics {
+                        args: QueryArgsMbx::new(
+                            self.prefix.clone(),
+                            id.clone(),
+                            IdentifierPrefix::Basic(witness.clone()),
+                            QueryTopics {
                                 credential: 0,
                                 receipt: 0,
                                 replay: 0,
@@ -620,11 +628,11 @@ impl<K: KeyManager> SimpleController<K, RedbDatabase> {
                                 delegate: 0,
                                 reply: 0,
                             },
-                        },
+                        ),
                         reply_route: "".to_string(),
                     },
                     SerializationFormats::JSON,
-                    HashFunctionCode::Blake3_256,
+                    DEFAULT_DERIVATION,
                 );
                 let signature = self
                     .key_manager
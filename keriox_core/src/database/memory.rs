@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     sync::{Arc, RwLock},
 };
 
@@ -10,8 +10,8 @@ use crate::query::reply_event::SignedReply;
 use crate::{
     database::{
         timestamped::{Timestamped, TimestampedSignedEventMessage},
-        EscrowCreator, EscrowDatabase, EventDatabase, LogDatabase, QueryParameters,
-        SequencedEventDatabase,
+        EscrowCreator, EscrowDatabase, EventDatabase, FirstSeenEvent, LogDatabase, QueryParameters,
+        SequencedEventDatabase, CHECKPOINT_INTERVAL,
     },
     error::Error,
     event::KeyEvent,
@@ -28,10 +28,12 @@ use crate::{
 
 /// In-memory implementation of EventDatabase for testing and validation.
 pub struct MemoryDatabase {
-    /// Events stored by identifier prefix, ordered by sn
+    /// Events stored by identifier prefix, in the order they were accepted
     events: RwLock<HashMap<IdentifierPrefix, Vec<TimestampedSignedEventMessage>>>,
     /// Key state per identifier
     states: RwLock<HashMap<IdentifierPrefix, IdentifierState>>,
+    /// Key state checkpoints per identifier, keyed by sn
+    checkpoints: RwLock<HashMap<IdentifierPrefix, BTreeMap<u64, IdentifierState>>>,
     /// Transferable receipts by (id, sn)
     receipts_t: RwLock<HashMap<(IdentifierPrefix, u64), Vec<Transferable>>>,
     /// Non-transferable receipts by (id, sn)
@@ -49,6 +51,7 @@ impl MemoryDatabase {
         Self {
             events: RwLock::new(HashMap::new()),
             states: RwLock::new(HashMap::new()),
+            checkpoints: RwLock::new(HashMap::new()),
             receipts_t: RwLock::new(HashMap::new()),
             receipts_nt: RwLock::new(HashMap::new()),
             log_db: Arc::new(MemoryLogDatabase::new()),
@@ -81,6 +84,14 @@ impl EventDatabase for MemoryDatabase {
             .cloned()
             .unwrap_or_default();
         let new_state = current_state.apply(&event.event_message)?;
+        if new_state.sn != 0 && new_state.sn % CHECKPOINT_INTERVAL == 0 {
+            self.checkpoints
+                .write()
+                .unwrap()
+                .entry(id.clone())
+                .or_default()
+                .insert(new_state.sn, new_state.clone());
+        }
         self.states.write().unwrap().insert(id.clone(), new_state);
 
         // Log the event
@@ -118,19 +129,55 @@ impl EventDatabase for MemoryDatabase {
         &self,
         receipt: SignedNontransferableReceipt,
         id: &IdentifierPrefix,
-    ) -> Result<(), Self::Error> {
+    ) -> Result<bool, Self::Error> {
         let sn = receipt.body.sn;
-        self.receipts_nt
+        let mut receipts = self.receipts_nt.write().unwrap();
+        let stored = receipts.entry((id.clone(), sn)).or_default();
+        let new_signatures: Vec<_> = receipt
+            .signatures
+            .into_iter()
+            .flat_map(Nontransferable::split)
+            .filter(|sig| {
+                !stored.iter().any(|rct| {
+                    rct.body.receipted_event_digest == receipt.body.receipted_event_digest
+                        && rct.signatures.contains(sig)
+                })
+            })
+            .collect();
+        if new_signatures.is_empty() {
+            return Ok(false);
+        }
+        stored.push(SignedNontransferableReceipt {
+            body: receipt.body,
+            signatures: new_signatures,
+        });
+        Ok(true)
+    }
+
+    fn get_key_state(&self, id: &IdentifierPrefix) -> Option<IdentifierState> {
+        self.states.read().unwrap().get(id).cloned()
+    }
+
+    fn save_checkpoint(&self, id: &IdentifierPrefix) -> Result<(), Self::Error> {
+        let state = self
+            .get_key_state(id)
+            .ok_or_else(|| Error::SemanticError("Unknown identifier".into()))?;
+        self.checkpoints
             .write()
             .unwrap()
-            .entry((id.clone(), sn))
+            .entry(id.clone())
             .or_default()
-            .push(receipt);
+            .insert(state.sn, state);
         Ok(())
     }
 
-    fn get_key_state(&self, id: &IdentifierPrefix) -> Option<IdentifierState> {
-        self.states.read().unwrap().get(id).cloned()
+    fn get_checkpoint(&self, id: &IdentifierPrefix, sn: u64) -> Option<IdentifierState> {
+        self.checkpoints
+            .read()
+            .unwrap()
+            .get(id)
+            .and_then(|checkpoints| checkpoints.range(..=sn).next_back())
+            .map(|(_, state)| state.clone())
     }
 
     fn get_kel_finalized_events(
@@ -194,6 +241,29 @@ impl EventDatabase for MemoryDatabase {
         }
     }
 
+    fn get_first_seen_events(
+        &self,
+        id: &IdentifierPrefix,
+        start: u64,
+        limit: u64,
+    ) -> Option<impl DoubleEndedIterator<Item = FirstSeenEvent>> {
+        // Events are pushed in acceptance order, so position in the vector is
+        // the first-seen ordinal.
+        let events = self.events.read().unwrap();
+        events.get(id).map(|evts| {
+            evts.iter()
+                .enumerate()
+                .skip(start as usize)
+                .take(limit as usize)
+                .map(|(ordinal, event)| FirstSeenEvent {
+                    ordinal: ordinal as u64,
+                    event: event.clone(),
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+        })
+    }
+
     fn accept_to_kel(&self, _event: &KeriEvent<KeyEvent>) -> Result<(), Self::Error> {
         // In redb, this saves the event to KEL tables. For memory, events
         // are already in the events map from add_kel_finalized_event.
@@ -259,12 +329,18 @@ impl MemoryLogDatabase {
 
     fn log_receipt_internal(&self, receipt: &SignedNontransferableReceipt) {
         let digest = receipt.body.receipted_event_digest.clone();
-        self.nontrans_couplets
-            .write()
-            .unwrap()
-            .entry(digest)
-            .or_default()
-            .extend(receipt.signatures.clone());
+        let mut couplets = self.nontrans_couplets.write().unwrap();
+        let stored = couplets.entry(digest).or_default();
+        for sig in receipt
+            .signatures
+            .iter()
+            .cloned()
+            .flat_map(Nontransferable::split)
+        {
+            if !stored.contains(&sig) {
+                stored.push(sig);
+            }
+        }
     }
 }
 
@@ -376,7 +452,10 @@ impl LogDatabase<'static> for MemoryLogDatabase {
         said: &SelfAddressingIdentifier,
         nontrans: impl IntoIterator<Item = Nontransferable>,
     ) -> Result<(), Self::Error> {
-        let to_remove: Vec<_> = nontrans.into_iter().collect();
+        let to_remove: Vec<_> = nontrans
+            .into_iter()
+            .flat_map(Nontransferable::split)
+            .collect();
         if let Some(existing) = self.nontrans_couplets.write().unwrap().get_mut(said) {
             existing.retain(|n| !to_remove.contains(n));
         }
@@ -572,6 +651,12 @@ impl EscrowDatabase for MemoryEscrowDb {
         }
     }
 
+    fn remove_key_value(&self, id: &IdentifierPrefix, sn: u64, event: &KeriEvent<KeyEvent>) {
+        if let Ok(digest) = event.digest() {
+            let _ = self.sequenced.remove(id, sn, &digest);
+        }
+    }
+
     fn contains(
         &self,
         id: &IdentifierPrefix,
@@ -607,11 +692,20 @@ mod tests {
 
     use super::MemoryDatabase;
     use crate::{
+        actor::event_generator::anchor_with_seal,
+        database::{EventDatabase, CHECKPOINT_INTERVAL},
         error::Error,
-        event_message::signed_event_message::{Message, Notice},
-        processor::{
-            basic_processor::BasicProcessor, event_storage::EventStorage, Processor,
+        event::KeyEvent,
+        event_message::{
+            event_msg_builder::EventMsgBuilder,
+            msg::KeriEvent,
+            signed_event_message::{Message, Notice},
+            EventTypeTag,
         },
+        prefix::{BasicPrefix, IndexedSignature, SelfSigningPrefix},
+        processor::{basic_processor::BasicProcessor, event_storage::EventStorage, Processor},
+        signer::Signer,
+        state::IdentifierState,
     };
 
     #[test]
@@ -647,4 +741,79 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_memory_db_checkpoints() -> Result<(), Error> {
+        let db = Arc::new(MemoryDatabase::new());
+        let processor = BasicProcessor::new(db.clone(), None);
+        let storage = EventStorage::new(db.clone());
+
+        let signer = Signer::new();
+        let process = |event: KeriEvent<KeyEvent>| -> Result<(), Error> {
+            let signature = SelfSigningPrefix::Ed25519Sha512(signer.sign(event.encode()?)?);
+            let signed = event.sign(
+                vec![IndexedSignature::new_both_same(signature, 0)],
+                None,
+                None,
+            );
+            processor.process_notice(&Notice::Event(signed))
+        };
+        let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+            .with_keys(vec![BasicPrefix::Ed25519(signer.public_key())])
+            .with_next_keys(vec![BasicPrefix::Ed25519(Signer::new().public_key())])
+            .build()?;
+        let id = icp.data.get_prefix();
+        process(icp)?;
+        for _ in 0..=CHECKPOINT_INTERVAL {
+            process(anchor_with_seal(storage.get_state(&id).unwrap(), &[])?)?;
+        }
+        let state = storage.get_state(&id).unwrap();
+        assert_eq!(state.sn, CHECKPOINT_INTERVAL + 1);
+
+        // Checkpoint is taken automatically.
+        let checkpoint = db.get_checkpoint(&id, u64::MAX).unwrap();
+        assert_eq!(checkpoint.sn, CHECKPOINT_INTERVAL);
+        assert!(db.get_checkpoint(&id, CHECKPOINT_INTERVAL - 1).is_none());
+
+        // State is replayed from the checkpoint. Marked checkpoint shows it
+        // wasn't computed from the whole KEL.
+        let marked = IdentifierState {
+            delegator: Some(id.clone()),
+            ..checkpoint.clone()
+        };
+        let set_checkpoint = |checkpoint: IdentifierState| {
+            db.checkpoints
+                .write()
+                .unwrap()
+                .get_mut(&id)
+                .unwrap()
+                .insert(checkpoint.sn, checkpoint);
+        };
+        set_checkpoint(marked);
+        let replayed = storage
+            .compute_state_at_sn(&id, CHECKPOINT_INTERVAL + 1)?
+            .unwrap();
+        assert_eq!(replayed.delegator, Some(id.clone()));
+        assert_eq!(replayed.sn, state.sn);
+        assert_eq!(replayed.last_event_digest, state.last_event_digest);
+        // Earlier states don't use it.
+        let earlier = storage
+            .compute_state_at_sn(&id, CHECKPOINT_INTERVAL - 1)?
+            .unwrap();
+        assert_eq!(earlier.delegator, None);
+
+        // Checkpoint that doesn't match KEL is ignored.
+        let stale = IdentifierState {
+            delegator: Some(id.clone()),
+            last_event_digest: state.last_event_digest.clone(),
+            ..checkpoint
+        };
+        set_checkpoint(stale);
+        assert_eq!(
+            storage.compute_state_at_sn(&id, CHECKPOINT_INTERVAL + 1)?,
+            Some(state)
+        );
+
+        Ok(())
+    }
 }
@@ -25,6 +25,18 @@ pub mod redb;
 pub(crate) mod rkyv_adapter;
 pub mod timestamped;
 
+/// Number of events after which key state checkpoint is persisted.
+pub const CHECKPOINT_INTERVAL: u64 = 100;
+
+/// Accepted event together with its first-seen ordinal. Ordinals are assigned
+/// per identifier, monotonically, in the order in which events were accepted
+/// into the KEL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirstSeenEvent {
+    pub ordinal: u64,
+    pub event: TimestampedSignedEventMessage,
+}
+
 pub enum QueryParameters<'a> {
     BySn {
         id: IdentifierPrefix,
@@ -58,14 +70,23 @@ pub trait EventDatabase {
         id: &IdentifierPrefix,
     ) -> Result<(), Self::Error>;
 
+    /// Saves nontransferable receipt. Signatures that are already stored are
+    /// skipped. Returns true if any new signature was saved.
     fn add_receipt_nt(
         &self,
         receipt: SignedNontransferableReceipt,
         id: &IdentifierPrefix,
-    ) -> Result<(), Self::Error>;
+    ) -> Result<bool, Self::Error>;
 
     fn get_key_state(&self, id: &IdentifierPrefix) -> Option<IdentifierState>;
 
+    /// Persists current key state of identifier as a checkpoint.
+    fn save_checkpoint(&self, id: &IdentifierPrefix) -> Result<(), Self::Error>;
+
+    /// Returns the latest key state checkpoint of identifier taken at or
+    /// below `sn`.
+    fn get_checkpoint(&self, id: &IdentifierPrefix, sn: u64) -> Option<IdentifierState>;
+
     fn get_kel_finalized_events(
         &self,
         params: QueryParameters,
@@ -81,6 +102,15 @@ pub trait EventDatabase {
         params: QueryParameters,
     ) -> Option<impl DoubleEndedIterator<Item = SignedNontransferableReceipt>>;
 
+    /// Returns at most `limit` accepted events of identifier, ordered by
+    /// first-seen ordinal and starting from ordinal `start`.
+    fn get_first_seen_events(
+        &self,
+        id: &IdentifierPrefix,
+        start: u64,
+        limit: u64,
+    ) -> Option<impl DoubleEndedIterator<Item = FirstSeenEvent>>;
+
     fn accept_to_kel(&self, event: &KeriEvent<KeyEvent>) -> Result<(), Self::Error>;
 
     #[cfg(feature = "query")]
@@ -241,6 +271,9 @@ pub trait EscrowDatabase: Send + Sync {
 
     fn remove(&self, event: &KeriEvent<KeyEvent>);
 
+    /// Removes event stored with `insert_key_value` under given key.
+    fn remove_key_value(&self, id: &IdentifierPrefix, sn: u64, event: &KeriEvent<KeyEvent>);
+
     fn contains(
         &self,
         id: &IdentifierPrefix,
@@ -139,6 +139,11 @@ impl crate::database::EscrowDatabase for SnKeyEscrow {
         self.escrow.remove(&id, sn, &said).unwrap();
     }
 
+    fn remove_key_value(&self, id: &IdentifierPrefix, sn: u64, event: &KeriEvent<KeyEvent>) {
+        let said = event.digest().unwrap();
+        self.escrow.remove(id, sn, &said).unwrap();
+    }
+
     fn contains(
         &self,
         id: &IdentifierPrefix,
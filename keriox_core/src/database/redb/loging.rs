@@ -172,7 +172,7 @@ impl<'db> LogDatabaseTrait<'db> for LogDatabase {
         execute_in_transaction(self.db.clone(), txn_mode, |write_txn| {
             let mut table = write_txn.open_multimap_table(NONTRANS_RCTS)?;
 
-            for value in nontrans {
+            for value in nontrans.into_iter().flat_map(Nontransferable::split) {
                 let value = rkyv::to_bytes::<rancor::Error>(&value)?;
                 table.remove(serialized_said.as_slice(), value.as_slice())?;
             }
@@ -254,13 +254,27 @@ impl LogDatabase {
         })
     }
 
+    /// Saves receipt signatures one by one, so the same (witness, signature)
+    /// pair is stored only once. Returns true if any new signature was saved.
     pub(super) fn insert_nontrans_receipt(
         &self,
         txn_mode: &WriteTxnMode,
         said: &SelfAddressingIdentifier,
         nontrans: &[Nontransferable],
-    ) -> Result<(), RedbError> {
-        self.insert_with_digest_key(txn_mode, NONTRANS_RCTS, said, nontrans)
+    ) -> Result<bool, RedbError> {
+        let serialized_said = rkyv_adapter::serialize_said(said)?;
+        let mut stored_new = false;
+        execute_in_transaction(self.db.clone(), txn_mode, |write_txn| {
+            let mut table = write_txn.open_multimap_table(NONTRANS_RCTS)?;
+
+            for value in nontrans.iter().cloned().flat_map(Nontransferable::split) {
+                let sig = rkyv::to_bytes::<rancor::Error>(&value)?;
+                let already_present = table.insert(serialized_said.as_slice(), sig.as_slice())?;
+                stored_new |= !already_present;
+            }
+            Ok(())
+        })?;
+        Ok(stored_new)
     }
 
     pub(super) fn insert_source_seal(
@@ -457,12 +471,20 @@ fn test_retrieve_receipts() {
         let ev = evs.first().unwrap();
         match ev {
             Message::Notice(Notice::NontransferableRct(rct)) => {
-                db.add_receipt_nt(rct.clone(), &first_id).unwrap();
+                assert!(db.add_receipt_nt(rct.clone(), &first_id).unwrap());
             }
             _ => unreachable!(),
         }
     }
 
+    // Re-submitting the same receipt doesn't store anything new.
+    match parse_event_stream(receipt0_0).unwrap().first().unwrap() {
+        Message::Notice(Notice::NontransferableRct(rct)) => {
+            assert!(!db.add_receipt_nt(rct.clone(), &first_id).unwrap());
+        }
+        _ => unreachable!(),
+    }
+
     let recipted_event_digest: SelfAddressingIdentifier =
         "EJufgwH347N2kobmes1IQw_1pfMipEFFy0RwinZTtah9"
             .parse()
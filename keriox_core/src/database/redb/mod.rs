@@ -14,10 +14,27 @@ const KELS: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("kels");
 /// as events are processed.
 const KEY_STATES: TableDefinition<&str, &[u8]> = TableDefinition::new("key_states");
 
+/// First seen events storage. (identifier, first seen ordinal) -> event digest
+/// The `FIRST_SEEN` table records the order in which events of each identifier
+/// were accepted into the KEL. Ordinals are monotonic per identifier.
+const FIRST_SEEN: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("first_seen");
+
+/// First seen times storage. (identifier, first seen ordinal) -> timestamp in microseconds
+/// The `FIRST_SEEN_TIMES` table records when event of given ordinal was accepted.
+const FIRST_SEEN_TIMES: TableDefinition<(&str, u64), i64> =
+    TableDefinition::new("first_seen_times");
+
+/// Key state checkpoints storage. (identifier, sn) -> key state
+/// The `CHECKPOINTS` table keeps snapshots of identifier state taken every
+/// `CHECKPOINT_INTERVAL` events or on demand, so that state can be rebuilt
+/// without replaying the whole KEL.
+const CHECKPOINTS: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("checkpoints");
+
 use std::{path::Path, sync::Arc, u64};
 
 #[cfg(feature = "query")]
 use crate::query::reply_event::SignedReply;
+use chrono::{Local, TimeZone};
 #[cfg(feature = "query")]
 use ksn_log::AcceptedKsn;
 use loging::LogDatabase;
@@ -38,7 +55,10 @@ use crate::{
 };
 use cesrox::primitives::CesrPrimitive;
 
-use super::{timestamped, EventDatabase, LogDatabase as LogDatabaseTrait, QueryParameters};
+use super::{
+    timestamped, EventDatabase, FirstSeenEvent, LogDatabase as LogDatabaseTrait, QueryParameters,
+    CHECKPOINT_INTERVAL,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum RedbError {
@@ -66,6 +86,8 @@ pub enum RedbError {
     Rkyv(#[from] rkyv::rancor::Error),
     #[error("Already saved: {0}")]
     AlreadySaved(SelfAddressingIdentifier),
+    #[error("No key state for identifier {0}")]
+    MissingKeyState(IdentifierPrefix),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -99,6 +121,9 @@ impl RedbDatabase {
         {
             write_txn.open_table(KELS)?;
             write_txn.open_table(KEY_STATES)?;
+            write_txn.open_table(FIRST_SEEN)?;
+            write_txn.open_table(FIRST_SEEN_TIMES)?;
+            write_txn.open_table(CHECKPOINTS)?;
         }
         write_txn.commit()?;
         Ok(Self {
@@ -149,7 +174,7 @@ impl EventDatabase for RedbDatabase {
         &self,
         receipt: SignedNontransferableReceipt,
         _id: &IdentifierPrefix,
-    ) -> Result<(), RedbError> {
+    ) -> Result<bool, RedbError> {
         let receipted_event_digest = receipt.body.receipted_event_digest;
         let receipts = receipt.signatures;
         self.log_db.insert_nontrans_receipt(
@@ -167,10 +192,36 @@ impl EventDatabase for RedbDatabase {
             let bytes = key_state.value();
             Some(rkyv_adapter::deserialize_identifier_state(bytes).unwrap())
         } else {
-            None
+            // Lookup doesn't write. Missing state is persisted again on next
+            // accepted event or by `restore_key_state`.
+            self.rebuild_key_state(id)
         }
     }
 
+    fn save_checkpoint(&self, id: &IdentifierPrefix) -> Result<(), RedbError> {
+        let key_state = self
+            .get_key_state(id)
+            .ok_or_else(|| RedbError::MissingKeyState(id.clone()))?;
+        let value = rkyv::to_bytes::<rkyv::rancor::Error>(&key_state)?;
+        execute_in_transaction(self.db.clone(), &WriteTxnMode::CreateNew, |write_txn| {
+            let mut table = write_txn.open_table(CHECKPOINTS)?;
+            table.insert((id.to_str().as_str(), key_state.sn), value.as_ref())?;
+            Ok(())
+        })
+    }
+
+    fn get_checkpoint(&self, id: &IdentifierPrefix, sn: u64) -> Option<IdentifierState> {
+        let read_txn = self.db.begin_read().ok()?;
+        let table = read_txn.open_table(CHECKPOINTS).ok()?;
+        let key = id.to_str();
+        let (_, value) = table
+            .range((key.as_str(), 0)..=(key.as_str(), sn))
+            .ok()?
+            .next_back()?
+            .ok()?;
+        rkyv_adapter::deserialize_identifier_state(value.value()).ok()
+    }
+
     fn get_kel_finalized_events(
         &self,
         params: super::QueryParameters,
@@ -232,10 +283,26 @@ impl EventDatabase for RedbDatabase {
         }
     }
 
+    fn get_first_seen_events(
+        &self,
+        id: &IdentifierPrefix,
+        start: u64,
+        limit: u64,
+    ) -> Option<impl DoubleEndedIterator<Item = FirstSeenEvent>> {
+        self.get_first_seen(id, start, limit)
+            .ok()
+            .map(|events| events.into_iter())
+    }
+
     fn accept_to_kel(&self, event: &KeriEvent<KeyEvent>) -> Result<(), RedbError> {
-        let txn_mode = WriteTxnMode::CreateNew;
-        self.save_to_kel(&txn_mode, event)?;
-        self.update_key_state(&txn_mode, event)?;
+        let write_txn = self.db.begin_write()?;
+        let txn_mode = WriteTxnMode::UseExisting(&write_txn);
+        // Event can be already in KEL, e.g. accepted by witness before it
+        // was fully witnessed.
+        if self.save_to_kel(&txn_mode, event)? {
+            self.update_key_state(&txn_mode, event)?;
+        }
+        write_txn.commit()?;
 
         Ok(())
     }
@@ -253,21 +320,85 @@ impl EventDatabase for RedbDatabase {
 
 impl RedbDatabase {
     /// Saves KEL event of given identifier. Key is identifier and sn of event, and value is event digest.
+    /// Event digest is also assigned next first seen ordinal of the identifier.
+    /// Returns `false` if the event was already saved, in which case it keeps
+    /// its first seen ordinal.
     fn save_to_kel(
         &self,
         txn_mode: &WriteTxnMode,
         event: &KeriEvent<KeyEvent>,
-    ) -> Result<(), RedbError> {
+    ) -> Result<bool, RedbError> {
         let digest = event.digest().map_err(|_e| RedbError::MissingDigest)?;
+        let mut saved = false;
 
         execute_in_transaction(self.db.clone(), txn_mode, |write_txn| {
             let mut table = write_txn.open_table(KELS)?;
             let id = event.data.prefix.to_str();
             let sn = event.data.sn;
             let serialized_said = rkyv_adapter::serialize_said(&digest)?;
+            let already_saved = table
+                .get((id.as_str(), sn))?
+                .is_some_and(|existing| existing.value() == serialized_said.as_slice());
+            if already_saved {
+                return Ok(());
+            }
             table.insert((id.as_str(), sn), &serialized_said.as_slice())?;
+
+            let mut first_seen_table = write_txn.open_table(FIRST_SEEN)?;
+            let next_ordinal = match first_seen_table
+                .range((id.as_str(), 0)..=(id.as_str(), u64::MAX))?
+                .next_back()
+            {
+                Some(last) => last?.0.value().1 + 1,
+                None => 0,
+            };
+            first_seen_table.insert((id.as_str(), next_ordinal), &serialized_said.as_slice())?;
+
+            let mut times_table = write_txn.open_table(FIRST_SEEN_TIMES)?;
+            times_table.insert((id.as_str(), next_ordinal), Local::now().timestamp_micros())?;
+            saved = true;
             Ok(())
-        })
+        })?;
+        Ok(saved)
+    }
+
+    fn get_first_seen(
+        &self,
+        id: &IdentifierPrefix,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<FirstSeenEvent>, RedbError> {
+        let id = id.to_str();
+        let read_txn = self.db.begin_read()?;
+        let digests = {
+            let table = read_txn.open_table(FIRST_SEEN)?;
+            table.range((id.as_str(), start)..(id.as_str(), start.saturating_add(limit)))?
+        };
+        let times = read_txn.open_table(FIRST_SEEN_TIMES)?;
+
+        digests
+            .filter_map(|entry| match entry {
+                Ok((key, value)) => self
+                    .log_db
+                    .get_signed_event_by_serialized_key(value.value())
+                    .and_then(|event| {
+                        let ordinal = key.value().1;
+                        // Events accepted before times were recorded keep
+                        // timestamp of reading.
+                        let accepted_at = times
+                            .get((id.as_str(), ordinal))?
+                            .and_then(|time| Local.timestamp_micros(time.value()).single());
+                        Ok(event.map(|mut event| {
+                            if let Some(accepted_at) = accepted_at {
+                                event.timestamp = accepted_at;
+                            }
+                            FirstSeenEvent { ordinal, event }
+                        }))
+                    })
+                    .transpose(),
+                Err(e) => Some(Err(e.into())),
+            })
+            .collect()
     }
 
     fn update_key_state(
@@ -283,7 +414,8 @@ impl RedbDatabase {
                 let bytes = key_state.value();
                 rkyv_adapter::deserialize_identifier_state(bytes).unwrap()
             } else {
-                IdentifierState::default()
+                self.rebuild_key_state(&event.data.prefix)
+                    .unwrap_or_default()
             };
 
             let key_state = key_state
@@ -292,10 +424,71 @@ impl RedbDatabase {
             let value = rkyv::to_bytes::<rkyv::rancor::Error>(&key_state)?;
             table.insert(key.as_str(), value.as_ref())?;
 
+            if key_state.sn != 0 && key_state.sn % CHECKPOINT_INTERVAL == 0 {
+                let mut checkpoints = write_txn.open_table(CHECKPOINTS)?;
+                checkpoints.insert((key.as_str(), key_state.sn), value.as_ref())?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Rebuilds identifier state from the latest checkpoint and persists it.
+    /// Returns `None` if identifier's KEL is empty.
+    pub fn restore_key_state(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<IdentifierState>, RedbError> {
+        match self.rebuild_key_state(id) {
+            Some(key_state) => {
+                self.save_key_state(&id.to_str(), &key_state)?;
+                Ok(Some(key_state))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn save_key_state(&self, key: &str, key_state: &IdentifierState) -> Result<(), RedbError> {
+        let value = rkyv::to_bytes::<rkyv::rancor::Error>(key_state)?;
+        execute_in_transaction(self.db.clone(), &WriteTxnMode::CreateNew, |write_txn| {
+            let mut table = write_txn.open_table(KEY_STATES)?;
+            table.insert(key, value.as_ref())?;
             Ok(())
         })
     }
 
+    /// Rebuilds identifier state from the latest checkpoint, applying only
+    /// events accepted after it. Checkpoint that doesn't match the KEL is
+    /// ignored and the whole KEL is replayed.
+    fn rebuild_key_state(&self, id: &IdentifierPrefix) -> Option<IdentifierState> {
+        let (state, from) = match self.get_checkpoint(id, u64::MAX) {
+            Some(checkpoint) if self.is_checkpoint_valid(id, &checkpoint) => {
+                let from = checkpoint.sn + 1;
+                (checkpoint, from)
+            }
+            _ => (IdentifierState::default(), 0),
+        };
+        let delta = self.get_kel(id, from, u64::MAX - from).ok()?;
+        if from == 0 && delta.is_empty() {
+            return None;
+        }
+        delta
+            .into_iter()
+            .try_fold(state, |state, event| {
+                state.apply(&event.signed_event_message.event_message)
+            })
+            .ok()
+    }
+
+    fn is_checkpoint_valid(&self, id: &IdentifierPrefix, checkpoint: &IdentifierState) -> bool {
+        let checkpoint_digest: SelfAddressingIdentifier =
+            checkpoint.last_event_digest.clone().into();
+        matches!(
+            self.get_event_digest(id, checkpoint.sn),
+            Ok(Some(digest)) if digest == checkpoint_digest
+        )
+    }
+
     fn get_event_digest(
         &self,
         identifier: &IdentifierPrefix,
@@ -564,6 +757,48 @@ fn test_retrieve_kel() -> Result<(), RedbError> {
 
     assert_eq!(part_of_kel_events.next(), None);
 
+    // Retrieve KEL in first seen order
+    let first_seen = db.get_first_seen(&first_id, 0, u64::MAX)?;
+    assert_eq!(
+        first_seen.iter().map(|fs| fs.ordinal).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    let page = db.get_first_seen(&first_id, 1, 1)?;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].ordinal, 1);
+    assert_eq!(
+        page[0].event.signed_event_message.event_message.event_type,
+        EventTypeTag::Rot
+    );
+    let second_id: IdentifierPrefix = "EFb-WY7Ie1WPEgsioZz1CyzwnuCg-C9k2QCNpcUfM5Jf"
+        .parse()
+        .unwrap();
+    let second_first_seen = db.get_first_seen(&second_id, 0, u64::MAX)?;
+    assert_eq!(second_first_seen.len(), 1);
+    assert_eq!(second_first_seen[0].ordinal, 0);
+
+    // Event accepted again, e.g. promoted from partially witnessed escrow
+    // after witness accepted it, keeps its first seen ordinal.
+    let ixn = match parse_event_stream(ixn_raw).unwrap().remove(0) {
+        Message::Notice(Notice::Event(event)) => event,
+        _ => unreachable!(),
+    };
+    db.accept_to_kel(&ixn.event_message)?;
+    assert!(db.add_kel_finalized_event(ixn.clone(), &first_id).is_err());
+    let first_seen = db.get_first_seen(&first_id, 0, u64::MAX)?;
+    assert_eq!(
+        first_seen.iter().map(|fs| fs.ordinal).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    assert_eq!(db.get_key_state(&first_id).unwrap().sn, 2);
+
+    // Checkpoints
+    assert!(db.get_checkpoint(&first_id, u64::MAX).is_none());
+    db.save_checkpoint(&first_id)?;
+    let checkpoint = db.get_checkpoint(&first_id, u64::MAX).unwrap();
+    assert_eq!(checkpoint.sn, 2);
+    assert!(db.get_checkpoint(&first_id, 1).is_none());
+
     let key_state = db.get_key_state(&first_id).unwrap();
     assert_eq!(key_state.sn, 2);
     assert_eq!(
@@ -573,5 +808,29 @@ fn test_retrieve_kel() -> Result<(), RedbError> {
             .unwrap()
             .into()
     );
+
+    // Missing key state is rebuilt from the checkpoint on lookup, but only
+    // saved again on explicit restore.
+    let key = first_id.to_str();
+    execute_in_transaction(db.db.clone(), &WriteTxnMode::CreateNew, |write_txn| {
+        write_txn.open_table(KEY_STATES)?.remove(key.as_str())?;
+        Ok(())
+    })?;
+    let rebuilt = db.get_key_state(&first_id).unwrap();
+    assert_eq!(rebuilt, key_state);
+    let read_txn = db.db.begin_read().unwrap();
+    assert!(read_txn
+        .open_table(KEY_STATES)?
+        .get(key.as_str())?
+        .is_none());
+    drop(read_txn);
+
+    assert_eq!(db.restore_key_state(&first_id)?, Some(key_state.clone()));
+    let read_txn = db.db.begin_read().unwrap();
+    let saved = read_txn.open_table(KEY_STATES)?.get(key.as_str())?.unwrap();
+    assert_eq!(
+        rkyv_adapter::deserialize_identifier_state(saved.value()).unwrap(),
+        key_state
+    );
     Ok(())
 }
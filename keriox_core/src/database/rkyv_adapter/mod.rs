@@ -45,7 +45,12 @@ pub fn deserialize_indexed_signatures(
 }
 
 pub fn deserialize_source_seal(bytes: &[u8]) -> Result<SourceSeal, rkyv::rancor::Error> {
-    let archived = rkyv::access::<ArchivedSourceSeal, rkyv::rancor::Error>(&bytes).unwrap();
+    // Archived seal holds `u64` sn, so bytes read from database need to be
+    // aligned first.
+    let mut aligned_bytes =
+        AlignedVec::<{ std::mem::align_of::<ArchivedSourceSeal>() }>::with_capacity(bytes.len());
+    aligned_bytes.extend_from_slice(bytes);
+    let archived = rkyv::access::<ArchivedSourceSeal, rkyv::rancor::Error>(&aligned_bytes)?;
     rkyv::deserialize::<SourceSeal, rkyv::rancor::Error>(archived)
 }
 
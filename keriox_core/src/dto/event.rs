@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    keys_to_str, IndexedSignatureDto, KeyConfigDto, SealDto, ThresholdDto, WitnessSignaturesDto,
+};
+use crate::{
+    error::Error,
+    event::{
+        event_data::{inception::InceptionEvent, rotation::RotationEvent, EventData},
+        sections::seal::{Seal, SourceSeal},
+        KeyEvent,
+    },
+    event_message::{msg::KeriEvent, signed_event_message::SignedEventMessage},
+};
+
+/// Key event. Fields specific to event type are flattened into it, next to
+/// its `type`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyEventDto {
+    pub identifier: String,
+    pub sn: u64,
+    pub digest: String,
+    #[serde(flatten)]
+    pub data: KeyEventDataDto,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeyEventDataDto {
+    Inception(InceptionDto),
+    Rotation(RotationDto),
+    Interaction(InteractionDto),
+    DelegatedInception(DelegatedInceptionDto),
+    DelegatedRotation(RotationDto),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InceptionDto {
+    #[serde(flatten)]
+    pub key_config: KeyConfigDto,
+    pub witnesses: Vec<String>,
+    pub witness_threshold: ThresholdDto,
+    pub configuration: Vec<String>,
+    pub seals: Vec<SealDto>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DelegatedInceptionDto {
+    #[serde(flatten)]
+    pub inception: InceptionDto,
+    pub delegator: String,
+}
+
+/// Rotation event. Witness threshold applies to witness list after
+/// removing and adding witnesses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RotationDto {
+    pub previous_digest: String,
+    #[serde(flatten)]
+    pub key_config: KeyConfigDto,
+    pub witnesses_removed: Vec<String>,
+    pub witnesses_added: Vec<String>,
+    pub witness_threshold: ThresholdDto,
+    pub seals: Vec<SealDto>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InteractionDto {
+    pub previous_digest: String,
+    pub seals: Vec<SealDto>,
+}
+
+impl TryFrom<&KeriEvent<KeyEvent>> for KeyEventDto {
+    type Error = Error;
+
+    fn try_from(event: &KeriEvent<KeyEvent>) -> Result<Self, Error> {
+        let data = match &event.data.event_data {
+            EventData::Icp(icp) => KeyEventDataDto::Inception(icp.into()),
+            EventData::Rot(rot) => KeyEventDataDto::Rotation(rot.into()),
+            EventData::Ixn(ixn) => KeyEventDataDto::Interaction(InteractionDto {
+                previous_digest: ixn.previous_event_hash().to_string(),
+                seals: seals(&ixn.data),
+            }),
+            EventData::Dip(dip) => KeyEventDataDto::DelegatedInception(DelegatedInceptionDto {
+                inception: (&dip.inception_data).into(),
+                delegator: dip.delegator.to_string(),
+            }),
+            EventData::Drt(drt) => KeyEventDataDto::DelegatedRotation(drt.into()),
+        };
+        Ok(Self {
+            identifier: event.data.prefix.to_string(),
+            sn: event.data.sn,
+            digest: event.digest()?.to_string(),
+            data,
+        })
+    }
+}
+
+impl From<&InceptionEvent> for InceptionDto {
+    fn from(icp: &InceptionEvent) -> Self {
+        Self {
+            key_config: (&icp.key_config).into(),
+            witnesses: keys_to_str(&icp.witness_config.initial_witnesses),
+            witness_threshold: (&icp.witness_config.tally).into(),
+            configuration: icp.inception_configuration.clone(),
+            seals: seals(&icp.data),
+        }
+    }
+}
+
+impl From<&RotationEvent> for RotationDto {
+    fn from(rot: &RotationEvent) -> Self {
+        Self {
+            previous_digest: rot.previous_event_hash().to_string(),
+            key_config: (&rot.key_config).into(),
+            witnesses_removed: keys_to_str(&rot.witness_config.prune),
+            witnesses_added: keys_to_str(&rot.witness_config.graft),
+            witness_threshold: (&rot.witness_config.tally).into(),
+            seals: seals(&rot.data),
+        }
+    }
+}
+
+fn seals(seals: &[Seal]) -> Vec<SealDto> {
+    seals.iter().map(SealDto::from).collect()
+}
+
+/// Reference to delegating event which anchors delegated event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SourceSealDto {
+    pub sn: u64,
+    pub digest: String,
+}
+
+impl From<&SourceSeal> for SourceSealDto {
+    fn from(seal: &SourceSeal) -> Self {
+        Self {
+            sn: seal.sn,
+            digest: seal.digest.said.to_string(),
+        }
+    }
+}
+
+/// Key event with attached controller signatures, and witness receipts and
+/// delegating event reference, if there are any.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignedEventDto {
+    pub event: KeyEventDto,
+    pub signatures: Vec<IndexedSignatureDto>,
+    pub witness_receipts: WitnessSignaturesDto,
+    pub delegator_seal: Option<SourceSealDto>,
+}
+
+impl TryFrom<&SignedEventMessage> for SignedEventDto {
+    type Error = Error;
+
+    fn try_from(signed: &SignedEventMessage) -> Result<Self, Error> {
+        Ok(Self {
+            event: (&signed.event_message).try_into()?,
+            signatures: signed
+                .signatures
+                .iter()
+                .map(IndexedSignatureDto::from)
+                .collect(),
+            witness_receipts: signed
+                .witness_receipts
+                .as_deref()
+                .unwrap_or_default()
+                .into(),
+            delegator_seal: signed.delegator_seal.as_ref().map(SourceSealDto::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        event::sections::seal::DigestSeal,
+        event_message::key_event_builder::{InceptionBuilder, InteractionBuilder},
+        prefix::{BasicPrefix, CesrPrimitive, IndexedSignature, SelfSigningPrefix},
+        signer::Signer,
+        state::IdentifierState,
+    };
+
+    #[test]
+    fn test_key_event_dto() -> Result<(), Error> {
+        let signer = Signer::new();
+        let key = BasicPrefix::Ed25519(signer.public_key());
+        let icp = InceptionBuilder::new(vec![key.clone()])
+            .with_next_keys(vec![key.clone()])
+            .build()?;
+        let prefix = icp.data.prefix.to_string();
+        let digest = icp.digest()?.to_string();
+        let next_digest = match &icp.data.event_data {
+            EventData::Icp(icp) => icp.key_config.next_keys_data.next_keys_hashes()[0].to_string(),
+            _ => unreachable!(),
+        };
+
+        let dto = KeyEventDto::try_from(&icp)?;
+        assert_eq!(
+            serde_json::to_value(&dto).unwrap(),
+            json!({
+                "identifier": prefix,
+                "sn": 0,
+                "digest": digest,
+                "type": "inception",
+                "keys": [key.to_str()],
+                "key_threshold": 1,
+                "next_key_digests": [next_digest],
+                "next_key_threshold": 1,
+                "witnesses": [],
+                "witness_threshold": 0,
+                "configuration": [],
+                "seals": [],
+            })
+        );
+        let json = serde_json::to_string(&dto).unwrap();
+        assert_eq!(serde_json::from_str::<KeyEventDto>(&json).unwrap(), dto);
+
+        let state = IdentifierState::default().apply(&icp)?;
+        let anchored = icp.digest()?;
+        let ixn = InteractionBuilder::new(&state)
+            .with_seals(vec![Seal::Digest(DigestSeal::new(anchored.clone()))])
+            .build()?;
+        let signature = IndexedSignature::new_both_same(
+            SelfSigningPrefix::Ed25519Sha512(signer.sign(ixn.encode()?)?),
+            0,
+        );
+        let signed = SignedEventMessage::new(&ixn, vec![signature], None, None);
+        let dto = SignedEventDto::try_from(&signed)?;
+        let value = serde_json::to_value(&dto).unwrap();
+        assert_eq!(value["event"]["type"], json!("interaction"));
+        assert_eq!(value["event"]["previous_digest"], json!(digest));
+        assert_eq!(
+            value["event"]["seals"],
+            json!([{"type": "digest", "digest": anchored.to_string()}])
+        );
+        assert_eq!(value["signatures"][0]["index"], json!(0));
+        assert_eq!(value["signatures"][0]["prior_index"], json!(0));
+        assert_eq!(value["delegator_seal"], json!(null));
+        Ok(())
+    }
+}
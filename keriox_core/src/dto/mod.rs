@@ -0,0 +1,249 @@
+//! Stable serde representations of KERI data.
+//!
+//! Internal types serialize to KERI wire format, with its one and two letter
+//! field names, and change whenever processing needs them to. Types here are
+//! meant to be exposed by APIs built on keriox, e.g. REST services: fields
+//! have descriptive names, identifiers, keys, digests and signatures are
+//! CESR text and sequence numbers are plain numbers. They are converted from
+//! internal types, and changing them is a breaking change of the crate.
+
+mod event;
+mod notification;
+mod receipt;
+#[cfg(feature = "query")]
+mod reply;
+mod state;
+
+use cesrox::primitives::CesrPrimitive;
+use said::SelfAddressingIdentifier;
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    event::{
+        DelegatedInceptionDto, InceptionDto, InteractionDto, KeyEventDataDto, KeyEventDto,
+        RotationDto, SignedEventDto, SourceSealDto,
+    },
+    notification::{EscrowKindDto, NotificationDto},
+    receipt::{ReceiptDto, ValidatorReceiptDto, WitnessReceiptDto},
+    state::{KeyStateDto, LastEstablishmentDto},
+};
+#[cfg(feature = "query")]
+pub use reply::ReplyDto;
+
+use crate::{
+    event::sections::{
+        seal::{EventSeal, Seal},
+        threshold::{SignatureThreshold, ThresholdClause, WeightedThreshold},
+        KeyConfig,
+    },
+    event_message::signature::Nontransferable,
+    prefix::{attached_signature::Index, BasicPrefix, IndexedSignature, SelfSigningPrefix},
+};
+
+/// Signing threshold. Simple threshold is a number, weighted one a list of
+/// fractions, e.g. `["1/2", "1/2"]`, or list of such lists if it has
+/// multiple clauses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ThresholdDto {
+    Simple(u64),
+    Weighted(Vec<String>),
+    MultiWeighted(Vec<Vec<String>>),
+}
+
+impl From<&SignatureThreshold> for ThresholdDto {
+    fn from(threshold: &SignatureThreshold) -> Self {
+        let fractions = |clause: &ThresholdClause| {
+            clause
+                .fractions()
+                .iter()
+                .map(|fraction| fraction.to_string())
+                .collect()
+        };
+        match threshold {
+            SignatureThreshold::Simple(t) => ThresholdDto::Simple(*t),
+            SignatureThreshold::Weighted(WeightedThreshold::Single(clause)) => {
+                ThresholdDto::Weighted(fractions(clause))
+            }
+            SignatureThreshold::Weighted(WeightedThreshold::Multi(clauses)) => {
+                ThresholdDto::MultiWeighted(clauses.clauses().iter().map(fractions).collect())
+            }
+        }
+    }
+}
+
+/// Current keys and next keys commitment.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyConfigDto {
+    pub keys: Vec<String>,
+    pub key_threshold: ThresholdDto,
+    pub next_key_digests: Vec<String>,
+    pub next_key_threshold: ThresholdDto,
+}
+
+impl From<&KeyConfig> for KeyConfigDto {
+    fn from(key_config: &KeyConfig) -> Self {
+        Self {
+            keys: keys_to_str(&key_config.public_keys),
+            key_threshold: (&key_config.threshold).into(),
+            next_key_digests: digests_to_str(&key_config.next_keys_data.next_keys_hashes()),
+            next_key_threshold: (&key_config.next_keys_data.threshold).into(),
+        }
+    }
+}
+
+/// Signature made with key of given index in the signer's key list.
+/// `prior_index` is index of the key in prior next keys list, if the
+/// signature counts toward prior next threshold too.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IndexedSignatureDto {
+    pub index: u16,
+    pub prior_index: Option<u16>,
+    pub signature: String,
+}
+
+impl From<&IndexedSignature> for IndexedSignatureDto {
+    fn from(signature: &IndexedSignature) -> Self {
+        let (index, prior_index) = match signature.index {
+            Index::CurrentOnly(i) => (i, None),
+            Index::BothSame(i) => (i, Some(i)),
+            Index::BothDifferent(i, pi) => (i, Some(pi)),
+        };
+        Self {
+            index,
+            prior_index,
+            signature: signature.signature.to_str(),
+        }
+    }
+}
+
+/// Signature made by non-transferable identifier, e.g. a witness.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CoupletDto {
+    pub signer: String,
+    pub signature: String,
+}
+
+impl From<&(BasicPrefix, SelfSigningPrefix)> for CoupletDto {
+    fn from((signer, signature): &(BasicPrefix, SelfSigningPrefix)) -> Self {
+        Self {
+            signer: signer.to_str(),
+            signature: signature.to_str(),
+        }
+    }
+}
+
+/// Witness signatures. Indexed ones are made by witness of given index in
+/// the witness list.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WitnessSignaturesDto {
+    pub indexed: Vec<IndexedSignatureDto>,
+    pub couplets: Vec<CoupletDto>,
+}
+
+impl From<&[Nontransferable]> for WitnessSignaturesDto {
+    fn from(signatures: &[Nontransferable]) -> Self {
+        signatures
+            .iter()
+            .fold(Self::default(), |mut dto, signature| {
+                match signature {
+                    Nontransferable::Indexed(sigs) => dto
+                        .indexed
+                        .extend(sigs.iter().map(IndexedSignatureDto::from)),
+                    Nontransferable::Couplet(couplets) => {
+                        dto.couplets.extend(couplets.iter().map(CoupletDto::from))
+                    }
+                }
+                dto
+            })
+    }
+}
+
+/// Reference to key event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventSealDto {
+    pub identifier: String,
+    pub sn: u64,
+    pub digest: String,
+}
+
+impl From<&EventSeal> for EventSealDto {
+    fn from(seal: &EventSeal) -> Self {
+        Self {
+            identifier: seal.prefix.to_string(),
+            sn: seal.sn,
+            digest: seal.event_digest().to_string(),
+        }
+    }
+}
+
+/// Data anchored in key event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SealDto {
+    Digest {
+        digest: String,
+    },
+    Root {
+        tree_root: String,
+    },
+    Event(EventSealDto),
+    Location {
+        identifier: String,
+        sn: u64,
+        event_type: String,
+        prior_digest: String,
+    },
+}
+
+impl From<&Seal> for SealDto {
+    fn from(seal: &Seal) -> Self {
+        match seal {
+            Seal::Digest(seal) => SealDto::Digest {
+                digest: seal.digest().to_string(),
+            },
+            Seal::Root(seal) => SealDto::Root {
+                tree_root: seal.tree_root().to_string(),
+            },
+            Seal::Event(seal) => SealDto::Event(seal.into()),
+            Seal::Location(seal) => SealDto::Location {
+                identifier: seal.prefix.to_string(),
+                sn: seal.sn,
+                event_type: seal.ilk.clone(),
+                prior_digest: seal.prior_digest().to_string(),
+            },
+        }
+    }
+}
+
+fn digests_to_str(digests: &[SelfAddressingIdentifier]) -> Vec<String> {
+    digests.iter().map(|digest| digest.to_string()).collect()
+}
+
+fn keys_to_str(keys: &[BasicPrefix]) -> Vec<String> {
+    keys.iter().map(|key| key.to_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_threshold_dto() {
+        let dto = ThresholdDto::from(&SignatureThreshold::Simple(2));
+        assert_eq!(serde_json::to_value(&dto).unwrap(), json!(2));
+
+        let dto = ThresholdDto::from(&SignatureThreshold::single_weighted(vec![(1, 2), (1, 2)]));
+        assert_eq!(serde_json::to_value(&dto).unwrap(), json!(["1/2", "1/2"]));
+
+        let dto = ThresholdDto::from(&SignatureThreshold::multi_weighted(vec![
+            vec![(1, 2), (1, 2)],
+            vec![(1, 1)],
+        ]));
+        let value = serde_json::to_value(&dto).unwrap();
+        assert_eq!(value, json!([["1/2", "1/2"], ["1"]]));
+        assert_eq!(serde_json::from_value::<ThresholdDto>(value).unwrap(), dto);
+    }
+}
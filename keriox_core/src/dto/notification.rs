@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "query")]
+use super::ReplyDto;
+use super::{
+    KeyStateDto, SignedEventDto, ValidatorReceiptDto, WitnessReceiptDto, WitnessSignaturesDto,
+};
+use crate::{
+    error::Error,
+    processor::{escrow::EscrowKind, notification::Notification},
+};
+
+/// Escrow an event waited in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowKindDto {
+    OutOfOrder,
+    PartiallySigned,
+    PartiallyWitnessed,
+    Delegation,
+    Duplicitous,
+}
+
+impl From<EscrowKind> for EscrowKindDto {
+    fn from(kind: EscrowKind) -> Self {
+        match kind {
+            EscrowKind::OutOfOrder => EscrowKindDto::OutOfOrder,
+            EscrowKind::PartiallySigned => EscrowKindDto::PartiallySigned,
+            EscrowKind::PartiallyWitnessed => EscrowKindDto::PartiallyWitnessed,
+            EscrowKind::Delegation => EscrowKindDto::Delegation,
+            EscrowKind::Duplicitous => EscrowKindDto::Duplicitous,
+        }
+    }
+}
+
+/// Notification emitted while processing messages, e.g. to be pushed to
+/// API clients.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationDto {
+    KeyEventAdded {
+        event: SignedEventDto,
+    },
+    KeyEventAccepted {
+        event: SignedEventDto,
+        state: Box<KeyStateDto>,
+        witness_receipts: WitnessSignaturesDto,
+        promoted_from: Option<EscrowKindDto>,
+    },
+    OutOfOrder {
+        event: SignedEventDto,
+    },
+    PartiallySigned {
+        event: SignedEventDto,
+    },
+    PartiallyWitnessed {
+        event: SignedEventDto,
+    },
+    ReceiptAccepted,
+    ReceiptEscrowed,
+    ReceiptOutOfOrder {
+        receipt: WitnessReceiptDto,
+    },
+    TransReceiptOutOfOrder {
+        receipt: ValidatorReceiptDto,
+    },
+    DuplicitousEvent {
+        event: SignedEventDto,
+    },
+    MissingDelegatingEvent {
+        event: SignedEventDto,
+    },
+    #[cfg(feature = "query")]
+    KsnOutOfOrder {
+        reply: ReplyDto,
+    },
+    #[cfg(feature = "query")]
+    KsnUpdated {
+        reply: ReplyDto,
+    },
+}
+
+impl TryFrom<&Notification> for NotificationDto {
+    type Error = Error;
+
+    fn try_from(notification: &Notification) -> Result<Self, Error> {
+        Ok(match notification {
+            Notification::KeyEventAdded(event) => NotificationDto::KeyEventAdded {
+                event: event.try_into()?,
+            },
+            Notification::KeyEventAccepted(accepted) => NotificationDto::KeyEventAccepted {
+                event: (&accepted.event).try_into()?,
+                state: Box::new((&accepted.state).into()),
+                witness_receipts: accepted.witness_receipts.as_slice().into(),
+                promoted_from: accepted.promoted_from.map(EscrowKindDto::from),
+            },
+            Notification::OutOfOrder(event) => NotificationDto::OutOfOrder {
+                event: event.try_into()?,
+            },
+            Notification::PartiallySigned(event) => NotificationDto::PartiallySigned {
+                event: event.try_into()?,
+            },
+            Notification::PartiallyWitnessed(event) => NotificationDto::PartiallyWitnessed {
+                event: event.try_into()?,
+            },
+            Notification::ReceiptAccepted => NotificationDto::ReceiptAccepted,
+            Notification::ReceiptEscrowed => NotificationDto::ReceiptEscrowed,
+            Notification::ReceiptOutOfOrder(receipt) => NotificationDto::ReceiptOutOfOrder {
+                receipt: receipt.into(),
+            },
+            Notification::TransReceiptOutOfOrder(receipt) => {
+                NotificationDto::TransReceiptOutOfOrder {
+                    receipt: receipt.into(),
+                }
+            }
+            Notification::DupliciousEvent(event) => NotificationDto::DuplicitousEvent {
+                event: event.try_into()?,
+            },
+            Notification::MissingDelegatingEvent(event) => {
+                NotificationDto::MissingDelegatingEvent {
+                    event: event.try_into()?,
+                }
+            }
+            #[cfg(feature = "query")]
+            Notification::KsnOutOfOrder(reply) => NotificationDto::KsnOutOfOrder {
+                reply: reply.try_into()?,
+            },
+            #[cfg(feature = "query")]
+            Notification::KsnUpdated(reply) => NotificationDto::KsnUpdated {
+                reply: reply.try_into()?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        event::receipt::Receipt,
+        event_message::{
+            key_event_builder::InceptionBuilder,
+            signature::Nontransferable,
+            signed_event_message::{SignedEventMessage, SignedNontransferableReceipt},
+        },
+        prefix::{BasicPrefix, CesrPrimitive, SelfSigningPrefix},
+        processor::notification::AcceptedEvent,
+        signer::Signer,
+        state::IdentifierState,
+    };
+
+    #[test]
+    fn test_notification_dto() -> Result<(), Error> {
+        let key = BasicPrefix::Ed25519(Signer::new().public_key());
+        let icp = InceptionBuilder::new(vec![key.clone()])
+            .with_next_keys(vec![key])
+            .build()?;
+        let state = IdentifierState::default().apply(&icp)?;
+        let witness = Signer::new();
+        let couplet = (
+            BasicPrefix::Ed25519NT(witness.public_key()),
+            SelfSigningPrefix::Ed25519Sha512(witness.sign(icp.encode()?)?),
+        );
+        let witness_receipts = vec![Nontransferable::Couplet(vec![couplet.clone()])];
+        let accepted = AcceptedEvent {
+            event: SignedEventMessage::new(&icp, vec![], None, None),
+            state: state.clone(),
+            witness_receipts: witness_receipts.clone(),
+            promoted_from: Some(EscrowKind::PartiallyWitnessed),
+        };
+
+        let dto = NotificationDto::try_from(&Notification::KeyEventAccepted(Box::new(accepted)))?;
+        let value = serde_json::to_value(&dto).unwrap();
+        assert_eq!(value["type"], json!("key_event_accepted"));
+        assert_eq!(value["event"]["event"]["type"], json!("inception"));
+        assert_eq!(
+            value["state"]["identifier"],
+            json!(state.prefix.to_string())
+        );
+        assert_eq!(value["state"]["last_establishment"]["sn"], json!(0));
+        assert_eq!(
+            value["witness_receipts"]["couplets"],
+            json!([{"signer": couplet.0.to_str(), "signature": couplet.1.to_str()}])
+        );
+        assert_eq!(value["promoted_from"], json!("partially_witnessed"));
+        let json = serde_json::to_string(&dto).unwrap();
+        assert_eq!(serde_json::from_str::<NotificationDto>(&json).unwrap(), dto);
+
+        let receipt = Receipt::new(
+            icp.serialization_info.kind,
+            icp.digest()?,
+            state.prefix.clone(),
+            0,
+        );
+        let receipt = SignedNontransferableReceipt::new(&receipt, witness_receipts);
+        let dto = NotificationDto::try_from(&Notification::ReceiptOutOfOrder(receipt))?;
+        assert_eq!(
+            serde_json::to_value(&dto).unwrap(),
+            json!({
+                "type": "receipt_out_of_order",
+                "receipt": {
+                    "identifier": state.prefix.to_string(),
+                    "sn": 0,
+                    "receipted_event_digest": icp.digest()?.to_string(),
+                    "signatures": {
+                        "indexed": [],
+                        "couplets": [{"signer": couplet.0.to_str(), "signature": couplet.1.to_str()}],
+                    },
+                },
+            })
+        );
+        Ok(())
+    }
+}
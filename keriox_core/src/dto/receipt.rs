@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use super::{EventSealDto, IndexedSignatureDto, WitnessSignaturesDto};
+use crate::{
+    event::receipt::Receipt,
+    event_message::signed_event_message::{
+        SignedNontransferableReceipt, SignedTransferableReceipt,
+    },
+};
+
+/// Receipted event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReceiptDto {
+    pub identifier: String,
+    pub sn: u64,
+    pub receipted_event_digest: String,
+}
+
+impl From<&Receipt> for ReceiptDto {
+    fn from(receipt: &Receipt) -> Self {
+        Self {
+            identifier: receipt.prefix.to_string(),
+            sn: receipt.sn,
+            receipted_event_digest: receipt.receipted_event_digest.to_string(),
+        }
+    }
+}
+
+/// Receipt signed by non-transferable identifiers, e.g. witnesses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WitnessReceiptDto {
+    #[serde(flatten)]
+    pub receipt: ReceiptDto,
+    pub signatures: WitnessSignaturesDto,
+}
+
+impl From<&SignedNontransferableReceipt> for WitnessReceiptDto {
+    fn from(receipt: &SignedNontransferableReceipt) -> Self {
+        Self {
+            receipt: (&receipt.body).into(),
+            signatures: receipt.signatures.as_slice().into(),
+        }
+    }
+}
+
+/// Receipt signed by transferable identifier, with its establishment event
+/// the signing keys come from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ValidatorReceiptDto {
+    #[serde(flatten)]
+    pub receipt: ReceiptDto,
+    pub validator: EventSealDto,
+    pub signatures: Vec<IndexedSignatureDto>,
+}
+
+impl From<&SignedTransferableReceipt> for ValidatorReceiptDto {
+    fn from(receipt: &SignedTransferableReceipt) -> Self {
+        Self {
+            receipt: (&receipt.body).into(),
+            validator: (&receipt.validator_seal).into(),
+            signatures: receipt
+                .signatures
+                .iter()
+                .map(IndexedSignatureDto::from)
+                .collect(),
+        }
+    }
+}
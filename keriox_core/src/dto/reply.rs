@@ -0,0 +1,43 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use super::KeyStateDto;
+use crate::{
+    error::Error,
+    query::reply_event::{ReplyRoute, SignedReply},
+};
+
+/// Signed reply message. Key state is present for key state notices.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplyDto {
+    pub digest: String,
+    pub route: String,
+    pub identifier: String,
+    pub timestamp: DateTime<FixedOffset>,
+    pub key_state: Option<KeyStateDto>,
+    pub signer: Option<String>,
+}
+
+impl TryFrom<&SignedReply> for ReplyDto {
+    type Error = Error;
+
+    fn try_from(signed: &SignedReply) -> Result<Self, Error> {
+        let (route, key_state) = match &signed.reply.data.data {
+            ReplyRoute::Ksn(id, ksn) => (format!("/ksn/{}", id), Some((&ksn.state).into())),
+            #[cfg(feature = "oobi")]
+            ReplyRoute::LocScheme(_) => ("/loc/scheme".to_string(), None),
+            #[cfg(feature = "oobi")]
+            ReplyRoute::EndRoleAdd(_) => ("/end/role/add".to_string(), None),
+            #[cfg(feature = "oobi")]
+            ReplyRoute::EndRoleCut(_) => ("/end/role/cut".to_string(), None),
+        };
+        Ok(Self {
+            digest: signed.reply.digest()?.to_string(),
+            route,
+            identifier: signed.reply.get_prefix().to_string(),
+            timestamp: signed.reply.get_timestamp(),
+            key_state,
+            signer: signed.signature.get_signer().map(|id| id.to_string()),
+        })
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use super::{keys_to_str, KeyConfigDto, ThresholdDto};
+use crate::state::IdentifierState;
+
+/// Key state of identifier, after applying its last event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyStateDto {
+    pub identifier: String,
+    pub sn: u64,
+    /// Digest of the last event.
+    pub digest: String,
+    /// Digest of event preceding the last one, if there is one.
+    pub previous_digest: Option<String>,
+    #[serde(flatten)]
+    pub key_config: KeyConfigDto,
+    pub witnesses: Vec<String>,
+    pub witness_threshold: ThresholdDto,
+    pub delegator: Option<String>,
+    pub last_establishment: LastEstablishmentDto,
+}
+
+/// Reference to the last establishment event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LastEstablishmentDto {
+    pub sn: u64,
+    pub digest: String,
+}
+
+impl From<&IdentifierState> for KeyStateDto {
+    fn from(state: &IdentifierState) -> Self {
+        Self {
+            identifier: state.prefix.to_string(),
+            sn: state.sn,
+            digest: state.last_event_digest.said.to_string(),
+            previous_digest: state
+                .last_previous
+                .as_ref()
+                .map(|digest| digest.said.to_string()),
+            key_config: (&state.current).into(),
+            witnesses: keys_to_str(&state.witness_config.witnesses),
+            witness_threshold: (&state.witness_config.tally).into(),
+            delegator: state.delegator.as_ref().map(|id| id.to_string()),
+            last_establishment: LastEstablishmentDto {
+                sn: state.last_est.sn,
+                digest: state.last_est.digest.said.to_string(),
+            },
+        }
+    }
+}
@@ -98,6 +98,9 @@ pub enum Error {
     #[error("Event generation error: {0}")]
     EventGenerationError(String),
 
+    #[error(transparent)]
+    EventBuilderError(#[from] crate::event_message::key_event_builder::EventBuilderError),
+
     #[error(transparent)]
     PrefixModuleError(#[from] crate::prefix::error::Error),
 
@@ -88,6 +88,8 @@ impl EventSemantics for KeyEvent {
                 // TODO recovery will break this rule when we implement it
                 } else if self.sn < state.sn + 1 {
                     return Err(Error::EventDuplicateError);
+                } else if state.is_abandoned() {
+                    return Err(Error::SemanticError("Identifier is abandoned".to_string()));
                 } else if self.sn > state.sn + 1 {
                     return Err(Error::EventOutOfOrderError);
                 }
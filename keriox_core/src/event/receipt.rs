@@ -1,4 +1,5 @@
 use crate::error::Error;
+use crate::event_message::encoding::encode;
 use crate::event_message::EventTypeTag;
 use crate::event_message::Typeable;
 use crate::prefix::IdentifierPrefix;
@@ -55,7 +56,7 @@ impl Receipt {
     }
 
     pub fn encode(&self) -> Result<Vec<u8>, Error> {
-        Ok(self.serialization_info.serialize(&self).unwrap())
+        encode(&self.serialization_info.kind, &self)
     }
 }
 
@@ -52,17 +52,35 @@ impl NextKeysData {
             .collect()
     }
 
+    /// Returns position of key's commitment in next keys list, if any.
+    pub fn key_position(&self, key: &BasicPrefix) -> Option<usize> {
+        self.next_key_hashes
+            .iter()
+            .position(|dig| dig.said.verify_binding(key.to_str().as_bytes()))
+    }
+
+    /// Returns commitments of next keys that are not exposed by provided
+    /// keys. In partial rotation they are reserved for later rotations.
+    pub fn reserved_hashes(&self, exposed: &[BasicPrefix]) -> Vec<SelfAddressingIdentifier> {
+        let exposed_positions: Vec<usize> = exposed
+            .iter()
+            .filter_map(|key| self.key_position(key))
+            .collect();
+        self.next_key_hashes
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !exposed_positions.contains(i))
+            .map(|(_, said)| said.clone().into())
+            .collect()
+    }
+
     /// Checks if next KeyConfig contains enough public keys to fulfill current
     /// next threshold.
     pub fn verify_next(&self, next: &KeyConfig) -> Result<bool, SignatureError> {
         let indexes: Vec<_> = next
             .public_keys
             .iter()
-            .filter_map(|key| {
-                self.next_key_hashes
-                    .iter()
-                    .position(|dig| dig.said.verify_binding(key.to_str().as_bytes()))
-            })
+            .filter_map(|key| self.key_position(key))
             .collect();
 
         // check previous next threshold
@@ -306,6 +324,30 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_reserved_hashes() {
+        let next_keys: Vec<BasicPrefix> = [
+            "DHqJ2DNmypwMKelWXLgl3V-9pDRcOenM5Wf03O1xx1Ri",
+            "DEIISiMvtnaPTpMHkoGs4d0JdbwjreW53OUBfMedLUaF",
+            "DDQFJ_uXcZum_DY6NNTtI5UrTEQo6PRWEANpn6hVtfyQ",
+        ]
+        .iter()
+        .map(|x| x.parse().unwrap())
+        .collect();
+        let nxt = nxt_commitment(
+            SignatureThreshold::Simple(1),
+            &next_keys,
+            &HashFunctionCode::Blake3_256.into(),
+        );
+
+        assert_eq!(nxt.key_position(&next_keys[1]), Some(1));
+
+        // Expose only the second key, the rest stays in reserve.
+        let reserved = nxt.reserved_hashes(&next_keys[1..2]);
+        let hashes = nxt.next_keys_hashes();
+        assert_eq!(reserved, vec![hashes[0].clone(), hashes[2].clone()]);
+    }
+
     #[test]
     fn test_threshold() -> Result<(), Error> {
         use ed25519_dalek::SigningKey;
@@ -44,6 +44,10 @@ impl DigestSeal {
     pub fn new(said: SelfAddressingIdentifier) -> Self {
         Self { dig: said.into() }
     }
+
+    pub fn digest(&self) -> SelfAddressingIdentifier {
+        self.dig.said.clone()
+    }
 }
 
 #[derive(
@@ -62,6 +66,12 @@ pub struct RootSeal {
     tree_root: SaidValue,
 }
 
+impl RootSeal {
+    pub fn tree_root(&self) -> SelfAddressingIdentifier {
+        self.tree_root.said.clone()
+    }
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -142,6 +152,12 @@ pub struct LocationSeal {
     prior_digest: SaidValue,
 }
 
+impl LocationSeal {
+    pub fn prior_digest(&self) -> SelfAddressingIdentifier {
+        self.prior_digest.said.clone()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DelegatingEventSeal {
     #[serde(rename = "i")]
@@ -1,4 +1,4 @@
-use std::{fmt, str::FromStr};
+use std::{collections::BTreeSet, fmt, str::FromStr};
 
 use fraction::{Fraction, One, Zero};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -107,6 +107,11 @@ impl WeightedThreshold {
             WeightedThreshold::Multi(clauses) => clauses.enough_signatures(sigs_indexes),
         }
     }
+
+    /// Returns true if signatures of provided indices satisfy all clauses.
+    pub fn is_satisfied(&self, indices: &[usize]) -> bool {
+        self.enough_signatures(indices).is_ok()
+    }
 }
 
 impl SignatureThreshold {
@@ -138,6 +143,19 @@ impl SignatureThreshold {
             SignatureThreshold::Weighted(ref thresh) => thresh.enough_signatures(sigs_indexes),
         }
     }
+
+    /// Checks if signatures of provided key indices satisfy the threshold.
+    /// Repeated indices are counted once, so it can be used to evaluate
+    /// progress of signature (or receipt) collection.
+    pub fn is_satisfied(&self, indices: &[usize]) -> bool {
+        let unique: Vec<usize> = indices
+            .iter()
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        self.enough_signatures(&unique).is_ok()
+    }
 }
 
 impl Default for SignatureThreshold {
@@ -175,15 +193,24 @@ impl ThresholdClause {
         self.0.len()
     }
 
+    pub fn fractions(&self) -> &[ThresholdFraction] {
+        &self.0
+    }
+
     pub fn enough_signatures(
         &self,
         start_index: usize,
         sigs_indexes: &[usize],
     ) -> Result<(), SignatureError> {
-        (sigs_indexes
+        // Each key contributes its weight only once.
+        let unique_indexes: BTreeSet<usize> = sigs_indexes.iter().cloned().collect();
+        (unique_indexes
             .iter()
             .fold(Some(Zero::zero()), |acc: Option<Fraction>, sig_index| {
-                if let (Some(element), Some(sum)) = (self.0.get(sig_index - start_index), acc) {
+                let element = sig_index
+                    .checked_sub(start_index)
+                    .and_then(|index| self.0.get(index));
+                if let (Some(element), Some(sum)) = (element, acc) {
                     Some(sum + element.fraction)
                 } else {
                     None
@@ -232,6 +259,10 @@ impl MultiClauses {
         self.0.iter().map(|l| l.length()).sum()
     }
 
+    pub fn clauses(&self) -> &[ThresholdClause] {
+        &self.0
+    }
+
     pub fn enough_signatures(&self, sigs_indexes: &[usize]) -> Result<(), SignatureError> {
         self.0
             .iter()
@@ -339,6 +370,32 @@ fn test_enough_sigs() -> Result<(), SignatureError> {
     Ok(())
 }
 
+#[test]
+fn test_is_satisfied() {
+    // Threshold: [[1/2, 1/2], [1/3, 1/3, 1/3]]
+    let threshold = SignatureThreshold::multi_weighted(vec![
+        vec![(1, 2), (1, 2)],
+        vec![(1, 3), (1, 3), (1, 3)],
+    ]);
+    // Only first clause satisfied.
+    assert!(!threshold.is_satisfied(&[0, 1]));
+    // Repeated signature doesn't add weight.
+    assert!(!threshold.is_satisfied(&[0, 0, 2, 3, 4]));
+    assert!(!threshold.is_satisfied(&[0, 2, 2, 3, 3]));
+    // Both clauses satisfied.
+    assert!(threshold.is_satisfied(&[0, 1, 2, 3, 4]));
+    assert!(threshold.is_satisfied(&[4, 3, 1, 2, 0, 0]));
+
+    let threshold = SignatureThreshold::single_weighted(vec![(1, 2), (1, 4), (1, 4)]);
+    assert!(!threshold.is_satisfied(&[1, 2]));
+    assert!(!threshold.is_satisfied(&[0, 0]));
+    assert!(threshold.is_satisfied(&[0, 1, 2]));
+
+    let threshold = SignatureThreshold::simple(2);
+    assert!(!threshold.is_satisfied(&[1, 1]));
+    assert!(threshold.is_satisfied(&[0, 1]));
+}
+
 #[test]
 pub fn test_weighted_treshold_serialization() -> Result<(), SignatureError> {
     let multi_threshold = r#"[["1"],["1/2","1/2","1/2"]]"#.to_string();
@@ -0,0 +1,258 @@
+use cesrox::{
+    group::Group,
+    payload::Payload,
+    primitives::{
+        codes::{serial_number::pack_sn, timestamp::pack_datetime},
+        CesrPrimitive, IndexedSignature,
+    },
+    ParsedData,
+};
+use said::version::format::SerializationFormats;
+
+use super::{
+    cesr_adapter::parse_versioned,
+    cesr_version::{genus_version, group_to_cesr, CesrVersion},
+    encoding::decode,
+};
+use crate::prefix::attached_signature::Index;
+
+/// Renders CESR stream as human readable breakdown: type and fields of
+/// each message body, and attachment groups with meaning of each of their
+/// primitives. Part of the stream that can't be parsed is shown as is, so
+/// it can be used to find where stream produced by other implementation
+/// breaks.
+pub fn annotate(stream: &[u8]) -> String {
+    let mut annotation = Annotation::default();
+    let mut rest = stream;
+    let mut version = CesrVersion::V1;
+    loop {
+        rest = rest.trim_ascii_start();
+        if let Ok((tail, genus)) = genus_version(rest) {
+            let code = String::from_utf8_lossy(&rest[..rest.len() - tail.len()]).into_owned();
+            annotation.line(0, &code, &format!("genus version, {:?}", genus));
+            rest = tail;
+            version = genus;
+            continue;
+        }
+        match parse_versioned(rest, version) {
+            Ok((tail, (next_version, parsed))) => {
+                annotation.message(&parsed, version);
+                rest = tail;
+                version = next_version;
+            }
+            Err(_) => break,
+        }
+    }
+    if !rest.is_empty() {
+        let shown = &rest[..rest.len().min(64)];
+        annotation.line(
+            0,
+            &String::from_utf8_lossy(shown),
+            &format!("unparsed, {} bytes", rest.len()),
+        );
+    }
+    annotation.0
+}
+
+#[derive(Default)]
+struct Annotation(String);
+
+impl Annotation {
+    fn line(&mut self, depth: usize, cesr: &str, meaning: &str) {
+        self.0.push_str(&"  ".repeat(depth));
+        self.0.push_str(cesr);
+        if !meaning.is_empty() {
+            self.0.push_str("  ");
+            self.0.push_str(meaning);
+        }
+        self.0.push('\n');
+    }
+
+    fn message(&mut self, message: &ParsedData, version: CesrVersion) {
+        let (format, body) = match &message.payload {
+            Payload::JSON(body) => (SerializationFormats::JSON, body),
+            Payload::CBOR(body) => (SerializationFormats::CBOR, body),
+            Payload::MGPK(body) => (SerializationFormats::MGPK, body),
+        };
+        match decode::<serde_json::Value>(&format, body) {
+            Ok(serde_json::Value::Object(fields)) => {
+                let message_type = fields
+                    .get("t")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or("unknown");
+                self.line(
+                    0,
+                    &format!("{} message", message_type),
+                    &format!("{}, {} bytes", format.to_str(), body.len()),
+                );
+                for (name, value) in fields {
+                    self.line(1, &format!("{}: {}", name, value), "");
+                }
+            }
+            Ok(_) | Err(_) => self.line(
+                0,
+                "undecodable message",
+                &format!("{}, {} bytes", format.to_str(), body.len()),
+            ),
+        }
+        for group in &message.attachments {
+            self.group(0, group, version);
+        }
+    }
+
+    fn group(&mut self, depth: usize, group: &Group, version: CesrVersion) {
+        let (meaning, count) = match group {
+            Group::IndexedControllerSignatures(sigs) => {
+                ("controller indexed signatures", sigs.len())
+            }
+            Group::IndexedWitnessSignatures(sigs) => ("witness indexed signatures", sigs.len()),
+            Group::NontransReceiptCouples(couples) => {
+                ("non-transferable receipt couples", couples.len())
+            }
+            Group::SourceSealCouples(couples) => ("source seal couples", couples.len()),
+            Group::FirstSeenReplyCouples(couples) => ("first seen replay couples", couples.len()),
+            Group::TransIndexedSigGroups(groups) => {
+                ("transferable indexed signature groups", groups.len())
+            }
+            Group::LastEstSignaturesGroups(groups) => {
+                ("last establishment event signature groups", groups.len())
+            }
+            Group::Frame(groups) => ("attachment group", groups.len()),
+            Group::PathedMaterialQuadruplet(_, groups) => ("pathed material group", groups.len()),
+        };
+        self.line(
+            depth,
+            &counter_code(group, version),
+            &format!("{}, {}", meaning, count),
+        );
+
+        let depth = depth + 1;
+        match group {
+            Group::IndexedControllerSignatures(sigs) | Group::IndexedWitnessSignatures(sigs) => {
+                self.signatures(depth, sigs)
+            }
+            Group::NontransReceiptCouples(couples) => {
+                for (key, sig) in couples {
+                    self.line(depth, &key.to_str(), "signer");
+                    self.line(depth, &sig.to_str(), &format!("{:?} signature", sig.0));
+                }
+            }
+            Group::SourceSealCouples(couples) => {
+                for (sn, digest) in couples {
+                    self.line(depth, &pack_sn(*sn), &format!("sn {}", sn));
+                    self.line(depth, &digest.to_str(), "source event digest");
+                }
+            }
+            Group::FirstSeenReplyCouples(couples) => {
+                for (sn, timestamp) in couples {
+                    self.line(depth, &pack_sn(*sn), &format!("first seen number {}", sn));
+                    self.line(depth, &pack_datetime(timestamp), &timestamp.to_rfc3339());
+                }
+            }
+            Group::TransIndexedSigGroups(groups) => {
+                for (id, sn, digest, sigs) in groups {
+                    self.line(depth, &id.to_str(), "signer");
+                    self.line(
+                        depth,
+                        &pack_sn(*sn),
+                        &format!("establishment event sn {}", sn),
+                    );
+                    self.line(depth, &digest.to_str(), "establishment event digest");
+                    self.group(
+                        depth,
+                        &Group::IndexedControllerSignatures(sigs.clone()),
+                        version,
+                    );
+                }
+            }
+            Group::LastEstSignaturesGroups(groups) => {
+                for (id, sigs) in groups {
+                    self.line(depth, &id.to_str(), "signer");
+                    self.group(
+                        depth,
+                        &Group::IndexedControllerSignatures(sigs.clone()),
+                        version,
+                    );
+                }
+            }
+            Group::Frame(groups) => {
+                for group in groups {
+                    self.group(depth, group, version);
+                }
+            }
+            Group::PathedMaterialQuadruplet(path, groups) => {
+                self.line(depth, &path.to_cesr(), "path");
+                for group in groups {
+                    self.group(depth, group, version);
+                }
+            }
+        }
+    }
+
+    fn signatures(&mut self, depth: usize, sigs: &[IndexedSignature]) {
+        for sig in sigs {
+            let index = match Index::from(sig.0.index) {
+                Index::CurrentOnly(i) => format!("current key {}", i),
+                Index::BothSame(i) => format!("key {}", i),
+                Index::BothDifferent(i, pi) => format!("key {}, prior next key {}", i, pi),
+            };
+            self.line(
+                depth,
+                &sig.to_str(),
+                &format!("{:?} signature by {}", sig.0.code, index),
+            );
+        }
+    }
+}
+
+/// Count code of the group, as encoded in given CESR version.
+fn counter_code(group: &Group, version: CesrVersion) -> String {
+    let encoded = match version {
+        CesrVersion::V1 => group.to_cesr_str(),
+        CesrVersion::V2 => group_to_cesr(group),
+    };
+    let len = if encoded.starts_with("--") { 8 } else { 4 };
+    encoded[..len].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEL: &[u8] = br#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD"#;
+
+    #[test]
+    fn test_annotate() {
+        let annotation = annotate(KEL);
+        let lines = annotation.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "icp message  JSON, 345 bytes");
+        assert_eq!(lines[1], r#"  v: "KERI10JSON000159_""#);
+        assert_eq!(
+            lines[7],
+            r#"  k: ["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"]"#
+        );
+        assert_eq!(lines[14], "-AAB  controller indexed signatures, 1");
+        assert_eq!(
+            lines[15],
+            "  AADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD  Ed25519Sha512 signature by key 0"
+        );
+        assert_eq!(lines.len(), 16);
+
+        // CESR 2.0 codes are shown once genus version selects it.
+        let (_, parsed) = cesrox::parse(KEL).unwrap();
+        let stream = [
+            CesrVersion::V2.genus_version().into_bytes(),
+            CesrVersion::V2.encode(&parsed),
+        ]
+        .concat();
+        let annotation = annotate(&stream);
+        assert!(annotation.starts_with("-_AAACAA  genus version, V2\n"));
+        assert!(annotation.contains("\n-KAW  controller indexed signatures, 1\n"));
+
+        // Broken attachment is shown as is.
+        let annotation = annotate(&KEL[..KEL.len() - 10]);
+        let last = annotation.lines().last().unwrap();
+        assert!(last.starts_with("-AABAADtEDd5x"));
+        assert!(last.ends_with("  unparsed, 82 bytes"));
+    }
+}
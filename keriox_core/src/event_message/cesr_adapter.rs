@@ -1,8 +1,8 @@
 use std::convert::{TryFrom, TryInto};
 
 use cesrox::{
-    group::Group,
-    payload::{parse_payload, Payload},
+    group::{parsers::parse_group, Group},
+    payload::Payload,
     primitives::IndexedSignature as CesrIndexedSignature,
     ParsedData,
 };
@@ -30,19 +30,23 @@ use crate::query::{
 
 #[cfg(feature = "mailbox")]
 use crate::{
-    event_message::signature,
+    event_message::{sad_path::PathedSignatures, signature},
     mailbox::exchange::{ExchangeMessage, SignedExchange},
     query::mailbox::{MailboxQuery, SignedMailboxQuery},
 };
 
 use super::{
+    cesr_version::{genus_version, parse_v2_group, CesrVersion},
+    encoding::decode,
     msg::{KeriEvent, TypedEvent},
+    protocol_version::{VersionError, VersionPolicy},
     signature::Nontransferable,
     signed_event_message::{
         Message, Notice, SignedEventMessage, SignedNontransferableReceipt,
         SignedTransferableReceipt,
     },
-    Typeable,
+    strict_parser::ParsedRef,
+    EventTypeTag, Typeable,
 };
 
 #[derive(Debug, thiserror::Error, Serialize, Deserialize)]
@@ -55,6 +59,122 @@ pub enum ParseError {
     AttachmentError(String),
     #[error("Wrong event type: {0}")]
     WrongEventType(String),
+    #[error("Unsupported message version: {0}")]
+    UnsupportedVersion(#[from] VersionError),
+}
+
+/// Parses message body. Unlike `cesrox`, which frames MGPK bodies only if
+/// all their values are strings, any MGPK event body is accepted.
+pub fn parse_payload(stream: &[u8]) -> nom::IResult<&[u8], Payload> {
+    match stream.first().map(|byte| byte >> 5) {
+        #[cfg(feature = "mgpk")]
+        Some(0b100) | Some(0b110) => parse_mgpk_payload(stream),
+        _ => cesrox::payload::parse_payload(stream),
+    }
+}
+
+#[cfg(feature = "mgpk")]
+fn parse_mgpk_payload(stream: &[u8]) -> nom::IResult<&[u8], Payload> {
+    use std::io::Cursor;
+
+    use nom::error::{make_error, ErrorKind};
+    use serde::de::IgnoredAny;
+
+    let mut deserializer = rmp_serde::Deserializer::new(Cursor::new(stream));
+    match IgnoredAny::deserialize(&mut deserializer) {
+        Ok(_) => {
+            let end = deserializer.get_ref().position() as usize;
+            Ok((&stream[end..], Payload::MGPK(stream[..end].to_vec())))
+        }
+        Err(_) => Err(nom::Err::Error(make_error(stream, ErrorKind::IsNot))),
+    }
+}
+
+/// Parses message body with its attachments. Should be used instead of
+/// `cesrox::parse`, see `parse_payload`.
+pub fn parse(stream: &[u8]) -> nom::IResult<&[u8], ParsedData> {
+    parse_versioned(stream, CesrVersion::V1).map(|(rest, (_version, parsed))| (rest, parsed))
+}
+
+/// Parses message with attachments counted the way CESR `version` does.
+/// Genus version code before message body or among attachments changes the
+/// version, so returned one should be used for the rest of stream.
+pub fn parse_versioned(
+    stream: &[u8],
+    version: CesrVersion,
+) -> nom::IResult<&[u8], (CesrVersion, ParsedData)> {
+    let (rest, mut version) = match genus_version(stream) {
+        Ok((rest, version)) => (rest, version),
+        Err(_) => (stream, version),
+    };
+    let (body_rest, payload) = parse_payload(rest)?;
+    // Attachments are in text domain. `cesrox` primitive parsers expect
+    // UTF-8 input, so binary body of the next message is cut off first.
+    let text = &body_rest[..body_rest
+        .iter()
+        .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+        .count()];
+    let mut rest = text;
+    let mut attachments = vec![];
+    loop {
+        if let Ok((tail, genus)) = genus_version(rest) {
+            (rest, version) = (tail, genus);
+            continue;
+        }
+        let group = match version {
+            CesrVersion::V1 => parse_group(rest),
+            CesrVersion::V2 => parse_v2_group(rest),
+        };
+        match group {
+            Ok((tail, group)) => {
+                attachments.push(group);
+                rest = tail;
+            }
+            Err(nom::Err::Error(_)) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    let rest = &body_rest[text.len() - rest.len()..];
+    Ok((
+        rest,
+        (
+            version,
+            ParsedData {
+                payload,
+                attachments,
+            },
+        ),
+    ))
+}
+
+/// Parses stream of messages, starting as CESR 1.0 one.
+pub fn parse_many(stream: &[u8]) -> nom::IResult<&[u8], Vec<ParsedData>> {
+    let (mut rest, mut version) = (stream, CesrVersion::V1);
+    let mut messages = vec![];
+    loop {
+        match parse_versioned(rest, version) {
+            Ok((tail, (next_version, parsed))) => {
+                messages.push(parsed);
+                (rest, version) = (tail, next_version);
+            }
+            Err(nom::Err::Error(_)) => return Ok((rest, messages)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Parses stream of messages like `parse_many`, but fails on message
+/// whose protocol version isn't accepted by `policy`.
+pub fn parse_many_with_policy(
+    stream: &[u8],
+    policy: VersionPolicy,
+) -> Result<Vec<ParsedData>, ParseError> {
+    let (_rest, messages) =
+        parse_many(stream).map_err(|e| ParseError::CesrError(e.to_string()))?;
+    for message in &messages {
+        policy.check_payload(&message.payload)?;
+    }
+    Ok(messages)
 }
 
 pub fn parse_event_type(input: &[u8]) -> Result<EventType, ParseError> {
@@ -224,10 +344,11 @@ impl From<SignedExchange> for ParsedData {
     fn from(ev: SignedExchange) -> Self {
         let mut attachments = signature::signatures_into_groups(&ev.signature);
 
-        let data_signatures = signature::signatures_into_groups(&ev.data_signature.1);
-
-        let data_attachment = Group::PathedMaterialQuadruplet(ev.data_signature.0, data_signatures);
-        attachments.push(data_attachment);
+        // Exchanges that don't carry events have no data signatures.
+        if !ev.data_signature.1.is_empty() {
+            let (path, signatures) = ev.data_signature;
+            attachments.push(PathedSignatures { path, signatures }.into());
+        }
         ParsedData {
             payload: ev.exchange_message.into(),
             attachments,
@@ -235,38 +356,82 @@ impl From<SignedExchange> for ParsedData {
     }
 }
 
+impl EventType {
+    /// Decodes message body of given format.
+    pub fn decode(format: SerializationFormats, body: &[u8]) -> Result<Self, ParseError> {
+        Ok(match decode(&format, body)? {
+            // `drt` and `rot` data are the same, so untagged event data is
+            // decoded as rotation. Event type tells them apart.
+            EventType::KeyEvent(mut event) => {
+                if let (EventTypeTag::Drt, EventData::Rot(rot)) =
+                    (&event.event_type, &event.data.event_data)
+                {
+                    event.data.event_data = EventData::Drt(rot.clone());
+                }
+                EventType::KeyEvent(event)
+            }
+            event => event,
+        })
+    }
+}
+
 impl TryFrom<Payload> for EventType {
     type Error = ParseError;
 
     fn try_from(value: Payload) -> Result<Self, Self::Error> {
-        let event: Result<EventType, _> = match value {
-            Payload::JSON(event) => serde_json::from_slice(&event),
-            Payload::CBOR(_event) => todo!(),
-            Payload::MGPK(_event) => todo!(),
-        };
-        event.map_err(|e| ParseError::DeserializeError(e.to_string()))
+        match &value {
+            Payload::JSON(event) => EventType::decode(SerializationFormats::JSON, event),
+            Payload::CBOR(event) => EventType::decode(SerializationFormats::CBOR, event),
+            Payload::MGPK(event) => EventType::decode(SerializationFormats::MGPK, event),
+        }
     }
 }
 
+fn message(event: EventType, attachments: Vec<Group>) -> Result<Message, ParseError> {
+    let msg = match event {
+        EventType::KeyEvent(ev) => Message::Notice(signed_key_event(ev, attachments)?),
+        EventType::Receipt(rct) => Message::Notice(signed_receipt(rct, attachments)?),
+        #[cfg(feature = "query")]
+        EventType::Qry(qry) => Message::Op(signed_query(qry, attachments)?),
+        #[cfg(feature = "query")]
+        EventType::Rpy(rpy) => Message::Op(signed_reply(rpy, attachments)?),
+        #[cfg(feature = "mailbox")]
+        EventType::Exn(exn) => Message::Op(signed_exchange(exn, attachments)?),
+        #[cfg(feature = "mailbox")]
+        EventType::MailboxQry(qry) => Message::Op(signed_management_query(qry, attachments)?),
+    };
+    Ok(msg)
+}
+
 impl TryFrom<ParsedData> for Message {
     type Error = ParseError;
 
     fn try_from(value: ParsedData) -> Result<Self, Self::Error> {
-        let msg = match value.payload.try_into()? {
-            EventType::KeyEvent(ev) => Message::Notice(signed_key_event(ev, value.attachments)?),
-            EventType::Receipt(rct) => Message::Notice(signed_receipt(rct, value.attachments)?),
-            #[cfg(feature = "query")]
-            EventType::Qry(qry) => Message::Op(signed_query(qry, value.attachments)?),
+        message(value.payload.try_into()?, value.attachments)
+    }
+}
+
+/// Decodes message straight from the body borrowed from parsed stream,
+/// without copying it first.
+impl TryFrom<ParsedRef<'_>> for Message {
+    type Error = ParseError;
+
+    fn try_from(value: ParsedRef<'_>) -> Result<Self, Self::Error> {
+        message(EventType::decode(value.format, value.body)?, value.attachments)
+    }
+}
+
+impl TryFrom<ParsedRef<'_>> for Notice {
+    type Error = ParseError;
+
+    fn try_from(value: ParsedRef<'_>) -> Result<Self, Self::Error> {
+        match Message::try_from(value)? {
+            Message::Notice(notice) => Ok(notice),
             #[cfg(feature = "query")]
-            EventType::Rpy(rpy) => Message::Op(signed_reply(rpy, value.attachments)?),
-            #[cfg(feature = "mailbox")]
-            EventType::Exn(exn) => Message::Op(signed_exchange(exn, value.attachments)?),
-            #[cfg(feature = "mailbox")]
-            EventType::MailboxQry(qry) => {
-                Message::Op(signed_management_query(qry, value.attachments)?)
-            }
-        };
-        Ok(msg)
+            _ => Err(ParseError::WrongEventType(
+                "Cannot convert SignedEventData to Notice".to_string(),
+            )),
+        }
     }
 }
 
@@ -592,6 +757,8 @@ fn signed_receipt(
 
 #[cfg(feature = "mailbox")]
 pub fn signed_exchange(exn: ExchangeMessage, attachments: Vec<Group>) -> Result<Op, ParseError> {
+    use cesrox::cesr_proof::MaterialPath;
+
     use crate::event_message::signature::get_signatures;
 
     use super::signature::Signature;
@@ -600,14 +767,20 @@ pub fn signed_exchange(exn: ExchangeMessage, attachments: Vec<Group>) -> Result<
     let att1 = atts
         .next()
         .ok_or_else(|| ParseError::AttachmentError("Missing attachment".into()))?;
-    let att2 = atts
-        .next()
-        .ok_or_else(|| ParseError::AttachmentError("Missing attachment".into()))?;
-    let (path, data_sigs, signatures): (_, _, Vec<Signature>) = match (att1, att2) {
-        (Group::PathedMaterialQuadruplet(path, sigs), anything)
-        | (anything, Group::PathedMaterialQuadruplet(path, sigs)) => {
+    let (path, data_sigs, signatures): (_, _, Vec<Signature>) = match (att1, atts.next()) {
+        (Group::PathedMaterialQuadruplet(path, sigs), Some(anything))
+        | (anything, Some(Group::PathedMaterialQuadruplet(path, sigs))) => {
             (path, sigs, get_signatures(anything)?)
         }
+        // Exchange without carried event, e.g. challenge response.
+        (Group::PathedMaterialQuadruplet(_, _), None) => {
+            return Err(ParseError::AttachmentError("Missing attachment".into()))
+        }
+        (anything, None) => (
+            MaterialPath::to_path("-a".into()),
+            vec![],
+            get_signatures(anything)?,
+        ),
         _ => return Err(ParseError::AttachmentError("Wrong attachment".into())),
     };
     let data_signatures: Result<Vec<Signature>, ParseError> =
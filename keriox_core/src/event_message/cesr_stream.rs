@@ -0,0 +1,362 @@
+use std::{
+    convert::TryFrom,
+    io::{ErrorKind, Read},
+    marker::PhantomData,
+};
+
+use cesrox::ParsedData;
+
+use super::{
+    cesr_adapter::{parse_versioned, ParseError},
+    cesr_version::{CesrVersion, GENUS_VERSION_CODE},
+    signed_event_message::Message,
+};
+
+/// Messages larger than that are rejected, unless configured otherwise.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+const CHUNK_SIZE: usize = 4096;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("Message exceeds {0} bytes")]
+    TooLarge(usize),
+}
+
+/// Splits CESR stream received in chunks into messages.
+///
+/// Message body and attachment groups are self-framing, so message is
+/// complete once its body and all count code groups that follow it in the
+/// buffer are fully received, i.e. body of the next message starts or
+/// nothing else is buffered. Message without attachments yet is complete
+/// only once the next one starts, or the stream ends. Attachment groups of
+/// message are therefore expected to be sent together. At most `max_size`
+/// bytes of incomplete message are buffered. Attachments are
+/// counted the way CESR `version` does, until genus version code changes it.
+pub struct CesrStreamParser {
+    buffer: Vec<u8>,
+    max_size: usize,
+    version: CesrVersion,
+}
+
+impl Default for CesrStreamParser {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_size: DEFAULT_MAX_MESSAGE_SIZE,
+            version: CesrVersion::V1,
+        }
+    }
+}
+
+impl CesrStreamParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn with_version(mut self, version: CesrVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// CESR version of the rest of stream.
+    pub fn version(&self) -> CesrVersion {
+        self.version
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Number of buffered bytes, not yet returned as messages.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns next complete message, if one is buffered. With `end` set,
+    /// no more data is expected, so incomplete message is an error.
+    /// Whitespace between messages is skipped.
+    pub fn next_message(&mut self, end: bool) -> Result<Option<ParsedData>, StreamError> {
+        let start = self
+            .buffer
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(self.buffer.len());
+        self.buffer.drain(..start);
+        let first = match self.buffer.first() {
+            Some(first) => *first,
+            None => return Ok(None),
+        };
+        if !starts_body(first) && !starts_genus_version(&self.buffer) {
+            return Err(ParseError::CesrError("Message body expected".to_string()).into());
+        }
+
+        let parsed = match parse_versioned(&self.buffer, self.version) {
+            Ok((rest, parsed)) => match rest.iter().find(|byte| !byte.is_ascii_whitespace()) {
+                Some(next) if starts_body(*next) => Some((self.buffer.len() - rest.len(), parsed)),
+                None if end || !parsed.1.attachments.is_empty() => {
+                    Some((self.buffer.len(), parsed))
+                }
+                Some(_) if end => {
+                    return Err(ParseError::AttachmentError(
+                        "Incomplete attachment at end of stream".to_string(),
+                    )
+                    .into())
+                }
+                // Attachment group isn't received fully yet.
+                _ => None,
+            },
+            Err(_) if end => {
+                return Err(ParseError::CesrError(
+                    "Incomplete message at end of stream".to_string(),
+                )
+                .into())
+            }
+            Err(_) => None,
+        };
+        match parsed {
+            Some((length, (version, parsed))) => {
+                self.buffer.drain(..length);
+                self.version = version;
+                Ok(Some(parsed))
+            }
+            None if self.buffer.len() > self.max_size => Err(StreamError::TooLarge(self.max_size)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Checks whether byte starts JSON, CBOR or MGPK message body, the same
+/// way `cesrox` tells bodies from attachments.
+fn starts_body(byte: u8) -> bool {
+    matches!(byte >> 5, 0b011..=0b110)
+}
+
+/// Checks whether buffer starts with genus version code, or its beginning.
+fn starts_genus_version(buffer: &[u8]) -> bool {
+    let len = buffer.len().min(GENUS_VERSION_CODE.len());
+    buffer[..len] == GENUS_VERSION_CODE.as_bytes()[..len]
+}
+
+/// Reads CESR messages from `Read`, e.g. long-lived TCP connection, as
+/// they arrive. See `CesrStreamParser` for when message is complete.
+pub struct CesrReader<R, T = Message> {
+    reader: R,
+    parser: CesrStreamParser,
+    end: bool,
+    message: PhantomData<T>,
+}
+
+impl<R: Read, T: TryFrom<ParsedData, Error = ParseError>> CesrReader<R, T> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            parser: CesrStreamParser::new(),
+            end: false,
+            message: PhantomData,
+        }
+    }
+
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.parser = self.parser.with_max_size(max_size);
+        self
+    }
+
+    pub fn with_version(mut self, version: CesrVersion) -> Self {
+        self.parser = self.parser.with_version(version);
+        self
+    }
+
+    /// Reads until next message is complete. Returns `None` once reader
+    /// is exhausted.
+    pub fn next_message(&mut self) -> Result<Option<T>, StreamError> {
+        let mut chunk = [0u8; CHUNK_SIZE];
+        loop {
+            if let Some(parsed) = self.parser.next_message(self.end)? {
+                return Ok(Some(T::try_from(parsed)?));
+            }
+            if self.end {
+                return Ok(None);
+            }
+            match self.reader.read(&mut chunk) {
+                Ok(0) => self.end = true,
+                Ok(read) => self.parser.push(&chunk[..read]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl<R: Read, T: TryFrom<ParsedData, Error = ParseError>> Iterator for CesrReader<R, T> {
+    type Item = Result<T, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_message().transpose()
+    }
+}
+
+/// Reads CESR messages from tokio `AsyncRead`, the same way `CesrReader`
+/// does.
+#[cfg(feature = "tokio")]
+pub struct AsyncCesrReader<R, T = Message> {
+    reader: R,
+    parser: CesrStreamParser,
+    end: bool,
+    message: PhantomData<T>,
+}
+
+#[cfg(feature = "tokio")]
+impl<R, T> AsyncCesrReader<R, T>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    T: TryFrom<ParsedData, Error = ParseError>,
+{
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            parser: CesrStreamParser::new(),
+            end: false,
+            message: PhantomData,
+        }
+    }
+
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.parser = self.parser.with_max_size(max_size);
+        self
+    }
+
+    pub fn with_version(mut self, version: CesrVersion) -> Self {
+        self.parser = self.parser.with_version(version);
+        self
+    }
+
+    /// Reads until next message is complete. Returns `None` once reader
+    /// is exhausted.
+    pub async fn next_message(&mut self) -> Result<Option<T>, StreamError> {
+        use tokio::io::AsyncReadExt;
+
+        let mut chunk = [0u8; CHUNK_SIZE];
+        loop {
+            if let Some(parsed) = self.parser.next_message(self.end)? {
+                return Ok(Some(T::try_from(parsed)?));
+            }
+            if self.end {
+                return Ok(None);
+            }
+            match self.reader.read(&mut chunk).await {
+                Ok(0) => self.end = true,
+                Ok(read) => self.parser.push(&chunk[..read]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use cesrox::parse_many;
+
+    use super::*;
+    use crate::event_message::signed_event_message::Notice;
+
+    const KEL: &[u8] = br#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD{"v":"KERI10JSON000160_","t":"rot","d":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"1","p":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","kt":"1","k":["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"],"nt":"1","n":["EBrEok_A-yJGpR9GH_ktdd11x3UR0cHaCg0nzAnYLgGj"],"bt":"0","br":[],"ba":[],"a":[]}-AABAADLgLBVFeCOP8t-sxOWKif-JbQ-PnOz0W7aZCuLPOUEri-OdGXjOV2d3y6-R_SsS2U3toE3TNVJ9UyO5NhBSkkO"#;
+
+    #[test]
+    fn test_chunked_stream() {
+        let expected = parse_many(KEL).unwrap().1;
+        // Split into chunks ending in the middle of body, of attachment,
+        // and right after it.
+        // Size of inception body is given by its version string, followed
+        // by group of single signature.
+        let icp_end = 0x159;
+        let rot_start = icp_end + 92;
+        let mut parser = CesrStreamParser::new();
+        let mut messages = vec![];
+        for chunk in [&KEL[..100], &KEL[100..icp_end + 10]] {
+            parser.push(chunk);
+            assert_eq!(parser.next_message(false).unwrap(), None);
+        }
+        parser.push(&KEL[icp_end + 10..rot_start]);
+        messages.push(parser.next_message(false).unwrap().unwrap());
+        parser.push(&KEL[rot_start..rot_start + 1]);
+        assert_eq!(parser.next_message(false).unwrap(), None);
+        parser.push(&KEL[rot_start + 1..]);
+        messages.push(parser.next_message(false).unwrap().unwrap());
+        assert_eq!(messages, expected);
+        assert_eq!(parser.buffered(), 0);
+        assert_eq!(parser.next_message(true).unwrap(), None);
+    }
+
+    #[test]
+    fn test_single_message() {
+        let rot_start = 0x159 + 92;
+        let expected = parse_many(&KEL[..rot_start]).unwrap().1;
+        let mut parser = CesrStreamParser::new();
+        parser.push(&KEL[..rot_start]);
+        assert_eq!(
+            parser.next_message(false).unwrap(),
+            expected.into_iter().next()
+        );
+        assert_eq!(parser.buffered(), 0);
+        assert_eq!(parser.next_message(false).unwrap(), None);
+    }
+
+    #[test]
+    fn test_reader() {
+        let stream = [KEL, b"\r\n"].concat();
+        let notices = CesrReader::<_, Notice>::new(Cursor::new(stream))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(notices.len(), 2);
+
+        // Truncated message is an error at end of stream.
+        let mut reader: CesrReader<_> = CesrReader::new(Cursor::new(&KEL[..KEL.len() - 10]));
+        assert!(matches!(
+            reader.next_message(),
+            Ok(Some(Message::Notice(_)))
+        ));
+        assert!(matches!(
+            reader.next_message(),
+            Err(StreamError::Parse(ParseError::AttachmentError(_)))
+        ));
+
+        // Buffering of incomplete message is bounded.
+        let mut parser = CesrStreamParser::new().with_max_size(200);
+        parser.push(&KEL[..300]);
+        assert!(matches!(
+            parser.next_message(false),
+            Err(StreamError::TooLarge(200))
+        ));
+
+        let mut reader: CesrReader<_> = CesrReader::new(Cursor::new(b"-AAB"));
+        assert!(matches!(reader.next_message(), Err(StreamError::Parse(_))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_reader() {
+        let (mut client, server) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            client.write_all(KEL).await.unwrap();
+        });
+        let mut reader: AsyncCesrReader<_> = AsyncCesrReader::new(server);
+        assert!(reader.next_message().await.unwrap().is_some());
+        assert!(reader.next_message().await.unwrap().is_some());
+        assert!(reader.next_message().await.unwrap().is_none());
+        writer.await.unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+//! CESR 2.0 count codes.
+//!
+//! CESR 2.0 assigns new codes to attachment groups and counts quadlets of
+//! counted material instead of its elements. Attachments of both versions
+//! are parsed into the same `Group`s. Stream is CESR 1.0 unless genus
+//! version code selects other version for the rest of it.
+
+use cesrox::{
+    cesr_proof::parsers::material_path,
+    group::Group,
+    primitives::{
+        codes::{
+            attached_signature_code::AttachedSignatureCode, basic::Basic,
+            self_addressing::SelfAddressing, self_signing::SelfSigning, serial_number::pack_sn,
+            timestamp::pack_datetime,
+        },
+        parsers::{identifier, parse_primitive, serial_number_parser, timestamp_parser},
+        CesrPrimitive, IdentifierSignaturesCouple, IndexedSignature, TransferableQuadruple,
+    },
+    ParsedData,
+};
+use nom::{
+    bytes::complete::take,
+    error::{make_error, ErrorKind},
+    sequence::tuple,
+    IResult,
+};
+
+/// Genus version code of KERI/ACDC protocol stack, followed by major (one
+/// character) and minor (two characters) CESR version.
+pub const GENUS_VERSION_CODE: &str = "-_AAA";
+
+const B64_DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CesrVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl CesrVersion {
+    /// Genus version code selecting this version for the rest of stream.
+    pub fn genus_version(&self) -> String {
+        let version = match self {
+            CesrVersion::V1 => "BAA",
+            CesrVersion::V2 => "CAA",
+        };
+        [GENUS_VERSION_CODE, version].concat()
+    }
+
+    /// Encodes message with attachments counted the way this version does.
+    pub fn encode(&self, data: &ParsedData) -> Vec<u8> {
+        let attachments = data
+            .attachments
+            .iter()
+            .map(|group| match self {
+                CesrVersion::V1 => group.to_cesr_str(),
+                CesrVersion::V2 => group_to_cesr(group),
+            })
+            .collect::<String>();
+        [data.payload.to_vec(), attachments.into_bytes()].concat()
+    }
+}
+
+/// Parses genus version code. Minor version is ignored, as it doesn't
+/// change count codes.
+pub fn genus_version(stream: &[u8]) -> IResult<&[u8], CesrVersion> {
+    let (rest, code) = take(GENUS_VERSION_CODE.len() + 3)(stream)?;
+    if !code.starts_with(GENUS_VERSION_CODE.as_bytes()) {
+        return Err(nom::Err::Error(make_error(stream, ErrorKind::Tag)));
+    }
+    match code[GENUS_VERSION_CODE.len()] {
+        b'B' => Ok((rest, CesrVersion::V1)),
+        b'C' => Ok((rest, CesrVersion::V2)),
+        _ => Err(nom::Err::Error(make_error(stream, ErrorKind::Verify))),
+    }
+}
+
+/// Parses CESR 2.0 attachment group.
+pub fn parse_v2_group(stream: &[u8]) -> IResult<&[u8], Group> {
+    if !stream.starts_with(b"-") || stream.starts_with(GENUS_VERSION_CODE.as_bytes()) {
+        return Err(nom::Err::Error(make_error(stream, ErrorKind::IsNot)));
+    }
+    let (rest, (code, quadlets)) = counter(stream)?;
+    let (rest, material) = take(quadlets * 4)(rest)?;
+    let group = match code {
+        b'K' => Group::IndexedControllerSignatures(all(
+            material,
+            parse_primitive::<AttachedSignatureCode>,
+        )?),
+        b'L' => Group::IndexedWitnessSignatures(all(
+            material,
+            parse_primitive::<AttachedSignatureCode>,
+        )?),
+        b'M' => Group::NontransReceiptCouples(all(
+            material,
+            tuple((parse_primitive::<Basic>, parse_primitive::<SelfSigning>)),
+        )?),
+        b'O' => Group::FirstSeenReplyCouples(all(
+            material,
+            tuple((serial_number_parser, timestamp_parser)),
+        )?),
+        b'P' => Group::TransIndexedSigGroups(all(material, transferable_quadruple)?),
+        b'Q' => Group::LastEstSignaturesGroups(all(material, identifier_signatures_couple)?),
+        b'R' => Group::SourceSealCouples(all(
+            material,
+            tuple((serial_number_parser, parse_primitive::<SelfAddressing>)),
+        )?),
+        b'T' => {
+            let (attachments, path) = material_path(material)?;
+            Group::PathedMaterialQuadruplet(path, all(attachments, parse_v2_group)?)
+        }
+        // Generic and attachment groups.
+        b'A' | b'C' => Group::Frame(all(material, parse_v2_group)?),
+        _ => return Err(nom::Err::Error(make_error(stream, ErrorKind::IsNot))),
+    };
+    Ok((rest, group))
+}
+
+/// Encodes attachment group with CESR 2.0 count codes.
+pub fn group_to_cesr(group: &Group) -> String {
+    let (code, material): (u8, String) = match group {
+        Group::IndexedControllerSignatures(sigs) => {
+            (b'K', sigs.iter().map(|sig| sig.to_str()).collect())
+        }
+        Group::IndexedWitnessSignatures(sigs) => {
+            (b'L', sigs.iter().map(|sig| sig.to_str()).collect())
+        }
+        Group::NontransReceiptCouples(couples) => (
+            b'M',
+            couples
+                .iter()
+                .map(|(key, sig)| [key.to_str(), sig.to_str()].concat())
+                .collect(),
+        ),
+        Group::FirstSeenReplyCouples(couples) => (
+            b'O',
+            couples
+                .iter()
+                .map(|(sn, dt)| [pack_sn(*sn), pack_datetime(dt)].concat())
+                .collect(),
+        ),
+        Group::TransIndexedSigGroups(groups) => (
+            b'P',
+            groups
+                .iter()
+                .map(|(id, sn, digest, sigs)| {
+                    [
+                        id.to_str(),
+                        pack_sn(*sn),
+                        digest.to_str(),
+                        group_to_cesr(&Group::IndexedControllerSignatures(sigs.clone())),
+                    ]
+                    .concat()
+                })
+                .collect(),
+        ),
+        Group::LastEstSignaturesGroups(couples) => (
+            b'Q',
+            couples
+                .iter()
+                .map(|(id, sigs)| {
+                    [
+                        id.to_str(),
+                        group_to_cesr(&Group::IndexedControllerSignatures(sigs.clone())),
+                    ]
+                    .concat()
+                })
+                .collect(),
+        ),
+        Group::SourceSealCouples(couples) => (
+            b'R',
+            couples
+                .iter()
+                .map(|(sn, digest)| [pack_sn(*sn), digest.to_str()].concat())
+                .collect(),
+        ),
+        Group::PathedMaterialQuadruplet(path, groups) => (
+            b'T',
+            [path.to_cesr(), groups.iter().map(group_to_cesr).collect()].concat(),
+        ),
+        Group::Frame(groups) => (b'C', groups.iter().map(group_to_cesr).collect()),
+    };
+    [counter_code(code, material.len() / 4), material].concat()
+}
+
+/// Small count codes have two character code and count. Big ones, starting
+/// with `--`, have three character code and five character count.
+pub(crate) fn counter(stream: &[u8]) -> IResult<&[u8], (u8, usize)> {
+    let (code_len, count_len): (usize, usize) = if stream.starts_with(b"--") {
+        (3, 5)
+    } else {
+        (2, 2)
+    };
+    let (rest, code) = take(code_len)(stream)?;
+    let (rest, count) = take(count_len)(rest)?;
+    let count = count
+        .iter()
+        .try_fold(0usize, |acc, digit| {
+            B64_DIGITS
+                .iter()
+                .position(|d| d == digit)
+                .map(|value| acc * 64 + value)
+        })
+        .ok_or_else(|| nom::Err::Error(make_error(stream, ErrorKind::Digit)))?;
+    Ok((rest, (code[code_len - 1], count)))
+}
+
+fn counter_code(code: u8, quadlets: usize) -> String {
+    let (prefix, count_len) = if quadlets < 64 * 64 {
+        ("-", 2)
+    } else {
+        ("--", 5)
+    };
+    let count = (0..count_len)
+        .rev()
+        .map(|i| B64_DIGITS[(quadlets >> (6 * i)) & 63] as char)
+        .collect::<String>();
+    format!("{}{}{}", prefix, code as char, count)
+}
+
+fn indexed_controller_signatures(stream: &[u8]) -> IResult<&[u8], Vec<IndexedSignature>> {
+    // Checked first, so that nested groups of other kinds aren't parsed.
+    if !stream.starts_with(b"-K") && !stream.starts_with(b"--K") {
+        return Err(nom::Err::Error(make_error(stream, ErrorKind::IsNot)));
+    }
+    match parse_v2_group(stream)? {
+        (rest, Group::IndexedControllerSignatures(sigs)) => Ok((rest, sigs)),
+        _ => Err(nom::Err::Error(make_error(stream, ErrorKind::IsNot))),
+    }
+}
+
+fn transferable_quadruple(stream: &[u8]) -> IResult<&[u8], TransferableQuadruple> {
+    tuple((
+        identifier,
+        serial_number_parser,
+        parse_primitive::<SelfAddressing>,
+        indexed_controller_signatures,
+    ))(stream)
+}
+
+fn identifier_signatures_couple(stream: &[u8]) -> IResult<&[u8], IdentifierSignaturesCouple> {
+    tuple((identifier, indexed_controller_signatures))(stream)
+}
+
+/// Counted material must consist of whole elements. Elements are parsed
+/// until the counted quadlets are consumed, never from empty input, which
+/// cesrox primitive parsers can't handle.
+fn all<'a, O>(
+    material: &'a [u8],
+    mut parser: impl FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+) -> Result<Vec<O>, nom::Err<nom::error::Error<&'a [u8]>>> {
+    let mut elements = vec![];
+    let mut rest = material;
+    while !rest.is_empty() {
+        let (remaining, element) = parser(rest)?;
+        if remaining.len() == rest.len() {
+            return Err(nom::Err::Error(make_error(rest, ErrorKind::Eof)));
+        }
+        elements.push(element);
+        rest = remaining;
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use cesrox::parse_many as parse_v1;
+
+    use super::*;
+    use crate::event_message::{
+        cesr_adapter::{parse, parse_many, parse_versioned},
+        cesr_stream::CesrStreamParser,
+    };
+
+    const KEL: &[u8] = br#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD{"v":"KERI10JSON000160_","t":"rot","d":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"1","p":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","kt":"1","k":["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"],"nt":"1","n":["EBrEok_A-yJGpR9GH_ktdd11x3UR0cHaCg0nzAnYLgGj"],"bt":"0","br":[],"ba":[],"a":[]}-AABAADLgLBVFeCOP8t-sxOWKif-JbQ-PnOz0W7aZCuLPOUEri-OdGXjOV2d3y6-R_SsS2U3toE3TNVJ9UyO5NhBSkkO"#;
+
+    #[test]
+    fn test_count_codes() {
+        assert_eq!(counter_code(b'K', 22), "-KAW");
+        assert_eq!(counter_code(b'K', 4096), "--KAABAA");
+        assert_eq!(counter(b"-KAW").unwrap().1, (b'K', 22));
+        assert_eq!(counter(b"--KAABAA").unwrap().1, (b'K', 4096));
+        assert_eq!(CesrVersion::V2.genus_version(), "-_AAACAA");
+        assert_eq!(genus_version(b"-_AAACAA").unwrap().1, CesrVersion::V2);
+        assert!(genus_version(b"-_AAADAA").is_err());
+    }
+
+    #[test]
+    fn test_v2_stream() {
+        let (_, expected) = parse_v1(KEL).unwrap();
+        let v2_messages = expected
+            .iter()
+            .map(|data| CesrVersion::V2.encode(data))
+            .collect::<Vec<_>>();
+        // Single signature of 22 quadlets.
+        assert!(v2_messages[0].ends_with(&KEL[0x159 + 4..0x159 + 92]));
+        assert_eq!(&v2_messages[0][0x159..0x159 + 4], b"-KAW");
+
+        // Version code is required to read CESR 2.0 attachments.
+        let (rest, _) = parse(&v2_messages[0]).unwrap();
+        assert_eq!(rest.len(), 92);
+
+        let stream = [
+            CesrVersion::V2.genus_version().into_bytes(),
+            v2_messages.concat(),
+        ]
+        .concat();
+        let (rest, (version, parsed)) = parse_versioned(&stream, CesrVersion::V1).unwrap();
+        assert_eq!(version, CesrVersion::V2);
+        assert_eq!(parsed, expected[0]);
+        let (_, parsed) = parse_versioned(rest, version).unwrap();
+        assert_eq!(parsed.1, expected[1]);
+        assert_eq!(parse_many(&stream).unwrap().1, expected);
+
+        // Version may change within stream.
+        let mixed = [
+            v2_messages[0].clone(),
+            b"-_AAABAA".to_vec(),
+            CesrVersion::V1.encode(&expected[1]),
+        ]
+        .concat();
+        let (_, parsed) = parse_versioned(&mixed, CesrVersion::V2).unwrap();
+        assert_eq!(parsed.0, CesrVersion::V1);
+        let mut parser = CesrStreamParser::new().with_version(CesrVersion::V2);
+        let mut messages = vec![];
+        for chunk in mixed.chunks(5) {
+            parser.push(chunk);
+            messages.extend(parser.next_message(false).unwrap());
+        }
+        messages.extend(parser.next_message(true).unwrap());
+        assert_eq!(messages, expected);
+    }
+}
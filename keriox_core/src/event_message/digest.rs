@@ -0,0 +1,79 @@
+//! Digest algorithm used when creating events, seals and SAIDs.
+//!
+//! Builders take the algorithm with `with_derivation` and fall back to
+//! `DEFAULT_DERIVATION` when it isn't given. Verification doesn't depend
+//! on it: received digests are checked with the algorithm of their own
+//! derivation code.
+
+use said::derivation::HashFunctionCode;
+
+/// Digest algorithm of created messages, unless other one is chosen.
+pub const DEFAULT_DERIVATION: HashFunctionCode = HashFunctionCode::Blake3_256;
+
+#[cfg(test)]
+mod tests {
+    use said::derivation::HashFunction;
+
+    use super::*;
+    use crate::{
+        error::Error,
+        event_message::key_event_builder::{InceptionBuilder, InteractionBuilder, RotationBuilder},
+        prefix::BasicPrefix,
+        signer::Signer,
+        state::IdentifierState,
+    };
+
+    #[test]
+    fn test_derivation_codes() -> Result<(), Error> {
+        // Keyed Blake2 variants are left out, `said` panics deriving them.
+        let codes = [
+            HashFunctionCode::Blake3_256,
+            HashFunctionCode::Blake3_512,
+            HashFunctionCode::Blake2B512,
+            HashFunctionCode::SHA3_256,
+            HashFunctionCode::SHA3_512,
+            HashFunctionCode::SHA2_256,
+            HashFunctionCode::SHA2_512,
+        ];
+        let keys: Vec<_> = (0..3)
+            .map(|_| BasicPrefix::Ed25519(Signer::new().public_key()))
+            .collect();
+        for code in codes {
+            let expected = HashFunction::from(code.clone());
+            let icp = InceptionBuilder::new(vec![keys[0].clone()])
+                .with_next_keys(vec![keys[1].clone()])
+                .with_derivation(code.clone())
+                .build()?;
+            assert_eq!(icp.digest()?.derivation, expected);
+            // Inception digest is also its prefix, so both are replaced
+            // with placeholder when it is computed.
+            assert!(icp.digest()?.verify_binding(&icp.to_derivation_data()?));
+            let state = IdentifierState::default().apply(&icp)?;
+
+            let rot = RotationBuilder::new(&state, vec![keys[1].clone()])
+                .with_next_keys(vec![keys[2].clone()])
+                .with_derivation(code.clone())
+                .build()?;
+            rot.check_digest()?;
+            let state = state.apply(&rot)?;
+
+            // Events in one KEL may use different algorithms.
+            let ixn = InteractionBuilder::new(&state)
+                .with_derivation(HashFunctionCode::Blake3_256)
+                .build()?;
+            ixn.check_digest()?;
+            state.apply(&ixn)?;
+
+            #[cfg(feature = "saidify")]
+            {
+                use crate::event_message::saidify::Saidify;
+
+                let saidify = Saidify::new().with_derivation(code);
+                let (said, bytes) = saidify.saidify(&serde_json::json!({"d": ""}))?;
+                assert_eq!(said.derivation, expected);
+                assert!(saidify.verify(&bytes)?);
+            }
+        }
+        Ok(())
+    }
+}
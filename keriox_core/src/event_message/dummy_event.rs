@@ -3,27 +3,29 @@ use crate::{
     event::event_data::{DelegatedInceptionEvent, EventData, InceptionEvent},
 };
 
-use super::{EventTypeTag, Typeable};
+use super::{encoding::encode, EventTypeTag, Typeable};
 use cesrox::primitives::codes::self_addressing::SelfAddressing;
 use said::version::{format::SerializationFormats, SerializationInfo};
-use said::{derivation::HashFunctionCode, sad::SAD, SelfAddressingIdentifier};
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    sad::{DerivationCode, SAD},
+    SelfAddressingIdentifier,
+};
 use serde::Serialize;
 use serde_hex::{Compact, SerHex};
 
 /// Dummy Inception Event
 ///
 /// Used only to encapsulate the prefix derivation process for inception and delegated inception
-#[derive(Serialize, Debug, Clone, SAD)]
+#[derive(Serialize, Debug, Clone)]
 pub(crate) struct DummyInceptionEvent {
     #[serde(rename = "v")]
     pub serialization_info: SerializationInfo,
     #[serde(rename = "t")]
     event_type: EventTypeTag,
     #[serde(rename = "d")]
-    #[said]
     digest: Option<SelfAddressingIdentifier>,
     #[serde(rename = "i")]
-    #[said]
     pub prefix: Option<SelfAddressingIdentifier>,
     #[serde(rename = "s", with = "SerHex::<Compact>")]
     sn: u8,
@@ -68,3 +70,47 @@ impl DummyInceptionEvent {
         Ok(tmp_icp)
     }
 }
+
+/// Inception event with digest and prefix replaced by placeholder, which is
+/// encoded to compute them.
+#[derive(Serialize)]
+struct DerivationInceptionEvent<'a> {
+    #[serde(rename = "v")]
+    serialization_info: &'a SerializationInfo,
+    #[serde(rename = "t")]
+    event_type: &'a EventTypeTag,
+    #[serde(rename = "d")]
+    digest: &'a str,
+    #[serde(rename = "i")]
+    prefix: &'a str,
+    #[serde(rename = "s", with = "SerHex::<Compact>")]
+    sn: u8,
+    #[serde(flatten)]
+    data: &'a EventData,
+}
+
+impl SAD for DummyInceptionEvent {
+    fn compute_digest(&mut self, derivation: &HashFunctionCode, format: &SerializationFormats) {
+        let said: SelfAddressingIdentifier = HashFunction::from(derivation.clone())
+            .derive(&self.derivation_data(derivation, format));
+        self.digest = Some(said.clone());
+        self.prefix = Some(said);
+    }
+
+    fn derivation_data(
+        &self,
+        derivation: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Vec<u8> {
+        let placeholder = "#".repeat(derivation.full_size());
+        let tmp_event = DerivationInceptionEvent {
+            serialization_info: &self.serialization_info,
+            event_type: &self.event_type,
+            digest: &placeholder,
+            prefix: &placeholder,
+            sn: self.sn,
+            data: &self.data,
+        };
+        encode(format, &tmp_event).unwrap()
+    }
+}
@@ -0,0 +1,52 @@
+use said::version::format::SerializationFormats;
+use serde::{Deserialize, Serialize};
+
+use super::cesr_adapter::ParseError;
+use crate::error::Error;
+
+/// Encodes message body in given serialization format.
+///
+/// Unlike `SerializationFormats::encode`, output matches other KERI
+/// implementations, so digests of their events can be verified:
+/// - structs are encoded as MGPK maps with field names, not arrays,
+/// - CBOR maps have definite length. `serde_cbor` writes flattened event
+///   data as indefinite length map, so body is converted to JSON value,
+///   which keeps field order, first.
+///
+/// CBOR and MGPK need `cbor` and `mgpk` features.
+pub fn encode<T: Serialize>(format: &SerializationFormats, value: &T) -> Result<Vec<u8>, Error> {
+    match format {
+        SerializationFormats::JSON => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        #[cfg(feature = "cbor")]
+        SerializationFormats::CBOR => serde_json::to_value(value)
+            .map_err(|e| e.to_string())
+            .and_then(|value| serde_cbor::to_vec(&value).map_err(|e| e.to_string())),
+        #[cfg(feature = "mgpk")]
+        SerializationFormats::MGPK => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        #[allow(unreachable_patterns)]
+        format => Err(unsupported(format)),
+    }
+    .map_err(Error::SerializationError)
+}
+
+/// Decodes message body encoded in given serialization format.
+pub fn decode<'de, T: Deserialize<'de>>(
+    format: &SerializationFormats,
+    bytes: &'de [u8],
+) -> Result<T, ParseError> {
+    match format {
+        SerializationFormats::JSON => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        #[cfg(feature = "cbor")]
+        SerializationFormats::CBOR => serde_cbor::from_slice(bytes).map_err(|e| e.to_string()),
+        #[cfg(feature = "mgpk")]
+        SerializationFormats::MGPK => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        #[allow(unreachable_patterns)]
+        format => Err(unsupported(format)),
+    }
+    .map_err(ParseError::DeserializeError)
+}
+
+#[allow(dead_code)]
+pub(crate) fn unsupported(format: &SerializationFormats) -> String {
+    format!("{} serialization is not enabled", format.to_str())
+}
@@ -29,7 +29,7 @@ use said::{
     SelfAddressingIdentifier,
 };
 
-use super::{msg::KeriEvent, EventTypeTag};
+use super::{digest::DEFAULT_DERIVATION, msg::KeriEvent, EventTypeTag};
 
 pub struct EventMsgBuilder {
     event_type: EventTypeTag,
@@ -58,7 +58,7 @@ impl EventMsgBuilder {
         let nkp = SigningKey::generate(&mut rng);
         let pk = PublicKey::new(kp.verifying_key().to_bytes().to_vec());
         let npk = PublicKey::new(nkp.verifying_key().to_bytes().to_vec());
-        let hash_function: HashFunction = HashFunctionCode::Blake3_256.into();
+        let hash_function: HashFunction = DEFAULT_DERIVATION.into();
         let basic_pref = BasicPrefix::Ed25519(pk);
         EventMsgBuilder {
             event_type,
@@ -167,6 +167,17 @@ impl EventMsgBuilder {
         }
     }
 
+    pub fn with_format(self, format: SerializationFormats) -> Self {
+        EventMsgBuilder { format, ..self }
+    }
+
+    pub fn with_derivation(self, derivation: HashFunctionCode) -> Self {
+        EventMsgBuilder {
+            derivation: derivation.into(),
+            ..self
+        }
+    }
+
     pub fn build(self) -> Result<KeriEvent<KeyEvent>, Error> {
         let next_key_hash = if let Some(hashes) = self.next_keys_hashes {
             NextKeysData::new(self.next_key_threshold, hashes)
@@ -247,7 +258,11 @@ impl EventMsgBuilder {
                 let rotation_data = RotationEvent::new(
                     self.prev_event,
                     key_config,
-                    RotationWitnessConfig::default(),
+                    RotationWitnessConfig {
+                        tally: self.witness_threshold,
+                        prune: self.witness_to_remove,
+                        graft: self.witness_to_add,
+                    },
                     self.data,
                 );
                 KeyEvent::new(prefix, self.sn, EventData::Drt(rotation_data))
@@ -0,0 +1,250 @@
+//! Access to fields of encoded messages without decoding them.
+//!
+//! Decoding `KeriEvent` allocates every prefix, digest and key of the
+//! event. `EventRef` decodes only fields used to inspect messages, e.g. to
+//! route or deduplicate them, and borrows them from the message body where
+//! the encoding allows it: CBOR and MGPK strings, and JSON strings without
+//! escapes. Body is decoded into `KeriEvent` only when needed, with
+//! `EventRef::to_event`.
+//!
+//! `actor::parse_new_notices` uses it to drop key events already accepted
+//! into KEL before they are decoded and validated, and decodes the rest
+//! straight from the stream.
+
+use std::borrow::Cow;
+
+use cesrox::payload::Payload;
+use said::version::{format::SerializationFormats, SerializationInfo};
+use serde::{Deserialize, Deserializer};
+
+use super::{
+    cesr_adapter::ParseError,
+    encoding::decode,
+    msg::KeriEvent,
+    protocol_version::{version_info, ProtocolVersion},
+};
+use crate::event::KeyEvent;
+
+/// Fields of encoded message, borrowed from its body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventRef<'a> {
+    format: SerializationFormats,
+    body: &'a [u8],
+    fields: Fields<'a>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct Fields<'a> {
+    #[serde(rename = "t", borrow)]
+    event_type: Cow<'a, str>,
+    #[serde(rename = "d", borrow)]
+    digest: Cow<'a, str>,
+    #[serde(rename = "i", borrow, default, deserialize_with = "optional")]
+    prefix: Option<Cow<'a, str>>,
+    #[serde(rename = "s", borrow, default, deserialize_with = "optional")]
+    sn: Option<Cow<'a, str>>,
+    #[serde(rename = "p", borrow, default, deserialize_with = "optional")]
+    previous: Option<Cow<'a, str>>,
+    #[serde(rename = "di", borrow, default, deserialize_with = "optional")]
+    delegator: Option<Cow<'a, str>>,
+    #[serde(rename = "k", borrow, default, deserialize_with = "list")]
+    keys: Vec<Cow<'a, str>>,
+    #[serde(rename = "n", borrow, default, deserialize_with = "list")]
+    next_key_digests: Vec<Cow<'a, str>>,
+    #[serde(rename = "b", borrow, default, deserialize_with = "list")]
+    witnesses: Vec<Cow<'a, str>>,
+    #[serde(rename = "br", borrow, default, deserialize_with = "list")]
+    witnesses_removed: Vec<Cow<'a, str>>,
+    #[serde(rename = "ba", borrow, default, deserialize_with = "list")]
+    witnesses_added: Vec<Cow<'a, str>>,
+}
+
+/// Serde borrows into `Cow` fields, but not into ones nested in `Option`
+/// or `Vec`, so they are deserialized through this wrapper.
+#[derive(Deserialize)]
+struct CowStr<'a>(#[serde(borrow)] Cow<'a, str>);
+
+fn optional<'de: 'a, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'a, str>>, D::Error> {
+    Ok(Option::<CowStr>::deserialize(deserializer)?.map(|value| value.0))
+}
+
+fn list<'de: 'a, 'a, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Cow<'a, str>>, D::Error> {
+    Ok(Vec::<CowStr>::deserialize(deserializer)?
+        .into_iter()
+        .map(|value| value.0)
+        .collect())
+}
+
+impl<'a> EventRef<'a> {
+    pub fn parse(format: SerializationFormats, body: &'a [u8]) -> Result<Self, ParseError> {
+        Ok(Self {
+            format,
+            body,
+            fields: decode(&format, body)?,
+        })
+    }
+
+    pub fn from_payload(payload: &'a Payload) -> Result<Self, ParseError> {
+        match payload {
+            Payload::JSON(body) => Self::parse(SerializationFormats::JSON, body),
+            Payload::CBOR(body) => Self::parse(SerializationFormats::CBOR, body),
+            Payload::MGPK(body) => Self::parse(SerializationFormats::MGPK, body),
+        }
+    }
+
+    pub fn format(&self) -> SerializationFormats {
+        self.format
+    }
+
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    pub fn serialization_info(&self) -> Result<SerializationInfo, ParseError> {
+        version_info(&self.format, self.body)
+            .map_err(|e| ParseError::DeserializeError(e.to_string()))
+    }
+
+    pub fn protocol_version(&self) -> Result<ProtocolVersion, ParseError> {
+        Ok((&self.serialization_info()?).into())
+    }
+
+    pub fn event_type(&self) -> &str {
+        &self.fields.event_type
+    }
+
+    /// Whether message is a key event, so its prefix, sn and digest
+    /// identify event of KEL.
+    pub fn is_key_event(&self) -> bool {
+        matches!(self.event_type(), "icp" | "rot" | "ixn" | "dip" | "drt")
+    }
+
+    pub fn digest(&self) -> &str {
+        &self.fields.digest
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.fields.prefix.as_deref()
+    }
+
+    /// Sequence number, hex encoded in message body.
+    pub fn sn(&self) -> Result<Option<u64>, ParseError> {
+        self.fields
+            .sn
+            .as_deref()
+            .map(|sn| {
+                u64::from_str_radix(sn, 16).map_err(|e| ParseError::DeserializeError(e.to_string()))
+            })
+            .transpose()
+    }
+
+    pub fn previous_digest(&self) -> Option<&str> {
+        self.fields.previous.as_deref()
+    }
+
+    pub fn delegator(&self) -> Option<&str> {
+        self.fields.delegator.as_deref()
+    }
+
+    pub fn keys(&self) -> &[Cow<'a, str>] {
+        &self.fields.keys
+    }
+
+    pub fn next_key_digests(&self) -> &[Cow<'a, str>] {
+        &self.fields.next_key_digests
+    }
+
+    pub fn witnesses(&self) -> &[Cow<'a, str>] {
+        &self.fields.witnesses
+    }
+
+    pub fn witnesses_removed(&self) -> &[Cow<'a, str>] {
+        &self.fields.witnesses_removed
+    }
+
+    pub fn witnesses_added(&self) -> &[Cow<'a, str>] {
+        &self.fields.witnesses_added
+    }
+
+    /// Decodes the whole body as key event.
+    pub fn to_event(&self) -> Result<KeriEvent<KeyEvent>, ParseError> {
+        decode(&self.format, self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_message::strict_parser::StrictParser;
+
+    const KEL: &[u8] = br#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD{"v":"KERI10JSON000160_","t":"rot","d":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"1","p":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","kt":"1","k":["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"],"nt":"1","n":["EBrEok_A-yJGpR9GH_ktdd11x3UR0cHaCg0nzAnYLgGj"],"bt":"0","br":[],"ba":[],"a":[]}-AABAADLgLBVFeCOP8t-sxOWKif-JbQ-PnOz0W7aZCuLPOUEri-OdGXjOV2d3y6-R_SsS2U3toE3TNVJ9UyO5NhBSkkO"#;
+
+    /// Checks that string is borrowed from `body`.
+    macro_rules! assert_borrowed {
+        ($value:expr, $body:expr) => {
+            assert!(matches!(
+                $value,
+                &Cow::Borrowed(value) if $body.as_ptr_range().contains(&value.as_ptr())
+            ))
+        };
+    }
+
+    #[test]
+    fn test_event_ref() -> Result<(), ParseError> {
+        let messages = StrictParser::new().parse_borrowed(KEL).unwrap();
+        let icp = messages[0].event()?;
+        assert_eq!(icp.event_type(), "icp");
+        assert_eq!(icp.digest(), "EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL");
+        assert_eq!(icp.prefix(), Some(icp.digest()));
+        assert_eq!(icp.sn()?, Some(0));
+        assert_eq!(icp.previous_digest(), None);
+        assert_eq!(icp.keys(), ["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"]);
+        assert_eq!(
+            icp.witnesses(),
+            ["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"]
+        );
+        assert_eq!(icp.protocol_version()?, ProtocolVersion::V1_0);
+        assert_borrowed!(&icp.fields.digest, KEL);
+        assert_borrowed!(&icp.fields.keys[0], KEL);
+        assert_borrowed!(icp.fields.prefix.as_ref().unwrap(), KEL);
+
+        let rot = messages[1].event()?;
+        assert_eq!(rot.event_type(), "rot");
+        assert_eq!(rot.sn()?, Some(1));
+        assert_eq!(rot.previous_digest(), Some(icp.digest()));
+        assert!(rot.witnesses_added().is_empty());
+        assert_eq!(rot.to_event()?.digest().unwrap().to_string(), rot.digest());
+
+        // Escaped strings can't be borrowed from JSON.
+        let body = String::from_utf8(messages[0].body.to_vec())
+            .unwrap()
+            .replace(r#""t":"icp""#, r#""t":"\u0069cp""#);
+        let escaped = EventRef::parse(SerializationFormats::JSON, body.as_bytes())?;
+        assert_eq!(escaped.event_type(), "icp");
+        assert!(matches!(escaped.fields.event_type, Cow::Owned(_)));
+        assert_borrowed!(&escaped.fields.digest, body.as_bytes());
+        Ok(())
+    }
+
+    #[cfg(all(feature = "cbor", feature = "mgpk"))]
+    #[test]
+    fn test_event_ref_formats() -> Result<(), ParseError> {
+        use crate::event_message::encoding::encode;
+
+        let messages = StrictParser::new().parse_borrowed(KEL).unwrap();
+        let event = messages[1].event()?.to_event()?;
+        for format in [SerializationFormats::CBOR, SerializationFormats::MGPK] {
+            let body = encode(&format, &event).unwrap();
+            let rot = EventRef::parse(format, &body)?;
+            assert_eq!(rot.event_type(), "rot");
+            assert_eq!(rot.sn()?, Some(1));
+            assert_eq!(rot.keys(), ["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"]);
+            assert_borrowed!(&rot.fields.digest, &body);
+            assert_borrowed!(&rot.fields.keys[0], &body);
+            assert_eq!(rot.to_event()?, event);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,850 @@
+//! Typed builders of key events.
+//!
+//! Unlike `EventMsgBuilder`, which fills whatever isn't set with defaults
+//! (random keys included), each builder here takes what its event can't do
+//! without up front and checks the rest when the event is built: thresholds
+//! against number of keys, witness lists against witness threshold and
+//! rotated keys against prior next keys commitment. So event that would be
+//! rejected by validators isn't signed in the first place.
+
+use cesrox::primitives::CesrPrimitive;
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    version::format::SerializationFormats,
+    SelfAddressingIdentifier,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{digest::DEFAULT_DERIVATION, msg::KeriEvent};
+use crate::{
+    error::Error,
+    event::{
+        event_data::{
+            delegated::DelegatedInceptionEvent, inception::InceptionEvent,
+            interaction::InteractionEvent, rotation::RotationEvent, EventData,
+        },
+        sections::{
+            key_config::NextKeysData,
+            seal::Seal,
+            threshold::{SignatureThreshold, WeightedThreshold},
+            InceptionWitnessConfig, KeyConfig, RotationWitnessConfig,
+        },
+        KeyEvent,
+    },
+    prefix::{BasicPrefix, IdentifierPrefix},
+    state::IdentifierState,
+};
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EventBuilderError {
+    #[error("No signing keys provided")]
+    NoKeys,
+
+    #[error("Key threshold can't be satisfied by {keys} keys")]
+    ImproperThreshold { keys: usize },
+
+    #[error("Next key threshold can't be satisfied by {keys} next keys")]
+    ImproperNextThreshold { keys: usize },
+
+    #[error("Both next keys and next key digests provided")]
+    ConflictingNextKeys,
+
+    #[error("Duplicated key {}", .0.to_str())]
+    DuplicateKey(BasicPrefix),
+
+    #[error("Duplicated next key digest {0}")]
+    DuplicateNextKey(SelfAddressingIdentifier),
+
+    #[error("Duplicated witness {}", .0.to_str())]
+    DuplicateWitness(BasicPrefix),
+
+    #[error("Witness threshold {threshold} improper for {witnesses} witnesses")]
+    ImproperWitnessThreshold { threshold: u64, witnesses: usize },
+
+    #[error("Witness {} to remove is not current witness", .0.to_str())]
+    UnknownWitness(BasicPrefix),
+
+    #[error("Witness {} to add is already current witness", .0.to_str())]
+    AlreadyWitness(BasicPrefix),
+
+    #[error("Identifier is not incepted")]
+    NotIncepted,
+
+    #[error("Identifier is abandoned")]
+    Abandoned,
+
+    #[error("Keys don't satisfy prior next key threshold")]
+    UnsatisfiedNextThreshold,
+}
+
+/// Current keys and next keys commitment of establishment event.
+struct KeySetup {
+    keys: Vec<BasicPrefix>,
+    threshold: Option<SignatureThreshold>,
+    next_keys: Option<Vec<BasicPrefix>>,
+    next_key_digests: Option<Vec<SelfAddressingIdentifier>>,
+    next_threshold: Option<SignatureThreshold>,
+}
+
+impl KeySetup {
+    fn new(keys: Vec<BasicPrefix>) -> Self {
+        Self {
+            keys,
+            threshold: None,
+            next_keys: None,
+            next_key_digests: None,
+            next_threshold: None,
+        }
+    }
+
+    fn key_config(self, derivation: &HashFunction) -> Result<KeyConfig, EventBuilderError> {
+        if self.keys.is_empty() {
+            return Err(EventBuilderError::NoKeys);
+        }
+        if let Some(key) = find_duplicate(&self.keys) {
+            return Err(EventBuilderError::DuplicateKey(key.clone()));
+        }
+        let threshold = self
+            .threshold
+            .unwrap_or_else(|| default_threshold(self.keys.len()));
+        if !is_satisfiable(&threshold, self.keys.len()) {
+            return Err(EventBuilderError::ImproperThreshold {
+                keys: self.keys.len(),
+            });
+        }
+
+        let next_key_digests = match (self.next_keys, self.next_key_digests) {
+            (Some(_), Some(_)) => return Err(EventBuilderError::ConflictingNextKeys),
+            (Some(next_keys), None) => next_keys
+                .iter()
+                .map(|key| derivation.derive(key.to_str().as_bytes()))
+                .collect(),
+            (None, Some(digests)) => digests,
+            (None, None) => vec![],
+        };
+        if let Some(digest) = find_duplicate(&next_key_digests) {
+            return Err(EventBuilderError::DuplicateNextKey(digest.clone()));
+        }
+        let next_threshold = self
+            .next_threshold
+            .unwrap_or_else(|| default_threshold(next_key_digests.len()));
+        if !is_satisfiable(&next_threshold, next_key_digests.len()) {
+            return Err(EventBuilderError::ImproperNextThreshold {
+                keys: next_key_digests.len(),
+            });
+        }
+
+        Ok(KeyConfig::new(
+            self.keys,
+            NextKeysData::new(next_threshold, next_key_digests),
+            Some(threshold),
+        ))
+    }
+}
+
+/// Majority of keys, as in keripy.
+fn default_threshold(keys: usize) -> SignatureThreshold {
+    SignatureThreshold::Simple(keys.div_ceil(2) as u64)
+}
+
+/// Checks if threshold can be satisfied by signatures of given number of
+/// keys, and isn't satisfied without any. Weighted threshold needs to have
+/// weight for each key. Only empty key list has zero threshold.
+fn is_satisfiable(threshold: &SignatureThreshold, keys: usize) -> bool {
+    match threshold {
+        SignatureThreshold::Simple(0) => keys == 0,
+        SignatureThreshold::Simple(t) => *t as usize <= keys,
+        SignatureThreshold::Weighted(weighted) => {
+            let weights = match weighted {
+                WeightedThreshold::Single(clause) => clause.length(),
+                WeightedThreshold::Multi(clauses) => clauses.length(),
+            };
+            keys > 0 && weights == keys && weighted.is_satisfied(&(0..keys).collect::<Vec<_>>())
+        }
+    }
+}
+
+fn check_witnesses(witnesses: &[BasicPrefix], threshold: u64) -> Result<(), EventBuilderError> {
+    if let Some(witness) = find_duplicate(witnesses) {
+        return Err(EventBuilderError::DuplicateWitness(witness.clone()));
+    }
+    let proper = if witnesses.is_empty() {
+        threshold == 0
+    } else {
+        threshold >= 1 && threshold as usize <= witnesses.len()
+    };
+    if proper {
+        Ok(())
+    } else {
+        Err(EventBuilderError::ImproperWitnessThreshold {
+            threshold,
+            witnesses: witnesses.len(),
+        })
+    }
+}
+
+fn check_state(state: &IdentifierState) -> Result<(), EventBuilderError> {
+    if state.prefix == IdentifierPrefix::default() {
+        Err(EventBuilderError::NotIncepted)
+    } else if state.is_abandoned() {
+        Err(EventBuilderError::Abandoned)
+    } else {
+        Ok(())
+    }
+}
+
+fn find_duplicate<T: PartialEq>(items: &[T]) -> Option<&T> {
+    items
+        .iter()
+        .enumerate()
+        .find(|(i, item)| items[..*i].contains(item))
+        .map(|(_, item)| item)
+}
+
+/// Builds inception event of self-addressing identifier.
+///
+/// Key threshold defaults to majority of keys, the same for next keys.
+/// Without next keys identifier can't be rotated. Witness threshold
+/// defaults to all witnesses.
+pub struct InceptionBuilder {
+    keys: KeySetup,
+    witnesses: Vec<BasicPrefix>,
+    witness_threshold: Option<u64>,
+    seals: Vec<Seal>,
+    format: SerializationFormats,
+    derivation: HashFunction,
+}
+
+impl InceptionBuilder {
+    pub fn new(keys: Vec<BasicPrefix>) -> Self {
+        Self {
+            keys: KeySetup::new(keys),
+            witnesses: vec![],
+            witness_threshold: None,
+            seals: vec![],
+            format: SerializationFormats::JSON,
+            derivation: DEFAULT_DERIVATION.into(),
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: SignatureThreshold) -> Self {
+        self.keys.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_next_keys(mut self, next_keys: Vec<BasicPrefix>) -> Self {
+        self.keys.next_keys = Some(next_keys);
+        self
+    }
+
+    pub fn with_next_key_digests(mut self, digests: Vec<SelfAddressingIdentifier>) -> Self {
+        self.keys.next_key_digests = Some(digests);
+        self
+    }
+
+    pub fn with_next_threshold(mut self, threshold: SignatureThreshold) -> Self {
+        self.keys.next_threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_witnesses(self, witnesses: Vec<BasicPrefix>) -> Self {
+        Self { witnesses, ..self }
+    }
+
+    pub fn with_witness_threshold(self, threshold: u64) -> Self {
+        Self {
+            witness_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    pub fn with_seals(mut self, seals: Vec<Seal>) -> Self {
+        self.seals.extend(seals);
+        self
+    }
+
+    pub fn with_format(self, format: SerializationFormats) -> Self {
+        Self { format, ..self }
+    }
+
+    pub fn with_derivation(self, derivation: HashFunctionCode) -> Self {
+        Self {
+            derivation: derivation.into(),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> Result<KeriEvent<KeyEvent>, Error> {
+        let (format, derivation) = (self.format, self.derivation.clone());
+        self.inception_data()?
+            .incept_self_addressing(derivation, format)
+    }
+
+    fn inception_data(self) -> Result<InceptionEvent, EventBuilderError> {
+        let key_config = self.keys.key_config(&self.derivation)?;
+        let witness_threshold = self
+            .witness_threshold
+            .unwrap_or(self.witnesses.len() as u64);
+        check_witnesses(&self.witnesses, witness_threshold)?;
+        Ok(InceptionEvent {
+            key_config,
+            witness_config: InceptionWitnessConfig {
+                tally: SignatureThreshold::Simple(witness_threshold),
+                initial_witnesses: self.witnesses,
+            },
+            inception_configuration: vec![],
+            data: self.seals,
+        })
+    }
+}
+
+/// Builds delegated inception event. Checks are the same as in
+/// `InceptionBuilder`.
+pub struct DelegatedInceptionBuilder {
+    inception: InceptionBuilder,
+    delegator: IdentifierPrefix,
+}
+
+impl DelegatedInceptionBuilder {
+    pub fn new(keys: Vec<BasicPrefix>, delegator: IdentifierPrefix) -> Self {
+        Self {
+            inception: InceptionBuilder::new(keys),
+            delegator,
+        }
+    }
+
+    pub fn with_threshold(self, threshold: SignatureThreshold) -> Self {
+        Self {
+            inception: self.inception.with_threshold(threshold),
+            ..self
+        }
+    }
+
+    pub fn with_next_keys(self, next_keys: Vec<BasicPrefix>) -> Self {
+        Self {
+            inception: self.inception.with_next_keys(next_keys),
+            ..self
+        }
+    }
+
+    pub fn with_next_key_digests(self, digests: Vec<SelfAddressingIdentifier>) -> Self {
+        Self {
+            inception: self.inception.with_next_key_digests(digests),
+            ..self
+        }
+    }
+
+    pub fn with_next_threshold(self, threshold: SignatureThreshold) -> Self {
+        Self {
+            inception: self.inception.with_next_threshold(threshold),
+            ..self
+        }
+    }
+
+    pub fn with_witnesses(self, witnesses: Vec<BasicPrefix>) -> Self {
+        Self {
+            inception: self.inception.with_witnesses(witnesses),
+            ..self
+        }
+    }
+
+    pub fn with_witness_threshold(self, threshold: u64) -> Self {
+        Self {
+            inception: self.inception.with_witness_threshold(threshold),
+            ..self
+        }
+    }
+
+    pub fn with_seals(self, seals: Vec<Seal>) -> Self {
+        Self {
+            inception: self.inception.with_seals(seals),
+            ..self
+        }
+    }
+
+    pub fn with_format(self, format: SerializationFormats) -> Self {
+        Self {
+            inception: self.inception.with_format(format),
+            ..self
+        }
+    }
+
+    pub fn with_derivation(self, derivation: HashFunctionCode) -> Self {
+        Self {
+            inception: self.inception.with_derivation(derivation),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> Result<KeriEvent<KeyEvent>, Error> {
+        let (format, derivation) = (self.inception.format, self.inception.derivation.clone());
+        DelegatedInceptionEvent {
+            inception_data: self.inception.inception_data()?,
+            delegator: self.delegator,
+        }
+        .incept_self_addressing(derivation, format)
+    }
+}
+
+/// Builds rotation event following the identifier's current state, or
+/// delegated rotation if identifier is delegated.
+///
+/// Keys need to satisfy prior next key threshold. Keys not committed to
+/// before can be added as long as they do. Witness threshold defaults to
+/// the current one.
+pub struct RotationBuilder {
+    state: IdentifierState,
+    keys: KeySetup,
+    witnesses_to_add: Vec<BasicPrefix>,
+    witnesses_to_remove: Vec<BasicPrefix>,
+    witness_threshold: Option<u64>,
+    seals: Vec<Seal>,
+    format: SerializationFormats,
+    derivation: HashFunction,
+}
+
+impl RotationBuilder {
+    pub fn new(state: &IdentifierState, keys: Vec<BasicPrefix>) -> Self {
+        Self {
+            state: state.clone(),
+            keys: KeySetup::new(keys),
+            witnesses_to_add: vec![],
+            witnesses_to_remove: vec![],
+            witness_threshold: None,
+            seals: vec![],
+            format: SerializationFormats::JSON,
+            derivation: DEFAULT_DERIVATION.into(),
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: SignatureThreshold) -> Self {
+        self.keys.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_next_keys(mut self, next_keys: Vec<BasicPrefix>) -> Self {
+        self.keys.next_keys = Some(next_keys);
+        self
+    }
+
+    pub fn with_next_key_digests(mut self, digests: Vec<SelfAddressingIdentifier>) -> Self {
+        self.keys.next_key_digests = Some(digests);
+        self
+    }
+
+    pub fn with_next_threshold(mut self, threshold: SignatureThreshold) -> Self {
+        self.keys.next_threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_witnesses_to_add(self, witnesses_to_add: Vec<BasicPrefix>) -> Self {
+        Self {
+            witnesses_to_add,
+            ..self
+        }
+    }
+
+    pub fn with_witnesses_to_remove(self, witnesses_to_remove: Vec<BasicPrefix>) -> Self {
+        Self {
+            witnesses_to_remove,
+            ..self
+        }
+    }
+
+    pub fn with_witness_threshold(self, threshold: u64) -> Self {
+        Self {
+            witness_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    pub fn with_seals(mut self, seals: Vec<Seal>) -> Self {
+        self.seals.extend(seals);
+        self
+    }
+
+    pub fn with_format(self, format: SerializationFormats) -> Self {
+        Self { format, ..self }
+    }
+
+    pub fn with_derivation(self, derivation: HashFunctionCode) -> Self {
+        Self {
+            derivation: derivation.into(),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> Result<KeriEvent<KeyEvent>, Error> {
+        check_state(&self.state)?;
+        let key_config = self.keys.key_config(&self.derivation)?;
+        let prior_next = &self.state.current.next_keys_data;
+        let exposed: Vec<usize> = key_config
+            .public_keys
+            .iter()
+            .filter_map(|key| prior_next.key_position(key))
+            .collect();
+        if !prior_next.threshold.is_satisfied(&exposed) {
+            return Err(EventBuilderError::UnsatisfiedNextThreshold.into());
+        }
+
+        let current = &self.state.witness_config.witnesses;
+        if let Some(witness) = find_duplicate(&self.witnesses_to_remove) {
+            return Err(EventBuilderError::DuplicateWitness(witness.clone()).into());
+        }
+        if let Some(witness) = self
+            .witnesses_to_remove
+            .iter()
+            .find(|witness| !current.contains(witness))
+        {
+            return Err(EventBuilderError::UnknownWitness(witness.clone()).into());
+        }
+        if let Some(witness) = self
+            .witnesses_to_add
+            .iter()
+            .find(|witness| current.contains(witness))
+        {
+            return Err(EventBuilderError::AlreadyWitness(witness.clone()).into());
+        }
+        let witnesses: Vec<BasicPrefix> = current
+            .iter()
+            .filter(|witness| !self.witnesses_to_remove.contains(witness))
+            .chain(self.witnesses_to_add.iter())
+            .cloned()
+            .collect();
+        let witness_threshold = match (self.witness_threshold, &self.state.witness_config.tally) {
+            (Some(threshold), _) => threshold,
+            (None, SignatureThreshold::Simple(threshold)) => *threshold,
+            (None, SignatureThreshold::Weighted(_)) => witnesses.len() as u64,
+        };
+        check_witnesses(&witnesses, witness_threshold)?;
+
+        let rotation = RotationEvent::new(
+            self.state.last_event_digest.clone().into(),
+            key_config,
+            RotationWitnessConfig {
+                tally: SignatureThreshold::Simple(witness_threshold),
+                prune: self.witnesses_to_remove,
+                graft: self.witnesses_to_add,
+            },
+            self.seals,
+        );
+        let event_data = if self.state.delegator.is_some() {
+            EventData::Drt(rotation)
+        } else {
+            EventData::Rot(rotation)
+        };
+        KeyEvent::new(self.state.prefix, self.state.sn + 1, event_data)
+            .to_message(self.format, self.derivation)
+    }
+}
+
+/// Builds interaction event following the identifier's current state.
+pub struct InteractionBuilder {
+    state: IdentifierState,
+    seals: Vec<Seal>,
+    format: SerializationFormats,
+    derivation: HashFunction,
+}
+
+impl InteractionBuilder {
+    pub fn new(state: &IdentifierState) -> Self {
+        Self {
+            state: state.clone(),
+            seals: vec![],
+            format: SerializationFormats::JSON,
+            derivation: DEFAULT_DERIVATION.into(),
+        }
+    }
+
+    pub fn with_seals(mut self, seals: Vec<Seal>) -> Self {
+        self.seals.extend(seals);
+        self
+    }
+
+    pub fn with_format(self, format: SerializationFormats) -> Self {
+        Self { format, ..self }
+    }
+
+    pub fn with_derivation(self, derivation: HashFunctionCode) -> Self {
+        Self {
+            derivation: derivation.into(),
+            ..self
+        }
+    }
+
+    pub fn build(self) -> Result<KeriEvent<KeyEvent>, Error> {
+        check_state(&self.state)?;
+        let interaction =
+            InteractionEvent::new(self.state.last_event_digest.clone().into(), self.seals);
+        KeyEvent::new(
+            self.state.prefix,
+            self.state.sn + 1,
+            EventData::Ixn(interaction),
+        )
+        .to_message(self.format, self.derivation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event_message::EventTypeTag, signer::Signer};
+
+    fn key() -> BasicPrefix {
+        BasicPrefix::Ed25519(Signer::new().public_key())
+    }
+
+    fn builder_error(result: Result<KeriEvent<KeyEvent>, Error>) -> EventBuilderError {
+        match result {
+            Err(Error::EventBuilderError(e)) => e,
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_inception_builder() -> Result<(), Error> {
+        // The same event as in `test_multisig_prefix_derivation`.
+        let expected_event = br#"{"v":"KERI10JSON0001e7_","t":"icp","d":"EBfxc4RiVY6saIFmUfEtETs1FcqmktZW88UkbnOg0Qen","i":"EBfxc4RiVY6saIFmUfEtETs1FcqmktZW88UkbnOg0Qen","s":"0","kt":"2","k":["DErocgXD2RGSyvn3MObcx59jeOsEQhv2TqHirVkzrp0Q","DFXLiTjiRdSBPLL6hLa0rskIxk3dh4XwJLfctkJFLRSS","DE9YgIQVgpLwocTVrG8tidKScsQSMWwLWywNC48fhq4f"],"nt":"2","n":["EDJk5EEpC4-tQ7YDwBiKbpaZahh1QCyQOnZRF7p2i8k8","EAXfDjKvUFRj-IEB_o4y-Y_qeJAjYfZtOMD9e7vHNFss","EN8l6yJC2PxribTN0xfri6bLz34Qvj-x3cNwcV3DvT2m"],"bt":"0","b":[],"c":[],"a":[]}"#;
+        let keys: Vec<BasicPrefix> = [
+            "DErocgXD2RGSyvn3MObcx59jeOsEQhv2TqHirVkzrp0Q",
+            "DFXLiTjiRdSBPLL6hLa0rskIxk3dh4XwJLfctkJFLRSS",
+            "DE9YgIQVgpLwocTVrG8tidKScsQSMWwLWywNC48fhq4f",
+        ]
+        .iter()
+        .map(|key| key.parse().unwrap())
+        .collect();
+        let next_keys: Vec<BasicPrefix> = [
+            "DCjxOXniUc5EUzDqERlXdptfKPHy6jNo_ZGsS4Vd8fAE",
+            "DNZHARO4dCJlluv0qezEMRmErIWWc-lzOzolBOQ15tHV",
+            "DOCQ4KN1jUlKbfjRteDYt9fxgpq1NK9_MqO5IA7shpED",
+        ]
+        .iter()
+        .map(|key| key.parse().unwrap())
+        .collect();
+
+        // Majority thresholds are the default.
+        let icp = InceptionBuilder::new(keys.clone())
+            .with_next_keys(next_keys.clone())
+            .build()?;
+        assert_eq!(icp.encode()?, expected_event.to_vec());
+
+        let digests = match icp.data.event_data {
+            EventData::Icp(icp) => icp.key_config.next_keys_data.next_keys_hashes(),
+            _ => unreachable!(),
+        };
+        let icp = InceptionBuilder::new(keys.clone())
+            .with_threshold(SignatureThreshold::Simple(2))
+            .with_next_key_digests(digests.clone())
+            .with_next_threshold(SignatureThreshold::Simple(2))
+            .build()?;
+        assert_eq!(icp.encode()?, expected_event.to_vec());
+
+        let weighted = SignatureThreshold::single_weighted(vec![(1, 2), (1, 2), (1, 2)]);
+        assert!(InceptionBuilder::new(keys.clone())
+            .with_threshold(weighted)
+            .build()
+            .is_ok());
+
+        let error = builder_error(InceptionBuilder::new(vec![]).build());
+        assert_eq!(error, EventBuilderError::NoKeys);
+        let error =
+            builder_error(InceptionBuilder::new(vec![keys[0].clone(), keys[0].clone()]).build());
+        assert_eq!(error, EventBuilderError::DuplicateKey(keys[0].clone()));
+        for threshold in [
+            SignatureThreshold::Simple(0),
+            SignatureThreshold::Simple(4),
+            SignatureThreshold::single_weighted(vec![(1, 2), (1, 2)]),
+            SignatureThreshold::single_weighted(vec![(1, 4), (1, 4), (1, 4)]),
+        ] {
+            let error = builder_error(
+                InceptionBuilder::new(keys.clone())
+                    .with_threshold(threshold)
+                    .build(),
+            );
+            assert_eq!(error, EventBuilderError::ImproperThreshold { keys: 3 });
+        }
+        let error = builder_error(
+            InceptionBuilder::new(keys.clone())
+                .with_next_keys(next_keys.clone())
+                .with_next_threshold(SignatureThreshold::Simple(4))
+                .build(),
+        );
+        assert_eq!(error, EventBuilderError::ImproperNextThreshold { keys: 3 });
+        let error = builder_error(
+            InceptionBuilder::new(keys.clone())
+                .with_next_threshold(SignatureThreshold::Simple(1))
+                .build(),
+        );
+        assert_eq!(error, EventBuilderError::ImproperNextThreshold { keys: 0 });
+        let error = builder_error(
+            InceptionBuilder::new(keys.clone())
+                .with_next_keys(next_keys.clone())
+                .with_next_key_digests(digests.clone())
+                .build(),
+        );
+        assert_eq!(error, EventBuilderError::ConflictingNextKeys);
+        let error = builder_error(
+            InceptionBuilder::new(keys.clone())
+                .with_next_key_digests(vec![digests[0].clone(), digests[0].clone()])
+                .build(),
+        );
+        assert_eq!(
+            error,
+            EventBuilderError::DuplicateNextKey(digests[0].clone())
+        );
+
+        // Witness threshold defaults to all witnesses.
+        let witnesses = vec![key(), key()];
+        let icp = InceptionBuilder::new(keys.clone())
+            .with_witnesses(witnesses.clone())
+            .build()?;
+        let state = IdentifierState::default().apply(&icp)?;
+        assert_eq!(state.witness_config.tally, SignatureThreshold::Simple(2));
+        assert_eq!(state.witness_config.witnesses, witnesses);
+        for threshold in [0, 3] {
+            let error = builder_error(
+                InceptionBuilder::new(keys.clone())
+                    .with_witnesses(witnesses.clone())
+                    .with_witness_threshold(threshold)
+                    .build(),
+            );
+            assert_eq!(
+                error,
+                EventBuilderError::ImproperWitnessThreshold {
+                    threshold,
+                    witnesses: 2
+                }
+            );
+        }
+        let error = builder_error(
+            InceptionBuilder::new(keys.clone())
+                .with_witnesses(vec![witnesses[0].clone(), witnesses[0].clone()])
+                .build(),
+        );
+        assert_eq!(
+            error,
+            EventBuilderError::DuplicateWitness(witnesses[0].clone())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_delegated_inception_builder() -> Result<(), Error> {
+        let delegator: IdentifierPrefix = "EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL".parse()?;
+        let next = key();
+        let dip = DelegatedInceptionBuilder::new(vec![key()], delegator.clone())
+            .with_next_keys(vec![next.clone()])
+            .build()?;
+        assert_eq!(dip.event_type, EventTypeTag::Dip);
+        let state = IdentifierState::default().apply(&dip)?;
+        assert_eq!(state.delegator, Some(delegator.clone()));
+
+        // Delegated identifier is rotated with delegated rotation.
+        let drt = RotationBuilder::new(&state, vec![next])
+            .with_next_keys(vec![key()])
+            .build()?;
+        assert_eq!(drt.event_type, EventTypeTag::Drt);
+        assert_eq!(state.apply(&drt)?.sn, 1);
+
+        let error = builder_error(
+            DelegatedInceptionBuilder::new(vec![key()], delegator)
+                .with_threshold(SignatureThreshold::Simple(2))
+                .build(),
+        );
+        assert_eq!(error, EventBuilderError::ImproperThreshold { keys: 1 });
+        Ok(())
+    }
+
+    #[test]
+    fn test_rotation_builder() -> Result<(), Error> {
+        let (current, next, next_next) = (key(), vec![key(), key()], key());
+        let witnesses = vec![key(), key()];
+        let icp = InceptionBuilder::new(vec![current])
+            .with_next_keys(next.clone())
+            .with_next_threshold(SignatureThreshold::Simple(2))
+            .with_witnesses(witnesses.clone())
+            .build()?;
+        let state = IdentifierState::default().apply(&icp)?;
+
+        let error =
+            builder_error(RotationBuilder::new(&IdentifierState::default(), next.clone()).build());
+        assert_eq!(error, EventBuilderError::NotIncepted);
+
+        // Both next keys need to be exposed, new key may be added.
+        let error = builder_error(RotationBuilder::new(&state, vec![next[0].clone()]).build());
+        assert_eq!(error, EventBuilderError::UnsatisfiedNextThreshold);
+        let rot = RotationBuilder::new(&state, [next.clone(), vec![key()]].concat())
+            .with_next_keys(vec![next_next.clone()])
+            .build()?;
+        assert_eq!(rot.event_type, EventTypeTag::Rot);
+        let rotated = state.clone().apply(&rot)?;
+        assert_eq!(rotated.sn, 1);
+        assert_eq!(rotated.current.threshold, SignatureThreshold::Simple(2));
+
+        // Witness changes.
+        let other = key();
+        let error = builder_error(
+            RotationBuilder::new(&state, next.clone())
+                .with_witnesses_to_remove(vec![other.clone()])
+                .build(),
+        );
+        assert_eq!(error, EventBuilderError::UnknownWitness(other.clone()));
+        let error = builder_error(
+            RotationBuilder::new(&state, next.clone())
+                .with_witnesses_to_add(vec![witnesses[1].clone()])
+                .build(),
+        );
+        assert_eq!(
+            error,
+            EventBuilderError::AlreadyWitness(witnesses[1].clone())
+        );
+        let error = builder_error(
+            RotationBuilder::new(&state, next.clone())
+                .with_witnesses_to_remove(vec![witnesses[0].clone()])
+                .build(),
+        );
+        assert_eq!(
+            error,
+            EventBuilderError::ImproperWitnessThreshold {
+                threshold: 2,
+                witnesses: 1
+            }
+        );
+        let rot = RotationBuilder::new(&state, next.clone())
+            .with_next_keys(vec![next_next])
+            .with_witnesses_to_remove(vec![witnesses[0].clone()])
+            .with_witnesses_to_add(vec![other.clone()])
+            .build()?;
+        let rotated = state.apply(&rot)?;
+        assert_eq!(
+            rotated.witness_config.witnesses,
+            vec![witnesses[1].clone(), other]
+        );
+        assert_eq!(rotated.witness_config.tally, SignatureThreshold::Simple(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_interaction_builder() -> Result<(), Error> {
+        let next = key();
+        let icp = InceptionBuilder::new(vec![key()])
+            .with_next_keys(vec![next.clone()])
+            .build()?;
+        let state = IdentifierState::default().apply(&icp)?;
+        let ixn = InteractionBuilder::new(&state).build()?;
+        assert_eq!(ixn.event_type, EventTypeTag::Ixn);
+        let state = state.apply(&ixn)?;
+        assert_eq!(state.sn, 1);
+
+        // Rotation without next keys abandons identifier.
+        let rot = RotationBuilder::new(&state, vec![next.clone()]).build()?;
+        let state = state.apply(&rot)?;
+        assert!(state.is_abandoned());
+        let error = builder_error(InteractionBuilder::new(&state).build());
+        assert_eq!(error, EventBuilderError::Abandoned);
+        let error = builder_error(RotationBuilder::new(&state, vec![next]).build());
+        assert_eq!(error, EventBuilderError::Abandoned);
+        Ok(())
+    }
+}
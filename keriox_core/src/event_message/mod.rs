@@ -1,11 +1,24 @@
+#[cfg(feature = "annotate")]
+pub mod annotate;
 pub mod cesr_adapter;
+pub mod cesr_stream;
+pub mod cesr_version;
+pub mod digest;
 pub mod dummy_event;
+pub mod encoding;
 pub mod event_msg_builder;
+pub mod event_ref;
+pub mod key_event_builder;
 pub mod key_event_message;
 pub mod msg;
+pub mod protocol_version;
+pub mod sad_path;
+#[cfg(feature = "saidify")]
+pub mod saidify;
 pub mod serializer;
 pub mod signature;
 pub mod signed_event_message;
+pub mod strict_parser;
 pub mod timestamped;
 
 use std::cmp::Ordering;
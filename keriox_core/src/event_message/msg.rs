@@ -4,7 +4,7 @@ use said::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::{EventTypeTag, Typeable};
+use super::{encoding::encode, EventTypeTag, Typeable};
 use crate::database::rkyv_adapter::said_wrapper::SaidValue;
 use crate::database::rkyv_adapter::serialization_info_wrapper::SerializationInfoDef;
 use crate::error::Error;
@@ -80,7 +80,7 @@ impl<T: Serialize + Clone, D: Serialize + Typeable<TypeTag = T> + Clone> TypedEv
     }
 
     pub fn encode(&self) -> Result<Vec<u8>, Error> {
-        Ok(self.serialization_info.serialize(&self).unwrap())
+        encode(&self.serialization_info.kind, &self)
     }
 }
 
@@ -99,7 +99,7 @@ impl<T: Serialize + Clone, D: Serialize + Clone + Typeable<TypeTag = T>> SAD for
         format: &SerializationFormats,
     ) -> Vec<u8> {
         let tmp_event = DummyTypedEvent::convert(self.clone(), derivation.clone());
-        format.encode(&tmp_event).unwrap()
+        encode(format, &tmp_event).unwrap()
     }
 }
 
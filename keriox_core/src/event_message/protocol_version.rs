@@ -0,0 +1,204 @@
+//! KERI protocol version of messages.
+//!
+//! Version string of every message starts with protocol code and its major
+//! and minor version. Messages of known versions are accepted. Minor
+//! versions are backwards compatible, so messages of newer minor version
+//! may be processed as the known one, ignoring what they add, if
+//! `VersionPolicy::BestEffort` is chosen. Other major versions and
+//! protocols are always rejected.
+
+use std::fmt;
+
+use cesrox::payload::Payload;
+use said::version::{format::SerializationFormats, SerializationInfo};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::encoding::decode;
+
+/// Protocol code of KERI messages version strings.
+pub const KERI_PROTOCOL: &str = "KERI";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ProtocolVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl ProtocolVersion {
+    pub const V1_0: Self = Self { major: 1, minor: 0 };
+
+    /// Newest known version. Messages are created with it.
+    pub const CURRENT: Self = Self::V1_0;
+
+    /// Reads version from version string of message body, without
+    /// deserializing the rest of it.
+    pub fn of_payload(payload: &Payload) -> Result<Self, VersionError> {
+        Ok((&payload_info(payload)?).into())
+    }
+}
+
+impl From<&SerializationInfo> for ProtocolVersion {
+    fn from(info: &SerializationInfo) -> Self {
+        Self {
+            major: info.major_version,
+            minor: info.minor_version,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum VersionError {
+    #[error("{0}")]
+    UndecodableBody(String),
+    #[error("Invalid version string: {0}")]
+    InvalidVersionString(String),
+    #[error("Unknown protocol: {0}")]
+    UnknownProtocol(String),
+    #[error("Unsupported major version {0}")]
+    UnsupportedMajorVersion(ProtocolVersion),
+    #[error("Newer minor version {0}")]
+    NewerMinorVersion(ProtocolVersion),
+}
+
+/// How to handle messages of newer minor version than the known one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VersionPolicy {
+    #[default]
+    Reject,
+    /// Accept and process them the way known version is processed.
+    BestEffort,
+}
+
+impl VersionPolicy {
+    /// Checks protocol and version of message with `info` version string.
+    /// Returns the version if message is accepted.
+    pub fn check(&self, info: &SerializationInfo) -> Result<ProtocolVersion, VersionError> {
+        let version = ProtocolVersion::from(info);
+        let current = ProtocolVersion::CURRENT;
+        if info.protocol_code != KERI_PROTOCOL {
+            Err(VersionError::UnknownProtocol(info.protocol_code.clone()))
+        } else if version.major != current.major {
+            Err(VersionError::UnsupportedMajorVersion(version))
+        } else if version.minor > current.minor && *self == VersionPolicy::Reject {
+            Err(VersionError::NewerMinorVersion(version))
+        } else {
+            Ok(version)
+        }
+    }
+
+    /// Checks protocol and version of message `payload`, see `check`.
+    pub fn check_payload(&self, payload: &Payload) -> Result<ProtocolVersion, VersionError> {
+        self.check(&payload_info(payload)?)
+    }
+}
+
+fn payload_info(payload: &Payload) -> Result<SerializationInfo, VersionError> {
+    let (format, body) = match payload {
+        Payload::JSON(body) => (SerializationFormats::JSON, body),
+        Payload::CBOR(body) => (SerializationFormats::CBOR, body),
+        Payload::MGPK(body) => (SerializationFormats::MGPK, body),
+    };
+    version_info(&format, body)
+}
+
+/// Parses version string of message `body`.
+pub fn version_info(
+    format: &SerializationFormats,
+    body: &[u8],
+) -> Result<SerializationInfo, VersionError> {
+    #[derive(Deserialize)]
+    struct Versioned {
+        v: String,
+    }
+    let version = decode::<Versioned>(format, body)
+        .map_err(|e| VersionError::UndecodableBody(e.to_string()))?
+        .v;
+    // `SerializationInfo` parser panics on too short strings.
+    if version.len() == 17 && version.is_ascii() {
+        version.parse().ok()
+    } else {
+        None
+    }
+    .ok_or(VersionError::InvalidVersionString(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_message::{
+        signed_event_message::Message,
+        strict_parser::{StrictParseError, StrictParser},
+    };
+
+    #[test]
+    fn test_version_policy() {
+        let info = |version: &str| -> SerializationInfo { version.parse().unwrap() };
+        for policy in [VersionPolicy::Reject, VersionPolicy::BestEffort] {
+            assert_eq!(
+                policy.check(&info("KERI10JSON000159_")),
+                Ok(ProtocolVersion::V1_0)
+            );
+            assert_eq!(
+                policy.check(&info("ACDC10JSON000159_")),
+                Err(VersionError::UnknownProtocol("ACDC".into()))
+            );
+            assert_eq!(
+                policy.check(&info("KERI20JSON000159_")),
+                Err(VersionError::UnsupportedMajorVersion(ProtocolVersion {
+                    major: 2,
+                    minor: 0
+                }))
+            );
+        }
+        let newer = ProtocolVersion { major: 1, minor: 1 };
+        assert_eq!(
+            VersionPolicy::Reject.check(&info("KERI11JSON000159_")),
+            Err(VersionError::NewerMinorVersion(newer))
+        );
+        assert_eq!(
+            VersionPolicy::BestEffort.check(&info("KERI11JSON000159_")),
+            Ok(newer)
+        );
+    }
+
+    #[test]
+    fn test_version_of_payload() {
+        let body = br#"{"v":"KERI11JSON000020_","t":"ixn"}"#.to_vec();
+        assert_eq!(
+            ProtocolVersion::of_payload(&Payload::JSON(body)),
+            Ok(ProtocolVersion { major: 1, minor: 1 })
+        );
+        #[cfg(feature = "cbor")]
+        {
+            let cbor = serde_cbor::to_vec(&serde_json::json!({"v": "KERI10CBOR000020_"})).unwrap();
+            assert_eq!(
+                ProtocolVersion::of_payload(&Payload::CBOR(cbor)),
+                Ok(ProtocolVersion::V1_0)
+            );
+        }
+        assert_eq!(
+            ProtocolVersion::of_payload(&Payload::JSON(br#"{"v":"KERI1"}"#.to_vec())),
+            Err(VersionError::InvalidVersionString("KERI1".into()))
+        );
+    }
+
+    #[test]
+    fn test_message_version() -> Result<(), StrictParseError> {
+        let icp = br#"{"v":"KERI11JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD"#;
+        let messages: Vec<Message> = StrictParser::new()
+            .with_version_policy(VersionPolicy::BestEffort)
+            .parse_messages(icp)?;
+        assert_eq!(
+            messages[0].protocol_version(),
+            ProtocolVersion { major: 1, minor: 1 }
+        );
+        Ok(())
+    }
+}
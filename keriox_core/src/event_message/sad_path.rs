@@ -0,0 +1,293 @@
+//! Signatures over parts of self-addressing data.
+//!
+//! SAD path selects part of message body: `-a` is value of its `a` field,
+//! `-a-i` value of `i` field nested in it and `-` the whole body. Path
+//! components may also be indexes of list elements, or of map fields in
+//! their order. Signatures attached in pathed material group sign the
+//! selected part, encoded in format of the body, so signatures of embedded
+//! events or credentials can be carried along with the message.
+
+use std::fmt;
+
+use cesrox::{cesr_proof::MaterialPath, group::Group};
+use said::version::format::SerializationFormats;
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::{value::RawValue, Value};
+
+use super::{
+    cesr_adapter::ParseError,
+    encoding::{decode, encode},
+    signature::{get_signatures, signatures_into_groups, Signature},
+};
+use crate::{database::EventDatabase, error::Error, processor::event_storage::EventStorage};
+
+/// Path of the whole message body.
+pub const ROOT_PATH: &str = "-";
+
+/// Signatures over part of message body selected by SAD path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathedSignatures {
+    pub path: MaterialPath,
+    pub signatures: Vec<Signature>,
+}
+
+impl PathedSignatures {
+    pub fn new(path: &str, signatures: Vec<Signature>) -> Self {
+        Self {
+            path: MaterialPath::to_path(path.to_string()),
+            signatures,
+        }
+    }
+
+    pub fn path(&self) -> String {
+        path_to_str(&self.path)
+    }
+
+    /// Verifies signatures over part of message `body`. Signers of
+    /// transferable signatures need to be known. No signatures aren't valid.
+    pub fn verify<D: EventDatabase>(
+        &self,
+        format: &SerializationFormats,
+        body: &[u8],
+        storage: &EventStorage<D>,
+    ) -> Result<bool, Error> {
+        if self.signatures.is_empty() {
+            return Ok(false);
+        }
+        let data = signed_data(format, body, &self.path())?;
+        self.signatures.iter().try_fold(true, |acc, signature| {
+            Ok(acc && signature.verify(&data, storage)?)
+        })
+    }
+}
+
+impl From<PathedSignatures> for Group {
+    fn from(pathed: PathedSignatures) -> Self {
+        Group::PathedMaterialQuadruplet(pathed.path, signatures_into_groups(&pathed.signatures))
+    }
+}
+
+/// Returns textual form of material path, e.g. `-a-i`.
+pub fn path_to_str(path: &MaterialPath) -> String {
+    // Path is kept as base64 text, with leading `A`s padding it to quadlets.
+    // Code is 4 characters long.
+    path.to_cesr()[4..].trim_start_matches('A').to_string()
+}
+
+fn components(path: &str) -> Option<impl Iterator<Item = &str>> {
+    Some(
+        path.strip_prefix('-')?
+            .split('-')
+            .filter(|component| !component.is_empty()),
+    )
+}
+
+/// Selects part of `value` with SAD path. Map fields are indexed in order
+/// of `Value` maps, which is their encoded order only with `preserve_order`
+/// feature of `serde_json`.
+pub fn select<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    components(path)?.try_fold(value, |value, component| {
+        let index = component.parse::<usize>().ok();
+        match value {
+            Value::Object(fields) => fields
+                .get(component)
+                .or_else(|| fields.values().nth(index?)),
+            Value::Array(elements) => elements.get(index?),
+            _ => None,
+        }
+    })
+}
+
+/// Fields of JSON object or elements of JSON array, borrowed from its
+/// encoding in order.
+struct Entries<'a>(Vec<(Option<String>, &'a RawValue)>);
+
+impl<'de> Deserialize<'de> for Entries<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = Entries<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("JSON object or array")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = vec![];
+                while let Some((key, value)) = map.next_entry::<String, &RawValue>()? {
+                    entries.push((Some(key), value));
+                }
+                Ok(Entries(entries))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut entries = vec![];
+                while let Some(value) = seq.next_element::<&RawValue>()? {
+                    entries.push((None, value));
+                }
+                Ok(Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_any(EntriesVisitor)
+    }
+}
+
+/// Selects part of JSON `body` with SAD path, without decoding it, so
+/// selected part keeps its encoding.
+fn select_json<'a>(body: &'a [u8], path: &str) -> Option<&'a str> {
+    let body: &RawValue = serde_json::from_slice(body).ok()?;
+    components(path)?
+        .try_fold(body, |value, component| {
+            let Entries(entries) = serde_json::from_str(value.get()).ok()?;
+            let index = component.parse::<usize>().ok();
+            entries
+                .iter()
+                .find(|(key, _)| key.as_deref() == Some(component))
+                .or_else(|| entries.get(index?))
+                .map(|(_, value)| *value)
+        })
+        .map(RawValue::get)
+}
+
+/// Encodes part of message body selected by `path`. That's the data signed
+/// by signatures attached under the path.
+pub fn signed_data(
+    format: &SerializationFormats,
+    body: &[u8],
+    path: &str,
+) -> Result<Vec<u8>, Error> {
+    let missing = || Error::SemanticError(format!("Nothing at path {}", path));
+    match format {
+        SerializationFormats::JSON => select_json(body, path)
+            .map(|part| part.as_bytes().to_vec())
+            .ok_or_else(missing),
+        format => {
+            let value: Value = decode(format, body)?;
+            encode(format, select(&value, path).ok_or_else(missing)?)
+        }
+    }
+}
+
+/// Gets signatures attached in pathed material group. Paths of nested
+/// pathed groups are relative to the enclosing one.
+pub fn get_pathed_signatures(group: Group) -> Result<Vec<PathedSignatures>, ParseError> {
+    match group {
+        Group::PathedMaterialQuadruplet(path, groups) => {
+            let path = path_to_str(&path);
+            let mut signatures = vec![];
+            let mut nested = vec![];
+            for group in groups {
+                match group {
+                    Group::PathedMaterialQuadruplet(_, _) => {
+                        nested.extend(get_pathed_signatures(group)?.into_iter().map(|pathed| {
+                            PathedSignatures::new(&join(&path, &pathed.path()), pathed.signatures)
+                        }))
+                    }
+                    group => signatures.extend(get_signatures(group)?),
+                }
+            }
+            if !signatures.is_empty() || nested.is_empty() {
+                nested.insert(0, PathedSignatures::new(&path, signatures));
+            }
+            Ok(nested)
+        }
+        _ => Err(ParseError::AttachmentError(
+            "Improper attachment type".into(),
+        )),
+    }
+}
+
+fn join(path: &str, relative: &str) -> String {
+    match (path, relative) {
+        (ROOT_PATH, relative) => relative.to_string(),
+        (path, ROOT_PATH) => path.to_string(),
+        (path, relative) => [path, relative].concat(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use cesrox::{payload::Payload, ParsedData};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        database::memory::MemoryDatabase,
+        event_message::{cesr_adapter::parse, signature::Nontransferable},
+        prefix::BasicPrefix,
+        signer::Signer,
+    };
+
+    const ICP: &str = r#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}"#;
+
+    #[test]
+    fn test_select() {
+        let value = json!({"t": "exn", "a": {"i": "EA", "l": [1, 2]}});
+        assert_eq!(select(&value, ROOT_PATH), Some(&value));
+        assert_eq!(select(&value, "-a-i"), Some(&json!("EA")));
+        assert_eq!(select(&value, "-a-l-1"), Some(&json!(2)));
+        assert_eq!(select(&value, "-a-0"), Some(&json!("EA")));
+        assert_eq!(select(&value, "-b"), None);
+        assert_eq!(select(&value, "-a-l-2"), None);
+
+        for path in [ROOT_PATH, "-a", "-A-", "-e-acdc"] {
+            assert_eq!(path_to_str(&MaterialPath::to_path(path.into())), path);
+        }
+    }
+
+    #[test]
+    fn test_pathed_signatures() -> Result<(), Error> {
+        let body = format!(r#"{{"t":"exn","a":{}}}"#, ICP);
+        let format = SerializationFormats::JSON;
+        assert_eq!(signed_data(&format, body.as_bytes(), "-a")?, ICP.as_bytes());
+
+        let signer = Signer::new();
+        let signature = Signature::NonTransferable(Nontransferable::Couplet(vec![(
+            BasicPrefix::Ed25519NT(signer.public_key()),
+            signer.sign_prefix(ICP)?,
+        )]));
+        let pathed = PathedSignatures::new("-a", vec![signature]);
+        let storage = EventStorage::new(Arc::new(MemoryDatabase::new()));
+        assert!(pathed.verify(&format, body.as_bytes(), &storage)?);
+
+        // Signature covers only the selected part.
+        let changed = body.replacen(r#""t":"exn""#, r#""t":"fwd""#, 1);
+        assert!(pathed.verify(&format, changed.as_bytes(), &storage)?);
+        let changed = body.replacen(r#""s":"0""#, r#""s":"1""#, 1);
+        assert!(!pathed.verify(&format, changed.as_bytes(), &storage)?);
+
+        // Attached and parsed back.
+        let stream = ParsedData {
+            payload: Payload::JSON(body.clone().into_bytes()),
+            attachments: vec![pathed.clone().into()],
+        }
+        .to_cesr()
+        .unwrap();
+        let (_, parsed) = parse(&stream).unwrap();
+        let attachments = parsed.attachments.into_iter().next().unwrap();
+        let parsed = get_pathed_signatures(attachments)?;
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].path(), "-a");
+        assert_eq!(parsed[0].signatures, pathed.signatures);
+
+        // Nested path is relative to enclosing one.
+        let nested = Group::PathedMaterialQuadruplet(
+            MaterialPath::to_path("-e".into()),
+            vec![Group::from(PathedSignatures::new(
+                "-acdc",
+                pathed.signatures.clone(),
+            ))],
+        );
+        let parsed = get_pathed_signatures(nested)?;
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].path(), "-e-acdc");
+        Ok(())
+    }
+}
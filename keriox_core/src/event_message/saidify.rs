@@ -0,0 +1,181 @@
+//! Self-addressing identifiers of arbitrary data.
+//!
+//! SAID of a map is the digest of its encoding with the SAID field filled
+//! with `#` placeholder of the digest's length. The same way digests of
+//! KERI events are computed, so it can be used for custom documents
+//! anchored in KEL, or ACDC-like structures.
+
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    version::{format::SerializationFormats, SerializationInfo},
+    SelfAddressingIdentifier,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{
+    digest::DEFAULT_DERIVATION,
+    encoding::{decode, encode},
+};
+use crate::error::Error;
+
+/// Name of the SAID field used by KERI and ACDC.
+pub const DEFAULT_LABEL: &str = "d";
+
+/// Computes and verifies SAIDs of serializable maps.
+///
+/// SAID field is appended to the map if it's not there, so it should be
+/// present, with any value, if its position matters. If the map has version
+/// string in `v` field, its size and serialization format are set too.
+#[derive(Debug, Clone)]
+pub struct Saidify {
+    label: String,
+    format: SerializationFormats,
+    derivation: HashFunctionCode,
+}
+
+impl Default for Saidify {
+    fn default() -> Self {
+        Self {
+            label: DEFAULT_LABEL.to_string(),
+            format: SerializationFormats::JSON,
+            derivation: DEFAULT_DERIVATION,
+        }
+    }
+}
+
+impl Saidify {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_label(self, label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            ..self
+        }
+    }
+
+    pub fn with_format(self, format: SerializationFormats) -> Self {
+        Self { format, ..self }
+    }
+
+    pub fn with_derivation(self, derivation: HashFunctionCode) -> Self {
+        Self { derivation, ..self }
+    }
+
+    /// Returns SAID of `data` and `data` encoded with the SAID embedded.
+    pub fn saidify<T: Serialize>(
+        &self,
+        data: &T,
+    ) -> Result<(SelfAddressingIdentifier, Vec<u8>), Error> {
+        let mut map = match serde_json::to_value(data) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => {
+                return Err(Error::SerializationError(
+                    "Only maps can be saidified".into(),
+                ))
+            }
+            Err(e) => return Err(Error::SerializationError(e.to_string())),
+        };
+        let derivation = HashFunction::from(self.derivation.clone());
+        map.insert(
+            self.label.clone(),
+            Value::String("#".repeat(derivation.get_len())),
+        );
+        if let Some(mut version) = version(&map) {
+            // Size field has fixed width, so setting it doesn't change size.
+            version.kind = self.format;
+            map.insert("v".into(), Value::String(version.to_str()));
+            version.size = encode(&self.format, &map)?.len();
+            map.insert("v".into(), Value::String(version.to_str()));
+        }
+
+        let said = derivation.derive(&encode(&self.format, &map)?);
+        map.insert(self.label.clone(), Value::String(said.to_string()));
+        Ok((said, encode(&self.format, &map)?))
+    }
+
+    /// Checks if SAID embedded in `bytes` is the digest of the data. The
+    /// data is expected to be encoded the way `saidify` encodes it.
+    pub fn verify(&self, bytes: &[u8]) -> Result<bool, Error> {
+        let mut map: Map<String, Value> = decode(&self.format, bytes)?;
+        let said: SelfAddressingIdentifier = match map.get(&self.label) {
+            Some(Value::String(said)) => said.parse()?,
+            _ => return Ok(false),
+        };
+        map.insert(
+            self.label.clone(),
+            Value::String("#".repeat(said.derivation.get_len())),
+        );
+        Ok(said.verify_binding(&encode(&self.format, &map)?))
+    }
+}
+
+/// Parses version string in `v` field, if there is one.
+fn version(map: &Map<String, Value>) -> Option<SerializationInfo> {
+    match map.get("v") {
+        // Parsing slices the string, so check it fits first.
+        Some(Value::String(v)) if v.len() == 17 && v.is_ascii() => v.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{event::KeyEvent, event_message::msg::KeriEvent};
+
+    #[test]
+    fn test_saidify() -> Result<(), Error> {
+        let data = json!({"d": "", "name": "anchored document", "items": [1, 2]});
+        let (said, bytes) = Saidify::new().saidify(&data)?;
+        let expected = format!(
+            r#"{{"d":"{}","name":"anchored document","items":[1,2]}}"#,
+            said
+        );
+        assert_eq!(bytes, expected.as_bytes());
+        assert!(Saidify::new().verify(&bytes)?);
+        let changed = expected.replace("anchored", "changed");
+        assert!(!Saidify::new().verify(changed.as_bytes())?);
+
+        // Field is appended if missing, and may be named differently.
+        let saidify = Saidify::new()
+            .with_label("i")
+            .with_derivation(HashFunctionCode::SHA3_256);
+        let (said, bytes) = saidify.saidify(&json!({"name": "document"}))?;
+        assert_eq!(said.derivation, HashFunctionCode::SHA3_256.into());
+        let value: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(value, json!({"name": "document", "i": said.to_string()}));
+        assert!(saidify.verify(&bytes)?);
+
+        #[cfg(all(feature = "cbor", feature = "mgpk"))]
+        for format in [SerializationFormats::CBOR, SerializationFormats::MGPK] {
+            let saidify = Saidify::new().with_format(format);
+            let (_, bytes) = saidify.saidify(&data)?;
+            assert!(saidify.verify(&bytes)?);
+        }
+
+        assert!(Saidify::new().saidify(&json!(["d"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_saidify_version() -> Result<(), Error> {
+        // Rotation event, SAID of which is its digest.
+        let rot = br#"{"v":"KERI10JSON000160_","t":"rot","d":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"1","p":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","kt":"1","k":["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"],"nt":"1","n":["EBrEok_A-yJGpR9GH_ktdd11x3UR0cHaCg0nzAnYLgGj"],"bt":"0","br":[],"ba":[],"a":[]}"#;
+        let event: KeriEvent<KeyEvent> = serde_json::from_slice(rot).unwrap();
+        assert!(Saidify::new().verify(rot)?);
+
+        // Size and format in version string are set.
+        let mut value: Value = serde_json::from_slice(rot).unwrap();
+        value["v"] = json!("KERI10CBOR000000_");
+        value["d"] = json!("");
+        let (said, bytes) = Saidify::new().saidify(&value)?;
+        assert_eq!(said, event.digest()?);
+        assert_eq!(bytes, rot.to_vec());
+        Ok(())
+    }
+}
@@ -58,6 +58,23 @@ pub enum SignerData {
     JustSignatures,
 }
 
+impl Nontransferable {
+    /// Splits receipt into receipts holding single signature each, so every
+    /// (witness, signature) pair can be stored and deduplicated separately.
+    pub fn split(self) -> Vec<Nontransferable> {
+        match self {
+            Nontransferable::Indexed(sigs) => sigs
+                .into_iter()
+                .map(|sig| Nontransferable::Indexed(vec![sig]))
+                .collect(),
+            Nontransferable::Couplet(couplets) => couplets
+                .into_iter()
+                .map(|couplet| Nontransferable::Couplet(vec![couplet]))
+                .collect(),
+        }
+    }
+}
+
 impl SignerData {
     pub fn get_signer(&self) -> Option<IdentifierPrefix> {
         match self {
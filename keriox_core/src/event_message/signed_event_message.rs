@@ -1,7 +1,10 @@
 use cesrox::{group::Group, ParsedData};
 use serde::{ser::SerializeStruct, Deserialize, Serialize};
 
-use super::{msg::KeriEvent, serializer::to_string, signature::Nontransferable};
+use super::{
+    cesr_version::CesrVersion, msg::KeriEvent, protocol_version::ProtocolVersion,
+    signature::Nontransferable,
+};
 #[cfg(feature = "query")]
 use crate::query::{query_event::SignedQueryMessage, reply_event::SignedReply};
 use crate::{
@@ -88,6 +91,13 @@ impl Message {
             .map_err(|_e| Error::CesrError)
     }
 
+    /// Encodes message with attachments counted the way CESR `version`
+    /// does. Stream of CESR 2.0 messages should start with
+    /// `CesrVersion::genus_version`.
+    pub fn to_cesr_with_version(&self, version: CesrVersion) -> Vec<u8> {
+        version.encode(&ParsedData::from(self.clone()))
+    }
+
     pub fn get_prefix(&self) -> IdentifierPrefix {
         match self {
             Message::Notice(notice) => notice.get_prefix(),
@@ -95,6 +105,15 @@ impl Message {
             Message::Op(op) => op.get_prefix(),
         }
     }
+
+    /// KERI protocol version from message version string.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        match self {
+            Message::Notice(notice) => notice.protocol_version(),
+            #[cfg(any(feature = "query", feature = "oobi"))]
+            Message::Op(op) => op.protocol_version(),
+        }
+    }
 }
 
 impl Notice {
@@ -105,6 +124,15 @@ impl Notice {
             Notice::TransferableRct(rct) => rct.body.prefix.clone(),
         }
     }
+
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        let info = match self {
+            Notice::Event(ev) => &ev.event_message.serialization_info,
+            Notice::NontransferableRct(rct) => &rct.body.serialization_info,
+            Notice::TransferableRct(rct) => &rct.body.serialization_info,
+        };
+        info.into()
+    }
 }
 
 #[cfg(feature = "query")]
@@ -118,6 +146,18 @@ impl Op {
             Op::Exchange(exn) => exn.exchange_message.data.data.get_prefix(),
         }
     }
+
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        let info = match self {
+            Op::Reply(reply) => &reply.reply.serialization_info,
+            Op::Query(SignedQueryMessage::KelQuery(qry)) => &qry.query.serialization_info,
+            #[cfg(feature = "mailbox")]
+            Op::Query(SignedQueryMessage::MailboxQuery(qry)) => &qry.query.serialization_info,
+            #[cfg(feature = "mailbox")]
+            Op::Exchange(exn) => &exn.exchange_message.serialization_info,
+        };
+        info.into()
+    }
 }
 
 // KERI serializer should be used to serialize this
@@ -210,7 +250,9 @@ impl SignedEventMessage {
     }
 
     pub fn encode(&self) -> Result<Vec<u8>, Error> {
-        Ok(to_string(&self)?.as_bytes().to_vec())
+        ParsedData::from(self)
+            .to_cesr()
+            .map_err(|_e| Error::CesrError)
     }
 }
 
@@ -0,0 +1,1014 @@
+//! Strict parsing of CESR streams received from untrusted sources.
+//!
+//! `cesr_adapter::parse_many` is lenient: it stops at the first part of
+//! stream it can't parse, and `cesrox` panics on some malformed
+//! attachments. `StrictParser` consumes the whole stream, or fails with
+//! error telling at which byte it failed and what was expected there. It
+//! frames message bodies and attachment groups itself, within configured
+//! limits, and uses `cesrox` only to decode single primitives.
+
+use std::fmt;
+
+use cesrox::{
+    cesr_proof::{parsers::material_path, MaterialPath},
+    group::Group,
+    payload::Payload,
+    primitives::{
+        codes::{
+            attached_signature_code::AttachedSignatureCode, basic::Basic,
+            self_addressing::SelfAddressing, self_signing::SelfSigning,
+        },
+        parsers::{identifier, parse_primitive, serial_number_parser, timestamp_parser},
+        IndexedSignature,
+    },
+    ParsedData,
+};
+use said::version::{format::SerializationFormats, SerializationInfo};
+use serde::de::IgnoredAny;
+
+use super::{
+    cesr_adapter::ParseError,
+    cesr_stream::DEFAULT_MAX_MESSAGE_SIZE,
+    cesr_version::{counter, genus_version, CesrVersion},
+    event_ref::EventRef,
+    protocol_version::{version_info, VersionError, VersionPolicy},
+};
+
+/// Attachment groups of single message allowed, unless configured otherwise.
+pub const DEFAULT_MAX_ATTACHMENTS: usize = 100;
+
+/// Nesting of attachment groups allowed, unless configured otherwise.
+pub const DEFAULT_MAX_NESTING: usize = 4;
+
+/// Primitives are decoded from windows of that many bytes, longer than the
+/// longest primitive. `cesrox` reads primitive code before checking length
+/// of input, so attachments are padded by window size.
+const PRIMITIVE_WINDOW: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParserLimits {
+    /// Size of message body together with its attachments.
+    pub max_message_size: usize,
+    /// Number of attachment groups of single message, nested ones included.
+    pub max_attachments: usize,
+    /// Depth of groups nested in attachment and pathed material groups.
+    pub max_nesting: usize,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_attachments: DEFAULT_MAX_ATTACHMENTS,
+            max_nesting: DEFAULT_MAX_NESTING,
+        }
+    }
+}
+
+/// Token expected at the place where parsing failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expected {
+    MessageBody,
+    GenusVersion,
+    CountCode,
+    IndexedSignature,
+    Identifier,
+    NontransferableIdentifier,
+    Signature,
+    Digest,
+    SerialNumber,
+    Timestamp,
+    MaterialPath,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Expected::MessageBody => "message body",
+            Expected::GenusVersion => "genus version code",
+            Expected::CountCode => "count code",
+            Expected::IndexedSignature => "indexed signature",
+            Expected::Identifier => "identifier",
+            Expected::NontransferableIdentifier => "non-transferable identifier",
+            Expected::Signature => "signature",
+            Expected::Digest => "digest",
+            Expected::SerialNumber => "serial number",
+            Expected::Timestamp => "timestamp",
+            Expected::MaterialPath => "material path",
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StrictParseError {
+    #[error("Unexpected end of stream at byte {offset}, expected {expected}")]
+    UnexpectedEnd { offset: usize, expected: Expected },
+    #[error("Unexpected input at byte {offset}, expected {expected}")]
+    Unexpected { offset: usize, expected: Expected },
+    #[error("Message at byte {offset} exceeds {limit} bytes")]
+    MessageTooLarge { offset: usize, limit: usize },
+    #[error("Attachment group at byte {offset} exceeds limit of {limit} groups per message")]
+    TooManyAttachments { offset: usize, limit: usize },
+    #[error("Attachment group at byte {offset} exceeds nesting limit of {limit}")]
+    TooDeep { offset: usize, limit: usize },
+    #[error("Invalid message body at byte {offset}: {reason}")]
+    InvalidBody { offset: usize, reason: String },
+    #[error("Invalid message at byte {offset}: {source}")]
+    InvalidMessage { offset: usize, source: ParseError },
+    #[error("Unsupported message version at byte {offset}: {source}")]
+    UnsupportedVersion { offset: usize, source: VersionError },
+}
+
+impl StrictParseError {
+    /// Stream offset where the error was found.
+    pub fn offset(&self) -> usize {
+        match self {
+            StrictParseError::UnexpectedEnd { offset, .. }
+            | StrictParseError::Unexpected { offset, .. }
+            | StrictParseError::MessageTooLarge { offset, .. }
+            | StrictParseError::TooManyAttachments { offset, .. }
+            | StrictParseError::TooDeep { offset, .. }
+            | StrictParseError::InvalidBody { offset, .. }
+            | StrictParseError::InvalidMessage { offset, .. }
+            | StrictParseError::UnsupportedVersion { offset, .. } => *offset,
+        }
+    }
+}
+
+/// Parses complete CESR stream within configured limits. Stream is CESR 1.0
+/// one, unless configured otherwise or genus version code changes it.
+/// Messages of newer KERI minor version are rejected, unless configured
+/// otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct StrictParser {
+    limits: ParserLimits,
+    version: CesrVersion,
+    version_policy: VersionPolicy,
+}
+
+impl StrictParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(mut self, limits: ParserLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_version(mut self, version: CesrVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn with_version_policy(mut self, policy: VersionPolicy) -> Self {
+        self.version_policy = policy;
+        self
+    }
+
+    /// Parses all messages of the stream. Whitespace between messages is
+    /// skipped.
+    pub fn parse_many(&self, stream: &[u8]) -> Result<Vec<ParsedData>, StrictParseError> {
+        Ok(self
+            .parse_borrowed(stream)?
+            .into_iter()
+            .map(ParsedData::from)
+            .collect())
+    }
+
+    /// Parses all messages of the stream and converts them into `T`.
+    pub fn parse_messages<T>(&self, stream: &[u8]) -> Result<Vec<T>, StrictParseError>
+    where
+        T: TryFrom<ParsedData, Error = ParseError>,
+    {
+        self.parse_borrowed(stream)?
+            .into_iter()
+            .map(|message| {
+                let offset = message.offset;
+                T::try_from(message.into())
+                    .map_err(|source| StrictParseError::InvalidMessage { offset, source })
+            })
+            .collect()
+    }
+
+    /// Parses all messages of the stream without copying their bodies.
+    pub fn parse_borrowed<'a>(
+        &self,
+        stream: &'a [u8],
+    ) -> Result<Vec<ParsedRef<'a>>, StrictParseError> {
+        let mut version = self.version;
+        let mut messages = vec![];
+        let mut offset = 0;
+        loop {
+            offset += stream[offset..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_whitespace())
+                .count();
+            if offset == stream.len() {
+                break;
+            }
+            match genus(&stream[offset..]) {
+                Genus::Version(next) => {
+                    version = next;
+                    offset += next.genus_version().len();
+                }
+                Genus::Truncated => {
+                    return Err(StrictParseError::UnexpectedEnd {
+                        offset: stream.len(),
+                        expected: Expected::GenusVersion,
+                    })
+                }
+                Genus::Invalid => {
+                    return Err(StrictParseError::Unexpected {
+                        offset,
+                        expected: Expected::GenusVersion,
+                    })
+                }
+                Genus::None => {
+                    let (next, message) = self.message(stream, offset, &mut version)?;
+                    messages.push(message);
+                    offset = next;
+                }
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Parses message starting at `offset`. Returns offset of its end.
+    fn message<'a>(
+        &self,
+        stream: &'a [u8],
+        offset: usize,
+        version: &mut CesrVersion,
+    ) -> Result<(usize, ParsedRef<'a>), StrictParseError> {
+        let limit = self.limits.max_message_size;
+        let window_end = stream.len().min(offset.saturating_add(limit));
+        let window = &stream[offset..window_end];
+
+        let format = body_format(stream[offset]).ok_or(StrictParseError::Unexpected {
+            offset,
+            expected: Expected::MessageBody,
+        })?;
+        let body_len = match body_length(&format, window) {
+            Ok(Some(len)) => len,
+            Ok(None) if window_end < stream.len() => {
+                return Err(StrictParseError::MessageTooLarge { offset, limit })
+            }
+            Ok(None) => {
+                return Err(StrictParseError::UnexpectedEnd {
+                    offset: stream.len(),
+                    expected: Expected::MessageBody,
+                })
+            }
+            Err(reason) => return Err(StrictParseError::InvalidBody { offset, reason }),
+        };
+        let body = &window[..body_len];
+        let info = check_version(&format, body)
+            .map_err(|reason| StrictParseError::InvalidBody { offset, reason })?;
+        self.version_policy
+            .check(&info)
+            .map_err(|source| StrictParseError::UnsupportedVersion { offset, source })?;
+
+        let start = offset + body_len;
+        let len = window[body_len..]
+            .iter()
+            .take_while(|byte| is_base64(**byte))
+            .count();
+        let end = if start + len == stream.len() {
+            End::Stream
+        } else if start + len == window_end && is_base64(stream[window_end]) {
+            End::Limit
+        } else {
+            End::Other
+        };
+        let mut attachments = Attachments {
+            text: [&stream[start..start + len], &[b'A'; PRIMITIVE_WINDOW][..]].concat(),
+            len,
+            start,
+            end,
+            message: offset,
+            limits: &self.limits,
+            groups: 0,
+        };
+        let (consumed, groups) = attachments.parse(version)?;
+
+        Ok((
+            start + consumed,
+            ParsedRef {
+                offset,
+                format,
+                body,
+                attachments: groups,
+            },
+        ))
+    }
+}
+
+/// Message framed by `StrictParser`, with body borrowed from the stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedRef<'a> {
+    /// Stream offset of message body.
+    pub offset: usize,
+    pub format: SerializationFormats,
+    pub body: &'a [u8],
+    pub attachments: Vec<Group>,
+}
+
+impl<'a> ParsedRef<'a> {
+    /// Decodes fields of message body, borrowing them from the stream.
+    pub fn event(&self) -> Result<EventRef<'a>, ParseError> {
+        EventRef::parse(self.format, self.body)
+    }
+}
+
+impl From<ParsedRef<'_>> for ParsedData {
+    fn from(message: ParsedRef<'_>) -> Self {
+        let payload = match message.format {
+            SerializationFormats::JSON => Payload::JSON(message.body.to_vec()),
+            SerializationFormats::CBOR => Payload::CBOR(message.body.to_vec()),
+            SerializationFormats::MGPK => Payload::MGPK(message.body.to_vec()),
+        };
+        ParsedData {
+            payload,
+            attachments: message.attachments,
+        }
+    }
+}
+
+enum Genus {
+    None,
+    Version(CesrVersion),
+    Truncated,
+    Invalid,
+}
+
+/// Checks for genus version code at the beginning of input. `-_` isn't
+/// code of any attachment group, so it always starts genus version code.
+fn genus(input: &[u8]) -> Genus {
+    let len = CesrVersion::V1.genus_version().len();
+    if !input.starts_with(b"-_") {
+        Genus::None
+    } else if input.len() < len {
+        Genus::Truncated
+    } else {
+        match genus_version(&input[..len]) {
+            Ok((_, version)) => Genus::Version(version),
+            Err(_) => Genus::Invalid,
+        }
+    }
+}
+
+fn is_base64(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_')
+}
+
+/// Tells message body format by its first byte. Bodies are maps.
+fn body_format(byte: u8) -> Option<SerializationFormats> {
+    if byte == b'{' {
+        return Some(SerializationFormats::JSON);
+    }
+    match byte >> 5 {
+        0b101 => Some(SerializationFormats::CBOR),
+        0b100 | 0b110 => Some(SerializationFormats::MGPK),
+        _ => None,
+    }
+}
+
+/// Finds length of message body at the beginning of window. Returns `None`
+/// if body doesn't end within it.
+fn body_length(format: &SerializationFormats, window: &[u8]) -> Result<Option<usize>, String> {
+    match format {
+        SerializationFormats::JSON => {
+            let mut bodies = serde_json::Deserializer::from_slice(window).into_iter::<IgnoredAny>();
+            match bodies.next() {
+                Some(Ok(_)) => Ok(Some(bodies.byte_offset())),
+                Some(Err(e)) if e.is_eof() => Ok(None),
+                Some(Err(e)) => Err(e.to_string()),
+                None => Ok(None),
+            }
+        }
+        #[cfg(feature = "cbor")]
+        SerializationFormats::CBOR => {
+            let mut deserializer = serde_cbor::Deserializer::from_slice(window);
+            match <IgnoredAny as serde::Deserialize>::deserialize(&mut deserializer) {
+                Ok(_) => Ok(Some(deserializer.byte_offset())),
+                Err(e) if e.is_eof() => Ok(None),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        #[cfg(feature = "mgpk")]
+        SerializationFormats::MGPK => {
+            let mut deserializer = rmp_serde::Deserializer::new(std::io::Cursor::new(window));
+            match <IgnoredAny as serde::Deserialize>::deserialize(&mut deserializer) {
+                Ok(_) => Ok(Some(deserializer.position() as usize)),
+                Err(rmp_serde::decode::Error::InvalidMarkerRead(e))
+                | Err(rmp_serde::decode::Error::InvalidDataRead(e))
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    Ok(None)
+                }
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        #[allow(unreachable_patterns)]
+        format => Err(super::encoding::unsupported(format)),
+    }
+}
+
+/// Checks that version string of message body declares its format and size.
+fn check_version(format: &SerializationFormats, body: &[u8]) -> Result<SerializationInfo, String> {
+    let info = version_info(format, body).map_err(|e| e.to_string())?;
+    if info.kind != *format {
+        Err(format!(
+            "Version string declares {} body, but it is {}",
+            info.kind.to_str(),
+            format.to_str()
+        ))
+    } else if info.size != body.len() {
+        Err(format!(
+            "Version string declares {} bytes, but body has {}",
+            info.size,
+            body.len()
+        ))
+    } else {
+        Ok(info)
+    }
+}
+
+/// What follows attachments text of the message.
+#[derive(Clone, Copy, PartialEq)]
+enum End {
+    Stream,
+    /// More text, beyond message size limit.
+    Limit,
+    /// Next message or other binary data.
+    Other,
+}
+
+#[derive(Clone, Copy)]
+enum GroupKind {
+    ControllerSignatures,
+    WitnessSignatures,
+    NontransReceiptCouples,
+    FirstSeenReplyCouples,
+    TransIndexedSigGroups,
+    LastEstSignaturesGroups,
+    SourceSealCouples,
+    PathedMaterial,
+    Frame,
+}
+
+impl GroupKind {
+    fn from_code(code: u8, version: CesrVersion) -> Option<Self> {
+        match (version, code) {
+            (CesrVersion::V1, b'A') | (CesrVersion::V2, b'K') => Some(Self::ControllerSignatures),
+            (CesrVersion::V1, b'B') | (CesrVersion::V2, b'L') => Some(Self::WitnessSignatures),
+            (CesrVersion::V1, b'C') | (CesrVersion::V2, b'M') => Some(Self::NontransReceiptCouples),
+            (CesrVersion::V1, b'E') | (CesrVersion::V2, b'O') => Some(Self::FirstSeenReplyCouples),
+            (CesrVersion::V1, b'F') | (CesrVersion::V2, b'P') => Some(Self::TransIndexedSigGroups),
+            (CesrVersion::V1, b'H') | (CesrVersion::V2, b'Q') => {
+                Some(Self::LastEstSignaturesGroups)
+            }
+            (CesrVersion::V1, b'G') | (CesrVersion::V2, b'R') => Some(Self::SourceSealCouples),
+            (CesrVersion::V1, b'L') | (CesrVersion::V2, b'T') => Some(Self::PathedMaterial),
+            (CesrVersion::V1, b'V') | (CesrVersion::V2, b'A') | (CesrVersion::V2, b'C') => {
+                Some(Self::Frame)
+            }
+            _ => None,
+        }
+    }
+
+    /// First token of group material.
+    fn expected(&self) -> Expected {
+        match self {
+            GroupKind::ControllerSignatures | GroupKind::WitnessSignatures => {
+                Expected::IndexedSignature
+            }
+            GroupKind::NontransReceiptCouples => Expected::NontransferableIdentifier,
+            GroupKind::FirstSeenReplyCouples | GroupKind::SourceSealCouples => {
+                Expected::SerialNumber
+            }
+            GroupKind::TransIndexedSigGroups | GroupKind::LastEstSignaturesGroups => {
+                Expected::Identifier
+            }
+            GroupKind::PathedMaterial => Expected::MaterialPath,
+            GroupKind::Frame => Expected::CountCode,
+        }
+    }
+}
+
+/// CESR 1.0 counts group elements, except for groups of groups, which
+/// count quadlets, as CESR 2.0 does for all groups.
+enum Count {
+    Elements(usize),
+    Until(usize),
+}
+
+/// Attachments text of single message. Positions are relative to its
+/// start.
+struct Attachments<'a> {
+    /// Attachments text, padded by primitive window.
+    text: Vec<u8>,
+    len: usize,
+    /// Stream offset of attachments text.
+    start: usize,
+    end: End,
+    /// Stream offset of message.
+    message: usize,
+    limits: &'a ParserLimits,
+    groups: usize,
+}
+
+type Parsed<T> = Result<(usize, T), StrictParseError>;
+
+impl Attachments<'_> {
+    /// Parses attachment groups and genus version codes between them.
+    /// Returns length of parsed text.
+    fn parse(&mut self, version: &mut CesrVersion) -> Parsed<Vec<Group>> {
+        let mut pos = 0;
+        let mut groups = vec![];
+        while pos < self.len && self.text[pos] == b'-' {
+            match genus(&self.text[pos..self.len]) {
+                Genus::Version(next) => {
+                    *version = next;
+                    pos += next.genus_version().len();
+                }
+                Genus::Truncated => return Err(self.end_error(Expected::GenusVersion)),
+                Genus::Invalid => return Err(self.unexpected(pos, Expected::GenusVersion)),
+                Genus::None => {
+                    let (next, group) = self.group(pos, self.len, *version, 0)?;
+                    groups.push(group);
+                    pos = next;
+                }
+            }
+        }
+        if pos == self.len && self.end == End::Limit {
+            return Err(self.end_error(Expected::CountCode));
+        }
+        Ok((pos, groups))
+    }
+
+    fn group(
+        &mut self,
+        pos: usize,
+        limit: usize,
+        version: CesrVersion,
+        depth: usize,
+    ) -> Parsed<Group> {
+        self.groups += 1;
+        if self.groups > self.limits.max_attachments {
+            return Err(StrictParseError::TooManyAttachments {
+                offset: self.start + pos,
+                limit: self.limits.max_attachments,
+            });
+        }
+        if depth > self.limits.max_nesting {
+            return Err(StrictParseError::TooDeep {
+                offset: self.start + pos,
+                limit: self.limits.max_nesting,
+            });
+        }
+        let (material, (kind, count)) = self.count_code(pos, limit, version)?;
+
+        Ok(match kind {
+            GroupKind::ControllerSignatures => {
+                let (end, sigs) = self.signatures(material, limit, count)?;
+                (end, Group::IndexedControllerSignatures(sigs))
+            }
+            GroupKind::WitnessSignatures => {
+                let (end, sigs) = self.signatures(material, limit, count)?;
+                (end, Group::IndexedWitnessSignatures(sigs))
+            }
+            GroupKind::NontransReceiptCouples => {
+                let (end, couples) = self.elements(material, limit, count, |p, pos, limit| {
+                    let (pos, key) = p.primitive(
+                        pos,
+                        limit,
+                        Expected::NontransferableIdentifier,
+                        parse_primitive::<Basic>,
+                    )?;
+                    let (pos, sig) = p.primitive(
+                        pos,
+                        limit,
+                        Expected::Signature,
+                        parse_primitive::<SelfSigning>,
+                    )?;
+                    Ok((pos, (key, sig)))
+                })?;
+                (end, Group::NontransReceiptCouples(couples))
+            }
+            GroupKind::FirstSeenReplyCouples => {
+                let (end, couples) = self.elements(material, limit, count, |p, pos, limit| {
+                    let (pos, sn) =
+                        p.primitive(pos, limit, Expected::SerialNumber, serial_number_parser)?;
+                    let (pos, timestamp) =
+                        p.primitive(pos, limit, Expected::Timestamp, timestamp_parser)?;
+                    Ok((pos, (sn, timestamp)))
+                })?;
+                (end, Group::FirstSeenReplyCouples(couples))
+            }
+            GroupKind::SourceSealCouples => {
+                let (end, couples) = self.elements(material, limit, count, |p, pos, limit| {
+                    let (pos, sn) =
+                        p.primitive(pos, limit, Expected::SerialNumber, serial_number_parser)?;
+                    let (pos, digest) = p.primitive(
+                        pos,
+                        limit,
+                        Expected::Digest,
+                        parse_primitive::<SelfAddressing>,
+                    )?;
+                    Ok((pos, (sn, digest)))
+                })?;
+                (end, Group::SourceSealCouples(couples))
+            }
+            GroupKind::TransIndexedSigGroups => {
+                let (end, groups) = self.elements(material, limit, count, |p, pos, limit| {
+                    let (pos, id) = p.primitive(pos, limit, Expected::Identifier, identifier)?;
+                    let (pos, sn) =
+                        p.primitive(pos, limit, Expected::SerialNumber, serial_number_parser)?;
+                    let (pos, digest) = p.primitive(
+                        pos,
+                        limit,
+                        Expected::Digest,
+                        parse_primitive::<SelfAddressing>,
+                    )?;
+                    let (pos, sigs) = p.controller_signatures(pos, limit, version)?;
+                    Ok((pos, (id, sn, digest, sigs)))
+                })?;
+                (end, Group::TransIndexedSigGroups(groups))
+            }
+            GroupKind::LastEstSignaturesGroups => {
+                let (end, couples) = self.elements(material, limit, count, |p, pos, limit| {
+                    let (pos, id) = p.primitive(pos, limit, Expected::Identifier, identifier)?;
+                    let (pos, sigs) = p.controller_signatures(pos, limit, version)?;
+                    Ok((pos, (id, sigs)))
+                })?;
+                (end, Group::LastEstSignaturesGroups(couples))
+            }
+            GroupKind::PathedMaterial => {
+                let end = match count {
+                    Count::Until(end) => end,
+                    Count::Elements(_) => unreachable!("pathed material counts quadlets"),
+                };
+                let (pos, path) = self.path(material, end)?;
+                let (end, groups) =
+                    self.elements(pos, end, Count::Until(end), |p, pos, limit| {
+                        p.group(pos, limit, version, depth + 1)
+                    })?;
+                (end, Group::PathedMaterialQuadruplet(path, groups))
+            }
+            GroupKind::Frame => {
+                let (end, groups) = self.elements(material, limit, count, |p, pos, limit| {
+                    p.group(pos, limit, version, depth + 1)
+                })?;
+                (end, Group::Frame(groups))
+            }
+        })
+    }
+
+    /// Parses count code of group. Returns position of its material.
+    fn count_code(
+        &self,
+        pos: usize,
+        limit: usize,
+        version: CesrVersion,
+    ) -> Parsed<(GroupKind, Count)> {
+        let input = &self.text[pos..limit];
+        // CESR 1.0 big count codes aren't supported.
+        let big = input.starts_with(b"--");
+        if !input.starts_with(b"-") || (big && version == CesrVersion::V1) {
+            return Err(self.unexpected(pos, Expected::CountCode));
+        }
+        let header_len = if big { 8 } else { 4 };
+        if input.len() < header_len {
+            return Err(self.missing(pos, limit, Expected::CountCode));
+        }
+        let (code, count) = counter(&input[..header_len])
+            .map_err(|_| self.unexpected(pos, Expected::CountCode))?
+            .1;
+        let kind = GroupKind::from_code(code, version)
+            .ok_or_else(|| self.unexpected(pos, Expected::CountCode))?;
+
+        let material = pos + header_len;
+        let count = match (version, kind) {
+            (CesrVersion::V1, GroupKind::PathedMaterial | GroupKind::Frame)
+            | (CesrVersion::V2, _) => {
+                let end = material + count * 4;
+                if end > limit {
+                    return Err(self.missing(pos, limit, kind.expected()));
+                }
+                Count::Until(end)
+            }
+            (CesrVersion::V1, _) => Count::Elements(count),
+        };
+        Ok((material, (kind, count)))
+    }
+
+    fn elements<T>(
+        &mut self,
+        mut pos: usize,
+        limit: usize,
+        count: Count,
+        mut element: impl FnMut(&mut Self, usize, usize) -> Parsed<T>,
+    ) -> Parsed<Vec<T>> {
+        let mut elements = vec![];
+        match count {
+            Count::Elements(n) => {
+                for _ in 0..n {
+                    let (next, parsed) = element(self, pos, limit)?;
+                    elements.push(parsed);
+                    pos = next;
+                }
+            }
+            Count::Until(end) => {
+                while pos < end {
+                    let (next, parsed) = element(self, pos, end)?;
+                    elements.push(parsed);
+                    pos = next;
+                }
+            }
+        }
+        Ok((pos, elements))
+    }
+
+    fn signatures(
+        &mut self,
+        pos: usize,
+        limit: usize,
+        count: Count,
+    ) -> Parsed<Vec<IndexedSignature>> {
+        self.elements(pos, limit, count, |p, pos, limit| {
+            // `cesrox` panics on Ed448 signatures with both indexes same.
+            if p.text[pos..].starts_with(b"3A") {
+                return Err(p.unexpected(pos, Expected::IndexedSignature));
+            }
+            p.primitive(
+                pos,
+                limit,
+                Expected::IndexedSignature,
+                parse_primitive::<AttachedSignatureCode>,
+            )
+        })
+    }
+
+    /// Parses controller signatures group nested in group element.
+    fn controller_signatures(
+        &mut self,
+        pos: usize,
+        limit: usize,
+        version: CesrVersion,
+    ) -> Parsed<Vec<IndexedSignature>> {
+        match self.count_code(pos, limit, version)? {
+            (material, (GroupKind::ControllerSignatures, count)) => {
+                self.signatures(material, limit, count)
+            }
+            _ => Err(self.unexpected(pos, Expected::CountCode)),
+        }
+    }
+
+    fn path(&self, pos: usize, end: usize) -> Parsed<MaterialPath> {
+        match material_path(&self.text[pos..end]) {
+            Ok((rest, path)) => Ok((end - rest.len(), path)),
+            Err(_) => Err(self.unexpected(pos, Expected::MaterialPath)),
+        }
+    }
+
+    /// Decodes single primitive, which must end before `limit`.
+    fn primitive<O>(
+        &self,
+        pos: usize,
+        limit: usize,
+        expected: Expected,
+        parser: impl FnOnce(&[u8]) -> nom::IResult<&[u8], O>,
+    ) -> Parsed<O> {
+        if pos >= limit {
+            return Err(self.missing(pos, limit, expected));
+        }
+        let window = &self.text[pos..pos + PRIMITIVE_WINDOW];
+        match parser(window) {
+            Ok((rest, value)) => {
+                let end = pos + window.len() - rest.len();
+                if end > limit {
+                    Err(self.missing(pos, limit, expected))
+                } else {
+                    Ok((end, value))
+                }
+            }
+            Err(_) => Err(self.unexpected(pos, expected)),
+        }
+    }
+
+    fn unexpected(&self, pos: usize, expected: Expected) -> StrictParseError {
+        StrictParseError::Unexpected {
+            offset: self.start + pos,
+            expected,
+        }
+    }
+
+    /// Element at `pos` doesn't fit before `limit`, which is either end of
+    /// enclosing group or of attachments text.
+    fn missing(&self, pos: usize, limit: usize, expected: Expected) -> StrictParseError {
+        if limit < self.len {
+            self.unexpected(pos, expected)
+        } else {
+            self.end_error(expected)
+        }
+    }
+
+    fn end_error(&self, expected: Expected) -> StrictParseError {
+        match self.end {
+            End::Stream => StrictParseError::UnexpectedEnd {
+                offset: self.start + self.len,
+                expected,
+            },
+            End::Limit => StrictParseError::MessageTooLarge {
+                offset: self.message,
+                limit: self.limits.max_message_size,
+            },
+            End::Other => self.unexpected(self.len, expected),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_message::{cesr_adapter::parse_many, signed_event_message::Message};
+
+    const KEL: &[u8] = br#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD{"v":"KERI10JSON000160_","t":"rot","d":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"1","p":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","kt":"1","k":["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"],"nt":"1","n":["EBrEok_A-yJGpR9GH_ktdd11x3UR0cHaCg0nzAnYLgGj"],"bt":"0","br":[],"ba":[],"a":[]}-AABAADLgLBVFeCOP8t-sxOWKif-JbQ-PnOz0W7aZCuLPOUEri-OdGXjOV2d3y6-R_SsS2U3toE3TNVJ9UyO5NhBSkkO"#;
+
+    const ICP_LEN: usize = 0x159;
+    const ROT_START: usize = ICP_LEN + 92;
+
+    fn limits(change: impl FnOnce(&mut ParserLimits)) -> StrictParser {
+        let mut limits = ParserLimits::default();
+        change(&mut limits);
+        StrictParser::new().with_limits(limits)
+    }
+
+    #[test]
+    fn test_strict_parse() {
+        let (_, expected) = parse_many(KEL).unwrap();
+        let parser = StrictParser::new();
+        assert_eq!(parser.parse_many(KEL).unwrap(), expected);
+        assert_eq!(parser.parse_messages::<Message>(KEL).unwrap().len(), 2);
+
+        // CESR 2.0 stream.
+        let stream = [
+            CesrVersion::V2.genus_version().into_bytes(),
+            expected
+                .iter()
+                .flat_map(|data| CesrVersion::V2.encode(data))
+                .collect(),
+        ]
+        .concat();
+        assert_eq!(parser.parse_many(&stream).unwrap(), expected);
+
+        // Nested attachment groups: frame of frame of signatures.
+        let stream = [&KEL[..ICP_LEN], b"-VAY-VAX", &KEL[ICP_LEN..ROT_START]].concat();
+        let parsed = parser.parse_many(&stream).unwrap();
+        assert_eq!(
+            parsed[0].attachments,
+            vec![Group::Frame(vec![Group::Frame(
+                expected[0].attachments.clone()
+            )])]
+        );
+    }
+
+    #[test]
+    fn test_strict_parse_errors() {
+        let parser = StrictParser::new();
+
+        assert!(matches!(
+            parser.parse_many(&KEL[..100]),
+            Err(StrictParseError::UnexpectedEnd {
+                offset: 100,
+                expected: Expected::MessageBody
+            })
+        ));
+        assert!(matches!(
+            parser.parse_many(&KEL[..KEL.len() - 10]),
+            Err(StrictParseError::UnexpectedEnd {
+                offset,
+                expected: Expected::IndexedSignature
+            }) if offset == KEL.len() - 10
+        ));
+
+        // Signature missing before the next message.
+        let stream = [&KEL[..ROT_START - 88], &KEL[ROT_START..]].concat();
+        assert!(matches!(
+            parser.parse_many(&stream),
+            Err(StrictParseError::Unexpected {
+                offset,
+                expected: Expected::IndexedSignature
+            }) if offset == ROT_START - 88
+        ));
+
+        // Unknown count code, which `cesrox` doesn't handle.
+        let stream = [&KEL[..ROT_START], b"-DAA", &KEL[ROT_START..]].concat();
+        let error = parser.parse_many(&stream).unwrap_err();
+        assert!(matches!(
+            error,
+            StrictParseError::Unexpected {
+                offset: ROT_START,
+                expected: Expected::CountCode
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Unexpected input at byte {}, expected count code",
+                ROT_START
+            )
+        );
+
+        let stream = [&KEL[..ICP_LEN + 4], b"3A", &KEL[ICP_LEN + 6..]].concat();
+        assert!(matches!(
+            parser.parse_many(&stream),
+            Err(StrictParseError::Unexpected {
+                offset,
+                expected: Expected::IndexedSignature
+            }) if offset == ICP_LEN + 4
+        ));
+
+        // Anything else than message after attachments.
+        let stream = [KEL, b"AAAA"].concat();
+        assert!(matches!(
+            parser.parse_many(&stream),
+            Err(StrictParseError::Unexpected {
+                offset,
+                expected: Expected::MessageBody
+            }) if offset == KEL.len()
+        ));
+
+        // Version string has to match body.
+        let stream = String::from_utf8(KEL.to_vec())
+            .unwrap()
+            .replacen("000159", "000158", 1);
+        assert!(matches!(
+            parser.parse_many(stream.as_bytes()),
+            Err(StrictParseError::InvalidBody { offset: 0, .. })
+        ));
+
+        // Newer minor version is accepted only if configured so.
+        let stream = String::from_utf8(KEL.to_vec())
+            .unwrap()
+            .replace("KERI10", "KERI11");
+        assert!(matches!(
+            parser.parse_many(stream.as_bytes()),
+            Err(StrictParseError::UnsupportedVersion {
+                offset: 0,
+                source: VersionError::NewerMinorVersion(_)
+            })
+        ));
+        let parser = StrictParser::new().with_version_policy(VersionPolicy::BestEffort);
+        assert_eq!(parser.parse_many(stream.as_bytes()).unwrap().len(), 2);
+        let stream = String::from_utf8(KEL.to_vec())
+            .unwrap()
+            .replace("KERI10", "KERI20");
+        assert!(matches!(
+            parser.parse_many(stream.as_bytes()),
+            Err(StrictParseError::UnsupportedVersion {
+                offset: 0,
+                source: VersionError::UnsupportedMajorVersion(_)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_strict_parse_limits() {
+        let parser = limits(|limits| limits.max_message_size = ICP_LEN - 1);
+        assert!(matches!(
+            parser.parse_many(KEL),
+            Err(StrictParseError::MessageTooLarge { offset: 0, .. })
+        ));
+        let parser = limits(|limits| limits.max_message_size = ROT_START - 1);
+        assert!(matches!(
+            parser.parse_many(KEL),
+            Err(StrictParseError::MessageTooLarge { offset: 0, .. })
+        ));
+        let parser = limits(|limits| limits.max_message_size = KEL.len() - ROT_START);
+        assert_eq!(parser.parse_many(KEL).unwrap().len(), 2);
+
+        let parser = limits(|limits| limits.max_attachments = 0);
+        assert!(matches!(
+            parser.parse_many(KEL),
+            Err(StrictParseError::TooManyAttachments {
+                offset: ICP_LEN,
+                limit: 0
+            })
+        ));
+
+        let stream = [&KEL[..ICP_LEN], b"-VAY-VAX", &KEL[ICP_LEN..ROT_START]].concat();
+        let parser = limits(|limits| limits.max_nesting = 1);
+        assert!(matches!(
+            parser.parse_many(&stream),
+            Err(StrictParseError::TooDeep { offset, limit: 1 }) if offset == ICP_LEN + 8
+        ));
+        let parser = limits(|limits| limits.max_attachments = 2);
+        assert!(matches!(
+            parser.parse_many(&stream),
+            Err(StrictParseError::TooManyAttachments { offset, .. }) if offset == ICP_LEN + 8
+        ));
+    }
+}
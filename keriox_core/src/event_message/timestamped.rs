@@ -1,4 +1,4 @@
-use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use chrono::{DateTime, FixedOffset, SecondsFormat, SubsecRound, Utc};
 use serde::{Deserialize, Serialize, Serializer};
 
 use super::Typeable;
@@ -23,7 +23,9 @@ where
 
 impl<T: Serialize, D: Serialize + Typeable<TypeTag = T> + Clone> Timestamped<D> {
     pub fn new(data: D) -> Self {
-        let timestamp: DateTime<FixedOffset> = Utc::now().into();
+        // Truncated to precision of serialized timestamp, so that message
+        // equals itself after encoding and parsing.
+        let timestamp: DateTime<FixedOffset> = Utc::now().trunc_subsecs(6).into();
         Timestamped { timestamp, data }
     }
 }
@@ -1,8 +1,16 @@
 use ed25519_dalek::{Signer, Verifier};
 use k256::ecdsa::{signature::Signer as EcdsaSigner, Signature as EcdsaSignature, SigningKey};
 use k256::ecdsa::{signature::Verifier as EcdsaVerifier, VerifyingKey};
+use std::sync::Mutex;
+
+use rand::{
+    rngs::{OsRng, StdRng},
+    RngCore, SeedableRng,
+};
 use serde_derive::{Deserialize, Serialize};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{prefix::SeedPrefix, signer::KeyType};
 
 #[derive(Debug, thiserror::Error, Serialize, Deserialize)]
 pub enum KeysError {
@@ -78,7 +86,7 @@ impl PublicKey {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(PartialEq, Clone)]
 pub struct PrivateKey {
     key: Vec<u8>,
 }
@@ -113,6 +121,126 @@ impl Drop for PrivateKey {
     }
 }
 
+/// Source of randomness of generated keys.
+pub trait KeySource: Send + Sync {
+    fn fill_bytes(&self, dest: &mut [u8]);
+}
+
+/// Randomness of operating system. Default source of keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsKeySource;
+
+impl KeySource for OsKeySource {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        OsRng.fill_bytes(dest)
+    }
+}
+
+/// Deterministic generator producing the same keys for the same seed, so
+/// that tests and simulations are reproducible. Never use it for keys
+/// protecting anything.
+pub struct SeededKeySource {
+    rng: Mutex<StdRng>,
+}
+
+impl SeededKeySource {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl KeySource for SeededKeySource {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        self.rng
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .fill_bytes(dest)
+    }
+}
+
+/// Copies secret bytes, e.g. key returned by value from other crate, and
+/// wipes the source.
+pub(crate) fn take_secret(mut bytes: impl AsMut<[u8]>) -> Vec<u8> {
+    let secret = bytes.as_mut().to_vec();
+    bytes.as_mut().zeroize();
+    secret
+}
+
+impl std::fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PrivateKey(..)")
+    }
+}
+
+/// Seed of a private key. Seed is wiped from memory when dropped and is
+/// never shown in debug output, so it doesn't end up in logs. Serialized
+/// the same way as `SeedPrefix`.
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretSeed(SeedPrefix);
+
+impl SecretSeed {
+    /// Generates random seed of a key of given type.
+    pub fn generate(key_type: KeyType) -> Self {
+        Self::generate_from(key_type, &OsKeySource)
+    }
+
+    /// Generates seed of a key of given type with bytes of `source`.
+    pub fn generate_from(key_type: KeyType, source: &dyn KeySource) -> Self {
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        let seed = match key_type {
+            KeyType::Ed25519 => {
+                source.fill_bytes(&mut bytes[..]);
+                SeedPrefix::RandomSeed256Ed25519(bytes.to_vec())
+            }
+            KeyType::ECDSAsecp256k1 => {
+                // Bytes that aren't valid secp256k1 scalar are drawn again.
+                loop {
+                    source.fill_bytes(&mut bytes[..]);
+                    if SigningKey::from_bytes(&bytes[..]).is_ok() {
+                        break SeedPrefix::RandomSeed256ECDSAsecp256k1(bytes.to_vec());
+                    }
+                }
+            }
+        };
+        Self(seed)
+    }
+
+    pub fn seed(&self) -> &SeedPrefix {
+        &self.0
+    }
+}
+
+impl From<SeedPrefix> for SecretSeed {
+    fn from(seed: SeedPrefix) -> Self {
+        Self(seed)
+    }
+}
+
+impl std::ops::Deref for SecretSeed {
+    type Target = SeedPrefix;
+
+    fn deref(&self) -> &SeedPrefix {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for SecretSeed {
+    type Err = crate::prefix::error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl std::fmt::Debug for SecretSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretSeed(..)")
+    }
+}
+
 #[test]
 fn libsodium_to_ed25519_dalek_compat() {
     use ed25519_dalek::Signature;
@@ -150,3 +278,40 @@ fn libsodium_to_ed25519_dalek_compat() {
         )
         .is_ok());
 }
+
+#[test]
+fn test_secret_seed() {
+    use crate::prefix::CesrPrimitive;
+
+    let seed = SecretSeed::generate(KeyType::Ed25519);
+    let encoded = seed.to_str();
+    assert!(!format!("{:?}", seed).contains(&encoded));
+    assert_eq!(encoded.parse::<SecretSeed>().unwrap(), seed);
+
+    let (_, private_key) = seed.derive_key_pair().unwrap();
+    assert_eq!(format!("{:?}", private_key), "PrivateKey(..)");
+
+    let seed = SecretSeed::generate(KeyType::ECDSAsecp256k1);
+    let json = serde_json::to_string(&seed).unwrap();
+    assert_eq!(json, format!("\"{}\"", seed.to_str()));
+    assert!(seed.derive_key_pair().is_ok());
+}
+
+#[test]
+fn test_seeded_key_source() {
+    let generate = |seed| {
+        let source = SeededKeySource::new(seed);
+        (
+            SecretSeed::generate_from(KeyType::Ed25519, &source),
+            SecretSeed::generate_from(KeyType::ECDSAsecp256k1, &source),
+        )
+    };
+    assert_eq!(generate(1), generate(1));
+    assert_ne!(generate(1), generate(2));
+
+    let source = SeededKeySource::new(1);
+    assert_ne!(
+        SecretSeed::generate_from(KeyType::Ed25519, &source),
+        SecretSeed::generate_from(KeyType::Ed25519, &source)
+    );
+}
@@ -1,5 +1,6 @@
 pub mod actor;
 pub mod database;
+pub mod dto;
 pub mod error;
 pub mod event;
 pub mod event_message;
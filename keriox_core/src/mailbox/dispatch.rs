@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    database::EventDatabase,
+    error::Error,
+    mailbox::exchange::{ExchangeRoute, ForwardTopic, SignedExchange},
+    processor::event_storage::EventStorage,
+};
+
+/// Handles verified exchange messages of routes it was registered for.
+pub trait ExchangeHandler: Send + Sync {
+    fn handle(&self, exn: &SignedExchange) -> Result<(), Error>;
+}
+
+/// Stores carried events in recipient's mailbox, so they can be queried
+/// later. Group events go to multisig mailbox, delegated events to delegate
+/// one.
+pub struct MailboxHandler<D: EventDatabase> {
+    storage: Arc<EventStorage<D>>,
+}
+
+impl<D: EventDatabase> MailboxHandler<D> {
+    /// Routes of exchange messages carrying events, which are handled by
+    /// mailbox.
+    pub const ROUTES: [ExchangeRoute; 6] = [
+        ExchangeRoute::Forward(ForwardTopic::Multisig),
+        ExchangeRoute::Forward(ForwardTopic::Delegate),
+        ExchangeRoute::MultisigIcp,
+        ExchangeRoute::MultisigRot,
+        ExchangeRoute::MultisigIxn,
+        ExchangeRoute::DelegationRequest,
+    ];
+
+    pub fn new(storage: Arc<EventStorage<D>>) -> Self {
+        Self { storage }
+    }
+}
+
+impl<D: EventDatabase + Send + Sync> ExchangeHandler for MailboxHandler<D> {
+    fn handle(&self, exn: &SignedExchange) -> Result<(), Error> {
+        let exchange = &exn.exchange_message.data.data;
+        let recipient = exchange.get_prefix();
+        let (topic, event) = exchange
+            .mailbox_topic()
+            .zip(exn.signed_event())
+            .ok_or_else(|| {
+                Error::SemanticError(format!(
+                    "Route {} doesn't carry events",
+                    exchange.route().as_str()
+                ))
+            })?;
+        match topic {
+            ForwardTopic::Multisig => self.storage.add_mailbox_multisig(&recipient, event),
+            ForwardTopic::Delegate => self.storage.add_mailbox_delegate(&recipient, event),
+        }
+    }
+}
+
+/// Verifies received exchange messages and passes them to handlers
+/// registered for their route. Messages of routes without handlers are
+/// rejected.
+pub struct ExchangeDispatcher<D: EventDatabase> {
+    storage: Arc<EventStorage<D>>,
+    handlers: RwLock<HashMap<ExchangeRoute, Vec<Arc<dyn ExchangeHandler>>>>,
+}
+
+impl<D: EventDatabase> ExchangeDispatcher<D> {
+    pub fn new(storage: Arc<EventStorage<D>>) -> Self {
+        Self {
+            storage,
+            handlers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn register_handler(
+        &self,
+        route: ExchangeRoute,
+        handler: Arc<dyn ExchangeHandler>,
+    ) -> Result<(), Error> {
+        self.handlers
+            .write()
+            .map_err(|_| Error::RwLockingError)?
+            .entry(route)
+            .or_default()
+            .push(handler);
+        Ok(())
+    }
+
+    /// Verifies exchange signatures and calls all handlers of its route.
+    pub fn dispatch(&self, exn: &SignedExchange) -> Result<(), Error> {
+        let route = exn.exchange_message.data.data.route();
+        let handlers = self
+            .handlers
+            .read()
+            .map_err(|_| Error::RwLockingError)?
+            .get(&route)
+            .cloned()
+            .unwrap_or_default();
+        if handlers.is_empty() {
+            return Err(Error::SemanticError(format!(
+                "No handler for exchange route {}",
+                route.as_str()
+            )));
+        }
+        if !exn.verify(self.storage.as_ref())? {
+            return Err(Error::SignatureVerificationError);
+        }
+        handlers.iter().try_for_each(|handler| handler.handle(exn))
+    }
+}
+
+impl<D: EventDatabase + Send + Sync + 'static> ExchangeDispatcher<D> {
+    /// Creates dispatcher which stores events carried by exchange messages
+    /// in mailbox of their recipients.
+    pub fn with_mailbox(storage: Arc<EventStorage<D>>) -> Result<Self, Error> {
+        let dispatcher = Self::new(storage.clone());
+        let mailbox = Arc::new(MailboxHandler::new(storage));
+        for route in MailboxHandler::<D>::ROUTES {
+            dispatcher.register_handler(route, mailbox.clone())?;
+        }
+        Ok(dispatcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use cesrox::{cesr_proof::MaterialPath, primitives::codes::basic::Basic};
+    use tempfile::NamedTempFile;
+
+    use super::{ExchangeDispatcher, ExchangeHandler};
+    use crate::{
+        actor::event_generator,
+        database::redb::RedbDatabase,
+        error::Error,
+        event::KeyEvent,
+        event_message::{
+            event_msg_builder::EventMsgBuilder,
+            msg::KeriEvent,
+            signature::{Signature, SignerData},
+            signed_event_message::{Notice, SignedEventMessage},
+            EventTypeTag,
+        },
+        mailbox::exchange::{ExchangeMessage, ExchangeRoute, SignedExchange},
+        prefix::{BasicPrefix, IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
+        processor::{basic_processor::BasicProcessor, event_storage::EventStorage, Processor},
+        query::mailbox::{QueryArgsMbx, QueryTopics},
+        signer::{CryptoBox, KeyManager},
+    };
+
+    /// Remembers handled exchange messages.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<SignedExchange>>);
+
+    impl ExchangeHandler for Recorder {
+        fn handle(&self, exn: &SignedExchange) -> Result<(), Error> {
+            self.0.lock().unwrap().push(exn.clone());
+            Ok(())
+        }
+    }
+
+    struct Setup {
+        _db_file: NamedTempFile,
+        storage: Arc<EventStorage<RedbDatabase>>,
+        key_manager: CryptoBox,
+        icp: SignedEventMessage,
+    }
+
+    impl Setup {
+        /// Incepts identifier and accepts its KEL, so its signatures can
+        /// be verified.
+        fn new() -> Result<Self, Error> {
+            let db_file = NamedTempFile::new().unwrap();
+            let events_db = Arc::new(RedbDatabase::new(db_file.path()).unwrap());
+            let storage = Arc::new(EventStorage::new_redb(events_db.clone()));
+            let processor = BasicProcessor::new(events_db, None);
+
+            let key_manager = CryptoBox::new()?;
+            let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+                .with_keys(vec![BasicPrefix::new(
+                    Basic::Ed25519,
+                    key_manager.public_key(),
+                )])
+                .with_next_keys(vec![BasicPrefix::new(
+                    Basic::Ed25519,
+                    key_manager.next_public_key(),
+                )])
+                .build()?;
+            let icp = sign_event(&key_manager, icp)?;
+            processor.process_notice(&Notice::Event(icp.clone()))?;
+
+            Ok(Self {
+                _db_file: db_file,
+                storage,
+                key_manager,
+                icp,
+            })
+        }
+
+        fn id(&self) -> IdentifierPrefix {
+            self.icp.event_message.data.get_prefix()
+        }
+
+        /// Signs exchange message carrying identifier's inception event.
+        fn signed_exchange(
+            &self,
+            recipient: &IdentifierPrefix,
+            route: ExchangeRoute,
+        ) -> Result<SignedExchange, Error> {
+            let exn = event_generator::routed_exchange(recipient, &self.icp.event_message, route)?;
+            self.sign_exchange(exn, &self.key_manager)
+        }
+
+        fn sign_exchange(
+            &self,
+            exn: ExchangeMessage,
+            key_manager: &CryptoBox,
+        ) -> Result<SignedExchange, Error> {
+            let signature = IndexedSignature::new_both_same(
+                SelfSigningPrefix::Ed25519Sha512(key_manager.sign(&exn.encode()?)?),
+                0,
+            );
+            Ok(SignedExchange {
+                exchange_message: exn,
+                signature: vec![Signature::Transferable(
+                    SignerData::LastEstablishment(self.id()),
+                    vec![signature],
+                )],
+                data_signature: (
+                    MaterialPath::to_path("-a".into()),
+                    vec![Signature::Transferable(
+                        SignerData::JustSignatures,
+                        self.icp.signatures.clone(),
+                    )],
+                ),
+            })
+        }
+
+        fn mailbox_args(&self, recipient: &IdentifierPrefix) -> QueryArgsMbx {
+            QueryArgsMbx::new(
+                self.id(),
+                recipient.clone(),
+                self.id(),
+                QueryTopics::default(),
+            )
+        }
+    }
+
+    fn sign_event(
+        key_manager: &CryptoBox,
+        event: KeriEvent<KeyEvent>,
+    ) -> Result<SignedEventMessage, Error> {
+        let signature = key_manager.sign(&event.encode()?)?;
+        Ok(event.sign(
+            vec![IndexedSignature::new_both_same(
+                SelfSigningPrefix::Ed25519Sha512(signature),
+                0,
+            )],
+            None,
+            None,
+        ))
+    }
+
+    #[test]
+    fn test_route_registration() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        let dispatcher = ExchangeDispatcher::new(setup.storage.clone());
+        let recorder = Arc::new(Recorder::default());
+        dispatcher.register_handler(ExchangeRoute::MultisigIcp, recorder.clone())?;
+
+        let recipient = setup.id();
+        let multisig = setup.signed_exchange(&recipient, ExchangeRoute::MultisigIcp)?;
+        dispatcher.dispatch(&multisig)?;
+        assert_eq!(*recorder.0.lock().unwrap(), vec![multisig]);
+
+        // Handler is called only for route it was registered for.
+        let delegation = setup.signed_exchange(&recipient, ExchangeRoute::DelegationRequest)?;
+        assert!(dispatcher.dispatch(&delegation).is_err());
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_handler() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        let dispatcher = ExchangeDispatcher::with_mailbox(setup.storage.clone())?;
+
+        // Mailbox doesn't handle exchanges without events.
+        let response =
+            event_generator::challenge_response(&setup.id(), &setup.id(), vec!["able".to_string()]);
+        let response = setup.sign_exchange(response, &setup.key_manager)?;
+        assert!(matches!(
+            dispatcher.dispatch(&response),
+            Err(Error::SemanticError(msg)) if msg == "No handler for exchange route /challenge/response"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_signature() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        let dispatcher = ExchangeDispatcher::new(setup.storage.clone());
+        let recorder = Arc::new(Recorder::default());
+        dispatcher.register_handler(ExchangeRoute::MultisigIcp, recorder.clone())?;
+
+        // Exchange signed with key which isn't identifier's current key.
+        let exn = event_generator::routed_exchange(
+            &setup.id(),
+            &setup.icp.event_message,
+            ExchangeRoute::MultisigIcp,
+        )?;
+        let forged = setup.sign_exchange(exn, &CryptoBox::new()?)?;
+        assert!(matches!(
+            dispatcher.dispatch(&forged),
+            Err(Error::SignatureVerificationError)
+        ));
+        assert!(recorder.0.lock().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mailbox_delivery() -> Result<(), Error> {
+        let setup = Setup::new()?;
+        let dispatcher = ExchangeDispatcher::with_mailbox(setup.storage.clone())?;
+        let recipient: IdentifierPrefix = "EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"
+            .parse()
+            .unwrap();
+
+        dispatcher.dispatch(&setup.signed_exchange(&recipient, ExchangeRoute::MultisigIcp)?)?;
+        let mailbox = setup
+            .storage
+            .get_mailbox_messages(&setup.mailbox_args(&recipient))?;
+        assert_eq!(mailbox.multisig, vec![setup.icp.clone()]);
+        assert!(mailbox.delegate.is_empty());
+
+        dispatcher
+            .dispatch(&setup.signed_exchange(&recipient, ExchangeRoute::DelegationRequest)?)?;
+        let mailbox = setup
+            .storage
+            .get_mailbox_messages(&setup.mailbox_args(&recipient))?;
+        assert_eq!(mailbox.multisig, vec![setup.icp.clone()]);
+        assert_eq!(mailbox.delegate, vec![setup.icp.clone()]);
+        Ok(())
+    }
+}
@@ -1,12 +1,17 @@
 use cesrox::cesr_proof::MaterialPath;
 use said::derivation::HashFunctionCode;
 use said::version::format::SerializationFormats;
+use said::SelfAddressingIdentifier;
 use serde::{Deserialize, Serialize};
 
+use crate::database::EventDatabase;
+use crate::error::Error;
 use crate::event::KeyEvent;
 use crate::event_message::msg::KeriEvent;
+use crate::event_message::signed_event_message::SignedEventMessage;
 use crate::event_message::timestamped::Timestamped;
 use crate::prefix::IdentifierPrefix;
+use crate::processor::event_storage::EventStorage;
 
 use crate::event_message::{signature::Signature, EventTypeTag, Typeable};
 
@@ -20,6 +25,46 @@ pub struct SignedExchange {
     pub data_signature: (MaterialPath, Vec<Signature>),
 }
 
+impl SignedExchange {
+    /// Verifies signatures of exchange message. Signers' KELs need to be
+    /// known. Message without any signature is not valid.
+    pub fn verify<D: EventDatabase>(&self, storage: &EventStorage<D>) -> Result<bool, Error> {
+        if self.signature.is_empty() {
+            return Ok(false);
+        }
+        let serialized = self.exchange_message.encode()?;
+        self.signature.iter().try_fold(true, |acc, signature| {
+            Ok(acc && signature.verify(&serialized, storage)?)
+        })
+    }
+
+    /// Returns carried event with data signatures attached, if exchange
+    /// carries any.
+    pub fn signed_event(&self) -> Option<SignedEventMessage> {
+        let event_message = self.exchange_message.data.data.event()?.clone();
+        let (sigs, witness_receipts) = self.data_signature.1.iter().cloned().fold(
+            (vec![], vec![]),
+            |(mut signatures, mut witness_receipts), s| {
+                match s {
+                    Signature::Transferable(_sd, mut sigs) => signatures.append(&mut sigs),
+                    Signature::NonTransferable(receipts) => witness_receipts.push(receipts),
+                }
+                (signatures, witness_receipts)
+            },
+        );
+        Some(SignedEventMessage {
+            event_message,
+            signatures: sigs,
+            witness_receipts: if witness_receipts.is_empty() {
+                None
+            } else {
+                Some(witness_receipts)
+            },
+            delegator_seal: None,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "r")]
 pub enum Exchange {
@@ -30,6 +75,31 @@ pub enum Exchange {
         #[serde(rename = "a")]
         to_forward: KeriEvent<KeyEvent>,
     },
+    #[serde(rename = "/multisig/icp")]
+    MultisigIcp(RoutedEvent),
+    #[serde(rename = "/multisig/rot")]
+    MultisigRot(RoutedEvent),
+    #[serde(rename = "/multisig/ixn")]
+    MultisigIxn(RoutedEvent),
+    #[serde(rename = "/delegation/request")]
+    DelegationRequest(RoutedEvent),
+    #[serde(rename = "/challenge/response")]
+    ChallengeResponse {
+        #[serde(rename = "q")]
+        args: RouteArgs,
+        #[serde(rename = "a")]
+        response: ChallengeResponse,
+    },
+    #[serde(rename = "/ipex/apply")]
+    IpexApply(Ipex),
+    #[serde(rename = "/ipex/offer")]
+    IpexOffer(Ipex),
+    #[serde(rename = "/ipex/agree")]
+    IpexAgree(Ipex),
+    #[serde(rename = "/ipex/grant")]
+    IpexGrant(Ipex),
+    #[serde(rename = "/ipex/admit")]
+    IpexAdmit(Ipex),
 }
 
 impl Exchange {
@@ -40,6 +110,86 @@ impl Exchange {
     ) -> ExchangeMessage {
         KeriEvent::new(format, derivation.into(), Timestamped::new(self))
     }
+
+    /// Builds exchange message of given route, which carries `event` to
+    /// `recipient`. Fails for routes that don't carry events.
+    pub fn new(
+        route: ExchangeRoute,
+        recipient: IdentifierPrefix,
+        event: KeriEvent<KeyEvent>,
+    ) -> Result<Self, Error> {
+        let routed = RoutedEvent {
+            args: RouteArgs {
+                recipient_id: recipient.clone(),
+            },
+            event: event.clone(),
+        };
+        Ok(match route {
+            ExchangeRoute::Forward(topic) => Exchange::Fwd {
+                args: FwdArgs {
+                    recipient_id: recipient,
+                    topic,
+                },
+                to_forward: event,
+            },
+            ExchangeRoute::MultisigIcp => Exchange::MultisigIcp(routed),
+            ExchangeRoute::MultisigRot => Exchange::MultisigRot(routed),
+            ExchangeRoute::MultisigIxn => Exchange::MultisigIxn(routed),
+            ExchangeRoute::DelegationRequest => Exchange::DelegationRequest(routed),
+            _ => {
+                return Err(Error::SemanticError(format!(
+                    "Route {} doesn't carry events",
+                    route.as_str()
+                )))
+            }
+        })
+    }
+
+    /// Builds IPEX message of given route, i.e. step of credential
+    /// presentation to `recipient`. `prior` is SAID of exchange message it
+    /// responds to. Fails for non IPEX routes.
+    pub fn ipex(
+        route: ExchangeRoute,
+        recipient: IdentifierPrefix,
+        prior: Option<SelfAddressingIdentifier>,
+        data: IpexData,
+    ) -> Result<Self, Error> {
+        let ipex = Ipex {
+            args: RouteArgs {
+                recipient_id: recipient,
+            },
+            prior,
+            data,
+        };
+        Ok(match route {
+            ExchangeRoute::IpexApply => Exchange::IpexApply(ipex),
+            ExchangeRoute::IpexOffer => Exchange::IpexOffer(ipex),
+            ExchangeRoute::IpexAgree => Exchange::IpexAgree(ipex),
+            ExchangeRoute::IpexGrant => Exchange::IpexGrant(ipex),
+            ExchangeRoute::IpexAdmit => Exchange::IpexAdmit(ipex),
+            _ => {
+                return Err(Error::SemanticError(format!(
+                    "Route {} is not an IPEX route",
+                    route.as_str()
+                )))
+            }
+        })
+    }
+
+    /// Builds response to challenge of `recipient`, in which `signer`
+    /// repeats challenge words.
+    pub fn challenge_response(
+        recipient: IdentifierPrefix,
+        signer: IdentifierPrefix,
+        words: Vec<String>,
+    ) -> Self {
+        Exchange::ChallengeResponse {
+            args: RouteArgs {
+                recipient_id: recipient,
+            },
+            response: ChallengeResponse { signer, words },
+        }
+    }
 }
 
 impl Exchange {
@@ -49,6 +199,81 @@ impl Exchange {
                 args,
                 to_forward: _,
             } => args.recipient_id.clone(),
+            Exchange::MultisigIcp(routed)
+            | Exchange::MultisigRot(routed)
+            | Exchange::MultisigIxn(routed)
+            | Exchange::DelegationRequest(routed) => routed.args.recipient_id.clone(),
+            Exchange::ChallengeResponse { args, .. } => args.recipient_id.clone(),
+            Exchange::IpexApply(ipex)
+            | Exchange::IpexOffer(ipex)
+            | Exchange::IpexAgree(ipex)
+            | Exchange::IpexGrant(ipex)
+            | Exchange::IpexAdmit(ipex) => ipex.args.recipient_id.clone(),
+        }
+    }
+
+    pub fn route(&self) -> ExchangeRoute {
+        match self {
+            Exchange::Fwd { args, .. } => ExchangeRoute::Forward(args.topic.clone()),
+            Exchange::MultisigIcp(_) => ExchangeRoute::MultisigIcp,
+            Exchange::MultisigRot(_) => ExchangeRoute::MultisigRot,
+            Exchange::MultisigIxn(_) => ExchangeRoute::MultisigIxn,
+            Exchange::DelegationRequest(_) => ExchangeRoute::DelegationRequest,
+            Exchange::ChallengeResponse { .. } => ExchangeRoute::ChallengeResponse,
+            Exchange::IpexApply(_) => ExchangeRoute::IpexApply,
+            Exchange::IpexOffer(_) => ExchangeRoute::IpexOffer,
+            Exchange::IpexAgree(_) => ExchangeRoute::IpexAgree,
+            Exchange::IpexGrant(_) => ExchangeRoute::IpexGrant,
+            Exchange::IpexAdmit(_) => ExchangeRoute::IpexAdmit,
+        }
+    }
+
+    /// Returns key event carried by exchange message.
+    pub fn event(&self) -> Option<&KeriEvent<KeyEvent>> {
+        match self {
+            Exchange::Fwd { to_forward, .. } => Some(to_forward),
+            Exchange::MultisigIcp(routed)
+            | Exchange::MultisigRot(routed)
+            | Exchange::MultisigIxn(routed)
+            | Exchange::DelegationRequest(routed) => Some(&routed.event),
+            Exchange::ChallengeResponse { .. } => None,
+            Exchange::IpexApply(_)
+            | Exchange::IpexOffer(_)
+            | Exchange::IpexAgree(_)
+            | Exchange::IpexGrant(_)
+            | Exchange::IpexAdmit(_) => None,
+        }
+    }
+
+    /// Returns IPEX payload, if exchange is a step of credential
+    /// presentation.
+    pub fn ipex_payload(&self) -> Option<&Ipex> {
+        match self {
+            Exchange::IpexApply(ipex)
+            | Exchange::IpexOffer(ipex)
+            | Exchange::IpexAgree(ipex)
+            | Exchange::IpexGrant(ipex)
+            | Exchange::IpexAdmit(ipex) => Some(ipex),
+            _ => None,
+        }
+    }
+
+    /// Whether exchange carries group event, to be stored in recipient's
+    /// multisig mailbox.
+    pub fn is_multisig(&self) -> bool {
+        self.mailbox_topic() == Some(ForwardTopic::Multisig)
+    }
+
+    /// Mailbox topic of carried event: multisig for group events, delegate
+    /// for delegated ones. None for exchanges without events.
+    pub fn mailbox_topic(&self) -> Option<ForwardTopic> {
+        match self.route() {
+            ExchangeRoute::Forward(topic) => Some(topic),
+            ExchangeRoute::MultisigIcp
+            | ExchangeRoute::MultisigRot
+            | ExchangeRoute::MultisigIxn => Some(ForwardTopic::Multisig),
+            ExchangeRoute::DelegationRequest => Some(ForwardTopic::Delegate),
+            _ => None,
         }
     }
 }
@@ -60,13 +285,102 @@ pub struct FwdArgs {
     pub topic: ForwardTopic,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum ForwardTopic {
     Multisig,
     Delegate,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RouteArgs {
+    #[serde(rename = "pre")]
+    pub recipient_id: IdentifierPrefix,
+}
+
+/// Payload of routed exchange message: recipient and carried event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RoutedEvent {
+    #[serde(rename = "q")]
+    pub args: RouteArgs,
+    #[serde(rename = "a")]
+    pub event: KeriEvent<KeyEvent>,
+}
+
+/// Challenge words repeated by `signer` to prove control of identifier.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChallengeResponse {
+    #[serde(rename = "i")]
+    pub signer: IdentifierPrefix,
+    pub words: Vec<String>,
+}
+
+/// Payload of IPEX exchange message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Ipex {
+    #[serde(rename = "q")]
+    pub args: RouteArgs,
+    /// SAID of exchange message this one responds to.
+    #[serde(rename = "p", default, skip_serializing_if = "Option::is_none")]
+    pub prior: Option<SelfAddressingIdentifier>,
+    #[serde(rename = "a")]
+    pub data: IpexData,
+}
+
+/// Content of IPEX step. Apply and offer point to credential schema,
+/// grant carries credential along with issuer's KEL and its TEL.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct IpexData {
+    #[serde(rename = "m", default)]
+    pub message: String,
+    /// SAID of schema of requested or offered credential.
+    #[serde(rename = "s", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<SelfAddressingIdentifier>,
+    /// Credential signed by its issuer, as CESR stream.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acdc: Option<String>,
+    /// Issuer's KEL, as CESR stream.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kel: Option<String>,
+    /// Registry and credential TEL, as CESR stream.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tel: Option<String>,
+}
+
+/// Route of exchange message, used to dispatch it to registered handlers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExchangeRoute {
+    Forward(ForwardTopic),
+    MultisigIcp,
+    MultisigRot,
+    MultisigIxn,
+    DelegationRequest,
+    ChallengeResponse,
+    IpexApply,
+    IpexOffer,
+    IpexAgree,
+    IpexGrant,
+    IpexAdmit,
+}
+
+impl ExchangeRoute {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExchangeRoute::Forward(_) => "/fwd",
+            ExchangeRoute::MultisigIcp => "/multisig/icp",
+            ExchangeRoute::MultisigRot => "/multisig/rot",
+            ExchangeRoute::MultisigIxn => "/multisig/ixn",
+            ExchangeRoute::DelegationRequest => "/delegation/request",
+            ExchangeRoute::ChallengeResponse => "/challenge/response",
+            ExchangeRoute::IpexApply => "/ipex/apply",
+            ExchangeRoute::IpexOffer => "/ipex/offer",
+            ExchangeRoute::IpexAgree => "/ipex/agree",
+            ExchangeRoute::IpexGrant => "/ipex/grant",
+            ExchangeRoute::IpexAdmit => "/ipex/admit",
+        }
+    }
+}
+
 impl Typeable for Exchange {
     type TypeTag = EventTypeTag;
     fn get_type(&self) -> EventTypeTag {
@@ -91,3 +405,128 @@ fn test_exn_serialization() -> Result<(), crate::error::Error> {
     assert_eq!(exchange, ser_deser);
     Ok(())
 }
+
+#[test]
+fn test_routed_exn_serialization() -> Result<(), crate::error::Error> {
+    let icp_raw = r#"{"v":"KERI10JSON000215_","t":"icp","d":"EC61gZ9lCKmHAS7U5ehUfEbGId5rcY0D7MirFZHDQcE2","i":"EC61gZ9lCKmHAS7U5ehUfEbGId5rcY0D7MirFZHDQcE2","s":"0","kt":"2","k":["DOZlWGPfDHLMf62zSFzE8thHmnQUOgA3_Y-KpOyF9ScG","DHGb2qY9WwZ1sBnC9Ip0F-M8QjTM27ftI-3jTGF9mc6K"],"nt":"2","n":["EBvD5VIVvf6NpP9GRmTqu_Cd1KN0RKrKNfPJ-uhIxurj","EHlpcaxffvtcpoUUMTc6tpqAVtb2qnOYVk_3HRsZ34PH"],"bt":"3","b":["BBilc4-L3tFUnfM_wJr4S4OJanAv_VmF_dJNN6vkf2Ha","BLskRTInXnMxWaGqcpSyMgo0nYbalW99cGZESrz3zapM","BIKKuvBwpmDVA4Ds-EpL5bt9OqPzWPja2LigFYZN2YfX"],"c":[],"a":[]}"#;
+    let icp: KeriEvent<KeyEvent> = serde_json::from_str(icp_raw).unwrap();
+    let recipient: IdentifierPrefix = "EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"
+        .parse()
+        .unwrap();
+
+    let exn = Exchange::new(ExchangeRoute::MultisigIcp, recipient.clone(), icp.clone())?
+        .to_message(SerializationFormats::JSON, HashFunctionCode::Blake3_256);
+    let encoded = String::from_utf8(exn.encode()?).unwrap();
+    assert!(encoded.contains(
+        r#""r":"/multisig/icp","q":{"pre":"EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"}"#
+    ));
+
+    let parsed: ExchangeMessage = serde_json::from_str(&encoded).unwrap();
+    assert_eq!(parsed, exn);
+    let exchange = &parsed.data.data;
+    assert_eq!(exchange.route(), ExchangeRoute::MultisigIcp);
+    assert_eq!(exchange.get_prefix(), recipient);
+    assert_eq!(exchange.event(), Some(&icp));
+    assert!(exchange.is_multisig());
+    Ok(())
+}
+
+#[test]
+fn test_challenge_response_serialization() -> Result<(), crate::error::Error> {
+    let recipient: IdentifierPrefix = "EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"
+        .parse()
+        .unwrap();
+    let signer: IdentifierPrefix = "EC61gZ9lCKmHAS7U5ehUfEbGId5rcY0D7MirFZHDQcE2"
+        .parse()
+        .unwrap();
+    let words = vec!["able".to_string(), "baker".to_string()];
+
+    let exn = Exchange::challenge_response(recipient.clone(), signer.clone(), words.clone())
+        .to_message(SerializationFormats::JSON, HashFunctionCode::Blake3_256);
+    let encoded = String::from_utf8(exn.encode()?).unwrap();
+    assert!(encoded.contains(
+        r#""r":"/challenge/response","q":{"pre":"EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"},"a":{"i":"EC61gZ9lCKmHAS7U5ehUfEbGId5rcY0D7MirFZHDQcE2","words":["able","baker"]}"#
+    ));
+
+    let parsed: ExchangeMessage = serde_json::from_str(&encoded).unwrap();
+    assert_eq!(parsed, exn);
+    let exchange = &parsed.data.data;
+    assert_eq!(exchange.route(), ExchangeRoute::ChallengeResponse);
+    assert_eq!(exchange.get_prefix(), recipient);
+    assert_eq!(exchange.event(), None);
+    Ok(())
+}
+
+#[test]
+fn test_unsigned_exn_verification() -> Result<(), crate::error::Error> {
+    use std::sync::Arc;
+
+    use crate::database::memory::MemoryDatabase;
+
+    let recipient: IdentifierPrefix = "EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"
+        .parse()
+        .unwrap();
+    let signer: IdentifierPrefix = "EC61gZ9lCKmHAS7U5ehUfEbGId5rcY0D7MirFZHDQcE2"
+        .parse()
+        .unwrap();
+    let exn = Exchange::challenge_response(recipient, signer, vec!["able".to_string()])
+        .to_message(SerializationFormats::JSON, HashFunctionCode::Blake3_256);
+    let unsigned = SignedExchange {
+        exchange_message: exn,
+        signature: vec![],
+        data_signature: (MaterialPath::to_path("-a".into()), vec![]),
+    };
+
+    let storage = EventStorage::new(Arc::new(MemoryDatabase::new()));
+    assert!(!unsigned.verify(&storage)?);
+    Ok(())
+}
+
+#[test]
+fn test_ipex_serialization() -> Result<(), crate::error::Error> {
+    let recipient: IdentifierPrefix = "EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"
+        .parse()
+        .unwrap();
+    let schema: SelfAddressingIdentifier = "EBdXt3gIXOf2BBWNHdSXCJnFJL5OuQPyM5K0neuniccM"
+        .parse()
+        .unwrap();
+
+    let apply = Exchange::ipex(
+        ExchangeRoute::IpexApply,
+        recipient.clone(),
+        None,
+        IpexData {
+            message: "Present your credential".to_string(),
+            schema: Some(schema.clone()),
+            ..Default::default()
+        },
+    )?
+    .to_message(SerializationFormats::JSON, HashFunctionCode::Blake3_256);
+    let encoded = String::from_utf8(apply.encode()?).unwrap();
+    assert!(encoded.contains(
+        r#""r":"/ipex/apply","q":{"pre":"EJccSRTfXYF6wrUVuenAIHzwcx3hJugeiJsEKmndi5q1"},"a":{"m":"Present your credential","s":"EBdXt3gIXOf2BBWNHdSXCJnFJL5OuQPyM5K0neuniccM"}"#
+    ));
+    let parsed: ExchangeMessage = serde_json::from_str(&encoded).unwrap();
+    assert_eq!(parsed, apply);
+
+    // Offer responds to apply message.
+    let offer = Exchange::ipex(
+        ExchangeRoute::IpexOffer,
+        recipient.clone(),
+        Some(apply.digest()?),
+        IpexData::default(),
+    )?;
+    assert_eq!(offer.route(), ExchangeRoute::IpexOffer);
+    assert_eq!(offer.get_prefix(), recipient);
+    assert_eq!(offer.event(), None);
+    assert_eq!(offer.ipex_payload().unwrap().prior, Some(apply.digest()?));
+
+    assert!(Exchange::ipex(
+        ExchangeRoute::ChallengeResponse,
+        recipient,
+        None,
+        IpexData::default()
+    )
+    .is_err());
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::event_message::signed_event_message::{
-    SignedEventMessage, SignedNontransferableReceipt,
+use crate::{
+    event_message::signed_event_message::{SignedEventMessage, SignedNontransferableReceipt},
+    query::mailbox::QueryTopics,
 };
 
+pub mod dispatch;
 pub mod exchange;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -12,3 +14,22 @@ pub struct MailboxResponse {
     pub multisig: Vec<SignedEventMessage>,
     pub delegate: Vec<SignedEventMessage>,
 }
+
+impl MailboxResponse {
+    /// Returns topics to query the next page of mailbox with, given topics
+    /// of query this response was returned for. Messages of each topic are
+    /// indexed in order of arrival, so cursor of topic is moved past
+    /// returned messages.
+    pub fn next_topics(&self, queried: &QueryTopics) -> QueryTopics {
+        QueryTopics {
+            receipt: queried.receipt + self.receipt.len(),
+            multisig: queried.multisig + self.multisig.len(),
+            delegate: queried.delegate + self.delegate.len(),
+            ..queried.clone()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.receipt.is_empty() && self.multisig.is_empty() && self.delegate.is_empty()
+    }
+}
@@ -1,12 +1,14 @@
 use std::{convert::TryFrom, sync::Arc};
 use crate::oobi::{Role, error::OobiError};
 
-use cesrox::parse_many;
 
 use crate::{
     database::redb::{RedbDatabase, RedbError},
     error::Error,
-    event_message::signed_event_message::{Message, Op},
+    event_message::{
+        cesr_adapter::parse_many,
+        signed_event_message::{Message, Op},
+    },
     prefix::IdentifierPrefix,
     query::reply_event::{bada_logic, ReplyEvent, ReplyRoute, SignedReply},
 };
@@ -55,11 +57,19 @@ impl OobiManager {
                 if rpy.signature.get_signer().ok_or(Error::MissingSigner)? != er.cid {
                     return Err(OobiError::SignerMismatch);
                 };
+                // Compare with the last reply about the same endpoint.
                 if let Some(old_rpy) = self
                     .store
                     .get_end_role(&er.cid, er.role)
                     .map_err(|err| OobiError::Db(err.to_string()))?
-                    .and_then(|rpys| rpys.last().cloned())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|old_rpy| match old_rpy.reply.get_route() {
+                        ReplyRoute::EndRoleAdd(old) | ReplyRoute::EndRoleCut(old) => {
+                            old.eid == er.eid
+                        }
+                        _ => false,
+                    })
                 {
                     bada_logic(rpy, &old_rpy)?;
                 };
@@ -132,8 +142,10 @@ mod tests {
     use cesrox::parse_many;
     use tempfile::NamedTempFile;
 
+    use chrono::SecondsFormat;
+
     use crate::{
-        oobi::error::OobiError,
+        oobi::{error::OobiError, Role},
         oobi_manager::OobiManager, prefix::IdentifierPrefix, query::reply_event::ReplyRoute,
     };
 
@@ -231,4 +243,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    pub fn test_end_role_update() -> Result<(), OobiError> {
+        let oobi_manager = setup_oobi_manager();
+
+        let old = r#"{"v":"KERI10JSON000116_","t":"rpy","d":"EXhq-JsyKmr7PJq7luQ0Psd1linhiL6yI4iiDStKPYSw","dt":"2022-04-08T15:00:29.166115+00:00","r":"/end/role/add","a":{"cid":"Bgoq68HCmYNUDgOz4Skvlu306o_NY-NrYuKAVhk3Zh9c","role":"controller","eid":"Bgoq68HCmYNUDgOz4Skvlu306o_NY-NrYuKAVhk3Zh9c"}}-VAi-CABBgoq68HCmYNUDgOz4Skvlu306o_NY-NrYuKAVhk3Zh9c0BJwAp49PBodHj42HlBoStigxsgGEWmdaMOyaY6_q1msdS5pi66SWFCNuLqPWX6p1YWXDmq97MgKZmTRJ3g7mPCg"#;
+        let new = r#"{"v":"KERI10JSON000116_","t":"rpy","d":"E2P4sXDFiU5MnLCk7pMm7IHWOu9UNrqLqnKZJWjdcvuo","dt":"2022-04-08T15:02:55.385191+00:00","r":"/end/role/add","a":{"cid":"Bgoq68HCmYNUDgOz4Skvlu306o_NY-NrYuKAVhk3Zh9c","role":"controller","eid":"Bgoq68HCmYNUDgOz4Skvlu306o_NY-NrYuKAVhk3Zh9c"}}-VAi-CABBgoq68HCmYNUDgOz4Skvlu306o_NY-NrYuKAVhk3Zh9c0B66IhoBb_nIQjY6wlNHwZHicm2Yf4Ioxbm5cnfSvPLQHFjhE7ROXTDlNfZIjyXMmmboHRtpLrCfHO5kz90PF6CA"#;
+        let cid: IdentifierPrefix = "Bgoq68HCmYNUDgOz4Skvlu306o_NY-NrYuKAVhk3Zh9c"
+            .parse()
+            .unwrap();
+
+        oobi_manager.parse_and_save(old)?;
+        oobi_manager.parse_and_save(new)?;
+
+        // Only the newer reply about the endpoint is kept.
+        let res = oobi_manager
+            .get_end_role(&cid, Role::Controller)?
+            .unwrap_or_default();
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0].reply.get_timestamp().to_rfc3339_opts(SecondsFormat::Micros, false),
+            "2022-04-08T15:02:55.385191+00:00"
+        );
+
+        // Older reply is rejected.
+        assert!(oobi_manager.parse_and_save(old).is_err());
+        assert_eq!(
+            oobi_manager
+                .get_end_role(&cid, Role::Controller)?
+                .unwrap_or_default()
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
 }
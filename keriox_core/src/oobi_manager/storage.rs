@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use redb::{MultimapTableDefinition, TableDefinition};
+use redb::{MultimapTableDefinition, ReadableMultimapTable, TableDefinition};
 
 use super::Role;
 use crate::oobi::Scheme;
@@ -135,6 +135,28 @@ impl OobiStorage {
                 let write_txn = self.db.begin_write().unwrap();
                 {
                     let mut table = (&write_txn).open_multimap_table(END_ROLE).unwrap();
+                    // Keep only the latest reply about given endpoint.
+                    let outdated: Vec<Vec<u8>> = table
+                        .get((eid.as_bytes(), role.as_slice()))
+                        .unwrap()
+                        .filter_map(|entry| {
+                            let value = entry.unwrap().value().to_vec();
+                            let old_rpy = serde_cbor::from_slice::<SignedReply>(&value).ok()?;
+                            match old_rpy.reply.get_route() {
+                                ReplyRoute::EndRoleAdd(old) | ReplyRoute::EndRoleCut(old)
+                                    if old.eid == end_role.eid =>
+                                {
+                                    Some(value)
+                                }
+                                _ => None,
+                            }
+                        })
+                        .collect();
+                    for value in outdated {
+                        table
+                            .remove((eid.as_bytes(), role.as_slice()), value.as_slice())
+                            .unwrap();
+                    }
                     table
                         .insert(
                             (eid.as_bytes(), role.as_slice()),
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use super::error::Error;
 use super::CesrPrimitive;
-use crate::keys::{KeysError, PrivateKey, PublicKey};
+use crate::keys::{take_secret, KeysError, PrivateKey, PublicKey};
 use cesrox::{
     conversion::from_text_to_bytes,
     derivation_code::DerivationCode,
@@ -10,6 +10,7 @@ use cesrox::{
 };
 use k256::ecdsa::{SigningKey, VerifyingKey};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::{Zeroize, Zeroizing};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum SeedPrefix {
@@ -38,14 +39,14 @@ impl SeedPrefix {
                 };
                 let secret = ed25519_dalek::SigningKey::from_bytes(key);
                 let vk = PublicKey::new(secret.verifying_key().to_bytes().to_vec());
-                let sk = PrivateKey::new(secret.to_bytes().to_vec());
+                let sk = PrivateKey::new(take_secret(secret.to_bytes()));
                 Ok((vk, sk))
             }
             Self::RandomSeed256ECDSAsecp256k1(seed) => {
                 let sk = SigningKey::from_bytes(seed).map_err(|_e| KeysError::EcdsaError)?;
                 Ok((
                     PublicKey::new(VerifyingKey::from(&sk).to_bytes().to_vec()),
-                    PrivateKey::new(sk.to_bytes().to_vec()),
+                    PrivateKey::new(take_secret(sk.to_bytes())),
                 ))
             }
             _ => Err(Error::WrongSeedTypeError),
@@ -53,6 +54,17 @@ impl SeedPrefix {
     }
 }
 
+impl Drop for SeedPrefix {
+    fn drop(&mut self) {
+        match self {
+            Self::RandomSeed128(seed)
+            | Self::RandomSeed256Ed25519(seed)
+            | Self::RandomSeed256ECDSAsecp256k1(seed)
+            | Self::RandomSeed448(seed) => seed.zeroize(),
+        }
+    }
+}
+
 impl FromStr for SeedPrefix {
     type Err = Error;
 
@@ -60,8 +72,8 @@ impl FromStr for SeedPrefix {
         let code = SeedCode::from_str(s)?;
 
         if s.len() == code.full_size() {
-            let k_vec =
-                from_text_to_bytes(s[code.code_size()..].as_bytes())?[code.code_size()..].to_vec();
+            let bytes = Zeroizing::new(from_text_to_bytes(s[code.code_size()..].as_bytes())?);
+            let k_vec = bytes[code.code_size()..].to_vec();
             Ok(Self::new(code, k_vec))
         } else {
             Err(Error::IncorrectLengthError(s.into()))
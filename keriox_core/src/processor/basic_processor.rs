@@ -1,7 +1,13 @@
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
 
 use super::{
+    escrow::{EscrowConfig, EscrowPolicy},
+    metrics::ProcessorMetrics,
     notification::{JustNotification, Notification, NotificationBus, Notifier},
+    notify_accepted,
     validator::EventValidator,
     EventProcessor, Processor,
 };
@@ -11,9 +17,14 @@ use crate::{
     database::EventDatabase,
     error::Error,
     event_message::signed_event_message::{Notice, SignedEventMessage},
+    prefix::IdentifierPrefix,
 };
 
-pub struct BasicProcessor<D: EventDatabase>(EventProcessor<D>);
+pub struct BasicProcessor<D: EventDatabase>(
+    EventProcessor<D>,
+    Arc<dyn EscrowPolicy>,
+    RwLock<HashSet<IdentifierPrefix>>,
+);
 
 impl<D: EventDatabase + 'static> Processor for BasicProcessor<D> {
     type Database = D;
@@ -27,7 +38,16 @@ impl<D: EventDatabase + 'static> Processor for BasicProcessor<D> {
 
     fn process_notice(&self, notice: &Notice) -> Result<(), Error> {
         self.0
-            .process_notice(notice, BasicProcessor::basic_processing_strategy)?;
+            .process_notice(notice, |events_db, publisher, signed_event| {
+                let own = self.2.read().map_err(|_e| Error::RwLockingError)?;
+                BasicProcessor::basic_processing_strategy(
+                    events_db,
+                    publisher,
+                    signed_event,
+                    self.1.as_ref(),
+                    &own,
+                )
+            })?;
         Ok(())
     }
 
@@ -40,29 +60,67 @@ impl<D: EventDatabase + 'static> Processor for BasicProcessor<D> {
 
 impl<D: EventDatabase + 'static> BasicProcessor<D> {
     pub fn new(db: Arc<D>, notification_bus: Option<NotificationBus>) -> Self {
+        Self::with_policy(db, notification_bus, Arc::new(EscrowConfig::default()))
+    }
+
+    pub fn with_policy(
+        db: Arc<D>,
+        notification_bus: Option<NotificationBus>,
+        policy: Arc<dyn EscrowPolicy>,
+    ) -> Self {
         let processor = EventProcessor::new(notification_bus.unwrap_or_default(), db.clone());
-        Self(processor)
+        Self(processor, policy, RwLock::new(HashSet::new()))
+    }
+
+    /// Marks identifier as controlled locally. Only events of such
+    /// identifiers are checked against `EscrowPolicy::accept_unsigned`.
+    pub fn register_own_identifier(&self, id: IdentifierPrefix) -> Result<(), Error> {
+        self.2
+            .write()
+            .map_err(|_e| Error::RwLockingError)?
+            .insert(id);
+        Ok(())
+    }
+
+    /// Starts collecting processing statistics into provided metrics.
+    pub fn register_metrics(&self, metrics: Arc<dyn ProcessorMetrics>) -> Result<(), Error> {
+        self.0.register_metrics(metrics)
     }
 
     fn basic_processing_strategy(
         events_db: Arc<D>,
         publisher: &NotificationBus,
         signed_event: SignedEventMessage,
+        policy: &dyn EscrowPolicy,
+        own_identifiers: &HashSet<IdentifierPrefix>,
     ) -> Result<(), Error> {
         let id = &signed_event.event_message.data.get_prefix();
         let validator = EventValidator::new(events_db.clone());
+        // Unsigned events of own identifiers skip only signature checks.
+        let validator = if signed_event.signatures.is_empty()
+            && own_identifiers.contains(id)
+            && policy.accept_unsigned(&signed_event)
+        {
+            validator.without_signatures()
+        } else {
+            validator
+        };
         match validator.validate_event(&signed_event) {
             Ok(_) => {
                 events_db
                     .add_kel_finalized_event(signed_event.clone(), id)
                     .map_err(|_e| Error::DbError)?;
-                publisher.notify(&Notification::KeyEventAdded(signed_event))
+                notify_accepted(events_db.as_ref(), publisher, signed_event, None)
             }
             Err(Error::EventOutOfOrderError) => {
                 publisher.notify(&Notification::OutOfOrder(signed_event))
             }
             Err(Error::NotEnoughReceiptsError) => {
-                publisher.notify(&Notification::PartiallyWitnessed(signed_event))
+                if policy.escrow_partially_witnessed(&signed_event) {
+                    publisher.notify(&Notification::PartiallyWitnessed(signed_event))
+                } else {
+                    Err(Error::NotEnoughReceiptsError)
+                }
             }
             Err(Error::NotEnoughSigsError) => {
                 publisher.notify(&Notification::PartiallySigned(signed_event))
@@ -4,16 +4,19 @@ use said::SelfAddressingIdentifier;
 
 use crate::{
     actor::prelude::EventStorage,
-    database::{EscrowCreator, EscrowDatabase, EventDatabase},
+    database::{EscrowCreator, EscrowDatabase, EventDatabase, QueryParameters},
     error::Error,
     event::{
         event_data::EventData,
         sections::seal::{EventSeal, Seal, SourceSeal},
+        KeyEvent,
     },
-    event_message::signed_event_message::SignedEventMessage,
+    event_message::{msg::KeriEvent, signed_event_message::SignedEventMessage},
     prefix::IdentifierPrefix,
     processor::{
+        escrow::EscrowKind,
         notification::{Notification, NotificationBus, Notifier},
+        notify_accepted,
         validator::EventValidator,
     },
 };
@@ -46,11 +49,73 @@ impl<D: EventDatabase + EscrowCreator + 'static> DelegationEscrow<D> {
             .and_then(|mut events| {
                 events.find(|event| {
                     event.event_message.data.sn == sn
-                        && event.event_message.digest().ok().as_ref() == Some(event_digest)
+                        && event
+                            .event_message
+                            .compare_digest(event_digest)
+                            .unwrap_or(false)
                 })
             })
     }
 
+    /// Removes `event` escrowed until `delegator_id` approves it.
+    pub fn remove(&self, delegator_id: &IdentifierPrefix, event: &SignedEventMessage) {
+        // Events are escrowed under delegator's event sn, if it was known.
+        let sn = event.delegator_seal.as_ref().map_or(0, |seal| seal.sn);
+        self.delegation_escrow
+            .remove_key_value(delegator_id, sn, &event.event_message);
+    }
+
+    /// Returns delegator of escrowed `event`.
+    pub fn delegator_of(&self, event: &SignedEventMessage) -> Option<IdentifierPrefix> {
+        match &event.event_message.data.event_data {
+            EventData::Dip(dip) => Some(dip.delegator.clone()),
+            EventData::Drt(_) => EventStorage::new(self.db.clone())
+                .get_state(&event.event_message.data.get_prefix())
+                .and_then(|state| state.delegator),
+            _ => None,
+        }
+    }
+
+    /// Checks whether `event` waits in escrow for approval of its delegator.
+    pub fn contains(&self, event: &SignedEventMessage) -> Result<bool, Error> {
+        let delegator_id = match self.delegator_of(event) {
+            Some(delegator_id) => delegator_id,
+            None => return Ok(false),
+        };
+        let sn = event.delegator_seal.as_ref().map_or(0, |seal| seal.sn);
+        self.delegation_escrow
+            .contains(&delegator_id, sn, &event.event_message.digest()?)
+            .map_err(|_| Error::DbError)
+    }
+
+    /// Looks for event anchoring delegated `event` in KEL of `delegator_id`
+    /// and returns seal pointing to it.
+    pub fn find_delegating_seal(
+        &self,
+        delegator_id: &IdentifierPrefix,
+        event: &SignedEventMessage,
+    ) -> Option<SourceSeal> {
+        self.db
+            .get_kel_finalized_events(QueryParameters::All { id: delegator_id })?
+            .map(|timestamped| timestamped.signed_event_message.event_message)
+            .find(|delegating| {
+                anchored_event_seals(delegating).iter().any(|seal| {
+                    seal.sn == event.event_message.data.get_sn()
+                        && seal.prefix == event.event_message.data.get_prefix()
+                        && event
+                            .event_message
+                            .compare_digest(&seal.event_digest())
+                            .unwrap_or(false)
+                })
+            })
+            .and_then(|delegating| {
+                Some(SourceSeal::new(
+                    delegating.data.get_sn(),
+                    delegating.digest().ok()?,
+                ))
+            })
+    }
+
     pub fn process_delegation_events(
         &self,
         bus: &NotificationBus,
@@ -60,11 +125,14 @@ impl<D: EventDatabase + EscrowCreator + 'static> DelegationEscrow<D> {
     ) -> Result<(), Error> {
         if let Ok(esc) = self.delegation_escrow.get_from_sn(delegator_id, 0) {
             for event in esc {
-                let event_digest = event.event_message.digest()?;
+                // Delegator may anchor digest made with other algorithm.
                 let seal = anchored_seals.iter().find(|seal| {
-                    seal.event_digest() == event_digest
-                        && seal.sn == event.event_message.data.get_sn()
+                    seal.sn == event.event_message.data.get_sn()
                         && seal.prefix == event.event_message.data.get_prefix()
+                        && event
+                            .event_message
+                            .compare_digest(&seal.event_digest())
+                            .unwrap_or(false)
                 });
                 let delegated_event = match seal {
                     Some(_s) => SignedEventMessage {
@@ -82,18 +150,23 @@ impl<D: EventDatabase + EscrowCreator + 'static> DelegationEscrow<D> {
                             .add_kel_finalized_event(delegated_event.clone(), &child_id)
                             .map_err(|_| Error::DbError)?;
                         // remove from escrow
-                        self.delegation_escrow.remove(&event.event_message);
-                        bus.notify(&Notification::KeyEventAdded(event))?;
+                        self.remove(delegator_id, &event);
+                        notify_accepted(
+                            self.db.as_ref(),
+                            bus,
+                            event,
+                            Some(EscrowKind::Delegation),
+                        )?;
                         // stop processing the escrow if kel was updated. It needs to start again.
                         break;
                     }
                     Err(Error::SignatureVerificationError) => {
                         // remove from escrow
-                        self.delegation_escrow.remove(&event.event_message);
+                        self.remove(delegator_id, &event);
                     }
                     Err(Error::NotEnoughReceiptsError) => {
                         // remove from escrow
-                        self.delegation_escrow.remove(&event.event_message);
+                        self.remove(delegator_id, &event);
                         bus.notify(&Notification::PartiallyWitnessed(delegated_event))?;
                     }
                     Err(_e) => (), // keep in escrow,
@@ -112,21 +185,7 @@ impl<D: EventDatabase + EscrowCreator + 'static> Notifier for DelegationEscrow<D
                 // delegator's prefix
                 let id = ev_message.event_message.data.get_prefix();
                 // get anchored data
-                let anchored_data: Vec<Seal> = match &ev_message.event_message.data.event_data {
-                    EventData::Icp(icp) => icp.data.clone(),
-                    EventData::Rot(rot) => rot.data.clone(),
-                    EventData::Ixn(ixn) => ixn.data.clone(),
-                    EventData::Dip(dip) => dip.inception_data.data.clone(),
-                    EventData::Drt(drt) => drt.data.clone(),
-                };
-
-                let seals: Vec<EventSeal> = anchored_data
-                    .into_iter()
-                    .filter_map(|seal| match seal {
-                        Seal::Event(es) => Some(es),
-                        _ => None,
-                    })
-                    .collect();
+                let seals = anchored_event_seals(&ev_message.event_message);
                 if !seals.is_empty() {
                     let potential_delegator_seal = SourceSeal::new(
                         ev_message.event_message.data.get_sn(),
@@ -153,12 +212,7 @@ impl<D: EventDatabase + EscrowCreator + 'static> Notifier for DelegationEscrow<D
                             Err(Error::SemanticError("Not delegated event".to_string()))
                         }
                     }?;
-                    let delegator_seal = signed_event.delegator_seal.clone();
-                    let sn = if let Some(delegator_seal) = delegator_seal {
-                        delegator_seal.sn
-                    } else {
-                        0
-                    };
+                    let sn = signed_event.delegator_seal.as_ref().map_or(0, |seal| seal.sn);
                     self.delegation_escrow
                         .insert_key_value(&delegator_id, sn, signed_event)
                         .map_err(|_| Error::DbError)?;
@@ -170,3 +224,21 @@ impl<D: EventDatabase + EscrowCreator + 'static> Notifier for DelegationEscrow<D
         Ok(())
     }
 }
+
+/// Event seals anchored in data of `event`.
+fn anchored_event_seals(event: &KeriEvent<KeyEvent>) -> Vec<EventSeal> {
+    let anchored_data = match &event.data.event_data {
+        EventData::Icp(icp) => &icp.data,
+        EventData::Rot(rot) => &rot.data,
+        EventData::Ixn(ixn) => &ixn.data,
+        EventData::Dip(dip) => &dip.inception_data.data,
+        EventData::Drt(drt) => &drt.data,
+    };
+    anchored_data
+        .iter()
+        .filter_map(|seal| match seal {
+            Seal::Event(es) => Some(es.clone()),
+            _ => None,
+        })
+        .collect()
+}
@@ -7,7 +7,9 @@ use crate::{
 };
 
 use crate::processor::{
+    escrow::EscrowKind,
     notification::{Notification, NotificationBus, Notifier},
+    notify_accepted,
     validator::EventValidator,
 };
 
@@ -46,7 +48,7 @@ impl<D: EventDatabase + EscrowCreator + 'static> MaybeOutOfOrderEscrow<D> {
                         .map_err(|_| Error::DbError)?;
                     // remove from escrow
                     self.escrowed_out_of_order.remove(&event.event_message);
-                    bus.notify(&Notification::KeyEventAdded(event))?;
+                    notify_accepted(self.db.as_ref(), bus, event, Some(EscrowKind::OutOfOrder))?;
                     // stop processing the escrow if kel was updated. It needs to start again.
                     break;
                 }
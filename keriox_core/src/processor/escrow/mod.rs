@@ -14,8 +14,29 @@ use maybe_out_of_order_escrow::MaybeOutOfOrderEscrow;
 use partially_signed_escrow::PartiallySignedEscrow;
 use partially_witnessed_escrow::PartiallyWitnessedEscrow;
 
-use super::notification::{JustNotification, NotificationBus};
-use crate::database::{EscrowCreator, EventDatabase};
+use super::{
+    notification::{JustNotification, NotificationBus},
+    Processor,
+};
+use said::SelfAddressingIdentifier;
+
+use crate::{
+    actor::prelude::EventStorage,
+    database::{EscrowCreator, EscrowDatabase, EventDatabase},
+    error::Error,
+    event_message::signed_event_message::{Notice, SignedEventMessage},
+    prefix::IdentifierPrefix,
+};
+
+/// Escrows provided by default escrow bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EscrowKind {
+    OutOfOrder,
+    PartiallySigned,
+    PartiallyWitnessed,
+    Delegation,
+    Duplicitous,
+}
 
 #[derive(Debug, Clone)]
 pub struct EscrowConfig {
@@ -38,7 +59,49 @@ impl Default for EscrowConfig {
     }
 }
 
+/// Decides how events that can't be accepted yet are handled by processor
+/// and escrows. `EscrowConfig` provides the default behaviour.
+pub trait EscrowPolicy: Send + Sync {
+    fn out_of_order_timeout(&self) -> Duration;
+    fn partially_signed_timeout(&self) -> Duration;
+    fn partially_witnessed_timeout(&self) -> Duration;
+    fn delegation_timeout(&self) -> Duration;
+
+    /// Whether event without enough witness receipts should be escrowed.
+    /// If not, it is rejected.
+    fn escrow_partially_witnessed(&self, _event: &SignedEventMessage) -> bool {
+        true
+    }
+
+    /// Whether event with no signatures attached can be accepted without
+    /// signature verification. It is consulted only for identifiers
+    /// registered with `BasicProcessor::register_own_identifier`, whose
+    /// events were generated and checked locally.
+    fn accept_unsigned(&self, _event: &SignedEventMessage) -> bool {
+        false
+    }
+}
+
+impl EscrowPolicy for EscrowConfig {
+    fn out_of_order_timeout(&self) -> Duration {
+        self.out_of_order_timeout
+    }
+
+    fn partially_signed_timeout(&self) -> Duration {
+        self.partially_signed_timeout
+    }
+
+    fn partially_witnessed_timeout(&self) -> Duration {
+        self.partially_witnessed_timeout
+    }
+
+    fn delegation_timeout(&self) -> Duration {
+        self.delegation_timeout
+    }
+}
+
 pub struct EscrowSet<D: EventDatabase + EscrowCreator> {
+    event_db: Arc<D>,
     pub out_of_order: Arc<MaybeOutOfOrderEscrow<D>>,
     pub partially_signed: Arc<PartiallySignedEscrow<D>>,
     pub partially_witnessed: Arc<PartiallyWitnessedEscrow<D>>,
@@ -46,11 +109,169 @@ pub struct EscrowSet<D: EventDatabase + EscrowCreator> {
     pub duplicitous: Arc<DuplicitousEvents<D>>,
 }
 
+impl<D: EventDatabase + EscrowCreator + 'static> EscrowSet<D> {
+    /// Processes all escrowed events of identifier again with provided
+    /// processor. Delegated events escrowed until `id` approves them get
+    /// delegating seal attached, if approving event is already in KEL of
+    /// `id`. Useful after missing events were obtained out of band.
+    ///
+    /// Events are removed from escrow only once accepted, moved to other
+    /// escrow or rejected. Events that failed because of storage errors
+    /// stay escrowed. Returns digests of rejected and failed events with
+    /// the reason.
+    pub fn reprocess<P: Processor>(
+        &self,
+        processor: &P,
+        id: &IdentifierPrefix,
+    ) -> Result<Vec<(SelfAddressingIdentifier, Error)>, Error> {
+        let storage = EventStorage::new(self.event_db.clone());
+        let escrows = [
+            (EscrowKind::OutOfOrder, &self.out_of_order.escrowed_out_of_order),
+            (
+                EscrowKind::PartiallySigned,
+                &self.partially_signed.escrowed_partially_signed,
+            ),
+            (
+                EscrowKind::PartiallyWitnessed,
+                &self.partially_witnessed.escrowed_partially_witnessed,
+            ),
+        ];
+        let mut escrowed = vec![];
+        for (kind, escrow) in escrows {
+            let events = escrow.get_from_sn(id, 0).map_err(|_| Error::DbError)?;
+            escrowed.extend(events.map(|event| (kind, event.clone(), event)));
+        }
+        // Delegation escrow is keyed by delegator, not by event's identifier.
+        let delegated = self
+            .delegation
+            .delegation_escrow
+            .get_from_sn(id, 0)
+            .map_err(|_| Error::DbError)?;
+        for event in delegated {
+            let to_process = match (
+                &event.delegator_seal,
+                self.delegation.find_delegating_seal(id, &event),
+            ) {
+                (None, Some(seal)) => SignedEventMessage {
+                    delegator_seal: Some(seal),
+                    ..event.clone()
+                },
+                _ => event.clone(),
+            };
+            escrowed.push((EscrowKind::Delegation, event, to_process));
+        }
+        escrowed.sort_by_key(|(_, _, event)| event.event_message.data.get_sn());
+
+        let mut errors = vec![];
+        for (kind, escrowed_event, event) in escrowed {
+            let digest = event.event_message.digest()?;
+            // Event may be accepted already, e.g. by escrow reacting to
+            // previously accepted event.
+            let outcome = if is_accepted(&storage, &event)? {
+                Ok(())
+            } else {
+                processor.process_notice(&Notice::Event(event.clone()))
+            };
+            let remove = match outcome {
+                Ok(()) => {
+                    is_accepted(&storage, &event)?
+                        || event.delegator_seal != escrowed_event.delegator_seal
+                        || self.moved(kind, &event)?
+                }
+                Err(e @ (Error::DbError | Error::RwLockingError)) => {
+                    errors.push((digest, e));
+                    false
+                }
+                Err(e) => {
+                    errors.push((digest, e));
+                    true
+                }
+            };
+            if remove {
+                self.remove(kind, id, &escrowed_event);
+            }
+        }
+        Ok(errors)
+    }
+
+    /// Checks whether event reprocessed from escrow of `kind` was put to
+    /// other escrow.
+    fn moved(&self, kind: EscrowKind, event: &SignedEventMessage) -> Result<bool, Error> {
+        let (id, sn) = (
+            event.event_message.data.get_prefix(),
+            event.event_message.data.get_sn(),
+        );
+        let digest = event.event_message.digest()?;
+        let escrows = [
+            (EscrowKind::OutOfOrder, &self.out_of_order.escrowed_out_of_order),
+            (
+                EscrowKind::PartiallySigned,
+                &self.partially_signed.escrowed_partially_signed,
+            ),
+            (
+                EscrowKind::PartiallyWitnessed,
+                &self.partially_witnessed.escrowed_partially_witnessed,
+            ),
+        ];
+        for (other, escrow) in escrows {
+            if other != kind && escrow.contains(&id, sn, &digest).map_err(|_| Error::DbError)? {
+                return Ok(true);
+            }
+        }
+        Ok(kind != EscrowKind::Delegation && self.delegation.contains(event)?)
+    }
+
+    fn remove(&self, kind: EscrowKind, id: &IdentifierPrefix, event: &SignedEventMessage) {
+        match kind {
+            EscrowKind::OutOfOrder => self
+                .out_of_order
+                .escrowed_out_of_order
+                .remove(&event.event_message),
+            EscrowKind::PartiallySigned => self
+                .partially_signed
+                .escrowed_partially_signed
+                .remove(&event.event_message),
+            EscrowKind::PartiallyWitnessed => self
+                .partially_witnessed
+                .escrowed_partially_witnessed
+                .remove(&event.event_message),
+            EscrowKind::Delegation => self.delegation.remove(id, event),
+            EscrowKind::Duplicitous => (),
+        }
+    }
+}
+
+fn is_accepted<D: EventDatabase>(
+    storage: &EventStorage<D>,
+    event: &SignedEventMessage,
+) -> Result<bool, Error> {
+    let accepted = storage
+        .get_event_at_sn(
+            &event.event_message.data.get_prefix(),
+            event.event_message.data.get_sn(),
+        )
+        .map(|accepted| accepted.signed_event_message.event_message.digest())
+        .transpose()?;
+    Ok(accepted == Some(event.event_message.digest()?))
+}
+
 pub fn default_escrow_bus<D>(
     event_db: Arc<D>,
     escrow_config: EscrowConfig,
     notification_bus: Option<NotificationBus>,
 ) -> (NotificationBus, EscrowSet<D>)
+where
+    D: EventDatabase + EscrowCreator + Sync + Send + 'static,
+{
+    escrow_bus_with_policy(event_db, Arc::new(escrow_config), notification_bus)
+}
+
+/// Registers default escrows, configured with provided policy.
+pub fn escrow_bus_with_policy<D>(
+    event_db: Arc<D>,
+    policy: Arc<dyn EscrowPolicy>,
+    notification_bus: Option<NotificationBus>,
+) -> (NotificationBus, EscrowSet<D>)
 where
     D: EventDatabase + EscrowCreator + Sync + Send + 'static,
 {
@@ -59,11 +280,11 @@ where
     // Register out of order escrow, to save and reprocess out of order events
     let ooo_escrow = Arc::new(MaybeOutOfOrderEscrow::new(
         event_db.clone(),
-        escrow_config.out_of_order_timeout,
+        policy.out_of_order_timeout(),
     ));
     println!(
         "Registering out of order escrow with timeout: {:?}",
-        escrow_config.out_of_order_timeout
+        policy.out_of_order_timeout()
     );
     bus.register_observer(
         ooo_escrow.clone(),
@@ -75,15 +296,18 @@ where
 
     let ps_escrow = Arc::new(PartiallySignedEscrow::new(
         event_db.clone(),
-        escrow_config.partially_signed_timeout,
+        policy.partially_signed_timeout(),
     ));
     bus.register_observer(ps_escrow.clone(), vec![JustNotification::PartiallySigned]);
 
-    let pw_escrow = Arc::new(PartiallyWitnessedEscrow::new(
-        event_db.clone(),
-        event_db.get_log_db(),
-        escrow_config.partially_witnessed_timeout,
-    ));
+    let pw_escrow = Arc::new(
+        PartiallyWitnessedEscrow::new(
+            event_db.clone(),
+            event_db.get_log_db(),
+            policy.partially_witnessed_timeout(),
+        )
+        .with_policy(policy.clone()),
+    );
     bus.register_observer(
         pw_escrow.clone(),
         vec![
@@ -94,7 +318,7 @@ where
 
     let delegation_escrow = Arc::new(DelegationEscrow::new(
         event_db.clone(),
-        escrow_config.delegation_timeout,
+        policy.delegation_timeout(),
     ));
     bus.register_observer(
         delegation_escrow.clone(),
@@ -104,12 +328,13 @@ where
         ],
     );
 
-    let dup = Arc::new(DuplicitousEvents::new(event_db));
+    let dup = Arc::new(DuplicitousEvents::new(event_db.clone()));
     bus.register_observer(dup.clone(), vec![JustNotification::DuplicitousEvent]);
 
     (
         bus,
         EscrowSet {
+            event_db,
             out_of_order: ooo_escrow,
             partially_signed: ps_escrow,
             partially_witnessed: pw_escrow,
@@ -6,7 +6,9 @@ use crate::{
     event::KeyEvent,
     event_message::{msg::KeriEvent, signed_event_message::SignedEventMessage},
     processor::{
+        escrow::EscrowKind,
         notification::{Notification, NotificationBus, Notifier},
+        notify_accepted,
         validator::EventValidator,
     },
 };
@@ -82,7 +84,12 @@ impl<D: EventDatabase + EscrowCreator + 'static> PartiallySignedEscrow<D> {
                         .unwrap_or_default();
                     // remove from escrow
                     self.remove_partially_signed(&new_event.event_message)?;
-                    bus.notify(&Notification::KeyEventAdded(new_event))?;
+                    notify_accepted(
+                        self.db.as_ref(),
+                        bus,
+                        new_event,
+                        Some(EscrowKind::PartiallySigned),
+                    )?;
                 }
                 Err(Error::NotEnoughReceiptsError) => {
                     // remove from escrow
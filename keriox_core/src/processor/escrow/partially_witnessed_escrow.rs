@@ -11,7 +11,11 @@ use crate::{
         signed_event_message::{SignedEventMessage, SignedNontransferableReceipt},
     },
     prefix::{BasicPrefix, IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
-    processor::notification::{Notification, NotificationBus, Notifier},
+    processor::{
+        escrow::{EscrowConfig, EscrowKind, EscrowPolicy},
+        notification::{Notification, NotificationBus, Notifier},
+        notify_accepted,
+    },
 };
 
 /// Store partially witnessed events and nontransferable receipts of events that
@@ -20,6 +24,7 @@ pub struct PartiallyWitnessedEscrow<D: EventDatabase + EscrowCreator> {
     db: Arc<D>,
     log: Arc<D::LogDatabaseType>,
     pub(crate) escrowed_partially_witnessed: D::EscrowDatabaseType,
+    policy: Arc<dyn EscrowPolicy>,
 }
 
 impl<D: EventDatabase + EscrowCreator + 'static> PartiallyWitnessedEscrow<D> {
@@ -29,9 +34,16 @@ impl<D: EventDatabase + EscrowCreator + 'static> PartiallyWitnessedEscrow<D> {
             log: log_db,
             db,
             escrowed_partially_witnessed: escrow_db,
+            policy: Arc::new(EscrowConfig::default()),
         }
     }
 
+    /// Escrows only events allowed by `EscrowPolicy::escrow_partially_witnessed`,
+    /// including the ones moved here from other escrows.
+    pub fn with_policy(self, policy: Arc<dyn EscrowPolicy>) -> Self {
+        Self { policy, ..self }
+    }
+
     /// Returns all escrowed partially witness events of given identifier.
     pub fn get_partially_witnessed_events<'a>(
         &'a self,
@@ -240,7 +252,7 @@ impl<D: EventDatabase + EscrowCreator + 'static> PartiallyWitnessedEscrow<D> {
     }
 
     /// Helper function for splitting iterator of transferable into couplets and
-    /// indexed signatures.
+    /// indexed signatures. Repeated signatures are taken into account only once.
     fn extract_receipt<I: IntoIterator<Item = Nontransferable>>(
         nontrans: I,
     ) -> (Vec<(BasicPrefix, SelfSigningPrefix)>, Vec<IndexedSignature>) {
@@ -249,10 +261,18 @@ impl<D: EventDatabase + EscrowCreator + 'static> PartiallyWitnessedEscrow<D> {
             |(mut all_couplets, mut all_indexed), snr| {
                 match snr {
                     Nontransferable::Indexed(indexed_sigs) => {
-                        all_indexed.append(&mut indexed_sigs.clone())
+                        for sig in indexed_sigs {
+                            if !all_indexed.contains(&sig) {
+                                all_indexed.push(sig);
+                            }
+                        }
                     }
                     Nontransferable::Couplet(couplets_sigs) => {
-                        all_couplets.append(&mut couplets_sigs.clone())
+                        for couplet in couplets_sigs {
+                            if !all_couplets.contains(&couplet) {
+                                all_couplets.push(couplet);
+                            }
+                        }
                     }
                 };
                 (all_couplets, all_indexed)
@@ -298,7 +318,12 @@ impl<D: EventDatabase + EscrowCreator + 'static> Notifier for PartiallyWitnessed
                                     delegator_seal: None,
                                 };
 
-                                bus.notify(&Notification::KeyEventAdded(added))?;
+                                notify_accepted(
+                                    self.db.as_ref(),
+                                    bus,
+                                    added,
+                                    Some(EscrowKind::PartiallyWitnessed),
+                                )?;
                             }
                             Err(Error::SignatureVerificationError) => {
                                 // remove from escrow
@@ -334,9 +359,16 @@ impl<D: EventDatabase + EscrowCreator + 'static> Notifier for PartiallyWitnessed
                         // accept receipts and remove them from escrow
                         self.accept_receipts_for(&signed_event)?;
 
-                        bus.notify(&Notification::KeyEventAdded(signed_event.clone()))?;
+                        notify_accepted(
+                            self.db.as_ref(),
+                            bus,
+                            signed_event.clone(),
+                            Some(EscrowKind::PartiallyWitnessed),
+                        )?;
                     }
                     Err(Error::SignatureVerificationError) => (),
+                    // rejected by policy
+                    Err(_) if !self.policy.escrow_partially_witnessed(signed_event) => (),
                     Err(_) => {
                         self.escrowed_partially_witnessed
                             .insert(&signed_event)
@@ -853,4 +885,63 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    pub fn test_partially_witnessed_escrow_policy() -> Result<(), Error> {
+        use crate::{
+            event_message::signed_event_message::SignedEventMessage,
+            processor::{
+                escrow::{escrow_bus_with_policy, EscrowConfig, EscrowPolicy},
+                notification::Notification,
+            },
+        };
+
+        struct NoPartiallyWitnessed;
+        impl EscrowPolicy for NoPartiallyWitnessed {
+            fn out_of_order_timeout(&self) -> Duration {
+                Duration::from_secs(60)
+            }
+            fn partially_signed_timeout(&self) -> Duration {
+                Duration::from_secs(60)
+            }
+            fn partially_witnessed_timeout(&self) -> Duration {
+                Duration::from_secs(60)
+            }
+            fn delegation_timeout(&self) -> Duration {
+                Duration::from_secs(60)
+            }
+            fn escrow_partially_witnessed(&self, _event: &SignedEventMessage) -> bool {
+                false
+            }
+        }
+
+        let icp_raw = br#"{"v":"KERI10JSON000273_","t":"icp","d":"EJufgwH347N2kobmes1IQw_1pfMipEFFy0RwinZTtah9","i":"EJufgwH347N2kobmes1IQw_1pfMipEFFy0RwinZTtah9","s":"0","kt":"2","k":["DLQ_T1HC_zZU5b3NsYhCQUX0c9GwyZW7U8pzkKTcFSod","DMW_TkkFsaufVLI0bYWjT7U8zZ_FV7PEiRF3W8RVGfpQ","DJEBW__ddS11UGhY_gofa4_PUE6SGU9wHFfk43AYW1zs"],"nt":"2","n":["EMBt6FEXUuQ02zCXVQicX2W60mmNy8VLiKUlokSf75WZ","EDTF0ZjY5ANPsHIONhplNVDOUEo5aQY9TiDTT3lm0JN6","EKw8rv7Uiugd6r7Zydvg6vY8MOQTOZtP43FodCH88hxk"],"bt":"2","b":["BN_PYSns7oFNixSohVW4raBwMV6iYeh0PEZ_bR-38Xev","BHndk6cXPCnghFqKt_0SikY1P9z_nIUrHq_SeHgLQCui","BJYw25nTX2-tyjqRleJpjysMsqdzsw7Ec6Ta3S9QUULb"],"c":[],"a":[]}-AADAABkmPJEhi5Pr8f-F4FEiBxU-5DF_Ff1LcyyYaOimqlPxs13RJWABWHx_NLQQ8L5O-pGW_zQ7dOWLP098IPoNFcJABAt-w_ejAVim4DrnqFQtZTwtoOqJrsvA1SWRvO-wu_FdyZDtcGhucP4Rl01irWx8MZlrCuY9QnftssqYcBTWBYOACAKMyHHcQ3htd4_NZwzBAUGgc0SxDdzeDvVeZa4g3iVfK4w0BMAOav2ebH8rcW6WoxsQcNyDHjkfYNTM4KNv50I"#;
+        let icp = match Message::try_from(parse(icp_raw).unwrap().1).unwrap() {
+            Message::Notice(Notice::Event(icp)) => icp,
+            _ => unreachable!(),
+        };
+        let id = icp.event_message.data.get_prefix();
+
+        // Events can be moved to partially witnessed escrow by other escrows,
+        // bypassing processor, so escrow checks policy too.
+        let policies: [(Arc<dyn EscrowPolicy>, usize); 2] = [
+            (Arc::new(NoPartiallyWitnessed), 0),
+            (Arc::new(EscrowConfig::default()), 1),
+        ];
+        for (policy, escrowed) in policies {
+            let events_db_path = NamedTempFile::new().unwrap();
+            let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+            let (bus, escrows) = escrow_bus_with_policy(events_db, policy, None);
+            bus.notify(&Notification::PartiallyWitnessed(icp.clone()))?;
+            assert_eq!(
+                escrows
+                    .partially_witnessed
+                    .get_partially_witnessed_events(&id)?
+                    .count(),
+                escrowed
+            );
+        }
+
+        Ok(())
+    }
 }
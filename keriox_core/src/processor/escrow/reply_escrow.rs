@@ -69,7 +69,7 @@ impl Notifier for ReplyEscrow<RedbDatabase> {
 impl ReplyEscrow<RedbDatabase> {
     pub fn process_reply_escrow(
         &self,
-        _bus: &NotificationBus,
+        bus: &NotificationBus,
         id: &IdentifierPrefix,
         sn: u64,
     ) -> Result<(), Error> {
@@ -81,6 +81,7 @@ impl ReplyEscrow<RedbDatabase> {
                 Ok(_) => {
                     self.escrowed_reply.remove(&sig_rep.reply);
                     self.accepted_ksn.insert(sig_rep.clone())?;
+                    bus.notify(&Notification::KsnUpdated(sig_rep))?;
                 }
                 Err(Error::SignatureVerificationError)
                 | Err(Error::QueryError(QueryError::StaleRpy)) => {
@@ -389,3 +389,51 @@ pub fn test_partially_witnessed_escrow_cleanup() -> Result<(), Error> {
 
 //     Ok(())
 // }
+
+#[test]
+fn test_reprocess_escrows() -> Result<(), Error> {
+    use crate::{
+        database::EventDatabase,
+        processor::escrow::{default_escrow_bus, EscrowConfig},
+    };
+
+    let kel = br#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD{"v":"KERI10JSON000160_","t":"rot","d":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"1","p":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","kt":"1","k":["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"],"nt":"1","n":["EBrEok_A-yJGpR9GH_ktdd11x3UR0cHaCg0nzAnYLgGj"],"bt":"0","br":[],"ba":[],"a":[]}-AABAADLgLBVFeCOP8t-sxOWKif-JbQ-PnOz0W7aZCuLPOUEri-OdGXjOV2d3y6-R_SsS2U3toE3TNVJ9UyO5NhBSkkO{"v":"KERI10JSON000160_","t":"rot","d":"ENtkE-NChURiXS5j8ES9GeX9VCqr5PLxilygqUJQ5Wr9","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"2","p":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","kt":"1","k":["DGx72gYpAdz0N3br4blkVRRoIASdcBTJaqtLnGI6PXHV"],"nt":"1","n":["EMEVqKOHmF9juqQSmphqjnP24tT__JILJJ2Z4u9QKSUn"],"bt":"0","br":[],"ba":[],"a":[]}-AABAAAHF__vhEKj4kn1uW0fdBRS75nyG3uvJuEfcOdnx4sfy2vNirkDLkm6WGluUVDfQ7y9_b2TIaIHLfAoBefjNBkF"#;
+    let mut kell = parse_many(kel)
+        .unwrap()
+        .1
+        .into_iter()
+        .map(|e| Message::try_from(e).unwrap());
+    let icp = kell.next().unwrap();
+    let rot = kell.next().unwrap();
+    let next_rot = kell.next().unwrap();
+    let id: IdentifierPrefix = "EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL".parse()?;
+
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let (bus, escrows) = default_escrow_bus(events_db.clone(), EscrowConfig::default(), None);
+    let processor = BasicProcessor::new(events_db.clone(), Some(bus));
+    let storage = EventStorage::new(events_db.clone());
+
+    // Second rotation is escrowed, because the first one is missing.
+    processor.process(&icp)?;
+    processor.process(&next_rot)?;
+    assert_eq!(storage.get_state(&id).unwrap().sn, 0);
+
+    // Rotation is saved out of band, so escrows aren't notified about it.
+    if let Message::Notice(Notice::Event(rot)) = rot {
+        events_db.add_kel_finalized_event(rot, &id).unwrap();
+    }
+    assert_eq!(storage.get_state(&id).unwrap().sn, 1);
+
+    assert!(escrows.reprocess(&processor, &id)?.is_empty());
+    assert_eq!(storage.get_state(&id).unwrap().sn, 2);
+    assert!(escrows
+        .out_of_order
+        .escrowed_out_of_order
+        .get_from_sn(&id, 0)
+        .unwrap()
+        .next()
+        .is_none());
+
+    Ok(())
+}
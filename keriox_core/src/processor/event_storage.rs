@@ -9,11 +9,13 @@ use crate::{
     error::Error,
     event::{
         event_data::EventData,
+        receipt::Receipt,
         sections::{seal::EventSeal, KeyConfig},
     },
     event_message::{
+        event_ref::EventRef,
         signature::Transferable,
-        signed_event_message::{Notice, SignedNontransferableReceipt},
+        signed_event_message::{Notice, SignedNontransferableReceipt, SignedTransferableReceipt},
     },
     prefix::{BasicPrefix, IdentifierPrefix},
     state::{EventSemantics, IdentifierState},
@@ -24,9 +26,10 @@ use crate::{
     query::mailbox::QueryArgsMbx,
 };
 use crate::{
-    database::{EventDatabase, QueryParameters},
+    database::{EventDatabase, FirstSeenEvent, QueryParameters},
     event_message::signed_event_message::SignedEventMessage,
 };
+use chrono::{DateTime, Local};
 #[cfg(feature = "query")]
 use said::version::format::SerializationFormats;
 use said::SelfAddressingIdentifier;
@@ -168,6 +171,61 @@ impl<D: EventDatabase> EventStorage<D> {
         }
     }
 
+    /// Get accepted events in first seen order
+    ///
+    /// Returns at most `limit` events of given identifier, starting from
+    /// first seen ordinal `start`. In contrast to sn ordering, it reflects
+    /// the order in which events were accepted, which is needed for
+    /// duplicity detection.
+    pub fn get_kel_first_seen(
+        &self,
+        id: &IdentifierPrefix,
+        start: u64,
+        limit: u64,
+    ) -> Option<Vec<FirstSeenEvent>> {
+        self.events_db
+            .get_first_seen_events(id, start, limit)
+            .map(|events| events.collect())
+    }
+
+    /// Get KEL of identifier for replay
+    ///
+    /// Returns all accepted events in first seen order. Each event carries
+    /// its controller signatures, delegator seal and witness receipts, and
+    /// is followed by transferable receipts of other validators, the same
+    /// way keripy replays KELs, so receiver can reproduce the same first
+    /// seen log.
+    pub fn get_kel_replay(&self, id: &IdentifierPrefix) -> Result<Vec<Notice>, Error> {
+        let mut replay = vec![];
+        for FirstSeenEvent { event, .. } in
+            self.get_kel_first_seen(id, 0, u64::MAX).unwrap_or_default()
+        {
+            let event = event.signed_event_message;
+            let sn = event.event_message.data.get_sn();
+            let receipt = Receipt::new(
+                event.event_message.serialization_info.kind,
+                event.event_message.digest()?,
+                id.clone(),
+                sn,
+            );
+            replay.push(Notice::Event(event));
+            let validator_receipts = self
+                .events_db
+                .get_receipts_t(QueryParameters::BySn { id: id.clone(), sn })
+                .into_iter()
+                .flatten()
+                .map(|Transferable::Seal(seal, signatures)| {
+                    Notice::TransferableRct(SignedTransferableReceipt::new(
+                        receipt.clone(),
+                        seal,
+                        signatures,
+                    ))
+                });
+            replay.extend(validator_receipts);
+        }
+        Ok(replay)
+    }
+
     pub fn get_event_at_sn(
         &self,
         id: &IdentifierPrefix,
@@ -183,6 +241,22 @@ impl<D: EventDatabase> EventStorage<D> {
         }
     }
 
+    /// Checks whether key event is already accepted into KEL, comparing
+    /// digests without decoding the event.
+    pub fn is_accepted(&self, event: &EventRef) -> Result<bool, Error> {
+        let (Some(prefix), Some(sn)) = (event.prefix(), event.sn()?) else {
+            return Ok(false);
+        };
+        if !event.is_key_event() {
+            return Ok(false);
+        }
+        let accepted = match self.get_event_at_sn(&prefix.parse()?, sn) {
+            Some(accepted) => accepted.signed_event_message.event_message.digest()?,
+            None => return Ok(false),
+        };
+        Ok(accepted.to_string() == event.digest())
+    }
+
     #[cfg(feature = "mailbox")]
     fn mailbox(&self) -> Result<&MailboxData, Error> {
         self.mailbox_data
@@ -234,26 +308,27 @@ impl<D: EventDatabase> EventStorage<D> {
     pub fn get_mailbox_messages(&self, args: &QueryArgsMbx) -> Result<MailboxResponse, Error> {
         let mailbox = self.mailbox()?;
         let id = args.i.clone();
+        let limit = args.limit.unwrap_or(usize::MAX);
 
         // query receipts
         let receipt = match mailbox
             .get_mailbox_receipts(&id, args.topics.receipt as u64)
         {
-            Some(receipts) => receipts.collect(),
+            Some(receipts) => receipts.take(limit).collect(),
             None => vec![],
         };
 
         let multisig = match mailbox
             .get_mailbox_multisig(&id, args.topics.multisig as u64)
         {
-            Some(multisig) => multisig.collect(),
+            Some(multisig) => multisig.take(limit).collect(),
             None => vec![],
         };
 
         let delegate = match mailbox
             .get_mailbox_delegate(&id, args.topics.delegate as u64)
         {
-            Some(delegate) => delegate.collect(),
+            Some(delegate) => delegate.take(limit).collect(),
             None => vec![],
         };
 
@@ -315,13 +390,20 @@ impl<D: EventDatabase> EventStorage<D> {
         id: &IdentifierPrefix,
         sn: u64,
     ) -> Result<Option<IdentifierState>, Error> {
-        let mut state = IdentifierState::default();
+        // Start from the latest checkpoint, if it is consistent with KEL.
+        let (mut state, start) = match self.events_db.get_checkpoint(id, sn) {
+            Some(checkpoint) if self.is_checkpoint_valid(id, &checkpoint) => {
+                let start = checkpoint.sn + 1;
+                (checkpoint, start)
+            }
+            _ => (IdentifierState::default(), 0),
+        };
         if let Some(events) = self
             .events_db
             .get_kel_finalized_events(QueryParameters::Range {
                 id: id.clone(),
-                start: 0,
-                limit: sn + 1,
+                start,
+                limit: sn + 1 - start,
             })
         {
             // TODO: testing approach if events come out sorted already (as they should coz of put sequence)
@@ -339,6 +421,68 @@ impl<D: EventDatabase> EventStorage<D> {
         Ok(Some(state))
     }
 
+    /// Get state of identifier right after event of given sn
+    ///
+    /// Returns `None` if identifier has no accepted event of that sn. Keys
+    /// of returned state are the ones signatures made at that point of KEL
+    /// need to be verified with, even if they were rotated since.
+    pub fn get_state_at(
+        &self,
+        id: &IdentifierPrefix,
+        sn: u64,
+    ) -> Result<Option<IdentifierState>, Error> {
+        Ok(self
+            .compute_state_at_sn(id, sn)?
+            .filter(|state| state.sn == sn && &state.prefix == id))
+    }
+
+    /// Get state of identifier as it was known at given time
+    ///
+    /// Applies all events accepted not later than `time`, according to
+    /// their first seen timestamps. Returns `None` if no event of
+    /// identifier was accepted before that time.
+    pub fn get_state_at_time(
+        &self,
+        id: &IdentifierPrefix,
+        time: DateTime<Local>,
+    ) -> Result<Option<IdentifierState>, Error> {
+        let last_sn = self
+            .get_kel_first_seen(id, 0, u64::MAX)
+            .unwrap_or_default()
+            .into_iter()
+            .take_while(|first_seen| first_seen.event.timestamp <= time)
+            .map(|first_seen| {
+                first_seen
+                    .event
+                    .signed_event_message
+                    .event_message
+                    .data
+                    .get_sn()
+            })
+            .max();
+        match last_sn {
+            Some(sn) => self.get_state_at(id, sn),
+            None => Ok(None),
+        }
+    }
+
+    /// Persists current state of identifier as a checkpoint, so that later
+    /// state computations start from it instead of replaying the whole KEL.
+    pub fn create_checkpoint(&self, id: &IdentifierPrefix) -> Result<(), Error> {
+        self.events_db
+            .save_checkpoint(id)
+            .map_err(|_e| Error::DbError)
+    }
+
+    fn is_checkpoint_valid(&self, id: &IdentifierPrefix, checkpoint: &IdentifierState) -> bool {
+        let checkpoint_digest: SelfAddressingIdentifier =
+            checkpoint.last_event_digest.clone().into();
+        self.get_event_at_sn(id, checkpoint.sn)
+            .and_then(|event| event.signed_event_message.event_message.digest().ok())
+            .map(|digest| digest == checkpoint_digest)
+            .unwrap_or(false)
+    }
+
     /// Get keys from Establishment Event
     ///
     /// Returns the current Key Config associated with
@@ -411,7 +555,9 @@ impl<D: EventDatabase> EventStorage<D> {
             .get_receipts_nt(QueryParameters::BySn { id: id.clone(), sn })
         {
             Some(mut events) => {
-                let sigs = events.next();
+                // Receipt without signatures is returned for events no one
+                // receipted yet.
+                let sigs = events.next().filter(|rct| !rct.signatures.is_empty());
                 // let body = Receipt::new(SerializationFormats::JSON, digest.clone(), id.clone(), sn);
                 Ok(sigs)
             }
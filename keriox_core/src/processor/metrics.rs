@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+use super::{
+    escrow::EscrowKind,
+    notification::{JustNotification, Notification, NotificationBus, Notifier},
+};
+use crate::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectReason {
+    FaultySignature,
+    NotEnoughReceipts,
+    Semantic,
+    Other,
+}
+
+impl From<&Error> for RejectReason {
+    fn from(err: &Error) -> Self {
+        match err {
+            Error::SignatureVerificationError
+            | Error::FaultySignatureVerification
+            | Error::MissingSignatures => RejectReason::FaultySignature,
+            Error::NotEnoughReceiptsError => RejectReason::NotEnoughReceipts,
+            Error::SemanticError(_) => RejectReason::Semantic,
+            _ => RejectReason::Other,
+        }
+    }
+}
+
+/// Collects statistics of event processing. Can be used to export counters
+/// to monitoring system and alert on anomalies, like sudden growth of
+/// duplicitous events or escrows.
+pub trait ProcessorMetrics: Send + Sync {
+    /// Event was added to KEL.
+    fn event_accepted(&self);
+    /// Event was saved in escrow.
+    fn event_escrowed(&self, escrow: EscrowKind);
+    /// Escrowed event was added to KEL.
+    fn event_promoted(&self, escrow: EscrowKind);
+    /// Event was rejected by processor.
+    fn event_rejected(&self, reason: RejectReason);
+    /// Time spent on validating and processing incoming event.
+    fn validation_time(&self, elapsed: Duration);
+}
+
+/// Default in-memory `ProcessorMetrics` implementation.
+#[derive(Default)]
+pub struct ProcessorCounters {
+    accepted: AtomicU64,
+    escrowed: RwLock<HashMap<EscrowKind, u64>>,
+    promoted: RwLock<HashMap<EscrowKind, u64>>,
+    rejected: RwLock<HashMap<RejectReason, u64>>,
+    validated: AtomicU64,
+    validation_nanos: AtomicU64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub accepted: u64,
+    pub escrowed: HashMap<EscrowKind, u64>,
+    pub promoted: HashMap<EscrowKind, u64>,
+    pub rejected: HashMap<RejectReason, u64>,
+    pub average_validation_time: Option<Duration>,
+}
+
+impl ProcessorCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let validated = self.validated.load(Ordering::Relaxed);
+        let average_validation_time = self
+            .validation_nanos
+            .load(Ordering::Relaxed)
+            .checked_div(validated)
+            .map(Duration::from_nanos);
+        MetricsSnapshot {
+            accepted: self.accepted.load(Ordering::Relaxed),
+            escrowed: self.escrowed.read().unwrap().clone(),
+            promoted: self.promoted.read().unwrap().clone(),
+            rejected: self.rejected.read().unwrap().clone(),
+            average_validation_time,
+        }
+    }
+}
+
+impl ProcessorMetrics for ProcessorCounters {
+    fn event_accepted(&self) {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn event_escrowed(&self, escrow: EscrowKind) {
+        *self.escrowed.write().unwrap().entry(escrow).or_default() += 1;
+    }
+
+    fn event_promoted(&self, escrow: EscrowKind) {
+        *self.promoted.write().unwrap().entry(escrow).or_default() += 1;
+    }
+
+    fn event_rejected(&self, reason: RejectReason) {
+        *self.rejected.write().unwrap().entry(reason).or_default() += 1;
+    }
+
+    fn validation_time(&self, elapsed: Duration) {
+        self.validated.fetch_add(1, Ordering::Relaxed);
+        self.validation_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Observer that translates processor notifications into metrics calls.
+/// Registered on notification bus by `EventProcessor::register_metrics`.
+pub(crate) struct MetricsObserver {
+    metrics: Arc<dyn ProcessorMetrics>,
+}
+
+impl MetricsObserver {
+    pub(crate) fn new(metrics: Arc<dyn ProcessorMetrics>) -> Self {
+        Self { metrics }
+    }
+
+    pub(crate) fn notifications() -> Vec<JustNotification> {
+        vec![
+            JustNotification::KeyEventAccepted,
+            JustNotification::OutOfOrder,
+            JustNotification::PartiallySigned,
+            JustNotification::PartiallyWitnessed,
+            JustNotification::MissingDelegatingEvent,
+            JustNotification::DuplicitousEvent,
+        ]
+    }
+}
+
+impl Notifier for MetricsObserver {
+    fn notify(&self, notification: &Notification, _bus: &NotificationBus) -> Result<(), Error> {
+        match notification {
+            Notification::KeyEventAccepted(accepted) => {
+                self.metrics.event_accepted();
+                if let Some(escrow) = accepted.promoted_from {
+                    self.metrics.event_promoted(escrow);
+                }
+            }
+            Notification::OutOfOrder(_) => self.metrics.event_escrowed(EscrowKind::OutOfOrder),
+            Notification::PartiallySigned(_) => {
+                self.metrics.event_escrowed(EscrowKind::PartiallySigned)
+            }
+            Notification::PartiallyWitnessed(_) => {
+                self.metrics.event_escrowed(EscrowKind::PartiallyWitnessed)
+            }
+            Notification::MissingDelegatingEvent(_) => {
+                self.metrics.event_escrowed(EscrowKind::Delegation)
+            }
+            Notification::DupliciousEvent(_) => {
+                self.metrics.event_escrowed(EscrowKind::Duplicitous)
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_escrow_metrics() -> Result<(), Error> {
+    use crate::actor::parse_event_stream;
+    use crate::database::redb::RedbDatabase;
+    use crate::processor::{
+        basic_processor::BasicProcessor,
+        escrow::{default_escrow_bus, EscrowConfig},
+        Processor,
+    };
+    use tempfile::NamedTempFile;
+
+    let kel = br#"{"v":"KERI10JSON000159_","t":"icp","d":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"0","kt":"1","k":["DODv7KGqEEhAP7-VYXzZvNi5wmgEB8w5y6HLUQL08PNh"],"nt":"1","n":["ECo41Mn5wku-tQd7L4Hp65KhaX1KkdTtSY_NXx4rQphS"],"bt":"0","b":["DPOIlcZk_GLVCVtG7KLbDQa2a5drXGt09wpaeY93G--1"],"c":[],"a":[]}-AABAADtEDd5x0DRfSlGl99G2V3aiJQlILTMG8LHNbG6V3ticL8r1vMK8-nmhZBhZglI06mVChxc-EkgqWPzPlI2rAwD{"v":"KERI10JSON000160_","t":"rot","d":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"1","p":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","kt":"1","k":["DIgRd-GK29iB-G7tao3-BCdMbUCATveeMrzivmmmM_Nf"],"nt":"1","n":["EBrEok_A-yJGpR9GH_ktdd11x3UR0cHaCg0nzAnYLgGj"],"bt":"0","br":[],"ba":[],"a":[]}-AABAADLgLBVFeCOP8t-sxOWKif-JbQ-PnOz0W7aZCuLPOUEri-OdGXjOV2d3y6-R_SsS2U3toE3TNVJ9UyO5NhBSkkO{"v":"KERI10JSON000160_","t":"rot","d":"ENtkE-NChURiXS5j8ES9GeX9VCqr5PLxilygqUJQ5Wr9","i":"EO8cED9H5XPqBdoVatgBkEuSP8yXic7HtWpkex-9e0sL","s":"2","p":"EDBBxc3_cczsEld6szaFdmhR3JyOhnYaDCCdo_wDe95p","kt":"1","k":["DGx72gYpAdz0N3br4blkVRRoIASdcBTJaqtLnGI6PXHV"],"nt":"1","n":["EMEVqKOHmF9juqQSmphqjnP24tT__JILJJ2Z4u9QKSUn"],"bt":"0","br":[],"ba":[],"a":[]}-AABAAAHF__vhEKj4kn1uW0fdBRS75nyG3uvJuEfcOdnx4sfy2vNirkDLkm6WGluUVDfQ7y9_b2TIaIHLfAoBefjNBkF"#;
+    let mut messages = parse_event_stream(kel).unwrap().into_iter();
+    let icp = messages.next().unwrap();
+    let rot = messages.next().unwrap();
+    let next_rot = messages.next().unwrap();
+
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let (bus, _escrows) = default_escrow_bus(events_db.clone(), EscrowConfig::default(), None);
+    let processor = BasicProcessor::new(events_db, Some(bus));
+    let counters = Arc::new(ProcessorCounters::new());
+    processor.register_metrics(counters.clone())?;
+
+    // Second rotation is escrowed until the first one arrives.
+    processor.process(&icp)?;
+    processor.process(&next_rot)?;
+    processor.process(&rot)?;
+
+    let snapshot = counters.snapshot();
+    assert_eq!(snapshot.accepted, 3);
+    assert_eq!(snapshot.escrowed.get(&EscrowKind::OutOfOrder), Some(&1));
+    assert_eq!(snapshot.promoted.get(&EscrowKind::OutOfOrder), Some(&1));
+    assert!(snapshot.rejected.is_empty());
+    assert!(snapshot.average_validation_time.is_some());
+
+    Ok(())
+}
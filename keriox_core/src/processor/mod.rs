@@ -1,10 +1,14 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, RwLock},
+    time::Instant,
+};
 
 pub mod basic_processor;
 pub mod escrow;
 #[cfg(test)]
 mod escrow_tests;
 pub mod event_storage;
+pub mod metrics;
 pub mod notification;
 #[cfg(test)]
 mod processor_tests;
@@ -14,17 +18,20 @@ pub mod validator;
 use said::version::format::SerializationFormats;
 
 use self::{
-    notification::{JustNotification, Notification, NotificationBus, Notifier},
+    escrow::EscrowKind,
+    metrics::{MetricsObserver, ProcessorMetrics, RejectReason},
+    notification::{AcceptedEvent, JustNotification, Notification, NotificationBus, Notifier},
     validator::EventValidator,
 };
 #[cfg(feature = "query")]
 use crate::query::reply_event::{ReplyRoute, SignedReply};
 use crate::{
-    database::{timestamped::TimestampedSignedEventMessage, EventDatabase},
+    database::{timestamped::TimestampedSignedEventMessage, EventDatabase, QueryParameters},
     error::Error,
     event::receipt::Receipt,
-    event_message::signed_event_message::{
-        Notice, SignedEventMessage, SignedNontransferableReceipt,
+    event_message::{
+        signature::Nontransferable,
+        signed_event_message::{Notice, SignedEventMessage, SignedNontransferableReceipt},
     },
     prefix::IdentifierPrefix,
     state::IdentifierState,
@@ -68,6 +75,7 @@ pub struct EventProcessor<D: EventDatabase> {
     events_db: Arc<D>,
     validator: EventValidator<D>,
     publisher: NotificationBus,
+    metrics: RwLock<Option<Arc<dyn ProcessorMetrics>>>,
 }
 
 /* impl EventProcessor<RedbDatabase> {
@@ -88,9 +96,20 @@ impl<D: EventDatabase + 'static> EventProcessor<D> {
             events_db,
             validator,
             publisher,
+            metrics: RwLock::new(None),
         }
     }
 
+    /// Starts collecting processing statistics into provided metrics.
+    pub fn register_metrics(&self, metrics: Arc<dyn ProcessorMetrics>) -> Result<(), Error> {
+        self.publisher.register_observer(
+            Arc::new(MetricsObserver::new(metrics.clone())),
+            MetricsObserver::notifications(),
+        );
+        *self.metrics.write().map_err(|_| Error::RwLockingError)? = Some(metrics);
+        Ok(())
+    }
+
     pub fn register_observer(
         &self,
         observer: Arc<dyn Notifier + Send + Sync>,
@@ -112,6 +131,8 @@ impl<D: EventDatabase + 'static> EventProcessor<D> {
                     self.events_db
                         .save_reply(rpy.clone())
                         .map_err(|_e| Error::DbError)?;
+                    self.publisher
+                        .notify(&Notification::KsnUpdated(rpy.clone()))?;
                 }
                 Err(Error::VerificationError(VerificationError::MoreInfo(
                     MoreInfoError::EventNotFound(_),
@@ -141,12 +162,25 @@ impl<D: EventDatabase + 'static> EventProcessor<D> {
     {
         match notice {
             Notice::Event(signed_event) => {
-                processing_strategy(
+                let start = Instant::now();
+                let result = processing_strategy(
                     self.events_db.clone(),
                     // self.db.clone(),
                     &self.publisher,
                     signed_event.clone(),
-                )?;
+                );
+                if let Some(metrics) = self
+                    .metrics
+                    .read()
+                    .map_err(|_| Error::RwLockingError)?
+                    .as_ref()
+                {
+                    metrics.validation_time(start.elapsed());
+                    if let Err(e) = &result {
+                        metrics.event_rejected(RejectReason::from(e));
+                    }
+                }
+                result?;
                 // check if receipts are attached
                 if let Some(witness_receipts) = &signed_event.witness_receipts {
                     // Create and process witness receipts
@@ -171,10 +205,16 @@ impl<D: EventDatabase + 'static> EventProcessor<D> {
                 let id = &rct.body.prefix;
                 match self.validator.validate_witness_receipt(rct) {
                     Ok(_) => {
-                        self.events_db
+                        let stored_new = self
+                            .events_db
                             .add_receipt_nt(rct.to_owned(), id)
                             .map_err(|_| Error::DbError)?;
-                        self.publisher.notify(&Notification::ReceiptAccepted)
+                        // Already known receipt doesn't change anything.
+                        if stored_new {
+                            self.publisher.notify(&Notification::ReceiptAccepted)
+                        } else {
+                            Ok(())
+                        }
                     }
                     Err(Error::MissingEvent) => self
                         .publisher
@@ -198,6 +238,49 @@ impl<D: EventDatabase + 'static> EventProcessor<D> {
     }
 }
 
+/// Notifies about event added to KEL. `KeyEventAccepted` with the resulting
+/// key state and known witness receipts is sent first, followed by
+/// `KeyEventAdded`.
+pub fn notify_accepted<D: EventDatabase>(
+    db: &D,
+    bus: &NotificationBus,
+    event: SignedEventMessage,
+    promoted_from: Option<EscrowKind>,
+) -> Result<(), Error> {
+    let id = event.event_message.data.get_prefix();
+    let sn = event.event_message.data.get_sn();
+    let digest = event.event_message.digest()?;
+    let state = db.get_key_state(&id).ok_or(Error::MissingEvent)?;
+
+    let mut witness_receipts: Vec<Nontransferable> = vec![];
+    let stored = db
+        .get_receipts_nt(QueryParameters::BySn { id, sn })
+        .into_iter()
+        .flatten()
+        .filter(|rct| rct.body.receipted_event_digest == digest)
+        .flat_map(|rct| rct.signatures);
+    for receipt in event
+        .witness_receipts
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .chain(stored)
+        .flat_map(Nontransferable::split)
+    {
+        if !witness_receipts.contains(&receipt) {
+            witness_receipts.push(receipt);
+        }
+    }
+
+    bus.notify(&Notification::KeyEventAccepted(Box::new(AcceptedEvent {
+        event: event.clone(),
+        state,
+        witness_receipts,
+        promoted_from,
+    })))?;
+    bus.notify(&Notification::KeyEventAdded(event))
+}
+
 /// Compute State for Prefix
 ///
 /// Returns the current State associated with
@@ -1,16 +1,21 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, OnceLock, RwLock},
+    sync::{Arc, OnceLock, RwLock, Weak},
 };
 
 #[cfg(feature = "query")]
 use crate::query::reply_event::SignedReply;
 
+use super::escrow::EscrowKind;
 use crate::{
     error::Error,
-    event_message::signed_event_message::{
-        SignedEventMessage, SignedNontransferableReceipt, SignedTransferableReceipt,
+    event_message::{
+        signature::Nontransferable,
+        signed_event_message::{
+            SignedEventMessage, SignedNontransferableReceipt, SignedTransferableReceipt,
+        },
     },
+    state::IdentifierState,
 };
 
 /// Internal dispatch strategy — the swappable part.
@@ -29,16 +34,17 @@ pub trait NotificationDispatch: Send + Sync {
 /// Uses `RwLock` for interior mutability so `register_observer` takes `&self`.
 struct InProcessDispatch {
     observers: RwLock<HashMap<JustNotification, Vec<Arc<dyn Notifier + Send + Sync>>>>,
-    /// Back-reference to the owning `NotificationBus` so we can pass it
-    /// to `Notifier::notify()` callbacks.
-    bus: OnceLock<NotificationBus>,
+    /// Back-reference to itself, used to rebuild the owning `NotificationBus`
+    /// passed to `Notifier::notify()` callbacks. It's weak, so the dispatch
+    /// and observers, which hold databases, are dropped with the last bus.
+    this: OnceLock<Weak<InProcessDispatch>>,
 }
 
 impl InProcessDispatch {
     fn new() -> Self {
         Self {
             observers: RwLock::new(HashMap::new()),
-            bus: OnceLock::new(),
+            this: OnceLock::new(),
         }
     }
 }
@@ -49,12 +55,17 @@ impl NotificationDispatch for InProcessDispatch {
             .observers
             .read()
             .map_err(|_| Error::RwLockingError)?;
-        let bus = self.bus.get().ok_or_else(|| {
-            Error::SemanticError("InProcessDispatch: bus back-reference not set".into())
-        })?;
+        let bus = self
+            .this
+            .get()
+            .and_then(Weak::upgrade)
+            .map(|inner| NotificationBus { inner })
+            .ok_or_else(|| {
+                Error::SemanticError("InProcessDispatch: bus back-reference not set".into())
+            })?;
         if let Some(obs) = observers.get(&notification.into()) {
             for esc in obs.iter() {
-                esc.notify(notification, bus)?;
+                esc.notify(notification, &bus)?;
             }
         }
         Ok(())
@@ -89,13 +100,10 @@ impl NotificationBus {
     /// Create a new bus with the default in-process dispatch.
     pub fn new() -> Self {
         let dispatch = Arc::new(InProcessDispatch::new());
-        let bus = Self {
-            inner: dispatch.clone(),
-        };
         // Set the back-reference so InProcessDispatch can pass &NotificationBus
         // to Notifier::notify() callbacks.
-        let _ = dispatch.bus.set(bus.clone());
-        bus
+        let _ = dispatch.this.set(Arc::downgrade(&dispatch));
+        Self { inner: dispatch }
     }
 
     /// Create a bus backed by a custom dispatch implementation.
@@ -128,9 +136,23 @@ pub trait Notifier {
     fn notify(&self, notification: &Notification, bus: &NotificationBus) -> Result<(), Error>;
 }
 
+/// Event added to KEL together with the context of its acceptance.
+#[derive(PartialEq, Debug, Clone)]
+pub struct AcceptedEvent {
+    pub event: SignedEventMessage,
+    /// Key state after applying the event.
+    pub state: IdentifierState,
+    /// Witness receipts known for the event when it was accepted.
+    pub witness_receipts: Vec<Nontransferable>,
+    /// Escrow the event was promoted from, if it was escrowed before.
+    pub promoted_from: Option<EscrowKind>,
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum Notification {
     KeyEventAdded(SignedEventMessage),
+    /// Sent right before `KeyEventAdded` for the same event.
+    KeyEventAccepted(Box<AcceptedEvent>),
     OutOfOrder(SignedEventMessage),
     PartiallySigned(SignedEventMessage),
     PartiallyWitnessed(SignedEventMessage),
@@ -142,11 +164,15 @@ pub enum Notification {
     MissingDelegatingEvent(SignedEventMessage),
     #[cfg(feature = "query")]
     KsnOutOfOrder(SignedReply),
+    /// Key state notice was accepted.
+    #[cfg(feature = "query")]
+    KsnUpdated(SignedReply),
 }
 
 #[derive(PartialEq, Hash, Eq, Clone, Debug)]
 pub enum JustNotification {
     KeyEventAdded,
+    KeyEventAccepted,
     OutOfOrder,
     PartiallySigned,
     PartiallyWitnessed,
@@ -174,6 +200,7 @@ impl From<&Notification> for JustNotification {
     fn from(notification: &Notification) -> Self {
         match notification {
             Notification::KeyEventAdded(_) => JustNotification::KeyEventAdded,
+            Notification::KeyEventAccepted(_) => JustNotification::KeyEventAccepted,
             Notification::OutOfOrder(_) => JustNotification::OutOfOrder,
             Notification::PartiallySigned(_) => JustNotification::PartiallySigned,
             Notification::PartiallyWitnessed(_) => JustNotification::PartiallyWitnessed,
@@ -184,6 +211,8 @@ impl From<&Notification> for JustNotification {
             Notification::DupliciousEvent(_) => JustNotification::DuplicitousEvent,
             #[cfg(feature = "query")]
             Notification::KsnOutOfOrder(_) => JustNotification::KsnOutOfOrder,
+            #[cfg(feature = "query")]
+            Notification::KsnUpdated(_) => JustNotification::KsnUpdated,
             Notification::MissingDelegatingEvent(_) => JustNotification::MissingDelegatingEvent,
         }
     }
@@ -296,6 +296,245 @@ fn test_compute_state_at_sn() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_kel_replay() -> Result<(), Error> {
+    use said::version::format::SerializationFormats;
+
+    use crate::{
+        event::receipt::Receipt,
+        event_message::signed_event_message::{SignedEventMessage, SignedTransferableReceipt},
+        signer::Signer,
+    };
+
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let (not_bus, _escrows) = default_escrow_bus(events_db.clone(), EscrowConfig::default(), None);
+    let event_processor = BasicProcessor::new(events_db.clone(), Some(not_bus));
+    let event_storage = EventStorage::new(Arc::clone(&events_db));
+
+    let incept = |signer: &Signer| -> Result<SignedEventMessage, Error> {
+        let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+            .with_keys(vec![BasicPrefix::Ed25519(signer.public_key())])
+            .with_next_keys(vec![BasicPrefix::Ed25519(Signer::new().public_key())])
+            .build()?;
+        let signature = SelfSigningPrefix::Ed25519Sha512(signer.sign(icp.encode()?)?);
+        let icp = icp.sign(
+            vec![IndexedSignature::new_both_same(signature, 0)],
+            None,
+            None,
+        );
+        event_processor.process_notice(&Notice::Event(icp.clone()))?;
+        Ok(icp)
+    };
+    let signer = Signer::new();
+    let icp = incept(&signer)?;
+    let id = icp.event_message.data.get_prefix();
+    let validator_signer = Signer::new();
+    let validator_icp = incept(&validator_signer)?;
+    let validator_id = validator_icp.event_message.data.get_prefix();
+
+    let ixn = EventMsgBuilder::new(EventTypeTag::Ixn)
+        .with_prefix(&id)
+        .with_sn(1)
+        .with_previous_event(&icp.event_message.digest()?)
+        .build()?;
+    let signature = SelfSigningPrefix::Ed25519Sha512(signer.sign(ixn.encode()?)?);
+    let ixn = ixn.sign(
+        vec![IndexedSignature::new_both_same(signature, 0)],
+        None,
+        None,
+    );
+    event_processor.process_notice(&Notice::Event(ixn.clone()))?;
+
+    // Validator receipts inception event.
+    let receipt = Receipt::new(
+        SerializationFormats::JSON,
+        icp.event_message.digest()?,
+        id.clone(),
+        0,
+    );
+    let signature =
+        SelfSigningPrefix::Ed25519Sha512(validator_signer.sign(icp.event_message.encode()?)?);
+    let vrc = SignedTransferableReceipt::new(
+        receipt,
+        event_storage
+            .get_last_establishment_event_seal(&validator_id)
+            .unwrap(),
+        vec![IndexedSignature::new_both_same(signature, 0)],
+    );
+    event_processor.process_notice(&Notice::TransferableRct(vrc.clone()))?;
+
+    let replay = event_storage.get_kel_replay(&id)?;
+    assert_eq!(replay.len(), 3);
+    assert!(matches!(&replay[0], Notice::Event(event) if event.event_message == icp.event_message));
+    assert_eq!(replay[1], Notice::TransferableRct(vrc));
+    assert!(matches!(&replay[2], Notice::Event(event) if event.event_message == ixn.event_message));
+    assert!(event_storage
+        .get_kel_replay(&"EFb-WY7Ie1WPEgsioZz1CyzwnuCg-C9k2QCNpcUfM5Jf".parse()?)?
+        .is_empty());
+
+    // Replayed KEL is accepted by other controller.
+    let other_db_path = NamedTempFile::new().unwrap();
+    let other_db = Arc::new(RedbDatabase::new(other_db_path.path()).unwrap());
+    let other_processor = BasicProcessor::new(other_db.clone(), None);
+    other_processor.process_notice(&Notice::Event(validator_icp))?;
+    for notice in &replay {
+        other_processor.process_notice(notice)?;
+    }
+    assert_eq!(EventStorage::new(other_db).get_kel_replay(&id)?, replay);
+
+    Ok(())
+}
+
+#[test]
+fn test_state_at() -> Result<(), Error> {
+    use std::{thread, time::Duration};
+
+    use chrono::Local;
+
+    use crate::signer::Signer;
+
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let event_processor = BasicProcessor::new(events_db.clone(), None);
+    let event_storage = EventStorage::new(Arc::clone(&events_db));
+
+    let before_inception = Local::now();
+    thread::sleep(Duration::from_millis(10));
+    let (signer, next_signer) = (Signer::new(), Signer::new());
+    let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+        .with_keys(vec![BasicPrefix::Ed25519(signer.public_key())])
+        .with_next_keys(vec![BasicPrefix::Ed25519(next_signer.public_key())])
+        .build()?;
+    let signature = SelfSigningPrefix::Ed25519Sha512(signer.sign(icp.encode()?)?);
+    let id = icp.data.get_prefix();
+    let icp_digest = icp.digest()?;
+    event_processor.process_notice(&Notice::Event(icp.sign(
+        vec![IndexedSignature::new_both_same(signature, 0)],
+        None,
+        None,
+    )))?;
+    thread::sleep(Duration::from_millis(10));
+    let before_rotation = Local::now();
+    thread::sleep(Duration::from_millis(10));
+
+    let rot = EventMsgBuilder::new(EventTypeTag::Rot)
+        .with_prefix(&id)
+        .with_sn(1)
+        .with_previous_event(&icp_digest)
+        .with_keys(vec![BasicPrefix::Ed25519(next_signer.public_key())])
+        .with_next_keys(vec![BasicPrefix::Ed25519(Signer::new().public_key())])
+        .build()?;
+    let signature = SelfSigningPrefix::Ed25519Sha512(next_signer.sign(rot.encode()?)?);
+    event_processor.process_notice(&Notice::Event(rot.sign(
+        vec![IndexedSignature::new_both_same(signature, 0)],
+        None,
+        None,
+    )))?;
+
+    // Keys of inception event are still available after rotation.
+    let state = event_storage.get_state_at(&id, 0)?.unwrap();
+    assert_eq!(state.sn, 0);
+    assert_eq!(
+        state.current.public_keys,
+        vec![BasicPrefix::Ed25519(signer.public_key())]
+    );
+    assert_eq!(
+        event_storage.get_state_at(&id, 1)?,
+        event_storage.get_state(&id)
+    );
+    assert_eq!(event_storage.get_state_at(&id, 5)?, None);
+
+    assert_eq!(
+        event_storage.get_state_at_time(&id, before_inception)?,
+        None
+    );
+    assert_eq!(
+        event_storage.get_state_at_time(&id, before_rotation)?,
+        Some(state)
+    );
+    assert_eq!(
+        event_storage.get_state_at_time(&id, Local::now())?,
+        event_storage.get_state(&id)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_abandonment() -> Result<(), Error> {
+    use crate::{
+        actor::event_generator, event::KeyEvent, event_message::msg::KeriEvent, signer::Signer,
+    };
+
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let event_processor = BasicProcessor::new(events_db.clone(), None);
+    let event_storage = EventStorage::new(Arc::clone(&events_db));
+
+    let (signer, next_signer) = (Signer::new(), Signer::new());
+    let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+        .with_keys(vec![BasicPrefix::Ed25519(signer.public_key())])
+        .with_next_keys(vec![BasicPrefix::Ed25519(next_signer.public_key())])
+        .build()?;
+    let signature = SelfSigningPrefix::Ed25519Sha512(signer.sign(icp.encode()?)?);
+    let id = icp.data.get_prefix();
+    event_processor.process_notice(&Notice::Event(icp.sign(
+        vec![IndexedSignature::new_both_same(signature, 0)],
+        None,
+        None,
+    )))?;
+    assert!(!event_storage.get_state(&id).unwrap().is_abandoned());
+
+    let process = |event: KeriEvent<KeyEvent>, signer: &Signer| -> Result<(), Error> {
+        let signature = SelfSigningPrefix::Ed25519Sha512(signer.sign(event.encode()?)?);
+        event_processor.process_notice(&Notice::Event(event.sign(
+            vec![IndexedSignature::new_both_same(signature, 0)],
+            None,
+            None,
+        )))
+    };
+
+    // Rotate to null.
+    let rot = event_generator::abandon(
+        event_storage.get_state(&id).unwrap(),
+        vec![BasicPrefix::Ed25519(next_signer.public_key())],
+        &SignatureThreshold::Simple(1),
+    )?;
+    process(rot, &next_signer)?;
+    let state = event_storage.get_state(&id).unwrap();
+    assert_eq!(state.sn, 1);
+    assert!(state.is_abandoned());
+    assert!(state.current.next_keys_data.next_keys_hashes().is_empty());
+
+    // No further events are accepted.
+    let ixn = EventMsgBuilder::new(EventTypeTag::Ixn)
+        .with_prefix(&id)
+        .with_sn(2)
+        .with_previous_event(&state.last_event_digest.clone().into())
+        .build()?;
+    assert!(matches!(
+        process(ixn, &next_signer),
+        Err(Error::SemanticError(_))
+    ));
+    let new_signer = Signer::new();
+    let rot = EventMsgBuilder::new(EventTypeTag::Rot)
+        .with_prefix(&id)
+        .with_sn(2)
+        .with_previous_event(&state.last_event_digest.clone().into())
+        .with_keys(vec![BasicPrefix::Ed25519(new_signer.public_key())])
+        .with_next_keys(vec![BasicPrefix::Ed25519(Signer::new().public_key())])
+        .build()?;
+    assert!(matches!(
+        process(rot, &new_signer),
+        Err(Error::SemanticError(_))
+    ));
+    assert_eq!(event_storage.get_state(&id), Some(state.clone()));
+
+    assert!(event_generator::abandon(state, vec![], &SignatureThreshold::Simple(0)).is_err());
+
+    Ok(())
+}
 #[test]
 pub fn test_partial_rotation_simple_threshold() -> Result<(), Error> {
     use tempfile::Builder;
@@ -962,3 +1201,201 @@ pub fn test_custorial_rotation() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_accept_unsigned_only_own_identifiers() -> Result<(), Error> {
+    use std::time::Duration;
+
+    use crate::{
+        event_message::signed_event_message::SignedEventMessage, processor::escrow::EscrowPolicy,
+        signer::Signer,
+    };
+
+    struct AcceptUnsigned;
+    impl EscrowPolicy for AcceptUnsigned {
+        fn out_of_order_timeout(&self) -> Duration {
+            Duration::from_secs(60)
+        }
+        fn partially_signed_timeout(&self) -> Duration {
+            Duration::from_secs(60)
+        }
+        fn partially_witnessed_timeout(&self) -> Duration {
+            Duration::from_secs(60)
+        }
+        fn delegation_timeout(&self) -> Duration {
+            Duration::from_secs(60)
+        }
+        fn accept_unsigned(&self, _event: &SignedEventMessage) -> bool {
+            true
+        }
+    }
+
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let processor = BasicProcessor::with_policy(events_db.clone(), None, Arc::new(AcceptUnsigned));
+    let storage = EventStorage::new(events_db);
+
+    let signer = Signer::new();
+    let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+        .with_keys(vec![BasicPrefix::Ed25519(signer.public_key())])
+        .with_next_keys(vec![BasicPrefix::Ed25519(Signer::new().public_key())])
+        .build()?;
+    let id = icp.data.get_prefix();
+    let unsigned_icp = icp.sign(vec![], None, None);
+
+    // Unsigned event of foreign identifier still needs signatures.
+    processor.process_notice(&Notice::Event(unsigned_icp.clone()))?;
+    assert!(storage.get_state(&id).is_none());
+
+    // Once identifier is controlled locally, policy decides.
+    processor.register_own_identifier(id.clone())?;
+    processor.process_notice(&Notice::Event(unsigned_icp))?;
+    assert_eq!(storage.get_state(&id).unwrap().sn, 0);
+
+    // Other checks still apply, e.g. witness receipts are required.
+    let witness = BasicPrefix::Ed25519NT(Signer::new().public_key());
+    let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+        .with_keys(vec![BasicPrefix::Ed25519(signer.public_key())])
+        .with_next_keys(vec![BasicPrefix::Ed25519(Signer::new().public_key())])
+        .with_witness_list(&[witness])
+        .with_witness_threshold(&SignatureThreshold::Simple(1))
+        .build()?;
+    let witnessed_id = icp.data.get_prefix();
+    processor.register_own_identifier(witnessed_id.clone())?;
+    processor.process_notice(&Notice::Event(icp.sign(vec![], None, None)))?;
+    assert!(storage.get_state(&witnessed_id).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_process_secp256k1() -> Result<(), Error> {
+    use crate::signer::Signer;
+
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let event_processor = BasicProcessor::new(events_db.clone(), None);
+    let event_storage = EventStorage::new(Arc::clone(&events_db));
+
+    let (signer, next_signer) = (Signer::new_secp256k1(), Signer::new_secp256k1());
+    let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+        .with_keys(vec![signer.public_prefix(true)])
+        .with_next_keys(vec![next_signer.public_prefix(true)])
+        .build()?;
+    let id = icp.data.get_prefix();
+    let signature = signer.sign_prefix(icp.encode()?)?;
+    assert!(matches!(
+        signature,
+        SelfSigningPrefix::ECDSAsecp256k1Sha256(_)
+    ));
+    event_processor.process_notice(&Notice::Event(icp.sign(
+        vec![IndexedSignature::new_both_same(signature, 0)],
+        None,
+        None,
+    )))?;
+    assert_eq!(event_storage.get_state(&id).unwrap().sn, 0);
+
+    let rot = EventMsgBuilder::new(EventTypeTag::Rot)
+        .with_prefix(&id)
+        .with_sn(1)
+        .with_previous_event(&icp.digest()?)
+        .with_keys(vec![next_signer.public_prefix(true)])
+        .with_next_keys(vec![Signer::new_secp256k1().public_prefix(true)])
+        .build()?;
+
+    // Ed25519 signature doesn't verify against secp256k1 key.
+    let wrong_signature = SelfSigningPrefix::Ed25519Sha512(Signer::new().sign(rot.encode()?)?);
+    let _ = event_processor.process_notice(&Notice::Event(rot.sign(
+        vec![IndexedSignature::new_both_same(wrong_signature, 0)],
+        None,
+        None,
+    )));
+    assert_eq!(event_storage.get_state(&id).unwrap().sn, 0);
+
+    let signature = next_signer.sign_prefix(rot.encode()?)?;
+    event_processor.process_notice(&Notice::Event(rot.sign(
+        vec![IndexedSignature::new_both_same(signature, 0)],
+        None,
+        None,
+    )))?;
+    let state = event_storage.get_state(&id).unwrap();
+    assert_eq!(state.sn, 1);
+    assert_eq!(
+        state.current.public_keys,
+        vec![next_signer.public_prefix(true)]
+    );
+
+    Ok(())
+}
+
+#[cfg(all(feature = "cbor", feature = "mgpk"))]
+#[test]
+fn test_process_binary_serializations() -> Result<(), Error> {
+    use said::version::format::SerializationFormats;
+
+    use crate::{
+        actor::parse_notice_stream,
+        event::sections::seal::{EventSeal, Seal},
+        event_message::cesr_adapter::{parse_event_type, EventType},
+        signer::Signer,
+    };
+
+    for format in [SerializationFormats::CBOR, SerializationFormats::MGPK] {
+        let events_db_path = NamedTempFile::new().unwrap();
+        let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+        let event_processor = BasicProcessor::new(events_db.clone(), None);
+        let event_storage = EventStorage::new(Arc::clone(&events_db));
+
+        let signer = Signer::new();
+        let icp = EventMsgBuilder::new(EventTypeTag::Icp)
+            .with_keys(vec![BasicPrefix::Ed25519(signer.public_key())])
+            .with_next_keys(vec![BasicPrefix::Ed25519(Signer::new().public_key())])
+            .with_format(format)
+            .build()?;
+        let id = icp.data.get_prefix();
+        // Seal is nested struct, encoded as map as well.
+        let ixn = EventMsgBuilder::new(EventTypeTag::Ixn)
+            .with_prefix(&id)
+            .with_sn(1)
+            .with_previous_event(&icp.digest()?)
+            .with_seal(vec![Seal::Event(EventSeal::new(
+                id.clone(),
+                0,
+                icp.digest()?,
+            ))])
+            .with_format(format)
+            .build()?;
+
+        let mut stream = vec![];
+        for event in [&icp, &ixn] {
+            let encoded = event.encode()?;
+            assert_eq!(encoded.len(), event.serialization_info.size);
+            match parse_event_type(&encoded)? {
+                EventType::KeyEvent(decoded) => {
+                    assert_eq!(&decoded, event);
+                    // Inception prefix is replaced with placeholder as well.
+                    assert!(decoded
+                        .digest()?
+                        .verify_binding(&decoded.to_derivation_data()?));
+                }
+                _ => unreachable!(),
+            }
+            let signature = SelfSigningPrefix::Ed25519Sha512(signer.sign(encoded)?);
+            let signed = event.sign(
+                vec![IndexedSignature::new_both_same(signature, 0)],
+                None,
+                None,
+            );
+            stream.extend(Message::Notice(Notice::Event(signed)).to_cesr()?);
+        }
+
+        let notices = parse_notice_stream(&stream)?;
+        assert_eq!(notices.len(), 2);
+        for notice in &notices {
+            event_processor.process_notice(notice)?;
+        }
+        assert_eq!(event_storage.get_state(&id).unwrap().sn, 1);
+    }
+
+    Ok(())
+}
@@ -58,12 +58,25 @@ pub enum MoreInfoError {
 
 pub struct EventValidator<D: EventDatabase> {
     event_storage: EventStorage<D>,
+    check_signatures: bool,
 }
 
 impl<D: EventDatabase> EventValidator<D> {
     pub fn new(event_database: Arc<D>) -> Self {
         Self {
             event_storage: EventStorage::new(event_database),
+            check_signatures: true,
+        }
+    }
+
+    /// Validator that doesn't check controller signatures of key events.
+    /// State transition, delegation and witness receipts are still checked.
+    /// Used for unsigned events of locally controlled identifiers, see
+    /// `EscrowPolicy::accept_unsigned`.
+    pub fn without_signatures(self) -> Self {
+        Self {
+            check_signatures: false,
+            ..self
         }
     }
 }
@@ -82,15 +95,23 @@ impl<D: EventDatabase> EventValidator<D> {
             .event_storage
             .get_state(&signed_event.event_message.data.get_prefix())
         {
+            // Events following abandonment are rejected, not escrowed.
+            Some(state)
+                if state.is_abandoned() && signed_event.event_message.data.get_sn() > state.sn =>
+            {
+                return Err(Error::SemanticError("Identifier is abandoned".into()));
+            }
             Some(state) => {
                 let new_state = signed_event.event_message.apply_to(state.clone())?;
                 // In case of rotation event, check if previous next threshold is satisfied
                 if let EventData::Rot(rot) = signed_event.event_message.data.get_event_data() {
-                    let new_public_keys = rot.key_config.public_keys;
-                    state.current.next_keys_data.check_threshold(
-                        &new_public_keys,
-                        signed_event.signatures.iter().map(|sig| &sig.index),
-                    )?;
+                    if self.check_signatures {
+                        let new_public_keys = rot.key_config.public_keys;
+                        state.current.next_keys_data.check_threshold(
+                            &new_public_keys,
+                            signed_event.signatures.iter().map(|sig| &sig.index),
+                        )?;
+                    }
                 }
                 new_state
             }
@@ -99,10 +120,11 @@ impl<D: EventDatabase> EventValidator<D> {
                 .apply_to(IdentifierState::default())?,
         };
         // match on verification result
-        let ver_result = new_state.current.verify(
-            &signed_event.event_message.encode()?,
-            &signed_event.signatures,
-        )?;
+        let ver_result = !self.check_signatures
+            || new_state.current.verify(
+                &signed_event.event_message.encode()?,
+                &signed_event.signatures,
+            )?;
         // If delegated event, check its delegator seal.
         if let Some(seal) = self.get_delegator_seal(signed_event)? {
             self.validate_seal(seal, &signed_event.event_message)?;
@@ -58,7 +58,10 @@ impl Typeable for MailboxRoute {
     }
 }
 
+/// Arguments of mailbox query. Construct them with [`QueryArgsMbx::new`], as
+/// new optional fields may be added.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct QueryArgsMbx {
     /// Controller's currently used identifier
     pub pre: IdentifierPrefix,
@@ -68,9 +71,37 @@ pub struct QueryArgsMbx {
     pub i: IdentifierPrefix,
     /// To which witness given query message reply will be sent
     pub src: IdentifierPrefix,
+    /// Maximum number of messages of each topic to be returned
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub limit: Option<usize>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+impl QueryArgsMbx {
+    /// Query of `i` identifier's mailbox asked by `pre`, sent to `src`.
+    /// Messages of each topic are returned starting from index set in
+    /// `topics`.
+    pub fn new(
+        pre: IdentifierPrefix,
+        i: IdentifierPrefix,
+        src: IdentifierPrefix,
+        topics: QueryTopics,
+    ) -> Self {
+        Self {
+            pre,
+            topics,
+            i,
+            src,
+            limit: None,
+        }
+    }
+
+    /// Sets maximum number of messages of each topic to be returned.
+    pub fn with_limit(self, limit: Option<usize>) -> Self {
+        Self { limit, ..self }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct QueryTopics {
     #[serde(rename = "/receipt")]
     pub receipt: usize,
@@ -112,3 +143,36 @@ fn test_query_mbx_deserialize() {
 
     assert_eq!(input_query, &String::from_utf8_lossy(&qr.encode().unwrap()));
 }
+
+#[test]
+fn test_query_mbx_limit() {
+    let id: IdentifierPrefix = "EC61gZ9lCKmHAS7U5ehUfEbGId5rcY0D7MirFZHDQcE2"
+        .parse()
+        .unwrap();
+    let witness: IdentifierPrefix = "BBilc4-L3tFUnfM_wJr4S4OJanAv_VmF_dJNN6vkf2Ha"
+        .parse()
+        .unwrap();
+    let qry = MailboxQuery::new_query(
+        MailboxRoute::Mbx {
+            reply_route: "".to_string(),
+            args: QueryArgsMbx::new(
+                id.clone(),
+                id,
+                witness,
+                QueryTopics {
+                    receipt: 2,
+                    ..Default::default()
+                },
+            )
+            .with_limit(Some(10)),
+        },
+        SerializationFormats::JSON,
+        HashFunctionCode::Blake3_256,
+    );
+    let encoded = qry.encode().unwrap();
+    assert!(String::from_utf8_lossy(&encoded).contains(r#""limit":10"#));
+
+    let parsed: MailboxQuery = serde_json::from_slice(&encoded).unwrap();
+    assert_eq!(parsed.get_args().limit, Some(10));
+    assert_eq!(parsed.get_args().topics.receipt, 2);
+}
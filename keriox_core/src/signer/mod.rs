@@ -1,9 +1,12 @@
+use std::sync::Arc;
+
 use rand::rngs::OsRng;
+use zeroize::Zeroizing;
 
 use crate::{
     error::Error,
-    keys::{KeysError, PrivateKey, PublicKey},
-    prefix::SeedPrefix,
+    keys::{take_secret, KeySource, KeysError, OsKeySource, PrivateKey, PublicKey},
+    prefix::{BasicPrefix, SeedPrefix, SelfSigningPrefix},
 };
 
 pub trait KeyManager {
@@ -17,6 +20,7 @@ pub struct CryptoBox {
     signer: Signer,
     next_priv_key: PrivateKey,
     pub next_pub_key: PublicKey,
+    source: Arc<dyn KeySource>,
 }
 
 impl KeyManager for CryptoBox {
@@ -33,11 +37,12 @@ impl KeyManager for CryptoBox {
     }
 
     fn rotate(&mut self) -> Result<(), Error> {
-        let (next_pub_key, next_priv_key) = generate_key_pair()?;
+        let (next_pub_key, next_priv_key) = generate_key_pair(self.source.as_ref())?;
 
         let new_signer = Signer {
             priv_key: self.next_priv_key.clone(),
             pub_key: self.next_pub_key.clone(),
+            key_type: KeyType::Ed25519,
         };
         self.signer = new_signer;
         self.next_priv_key = next_priv_key;
@@ -48,29 +53,68 @@ impl KeyManager for CryptoBox {
 }
 impl CryptoBox {
     pub fn new() -> Result<Self, Error> {
-        let signer = Signer::new();
-        let (next_pub_key, next_priv_key) = generate_key_pair()?;
+        Self::with_source(Arc::new(OsKeySource))
+    }
+
+    /// Creates key manager generating all its keys with bytes of `source`.
+    pub fn with_source(source: Arc<dyn KeySource>) -> Result<Self, Error> {
+        let signer = Signer::from_source(source.as_ref());
+        let (next_pub_key, next_priv_key) = generate_key_pair(source.as_ref())?;
         Ok(CryptoBox {
             signer,
             next_pub_key,
             next_priv_key,
+            source,
         })
     }
 }
 
+/// Signature scheme of signer's key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    Ed25519,
+    ECDSAsecp256k1,
+}
+
 pub struct Signer {
     priv_key: PrivateKey,
     pub_key: PublicKey,
+    key_type: KeyType,
 }
 
 impl Signer {
     /// Creates a new Signer with a random key.
     pub fn new() -> Self {
-        let ed = ed25519_dalek::SigningKey::generate(&mut OsRng);
+        Self::from_source(&OsKeySource)
+    }
+
+    /// Creates a new Signer with Ed25519 key generated with bytes of
+    /// `source`.
+    pub fn from_source(source: &dyn KeySource) -> Self {
+        let mut seed = Zeroizing::new([0u8; 32]);
+        source.fill_bytes(&mut seed[..]);
+        let ed = ed25519_dalek::SigningKey::from_bytes(&seed);
         let pub_key = PublicKey::new(ed.verifying_key().to_bytes().to_vec());
-        let priv_key = PrivateKey::new(ed.to_bytes().to_vec());
+        let priv_key = PrivateKey::new(take_secret(ed.to_bytes()));
+
+        Signer {
+            pub_key,
+            priv_key,
+            key_type: KeyType::Ed25519,
+        }
+    }
 
-        Signer { pub_key, priv_key }
+    /// Creates a new Signer with a random ECDSA secp256k1 key.
+    pub fn new_secp256k1() -> Self {
+        let sk = k256::ecdsa::SigningKey::random(&mut OsRng);
+        let pub_key = PublicKey::new(k256::ecdsa::VerifyingKey::from(&sk).to_bytes().to_vec());
+        let priv_key = PrivateKey::new(take_secret(sk.to_bytes()));
+
+        Signer {
+            pub_key,
+            priv_key,
+            key_type: KeyType::ECDSAsecp256k1,
+        }
     }
 
     /// Creates a new Signer with the given ED25519_dalek private key.
@@ -81,25 +125,61 @@ impl Signer {
         Ok(Signer {
             priv_key: PrivateKey::new(priv_key.as_bytes().to_vec()),
             pub_key: PublicKey::new(pub_key.as_bytes().to_vec()),
+            key_type: KeyType::Ed25519,
         })
     }
 
+    /// Creates a new Signer from Ed25519 or ECDSA secp256k1 seed.
     pub fn new_with_seed(seed: &SeedPrefix) -> Result<Self, Error> {
         let (public_key, private_key) = seed.derive_key_pair()?;
+        let key_type = match seed {
+            SeedPrefix::RandomSeed256ECDSAsecp256k1(_) => KeyType::ECDSAsecp256k1,
+            _ => KeyType::Ed25519,
+        };
 
         Ok(Signer {
             priv_key: private_key,
             pub_key: public_key,
+            key_type,
         })
     }
 
     pub fn sign(&self, msg: impl AsRef<[u8]>) -> Result<Vec<u8>, KeysError> {
-        self.priv_key.sign_ed(msg.as_ref())
+        match self.key_type {
+            KeyType::Ed25519 => self.priv_key.sign_ed(msg.as_ref()),
+            KeyType::ECDSAsecp256k1 => self.priv_key.sign_ecdsa(msg.as_ref()),
+        }
+    }
+
+    /// Signs message and tags signature with derivation code matching
+    /// signer's key type.
+    pub fn sign_prefix(&self, msg: impl AsRef<[u8]>) -> Result<SelfSigningPrefix, KeysError> {
+        let signature = self.sign(msg)?;
+        Ok(match self.key_type {
+            KeyType::Ed25519 => SelfSigningPrefix::Ed25519Sha512(signature),
+            KeyType::ECDSAsecp256k1 => SelfSigningPrefix::ECDSAsecp256k1Sha256(signature),
+        })
     }
 
     pub fn public_key(&self) -> PublicKey {
         self.pub_key.clone()
     }
+
+    /// Returns public key tagged with derivation code matching signer's key
+    /// type.
+    pub fn public_prefix(&self, transferable: bool) -> BasicPrefix {
+        let key = self.public_key();
+        match (self.key_type, transferable) {
+            (KeyType::Ed25519, true) => BasicPrefix::Ed25519(key),
+            (KeyType::Ed25519, false) => BasicPrefix::Ed25519NT(key),
+            (KeyType::ECDSAsecp256k1, true) => BasicPrefix::ECDSAsecp256k1(key),
+            (KeyType::ECDSAsecp256k1, false) => BasicPrefix::ECDSAsecp256k1NT(key),
+        }
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
 }
 
 impl Default for Signer {
@@ -108,12 +188,9 @@ impl Default for Signer {
     }
 }
 
-fn generate_key_pair() -> Result<(PublicKey, PrivateKey), Error> {
-    let kp = ed25519_dalek::SigningKey::generate(&mut OsRng {});
-    let (vk, sk) = (kp.verifying_key(), kp);
-    let vk = PublicKey::new(vk.to_bytes().to_vec());
-    let sk = PrivateKey::new(sk.to_bytes().to_vec());
-    Ok((vk, sk))
+fn generate_key_pair(source: &dyn KeySource) -> Result<(PublicKey, PrivateKey), Error> {
+    let signer = Signer::from_source(source);
+    Ok((signer.pub_key.clone(), signer.priv_key.clone()))
 }
 
 /// Helper function to generate keypairs that can be used for signing in tests.
@@ -5,7 +5,7 @@ use crate::{
     error::Error,
     event::{
         event_data::EventData,
-        sections::{threshold::SignatureThreshold, KeyConfig},
+        sections::{key_config::SignatureError, threshold::SignatureThreshold, KeyConfig},
     },
     event_message::EventTypeTag,
     prefix::{BasicPrefix, IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
@@ -97,6 +97,7 @@ impl WitnessConfig {
                     .collect::<Vec<_>>();
                 match t.enough_signatures(&indexes) {
                     Ok(_) => Ok(true),
+                    Err(SignatureError::NotEnoughSigsError) => Ok(false),
                     Err(e) => Err(Error::KeyConfigError(e)),
                 }
             }
@@ -202,6 +203,14 @@ impl IdentifierState {
     pub fn apply<T: EventSemantics>(self, event: &T) -> Result<Self, Error> {
         event.apply_to(self)
     }
+
+    /// Is Abandoned
+    ///
+    /// Identifier whose last establishment event commits to no next keys
+    /// can't be rotated anymore, so no further events are accepted.
+    pub fn is_abandoned(&self) -> bool {
+        self.current.next_keys_data.next_keys_hashes().is_empty()
+    }
 }
 
 /// EventSemantics
@@ -1,12 +1,18 @@
 use std::{fs, sync::Arc};
 
 use keri_core::{
-    actor::{parse_event_stream, parse_notice_stream, process_notice},
+    actor::{parse_event_stream, parse_new_notices, parse_notice_stream, process_notice},
     database::redb::RedbDatabase,
     error::Error,
-    event_message::signed_event_message::{Message, Notice},
-    prefix::IdentifierPrefix,
+    event_message::{
+        cesr_adapter::ParseError,
+        protocol_version::{ProtocolVersion, VersionError},
+        signature::Nontransferable,
+        signed_event_message::{Message, Notice},
+    },
+    prefix::{BasicPrefix, IdentifierPrefix, SelfSigningPrefix},
     processor::{basic_processor::BasicProcessor, event_storage::EventStorage},
+    signer::Signer,
 };
 use tempfile::NamedTempFile;
 
@@ -81,3 +87,79 @@ fn test_process() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_parse_new_notices() -> Result<(), Error> {
+    let events_db_path = NamedTempFile::new().unwrap();
+    let events_db = Arc::new(RedbDatabase::new(events_db_path.path()).unwrap());
+    let (processor, storage) = (
+        BasicProcessor::new(events_db.clone(), None),
+        EventStorage::new(events_db.clone()),
+    );
+
+    let icp_raw = br#"{"v":"KERI10JSON0001e7_","t":"icp","d":"EBfxc4RiVY6saIFmUfEtETs1FcqmktZW88UkbnOg0Qen","i":"EBfxc4RiVY6saIFmUfEtETs1FcqmktZW88UkbnOg0Qen","s":"0","kt":"2","k":["DErocgXD2RGSyvn3MObcx59jeOsEQhv2TqHirVkzrp0Q","DFXLiTjiRdSBPLL6hLa0rskIxk3dh4XwJLfctkJFLRSS","DE9YgIQVgpLwocTVrG8tidKScsQSMWwLWywNC48fhq4f"],"nt":"2","n":["EDJk5EEpC4-tQ7YDwBiKbpaZahh1QCyQOnZRF7p2i8k8","EAXfDjKvUFRj-IEB_o4y-Y_qeJAjYfZtOMD9e7vHNFss","EN8l6yJC2PxribTN0xfri6bLz34Qvj-x3cNwcV3DvT2m"],"bt":"0","b":[],"c":[],"a":[]}-AADAAD4SyJSYlsQG22MGXzRGz2PTMqpkgOyUfq7cS99sC2BCWwdVmEMKiTEeWe5kv-l_d9auxdadQuArLtAGEArW8wEABD0z_vQmFImZXfdR-0lclcpZFfkJJJNXDcUNrf7a-mGsxNLprJo-LROwDkH5m7tVrb-a1jcor2dHD9Jez-r4bQIACBFeU05ywfZycLdR0FxCvAR9BfV9im8tWe1DglezqJLf-vHRQSChY1KafbYNc96hYYpbuN90WzuCRMgV8KgRsEC"#;
+
+    // Unknown event is decoded the same way as by `parse_notice_stream`.
+    let new_notices = parse_new_notices(icp_raw, &storage)?;
+    assert_eq!(new_notices, parse_notice_stream(icp_raw).unwrap());
+    assert_eq!(new_notices.len(), 1);
+
+    process_notice(new_notices[0].clone(), &processor)?;
+
+    // Event already accepted into KEL is skipped before decoding.
+    assert!(parse_new_notices(icp_raw, &storage)?.is_empty());
+
+    // Malformed stream is still rejected.
+    assert!(parse_new_notices(&icp_raw[..100], &storage).is_err());
+
+    // Accepted event resent with receipt of a witness is kept, so that the
+    // receipt is stored.
+    let id: IdentifierPrefix = "EBfxc4RiVY6saIFmUfEtETs1FcqmktZW88UkbnOg0Qen".parse()?;
+    let mut icp = storage
+        .get_event_at_sn(&id, 0)
+        .unwrap()
+        .signed_event_message;
+    let witness = Signer::new();
+    let couplet = (
+        BasicPrefix::Ed25519NT(witness.public_key()),
+        SelfSigningPrefix::Ed25519Sha512(witness.sign(icp.event_message.encode()?)?),
+    );
+    icp.witness_receipts = Some(vec![Nontransferable::Couplet(vec![couplet.clone()])]);
+    let stream = Message::Notice(Notice::Event(icp)).to_cesr()?;
+    let new_notices = parse_new_notices(&stream, &storage)?;
+    assert_eq!(new_notices.len(), 1);
+    assert!(storage.get_nt_receipts(&id, 0)?.is_none());
+
+    process_notice(new_notices[0].clone(), &processor)?;
+    let receipts = storage.get_nt_receipts(&id, 0)?.unwrap();
+    assert_eq!(
+        receipts.signatures,
+        vec![Nontransferable::Couplet(vec![couplet])]
+    );
+    assert_eq!(storage.get_state(&id).unwrap().sn, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_unsupported_version() {
+    let icp_raw = r#"{"v":"KERI10JSON0001e7_","t":"icp","d":"EBfxc4RiVY6saIFmUfEtETs1FcqmktZW88UkbnOg0Qen","i":"EBfxc4RiVY6saIFmUfEtETs1FcqmktZW88UkbnOg0Qen","s":"0","kt":"2","k":["DErocgXD2RGSyvn3MObcx59jeOsEQhv2TqHirVkzrp0Q","DFXLiTjiRdSBPLL6hLa0rskIxk3dh4XwJLfctkJFLRSS","DE9YgIQVgpLwocTVrG8tidKScsQSMWwLWywNC48fhq4f"],"nt":"2","n":["EDJk5EEpC4-tQ7YDwBiKbpaZahh1QCyQOnZRF7p2i8k8","EAXfDjKvUFRj-IEB_o4y-Y_qeJAjYfZtOMD9e7vHNFss","EN8l6yJC2PxribTN0xfri6bLz34Qvj-x3cNwcV3DvT2m"],"bt":"0","b":[],"c":[],"a":[]}-AADAAD4SyJSYlsQG22MGXzRGz2PTMqpkgOyUfq7cS99sC2BCWwdVmEMKiTEeWe5kv-l_d9auxdadQuArLtAGEArW8wEABD0z_vQmFImZXfdR-0lclcpZFfkJJJNXDcUNrf7a-mGsxNLprJo-LROwDkH5m7tVrb-a1jcor2dHD9Jez-r4bQIACBFeU05ywfZycLdR0FxCvAR9BfV9im8tWe1DglezqJLf-vHRQSChY1KafbYNc96hYYpbuN90WzuCRMgV8KgRsEC"#;
+    assert_eq!(parse_event_stream(icp_raw.as_bytes()).unwrap().len(), 1);
+
+    // Other major versions and newer minor ones are rejected by handlers
+    // parsing streams the same way `StrictParser` does.
+    let major = icp_raw.replace("KERI10", "KERI20");
+    assert!(matches!(
+        parse_event_stream(major.as_bytes()),
+        Err(ParseError::UnsupportedVersion(
+            VersionError::UnsupportedMajorVersion(ProtocolVersion { major: 2, minor: 0 })
+        ))
+    ));
+    let minor = icp_raw.replace("KERI10", "KERI11");
+    assert!(matches!(
+        parse_notice_stream(minor.as_bytes()),
+        Err(ParseError::UnsupportedVersion(
+            VersionError::NewerMinorVersion(_)
+        ))
+    ));
+}
@@ -0,0 +1,85 @@
+/*
+ * C ABI of keri-sdk, built by keriox-ffi crate as shared and static
+ * library. See `src/lib.rs` for documentation of functions.
+ *
+ * Every function returns `KeriStatus` and writes results to out
+ * parameters. Buffers written by the library are owned by the caller and
+ * have to be released with `keri_buffer_free`. Strings passed to the
+ * library are NUL terminated UTF-8, buffers are borrowed for the duration
+ * of the call only. Message of the last error on calling thread is
+ * available with `keri_last_error`.
+ */
+#ifndef KERIOX_H
+#define KERIOX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum KeriStatus {
+  KERI_STATUS_OK = 0,
+  KERI_STATUS_NULL_ARGUMENT = 1,
+  KERI_STATUS_INVALID_ARGUMENT = 2,
+  KERI_STATUS_FAILED = 3,
+  KERI_STATUS_PANIC = 4,
+} KeriStatus;
+
+typedef struct KeriBuffer {
+  uint8_t *data;
+  size_t len;
+} KeriBuffer;
+
+typedef struct KeriController KeriController;
+
+KeriStatus keri_last_error(KeriBuffer *out);
+
+void keri_buffer_free(KeriBuffer buffer);
+
+KeriStatus keri_controller_open(const char *db_path,
+                                const char *keystore_path,
+                                const char *passphrase,
+                                KeriController **out);
+
+void keri_controller_free(KeriController *handle);
+
+KeriStatus keri_incept(const KeriController *handle, KeriBuffer *out);
+
+KeriStatus keri_rotate(const KeriController *handle, const char *id);
+
+KeriStatus keri_sign(const KeriController *handle,
+                     const char *id,
+                     const uint8_t *data,
+                     size_t len,
+                     KeriBuffer *out);
+
+KeriStatus keri_verify(const KeriController *handle,
+                       const uint8_t *stream,
+                       size_t len,
+                       KeriBuffer *signer,
+                       KeriBuffer *data);
+
+KeriStatus keri_verify_credential(const KeriController *handle,
+                                  const uint8_t *stream,
+                                  size_t len,
+                                  KeriBuffer *out);
+
+KeriStatus keri_resolve_oobi(const KeriController *handle,
+                             const char *url,
+                             KeriBuffer *out);
+
+KeriStatus keri_get_kel(const KeriController *handle,
+                        const char *id,
+                        KeriBuffer *out);
+
+KeriStatus keri_process_kel(const KeriController *handle,
+                            const uint8_t *stream,
+                            size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* KERIOX_H */
@@ -0,0 +1,493 @@
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+    sync::Arc,
+};
+
+use keri_core::{
+    actor::parse_event_stream, database::redb::RedbDatabase,
+    event_message::signed_event_message::Message, oobi_manager::OobiManager,
+    prefix::IdentifierPrefix,
+};
+use teliox::database::redb::RedbTelDatabase;
+
+use keri_sdk::{Controller, EncryptedFileKeyStore, HttpOobiFetcher};
+
+/// Result of C ABI call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeriStatus {
+    Ok = 0,
+    /// Required pointer argument is null.
+    NullArgument = 1,
+    /// Argument isn't valid UTF-8 or identifier.
+    InvalidArgument = 2,
+    /// Operation failed, see `keri_last_error`.
+    Failed = 3,
+    /// Operation panicked. Handle shouldn't be used anymore.
+    Panic = 4,
+}
+
+/// Byte buffer allocated by the library. It is owned by the caller and has
+/// to be released with `keri_buffer_free`. Buffers passed to the library
+/// are only borrowed for the duration of the call.
+#[repr(C)]
+pub struct KeriBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl KeriBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let mut bytes = bytes.into_boxed_slice();
+        let buffer = Self {
+            data: bytes.as_mut_ptr(),
+            len: bytes.len(),
+        };
+        std::mem::forget(bytes);
+        buffer
+    }
+}
+
+/// Opaque controller handle. Functions taking it return `KeriStatus` and
+/// write results to out parameters, message of the last error on calling
+/// thread is available with `keri_last_error`. Strings passed to them are
+/// NUL terminated UTF-8.
+pub struct KeriController {
+    inner: Controller<RedbDatabase, RedbTelDatabase>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+enum FfiError {
+    Null,
+    Invalid(String),
+    Failed(String),
+}
+
+impl From<String> for FfiError {
+    fn from(message: String) -> Self {
+        FfiError::Failed(message)
+    }
+}
+
+/// Runs `f`, recording its error message and catching panics.
+fn call(f: impl FnOnce() -> Result<(), FfiError>) -> KeriStatus {
+    let (status, error) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => (KeriStatus::Ok, None),
+        Ok(Err(FfiError::Null)) => (
+            KeriStatus::NullArgument,
+            Some("Null pointer argument".to_string()),
+        ),
+        Ok(Err(FfiError::Invalid(e))) => (KeriStatus::InvalidArgument, Some(e)),
+        Ok(Err(FfiError::Failed(e))) => (KeriStatus::Failed, Some(e)),
+        Err(_) => (KeriStatus::Panic, Some("Operation panicked".to_string())),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = error);
+    status
+}
+
+unsafe fn string<'a>(ptr: *const c_char) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::Null);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FfiError::Invalid("String isn't UTF-8".to_string()))
+}
+
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], FfiError> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(FfiError::Null),
+        (false, _) => Ok(std::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+unsafe fn prefix(ptr: *const c_char) -> Result<IdentifierPrefix, FfiError> {
+    let id = string(ptr)?;
+    id.parse()
+        .map_err(|_| FfiError::Invalid(format!("Invalid identifier {}", id)))
+}
+
+unsafe fn controller<'a>(
+    handle: *const KeriController,
+) -> Result<&'a Controller<RedbDatabase, RedbTelDatabase>, FfiError> {
+    handle
+        .as_ref()
+        .map(|handle| &handle.inner)
+        .ok_or(FfiError::Null)
+}
+
+unsafe fn write<T>(out: *mut T, value: T) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(FfiError::Null);
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// Writes message of the last error on calling thread to `out`, or empty
+/// buffer if the last call succeeded.
+///
+/// # Safety
+/// `out` has to point to writable `KeriBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn keri_last_error(out: *mut KeriBuffer) -> KeriStatus {
+    let message = LAST_ERROR.with(|last| last.borrow().clone());
+    if out.is_null() {
+        return KeriStatus::NullArgument;
+    }
+    out.write(KeriBuffer::new(message.unwrap_or_default().into_bytes()));
+    KeriStatus::Ok
+}
+
+/// Releases buffer returned by the library. Empty buffer is ignored.
+///
+/// # Safety
+/// `buffer` has to be returned by the library and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn keri_buffer_free(buffer: KeriBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+/// Opens controller whose databases are kept in `db_path` and key store,
+/// unlocked with `passphrase`, in `keystore_path`. Handle is written to
+/// `out` and has to be released with `keri_controller_free`.
+///
+/// # Safety
+/// Strings have to be NUL terminated, `out` has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn keri_controller_open(
+    db_path: *const c_char,
+    keystore_path: *const c_char,
+    passphrase: *const c_char,
+    out: *mut *mut KeriController,
+) -> KeriStatus {
+    call(|| {
+        // Checked first, so that controller holding the database isn't
+        // leaked.
+        if out.is_null() {
+            return Err(FfiError::Null);
+        }
+        let inner = Controller::builder()
+            .with_db_path(string(db_path)?)
+            .with_keystore(Arc::new(EncryptedFileKeyStore::new(string(
+                keystore_path,
+            )?)))
+            .build()?;
+        inner.unlock(string(passphrase)?)?;
+        write(out, Box::into_raw(Box::new(KeriController { inner })))
+    })
+}
+
+/// Releases controller handle. Null handle is ignored.
+///
+/// # Safety
+/// `handle` has to be opened with `keri_controller_open` and not released
+/// before.
+#[no_mangle]
+pub unsafe extern "C" fn keri_controller_free(handle: *mut KeriController) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Incepts single key identifier with keys saved in key store and writes
+/// its prefix to `out`.
+///
+/// # Safety
+/// `handle` has to be valid, `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn keri_incept(
+    handle: *const KeriController,
+    out: *mut KeriBuffer,
+) -> KeriStatus {
+    call(|| {
+        let controller = controller(handle)?;
+        let identifier = controller.incept_with_keystore()?;
+        let id = controller.manage(identifier)?.id.to_string();
+        write(out, KeriBuffer::new(id.into_bytes()))
+    })
+}
+
+/// Rotates identifier to its next keys, saved in key store.
+///
+/// # Safety
+/// `handle` has to be valid, `id` NUL terminated.
+#[no_mangle]
+pub unsafe extern "C" fn keri_rotate(
+    handle: *const KeriController,
+    id: *const c_char,
+) -> KeriStatus {
+    call(|| {
+        let controller = controller(handle)?;
+        let keystore = controller
+            .keystore()
+            .ok_or("No keystore configured".to_string())?;
+        controller.identifier(&prefix(id)?)?.rotate(keystore)?;
+        Ok(())
+    })
+}
+
+/// Signs JSON `data` with identifier's current key and writes CESR stream
+/// of data followed by the signature to `out`.
+///
+/// # Safety
+/// `handle` has to be valid, `id` NUL terminated, `data` readable for
+/// `len` bytes and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn keri_sign(
+    handle: *const KeriController,
+    id: *const c_char,
+    data: *const u8,
+    len: usize,
+    out: *mut KeriBuffer,
+) -> KeriStatus {
+    call(|| {
+        let controller = controller(handle)?;
+        let identifier = controller.identifier(&prefix(id)?)?;
+        let data = bytes(data, len)?;
+        let sig = controller.sign_with_keystore(&identifier.id, data)?;
+        let signed = identifier.sign_data(data, sig)?;
+        write(out, KeriBuffer::new(signed))
+    })
+}
+
+/// Verifies data signed with `keri_sign` against known KELs. Writes
+/// signer to `signer` and signed data to `data`.
+///
+/// # Safety
+/// `handle` has to be valid, `stream` readable for `len` bytes, `signer`
+/// and `data` writable.
+#[no_mangle]
+pub unsafe extern "C" fn keri_verify(
+    handle: *const KeriController,
+    stream: *const u8,
+    len: usize,
+    signer: *mut KeriBuffer,
+    data: *mut KeriBuffer,
+) -> KeriStatus {
+    call(|| {
+        let controller = controller(handle)?;
+        if signer.is_null() || data.is_null() {
+            return Err(FfiError::Null);
+        }
+        let (id, signed) =
+            controller.verify_signed_data(bytes(stream, len)?)?;
+        write(signer, KeriBuffer::new(id.to_string().into_bytes()))?;
+        write(data, KeriBuffer::new(signed))
+    })
+}
+
+/// Verifies signed ACDC, see `Controller::verify_credential`, and writes
+/// it as JSON to `out`.
+///
+/// # Safety
+/// `handle` has to be valid, `stream` readable for `len` bytes and `out`
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn keri_verify_credential(
+    handle: *const KeriController,
+    stream: *const u8,
+    len: usize,
+    out: *mut KeriBuffer,
+) -> KeriStatus {
+    call(|| {
+        let acdc =
+            controller(handle)?.verify_credential(bytes(stream, len)?)?;
+        write(out, KeriBuffer::new(acdc.encode()?))
+    })
+}
+
+/// Resolves OOBI URL with HTTP and writes identifier it is about to `out`.
+///
+/// # Safety
+/// `handle` has to be valid, `url` NUL terminated and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn keri_resolve_oobi(
+    handle: *const KeriController,
+    url: *const c_char,
+    out: *mut KeriBuffer,
+) -> KeriStatus {
+    call(|| {
+        let controller = controller(handle)?;
+        let resolver = controller.kel.oobi_resolver(
+            Arc::new(OobiManager::new(
+                controller.kel.storage.events_db.clone(),
+            )),
+            Arc::new(HttpOobiFetcher::new()),
+        );
+        let id = resolver.resolve_oobi(string(url)?)?;
+        write(out, KeriBuffer::new(id.to_string().into_bytes()))
+    })
+}
+
+/// Writes KEL of identifier, with receipts, as CESR stream to `out`.
+///
+/// # Safety
+/// `handle` has to be valid, `id` NUL terminated and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn keri_get_kel(
+    handle: *const KeriController,
+    id: *const c_char,
+    out: *mut KeriBuffer,
+) -> KeriStatus {
+    call(|| {
+        let controller = controller(handle)?;
+        let id = prefix(id)?;
+        let kel = controller
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(&id)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("No KEL of {}", id))?
+            .into_iter()
+            .map(|notice| Message::Notice(notice).to_cesr())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+            .concat();
+        write(out, KeriBuffer::new(kel))
+    })
+}
+
+/// Processes CESR stream of KEL events and receipts, e.g. one written by
+/// `keri_get_kel` of other controller.
+///
+/// # Safety
+/// `handle` has to be valid and `stream` readable for `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn keri_process_kel(
+    handle: *const KeriController,
+    stream: *const u8,
+    len: usize,
+) -> KeriStatus {
+    call(|| {
+        let messages = parse_event_stream(bytes(stream, len)?)
+            .map_err(|e| FfiError::Invalid(e.to_string()))?;
+        Ok(controller(handle)?.process_kel(&messages)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use tempfile::Builder;
+
+    use super::*;
+
+    unsafe fn take(buffer: KeriBuffer) -> Vec<u8> {
+        let bytes =
+            std::slice::from_raw_parts(buffer.data, buffer.len).to_vec();
+        keri_buffer_free(buffer);
+        bytes
+    }
+
+    fn empty() -> KeriBuffer {
+        KeriBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    #[test]
+    fn test_c_abi() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let path = |name: &str| {
+            CString::new(root.path().join(name).to_str().unwrap()).unwrap()
+        };
+        let passphrase = CString::new("passphrase").unwrap();
+        unsafe {
+            assert_eq!(
+                keri_controller_open(
+                    path("db").as_ptr(),
+                    path("keys.json").as_ptr(),
+                    passphrase.as_ptr(),
+                    ptr::null_mut(),
+                ),
+                KeriStatus::NullArgument
+            );
+
+            // Database isn't left locked by the failed call.
+            let mut handle = ptr::null_mut();
+            assert_eq!(
+                keri_controller_open(
+                    path("db").as_ptr(),
+                    path("keys.json").as_ptr(),
+                    passphrase.as_ptr(),
+                    &mut handle,
+                ),
+                KeriStatus::Ok
+            );
+
+            let mut id = empty();
+            assert_eq!(keri_incept(handle, &mut id), KeriStatus::Ok);
+            let id = CString::new(take(id)).unwrap();
+
+            let data = br#"{"hello":"world"}"#;
+            let mut signed = empty();
+            assert_eq!(
+                keri_sign(
+                    handle,
+                    id.as_ptr(),
+                    data.as_ptr(),
+                    data.len(),
+                    &mut signed
+                ),
+                KeriStatus::Ok
+            );
+            let signed = take(signed);
+            assert_eq!(keri_rotate(handle, id.as_ptr()), KeriStatus::Ok);
+            let (mut signer, mut verified) = (empty(), empty());
+            assert_eq!(
+                keri_verify(
+                    handle,
+                    signed.as_ptr(),
+                    signed.len(),
+                    &mut signer,
+                    &mut verified,
+                ),
+                KeriStatus::Ok
+            );
+            assert_eq!(take(signer), id.as_bytes());
+            assert_eq!(take(verified), data);
+
+            // KEL can be handed to other controllers.
+            let mut kel = empty();
+            assert_eq!(
+                keri_get_kel(handle, id.as_ptr(), &mut kel),
+                KeriStatus::Ok
+            );
+            let kel = take(kel);
+            assert_eq!(
+                keri_process_kel(handle, kel.as_ptr(), kel.len()),
+                KeriStatus::Ok
+            );
+
+            let invalid = CString::new("invalid").unwrap();
+            assert_eq!(
+                keri_rotate(handle, invalid.as_ptr()),
+                KeriStatus::InvalidArgument
+            );
+            let mut error = empty();
+            assert_eq!(keri_last_error(&mut error), KeriStatus::Ok);
+            assert_eq!(take(error), b"Invalid identifier invalid");
+            assert_eq!(
+                keri_incept(handle, ptr::null_mut()),
+                KeriStatus::NullArgument
+            );
+
+            keri_controller_free(handle);
+        }
+    }
+}
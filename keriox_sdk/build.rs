@@ -0,0 +1,12 @@
+fn main() {
+    // Agent service is generated only when needed. `protoc` is vendored, so
+    // building with `grpc` doesn't require it to be installed.
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path()
+            .expect("Vendored protoc is not available for this platform");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::compile_protos("proto/agent.proto")
+            .expect("Failed to compile agent.proto");
+    }
+}
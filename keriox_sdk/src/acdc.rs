@@ -0,0 +1,379 @@
+use keri_core::{
+    actor::prelude::{HashFunctionCode, SerializationFormats},
+    database::{EscrowCreator, EventDatabase},
+    prefix::IdentifierPrefix,
+};
+use said::{
+    derivation::HashFunction, version::SerializationInfo,
+    SelfAddressingIdentifier,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use teliox::database::TelEventDatabase;
+
+use crate::{credential::CredentialStatus, Controller, Identifier};
+
+/// Block of ACDC, i.e. attributes, edges or rules, identified by its own
+/// SAID. Fields are kept in order of insertion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AcdcSection {
+    #[serde(rename = "d")]
+    pub digest: Option<SelfAddressingIdentifier>,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl AcdcSection {
+    pub fn new(fields: Map<String, Value>) -> Result<Self, String> {
+        let mut section = Self {
+            digest: None,
+            fields,
+        };
+        section.digest = Some(derive_said(&section)?);
+        Ok(section)
+    }
+}
+
+/// Authentic Chained Data Container, i.e. credential issued by `issuer`
+/// whose attributes conform to schema identified by `schema` SAID.
+/// Credential issued in registry can be revoked, see
+/// `Controller::revoke_credential`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Acdc {
+    #[serde(rename = "v")]
+    pub version: SerializationInfo,
+    #[serde(rename = "d")]
+    pub digest: Option<SelfAddressingIdentifier>,
+    #[serde(rename = "i")]
+    pub issuer: IdentifierPrefix,
+    #[serde(rename = "ri", skip_serializing_if = "Option::is_none")]
+    pub registry: Option<IdentifierPrefix>,
+    #[serde(rename = "s")]
+    pub schema: SelfAddressingIdentifier,
+    #[serde(rename = "a")]
+    pub attributes: AcdcSection,
+    #[serde(rename = "e", skip_serializing_if = "Option::is_none")]
+    pub edges: Option<AcdcSection>,
+    #[serde(rename = "r", skip_serializing_if = "Option::is_none")]
+    pub rules: Option<AcdcSection>,
+}
+
+impl Acdc {
+    /// Builds ACDC and computes SAIDs of it and its attributes block.
+    pub fn new(
+        issuer: IdentifierPrefix,
+        registry: Option<IdentifierPrefix>,
+        schema: SelfAddressingIdentifier,
+        attributes: Map<String, Value>,
+    ) -> Result<Self, String> {
+        let mut acdc = Self {
+            version: SerializationInfo::new_empty(
+                "ACDC".to_string(),
+                1,
+                0,
+                SerializationFormats::JSON,
+            ),
+            digest: None,
+            issuer,
+            registry,
+            schema,
+            attributes: AcdcSection::new(attributes)?,
+            edges: None,
+            rules: None,
+        };
+        acdc.saidify()?;
+        Ok(acdc)
+    }
+
+    /// Sets edges block, i.e. references to other ACDCs.
+    pub fn with_edges(
+        mut self,
+        edges: Map<String, Value>,
+    ) -> Result<Self, String> {
+        self.edges = Some(AcdcSection::new(edges)?);
+        self.saidify()?;
+        Ok(self)
+    }
+
+    /// Sets rules block, e.g. terms of use of credential.
+    pub fn with_rules(
+        mut self,
+        rules: Map<String, Value>,
+    ) -> Result<Self, String> {
+        self.rules = Some(AcdcSection::new(rules)?);
+        self.saidify()?;
+        Ok(self)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(data).map_err(|e| e.to_string())
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(self).map_err(|e| e.to_string())
+    }
+
+    /// Returns SAID of ACDC, which identifies it in registry.
+    pub fn said(&self) -> Result<SelfAddressingIdentifier, String> {
+        self.digest.clone().ok_or("Missing ACDC SAID".to_string())
+    }
+
+    /// Checks SAIDs of ACDC, its blocks and size in its version string.
+    pub fn verify_said(&self) -> Result<(), String> {
+        let mut expected = self.clone();
+        for section in expected.sections_mut() {
+            section.digest = Some(derive_said(&*section)?);
+        }
+        expected.saidify()?;
+        if &expected != self {
+            return Err("ACDC SAID doesn't match its content".to_string());
+        }
+        Ok(())
+    }
+
+    fn sections_mut(&mut self) -> impl Iterator<Item = &mut AcdcSection> {
+        std::iter::once(&mut self.attributes)
+            .chain(self.edges.as_mut())
+            .chain(self.rules.as_mut())
+    }
+
+    fn saidify(&mut self) -> Result<(), String> {
+        self.version.size = dummy_data(self)?.len();
+        self.digest = Some(derive_said(self)?);
+        Ok(())
+    }
+}
+
+/// Serializes block with placeholder in place of its SAID.
+fn dummy_data<T: Serialize>(block: &T) -> Result<Vec<u8>, String> {
+    let mut value = serde_json::to_value(block).map_err(|e| e.to_string())?;
+    let digest = value
+        .get_mut("d")
+        .ok_or("Block lacks digest field".to_string())?;
+    *digest = Value::String(
+        "#".repeat(HashFunction::from(HashFunctionCode::Blake3_256).get_len()),
+    );
+    serde_json::to_vec(&value).map_err(|e| e.to_string())
+}
+
+fn derive_said<T: Serialize>(
+    block: &T,
+) -> Result<SelfAddressingIdentifier, String> {
+    Ok(HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&dummy_data(block)?))
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Generates issuance event of `acdc` in its registry, along with
+    /// interaction event anchoring it. Processed with
+    /// `finalize_issue_credential`.
+    pub fn issue_acdc(
+        &self,
+        identifier: &Identifier<D>,
+        acdc: &Acdc,
+    ) -> Result<(String, String), String> {
+        if acdc.issuer != identifier.id {
+            return Err("ACDC isn't issued by identifier".to_string());
+        }
+        acdc.verify_said()?;
+        self.validate_schema(acdc)?;
+        let registry = acdc
+            .registry
+            .as_ref()
+            .ok_or("ACDC has no registry".to_string())?;
+        self.issue(identifier, registry, acdc.said()?)
+    }
+
+    /// Verifies ACDC signed by its issuer with `Identifier::sign_data`.
+    /// Checks SAIDs, issuer's signature, schema if controller has schema
+    /// registry and, if ACDC is issued in registry, that it is issued and
+    /// not revoked according to locally known TEL. Credentials referenced
+    /// by its edges are verified the same way, see
+    /// `Controller::with_credential_resolver`. Returns verified ACDC.
+    pub fn verify_credential(&self, stream: &[u8]) -> Result<Acdc, String> {
+        self.verify_chained_credential(stream, &mut vec![])
+    }
+
+    /// Verifies credential referenced by edges of credentials in `chain`.
+    pub(crate) fn verify_chained_credential(
+        &self,
+        stream: &[u8],
+        chain: &mut Vec<SelfAddressingIdentifier>,
+    ) -> Result<Acdc, String> {
+        let (signer, data) = self.verify_signed_data(stream)?;
+        let acdc = Acdc::parse(&data)?;
+        acdc.verify_said()?;
+        if signer != acdc.issuer {
+            return Err("ACDC isn't signed by its issuer".to_string());
+        }
+        self.validate_schema(&acdc)?;
+        if let Some(registry) = &acdc.registry {
+            match self.credential_status(registry, &acdc.said()?)? {
+                CredentialStatus::Issued => {}
+                CredentialStatus::Revoked => {
+                    return Err("Credential is revoked".to_string())
+                }
+                CredentialStatus::Unknown => {
+                    return Err("Credential status is unknown".to_string())
+                }
+            }
+        }
+        self.verify_edges(&acdc, chain)?;
+        Ok(acdc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use keri_core::{prefix::BasicPrefix, signer::Signer};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        test_utils::{incept, person_schema, setup, sign},
+        SchemaRegistry,
+    };
+
+    #[test]
+    fn test_acdc() {
+        let (_root, controller) = setup("test-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+
+        let schema =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"schema");
+        let attributes = json!({"name": "John Doe", "age": 42});
+        let acdc = Acdc::new(
+            identifier.id.clone(),
+            Some(registry.clone()),
+            schema,
+            attributes.as_object().unwrap().clone(),
+        )
+        .unwrap()
+        .with_rules(json!({"usage": "Test only"}).as_object().unwrap().clone())
+        .unwrap();
+        let encoded = acdc.encode().unwrap();
+        assert!(encoded.starts_with(
+            format!(r#"{{"v":"ACDC10JSON{:06x}_""#, encoded.len()).as_bytes()
+        ));
+        // Attributes keep their order.
+        let a = serde_json::to_string(&acdc.attributes).unwrap();
+        assert!(a.find("name").unwrap() < a.find("age").unwrap());
+        let parsed = Acdc::parse(&encoded).unwrap();
+        assert_eq!(parsed, acdc);
+        parsed.verify_said().unwrap();
+
+        let mut tampered = acdc.clone();
+        tampered
+            .attributes
+            .fields
+            .insert("age".to_string(), json!(18));
+        assert!(tampered.verify_said().is_err());
+
+        let signed = identifier
+            .sign_data(&encoded, sign(&signer, &encoded))
+            .unwrap();
+        // Credential isn't valid until it's issued in registry.
+        assert!(controller.verify_credential(&signed).is_err());
+
+        let (ixn, iss) = controller.issue_acdc(&identifier, &acdc).unwrap();
+        let said = controller
+            .finalize_issue_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(said, acdc.said().unwrap());
+        assert_eq!(controller.verify_credential(&signed).unwrap(), acdc);
+
+        // ACDC signed by someone else than issuer is rejected.
+        let other_signer = Signer::new();
+        let other = incept(&controller, &other_signer);
+        let forged = other
+            .sign_data(&encoded, sign(&other_signer, &encoded))
+            .unwrap();
+        assert!(controller.verify_credential(&forged).is_err());
+        assert!(controller.issue_acdc(&other, &acdc).is_err());
+
+        let (ixn, rev) = controller
+            .revoke_credential(&identifier, &registry, &said)
+            .unwrap();
+        controller
+            .finalize_revoke_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                rev.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert!(controller.verify_credential(&signed).is_err());
+    }
+
+    #[test]
+    fn test_acdc_schema_validation() {
+        let schemas = SchemaRegistry::new();
+        let schema = schemas
+            .add(&serde_json::to_vec(&person_schema()).unwrap())
+            .unwrap();
+        let (_root, controller) = setup("test-db");
+        let controller = controller.with_schema_registry(Arc::new(schemas));
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+
+        let acdc = |attributes: Value| {
+            Acdc::new(
+                identifier.id.clone(),
+                Some(registry.clone()),
+                schema.clone(),
+                attributes.as_object().unwrap().clone(),
+            )
+            .unwrap()
+        };
+        let malformed = acdc(json!({"name": "John Doe"}));
+        assert!(controller.issue_acdc(&identifier, &malformed).is_err());
+        let encoded = malformed.encode().unwrap();
+        let signed = identifier
+            .sign_data(&encoded, sign(&signer, &encoded))
+            .unwrap();
+        assert!(controller.verify_credential(&signed).is_err());
+
+        let valid = acdc(json!({"name": "John Doe", "age": 42}));
+        assert!(controller.issue_acdc(&identifier, &valid).is_ok());
+    }
+}
@@ -0,0 +1,541 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use futures::future::join_all;
+use keri_core::{
+    actor::event_generator,
+    database::{EscrowCreator, EventDatabase},
+    event_message::signed_event_message::{
+        Message, Notice, SignedEventMessage,
+    },
+    prefix::{
+        BasicPrefix, CesrPrimitive, IdentifierPrefix, IndexedSignature,
+        SelfSigningPrefix,
+    },
+    state::IdentifierState,
+};
+use said::SelfAddressingIdentifier;
+use teliox::database::TelEventDatabase;
+use tokio::runtime::Runtime;
+
+use crate::{
+    query::{QuerySigner, QueryTransport},
+    receipts::ReceiptFetcher,
+    witness::WitnessPublisher,
+    Controller, Identifier,
+};
+
+/// Future returned by async transports.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Async counterpart of `QueryTransport`.
+pub trait AsyncQueryTransport: Send + Sync {
+    fn query<'a>(
+        &'a self,
+        recipient: &'a IdentifierPrefix,
+        query: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, String>>;
+}
+
+impl<F, Fut> AsyncQueryTransport for F
+where
+    F: Fn(IdentifierPrefix, Vec<u8>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Vec<u8>, String>> + Send + 'static,
+{
+    fn query<'a>(
+        &'a self,
+        recipient: &'a IdentifierPrefix,
+        query: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+        Box::pin(self(recipient.clone(), query.to_vec()))
+    }
+}
+
+/// Async counterpart of `WitnessPublisher`.
+pub trait AsyncWitnessPublisher: Send + Sync {
+    fn publish<'a>(
+        &'a self,
+        witness: &'a BasicPrefix,
+        stream: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), String>>;
+}
+
+impl<F, Fut> AsyncWitnessPublisher for F
+where
+    F: Fn(BasicPrefix, Vec<u8>) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    fn publish<'a>(
+        &'a self,
+        witness: &'a BasicPrefix,
+        stream: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(self(witness.clone(), stream.to_vec()))
+    }
+}
+
+/// Async counterpart of `ReceiptFetcher`.
+pub trait AsyncReceiptFetcher: Send + Sync {
+    fn fetch_receipts<'a>(
+        &'a self,
+        witness: &'a BasicPrefix,
+        id: &'a IdentifierPrefix,
+        sn: u64,
+    ) -> BoxFuture<'a, Result<Vec<u8>, String>>;
+}
+
+impl<F, Fut> AsyncReceiptFetcher for F
+where
+    F: Fn(BasicPrefix, IdentifierPrefix, u64) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Vec<u8>, String>> + Send + 'static,
+{
+    fn fetch_receipts<'a>(
+        &'a self,
+        witness: &'a BasicPrefix,
+        id: &'a IdentifierPrefix,
+        sn: u64,
+    ) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+        Box::pin(self(witness.clone(), id.clone(), sn))
+    }
+}
+
+/// Sync facade of async transport, so that it can be used with blocking
+/// parts of the SDK, e.g. `ReceiptCollector`. Futures are driven by
+/// internal runtime, so it can't be used from within async context.
+pub struct Blocking<T> {
+    inner: T,
+    runtime: Runtime,
+}
+
+impl<T> Blocking<T> {
+    pub fn new(inner: T) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { inner, runtime })
+    }
+}
+
+impl<T: AsyncQueryTransport> QueryTransport for Blocking<T> {
+    fn query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.runtime.block_on(self.inner.query(recipient, query))
+    }
+}
+
+impl<T: AsyncWitnessPublisher> WitnessPublisher for Blocking<T> {
+    fn publish(
+        &self,
+        witness: &BasicPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self.runtime.block_on(self.inner.publish(witness, stream))
+    }
+}
+
+impl<T: AsyncReceiptFetcher> ReceiptFetcher for Blocking<T> {
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        sn: u64,
+    ) -> Result<Vec<u8>, String> {
+        self.runtime
+            .block_on(self.inner.fetch_receipts(witness, id, sn))
+    }
+}
+
+/// Runs local processing or storage access on tokio's blocking thread
+/// pool, so it doesn't stall async tasks.
+async fn run_blocking<R, F>(f: F) -> Result<R, String>
+where
+    F: FnOnce() -> Result<R, String> + Send + 'static,
+    R: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Async entry point of the SDK. Network operations return futures, while
+/// event processing and storage run on tokio's blocking thread pool, so
+/// methods have to be called within tokio runtime. Wrapped `Controller`
+/// stays available as sync facade for non-async callers.
+pub struct AsyncController<
+    D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+    T: TelEventDatabase + Send + Sync + 'static,
+> {
+    inner: Arc<Controller<D, T>>,
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase + Send + Sync + 'static,
+    > AsyncController<D, T>
+{
+    pub fn new(controller: Controller<D, T>) -> Self {
+        Self {
+            inner: Arc::new(controller),
+        }
+    }
+
+    /// Sync facade of the controller.
+    pub fn blocking(&self) -> &Controller<D, T> {
+        &self.inner
+    }
+
+    pub fn incept(
+        &self,
+        public_keys: Vec<BasicPrefix>,
+        next_pub_keys: Vec<BasicPrefix>,
+    ) -> Result<String, String> {
+        self.inner
+            .incept(public_keys, next_pub_keys)
+            .map_err(|_| "Event generation error".to_string())
+    }
+
+    pub async fn finalize_incept(
+        &self,
+        event: Vec<u8>,
+        sig: SelfSigningPrefix,
+    ) -> Result<AsyncIdentifier<D>, String> {
+        let controller = self.inner.clone();
+        run_blocking(move || {
+            controller
+                .finalize_incept(&event, &sig)
+                .map_err(|_| "Inception error".to_string())
+        })
+        .await
+        .map(AsyncIdentifier::new)
+    }
+
+    pub async fn finalize_rotate(
+        &self,
+        event: Vec<u8>,
+        signatures: Vec<IndexedSignature>,
+    ) -> Result<(), String> {
+        let controller = self.inner.clone();
+        run_blocking(move || controller.finalize_rotate(&event, signatures))
+            .await
+    }
+
+    pub async fn load_identifier(
+        &self,
+        id: IdentifierPrefix,
+    ) -> Result<AsyncIdentifier<D>, String> {
+        let controller = self.inner.clone();
+        run_blocking(move || controller.load_identifier(&id))
+            .await
+            .map(AsyncIdentifier::new)
+    }
+
+    pub async fn process_kel(
+        &self,
+        messages: Vec<Message>,
+    ) -> Result<(), String> {
+        let controller = self.inner.clone();
+        run_blocking(move || controller.process_kel(&messages)).await
+    }
+
+    pub async fn process_tel(&self, tel: Vec<u8>) -> Result<(), String> {
+        let controller = self.inner.clone();
+        run_blocking(move || controller.process_tel(&tel)).await
+    }
+
+    pub async fn get_state(
+        &self,
+        id: IdentifierPrefix,
+    ) -> Result<Option<IdentifierState>, String> {
+        let controller = self.inner.clone();
+        run_blocking(move || Ok(controller.get_state(&id))).await
+    }
+
+    pub async fn reprocess_escrows(
+        &self,
+        id: IdentifierPrefix,
+    ) -> Result<Vec<(SelfAddressingIdentifier, String)>, String> {
+        let controller = self.inner.clone();
+        run_blocking(move || controller.kel.reprocess_escrows(&id)).await
+    }
+}
+
+/// Async counterpart of `Identifier`, obtained from `AsyncController`.
+pub struct AsyncIdentifier<D: EventDatabase + Send + Sync + 'static> {
+    inner: Arc<Identifier<D>>,
+}
+
+impl<D: EventDatabase + Send + Sync + 'static> AsyncIdentifier<D> {
+    pub fn new(identifier: Identifier<D>) -> Self {
+        Self {
+            inner: Arc::new(identifier),
+        }
+    }
+
+    pub fn id(&self) -> &IdentifierPrefix {
+        &self.inner.id
+    }
+
+    /// Sync facade of the identifier.
+    pub fn blocking(&self) -> &Identifier<D> {
+        &self.inner
+    }
+
+    pub async fn get_own_kel(&self) -> Result<Option<Vec<Notice>>, String> {
+        let identifier = self.inner.clone();
+        run_blocking(move || Ok(identifier.get_own_kel())).await
+    }
+
+    pub async fn finalize_anchor(
+        &self,
+        event: Vec<u8>,
+        sig: SelfSigningPrefix,
+    ) -> Result<SignedEventMessage, String> {
+        let identifier = self.inner.clone();
+        run_blocking(move || identifier.finalize_anchor(&event, sig)).await
+    }
+
+    /// Signs `query` and sends it with `transport`. Returns response of
+    /// queried witness or watcher.
+    pub async fn finalize_query(
+        &self,
+        query: &[u8],
+        sig: SelfSigningPrefix,
+        transport: &dyn AsyncQueryTransport,
+    ) -> Result<Vec<u8>, String> {
+        let (recipient, stream) = self.inner.signed_query(query, sig)?;
+        transport.query(&recipient, &stream).await
+    }
+
+    /// Queries KEL of `of` from `witness` in pages of `page_size` events,
+    /// starting at `from_sn`, and processes received events. Returns
+    /// number of received events.
+    pub async fn query_kel(
+        &self,
+        of: &IdentifierPrefix,
+        from_sn: u64,
+        witness: &IdentifierPrefix,
+        page_size: u64,
+        signer: &(dyn QuerySigner + Sync),
+        transport: &dyn AsyncQueryTransport,
+    ) -> Result<u64, String> {
+        let mut next_sn = from_sn;
+        let mut received = 0;
+        loop {
+            let query = event_generator::logs_query(
+                of,
+                witness,
+                Some(next_sn),
+                Some(page_size),
+            )
+            .encode()
+            .map_err(|_| "Event encoding error".to_string())?;
+            let sig = signer.sign(&query)?;
+            let response = self.finalize_query(&query, sig, transport).await?;
+            let (identifier, of_id) = (self.inner.clone(), of.clone());
+            let (page, sn) = run_blocking(move || {
+                let mut sn = next_sn;
+                let page =
+                    identifier.process_kel_page(&of_id, &response, &mut sn)?;
+                Ok((page, sn))
+            })
+            .await?;
+            next_sn = sn;
+            received += page;
+            if page < page_size {
+                return Ok(received);
+            }
+        }
+    }
+
+    /// Publishes identifier's `event` to all its witnesses at once. Returns
+    /// witnesses that received it, failures are logged.
+    pub async fn publish(
+        &self,
+        event: &SignedEventMessage,
+        publisher: &dyn AsyncWitnessPublisher,
+    ) -> Result<Vec<BasicPrefix>, String> {
+        let identifier = self.inner.clone();
+        let witnesses = run_blocking(move || {
+            identifier
+                .event_storage
+                .get_state(&identifier.id)
+                .ok_or("Unknown identifier".to_string())
+        })
+        .await?
+        .witness_config
+        .witnesses;
+        let stream = Message::Notice(Notice::Event(event.clone()))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+        let results = join_all(
+            witnesses
+                .iter()
+                .map(|witness| publisher.publish(witness, &stream)),
+        )
+        .await;
+        Ok(witnesses
+            .into_iter()
+            .zip(results)
+            .filter_map(|(witness, result)| match result {
+                Ok(()) => Some(witness),
+                Err(e) => {
+                    log::warn!(
+                        "Failed to publish to {}: {}",
+                        witness.to_str(),
+                        e
+                    );
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use keri_core::signer::Signer;
+
+    use super::*;
+    use crate::test_utils::{setup, sign};
+
+    #[tokio::test]
+    async fn test_async_controller() {
+        let (_root, controller) = setup("test-db");
+        let controller = AsyncController::new(controller);
+        let signer = Signer::new();
+        let icp = controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            )
+            .unwrap();
+
+        // Inception signed with other key is rejected.
+        assert!(controller
+            .finalize_incept(
+                icp.as_bytes().to_vec(),
+                sign(&Signer::new(), icp.as_bytes())
+            )
+            .await
+            .is_err());
+        let identifier = controller
+            .finalize_incept(
+                icp.as_bytes().to_vec(),
+                sign(&signer, icp.as_bytes()),
+            )
+            .await
+            .unwrap();
+        let state = controller
+            .get_state(identifier.id().clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.sn, 0);
+        // Sync facade sees the same state.
+        assert_eq!(
+            controller.blocking().get_state(identifier.id()).unwrap().sn,
+            0
+        );
+
+        let loaded = controller
+            .load_identifier(identifier.id().clone())
+            .await
+            .unwrap();
+        assert_eq!(loaded.get_own_kel().await.unwrap().unwrap().len(), 1);
+        let unknown = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        assert!(controller.load_identifier(unknown).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_async_transports() {
+        let (_root, controller) = setup("test-db");
+        let controller = AsyncController::new(controller);
+        let signer = Signer::new();
+        let icp = controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            )
+            .unwrap();
+        let identifier = controller
+            .finalize_incept(
+                icp.as_bytes().to_vec(),
+                sign(&signer, icp.as_bytes()),
+            )
+            .await
+            .unwrap();
+        let witness = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+
+        let queries = Arc::new(Mutex::new(vec![]));
+        let recorded = queries.clone();
+        let transport = move |recipient: IdentifierPrefix, query: Vec<u8>| {
+            recorded.lock().unwrap().push((recipient, query));
+            async { Ok::<_, String>(Vec::<u8>::new()) }
+        };
+        let query_signer = |query: &[u8]| -> Result<SelfSigningPrefix, String> {
+            Ok(sign(&signer, query))
+        };
+        let received = identifier
+            .query_kel(
+                identifier.id(),
+                0,
+                &witness,
+                10,
+                &query_signer,
+                &transport,
+            )
+            .await
+            .unwrap();
+        assert_eq!(received, 0);
+        assert_eq!(queries.lock().unwrap()[0].0, witness);
+
+        // Transport failures are returned.
+        let unreachable = |_: IdentifierPrefix, _: Vec<u8>| async {
+            Err::<Vec<u8>, _>("unreachable".to_string())
+        };
+        assert!(identifier
+            .query_kel(
+                identifier.id(),
+                0,
+                &witness,
+                10,
+                &query_signer,
+                &unreachable,
+            )
+            .await
+            .is_err());
+
+        let icp =
+            match identifier.get_own_kel().await.unwrap().unwrap().remove(0) {
+                Notice::Event(icp) => icp,
+                _ => unreachable!(),
+            };
+        let publisher = |_: BasicPrefix, _: Vec<u8>| async {
+            Err::<(), _>("offline".to_string())
+        };
+        assert!(identifier
+            .publish(&icp, &publisher)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Sync facade drives futures on its own runtime.
+        let blocking = std::thread::spawn(move || {
+            Blocking::new(transport)
+                .unwrap()
+                .query(&witness, b"query")
+                .unwrap()
+        });
+        assert!(blocking.join().unwrap().is_empty());
+        assert_eq!(queries.lock().unwrap().len(), 2);
+    }
+}
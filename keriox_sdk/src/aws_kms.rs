@@ -0,0 +1,98 @@
+use aws_config::BehaviorVersion;
+use aws_sdk_kms::{
+    primitives::Blob,
+    types::{KeySpec, KeyUsageType, MessageType, SigningAlgorithmSpec},
+    Client,
+};
+use keri_core::error::Error;
+use tokio::runtime::Runtime;
+
+use crate::remote_key::{
+    der_signature, remote_key_error, spki_key, RemoteKeyManager, RemoteKeyRef,
+    RemoteKeyService,
+};
+
+/// Key manager keeping ECDSA secp256k1 keys in AWS KMS.
+pub type KmsSigner = RemoteKeyManager<AwsKms>;
+
+/// AWS KMS holding asymmetric `ECC_SECG_P256K1` keys. Blocking calls are
+/// made on internal runtime, so it can't be used from within async
+/// context.
+pub struct AwsKms {
+    runtime: Runtime,
+    client: Client,
+}
+
+impl AwsKms {
+    /// Connects to KMS. AWS configuration, i.e. region and credentials, is
+    /// loaded from environment.
+    pub fn connect() -> Result<Self, Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(remote_key_error)?;
+        let config = runtime
+            .block_on(aws_config::load_defaults(BehaviorVersion::latest()));
+        let client = Client::new(&config);
+        Ok(Self { runtime, client })
+    }
+}
+
+impl RemoteKeyService for AwsKms {
+    fn create_key(&self) -> Result<RemoteKeyRef, Error> {
+        let output = self
+            .runtime
+            .block_on(
+                self.client
+                    .create_key()
+                    .key_spec(KeySpec::EccSecgP256K1)
+                    .key_usage(KeyUsageType::SignVerify)
+                    .description("KERI signing key")
+                    .send(),
+            )
+            .map_err(remote_key_error)?;
+        let key_id = output
+            .key_metadata()
+            .map(|metadata| metadata.key_id().to_string())
+            .ok_or(remote_key_error("missing key metadata"))?;
+        self.get_key(&key_id)
+    }
+
+    fn get_key(&self, id: &str) -> Result<RemoteKeyRef, Error> {
+        let output = self
+            .runtime
+            .block_on(self.client.get_public_key().key_id(id).send())
+            .map_err(remote_key_error)?;
+        let spki = output
+            .public_key()
+            .ok_or(remote_key_error("missing public key"))?;
+        Ok(RemoteKeyRef {
+            id: id.to_string(),
+            public_key: spki_key(spki.as_ref())?,
+        })
+    }
+
+    fn sign_digest(
+        &self,
+        key: &RemoteKeyRef,
+        digest: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let output = self
+            .runtime
+            .block_on(
+                self.client
+                    .sign()
+                    .key_id(&key.id)
+                    .message(Blob::new(digest))
+                    .message_type(MessageType::Digest)
+                    .signing_algorithm(SigningAlgorithmSpec::EcdsaSha256)
+                    .send(),
+            )
+            .map_err(|e| {
+                log::warn!("KMS signing failed: {}", e);
+                Error::SigningError
+            })?;
+        let signature = output.signature().ok_or(Error::SigningError)?;
+        der_signature(signature.as_ref())
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use keri_core::error::Error;
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::remote_key::{
+    compressed_key, remote_key_error, request_json, AccessTokenProvider,
+    RemoteKeyRef, RemoteKeyService,
+};
+
+const API_VERSION: &str = "api-version=7.4";
+
+/// Azure Key Vault holding `P-256K` keys, used with its REST API. Every
+/// created key is new version of the same vault key, so key ids are
+/// versioned key identifiers. Access tokens have to be issued for
+/// `https://vault.azure.net` scope.
+pub struct AzureKeyVault {
+    client: reqwest::blocking::Client,
+    vault: Url,
+    key_name: String,
+    token: Arc<dyn AccessTokenProvider>,
+}
+
+impl AzureKeyVault {
+    pub fn new(
+        vault: Url,
+        key_name: &str,
+        token: Arc<dyn AccessTokenProvider>,
+    ) -> Self {
+        Self {
+            client: reqwest::blocking::Client::default(),
+            vault,
+            key_name: key_name.to_string(),
+            token,
+        }
+    }
+
+    fn request(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+        body: Option<Value>,
+    ) -> Result<Value, Error> {
+        let token = self.token.access_token().map_err(remote_key_error)?;
+        request_json(request, &token, body)
+    }
+}
+
+impl RemoteKeyService for AzureKeyVault {
+    fn create_key(&self) -> Result<RemoteKeyRef, Error> {
+        let url = self
+            .vault
+            .join(&format!("keys/{}/create?{}", self.key_name, API_VERSION))
+            .map_err(remote_key_error)?;
+        let body = json!({
+            "kty": "EC",
+            "crv": "P-256K",
+            "key_ops": ["sign", "verify"],
+        });
+        let bundle = self.request(self.client.post(url), Some(body))?;
+        key_ref(&bundle)
+    }
+
+    fn get_key(&self, id: &str) -> Result<RemoteKeyRef, Error> {
+        let url = format!("{}?{}", id, API_VERSION);
+        let bundle = self.request(self.client.get(url), None)?;
+        key_ref(&bundle)
+    }
+
+    fn sign_digest(
+        &self,
+        key: &RemoteKeyRef,
+        digest: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let url = format!("{}/sign?{}", key.id, API_VERSION);
+        let body = json!({
+            "alg": "ES256K",
+            "value": base64::encode_config(digest, base64::URL_SAFE_NO_PAD),
+        });
+        let result =
+            self.request(self.client.post(url), Some(body))
+                .map_err(|e| {
+                    log::warn!("Key Vault signing failed: {}", e);
+                    Error::SigningError
+                })?;
+        // Key Vault returns `r || s` signature, as JWS does.
+        base64_field(&result, "value").map_err(|_| Error::SigningError)
+    }
+}
+
+/// Returns reference to key of key bundle, whose public key is JWK.
+fn key_ref(bundle: &Value) -> Result<RemoteKeyRef, Error> {
+    let jwk = &bundle["key"];
+    let id = jwk["kid"]
+        .as_str()
+        .ok_or(remote_key_error("missing key identifier"))?;
+    let point =
+        [vec![0x04], base64_field(jwk, "x")?, base64_field(jwk, "y")?].concat();
+    Ok(RemoteKeyRef {
+        id: id.to_string(),
+        public_key: compressed_key(&point)?,
+    })
+}
+
+fn base64_field(value: &Value, field: &str) -> Result<Vec<u8>, Error> {
+    value[field]
+        .as_str()
+        .and_then(|encoded| {
+            base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).ok()
+        })
+        .ok_or(remote_key_error(format!("invalid {} field", field)))
+}
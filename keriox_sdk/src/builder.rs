@@ -0,0 +1,449 @@
+use std::{path::PathBuf, sync::Arc};
+
+use keri_core::{
+    database::redb::RedbDatabase,
+    oobi::{LocationScheme, Oobi},
+    oobi_manager::OobiManager,
+    prefix::IdentifierPrefix,
+    processor::escrow::EscrowConfig,
+};
+use teliox::database::{
+    redb::RedbTelDatabase, EscrowDatabase, TelEventDatabase,
+};
+
+use crate::{
+    controller::KeriRuntime, keystore::KeyStore, managed::IdentifierRegistry,
+    network::NetworkPolicy, oobi::OobiFetcher, pending::PendingOperations,
+    transport::Transport, Controller,
+};
+
+/// Configuration of controller whose databases are kept in single
+/// directory. Configuration is validated and OOBIs of witnesses and
+/// watchers are resolved before controller is built.
+#[derive(Default)]
+pub struct ControllerBuilder {
+    db_path: Option<PathBuf>,
+    tel_escrow: bool,
+    escrow_config: EscrowConfig,
+    keystore: Option<Arc<dyn KeyStore>>,
+    witnesses: Vec<LocationScheme>,
+    watchers: Vec<LocationScheme>,
+    oobi_fetcher: Option<Arc<dyn OobiFetcher>>,
+    transport: Option<(Arc<dyn Transport>, NetworkPolicy)>,
+}
+
+impl ControllerBuilder {
+    /// Keeps databases in `path` directory, which is created if needed.
+    pub fn with_db_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.db_path = Some(path.into());
+        self
+    }
+
+    /// Keeps TEL events with unknown anchoring KEL events in escrow, see
+    /// `Controller::with_tel_escrow`.
+    pub fn with_tel_escrow(mut self) -> Self {
+        self.tel_escrow = true;
+        self
+    }
+
+    /// Sets how long events are kept in escrows.
+    pub fn with_escrow_config(mut self, escrow_config: EscrowConfig) -> Self {
+        self.escrow_config = escrow_config;
+        self
+    }
+
+    pub fn with_keystore(mut self, keystore: Arc<dyn KeyStore>) -> Self {
+        self.keystore = Some(keystore);
+        self
+    }
+
+    /// Adds witness, whose location is resolved on build.
+    pub fn with_witness(mut self, oobi: LocationScheme) -> Self {
+        self.witnesses.push(oobi);
+        self
+    }
+
+    /// Adds watcher, whose location is resolved on build.
+    pub fn with_watcher(mut self, oobi: LocationScheme) -> Self {
+        self.watchers.push(oobi);
+        self
+    }
+
+    /// Sets transport used to resolve OOBIs of witnesses and watchers.
+    pub fn with_oobi_fetcher(mut self, fetcher: Arc<dyn OobiFetcher>) -> Self {
+        self.oobi_fetcher = Some(fetcher);
+        self
+    }
+
+    /// Sets transport of controller, see `Controller::with_transport`. It
+    /// also resolves OOBIs of witnesses and watchers, unless OOBI fetcher
+    /// is set.
+    pub fn with_transport(
+        mut self,
+        transport: Arc<dyn Transport>,
+        policy: NetworkPolicy,
+    ) -> Self {
+        self.transport = Some((transport, policy));
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.db_path.is_none() {
+            return Err("Database path not set".to_string());
+        }
+        let escrow = &self.escrow_config;
+        if [
+            escrow.out_of_order_timeout,
+            escrow.partially_signed_timeout,
+            escrow.partially_witnessed_timeout,
+            escrow.trans_receipt_timeout,
+            escrow.delegation_timeout,
+        ]
+        .iter()
+        .any(|timeout| timeout.is_zero())
+        {
+            return Err("Escrow timeouts have to be positive".to_string());
+        }
+        for witness in &self.witnesses {
+            match &witness.eid {
+                IdentifierPrefix::Basic(bp) if !bp.is_transferable() => (),
+                _ => {
+                    return Err(format!(
+                        "Witness {} is not non-transferable basic identifier",
+                        witness.eid
+                    ))
+                }
+            }
+        }
+        if (!self.witnesses.is_empty() || !self.watchers.is_empty())
+            && self.oobi_fetcher.is_none()
+            && self.transport.is_none()
+        {
+            return Err("OOBI fetcher not set".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn build(
+        self,
+    ) -> Result<Controller<RedbDatabase, RedbTelDatabase>, String> {
+        self.validate()?;
+        let path = self.db_path.ok_or("Database path not set".to_string())?;
+        std::fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+        let event_db = Arc::new(
+            RedbDatabase::new(&path.join("events"))
+                .map_err(|e| e.to_string())?,
+        );
+        let tel_db = Arc::new(
+            RedbTelDatabase::new(path.join("tel"))
+                .map_err(|e| e.to_string())?,
+        );
+        let kel = KeriRuntime::with_config(
+            event_db.clone(),
+            self.escrow_config,
+            None,
+        );
+        let mut controller = if self.tel_escrow {
+            let tel_escrow_db = EscrowDatabase::new(&path.join("tel_escrow"))
+                .map_err(|e| e.to_string())?;
+            Controller::with_runtime_and_tel_escrow(kel, tel_db, tel_escrow_db)?
+        } else {
+            Controller::with_runtime(kel, tel_db)
+        };
+        controller = controller.with_identifier_registry(Arc::new(
+            IdentifierRegistry::new(&path.join("identifiers"))?,
+        ));
+        controller = controller.with_pending_operations(Arc::new(
+            PendingOperations::new(&path.join("pending"))?,
+        ));
+        if let Some(keystore) = self.keystore {
+            controller = controller.with_keystore(keystore);
+        }
+        if let Some((transport, policy)) = self.transport {
+            controller = controller.with_transport(transport, policy);
+        }
+
+        let fetcher = self.oobi_fetcher.or_else(|| {
+            controller
+                .transport()
+                .map(|transport| Arc::new(transport) as Arc<dyn OobiFetcher>)
+        });
+        if let Some(fetcher) = &fetcher {
+            let resolver = controller.kel.oobi_resolver(
+                Arc::new(OobiManager::new(event_db.clone())),
+                fetcher.clone(),
+            );
+            for oobi in self.witnesses.iter().chain(&self.watchers) {
+                resolver.resolve(&Oobi::Location(oobi.clone())).map_err(
+                    |e| {
+                        format!("Failed to resolve OOBI of {}: {}", oobi.eid, e)
+                    },
+                )?;
+            }
+        }
+        controller.witnesses = self
+            .witnesses
+            .into_iter()
+            .filter_map(|oobi| match oobi.eid {
+                IdentifierPrefix::Basic(bp) => Some(bp),
+                _ => None,
+            })
+            .collect();
+        controller.watchers =
+            self.watchers.into_iter().map(|oobi| oobi.eid).collect();
+
+        // Resume operations interrupted by restart. Receipts are collected
+        // over transport, if it is set.
+        if let Some(pending) = controller.pending_operations() {
+            let resolver = fetcher.map(|fetcher| {
+                controller.kel.kel_resolver(
+                    Arc::new(OobiManager::new(event_db)),
+                    fetcher,
+                    controller.watchers.clone(),
+                )
+            });
+            let collector = controller.transport().map(|transport| {
+                controller.kel.receipt_collector(
+                    Arc::new(transport.clone()),
+                    Arc::new(transport),
+                )
+            });
+            controller.kel.resume_pending(
+                &pending,
+                collector.as_ref(),
+                resolver.as_ref(),
+            )?;
+        }
+        Ok(controller)
+    }
+}
+
+impl Controller<RedbDatabase, RedbTelDatabase> {
+    pub fn builder() -> ControllerBuilder {
+        ControllerBuilder::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        actor::{event_generator, prelude::SerializationFormats},
+        event::receipt::Receipt,
+        event_message::{
+            cesr_adapter::{parse_event_type, EventType},
+            signature::Nontransferable,
+            signed_event_message::{
+                Message, Notice, Op, SignedNontransferableReceipt,
+            },
+        },
+        oobi::Scheme,
+        prefix::{BasicPrefix, IndexedSignature},
+        processor::Processor,
+        query::reply_event::SignedReply,
+        signer::Signer,
+    };
+    use tempfile::Builder;
+    use url::Url;
+
+    use super::*;
+    use crate::{
+        pending::PendingKind, test_utils::sign, EncryptedFileKeyStore,
+    };
+
+    /// Accepts events and serves the same receipts to any request.
+    struct ReceiptTransport(Vec<u8>);
+
+    impl Transport for ReceiptTransport {
+        fn send_event(
+            &self,
+            _recipient: &IdentifierPrefix,
+            _stream: &[u8],
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn send_query(
+            &self,
+            recipient: &IdentifierPrefix,
+            _query: &[u8],
+        ) -> Result<Vec<u8>, String> {
+            Err(format!("{} is unreachable", recipient))
+        }
+
+        fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+            Err(format!("{} is unreachable", url))
+        }
+
+        fn fetch_mailbox(
+            &self,
+            recipient: &IdentifierPrefix,
+            _query: &[u8],
+        ) -> Result<String, String> {
+            Err(format!("{} is unreachable", recipient))
+        }
+
+        fn fetch_receipts(
+            &self,
+            _witness: &BasicPrefix,
+            _id: &IdentifierPrefix,
+            _sn: u64,
+        ) -> Result<Vec<u8>, String> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_controller_builder() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let witness_signer = Signer::new();
+        let witness_key = BasicPrefix::Ed25519NT(witness_signer.public_key());
+        let witness = LocationScheme::new(
+            IdentifierPrefix::Basic(witness_key.clone()),
+            Scheme::Http,
+            Url::parse("http://127.0.0.1:3232/").unwrap(),
+        );
+        let rpy = event_generator::generate_loc_scheme(
+            &witness.eid,
+            witness.scheme.clone(),
+            witness.url.clone(),
+        );
+        let sig = sign(&witness_signer, &rpy.encode().unwrap());
+        let stream = Message::Op(Op::Reply(SignedReply::new_nontrans(
+            rpy,
+            witness_key.clone(),
+            sig,
+        )))
+        .to_cesr()
+        .unwrap();
+        let fetcher =
+            move |_: &Url| -> Result<Vec<u8>, String> { Ok(stream.clone()) };
+
+        assert!(Controller::builder().build().is_err());
+        let transferable = LocationScheme::new(
+            IdentifierPrefix::Basic(BasicPrefix::Ed25519(
+                Signer::new().public_key(),
+            )),
+            Scheme::Http,
+            Url::parse("http://127.0.0.1:3233/").unwrap(),
+        );
+        assert!(Controller::builder()
+            .with_db_path(root.path().join("transferable"))
+            .with_witness(transferable)
+            .with_oobi_fetcher(Arc::new(fetcher.clone()))
+            .build()
+            .is_err());
+        assert!(Controller::builder()
+            .with_db_path(root.path().join("no-fetcher"))
+            .with_witness(witness.clone())
+            .build()
+            .is_err());
+        assert!(Controller::builder()
+            .with_db_path(root.path().join("zero-timeout"))
+            .with_escrow_config(EscrowConfig {
+                delegation_timeout: std::time::Duration::ZERO,
+                ..EscrowConfig::default()
+            })
+            .build()
+            .is_err());
+
+        let path = root.path().join("controller");
+        let controller = Controller::builder()
+            .with_db_path(&path)
+            .with_tel_escrow()
+            .with_keystore(Arc::new(EncryptedFileKeyStore::new(
+                path.join("keys.json"),
+            )))
+            .with_witness(witness.clone())
+            .with_oobi_fetcher(Arc::new(fetcher.clone()))
+            .build()
+            .unwrap();
+        assert_eq!(controller.witnesses(), &[witness_key]);
+        assert!(controller.keystore().is_some());
+        let resolver = controller.kel.oobi_resolver(
+            Arc::new(OobiManager::new(
+                controller.kel.storage.events_db.clone(),
+            )),
+            Arc::new(fetcher),
+        );
+        assert_eq!(
+            resolver.get_loc_schemes(&witness.eid).unwrap(),
+            vec![witness]
+        );
+    }
+
+    #[test]
+    fn test_resume_receipts_on_build() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let path = root.path().join("controller");
+        let controller =
+            Controller::builder().with_db_path(&path).build().unwrap();
+
+        let witness_signer = Signer::new();
+        let witness = BasicPrefix::Ed25519NT(witness_signer.public_key());
+        let signer = Signer::new();
+        let icp = event_generator::incept(
+            vec![BasicPrefix::Ed25519(signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            vec![witness.clone()],
+            1,
+            None,
+        )
+        .unwrap();
+        let EventType::KeyEvent(icp) =
+            parse_event_type(icp.as_bytes()).unwrap()
+        else {
+            unreachable!()
+        };
+        let id = icp.data.get_prefix();
+        let sig = sign(&signer, &icp.encode().unwrap());
+        let signed_icp =
+            icp.sign(vec![IndexedSignature::new_both_same(sig, 0)], None, None);
+
+        // Controller is closed while inception waits for receipt.
+        let pending = controller.pending_operations().unwrap();
+        pending.track(&id).unwrap();
+        controller
+            .kel
+            .processor
+            .process_notice(&Notice::Event(signed_icp))
+            .unwrap();
+        let operations = pending.list().unwrap();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].1.kind, PendingKind::WitnessReceipts);
+        drop(pending);
+        drop(controller);
+
+        let receipt = Receipt::new(
+            SerializationFormats::JSON,
+            icp.digest().unwrap(),
+            id.clone(),
+            0,
+        );
+        let signature = sign(&witness_signer, &icp.encode().unwrap());
+        let receipts = Message::Notice(Notice::NontransferableRct(
+            SignedNontransferableReceipt::new(
+                &receipt,
+                vec![Nontransferable::Couplet(vec![(witness, signature)])],
+            ),
+        ))
+        .to_cesr()
+        .unwrap();
+
+        // Restarted controller collects receipt over its transport.
+        let controller = Controller::builder()
+            .with_db_path(&path)
+            .with_transport(
+                Arc::new(ReceiptTransport(receipts)),
+                NetworkPolicy::default(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(controller.get_state(&id).unwrap().sn, 0);
+        assert!(controller
+            .pending_operations()
+            .unwrap()
+            .list()
+            .unwrap()
+            .is_empty());
+    }
+}
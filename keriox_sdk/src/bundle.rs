@@ -0,0 +1,380 @@
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use keri_core::{
+    actor::{parse_event_stream, process_reply},
+    database::{EscrowCreator, EventDatabase},
+    event::{event_data::EventData, sections::seal::Seal},
+    event_message::signed_event_message::{Message, Notice, Op},
+    oobi::Role,
+    oobi_manager::OobiManager,
+    prefix::IdentifierPrefix,
+    query::reply_event::SignedReply,
+};
+use serde::{Deserialize, Serialize};
+use teliox::database::TelEventDatabase;
+use zeroize::Zeroizing;
+
+use crate::{
+    contacts::{Contact, ContactBook},
+    keystore::{derive_key, IdentifierKeys, SALT_LEN},
+    Controller, Identifier,
+};
+
+/// Encrypted identity bundle. Its content is encrypted with
+/// ChaCha20-Poly1305 key derived from passphrase with Argon2id, as in
+/// `EncryptedFileKeyStore`.
+#[derive(Serialize, Deserialize)]
+struct BundleFile {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Decrypted bundle. CESR streams are base64 encoded.
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    id: IdentifierPrefix,
+    kel: String,
+    tels: Vec<String>,
+    end_roles: String,
+    contacts: Vec<Contact>,
+    keys: Option<IdentifierKeys>,
+}
+
+/// Data exported in identity bundle along with identifier's KEL. TELs and
+/// end roles of identifier can be gathered with
+/// `Controller::bundle_contents`.
+#[derive(Default)]
+pub struct BundleContents {
+    tels: Vec<Vec<u8>>,
+    end_roles: Vec<SignedReply>,
+    contacts: Vec<Contact>,
+    keys: Option<IdentifierKeys>,
+}
+
+impl BundleContents {
+    /// Adds TEL exported with `Controller::export_tel`.
+    pub fn with_tel(mut self, tel: Vec<u8>) -> Self {
+        self.tels.push(tel);
+        self
+    }
+
+    pub fn with_end_roles(mut self, end_roles: Vec<SignedReply>) -> Self {
+        self.end_roles.extend(end_roles);
+        self
+    }
+
+    pub fn with_contacts(mut self, contacts: Vec<Contact>) -> Self {
+        self.contacts.extend(contacts);
+        self
+    }
+
+    /// Includes seeds of identifier's keys, so that it can be controlled
+    /// from the device bundle is imported on.
+    pub fn with_keys(mut self, keys: IdentifierKeys) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+}
+
+impl<D: EventDatabase + 'static> Identifier<D> {
+    /// Exports identifier's KEL together with `contents` as bundle
+    /// encrypted with key derived from `passphrase`. Bundle can be restored
+    /// on other device with `Controller::import_bundle`.
+    pub fn export_bundle(
+        &self,
+        contents: BundleContents,
+        passphrase: &str,
+    ) -> Result<Vec<u8>, String> {
+        let kel = self
+            .get_own_kel()
+            .ok_or("Unknown identifier".to_string())?
+            .into_iter()
+            .map(|notice| Message::Notice(notice).to_cesr())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+            .concat();
+        let end_roles = contents
+            .end_roles
+            .into_iter()
+            .map(|rpy| Message::Op(Op::Reply(rpy)).to_cesr())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+            .concat();
+        let bundle = Bundle {
+            id: self.id.clone(),
+            kel: base64::encode(kel),
+            tels: contents.tels.iter().map(base64::encode).collect(),
+            end_roles: base64::encode(end_roles),
+            contacts: contents.contacts,
+            keys: contents.keys,
+        };
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(&bundle).map_err(|e| e.to_string())?,
+        );
+
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_key(passphrase, &salt)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| "Bundle encryption error".to_string())?;
+        serde_json::to_vec(&BundleFile {
+            salt: base64::encode(salt),
+            nonce: base64::encode(nonce),
+            ciphertext: base64::encode(ciphertext),
+        })
+        .map_err(|e| e.to_string())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Gathers TELs of registries anchored in identifier's KEL and its end
+    /// role authorizations saved in `oobi_manager`.
+    pub fn bundle_contents(
+        &self,
+        identifier: &Identifier<D>,
+        oobi_manager: &OobiManager,
+    ) -> Result<BundleContents, String> {
+        let mut anchored: Vec<IdentifierPrefix> = vec![];
+        for notice in identifier.get_own_kel().unwrap_or_default() {
+            let Notice::Event(event) = notice else {
+                continue;
+            };
+            let data = match event.event_message.data.get_event_data() {
+                EventData::Ixn(ixn) => ixn.data,
+                EventData::Rot(rot) => rot.data,
+                EventData::Drt(drt) => drt.data,
+                _ => continue,
+            };
+            for seal in data {
+                if let Seal::Event(es) = seal {
+                    if !anchored.contains(&es.prefix) {
+                        anchored.push(es.prefix);
+                    }
+                }
+            }
+        }
+        let mut contents = BundleContents::default();
+        for id in anchored {
+            // Credentials are anchored too, only registries are exported.
+            let registry = self
+                .tel
+                .processor
+                .tel_reference
+                .get_management_events(&id)
+                .map_err(|e| e.to_string())?;
+            if registry.is_some() {
+                contents = contents.with_tel(self.export_tel(&id)?);
+            }
+        }
+        for role in [
+            Role::Controller,
+            Role::Witness,
+            Role::Watcher,
+            Role::Messagebox,
+        ] {
+            contents = contents.with_end_roles(
+                oobi_manager
+                    .get_end_role(&identifier.id, role)
+                    .map_err(|e| e.to_string())?
+                    .unwrap_or_default(),
+            );
+        }
+        Ok(contents)
+    }
+
+    /// Restores identifier from bundle made with `Identifier::export_bundle`.
+    /// Its KEL and TELs are verified as any other events. End roles are
+    /// saved in `oobi_manager`, contacts in `contacts`, and keys in
+    /// keystore set with `with_keystore`, which has to be unlocked.
+    pub fn import_bundle(
+        &self,
+        bundle: &[u8],
+        passphrase: &str,
+        oobi_manager: &OobiManager,
+        contacts: Option<&ContactBook>,
+    ) -> Result<Identifier<D>, String> {
+        let file: BundleFile = serde_json::from_slice(bundle)
+            .map_err(|_| "Bundle format error".to_string())?;
+        let decode = |field: &str| {
+            base64::decode(field).map_err(|_| "Bundle format error".to_string())
+        };
+        let salt = decode(&file.salt)?;
+        let nonce = decode(&file.nonce)?;
+        if nonce.len() != 12 {
+            return Err("Bundle format error".to_string());
+        }
+        let key = derive_key(passphrase, &salt)?;
+        let plaintext = Zeroizing::new(
+            ChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    decode(&file.ciphertext)?.as_slice(),
+                )
+                .map_err(|_| "Wrong bundle passphrase".to_string())?,
+        );
+        let bundle: Bundle = serde_json::from_slice(&plaintext)
+            .map_err(|_| "Bundle format error".to_string())?;
+        if bundle.keys.is_some() && self.keystore().is_none() {
+            return Err(
+                "Bundle contains keys, but no keystore configured".to_string()
+            );
+        }
+
+        let kel = parse_event_stream(&decode(&bundle.kel)?)
+            .map_err(|e| e.to_string())?;
+        self.process_kel(&kel)?;
+        let identifier = self.load_identifier(&bundle.id)?;
+
+        for rpy in parse_event_stream(&decode(&bundle.end_roles)?)
+            .map_err(|e| e.to_string())?
+        {
+            match rpy {
+                Message::Op(Op::Reply(rpy)) => process_reply(
+                    rpy,
+                    oobi_manager,
+                    self.kel.processor.as_ref(),
+                    &self.kel.storage,
+                )
+                .map_err(|e| e.to_string())?,
+                _ => return Err("Bundle format error".to_string()),
+            }
+        }
+        for tel in &bundle.tels {
+            self.import_tel(&decode(tel)?)?;
+        }
+        if let Some(book) = contacts {
+            bundle
+                .contacts
+                .iter()
+                .try_for_each(|contact| book.save(contact))?;
+        }
+        if let Some(keys) = &bundle.keys {
+            self.store_keys(&bundle.id, keys)?;
+        }
+        Ok(identifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use keri_core::{prefix::BasicPrefix, signer::Signer};
+
+    use super::*;
+    use crate::{
+        test_utils::{incept, setup, sign},
+        EncryptedFileKeyStore,
+    };
+
+    #[test]
+    fn test_identity_bundle() {
+        let (root, controller) = setup("test-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let (ixn, iss) = controller
+            .issue_credential(&identifier, &registry, br#"{"name":"Jo"}"#)
+            .unwrap();
+        let said = controller
+            .finalize_issue_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+
+        let oobi_manager =
+            OobiManager::new(controller.kel.storage.events_db.clone());
+        let watcher = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let rpy = identifier
+            .add_end_role(watcher.clone(), Role::Watcher)
+            .unwrap();
+        let end_role = identifier
+            .finalize_reply(rpy.as_bytes(), sign(&signer, rpy.as_bytes()))
+            .unwrap();
+        process_reply(
+            end_role,
+            &oobi_manager,
+            controller.kel.processor.as_ref(),
+            &controller.kel.storage,
+        )
+        .unwrap();
+
+        let contacts = ContactBook::new(&root.path().join("contacts")).unwrap();
+        contacts
+            .add(&Contact::new("watcher", watcher.clone()))
+            .unwrap();
+        let keys = IdentifierKeys::generate(1);
+        let contents = controller
+            .bundle_contents(&identifier, &oobi_manager)
+            .unwrap()
+            .with_contacts(contacts.list().unwrap())
+            .with_keys(keys.clone());
+        let bundle = identifier.export_bundle(contents, "secret").unwrap();
+
+        let (other_root, other) = setup("test-db");
+        let other = other.with_keystore(Arc::new(EncryptedFileKeyStore::new(
+            other_root.path().join("keys.json"),
+        )));
+        other.unlock("other").unwrap();
+        let other_oobi_manager =
+            OobiManager::new(other.kel.storage.events_db.clone());
+        let other_contacts =
+            ContactBook::new(&other_root.path().join("contacts")).unwrap();
+        assert!(other
+            .import_bundle(&bundle, "wrong", &other_oobi_manager, None)
+            .is_err());
+
+        let imported = other
+            .import_bundle(
+                &bundle,
+                "secret",
+                &other_oobi_manager,
+                Some(&other_contacts),
+            )
+            .unwrap();
+        assert_eq!(imported.id, identifier.id);
+        assert_eq!(
+            other.get_state(&identifier.id),
+            controller.get_state(&identifier.id)
+        );
+        assert!(other.get_vc_state(&said).unwrap().is_some());
+        assert_eq!(
+            other_oobi_manager
+                .get_end_role(&identifier.id, Role::Watcher)
+                .unwrap()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(other_contacts.list().unwrap(), contacts.list().unwrap());
+        assert_eq!(other.stored_keys(&identifier.id).unwrap(), keys);
+    }
+}
@@ -0,0 +1,198 @@
+use cesrox::cesr_proof::MaterialPath;
+use keri_core::{
+    actor::{event_generator, parse_exchange_stream},
+    database::{EscrowCreator, EventDatabase},
+    event_message::{
+        cesr_adapter::{parse_event_type, EventType},
+        signature::{Signature, SignerData},
+        signed_event_message::{Message, Op},
+    },
+    mailbox::exchange::{Exchange, SignedExchange},
+    prefix::{IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
+};
+use teliox::database::TelEventDatabase;
+
+use crate::{Controller, Identifier};
+
+impl<D: EventDatabase> Identifier<D> {
+    /// Generates response to `challenger`'s challenge, which repeats
+    /// challenge `words`. Signed response proves control of identifier.
+    pub fn respond_to_challenge(
+        &self,
+        challenger: &IdentifierPrefix,
+        words: &[String],
+    ) -> Result<String, String> {
+        let exn = event_generator::challenge_response(
+            challenger,
+            &self.id,
+            words.to_vec(),
+        )
+        .encode()
+        .map_err(|_| "Event encoding error".to_string())?;
+        String::from_utf8(exn).map_err(|_| "Event format error".to_string())
+    }
+
+    /// Attaches identifier's signature to challenge response. Returns CESR
+    /// stream, which should be sent to the challenger.
+    pub fn finalize_challenge_response(
+        &self,
+        exn: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<Vec<u8>, String> {
+        let exn = match parse_event_type(exn)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::Exn(exn) => exn,
+            _ => return Err("Event is not an exchange".to_string()),
+        };
+        match &exn.data.data {
+            Exchange::ChallengeResponse { response, .. }
+                if response.signer == self.id => {}
+            _ => {
+                return Err("Exchange is not identifier's challenge response"
+                    .to_string())
+            }
+        };
+        let signed = SignedExchange {
+            exchange_message: exn,
+            signature: vec![Signature::Transferable(
+                SignerData::LastEstablishment(self.id.clone()),
+                vec![IndexedSignature::new_both_same(sig, 0)],
+            )],
+            data_signature: (MaterialPath::to_path("-a".into()), vec![]),
+        };
+        Message::Op(Op::Exchange(signed))
+            .to_cesr()
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Verifies challenge response received as CESR stream. Response has to
+    /// repeat challenge `words` and be signed with current keys of its
+    /// signer, whose KEL needs to be known. Returns identifier which proved
+    /// its control.
+    pub fn verify_challenge_response(
+        &self,
+        response: &[u8],
+        words: &[String],
+    ) -> Result<IdentifierPrefix, String> {
+        let exn = parse_exchange_stream(response)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .next()
+            .ok_or("Empty response".to_string())?;
+        let Exchange::ChallengeResponse { response, .. } =
+            &exn.exchange_message.data.data
+        else {
+            return Err("Exchange is not a challenge response".to_string());
+        };
+        if response.words != words {
+            return Err("Challenge words don't match".to_string());
+        }
+        let signers_match = exn.signature.iter().all(|signature| {
+            signature.get_signer().as_ref() == Some(&response.signer)
+        });
+        if exn.signature.is_empty() || !signers_match {
+            return Err("Response wasn't signed by responder".to_string());
+        }
+        if !exn
+            .verify(self.kel.storage.as_ref())
+            .map_err(|e| e.to_string())?
+        {
+            return Err("Wrong challenge response signature".to_string());
+        }
+        Ok(response.signer.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::signer::Signer;
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_challenge_response() {
+        let (_responder_root, responder) = setup("responder-db");
+        let responder_signer = Signer::new();
+        let responder_id = incept(&responder, &responder_signer);
+
+        let (_root, controller) = setup("test-db");
+        let challenger = incept(&controller, &Signer::new());
+        let kel = responder
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(&responder_id.id)
+            .unwrap()
+            .unwrap();
+        controller
+            .process_kel(
+                &kel.into_iter().map(Message::Notice).collect::<Vec<_>>(),
+            )
+            .unwrap();
+
+        let words: Vec<String> = ["cargo", "wheat", "orbit", "lunar"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let exn = responder_id
+            .respond_to_challenge(&challenger.id, &words)
+            .unwrap();
+        let response = responder_id
+            .finalize_challenge_response(
+                exn.as_bytes(),
+                sign(&responder_signer, exn.as_bytes()),
+            )
+            .unwrap();
+        assert_eq!(
+            controller.verify_challenge_response(&response, &words),
+            Ok(responder_id.id.clone())
+        );
+
+        // Response has to repeat all challenge words.
+        assert!(controller
+            .verify_challenge_response(&response, &words[..3])
+            .is_err());
+
+        // Response signed with other keys is rejected.
+        let forged = responder_id
+            .finalize_challenge_response(
+                exn.as_bytes(),
+                sign(&Signer::new(), exn.as_bytes()),
+            )
+            .unwrap();
+        assert!(controller
+            .verify_challenge_response(&forged, &words)
+            .is_err());
+
+        // Identifier can't sign response made by other identifier.
+        let stranger_signer = Signer::new();
+        let stranger = incept(&responder, &stranger_signer);
+        assert!(stranger
+            .finalize_challenge_response(
+                exn.as_bytes(),
+                sign(&stranger_signer, exn.as_bytes())
+            )
+            .is_err());
+
+        // Response of identifier with unknown KEL can't be verified.
+        let exn = stranger
+            .respond_to_challenge(&challenger.id, &words)
+            .unwrap();
+        let response = stranger
+            .finalize_challenge_response(
+                exn.as_bytes(),
+                sign(&stranger_signer, exn.as_bytes()),
+            )
+            .unwrap();
+        assert!(controller
+            .verify_challenge_response(&response, &words)
+            .is_err());
+    }
+}
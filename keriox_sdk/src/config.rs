@@ -0,0 +1,347 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use figment::{
+    providers::{Format, Toml, Yaml},
+    Figment,
+};
+use keri_core::{
+    database::redb::RedbDatabase, oobi::LocationScheme,
+    processor::escrow::EscrowConfig,
+};
+use serde::Deserialize;
+
+use crate::{
+    builder::ControllerBuilder, network::NetworkPolicy, receipts::RetryPolicy,
+    KeriRuntime,
+};
+
+/// Runtime configuration loaded from TOML or YAML file, so that agents can
+/// be reconfigured without recompiling. Example in YAML:
+///
+/// ```yaml
+/// db_path: "db/"
+/// tel_escrow: true
+/// witnesses:
+///   - eid: "BJq7UABlttINuWJh1Xl2lkqZG4NTdUdqnbFJDa6ZyxCC"
+///     scheme: "http"
+///     url: "http://witness1.example.com:3232/"
+/// escrow:
+///   default_timeout: 60
+///   partially_witnessed_timeout: 300
+/// transport:
+///   timeout: 10
+///   max_attempts: 5
+///   failure_threshold: 3
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuntimeConfig {
+    /// Directory where databases are kept.
+    pub db_path: PathBuf,
+
+    #[serde(default)]
+    pub tel_escrow: bool,
+
+    #[serde(default)]
+    pub witnesses: Vec<LocationScheme>,
+
+    #[serde(default)]
+    pub watchers: Vec<LocationScheme>,
+
+    #[serde(default)]
+    pub escrow: EscrowTimeouts,
+
+    #[serde(default)]
+    pub transport: TransportConfig,
+}
+
+/// Escrow timeouts in seconds. Timeouts that aren't set fall back to
+/// `default_timeout`, and then to `EscrowConfig::default`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EscrowTimeouts {
+    pub default_timeout: Option<u64>,
+    pub out_of_order_timeout: Option<u64>,
+    pub partially_signed_timeout: Option<u64>,
+    pub partially_witnessed_timeout: Option<u64>,
+    pub trans_receipt_timeout: Option<u64>,
+    pub delegation_timeout: Option<u64>,
+}
+
+/// Timeout of single request in seconds, retries of failed requests and
+/// circuit breaking of failing endpoints, see `NetworkPolicy`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransportConfig {
+    #[serde(default = "default_request_timeout")]
+    pub timeout: u64,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_initial_backoff")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff_ms: u64,
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    #[serde(default = "default_cooldown")]
+    pub cooldown: u64,
+    #[serde(default = "default_max_pending_requests")]
+    pub max_pending_requests: usize,
+}
+
+fn default_request_timeout() -> u64 {
+    30
+}
+
+fn default_max_attempts() -> u32 {
+    RetryPolicy::default().max_attempts
+}
+
+fn default_initial_backoff() -> u64 {
+    RetryPolicy::default().initial_backoff.as_millis() as u64
+}
+
+fn default_max_backoff() -> u64 {
+    RetryPolicy::default().max_backoff.as_millis() as u64
+}
+
+fn default_jitter() -> f64 {
+    NetworkPolicy::default().jitter
+}
+
+fn default_failure_threshold() -> u32 {
+    NetworkPolicy::default().failure_threshold
+}
+
+fn default_cooldown() -> u64 {
+    NetworkPolicy::default().cooldown.as_secs()
+}
+
+fn default_max_pending_requests() -> usize {
+    NetworkPolicy::default().max_pending_requests
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            timeout: default_request_timeout(),
+            max_attempts: default_max_attempts(),
+            initial_backoff_ms: default_initial_backoff(),
+            max_backoff_ms: default_max_backoff(),
+            jitter: default_jitter(),
+            failure_threshold: default_failure_threshold(),
+            cooldown: default_cooldown(),
+            max_pending_requests: default_max_pending_requests(),
+        }
+    }
+}
+
+impl RuntimeConfig {
+    /// Loads configuration from `path`. Files with `toml` extension are
+    /// parsed as TOML, others as YAML.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let figment = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Figment::new().merge(Toml::file(path)),
+            _ => Figment::new().merge(Yaml::file(path)),
+        };
+        figment
+            .extract()
+            .map_err(|e| format!("Failed to load config: {}", e))
+    }
+
+    pub fn escrow_config(&self) -> EscrowConfig {
+        let default = EscrowConfig::default();
+        let timeout = |timeout: Option<u64>, default: Duration| {
+            timeout
+                .or(self.escrow.default_timeout)
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        EscrowConfig {
+            out_of_order_timeout: timeout(
+                self.escrow.out_of_order_timeout,
+                default.out_of_order_timeout,
+            ),
+            partially_signed_timeout: timeout(
+                self.escrow.partially_signed_timeout,
+                default.partially_signed_timeout,
+            ),
+            partially_witnessed_timeout: timeout(
+                self.escrow.partially_witnessed_timeout,
+                default.partially_witnessed_timeout,
+            ),
+            trans_receipt_timeout: timeout(
+                self.escrow.trans_receipt_timeout,
+                default.trans_receipt_timeout,
+            ),
+            delegation_timeout: timeout(
+                self.escrow.delegation_timeout,
+                default.delegation_timeout,
+            ),
+        }
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.transport.max_attempts,
+            initial_backoff: Duration::from_millis(
+                self.transport.initial_backoff_ms,
+            ),
+            max_backoff: Duration::from_millis(self.transport.max_backoff_ms),
+        }
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.transport.timeout)
+    }
+
+    /// Policy to be passed to `ControllerBuilder::with_transport`.
+    pub fn network_policy(&self) -> NetworkPolicy {
+        NetworkPolicy {
+            retry: self.retry_policy(),
+            jitter: self.transport.jitter,
+            timeout: self.request_timeout(),
+            failure_threshold: self.transport.failure_threshold,
+            cooldown: Duration::from_secs(self.transport.cooldown),
+            max_pending_requests: self.transport.max_pending_requests,
+            ..NetworkPolicy::default()
+        }
+    }
+
+    /// Creates controller builder set up according to configuration. With
+    /// `http` feature, OOBIs are fetched with HTTP client that respects
+    /// configured request timeout.
+    pub fn builder(&self) -> Result<ControllerBuilder, String> {
+        let builder = ControllerBuilder::default()
+            .with_db_path(&self.db_path)
+            .with_escrow_config(self.escrow_config());
+        let builder = if self.tel_escrow {
+            builder.with_tel_escrow()
+        } else {
+            builder
+        };
+        #[cfg(feature = "http")]
+        let builder = builder.with_oobi_fetcher(Arc::new(
+            crate::oobi::HttpOobiFetcher::with_timeout(self.request_timeout())?,
+        ));
+        let builder = self
+            .witnesses
+            .iter()
+            .fold(builder, |builder, oobi| builder.with_witness(oobi.clone()));
+        Ok(self
+            .watchers
+            .iter()
+            .fold(builder, |builder, oobi| builder.with_watcher(oobi.clone())))
+    }
+}
+
+impl KeriRuntime<RedbDatabase> {
+    /// Creates runtime from configuration file, see `RuntimeConfig`. Events
+    /// are kept in the same database as in controller built from it.
+    pub fn from_config(path: impl AsRef<Path>) -> Result<Self, String> {
+        let config = RuntimeConfig::load(path)?;
+        std::fs::create_dir_all(&config.db_path).map_err(|e| e.to_string())?;
+        let event_db = RedbDatabase::new(&config.db_path.join("events"))
+            .map_err(|e| e.to_string())?;
+        Ok(Self::with_config(
+            Arc::new(event_db),
+            config.escrow_config(),
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{oobi::Scheme, prefix::IdentifierPrefix};
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_runtime_config() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let witness = "BJq7UABlttINuWJh1Xl2lkqZG4NTdUdqnbFJDa6ZyxCC";
+
+        let yaml_path = root.path().join("keri.yml");
+        std::fs::write(
+            &yaml_path,
+            format!(
+                "db_path: {:?}\n\
+                 witnesses:\n  \
+                 - eid: \"{}\"\n    \
+                   scheme: \"http\"\n    \
+                   url: \"http://127.0.0.1:3232/\"\n\
+                 escrow:\n  \
+                 default_timeout: 10\n  \
+                 delegation_timeout: 120\n\
+                 transport:\n  \
+                 max_attempts: 3\n  \
+                 failure_threshold: 2\n",
+                root.path().join("yaml"),
+                witness
+            ),
+        )
+        .unwrap();
+        let config = RuntimeConfig::load(&yaml_path).unwrap();
+        assert_eq!(
+            config.witnesses,
+            vec![LocationScheme::new(
+                witness.parse::<IdentifierPrefix>().unwrap(),
+                Scheme::Http,
+                "http://127.0.0.1:3232/".parse().unwrap(),
+            )]
+        );
+        let escrow = config.escrow_config();
+        assert_eq!(escrow.out_of_order_timeout, Duration::from_secs(10));
+        assert_eq!(escrow.delegation_timeout, Duration::from_secs(120));
+        assert_eq!(config.retry_policy().max_attempts, 3);
+        assert_eq!(
+            config.retry_policy().max_backoff,
+            RetryPolicy::default().max_backoff
+        );
+        assert!(!config.tel_escrow);
+        let policy = config.network_policy();
+        assert_eq!(policy.failure_threshold, 2);
+        assert_eq!(policy.retry.max_attempts, 3);
+        assert_eq!(policy.cooldown, NetworkPolicy::default().cooldown);
+
+        let toml_path = root.path().join("keri.toml");
+        std::fs::write(
+            &toml_path,
+            format!(
+                "db_path = {:?}\n\
+                 tel_escrow = true\n\
+                 [transport]\n\
+                 timeout = 5\n",
+                root.path().join("toml")
+            ),
+        )
+        .unwrap();
+        let config = RuntimeConfig::load(&toml_path).unwrap();
+        assert!(config.tel_escrow);
+        assert!(config.witnesses.is_empty());
+        assert_eq!(config.request_timeout(), Duration::from_secs(5));
+        assert_eq!(
+            config.escrow_config().out_of_order_timeout,
+            EscrowConfig::default().out_of_order_timeout
+        );
+
+        let runtime = KeriRuntime::from_config(&toml_path).unwrap();
+        assert!(runtime
+            .storage
+            .get_state(&witness.parse().unwrap())
+            .is_none());
+        drop(runtime);
+        let controller = config.builder().unwrap().build().unwrap();
+        assert!(controller.witnesses().is_empty());
+
+        std::fs::write(root.path().join("broken.yml"), "witnesses: []\n")
+            .unwrap();
+        assert!(RuntimeConfig::load(root.path().join("broken.yml")).is_err());
+    }
+}
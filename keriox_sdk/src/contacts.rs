@@ -0,0 +1,271 @@
+use std::{collections::BTreeMap, path::Path};
+
+use keri_core::{oobi::Oobi, prefix::IdentifierPrefix};
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+
+/// Contacts store alias -> JSON serialized contact
+const CONTACTS: TableDefinition<&str, &[u8]> = TableDefinition::new("contacts");
+
+/// Result of challenging contact to prove control of its identifier.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum ChallengeStatus {
+    #[default]
+    NotChallenged,
+    /// Contact responded with valid challenge response, see
+    /// `Controller::verify_challenge_response`.
+    Verified,
+    Failed,
+}
+
+/// Identifier known under human-readable alias.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contact {
+    pub alias: String,
+    pub id: IdentifierPrefix,
+    /// OOBIs the contact's KEL and endpoints can be resolved with.
+    pub oobis: Vec<Oobi>,
+    pub challenge: ChallengeStatus,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Contact {
+    pub fn new(alias: &str, id: IdentifierPrefix) -> Self {
+        Self {
+            alias: alias.to_string(),
+            id,
+            oobis: vec![],
+            challenge: ChallengeStatus::default(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.alias.to_lowercase().contains(&query)
+            || self.id.to_string().to_lowercase().contains(&query)
+            || self
+                .metadata
+                .values()
+                .any(|value| value.to_lowercase().contains(&query))
+    }
+}
+
+/// Persistent store of contacts, keyed by alias.
+pub struct ContactBook {
+    db: Database,
+}
+
+impl ContactBook {
+    pub fn new(path: &Path) -> Result<Self, String> {
+        let db = Database::create(path).map_err(|e| e.to_string())?;
+        // Create table
+        let write_txn = db.begin_write().map_err(|e| e.to_string())?;
+        write_txn.open_table(CONTACTS).map_err(|e| e.to_string())?;
+        write_txn.commit().map_err(|e| e.to_string())?;
+        Ok(Self { db })
+    }
+
+    /// Adds new contact. Fails if its alias is already taken.
+    pub fn add(&self, contact: &Contact) -> Result<(), String> {
+        if self.get(&contact.alias)?.is_some() {
+            return Err(format!("Alias {} already exists", contact.alias));
+        }
+        self.save(contact)
+    }
+
+    /// Saves contact, replacing the one of the same alias.
+    pub fn save(&self, contact: &Contact) -> Result<(), String> {
+        let value = serde_json::to_vec(contact).map_err(|e| e.to_string())?;
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table =
+                write_txn.open_table(CONTACTS).map_err(|e| e.to_string())?;
+            table
+                .insert(contact.alias.as_str(), value.as_slice())
+                .map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, alias: &str) -> Result<Option<Contact>, String> {
+        let read_txn = self.db.begin_read().map_err(|e| e.to_string())?;
+        let table = read_txn.open_table(CONTACTS).map_err(|e| e.to_string())?;
+        let contact = table
+            .get(alias)
+            .map_err(|e| e.to_string())?
+            .map(|value| {
+                serde_json::from_slice(value.value()).map_err(|e| e.to_string())
+            })
+            .transpose();
+        contact
+    }
+
+    /// Returns contacts of identifier.
+    pub fn get_by_id(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Vec<Contact>, String> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|contact| &contact.id == id)
+            .collect())
+    }
+
+    /// Removes contact and returns it, if it existed.
+    pub fn remove(&self, alias: &str) -> Result<Option<Contact>, String> {
+        let removed = self.get(alias)?;
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table =
+                write_txn.open_table(CONTACTS).map_err(|e| e.to_string())?;
+            table.remove(alias).map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())?;
+        Ok(removed)
+    }
+
+    /// Returns all contacts ordered by alias.
+    pub fn list(&self) -> Result<Vec<Contact>, String> {
+        let read_txn = self.db.begin_read().map_err(|e| e.to_string())?;
+        let table = read_txn.open_table(CONTACTS).map_err(|e| e.to_string())?;
+        let contacts = table
+            .iter()
+            .map_err(|e| e.to_string())?
+            .map(|entry| {
+                let (_, value) = entry.map_err(|e| e.to_string())?;
+                serde_json::from_slice(value.value()).map_err(|e| e.to_string())
+            })
+            .collect();
+        contacts
+    }
+
+    /// Returns contacts whose alias, identifier or metadata values contain
+    /// `query`, ignoring case.
+    pub fn search(&self, query: &str) -> Result<Vec<Contact>, String> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|contact| contact.matches(query))
+            .collect())
+    }
+
+    /// Attaches OOBI to contact, unless it's already attached.
+    pub fn add_oobi(&self, alias: &str, oobi: Oobi) -> Result<(), String> {
+        self.update(alias, |contact| {
+            if !contact.oobis.contains(&oobi) {
+                contact.oobis.push(oobi);
+            }
+        })
+    }
+
+    /// Sets challenge status of all contacts of identifier, e.g. after its
+    /// challenge response was verified.
+    pub fn set_challenge_status(
+        &self,
+        id: &IdentifierPrefix,
+        status: ChallengeStatus,
+    ) -> Result<(), String> {
+        self.get_by_id(id)?.into_iter().try_for_each(|mut contact| {
+            contact.challenge = status;
+            self.save(&contact)
+        })
+    }
+
+    pub fn set_metadata(
+        &self,
+        alias: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        self.update(alias, |contact| {
+            contact.metadata.insert(key.to_string(), value.to_string());
+        })
+    }
+
+    fn update(
+        &self,
+        alias: &str,
+        change: impl FnOnce(&mut Contact),
+    ) -> Result<(), String> {
+        let mut contact = self
+            .get(alias)?
+            .ok_or(format!("Unknown contact {}", alias))?;
+        change(&mut contact);
+        self.save(&contact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        oobi::{EndRole, Role},
+        prefix::BasicPrefix,
+        signer::Signer,
+    };
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_contact_book() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let path = root.path().join("contacts");
+        let new_id = || {
+            IdentifierPrefix::Basic(BasicPrefix::Ed25519(
+                Signer::new().public_key(),
+            ))
+        };
+        let (alice, bob) = (new_id(), new_id());
+
+        {
+            let contacts = ContactBook::new(&path).unwrap();
+            contacts.add(&Contact::new("bob", bob.clone())).unwrap();
+            contacts.add(&Contact::new("alice", alice.clone())).unwrap();
+            assert!(contacts.add(&Contact::new("alice", bob.clone())).is_err());
+
+            let oobi = Oobi::EndRole(EndRole {
+                cid: alice.clone(),
+                role: Role::Witness,
+                eid: new_id(),
+            });
+            contacts.add_oobi("alice", oobi.clone()).unwrap();
+            contacts.add_oobi("alice", oobi).unwrap();
+            contacts.set_metadata("alice", "org", "Acme Corp").unwrap();
+            contacts
+                .set_challenge_status(&alice, ChallengeStatus::Verified)
+                .unwrap();
+            assert!(contacts.set_metadata("carol", "org", "Acme").is_err());
+        }
+
+        // Contacts are kept after reopening.
+        let contacts = ContactBook::new(&path).unwrap();
+        let listed: Vec<String> = contacts
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|contact| contact.alias)
+            .collect();
+        assert_eq!(listed, vec!["alice", "bob"]);
+        let contact = contacts.get("alice").unwrap().unwrap();
+        assert_eq!(contact.id, alice);
+        assert_eq!(contact.oobis.len(), 1);
+        assert_eq!(contact.challenge, ChallengeStatus::Verified);
+        assert_eq!(
+            contacts.get("bob").unwrap().unwrap().challenge,
+            ChallengeStatus::NotChallenged
+        );
+
+        assert_eq!(contacts.search("ACME").unwrap(), vec![contact.clone()]);
+        assert_eq!(contacts.search("bo").unwrap().len(), 1);
+        assert_eq!(contacts.search(&bob.to_string()).unwrap().len(), 1);
+        assert_eq!(contacts.get_by_id(&alice).unwrap(), vec![contact.clone()]);
+
+        assert_eq!(contacts.remove("alice").unwrap(), Some(contact));
+        assert_eq!(contacts.get("alice").unwrap(), None);
+        assert_eq!(contacts.remove("alice").unwrap(), None);
+    }
+}
@@ -1,4 +1,6 @@
-use std::sync::Arc;
+#[cfg(feature = "storage-redb")]
+use std::{collections::HashMap, sync::RwLock};
+use std::{sync::Arc, time::Duration};
 
 use keri_core::{
     actor::{event_generator, prelude::EventStorage},
@@ -14,17 +16,52 @@ use keri_core::{
     },
     processor::{
         basic_processor::BasicProcessor,
-        escrow::{default_escrow_bus, EscrowConfig, EscrowSet},
-        notification::NotificationBus,
+        escrow::{
+            escrow_bus_with_policy, EscrowConfig, EscrowPolicy, EscrowSet,
+        },
+        notification::{JustNotification, NotificationBus},
         Processor,
-    }, state::IdentifierState,
+    },
+    state::IdentifierState,
+};
+use said::SelfAddressingIdentifier;
+use teliox::{
+    database::TelEventDatabase,
+    processor::{notification::TelNotificationKind, storage::TelEventStorage},
+    state::vc_state::TelState,
+    tel::Tel,
 };
+#[cfg(feature = "storage-redb")]
 use teliox::{
-    database::TelEventDatabase, processor::storage::TelEventStorage,
-    state::vc_state::TelState, tel::Tel,
+    database::{EscrowDatabase, TelLogDatabase},
+    processor::escrow::default_escrow_bus,
 };
 
-use crate::Identifier;
+use crate::{
+    acdc::Acdc,
+    edges::{ChainConfig, CredentialResolver},
+    keystore::KeyStore,
+    ksn::{KsnListener, KsnObserver},
+    network::{NetworkPolicy, PolicyTransport},
+    receipts::{ReceiptCollector, ReceiptFetcher},
+    schema::SchemaRegistry,
+    status_cache::CredentialStatusCache,
+    transport::{Transport, TransportAdapter},
+    witness::{WitnessPublisher, WitnessSubmitter},
+    Identifier,
+};
+#[cfg(feature = "oobi-manager")]
+use crate::{
+    delegation::DelegationObserver,
+    did::DidResolver,
+    oobi::{KelResolver, OobiFetcher, OobiResolver},
+};
+#[cfg(feature = "storage-redb")]
+use crate::{managed::IdentifierRegistry, pending::PendingOperations};
+#[cfg(feature = "oobi-manager")]
+use keri_core::oobi_manager::OobiManager;
+#[cfg(feature = "oobi-manager")]
+use url::Url;
 
 pub struct KeriRuntime<D: EventDatabase + EscrowCreator + Send + Sync + 'static> {
     pub processor: Arc<BasicProcessor<D>>,
@@ -43,11 +80,26 @@ impl<D: EventDatabase + EscrowCreator + Send + Sync + 'static> KeriRuntime<D> {
         escrow_config: EscrowConfig,
         notification_bus: Option<NotificationBus>,
     ) -> Self {
-        let (bus, escrows) =
-            default_escrow_bus(event_db.clone(), escrow_config, notification_bus);
+        Self::with_policy(event_db, Arc::new(escrow_config), notification_bus)
+    }
 
-        let processor =
-            Arc::new(BasicProcessor::new(event_db.clone(), Some(bus.clone())));
+    /// Creates runtime whose processor and escrows follow provided policy.
+    pub fn with_policy(
+        event_db: Arc<D>,
+        policy: Arc<dyn EscrowPolicy>,
+        notification_bus: Option<NotificationBus>,
+    ) -> Self {
+        let (bus, escrows) = escrow_bus_with_policy(
+            event_db.clone(),
+            policy.clone(),
+            notification_bus,
+        );
+
+        let processor = Arc::new(BasicProcessor::with_policy(
+            event_db.clone(),
+            Some(bus.clone()),
+            policy,
+        ));
         let storage = Arc::new(EventStorage::new(event_db));
 
         Self {
@@ -57,11 +109,140 @@ impl<D: EventDatabase + EscrowCreator + Send + Sync + 'static> KeriRuntime<D> {
             notification_bus: bus,
         }
     }
+
+    /// Processes escrowed events of identifier again. Should be called
+    /// after missing events were obtained out of band. Returns digests of
+    /// events that were rejected or couldn't be processed, with the reason.
+    pub fn reprocess_escrows(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Vec<(SelfAddressingIdentifier, String)>, String> {
+        self.escrows
+            .reprocess(self.processor.as_ref(), id)
+            .map(|errors| {
+                errors
+                    .into_iter()
+                    .map(|(digest, e)| (digest, e.to_string()))
+                    .collect()
+            })
+            .map_err(|e| e.to_string())
+    }
+
+    /// Registers observer that requests delegator's KEL from `resolver`
+    /// whenever delegated event is missing its delegating event. Requested
+    /// KELs are fetched with `KelResolver::resolve_pending`.
+    #[cfg(feature = "oobi-manager")]
+    pub fn register_delegator_resolver(&self, resolver: Arc<KelResolver<D>>) {
+        let observer =
+            Arc::new(DelegationObserver::new(self.storage.clone(), resolver));
+        self.notification_bus.register_observer(
+            observer,
+            vec![JustNotification::MissingDelegatingEvent],
+        );
+    }
+
+    /// Registers observer that publishes accepted events of watched
+    /// identifiers to their witnesses. Identifiers are added with
+    /// `WitnessSubmitter::watch`.
+    pub fn register_witness_publisher(
+        &self,
+        publisher: Arc<dyn WitnessPublisher>,
+    ) -> Arc<WitnessSubmitter> {
+        let submitter = Arc::new(WitnessSubmitter::new(publisher));
+        self.notification_bus.register_observer(
+            submitter.clone(),
+            vec![JustNotification::KeyEventAccepted],
+        );
+        submitter
+    }
+
+    /// Registers listener called whenever key state notice is accepted,
+    /// including notices accepted from escrow once missing events arrive.
+    pub fn register_ksn_listener(&self, listener: Arc<dyn KsnListener>) {
+        self.notification_bus.register_observer(
+            Arc::new(KsnObserver::new(listener)),
+            vec![JustNotification::KsnUpdated],
+        );
+    }
+
+    /// Creates resolver that processes OOBIs fetched with `fetcher` and
+    /// saves endpoint information in `oobi_manager`.
+    #[cfg(feature = "oobi-manager")]
+    pub fn oobi_resolver(
+        &self,
+        oobi_manager: Arc<OobiManager>,
+        fetcher: Arc<dyn OobiFetcher>,
+    ) -> OobiResolver<D> {
+        OobiResolver::new(
+            self.processor.clone(),
+            self.storage.clone(),
+            oobi_manager,
+            fetcher,
+        )
+    }
+
+    /// Creates resolver that fetches KELs from identifiers' known
+    /// locations and from `watchers`, whose locations need to be known.
+    #[cfg(feature = "oobi-manager")]
+    pub fn kel_resolver(
+        &self,
+        oobi_manager: Arc<OobiManager>,
+        fetcher: Arc<dyn OobiFetcher>,
+        watchers: Vec<IdentifierPrefix>,
+    ) -> KelResolver<D> {
+        KelResolver::new(self.oobi_resolver(oobi_manager, fetcher), watchers)
+    }
+
+    /// Creates resolver of `did:keri` identifiers, which fetches their KELs
+    /// from `sources` witnesses or watchers. `did:webs` identifiers are
+    /// fetched from their own web locations.
+    #[cfg(feature = "oobi-manager")]
+    pub fn did_resolver(
+        &self,
+        oobi_manager: Arc<OobiManager>,
+        fetcher: Arc<dyn OobiFetcher>,
+        sources: Vec<Url>,
+    ) -> DidResolver<D> {
+        DidResolver::new(
+            self.oobi_resolver(oobi_manager, fetcher.clone()),
+            self.storage.clone(),
+            fetcher,
+            sources,
+        )
+    }
+
+    /// Creates collector that publishes events to witnesses and gathers
+    /// their receipts.
+    pub fn receipt_collector(
+        &self,
+        publisher: Arc<dyn WitnessPublisher>,
+        fetcher: Arc<dyn ReceiptFetcher>,
+    ) -> ReceiptCollector<D> {
+        ReceiptCollector::new(
+            self.processor.clone(),
+            self.storage.clone(),
+            publisher,
+            fetcher,
+        )
+    }
 }
 
 pub struct Controller<D: EventDatabase + EscrowCreator + Send + Sync + 'static, T: TelEventDatabase> {
     pub kel: KeriRuntime<D>,
     pub tel: Arc<Tel<T, D>>,
+    schemas: Option<Arc<SchemaRegistry>>,
+    chain: Option<ChainConfig>,
+    status_cache: Option<Arc<CredentialStatusCache>>,
+    keystore: Option<Arc<dyn KeyStore>>,
+    transport: Option<TransportAdapter>,
+    #[cfg(feature = "storage-redb")]
+    pub(crate) registry: Option<Arc<IdentifierRegistry>>,
+    #[cfg(feature = "storage-redb")]
+    pub(crate) managed: RwLock<HashMap<IdentifierPrefix, Arc<Identifier<D>>>>,
+    #[cfg(feature = "storage-redb")]
+    pending: Option<Arc<PendingOperations>>,
+    pub(crate) witnesses: Vec<BasicPrefix>,
+    pub(crate) watchers: Vec<IdentifierPrefix>,
 }
 
 impl<
@@ -70,13 +251,233 @@ impl<
     > Controller<D, T>
 {
     pub fn new(event_db: Arc<D>, tel_db: Arc<T>) -> Self {
-        let kel = KeriRuntime::new(event_db);
+        Self::with_runtime(KeriRuntime::new(event_db), tel_db)
+    }
 
+    /// Creates controller on top of already set up KEL runtime, e.g. one
+    /// with custom escrow policy.
+    pub fn with_runtime(kel: KeriRuntime<D>, tel_db: Arc<T>) -> Self {
         let tel_storage = Arc::new(TelEventStorage::new(tel_db));
         let tel =
             Arc::new(Tel::new(tel_storage.clone(), kel.storage.clone(), None));
 
-        Self { kel, tel }
+        Self {
+            kel,
+            tel,
+            schemas: None,
+            chain: None,
+            status_cache: None,
+            keystore: None,
+            transport: None,
+            #[cfg(feature = "storage-redb")]
+            registry: None,
+            #[cfg(feature = "storage-redb")]
+            managed: RwLock::new(HashMap::new()),
+            #[cfg(feature = "storage-redb")]
+            pending: None,
+            witnesses: vec![],
+            watchers: vec![],
+        }
+    }
+
+    /// Creates controller which keeps TEL events, whose anchoring KEL
+    /// events aren't known yet, in escrow stored in `tel_escrow_db`.
+    /// Escrowed events are accepted once anchoring events are processed.
+    /// Missing KEL events can be fetched automatically, see
+    /// `register_issuer_resolver`.
+    #[cfg(feature = "storage-redb")]
+    pub fn with_tel_escrow(
+        event_db: Arc<D>,
+        tel_db: Arc<T>,
+        tel_escrow_db: EscrowDatabase,
+    ) -> Result<Self, String>
+    where
+        T: TelLogDatabase + Send + Sync + 'static,
+    {
+        Self::with_runtime_and_tel_escrow(
+            KeriRuntime::new(event_db),
+            tel_db,
+            tel_escrow_db,
+        )
+    }
+
+    /// Same as `with_tel_escrow`, but on top of already set up KEL runtime.
+    #[cfg(feature = "storage-redb")]
+    pub fn with_runtime_and_tel_escrow(
+        kel: KeriRuntime<D>,
+        tel_db: Arc<T>,
+        tel_escrow_db: EscrowDatabase,
+    ) -> Result<Self, String>
+    where
+        T: TelLogDatabase + Send + Sync + 'static,
+    {
+        let (
+            tel_bus,
+            missing_issuer,
+            _out_of_order,
+            _missing_registry,
+            _partially_receipted,
+        ) =
+            default_escrow_bus(
+                tel_db.clone(),
+                kel.storage.clone(),
+                tel_escrow_db,
+            )
+            .map_err(|e| e.to_string())?;
+        kel.notification_bus.register_observer(
+            missing_issuer,
+            vec![JustNotification::KeyEventAdded],
+        );
+
+        let tel_storage = Arc::new(TelEventStorage::new(tel_db));
+        let tel =
+            Arc::new(Tel::new(tel_storage, kel.storage.clone(), Some(tel_bus)));
+
+        Ok(Self {
+            kel,
+            tel,
+            schemas: None,
+            chain: None,
+            status_cache: None,
+            keystore: None,
+            transport: None,
+            #[cfg(feature = "storage-redb")]
+            registry: None,
+            #[cfg(feature = "storage-redb")]
+            managed: RwLock::new(HashMap::new()),
+            #[cfg(feature = "storage-redb")]
+            pending: None,
+            witnesses: vec![],
+            watchers: vec![],
+        })
+    }
+
+    /// Validates ACDCs against their schemas, fetched from `schemas`,
+    /// whenever they are issued or verified.
+    pub fn with_schema_registry(
+        mut self,
+        schemas: Arc<SchemaRegistry>,
+    ) -> Self {
+        self.schemas = Some(schemas);
+        self
+    }
+
+    /// Verifies credentials referenced by edges of ACDCs whenever they are
+    /// verified. Linked credentials are fetched with `resolver`, following
+    /// at most `max_depth` links from the verified ACDC.
+    pub fn with_credential_resolver(
+        mut self,
+        resolver: Arc<dyn CredentialResolver>,
+        max_depth: usize,
+    ) -> Self {
+        self.chain = Some(ChainConfig {
+            resolver,
+            max_depth,
+        });
+        self
+    }
+
+    /// Caches statuses of credentials for `ttl`, so that their TELs aren't
+    /// replayed on every `credential_status` check. Cached status of
+    /// credential is dropped whenever its TEL event is accepted.
+    pub fn with_status_cache(mut self, ttl: Duration) -> Result<Self, String> {
+        let cache = Arc::new(CredentialStatusCache::new(ttl));
+        self.tel
+            .processor
+            .register_observer(
+                cache.clone(),
+                vec![TelNotificationKind::TelEventAdded],
+            )
+            .map_err(|e| e.to_string())?;
+        self.status_cache = Some(cache);
+        Ok(self)
+    }
+
+    pub fn status_cache(&self) -> Option<&CredentialStatusCache> {
+        self.status_cache.as_deref()
+    }
+
+    /// Keeps seeds of identifiers' keys in `keystore`, which needs to be
+    /// unlocked with `unlock` before use.
+    pub fn with_keystore(mut self, keystore: Arc<dyn KeyStore>) -> Self {
+        self.keystore = Some(keystore);
+        self
+    }
+
+    pub fn keystore(&self) -> Option<&dyn KeyStore> {
+        self.keystore.as_deref()
+    }
+
+    /// Sends requests of controller over `transport`, retried and limited
+    /// according to `policy`.
+    pub fn with_transport(
+        mut self,
+        transport: Arc<dyn Transport>,
+        policy: NetworkPolicy,
+    ) -> Self {
+        self.transport = Some(TransportAdapter(Arc::new(
+            PolicyTransport::new(transport, policy),
+        )));
+        self
+    }
+
+    /// Keeps identifiers managed by controller and their settings in
+    /// `registry`, see `Controller::manage`.
+    #[cfg(feature = "storage-redb")]
+    pub fn with_identifier_registry(
+        mut self,
+        registry: Arc<IdentifierRegistry>,
+    ) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Records operations of managed identifiers in `pending` until their
+    /// events are accepted, so they can be resumed after restart with
+    /// `KeriRuntime::resume_pending`.
+    #[cfg(feature = "storage-redb")]
+    pub fn with_pending_operations(
+        mut self,
+        pending: Arc<PendingOperations>,
+    ) -> Self {
+        self.kel.register_pending_operations(pending.clone());
+        self.pending = Some(pending);
+        self
+    }
+
+    #[cfg(feature = "storage-redb")]
+    pub fn pending_operations(&self) -> Option<Arc<PendingOperations>> {
+        self.pending.clone()
+    }
+
+    /// Transport set with `with_transport`, usable wherever operations
+    /// take `WitnessPublisher`, `QueryTransport`, `OobiFetcher` or
+    /// `MailboxTransport`.
+    pub fn transport(&self) -> Option<TransportAdapter> {
+        self.transport.clone()
+    }
+
+    /// Witnesses configured with `ControllerBuilder`. Their locations are
+    /// known.
+    pub fn witnesses(&self) -> &[BasicPrefix] {
+        &self.witnesses
+    }
+
+    /// Watchers configured with `ControllerBuilder`. Their locations are
+    /// known.
+    pub fn watchers(&self) -> &[IdentifierPrefix] {
+        &self.watchers
+    }
+
+    pub(crate) fn chain_config(&self) -> Option<&ChainConfig> {
+        self.chain.as_ref()
+    }
+
+    pub(crate) fn validate_schema(&self, acdc: &Acdc) -> Result<(), String> {
+        match &self.schemas {
+            Some(schemas) => schemas.validate(acdc),
+            None => Ok(()),
+        }
     }
 
     pub fn incept(
@@ -95,21 +496,50 @@ impl<
     ) -> Result<Identifier<D>, ()> {
         let id_prefix = self.finalize_inception(event, sig)?;
 
-        Ok(Identifier::new(id_prefix, self.kel.storage.clone()))
+        Ok(Identifier::new(id_prefix, self.kel.storage.clone())
+            .with_processor(self.kel.processor.clone()))
+    }
+
+    /// Processes rotation event signed with provided indexed signatures.
+    /// Signatures of partial rotation can be prepared with
+    /// `Identifier::rotation_signature`.
+    pub fn finalize_rotate(
+        &self,
+        event: &[u8],
+        signatures: Vec<IndexedSignature>,
+    ) -> Result<(), String> {
+        let parsed_event =
+            parse_event_type(event).map_err(|e| e.to_string())?;
+        match parsed_event {
+            EventType::KeyEvent(ke) => {
+                if let EventData::Rot(_) = &ke.data.get_event_data() {
+                    let signed_message = ke.sign(signatures, None, None);
+                    self.kel
+                        .processor
+                        .process_notice(&Notice::Event(signed_message))
+                        .map_err(|e| e.to_string())
+                } else {
+                    Err("Event is not a rotation".to_string())
+                }
+            }
+            _ => Err("Event is not a key event".to_string()),
+        }
     }
 
     pub fn load_identifier(
         &self,
         id: &IdentifierPrefix,
     ) -> Result<Identifier<D>, String> {
-        self.kel.storage
+        self.kel
+            .storage
             .get_kel_messages_with_receipts_all(id)
             .map_err(|e| e.to_string())
             .and_then(|kel| {
                 if kel.is_none_or(|v| v.is_empty()) {
                     Err("No KEL found for the identifier".to_string())
                 } else {
-                    Ok(Identifier::new(id.clone(), self.kel.storage.clone()))
+                    Ok(Identifier::new(id.clone(), self.kel.storage.clone())
+                        .with_processor(self.kel.processor.clone()))
                 }
             })
     }
@@ -117,7 +547,8 @@ impl<
     pub fn process_kel(&self, messages: &[Message]) -> Result<(), String> {
         messages.iter().try_for_each(|msg| match msg {
             Message::Notice(notice) => self
-                .kel.processor
+                .kel
+                .processor
                 .process_notice(notice)
                 .map_err(|e| e.to_string()),
             Message::Op(_) => {
@@ -174,7 +605,8 @@ impl<
             IndexedSignature::new_both_same(sig.clone(), own_index as u16);
 
         let signed_message = event.sign(vec![signature], None, None);
-        self.kel.processor
+        self.kel
+            .processor
             .process_notice(&Notice::Event(signed_message))
             .map_err(|_e| ())?;
 
@@ -182,6 +614,28 @@ impl<
     }
 }
 
+pub(crate) fn serialize_event(
+    event: &KeriEvent<KeyEvent>,
+) -> Result<String, String> {
+    String::from_utf8(
+        event
+            .encode()
+            .map_err(|_| "Event encoding error".to_string())?,
+    )
+    .map_err(|_| "Event format error".to_string())
+}
+
+pub(crate) fn parse_key_event(
+    event: &[u8],
+) -> Result<KeriEvent<KeyEvent>, String> {
+    match parse_event_type(event)
+        .map_err(|_| "Event parsing error".to_string())?
+    {
+        EventType::KeyEvent(ke) => Ok(ke),
+        _ => Err("Event is not a key event".to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use keri_core::database::redb::RedbDatabase;
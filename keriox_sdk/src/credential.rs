@@ -0,0 +1,1034 @@
+use keri_core::{
+    actor::{event_generator, prelude::HashFunctionCode},
+    database::{EscrowCreator, EventDatabase},
+    event::sections::seal::{EventSeal, Seal},
+    event_message::signed_event_message::{Message, Notice},
+    prefix::{CesrPrimitive, IdentifierPrefix, SelfSigningPrefix},
+};
+use said::{derivation::HashFunction, SelfAddressingIdentifier};
+use teliox::{
+    database::TelEventDatabase,
+    event::{manager_event::Config, verifiable_event::VerifiableEvent, Event},
+    query::TelStateNotice,
+    seal::AttachedSourceSeal,
+    state::vc_state::TelState,
+};
+
+use crate::{
+    query::{QuerySigner, QueryTransport},
+    witness::WitnessPublisher,
+    Controller, Identifier, KEL_QUERY_PAGE_SIZE,
+};
+
+/// Status of credential in its registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialStatus {
+    Issued,
+    Revoked,
+    /// Neither issuance nor revocation of credential in the registry is
+    /// known.
+    Unknown,
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Generates inception event of registry managed by `identifier`,
+    /// along with interaction event anchoring it. Registry has no backers,
+    /// so its events are secured by identifier's KEL only. Returns
+    /// serialized ixn, which should be signed by identifier, and vcp.
+    pub fn incept_registry(
+        &self,
+        identifier: &Identifier<D>,
+    ) -> Result<(String, String), String> {
+        let vcp = self
+            .tel
+            .make_inception_event(
+                identifier.id.clone(),
+                vec![Config::NoBackers],
+                0,
+                vec![],
+            )
+            .map_err(|e| e.to_string())?;
+        self.anchor_tel_event(identifier, &vcp)
+    }
+
+    /// Processes signed ixn and vcp generated by `incept_registry` and
+    /// publishes them to identifier's witnesses. Returns registry
+    /// identifier.
+    pub fn finalize_incept_registry(
+        &self,
+        identifier: &Identifier<D>,
+        ixn: &[u8],
+        sig: SelfSigningPrefix,
+        vcp: &[u8],
+        publisher: &dyn WitnessPublisher,
+    ) -> Result<IdentifierPrefix, String> {
+        let vcp =
+            self.finalize_tel_anchor(identifier, ixn, sig, vcp, publisher)?;
+        Ok(vcp.event.get_prefix())
+    }
+
+    /// Generates issuance event of credential `payload` in `registry`,
+    /// along with interaction event anchoring it. Credential is identified
+    /// by digest of the payload. Returns serialized ixn, which should be
+    /// signed by identifier, and issuance event.
+    pub fn issue_credential(
+        &self,
+        identifier: &Identifier<D>,
+        registry: &IdentifierPrefix,
+        payload: &[u8],
+    ) -> Result<(String, String), String> {
+        let said =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(payload);
+        self.issue(identifier, registry, said)
+    }
+
+    /// Generates issuance event of credential identified by `said`, along
+    /// with interaction event anchoring it.
+    pub(crate) fn issue(
+        &self,
+        identifier: &Identifier<D>,
+        registry: &IdentifierPrefix,
+        said: SelfAddressingIdentifier,
+    ) -> Result<(String, String), String> {
+        let registry_state = self
+            .tel
+            .get_management_tel_state(registry)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Unknown registry {}", registry))?;
+        if registry_state.issuer != identifier.id {
+            return Err("Registry isn't managed by identifier".to_string());
+        }
+        let iss = self
+            .tel
+            .make_issuance_event(registry, said)
+            .map_err(|e| e.to_string())?;
+        self.anchor_tel_event(identifier, &iss)
+    }
+
+    /// Processes signed ixn and issuance event generated by
+    /// `issue_credential` and publishes them to identifier's witnesses.
+    /// Returns SAID of issued credential.
+    pub fn finalize_issue_credential(
+        &self,
+        identifier: &Identifier<D>,
+        ixn: &[u8],
+        sig: SelfSigningPrefix,
+        iss: &[u8],
+        publisher: &dyn WitnessPublisher,
+    ) -> Result<SelfAddressingIdentifier, String> {
+        let iss =
+            self.finalize_tel_anchor(identifier, ixn, sig, iss, publisher)?;
+        match iss.event {
+            Event::Vc(vc) => match vc.data.data.prefix {
+                IdentifierPrefix::SelfAddressing(said) => Ok(said.into()),
+                _ => Err("Improper credential identifier".to_string()),
+            },
+            Event::Management(_) => {
+                Err("Event is not a credential issuance".to_string())
+            }
+        }
+    }
+
+    /// Generates issuance events of many credentials in `registry`, along
+    /// with single interaction event anchoring all of them. Returns
+    /// serialized ixn, which should be signed by identifier, and issuance
+    /// events, in order of `saids`.
+    pub fn issue_credentials(
+        &self,
+        identifier: &Identifier<D>,
+        registry: &IdentifierPrefix,
+        saids: Vec<SelfAddressingIdentifier>,
+    ) -> Result<(String, Vec<String>), String> {
+        let registry_state = self
+            .tel
+            .get_management_tel_state(registry)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Unknown registry {}", registry))?;
+        if registry_state.issuer != identifier.id {
+            return Err("Registry isn't managed by identifier".to_string());
+        }
+        let events = saids
+            .into_iter()
+            .map(|said| {
+                self.tel
+                    .make_issuance_event(registry, said)
+                    .map_err(|e| e.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.anchor_tel_events(identifier, &events)
+    }
+
+    /// Processes signed ixn and issuance events generated by
+    /// `issue_credentials`. Issuance events are accepted together, in one
+    /// database transaction, then published to identifier's witnesses.
+    /// Returns SAIDs of issued credentials.
+    pub fn finalize_issue_credentials(
+        &self,
+        identifier: &Identifier<D>,
+        ixn: &[u8],
+        sig: SelfSigningPrefix,
+        issuances: &[String],
+        publisher: &dyn WitnessPublisher,
+    ) -> Result<Vec<SelfAddressingIdentifier>, String> {
+        let events = issuances
+            .iter()
+            .map(|iss| {
+                serde_json::from_str::<Event>(iss).map_err(|e| e.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let saids = events
+            .iter()
+            .map(|event| match event {
+                Event::Vc(vc) => match &vc.data.data.prefix {
+                    IdentifierPrefix::SelfAddressing(said) => {
+                        Ok(said.clone().into())
+                    }
+                    _ => Err("Improper credential identifier".to_string()),
+                },
+                Event::Management(_) => {
+                    Err("Event is not a credential issuance".to_string())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let signed_ixn = identifier.finalize_anchor(ixn, sig)?;
+        let seal = AttachedSourceSeal::new(
+            signed_ixn.event_message.data.get_sn(),
+            signed_ixn
+                .event_message
+                .digest()
+                .map_err(|e| e.to_string())?,
+        );
+        let verifiable = events
+            .into_iter()
+            .map(|event| VerifiableEvent::new(event, seal.clone()))
+            .collect::<Vec<_>>();
+        self.tel
+            .processor
+            .process_batch(verifiable.clone())
+            .map_err(|e| e.to_string())?;
+
+        let kel_stream = Message::Notice(Notice::Event(signed_ixn))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+        let tel_stream = verifiable
+            .iter()
+            .map(|event| event.serialize().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        self.publish_tel_anchor(
+            identifier,
+            &kel_stream,
+            &tel_stream,
+            publisher,
+        );
+        Ok(saids)
+    }
+
+    /// Generates revocation event of credential `said` issued in
+    /// `registry`, along with interaction event anchoring it. Returns
+    /// serialized ixn, which should be signed by identifier, and
+    /// revocation event.
+    pub fn revoke_credential(
+        &self,
+        identifier: &Identifier<D>,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<(String, String), String> {
+        let registry_state = self
+            .tel
+            .get_management_tel_state(registry)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Unknown registry {}", registry))?;
+        if registry_state.issuer != identifier.id {
+            return Err("Registry isn't managed by identifier".to_string());
+        }
+        if self.credential_status(registry, said)? != CredentialStatus::Issued {
+            return Err(format!("Credential {} isn't issued", said));
+        }
+        let rev = self
+            .tel
+            .make_revoke_event(registry, said)
+            .map_err(|e| e.to_string())?;
+        self.anchor_tel_event(identifier, &rev)
+    }
+
+    /// Processes signed ixn and revocation event generated by
+    /// `revoke_credential` and publishes them to identifier's witnesses.
+    pub fn finalize_revoke_credential(
+        &self,
+        identifier: &Identifier<D>,
+        ixn: &[u8],
+        sig: SelfSigningPrefix,
+        rev: &[u8],
+        publisher: &dyn WitnessPublisher,
+    ) -> Result<(), String> {
+        self.finalize_tel_anchor(identifier, ixn, sig, rev, publisher)?;
+        Ok(())
+    }
+
+    /// Returns status of credential `said` in `registry`, according to
+    /// locally known TEL. Status is taken from cache if controller has
+    /// one, see `Controller::with_status_cache`.
+    pub fn credential_status(
+        &self,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<CredentialStatus, String> {
+        let Some(cache) = self.status_cache() else {
+            return self.tel_credential_status(registry, said);
+        };
+        if let Some(status) = cache.get(registry, said) {
+            return Ok(status);
+        }
+        let status = self.tel_credential_status(registry, said)?;
+        cache.insert(registry, said, status);
+        Ok(status)
+    }
+
+    /// Computes status of credential from its TEL.
+    fn tel_credential_status(
+        &self,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<CredentialStatus, String> {
+        let events = self
+            .tel
+            .processor
+            .tel_reference
+            .get_events(&IdentifierPrefix::self_addressing(said.clone()))
+            .map_err(|e| e.to_string())?;
+        let issuance = match events.first() {
+            Some(issuance) => issuance,
+            None => return Ok(CredentialStatus::Unknown),
+        };
+        let registry_id = issuance
+            .event
+            .get_registry_id()
+            .map_err(|e| e.to_string())?;
+        if &registry_id != registry {
+            return Ok(CredentialStatus::Unknown);
+        }
+        Ok(match self.get_vc_state(said)? {
+            Some(TelState::Issued(_)) => CredentialStatus::Issued,
+            Some(TelState::Revoked) => CredentialStatus::Revoked,
+            Some(TelState::NotIssued) | None => CredentialStatus::Unknown,
+        })
+    }
+
+    /// Returns status of credential `said` in `registry`. If its TEL isn't
+    /// known locally, `identifier` queries witnesses of `issuer` for
+    /// issuer's KEL and credential's TEL, until one of them knows the
+    /// credential. Issuer's KEL has to be known, e.g. from its OOBI, so
+    /// that its witnesses can be found. Every query is signed with
+    /// `signer`.
+    pub fn query_credential_status(
+        &self,
+        identifier: &Identifier<D>,
+        issuer: &IdentifierPrefix,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<CredentialStatus, String> {
+        let status = self.credential_status(registry, said)?;
+        if status != CredentialStatus::Unknown {
+            return Ok(status);
+        }
+        let state = self
+            .get_state(issuer)
+            .ok_or(format!("Unknown issuer {}", issuer))?;
+        for witness in state.witness_config.witnesses {
+            let witness = IdentifierPrefix::Basic(witness);
+            // TEL events are accepted only if KEL events anchoring them
+            // are known.
+            let from_sn = self
+                .get_state(issuer)
+                .map(|state| state.sn + 1)
+                .unwrap_or_default();
+            let tel = identifier
+                .query_kel(
+                    issuer,
+                    from_sn,
+                    &witness,
+                    KEL_QUERY_PAGE_SIZE,
+                    signer,
+                    transport,
+                )
+                .and_then(|_| {
+                    self.query_tel(
+                        identifier, registry, said, &witness, signer, transport,
+                    )
+                });
+            if let Err(e) = tel {
+                log::warn!("Failed to query TEL from {}: {}", witness, e);
+                continue;
+            }
+            let status = self.credential_status(registry, said)?;
+            if status != CredentialStatus::Unknown {
+                return Ok(status);
+            }
+        }
+        Ok(CredentialStatus::Unknown)
+    }
+
+    /// Queries `recipient` witness or watcher for TEL of credential `said`
+    /// issued in `registry` and processes TEL it responds with. TEL events
+    /// are accepted only if KEL events anchoring them are known. Query is
+    /// signed with `signer`.
+    pub fn query_tel(
+        &self,
+        identifier: &Identifier<D>,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+        recipient: &IdentifierPrefix,
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<(), String> {
+        let query = identifier.get_tel_query(
+            registry.clone(),
+            IdentifierPrefix::self_addressing(said.clone()),
+        )?;
+        let sig = signer.sign(&query.encode().map_err(|e| e.to_string())?)?;
+        let tel =
+            identifier.finalize_tel_query(&query, sig, recipient, transport)?;
+        self.process_tel(&tel)
+    }
+
+    /// Queries `recipient` for TEL state notice of credential `said` issued
+    /// in `registry`. Notice isn't verifiable by itself, but shows whether
+    /// locally known TEL is behind, so it can be updated with `query_tel`.
+    pub fn query_tel_state(
+        &self,
+        identifier: &Identifier<D>,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+        recipient: &IdentifierPrefix,
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<TelStateNotice, String> {
+        let query = identifier.get_tel_state_query(
+            registry.clone(),
+            IdentifierPrefix::self_addressing(said.clone()),
+        )?;
+        let sig = signer.sign(&query.encode().map_err(|e| e.to_string())?)?;
+        let response =
+            identifier.finalize_tel_query(&query, sig, recipient, transport)?;
+        serde_json::from_slice(&response).map_err(|e| e.to_string())
+    }
+
+    /// Returns complete TEL of `registry`, i.e. its events followed by
+    /// events of all credentials issued in it, as CESR stream. It can be
+    /// processed by other controllers with `import_tel`.
+    pub fn export_tel(
+        &self,
+        registry: &IdentifierPrefix,
+    ) -> Result<Vec<u8>, String> {
+        self.tel.export_tel(registry).map_err(|e| e.to_string())
+    }
+
+    /// Processes TEL exported with `export_tel`. KEL of registry issuer
+    /// has to be known, so that anchors of TEL events can be verified.
+    pub fn import_tel(&self, stream: &[u8]) -> Result<(), String> {
+        self.tel.import_tel(stream).map_err(|e| e.to_string())
+    }
+
+    /// Generates interaction event anchoring seal of TEL event.
+    fn anchor_tel_event(
+        &self,
+        identifier: &Identifier<D>,
+        event: &Event,
+    ) -> Result<(String, String), String> {
+        let (ixn, mut events) =
+            self.anchor_tel_events(identifier, std::slice::from_ref(event))?;
+        Ok((ixn, events.remove(0)))
+    }
+
+    /// Generates interaction event anchoring seals of all TEL events.
+    fn anchor_tel_events(
+        &self,
+        identifier: &Identifier<D>,
+        events: &[Event],
+    ) -> Result<(String, Vec<String>), String> {
+        let state = self
+            .get_state(&identifier.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let seals = events
+            .iter()
+            .map(|event| {
+                Ok(Seal::Event(EventSeal::new(
+                    event.get_prefix(),
+                    event.get_sn(),
+                    event.get_digest().map_err(|e| e.to_string())?,
+                )))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let ixn = event_generator::anchor_with_seal(state, &seals)
+            .map_err(|e| e.to_string())?;
+        let ixn = String::from_utf8(
+            ixn.encode()
+                .map_err(|_| "Event encoding error".to_string())?,
+        )
+        .map_err(|_| "Event format error".to_string())?;
+        let events = events
+            .iter()
+            .map(|event| {
+                String::from_utf8(
+                    event
+                        .serialize()
+                        .map_err(|_| "Event encoding error".to_string())?,
+                )
+                .map_err(|_| "Event format error".to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((ixn, events))
+    }
+
+    /// Processes anchoring interaction event and TEL event it anchors,
+    /// then publishes both to identifier's witnesses. Unreachable
+    /// witnesses are skipped. If anchoring event waits for witness
+    /// receipts, TEL event isn't accepted locally until it is processed
+    /// again with `process_tel`.
+    fn finalize_tel_anchor(
+        &self,
+        identifier: &Identifier<D>,
+        ixn: &[u8],
+        sig: SelfSigningPrefix,
+        event: &[u8],
+        publisher: &dyn WitnessPublisher,
+    ) -> Result<VerifiableEvent, String> {
+        let event: Event =
+            serde_json::from_slice(event).map_err(|e| e.to_string())?;
+        let signed_ixn = identifier.finalize_anchor(ixn, sig)?;
+        let verifiable = VerifiableEvent::new(
+            event,
+            AttachedSourceSeal::new(
+                signed_ixn.event_message.data.get_sn(),
+                signed_ixn
+                    .event_message
+                    .digest()
+                    .map_err(|e| e.to_string())?,
+            ),
+        );
+        self.tel
+            .processor
+            .process(verifiable.clone())
+            .map_err(|e| e.to_string())?;
+
+        let kel_stream = Message::Notice(Notice::Event(signed_ixn))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+        let tel_stream = verifiable.serialize().map_err(|e| e.to_string())?;
+        self.publish_tel_anchor(
+            identifier,
+            &kel_stream,
+            &tel_stream,
+            publisher,
+        );
+        Ok(verifiable)
+    }
+
+    /// Publishes anchoring event and TEL events to identifier's
+    /// witnesses. Unreachable witnesses are skipped.
+    fn publish_tel_anchor(
+        &self,
+        identifier: &Identifier<D>,
+        kel_stream: &[u8],
+        tel_stream: &[u8],
+        publisher: &dyn WitnessPublisher,
+    ) {
+        let witnesses = self
+            .get_state(&identifier.id)
+            .map(|state| state.witness_config.witnesses)
+            .unwrap_or_default();
+        for witness in &witnesses {
+            if let Err(e) = publisher
+                .publish(witness, kel_stream)
+                .and_then(|_| publisher.publish_tel(witness, tel_stream))
+            {
+                log::warn!(
+                    "Failed to publish TEL event of {} to {}: {}",
+                    identifier.id,
+                    witness.to_str(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        actor::{parse_event_stream, parse_query_stream},
+        event::sections::threshold::SignatureThreshold,
+        prefix::BasicPrefix,
+        query::query_event::{QueryRoute, SignedQueryMessage},
+        signer::Signer,
+    };
+    use teliox::event::parse_tel_query_stream;
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_issue_credential() {
+        let (_root, controller) = setup("test-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+
+        let other_signer = Signer::new();
+
+        // Anchor signed with wrong key is rejected with registry inception.
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        assert!(controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&other_signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .is_err());
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 0);
+
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(
+            controller
+                .tel
+                .get_management_tel_state(&registry)
+                .unwrap()
+                .unwrap()
+                .issuer,
+            identifier.id
+        );
+
+        let payload = br#"{"name":"John Doe"}"#;
+        let (ixn, iss) = controller
+            .issue_credential(&identifier, &registry, payload)
+            .unwrap();
+        let expected_said =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(payload);
+        assert!(controller
+            .finalize_issue_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&other_signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .is_err());
+        assert!(controller.get_vc_state(&expected_said).unwrap().is_none());
+
+        let said = controller
+            .finalize_issue_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(said, expected_said);
+        assert!(matches!(
+            controller.get_vc_state(&said).unwrap(),
+            Some(TelState::Issued(_))
+        ));
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 2);
+
+        // Credentials can be issued and revoked only in registries managed
+        // by identifier.
+        let other = incept(&controller, &other_signer);
+        assert!(controller
+            .issue_credential(&other, &registry, payload)
+            .is_err());
+        assert!(controller
+            .issue_credential(&identifier, &other.id, payload)
+            .is_err());
+        assert!(controller
+            .revoke_credential(&other, &registry, &said)
+            .is_err());
+        assert!(controller
+            .revoke_credential(&identifier, &other.id, &said)
+            .is_err());
+    }
+
+    #[test]
+    fn test_issue_credentials() {
+        let (_root, controller) = setup("test-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+
+        let saids = (0..10)
+            .map(|i| {
+                HashFunction::from(HashFunctionCode::Blake3_256)
+                    .derive(format!(r#"{{"number":{}}}"#, i).as_bytes())
+            })
+            .collect::<Vec<_>>();
+        let (ixn, issuances) = controller
+            .issue_credentials(&identifier, &registry, saids.clone())
+            .unwrap();
+        assert_eq!(issuances.len(), 10);
+
+        // Batch with the same credential twice is rejected as a whole.
+        let duplicated = [issuances.clone(), issuances[..1].to_vec()].concat();
+        assert!(controller
+            .finalize_issue_credentials(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                &duplicated,
+                &publisher,
+            )
+            .is_err());
+        assert!(controller.get_vc_state(&saids[0]).unwrap().is_none());
+
+        let issued = controller
+            .finalize_issue_credentials(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                &issuances,
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(issued, saids);
+        for said in &saids {
+            assert_eq!(
+                controller.credential_status(&registry, said).unwrap(),
+                CredentialStatus::Issued
+            );
+        }
+        // All issuances are anchored in single interaction event.
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 2);
+    }
+
+    #[test]
+    fn test_revoke_credential() {
+        let (_issuer_root, issuer_controller) = setup("issuer-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let (signer, next_signer) = (Signer::new(), Signer::new());
+        let icp = issuer_controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(next_signer.public_key())],
+            )
+            .unwrap();
+        let issuer = issuer_controller
+            .finalize_incept(icp.as_bytes(), &sign(&signer, icp.as_bytes()))
+            .unwrap();
+
+        // Add witness, whose receipts aren't required.
+        let witness = BasicPrefix::Ed25519NT(Signer::new().public_key());
+        let config = issuer
+            .witness_rotation(std::slice::from_ref(&witness), &[], Some(0))
+            .unwrap();
+        let next_key = BasicPrefix::Ed25519(next_signer.public_key());
+        let rot = issuer
+            .rotate_witnesses(
+                vec![next_key.clone()],
+                SignatureThreshold::Simple(1),
+                &[BasicPrefix::Ed25519(Signer::new().public_key())],
+                SignatureThreshold::Simple(1),
+                config,
+            )
+            .unwrap();
+        let signature = issuer
+            .rotation_signature(
+                std::slice::from_ref(&next_key),
+                &next_key,
+                sign(&next_signer, rot.as_bytes()),
+            )
+            .unwrap();
+        issuer_controller
+            .finalize_rotate(rot.as_bytes(), vec![signature])
+            .unwrap();
+        let issuer_kel: Vec<Message> = issuer_controller
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(&issuer.id)
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(Message::Notice)
+            .collect();
+
+        let (ixn, vcp) = issuer_controller.incept_registry(&issuer).unwrap();
+        let registry = issuer_controller
+            .finalize_incept_registry(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&next_signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let payload = br#"{"name":"John Doe"}"#;
+        let (ixn, iss) = issuer_controller
+            .issue_credential(&issuer, &registry, payload)
+            .unwrap();
+        let said = issuer_controller
+            .finalize_issue_credential(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&next_signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(
+            issuer_controller
+                .credential_status(&registry, &said)
+                .unwrap(),
+            CredentialStatus::Issued
+        );
+
+        let (ixn, rev) = issuer_controller
+            .revoke_credential(&issuer, &registry, &said)
+            .unwrap();
+        issuer_controller
+            .finalize_revoke_credential(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&next_signer, ixn.as_bytes()),
+                rev.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(
+            issuer_controller
+                .credential_status(&registry, &said)
+                .unwrap(),
+            CredentialStatus::Revoked
+        );
+        assert!(issuer_controller
+            .revoke_credential(&issuer, &registry, &said)
+            .is_err());
+        let unknown =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"other");
+        assert_eq!(
+            issuer_controller
+                .credential_status(&registry, &unknown)
+                .unwrap(),
+            CredentialStatus::Unknown
+        );
+        assert_eq!(
+            issuer_controller
+                .credential_status(&issuer.id, &said)
+                .unwrap(),
+            CredentialStatus::Unknown
+        );
+
+        // Verifier knows issuer's KEL only up to witness rotation, so TEL
+        // and anchoring events are fetched from the witness.
+        let issuer_storage = issuer_controller.kel.storage.clone();
+        let issuer_tel = issuer_controller.tel.processor.tel_reference.clone();
+        let transport = move |_recipient: &IdentifierPrefix,
+                              query: &[u8]|
+              -> Result<Vec<u8>, String> {
+            if let Ok(mut queries) = parse_query_stream(query) {
+                // Only KEL queries exist without `mailbox` feature.
+                #[allow(irrefutable_let_patterns)]
+                let SignedQueryMessage::KelQuery(qry) = queries.remove(0)
+                else {
+                    return Err("Not a KEL query".to_string());
+                };
+                let QueryRoute::Logs { args, .. } = qry.query.get_route()
+                else {
+                    return Err("Not a logs query".to_string());
+                };
+                return Ok(issuer_storage
+                    .get_kel_messages_with_receipts_range(
+                        &args.i,
+                        args.s.unwrap_or_default(),
+                        args.limit.unwrap_or(KEL_QUERY_PAGE_SIZE),
+                    )
+                    .unwrap()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .flat_map(|notice| {
+                        Message::Notice(notice).to_cesr().unwrap()
+                    })
+                    .collect());
+            }
+            let qry = parse_tel_query_stream(query).unwrap().remove(0);
+            issuer_tel
+                .process_query(&qry.query.data.data)
+                .and_then(|reply| reply.encode())
+                .map_err(|e| e.to_string())
+        };
+        let (_root, controller) = setup("verifier-db");
+        let verifier_signer = Signer::new();
+        let verifier = incept(&controller, &verifier_signer);
+        let query_signer = |query: &[u8]| -> Result<SelfSigningPrefix, String> {
+            Ok(sign(&verifier_signer, query))
+        };
+        assert!(controller
+            .query_credential_status(
+                &verifier,
+                &issuer.id,
+                &registry,
+                &said,
+                &query_signer,
+                &transport,
+            )
+            .is_err());
+
+        controller.process_kel(&issuer_kel).unwrap();
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Unknown
+        );
+        assert_eq!(
+            controller
+                .query_credential_status(
+                    &verifier,
+                    &issuer.id,
+                    &registry,
+                    &said,
+                    &query_signer,
+                    &transport,
+                )
+                .unwrap(),
+            CredentialStatus::Revoked
+        );
+        assert_eq!(controller.get_state(&issuer.id).unwrap().sn, 4);
+
+        let witness = IdentifierPrefix::Basic(witness);
+        let notice = controller
+            .query_tel_state(
+                &verifier,
+                &registry,
+                &said,
+                &witness,
+                &query_signer,
+                &transport,
+            )
+            .unwrap();
+        assert_eq!(notice.registry_id, registry);
+        assert_eq!(notice.sn, 0);
+        assert_eq!(notice.vc_state, Some(TelState::Revoked));
+        controller
+            .query_tel(
+                &verifier,
+                &registry,
+                &said,
+                &witness,
+                &query_signer,
+                &transport,
+            )
+            .unwrap();
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Revoked
+        );
+    }
+
+    #[test]
+    fn test_export_tel() {
+        let (_root, controller) = setup("test-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let saids = [b"first".as_slice(), b"second".as_slice()]
+            .into_iter()
+            .map(|payload| {
+                let (ixn, iss) = controller
+                    .issue_credential(&identifier, &registry, payload)
+                    .unwrap();
+                controller
+                    .finalize_issue_credential(
+                        &identifier,
+                        ixn.as_bytes(),
+                        sign(&signer, ixn.as_bytes()),
+                        iss.as_bytes(),
+                        &publisher,
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let (ixn, rev) = controller
+            .revoke_credential(&identifier, &registry, &saids[1])
+            .unwrap();
+        controller
+            .finalize_revoke_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                rev.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let tel = controller.export_tel(&registry).unwrap();
+        assert!(controller.export_tel(&identifier.id).is_err());
+
+        // TEL events aren't accepted until issuer's KEL is known.
+        let (_verifier_root, verifier) = setup("verifier-db");
+        verifier.import_tel(&tel).unwrap();
+        assert_eq!(
+            verifier.credential_status(&registry, &saids[0]).unwrap(),
+            CredentialStatus::Unknown
+        );
+        let kel = parse_event_stream(
+            &controller
+                .kel
+                .storage
+                .get_kel(&identifier.id)
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        verifier.process_kel(&kel).unwrap();
+        verifier.import_tel(&tel).unwrap();
+        assert_eq!(
+            verifier.credential_status(&registry, &saids[0]).unwrap(),
+            CredentialStatus::Issued
+        );
+        assert_eq!(
+            verifier.credential_status(&registry, &saids[1]).unwrap(),
+            CredentialStatus::Revoked
+        );
+        verifier.tel.verify_tel(&registry).unwrap();
+    }
+}
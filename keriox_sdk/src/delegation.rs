@@ -0,0 +1,713 @@
+#[cfg(feature = "oobi-manager")]
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "oobi-manager")]
+use keri_core::{
+    actor::prelude::EventStorage,
+    error::Error,
+    processor::notification::{Notification, NotificationBus, Notifier},
+};
+use keri_core::{
+    actor::{event_generator, parse_event_stream},
+    database::{EscrowCreator, EscrowDatabase, EventDatabase},
+    event::{
+        event_data::EventData,
+        sections::seal::{EventSeal, Seal, SourceSeal},
+        KeyEvent,
+    },
+    event_message::{
+        msg::KeriEvent,
+        signed_event_message::{Message, Notice, SignedEventMessage},
+    },
+    prefix::{
+        BasicPrefix, IdentifierPrefix, IndexedSignature, SelfSigningPrefix,
+    },
+    processor::Processor,
+};
+#[cfg(feature = "mailbox")]
+use keri_core::{
+    event_message::{signature::Signature, signed_event_message::Op},
+    mailbox::exchange::{ExchangeRoute, ForwardTopic},
+};
+use teliox::database::TelEventDatabase;
+
+#[cfg(feature = "oobi-manager")]
+use crate::oobi::KelResolver;
+use crate::{
+    controller::{parse_key_event, serialize_event},
+    Controller, Identifier,
+};
+
+/// How often `Controller::wait_for_delegation` asks for delegator's KEL.
+#[cfg(feature = "oobi-manager")]
+const DELEGATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Observes `MissingDelegatingEvent` notifications and requests KEL of
+/// delegator from resolver. Once the anchoring event is processed,
+/// delegation escrow re-submits the escrowed delegated event.
+#[cfg(feature = "oobi-manager")]
+pub struct DelegationObserver<D: EventDatabase + EscrowCreator + 'static> {
+    storage: Arc<EventStorage<D>>,
+    resolver: Arc<KelResolver<D>>,
+}
+
+#[cfg(feature = "oobi-manager")]
+impl<D: EventDatabase + EscrowCreator + Send + Sync + 'static>
+    DelegationObserver<D>
+{
+    pub fn new(
+        storage: Arc<EventStorage<D>>,
+        resolver: Arc<KelResolver<D>>,
+    ) -> Self {
+        Self { storage, resolver }
+    }
+
+    fn delegator(
+        &self,
+        event: &SignedEventMessage,
+    ) -> Option<IdentifierPrefix> {
+        match &event.event_message.data.event_data {
+            EventData::Dip(dip) => Some(dip.delegator.clone()),
+            EventData::Drt(_) => self
+                .storage
+                .get_state(&event.event_message.data.get_prefix())
+                .and_then(|state| state.delegator),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "oobi-manager")]
+impl<D: EventDatabase + EscrowCreator + Send + Sync + 'static> Notifier
+    for DelegationObserver<D>
+{
+    fn notify(
+        &self,
+        notification: &Notification,
+        _bus: &NotificationBus,
+    ) -> Result<(), Error> {
+        if let Notification::MissingDelegatingEvent(event) = notification {
+            if let Some(delegator) = self.delegator(event) {
+                self.resolver.request(&delegator);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns seal of delegated event, which delegator anchors in its KEL to
+/// approve the delegation.
+pub fn delegating_seal(event: &[u8]) -> Result<Seal, String> {
+    let event = parse_key_event(event)?;
+    let digest = event.digest().map_err(|e| e.to_string())?;
+    Ok(Seal::Event(EventSeal::new(
+        event.data.get_prefix(),
+        event.data.get_sn(),
+        digest,
+    )))
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Generates delegated inception event. Identifier is established once
+    /// `delegator` anchors it in its KEL.
+    pub fn incept_delegated(
+        &self,
+        public_keys: Vec<BasicPrefix>,
+        next_pub_keys: Vec<BasicPrefix>,
+        delegator: &IdentifierPrefix,
+    ) -> Result<String, String> {
+        event_generator::incept(
+            public_keys,
+            next_pub_keys,
+            vec![],
+            0,
+            Some(delegator),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// Signs and processes delegated inception event. Until the delegator
+    /// approves it, the event waits in delegation escrow. Returns CESR
+    /// stream of signed event, which should be sent to the delegator as
+    /// approval request.
+    pub fn finalize_incept_delegated(
+        &self,
+        event: &[u8],
+        sig: &SelfSigningPrefix,
+    ) -> Result<(Identifier<D>, Vec<u8>), String> {
+        let dip = parse_key_event(event)?;
+        if !matches!(dip.data.get_event_data(), EventData::Dip(_)) {
+            return Err("Event is not a delegated inception".to_string());
+        }
+        #[cfg(feature = "storage-redb")]
+        self.track_pending(&dip.data.get_prefix())?;
+        let request = self.submit_delegated(
+            &dip,
+            vec![IndexedSignature::new_both_same(sig.clone(), 0)],
+        )?;
+        Ok((
+            Identifier::new(dip.data.get_prefix(), self.kel.storage.clone())
+                .with_processor(self.kel.processor.clone()),
+            request,
+        ))
+    }
+
+    /// Processes delegated rotation event signed with provided indexed
+    /// signatures. Returns CESR stream of signed event, which should be
+    /// sent to the delegator as approval request.
+    pub fn finalize_delegated_rotate(
+        &self,
+        event: &[u8],
+        signatures: Vec<IndexedSignature>,
+    ) -> Result<Vec<u8>, String> {
+        let drt = parse_key_event(event)?;
+        if !matches!(drt.data.get_event_data(), EventData::Drt(_)) {
+            return Err("Event is not a delegated rotation".to_string());
+        }
+        self.submit_delegated(&drt, signatures)
+    }
+
+    /// Checks whether delegated event was approved, i.e. delegator's KEL
+    /// anchoring it is known and the event is accepted.
+    pub fn is_delegation_approved(&self, event: &[u8]) -> Result<bool, String> {
+        let event = parse_key_event(event)?;
+        self.complete_delegated(&event)
+    }
+
+    /// Periodically fetches delegator's KEL with `resolver` until it
+    /// anchors delegated event, or `timeout` passes.
+    #[cfg(feature = "oobi-manager")]
+    pub fn wait_for_delegation(
+        &self,
+        event: &[u8],
+        resolver: &KelResolver<D>,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let event = parse_key_event(event)?;
+        let delegator = self.delegator_of(&event)?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            resolver.resolve(&delegator)?;
+            if self.complete_delegated(&event)? {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err("Delegation not approved in time".to_string());
+            }
+            std::thread::sleep(DELEGATION_POLL_INTERVAL);
+        }
+    }
+
+    /// Processes delegation requests received by `delegator`, either as
+    /// signed delegated events or as exchange messages forwarding them, e.g.
+    /// from mailbox. Events wait in delegation escrow until approved.
+    /// Returns requested events, so they can be reviewed.
+    pub fn process_delegation_request(
+        &self,
+        delegator: &Identifier<D>,
+        stream: &[u8],
+    ) -> Result<Vec<String>, String> {
+        let messages = parse_event_stream(stream).map_err(|e| e.to_string())?;
+        let mut requested = vec![];
+        for message in messages {
+            let signed_event = match message {
+                Message::Notice(Notice::Event(event)) => event,
+                #[cfg(feature = "mailbox")]
+                Message::Op(Op::Exchange(exn)) => {
+                    // Exchange signature can't be verified before delegate
+                    // is established. Forwarded event carries its own
+                    // signatures, which are verified by processor.
+                    let exchange = &exn.exchange_message.data.data;
+                    if !matches!(
+                        exchange.route(),
+                        ExchangeRoute::Forward(ForwardTopic::Delegate)
+                            | ExchangeRoute::DelegationRequest
+                    ) {
+                        return Err(
+                            "Exchange is not a delegation request".to_string()
+                        );
+                    }
+                    let signatures = exn
+                        .data_signature
+                        .1
+                        .into_iter()
+                        .filter_map(|sig| match sig {
+                            Signature::Transferable(_, sigs) => Some(sigs),
+                            Signature::NonTransferable(_) => None,
+                        })
+                        .flatten()
+                        .collect();
+                    exchange
+                        .event()
+                        .ok_or("Exchange carries no event".to_string())?
+                        .sign(signatures, None, None)
+                }
+                _ => {
+                    return Err(
+                        "Message is not a delegation request".to_string()
+                    )
+                }
+            };
+            if self.requested_delegator(&signed_event.event_message)?
+                != Some(delegator.id.clone())
+            {
+                return Err("Event is not delegated by identifier".to_string());
+            }
+            self.kel
+                .processor
+                .process_notice(&Notice::Event(signed_event.clone()))
+                .map_err(|e| e.to_string())?;
+            requested.push(serialize_event(&signed_event.event_message)?);
+        }
+        Ok(requested)
+    }
+
+    /// Returns delegated events waiting for approval of `delegator`.
+    pub fn pending_delegation_requests(
+        &self,
+        delegator: &IdentifierPrefix,
+    ) -> Result<Vec<String>, String> {
+        let mut pending: Vec<KeriEvent<KeyEvent>> = vec![];
+        for event in self
+            .kel
+            .escrows
+            .delegation
+            .delegation_escrow
+            .get_from_sn(delegator, 0)
+            .map_err(|_| "Escrow error".to_string())?
+        {
+            if !pending.contains(&event.event_message) {
+                pending.push(event.event_message);
+            }
+        }
+        pending.iter().map(serialize_event).collect()
+    }
+
+    /// Generates interaction event of `delegator` that anchors seal of
+    /// reviewed delegated `event`. It should be signed and finalized with
+    /// `finalize_approve_delegation`.
+    pub fn approve_delegation(
+        &self,
+        delegator: &Identifier<D>,
+        event: &[u8],
+    ) -> Result<String, String> {
+        let delegated = parse_key_event(event)?;
+        if self.requested_delegator(&delegated)? != Some(delegator.id.clone()) {
+            return Err("Event is not delegated by identifier".to_string());
+        }
+        let state = self
+            .kel
+            .storage
+            .get_state(&delegator.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let ixn = event_generator::anchor_with_seal(
+            state,
+            &[delegating_seal(event)?],
+        )
+        .map_err(|e| e.to_string())?;
+        serialize_event(&ixn)
+    }
+
+    /// Processes signed delegating interaction event. Escrowed delegated
+    /// event it anchors is accepted. Returns anchoring proof: CESR stream of
+    /// delegator's KEL, which ends with the delegating event. It should be
+    /// sent back to the delegate.
+    pub fn finalize_approve_delegation(
+        &self,
+        delegator: &Identifier<D>,
+        event: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<Vec<u8>, String> {
+        let ixn = parse_key_event(event)?;
+        if !matches!(ixn.data.get_event_data(), EventData::Ixn(_))
+            || ixn.data.get_prefix() != delegator.id
+        {
+            return Err("Event is not delegator's interaction".to_string());
+        }
+        self.kel
+            .processor
+            .process_notice(&Notice::Event(ixn.sign(
+                vec![IndexedSignature::new_both_same(sig, 0)],
+                None,
+                None,
+            )))
+            .map_err(|e| e.to_string())?;
+
+        let kel = self
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(&delegator.id)
+            .map_err(|e| e.to_string())?
+            .ok_or("Identifier not found".to_string())?;
+        let mut proof = vec![];
+        for notice in kel {
+            proof.extend(
+                Message::Notice(notice)
+                    .to_cesr()
+                    .map_err(|e| e.to_string())?,
+            );
+        }
+        Ok(proof)
+    }
+
+    /// Attaches delegator seal to delegated event if its anchor is already
+    /// known and processes it.
+    fn submit_delegated(
+        &self,
+        event: &KeriEvent<KeyEvent>,
+        signatures: Vec<IndexedSignature>,
+    ) -> Result<Vec<u8>, String> {
+        let delegator = self.delegator_of(event)?;
+        let source_seal = self.find_delegating_event(&delegator, event)?;
+        let signed = event.sign(signatures, None, source_seal);
+        self.kel
+            .processor
+            .process_notice(&Notice::Event(signed.clone()))
+            .map_err(|e| e.to_string())?;
+        Message::Notice(Notice::Event(signed))
+            .to_cesr()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Moves delegated event out of delegation escrow once its anchor is
+    /// known. Returns whether the event is accepted.
+    fn complete_delegated(
+        &self,
+        event: &KeriEvent<KeyEvent>,
+    ) -> Result<bool, String> {
+        let id = event.data.get_prefix();
+        let sn = event.data.get_sn();
+        let digest = event.digest().map_err(|e| e.to_string())?;
+        let is_accepted = || {
+            self.kel
+                .storage
+                .get_event_at_sn(&id, sn)
+                .and_then(|ev| {
+                    ev.signed_event_message.event_message.digest().ok()
+                })
+                .as_ref()
+                == Some(&digest)
+        };
+        if is_accepted() {
+            return Ok(true);
+        }
+
+        let delegator = self.delegator_of(event)?;
+        let Some(source_seal) =
+            self.find_delegating_event(&delegator, event)?
+        else {
+            return Ok(false);
+        };
+        let escrowed: Vec<SignedEventMessage> = self
+            .kel
+            .escrows
+            .delegation
+            .delegation_escrow
+            .get_from_sn(&delegator, 0)
+            .map_err(|_| "Escrow error".to_string())?
+            .filter(|ev| &ev.event_message == event)
+            .collect();
+        for escrowed_event in escrowed {
+            self.kel
+                .escrows
+                .delegation
+                .remove(&delegator, &escrowed_event);
+            let signed = SignedEventMessage {
+                delegator_seal: Some(source_seal.clone()),
+                ..escrowed_event
+            };
+            self.kel
+                .processor
+                .process_notice(&Notice::Event(signed))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(is_accepted())
+    }
+
+    /// Searches delegator's KEL for event anchoring delegated `event`.
+    fn find_delegating_event(
+        &self,
+        delegator: &IdentifierPrefix,
+        event: &KeriEvent<KeyEvent>,
+    ) -> Result<Option<SourceSeal>, String> {
+        let digest = event.digest().map_err(|e| e.to_string())?;
+        let kel = self
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(delegator)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default();
+        for notice in kel {
+            let Notice::Event(delegating) = notice else {
+                continue;
+            };
+            let data = match delegating.event_message.data.get_event_data() {
+                EventData::Ixn(ixn) => ixn.data,
+                EventData::Rot(rot) => rot.data,
+                EventData::Drt(drt) => drt.data,
+                _ => continue,
+            };
+            let anchored = data.iter().any(|seal| match seal {
+                Seal::Event(es) => {
+                    es.prefix == event.data.get_prefix()
+                        && es.sn == event.data.get_sn()
+                        && es.event_digest() == digest
+                }
+                _ => false,
+            });
+            if anchored {
+                return Ok(Some(SourceSeal::new(
+                    delegating.event_message.data.get_sn(),
+                    delegating
+                        .event_message
+                        .digest()
+                        .map_err(|e| e.to_string())?,
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns delegator named in delegated event. For rotations it is
+    /// known only if delegate's KEL is known.
+    fn requested_delegator(
+        &self,
+        event: &KeriEvent<KeyEvent>,
+    ) -> Result<Option<IdentifierPrefix>, String> {
+        match event.data.get_event_data() {
+            EventData::Dip(dip) => Ok(Some(dip.delegator)),
+            EventData::Drt(_) => Ok(self
+                .kel
+                .storage
+                .get_state(&event.data.get_prefix())
+                .and_then(|state| state.delegator)),
+            _ => Err("Event is not delegated".to_string()),
+        }
+    }
+
+    pub(crate) fn delegator_of(
+        &self,
+        event: &KeriEvent<KeyEvent>,
+    ) -> Result<IdentifierPrefix, String> {
+        self.requested_delegator(event)?
+            .ok_or("Unknown delegator".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        event::sections::threshold::SignatureThreshold, signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_delegated_lifecycle() {
+        let (_root, controller) = setup("test-db");
+
+        let delegator_signer = Signer::new();
+        let delegator = incept(&controller, &delegator_signer);
+        // Delegator approves event by anchoring its seal.
+        let approve = |event: &[u8]| {
+            let state = controller.get_state(&delegator.id).unwrap();
+            let ixn = event_generator::anchor_with_seal(
+                state,
+                &[delegating_seal(event).unwrap()],
+            )
+            .unwrap();
+            let sig = sign(&delegator_signer, &ixn.encode().unwrap());
+            controller
+                .kel
+                .processor
+                .process_notice(&Notice::Event(ixn.sign(
+                    vec![IndexedSignature::new_both_same(sig, 0)],
+                    None,
+                    None,
+                )))
+                .unwrap();
+        };
+
+        let (signer, next_signer) = (Signer::new(), Signer::new());
+        let dip = controller
+            .incept_delegated(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(next_signer.public_key())],
+                &delegator.id,
+            )
+            .unwrap();
+        let (delegate, _request) = controller
+            .finalize_incept_delegated(
+                dip.as_bytes(),
+                &sign(&signer, dip.as_bytes()),
+            )
+            .unwrap();
+        assert!(!controller.is_delegation_approved(dip.as_bytes()).unwrap());
+        assert!(controller.get_state(&delegate.id).is_none());
+
+        approve(dip.as_bytes());
+        assert!(controller.is_delegation_approved(dip.as_bytes()).unwrap());
+
+        let next_key = BasicPrefix::Ed25519(next_signer.public_key());
+        let drt = delegate
+            .partial_rotate(
+                vec![next_key.clone()],
+                SignatureThreshold::Simple(1),
+                &[BasicPrefix::Ed25519(Signer::new().public_key())],
+                SignatureThreshold::Simple(1),
+            )
+            .unwrap();
+        let signature = delegate
+            .rotation_signature(
+                std::slice::from_ref(&next_key),
+                &next_key,
+                sign(&next_signer, drt.as_bytes()),
+            )
+            .unwrap();
+        controller
+            .finalize_delegated_rotate(drt.as_bytes(), vec![signature])
+            .unwrap();
+        assert!(!controller.is_delegation_approved(drt.as_bytes()).unwrap());
+
+        approve(drt.as_bytes());
+        assert!(controller.is_delegation_approved(drt.as_bytes()).unwrap());
+        let state = controller.get_state(&delegate.id).unwrap();
+        assert_eq!(state.sn, 1);
+        assert_eq!(state.current.public_keys, vec![next_key]);
+    }
+
+    #[test]
+    fn test_delegator_approval() {
+        let (_delegator_root, delegator_controller) = setup("delegator-db");
+        let (_delegate_root, delegate_controller) = setup("delegate-db");
+
+        let delegator_signer = Signer::new();
+        let delegator = incept(&delegator_controller, &delegator_signer);
+
+        let signer = Signer::new();
+        let dip = delegate_controller
+            .incept_delegated(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+                &delegator.id,
+            )
+            .unwrap();
+        let (delegate, request) = delegate_controller
+            .finalize_incept_delegated(
+                dip.as_bytes(),
+                &sign(&signer, dip.as_bytes()),
+            )
+            .unwrap();
+
+        // Delegator reviews the request and anchors it.
+        let requested = delegator_controller
+            .process_delegation_request(&delegator, &request)
+            .unwrap();
+        assert_eq!(requested, vec![dip.clone()]);
+        assert_eq!(
+            delegator_controller
+                .pending_delegation_requests(&delegator.id)
+                .unwrap(),
+            vec![dip.clone()]
+        );
+        let ixn = delegator_controller
+            .approve_delegation(&delegator, dip.as_bytes())
+            .unwrap();
+        let proof = delegator_controller
+            .finalize_approve_delegation(
+                &delegator,
+                ixn.as_bytes(),
+                sign(&delegator_signer, ixn.as_bytes()),
+            )
+            .unwrap();
+        assert!(delegator_controller.get_state(&delegate.id).is_some());
+        assert!(delegator_controller
+            .pending_delegation_requests(&delegator.id)
+            .unwrap()
+            .is_empty());
+
+        // Delegate completes its inception with the anchoring proof.
+        delegate_controller
+            .process_kel(&parse_event_stream(&proof).unwrap())
+            .unwrap();
+        assert!(delegate_controller
+            .is_delegation_approved(dip.as_bytes())
+            .unwrap());
+    }
+
+    #[cfg(feature = "oobi-manager")]
+    #[test]
+    fn test_resolve_pending_delegator() {
+        use std::sync::Mutex;
+
+        use crate::test_utils::watcher_kel_resolver;
+
+        let (_delegator_root, delegator_controller) = setup("delegator-db");
+        let (_delegate_root, delegate_controller) = setup("delegate-db");
+
+        let delegator_signer = Signer::new();
+        let delegator = incept(&delegator_controller, &delegator_signer);
+
+        // Watcher serves delegator's KEL at its location.
+        let served = Arc::new(Mutex::new(vec![]));
+        let resolver = watcher_kel_resolver(
+            &delegate_controller,
+            &delegator.id,
+            served.clone(),
+        );
+        delegate_controller
+            .kel
+            .register_delegator_resolver(resolver.clone());
+
+        // Inception waits in escrow for unknown delegator.
+        let signer = Signer::new();
+        let dip = delegate_controller
+            .incept_delegated(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+                &delegator.id,
+            )
+            .unwrap();
+        let (delegate, request) = delegate_controller
+            .finalize_incept_delegated(
+                dip.as_bytes(),
+                &sign(&signer, dip.as_bytes()),
+            )
+            .unwrap();
+        assert!(delegate_controller.get_state(&delegate.id).is_none());
+        assert_eq!(resolver.pending(), vec![delegator.id.clone()]);
+
+        delegator_controller
+            .process_delegation_request(&delegator, &request)
+            .unwrap();
+        let ixn = delegator_controller
+            .approve_delegation(&delegator, dip.as_bytes())
+            .unwrap();
+        delegator_controller
+            .finalize_approve_delegation(
+                &delegator,
+                ixn.as_bytes(),
+                sign(&delegator_signer, ixn.as_bytes()),
+            )
+            .unwrap();
+        *served.lock().unwrap() = delegator
+            .get_own_kel()
+            .unwrap()
+            .into_iter()
+            .flat_map(|notice| Message::Notice(notice).to_cesr().unwrap())
+            .collect();
+
+        resolver.resolve_pending();
+        assert!(resolver.pending().is_empty());
+        let state = delegate_controller.get_state(&delegate.id).unwrap();
+        assert_eq!(state.delegator, Some(delegator.id));
+    }
+}
@@ -0,0 +1,321 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use keri_core::{
+    actor::prelude::EventStorage,
+    database::{EscrowCreator, EventDatabase},
+    oobi::{LocationScheme, Oobi, Role, Scheme},
+    prefix::{BasicPrefix, CesrPrimitive, IdentifierPrefix},
+    state::IdentifierState,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::oobi::{OobiFetcher, OobiResolver};
+
+pub const DID_KERI_PREFIX: &str = "did:keri:";
+
+const DID_CONTEXT: [&str; 2] = [
+    "https://www.w3.org/ns/did/v1",
+    "https://w3id.org/security/suites/jws-2020/v1",
+];
+
+/// DID Document of `did:keri` identifier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_known_as: Vec<String>,
+    pub verification_method: Vec<VerificationMethod>,
+    pub service: Vec<Service>,
+}
+
+/// Current public key of identifier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    pub public_key_jwk: PublicKeyJwk,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublicKeyJwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+}
+
+/// Endpoint of identifier's witness, watcher, mailbox or controller.
+/// Endpoints are keyed by scheme.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub service_type: String,
+    pub service_endpoint: BTreeMap<String, Url>,
+}
+
+/// Extracts identifier from `did:keri:<aid>` string.
+pub fn parse_did_keri(did: &str) -> Result<IdentifierPrefix, String> {
+    did.strip_prefix(DID_KERI_PREFIX)
+        .ok_or(format!("{} is not a did:keri", did))?
+        .parse()
+        .map_err(|_| format!("Improper identifier in {}", did))
+}
+
+/// Resolves `did:keri` identifiers into DID Documents. KEL and endpoint
+/// replies are fetched as OOBIs from configured witnesses or watchers.
+pub struct DidResolver<D: EventDatabase + EscrowCreator + 'static> {
+    pub(crate) oobi_resolver: OobiResolver<D>,
+    pub(crate) storage: Arc<EventStorage<D>>,
+    pub(crate) fetcher: Arc<dyn OobiFetcher>,
+    sources: Vec<Url>,
+}
+
+impl<D: EventDatabase + EscrowCreator + 'static> DidResolver<D> {
+    pub fn new(
+        oobi_resolver: OobiResolver<D>,
+        storage: Arc<EventStorage<D>>,
+        fetcher: Arc<dyn OobiFetcher>,
+        sources: Vec<Url>,
+    ) -> Self {
+        Self {
+            oobi_resolver,
+            storage,
+            fetcher,
+            sources,
+        }
+    }
+
+    /// Fetches identifier's KEL from all sources and builds DID Document
+    /// from its current state. Unreachable sources are skipped, so
+    /// previously known KEL is used if none of them responds.
+    pub fn resolve(&self, did: &str) -> Result<DidDocument, String> {
+        let id = parse_did_keri(did)?;
+        for source in &self.sources {
+            let oobi = Oobi::Location(LocationScheme::new(
+                id.clone(),
+                Scheme::Http,
+                source.clone(),
+            ));
+            if let Err(e) = self.oobi_resolver.resolve(&oobi) {
+                log::warn!("Failed to resolve {} from {}: {}", id, source, e);
+            }
+        }
+        let state = self
+            .storage
+            .get_state(&id)
+            .ok_or(format!("Unable to resolve KEL of {}", did))?;
+        self.did_document(&state)
+    }
+
+    /// Builds DID Document of known identifier state. Services are made of
+    /// identifier's witnesses and authorized end roles with known
+    /// locations.
+    pub fn did_document(
+        &self,
+        state: &IdentifierState,
+    ) -> Result<DidDocument, String> {
+        self.document(format!("{}{}", DID_KERI_PREFIX, state.prefix), state)
+    }
+
+    pub(crate) fn document(
+        &self,
+        did: String,
+        state: &IdentifierState,
+    ) -> Result<DidDocument, String> {
+        let verification_method = state
+            .current
+            .public_keys
+            .iter()
+            .filter_map(|key| verification_method(&did, key))
+            .collect();
+
+        let mut endpoints: Vec<(IdentifierPrefix, Role)> = state
+            .witness_config
+            .witnesses
+            .iter()
+            .map(|witness| {
+                (IdentifierPrefix::Basic(witness.clone()), Role::Witness)
+            })
+            .collect();
+        for role in [
+            Role::Controller,
+            Role::Witness,
+            Role::Watcher,
+            Role::Messagebox,
+        ] {
+            for end_role in self
+                .oobi_resolver
+                .get_end_roles(&state.prefix, role.clone())?
+            {
+                if !endpoints.contains(&(end_role.eid.clone(), role.clone())) {
+                    endpoints.push((end_role.eid, role.clone()));
+                }
+            }
+        }
+        let mut service = vec![];
+        for (eid, role) in endpoints {
+            let service_endpoint: BTreeMap<String, Url> = self
+                .oobi_resolver
+                .get_loc_schemes(&eid)?
+                .into_iter()
+                .map(|loc| (scheme_name(&loc.scheme).to_string(), loc.url))
+                .collect();
+            if service_endpoint.is_empty() {
+                continue;
+            }
+            service.push(Service {
+                id: format!("#{}/{}", eid, role_name(&role)),
+                service_type: role_name(&role).to_string(),
+                service_endpoint,
+            });
+        }
+
+        Ok(DidDocument {
+            context: DID_CONTEXT.iter().map(|c| c.to_string()).collect(),
+            id: did,
+            also_known_as: vec![],
+            verification_method,
+            service,
+        })
+    }
+}
+
+/// Returns JSON Web Key verification method of Edwards curve key. Other
+/// key types have no JWK representation made of raw key alone and are
+/// skipped.
+fn verification_method(
+    did: &str,
+    key: &BasicPrefix,
+) -> Option<VerificationMethod> {
+    let crv = match key {
+        BasicPrefix::Ed25519(_) | BasicPrefix::Ed25519NT(_) => "Ed25519",
+        BasicPrefix::Ed448(_) | BasicPrefix::Ed448NT(_) => "Ed448",
+        _ => {
+            log::warn!("Key {} can't be expressed as JWK", key.to_str());
+            return None;
+        }
+    };
+    Some(VerificationMethod {
+        id: format!("#{}", key.to_str()),
+        method_type: "JsonWebKey2020".to_string(),
+        controller: did.to_string(),
+        public_key_jwk: PublicKeyJwk {
+            kty: "OKP".to_string(),
+            crv: crv.to_string(),
+            x: base64::encode_config(key.derivative(), base64::URL_SAFE_NO_PAD),
+        },
+    })
+}
+
+fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::Controller => "controller",
+        Role::Witness => "witness",
+        Role::Watcher => "watcher",
+        Role::Messagebox => "messagebox",
+    }
+}
+
+fn scheme_name(scheme: &Scheme) -> &'static str {
+    match scheme {
+        Scheme::Http => "http",
+        Scheme::Tcp => "tcp",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        event_message::signed_event_message::{Message, Op},
+        oobi_manager::OobiManager,
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_resolve_did() {
+        // Identifier that advertises its own endpoint.
+        let (_root, controller) = setup("test-db");
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let finalize = |rpy: String| {
+            identifier
+                .finalize_reply(rpy.as_bytes(), sign(&signer, rpy.as_bytes()))
+                .unwrap()
+        };
+        let endpoint = Url::parse("http://127.0.0.1:3232/").unwrap();
+        let loc = finalize(
+            identifier
+                .add_loc_scheme(Scheme::Http, endpoint.clone())
+                .unwrap(),
+        );
+        let end_role = finalize(
+            identifier
+                .add_end_role(identifier.id.clone(), Role::Controller)
+                .unwrap(),
+        );
+        let mut served: Vec<u8> = identifier
+            .get_own_kel()
+            .unwrap()
+            .into_iter()
+            .flat_map(|notice| Message::Notice(notice).to_cesr().unwrap())
+            .collect();
+        for rpy in [loc, end_role] {
+            served.extend(Message::Op(Op::Reply(rpy)).to_cesr().unwrap());
+        }
+
+        let (_other_root, other) = setup("other-db");
+        let source = Url::parse("http://127.0.0.1:5631/").unwrap();
+        let resolver = other.kel.did_resolver(
+            Arc::new(OobiManager::new(other.kel.storage.events_db.clone())),
+            Arc::new(move |_url: &Url| -> Result<Vec<u8>, String> {
+                Ok(served.clone())
+            }),
+            vec![source],
+        );
+
+        let did = format!("did:keri:{}", identifier.id);
+        let document = resolver.resolve(&did).unwrap();
+        assert_eq!(document.id, did);
+        assert_eq!(document.verification_method.len(), 1);
+        assert_eq!(
+            document.verification_method[0].id,
+            format!("#{}", BasicPrefix::Ed25519(signer.public_key()).to_str())
+        );
+        assert_eq!(
+            document.verification_method[0].public_key_jwk.crv,
+            "Ed25519"
+        );
+        assert_eq!(document.service.len(), 1);
+        assert_eq!(
+            document.service[0].id,
+            format!("#{}/controller", identifier.id)
+        );
+        assert_eq!(document.service[0].service_endpoint["http"], endpoint);
+
+        assert!(resolver.resolve("did:web:example.com").is_err());
+
+        // KEL can't be resolved if sources are unreachable.
+        let (_unreachable_root, unreachable) = setup("unreachable-db");
+        let resolver = unreachable.kel.did_resolver(
+            Arc::new(OobiManager::new(
+                unreachable.kel.storage.events_db.clone(),
+            )),
+            Arc::new(|url: &Url| -> Result<Vec<u8>, String> {
+                Err(format!("{} is unreachable", url))
+            }),
+            vec![Url::parse("http://127.0.0.1:5631/").unwrap()],
+        );
+        assert!(resolver.resolve(&did).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use keri_core::{
+    database::{EscrowCreator, EventDatabase},
+    event_message::signed_event_message::Message,
+    prefix::IdentifierPrefix,
+};
+use url::Url;
+
+use crate::did::{DidDocument, DidResolver, DID_KERI_PREFIX};
+
+pub const DID_WEBS_PREFIX: &str = "did:webs:";
+
+/// Files served under `did:webs` location: DID Document and KEL it was
+/// generated from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DidWebsArtifacts {
+    /// Content of `did.json`.
+    pub did_json: DidDocument,
+    /// Content of `keri.cesr`.
+    pub keri_cesr: Vec<u8>,
+}
+
+/// Builds `did:webs` of identifier hosted on `host`, under optional
+/// `path`.
+pub fn did_webs(
+    host: &str,
+    port: Option<u16>,
+    path: &[&str],
+    id: &IdentifierPrefix,
+) -> String {
+    let host = match port {
+        Some(port) => format!("{}%3A{}", host, port),
+        None => host.to_string(),
+    };
+    let mut segments = vec![host];
+    segments.extend(path.iter().map(|segment| segment.to_string()));
+    segments.push(id.to_string());
+    format!("{}{}", DID_WEBS_PREFIX, segments.join(":"))
+}
+
+/// Splits `did:webs` into identifier and URL of directory its artifacts
+/// are served from, i.e. `https://{host}/{path}/{aid}/`.
+pub fn parse_did_webs(did: &str) -> Result<(Url, IdentifierPrefix), String> {
+    let segments: Vec<&str> = did
+        .strip_prefix(DID_WEBS_PREFIX)
+        .ok_or(format!("{} is not a did:webs", did))?
+        .split(':')
+        .collect();
+    let (host, rest) = match segments.split_first() {
+        Some((host, rest)) if !rest.is_empty() => (host, rest),
+        _ => return Err(format!("{} lacks identifier", did)),
+    };
+    let id: IdentifierPrefix = rest[rest.len() - 1]
+        .parse()
+        .map_err(|_| format!("Improper identifier in {}", did))?;
+    let url =
+        format!("https://{}/{}/", host.replace("%3A", ":"), rest.join("/"));
+    let url = Url::parse(&url).map_err(|e| e.to_string())?;
+    Ok((url, id))
+}
+
+impl<D: EventDatabase + EscrowCreator + 'static> DidResolver<D> {
+    /// Generates `did.json` and `keri.cesr` to be hosted under location of
+    /// `did`. Identifier's KEL needs to be known.
+    pub fn did_webs_artifacts(
+        &self,
+        did: &str,
+    ) -> Result<DidWebsArtifacts, String> {
+        let (_, id) = parse_did_webs(did)?;
+        let state = self
+            .storage
+            .get_state(&id)
+            .ok_or(format!("Unknown identifier {}", id))?;
+        let mut did_json = self.document(did.to_string(), &state)?;
+        did_json.also_known_as = vec![format!("{}{}", DID_KERI_PREFIX, id)];
+        let keri_cesr = self
+            .storage
+            .get_kel_messages_with_receipts_all(&id)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|notice| Message::Notice(notice).to_cesr())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+            .concat();
+        Ok(DidWebsArtifacts {
+            did_json,
+            keri_cesr,
+        })
+    }
+
+    /// Fetches `keri.cesr` and `did.json` of `did`. Served KEL has to
+    /// establish identifier of `did`, and keys listed in document have to
+    /// be its current keys. Returns verified DID Document.
+    pub fn resolve_webs(&self, did: &str) -> Result<DidDocument, String> {
+        let (location, id) = parse_did_webs(did)?;
+        let keri_cesr = self
+            .fetcher
+            .fetch(&location.join("keri.cesr").map_err(|e| e.to_string())?)?;
+        self.oobi_resolver.process_stream(&keri_cesr)?;
+        let state = self
+            .storage
+            .get_state(&id)
+            .ok_or(format!("KEL of {} wasn't served", id))?;
+
+        let did_json = self
+            .fetcher
+            .fetch(&location.join("did.json").map_err(|e| e.to_string())?)?;
+        let document: DidDocument =
+            serde_json::from_slice(&did_json).map_err(|e| e.to_string())?;
+        if document.id != did {
+            return Err(format!(
+                "Document of {} served for {}",
+                document.id, did
+            ));
+        }
+        let expected = self.document(did.to_string(), &state)?;
+        if document.verification_method != expected.verification_method {
+            return Err(format!("Keys of {} don't match its KEL", did));
+        }
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use keri_core::{
+        database::redb::RedbDatabase, oobi_manager::OobiManager,
+        prefix::BasicPrefix, signer::Signer,
+    };
+
+    use super::*;
+    use crate::{
+        oobi::OobiFetcher,
+        test_utils::{incept, setup, TestController},
+    };
+
+    fn did_resolver(
+        controller: &TestController,
+        fetcher: Arc<dyn OobiFetcher>,
+    ) -> DidResolver<RedbDatabase> {
+        controller.kel.did_resolver(
+            Arc::new(OobiManager::new(
+                controller.kel.storage.events_db.clone(),
+            )),
+            fetcher,
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_parse_did_webs() {
+        let id = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let did = did_webs("example.com", Some(8080), &["dids"], &id);
+        assert_eq!(did, format!("did:webs:example.com%3A8080:dids:{}", id));
+        let (url, parsed) = parse_did_webs(&did).unwrap();
+        assert_eq!(parsed, id);
+        assert_eq!(
+            url.as_str(),
+            format!("https://example.com:8080/dids/{}/", id)
+        );
+
+        assert!(parse_did_webs("did:webs:example.com").is_err());
+        assert!(parse_did_webs(&format!("did:keri:{}", id)).is_err());
+    }
+
+    #[test]
+    fn test_resolve_did_webs() {
+        let (_root, controller) = setup("test-db");
+        let resolver = did_resolver(
+            &controller,
+            Arc::new(|url: &Url| -> Result<Vec<u8>, String> {
+                Err(format!("{} is unreachable", url))
+            }),
+        );
+        let id = incept(&controller, &Signer::new()).id;
+        let other_id = incept(&controller, &Signer::new()).id;
+        let did = did_webs("example.com", None, &[], &id);
+        let artifacts = resolver.did_webs_artifacts(&did).unwrap();
+        assert_eq!(artifacts.did_json.id, did);
+        assert_eq!(
+            artifacts.did_json.also_known_as,
+            vec![format!("did:keri:{}", id)]
+        );
+
+        let serve = |did_json: &DidDocument, keri_cesr: &[u8]| {
+            let (location, _) = parse_did_webs(&did).unwrap();
+            let files: HashMap<Url, Vec<u8>> = HashMap::from([
+                (
+                    location.join("did.json").unwrap(),
+                    serde_json::to_vec(did_json).unwrap(),
+                ),
+                (location.join("keri.cesr").unwrap(), keri_cesr.to_vec()),
+            ]);
+            let fetcher = move |url: &Url| -> Result<Vec<u8>, String> {
+                files.get(url).cloned().ok_or(format!("{} not found", url))
+            };
+            Arc::new(fetcher)
+        };
+        let resolve = |name: &str, did_json: &DidDocument, keri_cesr: &[u8]| {
+            let (_root, controller) = setup(name);
+            did_resolver(&controller, serve(did_json, keri_cesr))
+                .resolve_webs(&did)
+        };
+
+        let document =
+            resolve("resolver-db", &artifacts.did_json, &artifacts.keri_cesr)
+                .unwrap();
+        assert_eq!(document, artifacts.did_json);
+
+        // KEL of other identifier doesn't establish identifier of DID.
+        let other = resolver
+            .did_webs_artifacts(&did_webs("example.com", None, &[], &other_id))
+            .unwrap();
+        assert!(
+            resolve("other-kel-db", &artifacts.did_json, &other.keri_cesr)
+                .is_err()
+        );
+
+        // Document listing keys not matching KEL is rejected.
+        let mut forged = artifacts.did_json.clone();
+        forged.verification_method = other.did_json.verification_method;
+        assert!(resolve("forged-db", &forged, &artifacts.keri_cesr).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+use std::{collections::HashSet, sync::Arc};
+
+use cesrox::{parse_many, payload::Payload};
+use keri_core::database::{EscrowCreator, EventDatabase};
+use said::SelfAddressingIdentifier;
+use serde_json::{Map, Value};
+use teliox::database::TelEventDatabase;
+
+use crate::{
+    acdc::Acdc,
+    query::{QuerySigner, QueryTransport},
+    Controller, Identifier,
+};
+
+/// Field of edge holding SAID of linked credential.
+const EDGE_NODE: &str = "n";
+/// Field of edge holding SAID of linked credential's schema.
+const EDGE_SCHEMA: &str = "s";
+
+/// Fetches credential identified by its SAID, signed by its issuer with
+/// `Identifier::sign_data`.
+pub trait CredentialResolver: Send + Sync {
+    fn resolve(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<Vec<u8>, String>;
+}
+
+impl<F> CredentialResolver for F
+where
+    F: Fn(&SelfAddressingIdentifier) -> Result<Vec<u8>, String> + Send + Sync,
+{
+    fn resolve(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<Vec<u8>, String> {
+        self(said)
+    }
+}
+
+pub(crate) struct ChainConfig {
+    pub(crate) resolver: Arc<dyn CredentialResolver>,
+    pub(crate) max_depth: usize,
+}
+
+/// Edge of ACDC, i.e. reference to other credential.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcdcEdge {
+    pub label: String,
+    /// SAID of linked credential.
+    pub node: SelfAddressingIdentifier,
+    /// SAID of schema linked credential has to conform to.
+    pub schema: Option<SelfAddressingIdentifier>,
+}
+
+impl Acdc {
+    /// Returns edges of ACDC, including edges of nested edge groups.
+    pub fn edge_nodes(&self) -> Result<Vec<AcdcEdge>, String> {
+        let mut edges = vec![];
+        if let Some(section) = &self.edges {
+            collect_edges(&section.fields, &mut edges)?;
+        }
+        Ok(edges)
+    }
+}
+
+fn collect_edges(
+    group: &Map<String, Value>,
+    edges: &mut Vec<AcdcEdge>,
+) -> Result<(), String> {
+    for (label, value) in group {
+        let Value::Object(edge) = value else {
+            continue;
+        };
+        let Some(node) = edge.get(EDGE_NODE) else {
+            collect_edges(edge, edges)?;
+            continue;
+        };
+        let parse_said = |value: &Value| {
+            value
+                .as_str()
+                .and_then(|said| said.parse().ok())
+                .ok_or(format!("Invalid SAID in edge {}", label))
+        };
+        edges.push(AcdcEdge {
+            label: label.clone(),
+            node: parse_said(node)?,
+            schema: edge.get(EDGE_SCHEMA).map(parse_said).transpose()?,
+        });
+    }
+    Ok(())
+}
+
+/// Returns payload of signed data without verifying its signatures.
+fn signed_payload(stream: &[u8]) -> Result<Vec<u8>, String> {
+    let (_rest, parsed) =
+        parse_many(stream).map_err(|_| "CESR format error".to_string())?;
+    match parsed.as_slice() {
+        [signed] => match &signed.payload {
+            Payload::JSON(data) => Ok(data.clone()),
+            _ => Err("Unsupported payload format".to_string()),
+        },
+        _ => Err("Expected single signed payload".to_string()),
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Verifies credentials linked by edges of `acdc`. `chain` holds SAIDs
+    /// of credentials linking to `acdc`.
+    pub(crate) fn verify_edges(
+        &self,
+        acdc: &Acdc,
+        chain: &mut Vec<SelfAddressingIdentifier>,
+    ) -> Result<(), String> {
+        let edges = acdc.edge_nodes()?;
+        if edges.is_empty() {
+            return Ok(());
+        }
+        let config = self
+            .chain_config()
+            .ok_or("Unable to resolve credentials of ACDC edges".to_string())?;
+        chain.push(acdc.said()?);
+        if chain.len() > config.max_depth {
+            return Err("Credential chain is too long".to_string());
+        }
+        for edge in edges {
+            if chain.contains(&edge.node) {
+                return Err("Credential chain has a cycle".to_string());
+            }
+            let linked = self.verify_chained_credential(
+                &config.resolver.resolve(&edge.node)?,
+                chain,
+            )?;
+            if linked.said()? != edge.node {
+                return Err(format!("Wrong credential of edge {}", edge.label));
+            }
+            if edge.schema.is_some_and(|schema| schema != linked.schema) {
+                return Err(format!(
+                    "Credential of edge {} doesn't match its schema",
+                    edge.label
+                ));
+            }
+        }
+        chain.pop();
+        Ok(())
+    }
+
+    /// Verifies credential as `verify_credential` does, but first updates
+    /// state of every credential in its chain with
+    /// `query_credential_status`, so that KELs of issuers and TELs of
+    /// credentials missing locally are fetched from issuers' witnesses.
+    /// KELs of issuers have to be known, e.g. from their OOBIs.
+    pub fn query_credential_chain(
+        &self,
+        identifier: &Identifier<D>,
+        stream: &[u8],
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<Acdc, String> {
+        let acdc = Acdc::parse(&signed_payload(stream)?)?;
+        let mut visited = HashSet::from([acdc.said()?]);
+        self.query_chain_status(
+            identifier,
+            &acdc,
+            0,
+            &mut visited,
+            signer,
+            transport,
+        )?;
+        self.verify_credential(stream)
+    }
+
+    fn query_chain_status(
+        &self,
+        identifier: &Identifier<D>,
+        acdc: &Acdc,
+        depth: usize,
+        visited: &mut HashSet<SelfAddressingIdentifier>,
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<(), String> {
+        if let Some(registry) = &acdc.registry {
+            self.query_credential_status(
+                identifier,
+                &acdc.issuer,
+                registry,
+                &acdc.said()?,
+                signer,
+                transport,
+            )?;
+        }
+        let Some(config) = self.chain_config() else {
+            return Ok(());
+        };
+        if depth >= config.max_depth {
+            return Ok(());
+        }
+        for edge in acdc.edge_nodes()? {
+            if !visited.insert(edge.node.clone()) {
+                continue;
+            }
+            let linked = Acdc::parse(&signed_payload(
+                &config.resolver.resolve(&edge.node)?,
+            )?)?;
+            self.query_chain_status(
+                identifier,
+                &linked,
+                depth + 1,
+                visited,
+                signer,
+                transport,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use keri_core::{
+        actor::prelude::HashFunctionCode, prefix::BasicPrefix, signer::Signer,
+    };
+    use said::derivation::HashFunction;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_credential_chain() {
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let (_root, controller) = setup("test-db");
+        let signer = Signer::new();
+        let issuer = incept(&controller, &signer);
+        let (ixn, vcp) = controller.incept_registry(&issuer).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let issue = |acdc: &Acdc| {
+            let (ixn, iss) = controller.issue_acdc(&issuer, acdc).unwrap();
+            controller
+                .finalize_issue_credential(
+                    &issuer,
+                    ixn.as_bytes(),
+                    sign(&signer, ixn.as_bytes()),
+                    iss.as_bytes(),
+                    &publisher,
+                )
+                .unwrap();
+            let encoded = acdc.encode().unwrap();
+            issuer.sign_data(&encoded, sign(&signer, &encoded)).unwrap()
+        };
+
+        let qualification_schema =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"qvi");
+        let qualification = Acdc::new(
+            issuer.id.clone(),
+            Some(registry.clone()),
+            qualification_schema.clone(),
+            json!({"lei": "5493001KJTIIGC8Y1R12"})
+                .as_object()
+                .unwrap()
+                .clone(),
+        )
+        .unwrap();
+        let qualification_said = qualification.said().unwrap();
+        let signed_qualification = issue(&qualification);
+
+        let chained = |schema: &SelfAddressingIdentifier| {
+            Acdc::new(
+                issuer.id.clone(),
+                Some(registry.clone()),
+                HashFunction::from(HashFunctionCode::Blake3_256)
+                    .derive(b"role"),
+                json!({"role": "CEO"}).as_object().unwrap().clone(),
+            )
+            .unwrap()
+            .with_edges(
+                json!({"qvi": {
+                    "n": qualification_said.to_string(),
+                    "s": schema.to_string()
+                }})
+                .as_object()
+                .unwrap()
+                .clone(),
+            )
+            .unwrap()
+        };
+        let role = chained(&qualification_schema);
+        assert_eq!(
+            role.edge_nodes().unwrap(),
+            vec![AcdcEdge {
+                label: "qvi".to_string(),
+                node: qualification_said.clone(),
+                schema: Some(qualification_schema.clone()),
+            }]
+        );
+        let signed_role = issue(&role);
+        let mismatched = chained(&role.schema);
+        let signed_mismatched = issue(&mismatched);
+
+        // Edges can't be verified without resolver of linked credentials.
+        assert!(controller.verify_credential(&signed_role).is_err());
+
+        let credentials = Arc::new(Mutex::new(HashMap::from([(
+            qualification_said.clone(),
+            signed_qualification,
+        )])));
+        let served = credentials.clone();
+        let resolver = move |said: &SelfAddressingIdentifier| {
+            served
+                .lock()
+                .unwrap()
+                .get(said)
+                .cloned()
+                .ok_or(format!("Unknown credential {}", said))
+        };
+        let controller =
+            controller.with_credential_resolver(Arc::new(resolver), 1);
+        assert_eq!(controller.verify_credential(&signed_role).unwrap(), role);
+        assert!(controller.verify_credential(&signed_mismatched).is_err());
+
+        // Credential linking to role credential exceeds maximal depth.
+        let delegated = Acdc::new(
+            issuer.id.clone(),
+            None,
+            qualification_schema,
+            json!({"role": "Deputy"}).as_object().unwrap().clone(),
+        )
+        .unwrap()
+        .with_edges(
+            json!({"role": {"n": role.said().unwrap().to_string()}})
+                .as_object()
+                .unwrap()
+                .clone(),
+        )
+        .unwrap();
+        let encoded = delegated.encode().unwrap();
+        let signed_delegated =
+            issuer.sign_data(&encoded, sign(&signer, &encoded)).unwrap();
+        credentials
+            .lock()
+            .unwrap()
+            .insert(role.said().unwrap(), signed_role.clone());
+        assert!(controller.verify_credential(&signed_delegated).is_err());
+
+        // Revoking linked credential invalidates the chain.
+        let (ixn, rev) = controller
+            .revoke_credential(&issuer, &registry, &qualification_said)
+            .unwrap();
+        controller
+            .finalize_revoke_credential(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                rev.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert!(controller.verify_credential(&signed_role).is_err());
+    }
+}
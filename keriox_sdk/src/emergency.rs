@@ -0,0 +1,385 @@
+use std::{sync::Arc, time::Duration};
+
+use keri_core::{
+    actor::event_generator,
+    database::{EscrowCreator, EventDatabase},
+    event::sections::{
+        seal::EventSeal, threshold::SignatureThreshold, RotationWitnessConfig,
+    },
+    event_message::signed_event_message::{
+        Message, Notice, Op, SignedEventMessage,
+    },
+    keys::{OsKeySource, SecretSeed},
+    oobi::Role,
+    prefix::{
+        CesrPrimitive, IdentifierPrefix, IndexedSignature, SelfSigningPrefix,
+    },
+    query::reply_event::SignedReply,
+};
+
+use crate::{
+    keystore::{public_keys, random_seed, IdentifierKeys, KeyStore},
+    receipts::{ReceiptCollector, ReceiptFetcher, RetryPolicy},
+    watcher::WatcherTransport,
+    witness::WitnessPublisher,
+    Identifier,
+};
+
+/// Parameters of rotation made in response to compromise of identifier's
+/// current keys.
+pub struct EmergencyRotation {
+    /// Seeds of reserve keys: pre-rotated keys kept apart from the
+    /// compromised ones, e.g. offline. All of them become current keys.
+    pub reserve: Vec<SecretSeed>,
+    /// How witnesses are retried until rotation is fully witnessed.
+    pub retry: RetryPolicy,
+    /// End role authorizations to revoke with the new keys.
+    pub revoke: Vec<(IdentifierPrefix, Role)>,
+}
+
+impl EmergencyRotation {
+    /// Rotates to `reserve` keys, retrying witnesses more often and for
+    /// longer than by default.
+    pub fn new(reserve: Vec<SecretSeed>) -> Self {
+        Self {
+            reserve,
+            retry: RetryPolicy {
+                max_attempts: 20,
+                initial_backoff: Duration::from_millis(50),
+                max_backoff: Duration::from_secs(2),
+            },
+            revoke: vec![],
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Revokes authorization of `eid` to act as identifier's endpoint of
+    /// given role, e.g. agent which could have been authorized by attacker.
+    pub fn revoking(mut self, eid: IdentifierPrefix, role: Role) -> Self {
+        self.revoke.push((eid, role));
+        self
+    }
+}
+
+/// Outcome of emergency rotation. Steps following the rotation don't stop
+/// on failure, their errors are gathered instead.
+pub struct EmergencyReport {
+    pub rotation: SignedEventMessage,
+    pub witnessed: bool,
+    pub notified_watchers: Vec<IdentifierPrefix>,
+    /// Signed end role revocations, already sent to witnesses and watchers.
+    pub revocations: Vec<SignedReply>,
+    pub errors: Vec<String>,
+}
+
+impl<D: EventDatabase + EscrowCreator + Send + Sync + 'static> Identifier<D> {
+    /// Responds to compromise of current keys: rotates to reserve keys,
+    /// committing to fresh next keys, publishes rotation to all witnesses
+    /// until it's fully witnessed, sends it to registered watchers and
+    /// revokes requested end roles. Next keys stored in `keystore` which
+    /// aren't exposed stay reserved. Fails only if the rotation itself
+    /// couldn't be made.
+    pub fn emergency_rotate(
+        &self,
+        keystore: &dyn KeyStore,
+        rotation: EmergencyRotation,
+        publisher: Arc<dyn WitnessPublisher>,
+        fetcher: Arc<dyn ReceiptFetcher>,
+        watchers: &dyn WatcherTransport,
+    ) -> Result<EmergencyReport, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let keys = keystore
+            .load(&self.id)?
+            .ok_or(format!("No keys of {} stored", self.id))?;
+        if rotation.reserve.is_empty() {
+            return Err("No reserve keys".to_string());
+        }
+        let next_keys = &state.current.next_keys_data;
+        let reserve = public_keys(&rotation.reserve)?;
+        if let Some(key) = reserve
+            .iter()
+            .find(|key| next_keys.key_position(key).is_none())
+        {
+            return Err(format!(
+                "Reserve key {} is not committed",
+                key.to_str()
+            ));
+        }
+
+        let mut retired = keys.retired.clone();
+        retired.extend(state.current.public_keys.clone());
+        let fresh = (0..reserve.len())
+            .map(|_| random_seed(&OsKeySource))
+            .collect::<Vec<_>>();
+        let fresh_keys = public_keys(&fresh)?;
+        if fresh_keys
+            .iter()
+            .any(|key| reserve.contains(key) || retired.contains(key))
+        {
+            return Err("New next key was already exposed".to_string());
+        }
+        // Stored next keys which stay unexposed are still committed, after
+        // the fresh ones.
+        let unexposed = keys
+            .next
+            .iter()
+            .zip(public_keys(&keys.next)?)
+            .filter(|(_, key)| {
+                next_keys.key_position(key).is_some() && !reserve.contains(key)
+            })
+            .map(|(seed, _)| seed.clone());
+        let rotated = IdentifierKeys {
+            current: rotation.reserve,
+            next: fresh.into_iter().chain(unexposed).collect(),
+            retired,
+        };
+        let threshold = SignatureThreshold::Simple(reserve.len() as u64);
+        let rot = self.rotate_witnesses(
+            reserve,
+            threshold,
+            &fresh_keys,
+            next_keys.threshold.clone(),
+            RotationWitnessConfig {
+                tally: state.witness_config.tally.clone(),
+                prune: vec![],
+                graft: vec![],
+            },
+        )?;
+        let rot =
+            self.finalize_key_rotation(keystore, &rot, &keys, &rotated)?;
+
+        let mut errors = vec![];
+        let processor = self
+            .processor
+            .clone()
+            .ok_or("Identifier has no processor".to_string())?;
+        let collector = ReceiptCollector::new(
+            processor,
+            self.event_storage.clone(),
+            publisher.clone(),
+            fetcher,
+        )
+        .with_retry_policy(rotation.retry);
+        let witnessed = match collector.collect(&rot) {
+            Ok(()) => true,
+            Err(e) => {
+                errors.push(e);
+                false
+            }
+        };
+
+        let sn = rot.event_message.data.get_sn();
+        let mut stream = Message::Notice(Notice::Event(rot.clone()))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+        if let Some(receipts) = self
+            .event_storage
+            .get_nt_receipts(&self.id, sn)
+            .map_err(|e| e.to_string())?
+        {
+            stream.extend(
+                Message::Notice(Notice::NontransferableRct(receipts))
+                    .to_cesr()
+                    .map_err(|e| e.to_string())?,
+            );
+        }
+        let mut notified_watchers = vec![];
+        for watcher in self.watchers() {
+            match watchers.send(&watcher, &stream) {
+                Ok(()) => notified_watchers.push(watcher),
+                Err(e) => errors.push(format!("Watcher {}: {}", watcher, e)),
+            }
+        }
+
+        // Revocations are signed with keys established by the rotation, which
+        // may still wait for receipts.
+        let seal = EventSeal::new(
+            self.id.clone(),
+            sn,
+            rot.event_message.digest().map_err(|e| e.to_string())?,
+        );
+        let signers = rotated.signers()?;
+        let mut revocations = vec![];
+        for (eid, role) in rotation.revoke {
+            let rpy =
+                event_generator::generate_end_role(&self.id, &eid, role, false);
+            let encoded = rpy.encode().map_err(|e| e.to_string())?;
+            let signatures = signers
+                .iter()
+                .enumerate()
+                .map(|(i, signer)| {
+                    let signature =
+                        signer.sign(&encoded).map_err(|e| e.to_string())?;
+                    Ok(IndexedSignature::new_both_same(
+                        SelfSigningPrefix::Ed25519Sha512(signature),
+                        i as u16,
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let revocation =
+                SignedReply::new_trans(rpy, seal.clone(), signatures);
+            let stream = Message::Op(Op::Reply(revocation.clone()))
+                .to_cesr()
+                .map_err(|e| e.to_string())?;
+            for witness in &state.witness_config.witnesses {
+                if let Err(e) = publisher.publish(witness, &stream) {
+                    errors.push(format!("Witness {}: {}", witness.to_str(), e));
+                }
+            }
+            for watcher in &notified_watchers {
+                if let Err(e) = watchers.send(watcher, &stream) {
+                    errors.push(format!("Watcher {}: {}", watcher, e));
+                }
+            }
+            revocations.push(revocation);
+        }
+
+        Ok(EmergencyReport {
+            rotation: rot,
+            witnessed,
+            notified_watchers,
+            revocations,
+            errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use keri_core::{
+        oobi::Oobi,
+        prefix::{BasicPrefix, SelfSigningPrefix},
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::{test_utils::setup, EncryptedFileKeyStore};
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<IdentifierPrefix>>,
+    }
+
+    impl WatcherTransport for RecordingTransport {
+        fn send(
+            &self,
+            watcher: &IdentifierPrefix,
+            _stream: &[u8],
+        ) -> Result<(), String> {
+            self.sent.lock().unwrap().push(watcher.clone());
+            Ok(())
+        }
+
+        fn resolve_oobi(
+            &self,
+            _watcher: &IdentifierPrefix,
+            _oobi: &Oobi,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_emergency_rotate() {
+        let (root, controller) = setup("test-db");
+        let store = EncryptedFileKeyStore::new(root.path().join("keys.json"));
+        store.unlock("passphrase").unwrap();
+
+        // Second next key is the reserve one, kept apart from the others.
+        let keys = IdentifierKeys::generate(2);
+        let reserve = keys.next[1].clone();
+        let icp = controller
+            .incept(
+                keys.public_keys().unwrap()[..1].to_vec(),
+                keys.next_public_keys().unwrap(),
+            )
+            .unwrap();
+        let signature = SelfSigningPrefix::Ed25519Sha512(
+            keys.signers().unwrap()[0].sign(icp.as_bytes()).unwrap(),
+        );
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &signature)
+            .unwrap();
+        let stored = IdentifierKeys {
+            current: keys.current[..1].to_vec(),
+            next: keys.next[..1].to_vec(),
+            retired: vec![],
+        };
+        store.save(&identifier.id, &stored).unwrap();
+
+        let transport = RecordingTransport::default();
+        let watcher_signer = Signer::new();
+        let watcher = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            watcher_signer.public_key(),
+        ));
+        let rpy = identifier.add_watcher(watcher.clone()).unwrap();
+        let sig = SelfSigningPrefix::Ed25519Sha512(
+            keys.signers().unwrap()[0].sign(rpy.as_bytes()).unwrap(),
+        );
+        identifier
+            .register_watcher(rpy.as_bytes(), sig, &transport)
+            .unwrap();
+
+        let publisher =
+            Arc::new(|_: &BasicPrefix, _: &[u8]| -> Result<(), String> {
+                Ok(())
+            });
+        let fetcher = Arc::new(
+            |_: &BasicPrefix,
+             _: &IdentifierPrefix,
+             _: u64|
+             -> Result<Vec<u8>, String> { Ok(vec![]) },
+        );
+        let mailbox = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+
+        // Key which isn't committed can't be used.
+        let uncommitted = EmergencyRotation::new(vec![keys.current[1].clone()]);
+        assert!(identifier
+            .emergency_rotate(
+                &store,
+                uncommitted,
+                publisher.clone(),
+                fetcher.clone(),
+                &transport
+            )
+            .is_err());
+
+        let rotation = EmergencyRotation::new(vec![reserve.clone()])
+            .revoking(mailbox.clone(), Role::Messagebox);
+        let report = identifier
+            .emergency_rotate(&store, rotation, publisher, fetcher, &transport)
+            .unwrap();
+        assert!(report.witnessed);
+        assert!(report.errors.is_empty());
+        assert_eq!(report.notified_watchers, vec![watcher.clone()]);
+        assert_eq!(report.revocations.len(), 1);
+        // Watcher got its registration, the rotation and the revocation.
+        assert_eq!(transport.sent.lock().unwrap().len(), 3);
+
+        let state = controller.get_state(&identifier.id).unwrap();
+        assert_eq!(state.sn, 1);
+        assert_eq!(
+            state.current.public_keys,
+            keys.next_public_keys().unwrap()[1..].to_vec()
+        );
+        // Unexposed next key stays reserved, so regular rotation follows.
+        let rotated = store.load(&identifier.id).unwrap().unwrap();
+        assert_eq!(rotated.current, vec![reserve]);
+        assert_eq!(rotated.next.len(), 2);
+        assert_eq!(rotated.next[1], keys.next[0]);
+        assert_eq!(rotated.retired, keys.public_keys().unwrap()[..1].to_vec());
+        identifier.rotate(&store).unwrap();
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 2);
+    }
+}
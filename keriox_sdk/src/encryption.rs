@@ -0,0 +1,320 @@
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use keri_core::{
+    database::{EscrowCreator, EventDatabase},
+    keys::{PublicKey, SecretSeed},
+    prefix::{BasicPrefix, IdentifierPrefix, SeedPrefix},
+    signer::Signer,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use teliox::database::TelEventDatabase;
+use zeroize::Zeroizing;
+
+use crate::{keystore::IdentifierKeys, Controller, Identifier};
+
+/// Domain separation of key wrapping keys.
+const KEY_WRAPPING: &[u8] = b"keri-sdk sealed payload v1";
+
+/// Payload encrypted to one or more Ed25519 keys. Payload is encrypted with
+/// random ChaCha20-Poly1305 key, which is wrapped for every recipient key
+/// with key agreed between ephemeral X25519 key and X25519 form of the
+/// recipient key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SealedPayload {
+    /// Base64 encoded ephemeral X25519 public key.
+    pub ephemeral: String,
+    pub recipients: Vec<SealedKey>,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Payload key wrapped for single recipient key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SealedKey {
+    /// Ed25519 key of recipient.
+    pub key: BasicPrefix,
+    pub nonce: String,
+    pub wrapped: String,
+}
+
+/// Returns X25519 key corresponding to Ed25519 `key`.
+pub fn encryption_key(key: &BasicPrefix) -> Result<BasicPrefix, String> {
+    let point = montgomery_point(key)?;
+    Ok(BasicPrefix::X25519(PublicKey::new(
+        point.to_bytes().to_vec(),
+    )))
+}
+
+fn montgomery_point(key: &BasicPrefix) -> Result<MontgomeryPoint, String> {
+    match key {
+        BasicPrefix::Ed25519(key) | BasicPrefix::Ed25519NT(key) => {
+            let bytes: [u8; 32] = key
+                .key()
+                .try_into()
+                .map_err(|_| "Invalid Ed25519 key length".to_string())?;
+            let key =
+                VerifyingKey::from_bytes(&bytes).map_err(|e| e.to_string())?;
+            Ok(key.to_montgomery())
+        }
+        _ => Err("Only Ed25519 keys can be used for encryption".to_string()),
+    }
+}
+
+/// Derives key wrapping key from X25519 shared secret. Secret of low order
+/// point is refused, as it doesn't depend on ephemeral key.
+fn wrapping_key(
+    shared: MontgomeryPoint,
+    ephemeral: &MontgomeryPoint,
+    recipient: &MontgomeryPoint,
+) -> Result<Zeroizing<[u8; 32]>, String> {
+    let shared = Zeroizing::new(shared.to_bytes());
+    if shared.iter().all(|byte| *byte == 0) {
+        return Err("Invalid recipient key".to_string());
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_WRAPPING);
+    hasher.update(&shared[..]);
+    hasher.update(ephemeral.as_bytes());
+    hasher.update(recipient.as_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&hasher.finalize());
+    Ok(key)
+}
+
+fn decode(encoded: &str) -> Result<Vec<u8>, String> {
+    base64::decode(encoded).map_err(|e| e.to_string())
+}
+
+fn decode_nonce(encoded: &str) -> Result<Nonce, String> {
+    let nonce = decode(encoded)?;
+    if nonce.len() != 12 {
+        return Err("Invalid nonce length".to_string());
+    }
+    Ok(*Nonce::from_slice(&nonce))
+}
+
+/// Encrypts `payload`, so that it can be decrypted with private key of any
+/// of `recipients`.
+pub fn seal(
+    payload: &[u8],
+    recipients: &[BasicPrefix],
+) -> Result<SealedPayload, String> {
+    if recipients.is_empty() {
+        return Err("No recipients".to_string());
+    }
+    let mut ephemeral_secret = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(&mut ephemeral_secret[..]);
+    let ephemeral = MontgomeryPoint::mul_base_clamped(*ephemeral_secret);
+    let mut payload_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(&mut payload_key[..]);
+
+    let recipients = recipients
+        .iter()
+        .map(|key| {
+            let point = montgomery_point(key)?;
+            let wrapping_key = wrapping_key(
+                point.mul_clamped(*ephemeral_secret),
+                &ephemeral,
+                &point,
+            )?;
+            let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+            let wrapped =
+                ChaCha20Poly1305::new(Key::from_slice(&wrapping_key[..]))
+                    .encrypt(&nonce, &payload_key[..])
+                    .map_err(|_| "Key wrapping error".to_string())?;
+            Ok(SealedKey {
+                key: key.clone(),
+                nonce: base64::encode(nonce),
+                wrapped: base64::encode(wrapped),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&payload_key[..]))
+        .encrypt(&nonce, payload)
+        .map_err(|_| "Payload encryption error".to_string())?;
+    Ok(SealedPayload {
+        ephemeral: base64::encode(ephemeral.as_bytes()),
+        recipients,
+        nonce: base64::encode(nonce),
+        ciphertext: base64::encode(ciphertext),
+    })
+}
+
+impl SealedPayload {
+    /// Decrypts payload with Ed25519 `seed` of one of recipient keys.
+    pub fn open(&self, seed: &SecretSeed) -> Result<Vec<u8>, String> {
+        let signing_key = match seed.seed() {
+            SeedPrefix::RandomSeed256Ed25519(seed) => {
+                let seed = Zeroizing::new(
+                    <[u8; 32]>::try_from(seed.as_slice()).map_err(|_| {
+                        "Invalid Ed25519 seed length".to_string()
+                    })?,
+                );
+                SigningKey::from_bytes(&seed)
+            }
+            _ => {
+                return Err(
+                    "Only Ed25519 keys can be used for encryption".into()
+                )
+            }
+        };
+        let point = signing_key.verifying_key().to_montgomery();
+        let sealed_key = self
+            .recipients
+            .iter()
+            .find(|recipient| {
+                montgomery_point(&recipient.key).ok() == Some(point)
+            })
+            .ok_or("Payload isn't sealed to the key".to_string())?;
+
+        let ephemeral: [u8; 32] = decode(&self.ephemeral)?
+            .try_into()
+            .map_err(|_| "Invalid ephemeral key length".to_string())?;
+        let ephemeral = MontgomeryPoint(ephemeral);
+        let scalar = Zeroizing::new(signing_key.to_scalar_bytes());
+        let wrapping_key =
+            wrapping_key(ephemeral.mul_clamped(*scalar), &ephemeral, &point)?;
+        let payload_key = Zeroizing::new(
+            ChaCha20Poly1305::new(Key::from_slice(&wrapping_key[..]))
+                .decrypt(
+                    &decode_nonce(&sealed_key.nonce)?,
+                    decode(&sealed_key.wrapped)?.as_slice(),
+                )
+                .map_err(|_| "Key unwrapping error".to_string())?,
+        );
+        if payload_key.len() != 32 {
+            return Err("Invalid payload key length".to_string());
+        }
+        ChaCha20Poly1305::new(Key::from_slice(&payload_key))
+            .decrypt(
+                &decode_nonce(&self.nonce)?,
+                decode(&self.ciphertext)?.as_slice(),
+            )
+            .map_err(|_| "Payload decryption error".to_string())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Encrypts `payload` to current keys of `recipients`, e.g. content of
+    /// exchange message or mailbox item. Every current key of every
+    /// recipient can decrypt it on its own. KELs of recipients have to be
+    /// known.
+    pub fn seal(
+        &self,
+        payload: &[u8],
+        recipients: &[IdentifierPrefix],
+    ) -> Result<SealedPayload, String> {
+        let keys = recipients
+            .iter()
+            .map(|id| {
+                self.kel
+                    .storage
+                    .get_state(id)
+                    .map(|state| state.current.public_keys)
+                    .ok_or(format!("Unknown identifier {}", id))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        seal(payload, &keys.concat())
+    }
+}
+
+impl<D: EventDatabase> Identifier<D> {
+    /// Decrypts payload sealed to identifier. Only seeds of keys current in
+    /// identifier's KEL are used, so payload sealed to keys since rotated
+    /// out can't be opened.
+    pub fn open(
+        &self,
+        sealed: &SealedPayload,
+        keys: &IdentifierKeys,
+    ) -> Result<Vec<u8>, String> {
+        let current = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?
+            .current
+            .public_keys;
+        let mut opened = Err("Payload isn't sealed to current keys".into());
+        for seed in &keys.current {
+            let signer =
+                Signer::new_with_seed(seed).map_err(|e| e.to_string())?;
+            if !current.contains(&BasicPrefix::Ed25519(signer.public_key())) {
+                continue;
+            }
+            opened = sealed.open(seed);
+            if opened.is_ok() {
+                break;
+            }
+        }
+        opened
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{setup, sign};
+
+    #[test]
+    fn test_sealed_payload() {
+        let (_root, controller) = setup("test-db");
+        let incept = |keys: &IdentifierKeys| {
+            let icp = controller
+                .incept(
+                    keys.public_keys().unwrap(),
+                    keys.next_public_keys().unwrap(),
+                )
+                .unwrap();
+            let signers = keys.signers().unwrap();
+            controller
+                .finalize_incept(
+                    icp.as_bytes(),
+                    &sign(&signers[0], icp.as_bytes()),
+                )
+                .unwrap()
+        };
+        let (alice_keys, bob_keys, eve_keys) = (
+            IdentifierKeys::generate(1),
+            IdentifierKeys::generate(1),
+            IdentifierKeys::generate(1),
+        );
+        let (alice, bob, eve) =
+            (incept(&alice_keys), incept(&bob_keys), incept(&eve_keys));
+
+        let key = &alice_keys.public_keys().unwrap()[0];
+        assert!(matches!(encryption_key(key), Ok(BasicPrefix::X25519(_))));
+        assert!(
+            encryption_key(&BasicPrefix::X25519(PublicKey::default())).is_err()
+        );
+
+        let payload = b"exchange message payload";
+        let sealed = controller
+            .seal(payload, &[alice.id.clone(), bob.id.clone()])
+            .unwrap();
+        assert_eq!(sealed.recipients.len(), 2);
+        assert_eq!(alice.open(&sealed, &alice_keys).unwrap(), payload);
+        assert_eq!(bob.open(&sealed, &bob_keys).unwrap(), payload);
+        assert!(eve.open(&sealed, &eve_keys).is_err());
+        // Modified ciphertext is rejected.
+        let mut tampered = sealed.clone();
+        tampered.ciphertext = base64::encode(b"other payload");
+        assert!(alice.open(&tampered, &alice_keys).is_err());
+
+        // Next keys aren't current yet, so they aren't used.
+        let mut rotated = alice_keys.clone();
+        rotated.rotate().unwrap();
+        let sealed = seal(payload, &rotated.public_keys().unwrap()).unwrap();
+        assert_eq!(sealed.open(&rotated.current[0]).unwrap(), payload);
+        assert!(alice.open(&sealed, &rotated).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use std::{
+    collections::HashMap,
+    sync::{mpsc, Arc, Mutex, RwLock},
+};
+
+use keri_core::{
+    actor::prelude::EventStorage,
+    database::{EscrowCreator, EventDatabase},
+    error::Error,
+    event::{event_data::EventData, sections::seal::Seal},
+    event_message::signed_event_message::SignedEventMessage,
+    prefix::{BasicPrefix, IdentifierPrefix},
+    processor::notification::{
+        JustNotification, Notification, NotificationBus, Notifier,
+    },
+};
+use teliox::{
+    database::TelEventDatabase,
+    event::{vc_event::VCEventType, Event},
+    processor::notification::{
+        TelNotification, TelNotificationBus, TelNotificationKind, TelNotifier,
+    },
+};
+
+use crate::{Controller, Identifier};
+
+/// Occurrence concerning identifier, delivered to its subscribers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdentifierEvent {
+    /// Identifier's event got receipts of enough witnesses and was
+    /// accepted.
+    ReceiptThresholdMet { sn: u64 },
+    /// Rotation was accepted, `keys` are identifier's new current keys.
+    RotationAccepted { sn: u64, keys: Vec<BasicPrefix> },
+    /// Credential issued by identifier was revoked.
+    CredentialRevoked {
+        registry: IdentifierPrefix,
+        credential: IdentifierPrefix,
+    },
+    /// Event conflicting with accepted event of the same sn was received.
+    DuplicityDetected(Box<SignedEventMessage>),
+}
+
+/// Called with every event of subscribed identifier. Returns false once
+/// subscriber is gone.
+type Subscriber = Box<dyn Fn(&IdentifierEvent) -> bool + Send + Sync>;
+
+/// Turns KEL and TEL notifications into `IdentifierEvent`s of subscribed
+/// identifiers. Registered with `Controller::register_event_hub`.
+pub struct EventHub<D: EventDatabase> {
+    storage: Arc<EventStorage<D>>,
+    subscribers: RwLock<Vec<(IdentifierPrefix, Subscriber)>>,
+    /// TEL events anchored in KEL of subscribed identifiers, mapped to the
+    /// anchoring identifier. Anchors are accepted before TEL events.
+    anchors: Mutex<HashMap<IdentifierPrefix, IdentifierPrefix>>,
+}
+
+impl<D: EventDatabase> EventHub<D> {
+    pub fn new(storage: Arc<EventStorage<D>>) -> Self {
+        Self {
+            storage,
+            subscribers: RwLock::new(vec![]),
+            anchors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn subscribe(&self, id: &IdentifierPrefix, subscriber: Subscriber) {
+        if let Ok(mut subscribers) = self.subscribers.write() {
+            subscribers.push((id.clone(), subscriber));
+        }
+    }
+
+    fn is_subscribed(&self, id: &IdentifierPrefix) -> bool {
+        self.subscribers
+            .read()
+            .map(|subscribers| subscribers.iter().any(|(sub, _)| sub == id))
+            .unwrap_or(false)
+    }
+
+    fn publish(&self, id: &IdentifierPrefix, event: IdentifierEvent) {
+        if let Ok(mut subscribers) = self.subscribers.write() {
+            subscribers
+                .retain(|(sub, subscriber)| sub != id || subscriber(&event));
+        }
+    }
+
+    /// Checks whether event differs from the accepted one of the same sn,
+    /// rather than being received again.
+    fn is_duplicitous(&self, event: &SignedEventMessage) -> bool {
+        let data = &event.event_message.data;
+        match self
+            .storage
+            .get_event_at_sn(&data.get_prefix(), data.get_sn())
+        {
+            Some(accepted) => {
+                accepted.signed_event_message.event_message.digest().ok()
+                    != event.event_message.digest().ok()
+            }
+            None => false,
+        }
+    }
+}
+
+impl<D: EventDatabase> Notifier for EventHub<D> {
+    fn notify(
+        &self,
+        notification: &Notification,
+        _bus: &NotificationBus,
+    ) -> Result<(), Error> {
+        match notification {
+            Notification::KeyEventAccepted(accepted) => {
+                let data = &accepted.event.event_message.data;
+                let id = data.get_prefix();
+                if !self.is_subscribed(&id) {
+                    return Ok(());
+                }
+                let (seals, rotation) = match data.get_event_data() {
+                    EventData::Ixn(ixn) => (ixn.data, false),
+                    EventData::Rot(rot) => (rot.data, true),
+                    EventData::Drt(drt) => (drt.data, true),
+                    _ => (vec![], false),
+                };
+                if let Ok(mut anchors) = self.anchors.lock() {
+                    for seal in seals {
+                        if let Seal::Event(es) = seal {
+                            anchors.insert(es.prefix, id.clone());
+                        }
+                    }
+                }
+                if !accepted.state.witness_config.witnesses.is_empty() {
+                    self.publish(
+                        &id,
+                        IdentifierEvent::ReceiptThresholdMet {
+                            sn: data.get_sn(),
+                        },
+                    );
+                }
+                if rotation {
+                    self.publish(
+                        &id,
+                        IdentifierEvent::RotationAccepted {
+                            sn: data.get_sn(),
+                            keys: accepted.state.current.public_keys.clone(),
+                        },
+                    );
+                }
+            }
+            Notification::DupliciousEvent(event) => {
+                let id = event.event_message.data.get_prefix();
+                if self.is_subscribed(&id) && self.is_duplicitous(event) {
+                    self.publish(
+                        &id,
+                        IdentifierEvent::DuplicityDetected(Box::new(
+                            event.clone(),
+                        )),
+                    );
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+impl<D: EventDatabase> TelNotifier for EventHub<D> {
+    fn notify(
+        &self,
+        notification: &TelNotification,
+        _bus: &TelNotificationBus,
+    ) -> Result<(), teliox::error::Error> {
+        if let TelNotification::TelEventAdded(event) = notification {
+            let prefix = event.event.get_prefix();
+            let issuer = match self.anchors.lock() {
+                Ok(mut anchors) => anchors.remove(&prefix),
+                Err(_) => None,
+            };
+            if let (Some(issuer), Event::Vc(vc)) = (issuer, &event.event) {
+                if matches!(
+                    vc.data.data.event_type,
+                    VCEventType::Rev(_) | VCEventType::Brv(_)
+                ) {
+                    self.publish(
+                        &issuer,
+                        IdentifierEvent::CredentialRevoked {
+                            registry: vc.data.data.registry_id()?,
+                            credential: prefix,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Registers hub delivering events of identifiers, which subscribe
+    /// with `Identifier::subscribe` or `Identifier::events`.
+    pub fn register_event_hub(&self) -> Result<Arc<EventHub<D>>, String> {
+        let hub = Arc::new(EventHub::new(self.kel.storage.clone()));
+        self.kel.notification_bus.register_observer(
+            hub.clone(),
+            vec![
+                JustNotification::KeyEventAccepted,
+                JustNotification::DuplicitousEvent,
+            ],
+        );
+        self.tel
+            .processor
+            .register_observer(
+                hub.clone(),
+                vec![TelNotificationKind::TelEventAdded],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(hub)
+    }
+}
+
+impl<D: EventDatabase> Identifier<D> {
+    /// Returns receiver of identifier's events published by `hub`.
+    pub fn subscribe(
+        &self,
+        hub: &EventHub<D>,
+    ) -> mpsc::Receiver<IdentifierEvent> {
+        let (sender, receiver) = mpsc::channel();
+        hub.subscribe(
+            &self.id,
+            Box::new(move |event| sender.send(event.clone()).is_ok()),
+        );
+        receiver
+    }
+
+    /// Returns stream of identifier's events published by `hub`.
+    #[cfg(feature = "async")]
+    pub fn events(
+        &self,
+        hub: &EventHub<D>,
+    ) -> impl futures::Stream<Item = IdentifierEvent> {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        hub.subscribe(
+            &self.id,
+            Box::new(move |event| sender.unbounded_send(event.clone()).is_ok()),
+        );
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{prefix::SelfSigningPrefix, signer::Signer};
+    use said::derivation::{HashFunction, HashFunctionCode};
+
+    use super::*;
+    use crate::{
+        test_utils::{incept, setup},
+        EncryptedFileKeyStore, IdentifierKeys, KeyStore,
+    };
+
+    #[test]
+    fn test_identifier_events() {
+        let (root, controller) = setup("test-db");
+        let hub = controller.register_event_hub().unwrap();
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let store = EncryptedFileKeyStore::new(root.path().join("keys.json"));
+        store.unlock("passphrase").unwrap();
+        let keys = IdentifierKeys::generate(1);
+        let sign = |keys: &IdentifierKeys, data: &[u8]| {
+            SelfSigningPrefix::Ed25519Sha512(
+                keys.signers().unwrap()[0].sign(data).unwrap(),
+            )
+        };
+        let icp = controller
+            .incept(
+                keys.public_keys().unwrap(),
+                keys.next_public_keys().unwrap(),
+            )
+            .unwrap();
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &sign(&keys, icp.as_bytes()))
+            .unwrap();
+        store.save(&identifier.id, &keys).unwrap();
+        let events = identifier.subscribe(&hub);
+
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&keys, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let (ixn, iss) = controller
+            .issue_credential(&identifier, &registry, br#"{"name":"Jo"}"#)
+            .unwrap();
+        let said = controller
+            .finalize_issue_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&keys, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert!(events.try_recv().is_err());
+
+        let (ixn, rev) = controller
+            .revoke_credential(&identifier, &registry, &said)
+            .unwrap();
+        controller
+            .finalize_revoke_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&keys, ixn.as_bytes()),
+                rev.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(
+            events.try_recv().unwrap(),
+            IdentifierEvent::CredentialRevoked {
+                registry,
+                credential: IdentifierPrefix::self_addressing(said),
+            }
+        );
+
+        identifier.rotate(&store).unwrap();
+        let rotated = store.load(&identifier.id).unwrap().unwrap();
+        assert_eq!(
+            events.try_recv().unwrap(),
+            IdentifierEvent::RotationAccepted {
+                sn: 4,
+                keys: rotated.public_keys().unwrap(),
+            }
+        );
+
+        // Two different interactions of the same sn.
+        let first = identifier.anchor(&[]).unwrap();
+        let digest =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"other");
+        let second = identifier.anchor(&[digest]).unwrap();
+        identifier
+            .finalize_anchor(first.as_bytes(), sign(&rotated, first.as_bytes()))
+            .unwrap();
+        // Receiving the same event again isn't duplicity.
+        let _ = identifier.finalize_anchor(
+            first.as_bytes(),
+            sign(&rotated, first.as_bytes()),
+        );
+        assert!(events.try_recv().is_err());
+        let _ = identifier.finalize_anchor(
+            second.as_bytes(),
+            sign(&rotated, second.as_bytes()),
+        );
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            IdentifierEvent::DuplicityDetected(_)
+        ));
+
+        // Events of other identifiers aren't delivered.
+        let other = Signer::new();
+        incept(&controller, &other);
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::{sync::Arc, thread, time::Duration};
+
+use keri_core::error::Error;
+use serde_json::{json, Value};
+
+use crate::remote_key::{
+    der_signature, remote_key_error, request_json, spki_key,
+    AccessTokenProvider, RemoteKeyRef, RemoteKeyService,
+};
+
+const API_URL: &str = "https://cloudkms.googleapis.com/v1/";
+/// Number of checks whether created key version was generated.
+const GENERATION_CHECKS: u32 = 10;
+const GENERATION_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Google Cloud KMS crypto key of `ASYMMETRIC_SIGN` purpose and
+/// `EC_SIGN_SECP256K1_SHA256` algorithm, used with its REST API. Every
+/// created key is new version of the crypto key, so key ids are crypto key
+/// version resource names. Access tokens have to be issued for
+/// `https://www.googleapis.com/auth/cloudkms` scope.
+pub struct GcpKms {
+    client: reqwest::blocking::Client,
+    crypto_key: String,
+    token: Arc<dyn AccessTokenProvider>,
+}
+
+impl GcpKms {
+    /// `crypto_key` is resource name of crypto key, i.e.
+    /// `projects/<project>/locations/<location>/keyRings/<key ring>/cryptoKeys/<key>`.
+    pub fn new(crypto_key: &str, token: Arc<dyn AccessTokenProvider>) -> Self {
+        Self {
+            client: reqwest::blocking::Client::default(),
+            crypto_key: crypto_key.to_string(),
+            token,
+        }
+    }
+
+    fn request(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+        body: Option<Value>,
+    ) -> Result<Value, Error> {
+        let token = self.token.access_token().map_err(remote_key_error)?;
+        request_json(request, &token, body)
+    }
+
+    /// Waits until key version is generated, which happens asynchronously.
+    fn wait_for_generation(&self, version: &str) -> Result<(), Error> {
+        for _ in 0..GENERATION_CHECKS {
+            let url = format!("{}{}", API_URL, version);
+            let state = self.request(self.client.get(url), None)?["state"]
+                .as_str()
+                .map(str::to_string);
+            match state.as_deref() {
+                Some("ENABLED") => return Ok(()),
+                Some("PENDING_GENERATION") => {
+                    thread::sleep(GENERATION_CHECK_INTERVAL)
+                }
+                _ => {
+                    return Err(remote_key_error(format!(
+                        "key version {} is not enabled",
+                        version
+                    )))
+                }
+            }
+        }
+        Err(remote_key_error(format!(
+            "key version {} not generated",
+            version
+        )))
+    }
+}
+
+impl RemoteKeyService for GcpKms {
+    fn create_key(&self) -> Result<RemoteKeyRef, Error> {
+        let url = format!("{}{}/cryptoKeyVersions", API_URL, self.crypto_key);
+        let version = self.request(self.client.post(url), Some(json!({})))?;
+        let name = version["name"]
+            .as_str()
+            .ok_or(remote_key_error("missing key version name"))?;
+        self.wait_for_generation(name)?;
+        self.get_key(name)
+    }
+
+    fn get_key(&self, id: &str) -> Result<RemoteKeyRef, Error> {
+        let url = format!("{}{}/publicKey", API_URL, id);
+        let public_key = self.request(self.client.get(url), None)?;
+        let pem = public_key["pem"]
+            .as_str()
+            .ok_or(remote_key_error("missing public key"))?;
+        Ok(RemoteKeyRef {
+            id: id.to_string(),
+            public_key: spki_key(&pem_decode(pem)?)?,
+        })
+    }
+
+    fn sign_digest(
+        &self,
+        key: &RemoteKeyRef,
+        digest: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let url = format!("{}{}:asymmetricSign", API_URL, key.id);
+        let body = json!({ "digest": { "sha256": base64::encode(digest) } });
+        let response = self
+            .request(self.client.post(url), Some(body))
+            .map_err(|e| {
+                log::warn!("Cloud KMS signing failed: {}", e);
+                Error::SigningError
+            })?;
+        let signature = response["signature"]
+            .as_str()
+            .and_then(|signature| base64::decode(signature).ok())
+            .ok_or(Error::SigningError)?;
+        der_signature(&signature)
+    }
+}
+
+/// Decodes PEM document, skipping its boundary lines.
+fn pem_decode(pem: &str) -> Result<Vec<u8>, Error> {
+    let encoded = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect::<String>();
+    base64::decode(encoded.trim()).map_err(remote_key_error)
+}
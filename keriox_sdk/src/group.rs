@@ -0,0 +1,894 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use keri_core::{
+    actor::{
+        event_generator, parse_event_stream, prelude::EventStorage,
+        MaterialPath,
+    },
+    database::{EscrowCreator, EscrowDatabase, EventDatabase},
+    event::{
+        event_data::EventData,
+        sections::{seal::Seal, threshold::SignatureThreshold},
+        KeyEvent,
+    },
+    event_message::{
+        cesr_adapter::{parse_event_type, EventType},
+        msg::KeriEvent,
+        signature::{Signature, SignerData},
+        signed_event_message::{Message, Notice, Op},
+    },
+    mailbox::exchange::{ForwardTopic, SignedExchange},
+    prefix::{
+        BasicPrefix, CesrPrimitive, IdentifierPrefix, IndexedSignature,
+        SelfSigningPrefix,
+    },
+    processor::Processor,
+    state::IdentifierState,
+};
+use said::SelfAddressingIdentifier;
+use teliox::database::TelEventDatabase;
+
+use crate::{
+    controller::{parse_key_event, serialize_event},
+    Controller, Identifier,
+};
+
+/// Time after which group event proposal that didn't collect enough
+/// signatures is considered abandoned.
+pub const DEFAULT_PROPOSAL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Group event signed by local member, waiting for signatures of other
+/// participants.
+struct Proposal {
+    digest: SelfAddressingIdentifier,
+    event: KeriEvent<KeyEvent>,
+    created: Instant,
+}
+
+/// Multisig identifier seen from the perspective of one of its members.
+pub struct GroupIdentifier<D: EventDatabase> {
+    pub id: IdentifierPrefix,
+    member: IdentifierPrefix,
+    event_storage: Arc<EventStorage<D>>,
+    proposals: Mutex<HashMap<u64, Proposal>>,
+    proposal_timeout: Duration,
+}
+
+impl<D: EventDatabase> GroupIdentifier<D> {
+    pub fn new(
+        id: IdentifierPrefix,
+        member: IdentifierPrefix,
+        event_storage: Arc<EventStorage<D>>,
+    ) -> Self {
+        Self {
+            id,
+            member,
+            event_storage,
+            proposals: Mutex::new(HashMap::new()),
+            proposal_timeout: DEFAULT_PROPOSAL_TIMEOUT,
+        }
+    }
+
+    pub fn with_proposal_timeout(mut self, timeout: Duration) -> Self {
+        self.proposal_timeout = timeout;
+        self
+    }
+
+    pub fn get_prefix(&self) -> &IdentifierPrefix {
+        &self.id
+    }
+
+    /// Returns local member of the group.
+    pub fn member(&self) -> &IdentifierPrefix {
+        &self.member
+    }
+
+    /// Group inception is accepted once enough members signed it.
+    pub fn is_established(&self) -> bool {
+        self.event_storage.get_state(&self.id).is_some()
+    }
+
+    pub fn get_state(&self) -> Option<IdentifierState> {
+        self.event_storage.get_state(&self.id)
+    }
+
+    /// Returns events signed by local member that are still waiting for
+    /// signatures of other participants.
+    pub fn pending_proposals(&self) -> Vec<KeriEvent<KeyEvent>> {
+        let mut proposals = self.proposals.lock().unwrap();
+        self.prune_accepted(&mut proposals);
+        let mut pending: Vec<_> = proposals
+            .values()
+            .filter(|proposal| !self.is_expired(proposal))
+            .map(|proposal| proposal.event.clone())
+            .collect();
+        pending.sort_by_key(|event| event.data.get_sn());
+        pending
+    }
+
+    /// Records event as signed by local member. Fails if different event
+    /// was already accepted or proposed at the same sn, so member never
+    /// signs two conflicting group events.
+    pub(crate) fn register_proposal(
+        &self,
+        event: &KeriEvent<KeyEvent>,
+    ) -> Result<(), String> {
+        if event.data.get_prefix() != self.id {
+            return Err("Event doesn't belong to the group".to_string());
+        }
+        let sn = event.data.get_sn();
+        let digest = event.digest().map_err(|e| e.to_string())?;
+        let accepted = self
+            .event_storage
+            .get_event_at_sn(&self.id, sn)
+            .and_then(|ev| ev.signed_event_message.event_message.digest().ok());
+        match accepted {
+            Some(accepted) if accepted != digest => {
+                return Err(format!(
+                    "Conflicting group event already accepted at sn {}",
+                    sn
+                ))
+            }
+            Some(_) => return Ok(()),
+            None => (),
+        }
+
+        let mut proposals = self.proposals.lock().unwrap();
+        match proposals.get(&sn) {
+            Some(proposal) if proposal.digest == digest => Ok(()),
+            Some(proposal) if !self.is_expired(proposal) => Err(format!(
+                "Conflicting group event proposal pending at sn {}",
+                sn
+            )),
+            _ => {
+                proposals.insert(
+                    sn,
+                    Proposal {
+                        digest,
+                        event: event.clone(),
+                        created: Instant::now(),
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes and returns proposals that weren't accepted before timeout.
+    pub(crate) fn take_expired(&self) -> Vec<KeriEvent<KeyEvent>> {
+        let mut proposals = self.proposals.lock().unwrap();
+        self.prune_accepted(&mut proposals);
+        let expired: Vec<u64> = proposals
+            .iter()
+            .filter(|(_, proposal)| self.is_expired(proposal))
+            .map(|(sn, _)| *sn)
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|sn| proposals.remove(&sn))
+            .map(|proposal| proposal.event)
+            .collect()
+    }
+
+    fn is_expired(&self, proposal: &Proposal) -> bool {
+        proposal.created.elapsed() >= self.proposal_timeout
+    }
+
+    fn prune_accepted(&self, proposals: &mut HashMap<u64, Proposal>) {
+        if let Some(state) = self.get_state() {
+            proposals.retain(|sn, _| *sn > state.sn);
+        }
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Generates group inception event from current and next keys of
+    /// `member` and all `participants`, whose KELs need to be known.
+    /// Returns serialized group icp and exchange messages that forward it to
+    /// other participants. Both should be signed by `member`.
+    pub fn incept_group(
+        &self,
+        member: &Identifier<D>,
+        participants: Vec<IdentifierPrefix>,
+        threshold: SignatureThreshold,
+        next_threshold: SignatureThreshold,
+        witnesses: Vec<BasicPrefix>,
+        witness_threshold: u64,
+    ) -> Result<(String, Vec<String>), String> {
+        let mut public_keys = vec![];
+        let mut next_keys_hashes = vec![];
+        for id in std::iter::once(&member.id).chain(participants.iter()) {
+            let state = self
+                .kel
+                .storage
+                .get_state(id)
+                .ok_or(format!("Unknown participant {}", id.to_str()))?;
+            public_keys.extend(state.current.public_keys);
+            next_keys_hashes
+                .extend(state.current.next_keys_data.next_keys_hashes());
+        }
+
+        let icp = event_generator::incept_with_next_hashes(
+            public_keys,
+            &threshold,
+            next_keys_hashes,
+            &next_threshold,
+            witnesses,
+            witness_threshold,
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        let serialized_icp = String::from_utf8(
+            icp.encode()
+                .map_err(|_| "Event encoding error".to_string())?,
+        )
+        .map_err(|_| "Event format error".to_string())?;
+        let exchanges = self.group_exchanges(&icp, &participants)?;
+
+        Ok((serialized_icp, exchanges))
+    }
+
+    /// Generates exchange messages that forward group event to
+    /// `participants`.
+    pub fn group_exchanges(
+        &self,
+        event: &KeriEvent<KeyEvent>,
+        participants: &[IdentifierPrefix],
+    ) -> Result<Vec<String>, String> {
+        participants
+            .iter()
+            .map(|id| {
+                let exn = event_generator::exchange(
+                    id,
+                    event,
+                    ForwardTopic::Multisig,
+                )
+                .encode()
+                .map_err(|_| "Event encoding error".to_string())?;
+                String::from_utf8(exn)
+                    .map_err(|_| "Event format error".to_string())
+            })
+            .collect()
+    }
+
+    /// Adds `member` signature to group inception event. Event is accepted
+    /// once signatures of enough members are collected. Returns indexed
+    /// signature to be attached to exchange messages.
+    pub fn finalize_group_incept(
+        &self,
+        member: &Identifier<D>,
+        event: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<(GroupIdentifier<D>, IndexedSignature), String> {
+        let icp = parse_key_event(event)?;
+        if !matches!(icp.data.get_event_data(), EventData::Icp(_)) {
+            return Err("Event is not an inception".to_string());
+        }
+        self.track_pending(&icp.data.get_prefix())?;
+        let signature = self.finalize_group_event(member, &icp, sig)?;
+        let group = GroupIdentifier::new(
+            icp.data.get_prefix(),
+            member.id.clone(),
+            self.kel.storage.clone(),
+        );
+        Ok((group, signature))
+    }
+
+    /// Loads group identifier whose KEL is known, as seen by `member`.
+    pub fn load_group(
+        &self,
+        id: &IdentifierPrefix,
+        member: &Identifier<D>,
+    ) -> Result<GroupIdentifier<D>, String> {
+        let state = self
+            .kel
+            .storage
+            .get_state(id)
+            .ok_or("No KEL found for the group".to_string())?;
+        let own_keys = self
+            .kel
+            .storage
+            .get_state(&member.id)
+            .ok_or("Unknown identifier".to_string())?
+            .current
+            .public_keys;
+        if !own_keys
+            .iter()
+            .any(|key| state.current.public_keys.contains(key))
+        {
+            return Err("Identifier is not a group participant".to_string());
+        }
+        Ok(GroupIdentifier::new(
+            id.clone(),
+            member.id.clone(),
+            self.kel.storage.clone(),
+        ))
+    }
+
+    /// Generates group rotation event from current and next keys of
+    /// `member` and `participants`. Participants are expected to rotate
+    /// their own KELs first, so their current keys are exposed next keys of
+    /// the group. Returns serialized rotation and exchange messages that
+    /// forward it to other participants.
+    pub fn propose_group_rotation(
+        &self,
+        group: &GroupIdentifier<D>,
+        member: &Identifier<D>,
+        participants: Vec<IdentifierPrefix>,
+        threshold: SignatureThreshold,
+        next_threshold: SignatureThreshold,
+    ) -> Result<(String, Vec<String>), String> {
+        let state = group
+            .get_state()
+            .ok_or("Group is not established".to_string())?;
+        let mut public_keys = vec![];
+        let mut next_keys_hashes = vec![];
+        for id in std::iter::once(&member.id).chain(participants.iter()) {
+            let state = self
+                .kel
+                .storage
+                .get_state(id)
+                .ok_or(format!("Unknown participant {}", id.to_str()))?;
+            public_keys.extend(state.current.public_keys);
+            next_keys_hashes
+                .extend(state.current.next_keys_data.next_keys_hashes());
+        }
+
+        let rot = event_generator::partial_rotate(
+            state,
+            public_keys,
+            &threshold,
+            next_keys_hashes,
+            &next_threshold,
+        )
+        .map_err(|e| e.to_string())?;
+        let exchanges = self.group_exchanges(&rot, &participants)?;
+        Ok((serialize_event(&rot)?, exchanges))
+    }
+
+    /// Generates group interaction event anchoring `seals`. Returns
+    /// serialized interaction and exchange messages that forward it to
+    /// `participants`.
+    pub fn propose_group_interaction(
+        &self,
+        group: &GroupIdentifier<D>,
+        participants: Vec<IdentifierPrefix>,
+        seals: Vec<Seal>,
+    ) -> Result<(String, Vec<String>), String> {
+        let state = group
+            .get_state()
+            .ok_or("Group is not established".to_string())?;
+        let ixn = event_generator::anchor_with_seal(state, &seals)
+            .map_err(|e| e.to_string())?;
+        let exchanges = self.group_exchanges(&ixn, &participants)?;
+        Ok((serialize_event(&ixn)?, exchanges))
+    }
+
+    /// Adds `member` signature to proposed group rotation or interaction.
+    /// Refuses to sign event conflicting with one already accepted or
+    /// pending at the same sn. Once threshold is met the event is accepted
+    /// and, if witness publisher watches the group, sent to its witnesses.
+    /// Returns indexed signature to be attached to exchange messages.
+    pub fn finalize_group_proposal(
+        &self,
+        group: &GroupIdentifier<D>,
+        member: &Identifier<D>,
+        event: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<IndexedSignature, String> {
+        let event = parse_key_event(event)?;
+        match event.data.get_event_data() {
+            EventData::Rot(_) | EventData::Ixn(_) => (),
+            _ => {
+                return Err("Event is not a rotation or interaction".to_string())
+            }
+        }
+        group.register_proposal(&event)?;
+        self.finalize_group_event(member, &event, sig)
+    }
+
+    /// Removes proposals that didn't collect enough signatures within
+    /// group timeout, together with their partially signed escrow
+    /// entries. Returns dropped events, so they can be proposed again.
+    pub fn drop_expired_proposals(
+        &self,
+        group: &GroupIdentifier<D>,
+    ) -> Result<Vec<String>, String> {
+        group
+            .take_expired()
+            .into_iter()
+            .map(|event| {
+                self.kel
+                    .escrows
+                    .partially_signed
+                    .escrowed_partially_signed
+                    .remove(&event);
+                serialize_event(&event)
+            })
+            .collect()
+    }
+
+    /// Attaches `member` signature of exchange message and its signature of
+    /// forwarded group event. Returns CESR stream to be delivered to the
+    /// recipient, e.g. through witness mailbox.
+    pub fn finalize_group_exchange(
+        &self,
+        member: &Identifier<D>,
+        exchange: &[u8],
+        exn_sig: SelfSigningPrefix,
+        data_signature: IndexedSignature,
+    ) -> Result<Vec<u8>, String> {
+        let exn = match parse_event_type(exchange)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::Exn(exn) => exn,
+            _ => return Err("Event is not an exchange".to_string()),
+        };
+        let signed_exn = Message::Op(Op::Exchange(SignedExchange {
+            exchange_message: exn,
+            signature: vec![Signature::Transferable(
+                SignerData::LastEstablishment(member.id.clone()),
+                vec![IndexedSignature::new_both_same(exn_sig, 0)],
+            )],
+            data_signature: (
+                MaterialPath::to_path("-a".into()),
+                vec![Signature::Transferable(
+                    SignerData::JustSignatures,
+                    vec![data_signature],
+                )],
+            ),
+        }));
+        signed_exn.to_cesr().map_err(|e| e.to_string())
+    }
+
+    /// Processes exchange messages forwarding group events from other
+    /// participants. Signatures they carry are collected until group
+    /// threshold is met. Returns forwarded events, so they can be reviewed
+    /// and signed by local member.
+    pub fn process_group_exchange(
+        &self,
+        stream: &[u8],
+    ) -> Result<Vec<String>, String> {
+        let messages = parse_event_stream(stream).map_err(|e| e.to_string())?;
+        let mut events = vec![];
+        for message in messages {
+            let exn = match message {
+                Message::Op(Op::Exchange(exn)) => exn,
+                _ => return Err("Message is not an exchange".to_string()),
+            };
+            if !exn
+                .verify(self.kel.storage.as_ref())
+                .map_err(|e| e.to_string())?
+            {
+                return Err("Wrong exchange signature".to_string());
+            }
+            let exchange = &exn.exchange_message.data.data;
+            if !exchange.is_multisig() {
+                return Err("Exchange is not a multisig request".to_string());
+            }
+            let to_forward = exchange
+                .event()
+                .ok_or("Exchange carries no event".to_string())?
+                .clone();
+            // Only group participants can propose group events.
+            for signature in &exn.signature {
+                let signer = signature
+                    .get_signer()
+                    .ok_or("Unknown exchange signer".to_string())?;
+                self.member_index(&signer, &to_forward)?;
+            }
+            let signatures = exn
+                .data_signature
+                .1
+                .into_iter()
+                .filter_map(|sig| match sig {
+                    Signature::Transferable(_, sigs) => Some(sigs),
+                    Signature::NonTransferable(_) => None,
+                })
+                .flatten()
+                .collect();
+            let signed_event = to_forward.sign(signatures, None, None);
+            self.kel
+                .processor
+                .process_notice(&Notice::Event(signed_event))
+                .map_err(|e| e.to_string())?;
+            events.push(
+                String::from_utf8(
+                    to_forward
+                        .encode()
+                        .map_err(|_| "Event encoding error".to_string())?,
+                )
+                .map_err(|_| "Event format error".to_string())?,
+            );
+        }
+        Ok(events)
+    }
+
+    /// Returns signatures of group event collected so far.
+    pub fn collected_signatures(
+        &self,
+        event: &[u8],
+    ) -> Result<Vec<IndexedSignature>, String> {
+        let event = parse_key_event(event)?;
+        let id = event.data.get_prefix();
+        let sn = event.data.get_sn();
+        let digest = event.digest().map_err(|e| e.to_string())?;
+        let accepted = self
+            .kel
+            .storage
+            .get_event_at_sn(&id, sn)
+            .map(|ev| ev.signed_event_message)
+            .filter(|ev| {
+                ev.event_message.digest().ok() == Some(digest.clone())
+            });
+        if let Some(accepted) = accepted {
+            return Ok(accepted.signatures);
+        }
+        let escrowed = self
+            .kel
+            .escrows
+            .partially_signed
+            .escrowed_partially_signed
+            .get(&id, sn)
+            .map_err(|_| "Escrow error".to_string())?
+            .filter(|ev| ev.event_message == event)
+            .flat_map(|ev| ev.signatures)
+            .fold(vec![], |mut acc, sig| {
+                if !acc.contains(&sig) {
+                    acc.push(sig);
+                }
+                acc
+            });
+        Ok(escrowed)
+    }
+
+    /// Signs group event as `member` and processes it.
+    pub(crate) fn finalize_group_event(
+        &self,
+        member: &Identifier<D>,
+        event: &KeriEvent<KeyEvent>,
+        sig: SelfSigningPrefix,
+    ) -> Result<IndexedSignature, String> {
+        let index = self.member_index(&member.id, event)?;
+        let signature = IndexedSignature::new_both_same(sig, index as u16);
+        self.kel
+            .processor
+            .process_notice(&Notice::Event(event.sign(
+                vec![signature.clone()],
+                None,
+                None,
+            )))
+            .map_err(|e| e.to_string())?;
+        Ok(signature)
+    }
+
+    /// Finds position of `member` key in keys of group event.
+    fn member_index(
+        &self,
+        member: &IdentifierPrefix,
+        event: &KeriEvent<KeyEvent>,
+    ) -> Result<usize, String> {
+        let member_state = self
+            .kel
+            .storage
+            .get_state(member)
+            .ok_or("Unknown identifier".to_string())?;
+        let own_key = member_state
+            .current
+            .public_keys
+            .first()
+            .ok_or("Identifier has no keys".to_string())?;
+        let own_next = member_state
+            .current
+            .next_keys_data
+            .next_keys_hashes()
+            .first()
+            .cloned();
+        let by_next_key = |keys: &[BasicPrefix]| {
+            own_next.as_ref().and_then(|next| {
+                keys.iter()
+                    .position(|pk| next.verify_binding(pk.to_str().as_bytes()))
+            })
+        };
+        let index = match event.data.get_event_data() {
+            EventData::Icp(icp) => icp
+                .key_config
+                .public_keys
+                .iter()
+                .position(|pk| pk == own_key),
+            EventData::Dip(dip) => dip
+                .inception_data
+                .key_config
+                .public_keys
+                .iter()
+                .position(|pk| pk == own_key),
+            EventData::Rot(rot) => by_next_key(&rot.key_config.public_keys),
+            EventData::Drt(drt) => by_next_key(&drt.key_config.public_keys),
+            EventData::Ixn(_) => self
+                .kel
+                .storage
+                .get_state(&event.data.get_prefix())
+                .and_then(|state| {
+                    state
+                        .current
+                        .public_keys
+                        .iter()
+                        .position(|pk| pk == own_key)
+                }),
+        };
+        index.ok_or("Identifier is not a group participant".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        event::sections::{seal::DigestSeal, threshold::SignatureThreshold},
+        signer::Signer,
+    };
+    use said::derivation::{HashFunction, HashFunctionCode};
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_group_incept() {
+        let (_root, controller) = setup("test-db");
+
+        let (alice_signer, bob_signer) = (Signer::new(), Signer::new());
+        let alice = incept(&controller, &alice_signer);
+        let bob = incept(&controller, &bob_signer);
+
+        let (icp, exchanges) = controller
+            .incept_group(
+                &alice,
+                vec![bob.id.clone()],
+                SignatureThreshold::Simple(2),
+                SignatureThreshold::Simple(2),
+                vec![],
+                0,
+            )
+            .unwrap();
+        assert_eq!(exchanges.len(), 1);
+
+        let (group, alice_signature) = controller
+            .finalize_group_incept(
+                &alice,
+                icp.as_bytes(),
+                sign(&alice_signer, icp.as_bytes()),
+            )
+            .unwrap();
+        assert!(!group.is_established());
+
+        let exn = exchanges[0].as_bytes();
+        let signed_exn = controller
+            .finalize_group_exchange(
+                &alice,
+                exn,
+                sign(&alice_signer, exn),
+                alice_signature,
+            )
+            .unwrap();
+
+        // Bob reviews forwarded event and adds his signature.
+        let forwarded = controller.process_group_exchange(&signed_exn).unwrap();
+        assert_eq!(forwarded, vec![icp.clone()]);
+        assert_eq!(
+            controller
+                .collected_signatures(icp.as_bytes())
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let (group, _) = controller
+            .finalize_group_incept(
+                &bob,
+                icp.as_bytes(),
+                sign(&bob_signer, icp.as_bytes()),
+            )
+            .unwrap();
+        assert!(group.is_established());
+        assert_eq!(
+            controller
+                .collected_signatures(icp.as_bytes())
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_group_interaction_conflict() {
+        let (_root, controller) = setup("test-db");
+
+        let (alice_signer, bob_signer) = (Signer::new(), Signer::new());
+        let alice = incept(&controller, &alice_signer);
+        let bob = incept(&controller, &bob_signer);
+
+        let (icp, _) = controller
+            .incept_group(
+                &alice,
+                vec![bob.id.clone()],
+                SignatureThreshold::Simple(2),
+                SignatureThreshold::Simple(2),
+                vec![],
+                0,
+            )
+            .unwrap();
+        let (alice_group, _) = controller
+            .finalize_group_incept(
+                &alice,
+                icp.as_bytes(),
+                sign(&alice_signer, icp.as_bytes()),
+            )
+            .unwrap();
+        let (bob_group, _) = controller
+            .finalize_group_incept(
+                &bob,
+                icp.as_bytes(),
+                sign(&bob_signer, icp.as_bytes()),
+            )
+            .unwrap();
+        assert!(alice_group.is_established());
+
+        let seal = |data: &[u8]| {
+            Seal::Digest(DigestSeal::new(
+                HashFunction::from(HashFunctionCode::Blake3_256).derive(data),
+            ))
+        };
+        let (ixn, _) = controller
+            .propose_group_interaction(
+                &alice_group,
+                vec![bob.id.clone()],
+                vec![seal(b"first")],
+            )
+            .unwrap();
+        controller
+            .finalize_group_proposal(
+                &alice_group,
+                &alice,
+                ixn.as_bytes(),
+                sign(&alice_signer, ixn.as_bytes()),
+            )
+            .unwrap();
+        assert_eq!(alice_group.pending_proposals().len(), 1);
+
+        // Alice refuses to sign another event at the same sn.
+        let (conflicting, _) = controller
+            .propose_group_interaction(
+                &alice_group,
+                vec![bob.id.clone()],
+                vec![seal(b"second")],
+            )
+            .unwrap();
+        assert!(controller
+            .finalize_group_proposal(
+                &alice_group,
+                &alice,
+                conflicting.as_bytes(),
+                sign(&alice_signer, conflicting.as_bytes()),
+            )
+            .is_err());
+
+        controller
+            .finalize_group_proposal(
+                &bob_group,
+                &bob,
+                ixn.as_bytes(),
+                sign(&bob_signer, ixn.as_bytes()),
+            )
+            .unwrap();
+        assert_eq!(alice_group.get_state().unwrap().sn, 1);
+        assert!(alice_group.pending_proposals().is_empty());
+
+        // Proposal that doesn't collect signatures in time is dropped.
+        let alice_group = controller
+            .load_group(&alice_group.id, &alice)
+            .unwrap()
+            .with_proposal_timeout(Duration::ZERO);
+        let (ixn, _) = controller
+            .propose_group_interaction(
+                &alice_group,
+                vec![bob.id.clone()],
+                vec![seal(b"third")],
+            )
+            .unwrap();
+        controller
+            .finalize_group_proposal(
+                &alice_group,
+                &alice,
+                ixn.as_bytes(),
+                sign(&alice_signer, ixn.as_bytes()),
+            )
+            .unwrap();
+        assert!(alice_group.pending_proposals().is_empty());
+        assert_eq!(
+            controller.drop_expired_proposals(&alice_group).unwrap(),
+            vec![ixn.clone()]
+        );
+        assert!(controller
+            .collected_signatures(ixn.as_bytes())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_reject_foreign_group_exchange() {
+        let (_root, controller) = setup("test-db");
+
+        let (alice_signer, bob_signer, carol_signer) =
+            (Signer::new(), Signer::new(), Signer::new());
+        let alice = incept(&controller, &alice_signer);
+        let bob = incept(&controller, &bob_signer);
+        let carol = incept(&controller, &carol_signer);
+
+        let (icp, exchanges) = controller
+            .incept_group(
+                &alice,
+                vec![bob.id.clone()],
+                SignatureThreshold::Simple(2),
+                SignatureThreshold::Simple(2),
+                vec![],
+                0,
+            )
+            .unwrap();
+        let (_group, alice_signature) = controller
+            .finalize_group_incept(
+                &alice,
+                icp.as_bytes(),
+                sign(&alice_signer, icp.as_bytes()),
+            )
+            .unwrap();
+        let exn = exchanges[0].as_bytes();
+
+        // Carol is not a group participant.
+        let carol_exn = controller
+            .finalize_group_exchange(
+                &carol,
+                exn,
+                sign(&carol_signer, exn),
+                alice_signature.clone(),
+            )
+            .unwrap();
+        assert!(controller.process_group_exchange(&carol_exn).is_err());
+
+        // Exchange without signatures is rejected.
+        let signed_exn = controller
+            .finalize_group_exchange(
+                &alice,
+                exn,
+                sign(&alice_signer, exn),
+                alice_signature,
+            )
+            .unwrap();
+        let unsigned_exn =
+            match parse_event_stream(&signed_exn).unwrap().pop().unwrap() {
+                Message::Op(Op::Exchange(exn)) => {
+                    Message::Op(Op::Exchange(SignedExchange {
+                        signature: vec![],
+                        ..exn
+                    }))
+                }
+                _ => unreachable!(),
+            };
+        assert!(controller
+            .process_group_exchange(&unsigned_exn.to_cesr().unwrap())
+            .is_err());
+        assert_eq!(
+            controller
+                .collected_signatures(icp.as_bytes())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use keri_core::{
+    database::redb::RedbDatabase, event_message::signed_event_message::Message,
+    oobi_manager::OobiManager, prefix::IdentifierPrefix,
+};
+use teliox::database::redb::RedbTelDatabase;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::{oobi::HttpOobiFetcher, Controller};
+
+use self::proto::{
+    agent_server::{Agent, AgentServer},
+    CredentialReply, IdentifierReply, IdentifierRequest, IdentifiersReply,
+    InceptRequest, KelReply, ListIdentifiersRequest, ResolveOobiRequest,
+    SignReply, SignRequest, VerifyReply, VerifyRequest,
+};
+
+/// Messages and service stubs generated from `proto/agent.proto`. Clients
+/// in other languages are generated from the same file.
+pub mod proto {
+    tonic::include_proto!("keriox.agent.v1");
+}
+
+type AgentController = Controller<RedbDatabase, RedbTelDatabase>;
+
+/// gRPC service exposing operations of controller, whose identifiers' keys
+/// are kept in its key store. Controller is shared by all clients, so key
+/// store should be unlocked before serving. Controller operations block,
+/// so they are run on blocking threads of the runtime.
+pub struct AgentService {
+    controller: Arc<AgentController>,
+}
+
+impl AgentService {
+    pub fn new(controller: Arc<AgentController>) -> Self {
+        Self { controller }
+    }
+
+    /// Serves agent on `addr` until the server fails.
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), String> {
+        Server::builder()
+            .add_service(AgentServer::new(self))
+            .serve(addr)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn run<R: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&AgentController) -> Result<R, String>
+            + Send
+            + 'static,
+    ) -> Result<Response<R>, Status> {
+        let controller = self.controller.clone();
+        tokio::task::spawn_blocking(move || operation(&controller))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map(Response::new)
+            .map_err(Status::failed_precondition)
+    }
+}
+
+#[tonic::async_trait]
+impl Agent for AgentService {
+    async fn incept(
+        &self,
+        request: Request<InceptRequest>,
+    ) -> Result<Response<IdentifierReply>, Status> {
+        let name = request.into_inner().name;
+        self.run(move |controller| {
+            let identifier = controller.incept_with_keystore()?;
+            let identifier = controller.manage(identifier)?;
+            if let Some(name) = name {
+                controller.set_identifier_name(&identifier.id, &name)?;
+            }
+            Ok(IdentifierReply {
+                id: identifier.id.to_string(),
+            })
+        })
+        .await
+    }
+
+    async fn rotate(
+        &self,
+        request: Request<IdentifierRequest>,
+    ) -> Result<Response<IdentifierReply>, Status> {
+        let id = parse_prefix(&request.into_inner().id)?;
+        self.run(move |controller| {
+            let keystore = controller
+                .keystore()
+                .ok_or("No keystore configured".to_string())?;
+            controller.identifier(&id)?.rotate(keystore)?;
+            Ok(IdentifierReply { id: id.to_string() })
+        })
+        .await
+    }
+
+    async fn list_identifiers(
+        &self,
+        _request: Request<ListIdentifiersRequest>,
+    ) -> Result<Response<IdentifiersReply>, Status> {
+        self.run(|controller| {
+            Ok(IdentifiersReply {
+                ids: controller
+                    .identifiers()?
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+            })
+        })
+        .await
+    }
+
+    async fn get_kel(
+        &self,
+        request: Request<IdentifierRequest>,
+    ) -> Result<Response<KelReply>, Status> {
+        let id = parse_prefix(&request.into_inner().id)?;
+        self.run(move |controller| {
+            let kel = controller
+                .kel
+                .storage
+                .get_kel_messages_with_receipts_all(&id)
+                .map_err(|e| e.to_string())?
+                .ok_or(format!("No KEL of {}", id))?
+                .into_iter()
+                .map(|notice| Message::Notice(notice).to_cesr())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Ok(KelReply { kel: kel.concat() })
+        })
+        .await
+    }
+
+    async fn sign(
+        &self,
+        request: Request<SignRequest>,
+    ) -> Result<Response<SignReply>, Status> {
+        let SignRequest { id, data } = request.into_inner();
+        let id = parse_prefix(&id)?;
+        self.run(move |controller| {
+            let identifier = controller.identifier(&id)?;
+            let sig = controller.sign_with_keystore(&id, &data)?;
+            Ok(SignReply {
+                stream: identifier.sign_data(&data, sig)?,
+            })
+        })
+        .await
+    }
+
+    async fn verify(
+        &self,
+        request: Request<VerifyRequest>,
+    ) -> Result<Response<VerifyReply>, Status> {
+        let stream = request.into_inner().stream;
+        self.run(move |controller| {
+            let (signer, data) = controller.verify_signed_data(&stream)?;
+            Ok(VerifyReply {
+                signer: signer.to_string(),
+                data,
+            })
+        })
+        .await
+    }
+
+    async fn resolve_oobi(
+        &self,
+        request: Request<ResolveOobiRequest>,
+    ) -> Result<Response<IdentifierReply>, Status> {
+        let url = request.into_inner().url;
+        self.run(move |controller| {
+            let resolver = controller.kel.oobi_resolver(
+                Arc::new(OobiManager::new(
+                    controller.kel.storage.events_db.clone(),
+                )),
+                Arc::new(HttpOobiFetcher::new()),
+            );
+            Ok(IdentifierReply {
+                id: resolver.resolve_oobi(&url)?.to_string(),
+            })
+        })
+        .await
+    }
+
+    async fn verify_credential(
+        &self,
+        request: Request<VerifyRequest>,
+    ) -> Result<Response<CredentialReply>, Status> {
+        let stream = request.into_inner().stream;
+        self.run(move |controller| {
+            let acdc = controller.verify_credential(&stream)?;
+            Ok(CredentialReply {
+                acdc: String::from_utf8(acdc.encode()?)
+                    .map_err(|e| e.to_string())?,
+            })
+        })
+        .await
+    }
+}
+
+fn parse_prefix(id: &str) -> Result<IdentifierPrefix, Status> {
+    id.parse().map_err(|_| {
+        Status::invalid_argument(format!("Invalid identifier {}", id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+    use tonic::Code;
+
+    use super::*;
+    use crate::keystore::EncryptedFileKeyStore;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_agent_service() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let controller = Controller::builder()
+            .with_db_path(root.path().join("db"))
+            .with_keystore(Arc::new(EncryptedFileKeyStore::new(
+                root.path().join("keys.json"),
+            )))
+            .build()
+            .unwrap();
+        controller.unlock("passphrase").unwrap();
+        let agent = AgentService::new(Arc::new(controller));
+
+        let id = agent
+            .incept(Request::new(InceptRequest {
+                name: Some("alice".to_string()),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .id;
+        let ids = agent
+            .list_identifiers(Request::new(ListIdentifiersRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .ids;
+        assert_eq!(ids, vec![id.clone()]);
+
+        let signed = agent
+            .sign(Request::new(SignRequest {
+                id: id.clone(),
+                data: br#"{"hello":"world"}"#.to_vec(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .stream;
+        agent
+            .rotate(Request::new(IdentifierRequest { id: id.clone() }))
+            .await
+            .unwrap();
+        let verified = agent
+            .verify(Request::new(VerifyRequest { stream: signed }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(verified.signer, id);
+        assert_eq!(verified.data, br#"{"hello":"world"}"#.to_vec());
+
+        let kel = agent
+            .get_kel(Request::new(IdentifierRequest { id: id.clone() }))
+            .await
+            .unwrap()
+            .into_inner()
+            .kel;
+        assert!(!kel.is_empty());
+
+        let status = agent
+            .rotate(Request::new(IdentifierRequest {
+                id: "not an identifier".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let status = agent
+            .verify(Request::new(VerifyRequest {
+                stream: b"not signed".to_vec(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+    }
+}
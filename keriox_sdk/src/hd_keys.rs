@@ -0,0 +1,167 @@
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac, NewMac};
+use keri_core::{keys::SecretSeed, prefix::SeedPrefix, signer::Signer};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+use crate::keystore::IdentifierKeys;
+
+/// HMAC key of SLIP-0010 master key derivation for Ed25519 curve.
+const ED25519_CURVE: &[u8] = b"ed25519 seed";
+/// Offset of hardened child indexes, the only ones SLIP-0010 defines for
+/// Ed25519.
+const HARDENED: u32 = 0x8000_0000;
+
+/// Derives Ed25519 keys of identifiers from BIP39 mnemonic, following
+/// SLIP-0010. Key `key` established by `rotation`-th establishment event
+/// of identifier `account` is derived along hardened path
+/// `m/account'/rotation'/key'`, where inception is rotation 0. Mnemonic
+/// is all that needs to be backed up to recover every key.
+pub struct HdKeyManager {
+    seed: Zeroizing<[u8; 64]>,
+}
+
+impl HdKeyManager {
+    /// Generates new 24 words mnemonic. Returns it along with key manager,
+    /// whose seed is protected with `passphrase`.
+    pub fn generate(passphrase: &str) -> Result<(Self, String), String> {
+        let mnemonic = Mnemonic::generate(24).map_err(|e| e.to_string())?;
+        let manager = Self::from_mnemonic(&mnemonic.to_string(), passphrase)?;
+        Ok((manager, mnemonic.to_string()))
+    }
+
+    /// Restores key manager from English BIP39 mnemonic and passphrase.
+    pub fn from_mnemonic(
+        mnemonic: &str,
+        passphrase: &str,
+    ) -> Result<Self, String> {
+        let mnemonic = Mnemonic::parse(mnemonic).map_err(|e| e.to_string())?;
+        Ok(Self {
+            seed: Zeroizing::new(mnemonic.to_seed(passphrase)),
+        })
+    }
+
+    /// Returns seed of key `key` established by `rotation`.
+    pub fn seed(&self, account: u32, rotation: u32, key: u32) -> SecretSeed {
+        let (private_key, _) =
+            derive(&self.seed[..], &[account, rotation, key]);
+        SeedPrefix::RandomSeed256Ed25519(private_key.to_vec()).into()
+    }
+
+    /// Returns signer of key `key` established by `rotation`.
+    pub fn signer(
+        &self,
+        account: u32,
+        rotation: u32,
+        key: u32,
+    ) -> Result<Signer, String> {
+        Signer::new_with_seed(&self.seed(account, rotation, key))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns `count` current keys established by `rotation` along with
+    /// next keys, to be established by the following rotation. Keys of
+    /// earlier rotations aren't listed as retired, as their derivation paths
+    /// are never used again.
+    pub fn keys(
+        &self,
+        account: u32,
+        rotation: u32,
+        count: u32,
+    ) -> IdentifierKeys {
+        let seeds = |rotation| {
+            (0..count)
+                .map(|key| self.seed(account, rotation, key))
+                .collect()
+        };
+        IdentifierKeys {
+            current: seeds(rotation),
+            next: seeds(rotation + 1),
+            retired: vec![],
+        }
+    }
+}
+
+/// Derives private key and chain code along path of hardened indexes.
+fn derive(
+    seed: &[u8],
+    path: &[u32],
+) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 32]>) {
+    let master = hmac_sha512(ED25519_CURVE, &[seed]);
+    path.iter().fold(master, |(key, chain_code), index| {
+        hmac_sha512(
+            &chain_code[..],
+            &[&[0], &key[..], &(index | HARDENED).to_be_bytes()],
+        )
+    })
+}
+
+/// Splits HMAC-SHA512 of concatenated `data` into key and chain code.
+fn hmac_sha512(
+    key: &[u8],
+    data: &[&[u8]],
+) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 32]>) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key)
+        .expect("HMAC accepts keys of any length");
+    data.iter().for_each(|data| mac.update(data));
+    let mut output = Zeroizing::new([0u8; 64]);
+    output.copy_from_slice(&mac.finalize().into_bytes());
+    let (mut left, mut right) =
+        (Zeroizing::new([0u8; 32]), Zeroizing::new([0u8; 32]));
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(encoded: &str) -> Vec<u8> {
+        (0..encoded.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&encoded[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_hd_keys() {
+        // Test vectors of BIP39 and SLIP-0010.
+        let manager = HdKeyManager::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon about",
+            "TREZOR",
+        )
+        .unwrap();
+        assert_eq!(
+            manager.seed.to_vec(),
+            hex(concat!(
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553",
+                "1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            ))
+        );
+        let (key, chain_code) =
+            derive(&hex("000102030405060708090a0b0c0d0e0f"), &[]);
+        assert_eq!(
+            key.to_vec(),
+            hex("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7")
+        );
+        assert_eq!(
+            chain_code.to_vec(),
+            hex("90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb")
+        );
+
+        // Next keys of one rotation are current keys of the following one.
+        let keys = manager.keys(0, 0, 2);
+        let rotated = manager.keys(0, 1, 2);
+        assert_eq!(keys.next, rotated.current);
+        assert_ne!(keys.current[0], keys.current[1]);
+        assert_ne!(manager.keys(1, 0, 2), keys);
+
+        // Keys are recovered from the same mnemonic.
+        let (generated, mnemonic) = HdKeyManager::generate("").unwrap();
+        let restored = HdKeyManager::from_mnemonic(&mnemonic, "").unwrap();
+        assert_eq!(generated.keys(3, 5, 1), restored.keys(3, 5, 1));
+        assert!(HdKeyManager::from_mnemonic("abandon about", "").is_err());
+    }
+}
@@ -0,0 +1,429 @@
+use std::sync::Arc;
+
+use keri_core::{
+    actor::prelude::EventStorage,
+    database::{EscrowCreator, EventDatabase},
+    error::Error,
+    event::{event_data::EventData, sections::threshold::SignatureThreshold},
+    event_message::{
+        signature::Nontransferable,
+        signed_event_message::{
+            SignedEventMessage, SignedNontransferableReceipt,
+        },
+    },
+    prefix::{BasicPrefix, IdentifierPrefix},
+    processor::notification::{
+        AcceptedEvent, JustNotification, Notification, NotificationBus,
+        Notifier,
+    },
+};
+use said::SelfAddressingIdentifier;
+use teliox::database::TelEventDatabase;
+
+use crate::Controller;
+
+/// Accepted rotation of identifier's keys.
+#[derive(Debug, Clone)]
+pub struct RotationContext {
+    pub id: IdentifierPrefix,
+    pub sn: u64,
+    pub previous_keys: Vec<BasicPrefix>,
+    pub keys: Vec<BasicPrefix>,
+    pub threshold: SignatureThreshold,
+    /// Witnesses after rotation.
+    pub witnesses: Vec<BasicPrefix>,
+    pub event: SignedEventMessage,
+}
+
+/// Witness receipt of event that waits for receipts, before it is
+/// accepted.
+#[derive(Debug, Clone)]
+pub struct WitnessReceiptContext {
+    pub id: IdentifierPrefix,
+    pub sn: u64,
+    pub digest: SelfAddressingIdentifier,
+    /// Witnesses whose signatures are attached with their keys. Witnesses
+    /// of indexed signatures are known only from the receipted event.
+    pub witnesses: Vec<BasicPrefix>,
+    pub receipt: SignedNontransferableReceipt,
+}
+
+/// Delegated event waiting for delegator to anchor it.
+#[derive(Debug, Clone)]
+pub struct DelegationRequestContext {
+    pub delegator: IdentifierPrefix,
+    pub delegate: IdentifierPrefix,
+    pub sn: u64,
+    pub event: SignedEventMessage,
+}
+
+/// Event conflicting with the accepted event of the same sn.
+#[derive(Debug, Clone)]
+pub struct DuplicityContext {
+    pub id: IdentifierPrefix,
+    pub sn: u64,
+    pub accepted: SignedEventMessage,
+    pub conflicting: SignedEventMessage,
+}
+
+type Hook<C> = Box<dyn Fn(&C) + Send + Sync>;
+
+enum LifecycleHook {
+    Rotation(Hook<RotationContext>),
+    WitnessReceipt(Hook<WitnessReceiptContext>),
+    DelegationRequest(Hook<DelegationRequestContext>),
+    Duplicity(Hook<DuplicityContext>),
+}
+
+/// Calls hook with context built from notification it is registered for.
+struct HookObserver<D: EventDatabase> {
+    storage: Arc<EventStorage<D>>,
+    hook: LifecycleHook,
+}
+
+impl<D: EventDatabase> HookObserver<D> {
+    fn rotation(&self, accepted: &AcceptedEvent) -> Option<RotationContext> {
+        let data = &accepted.event.event_message.data;
+        if !matches!(data.event_data, EventData::Rot(_) | EventData::Drt(_)) {
+            return None;
+        }
+        let id = data.get_prefix();
+        let sn = data.get_sn();
+        let state = accepted.state.clone();
+        let previous = self.storage.get_state_at(&id, sn - 1).ok()??;
+        Some(RotationContext {
+            id,
+            sn,
+            previous_keys: previous.current.public_keys,
+            keys: state.current.public_keys,
+            threshold: state.current.threshold,
+            witnesses: state.witness_config.witnesses,
+            event: accepted.event.clone(),
+        })
+    }
+
+    fn delegator(
+        &self,
+        event: &SignedEventMessage,
+    ) -> Option<IdentifierPrefix> {
+        match &event.event_message.data.event_data {
+            EventData::Dip(dip) => Some(dip.delegator.clone()),
+            EventData::Drt(_) => self
+                .storage
+                .get_state(&event.event_message.data.get_prefix())
+                .and_then(|state| state.delegator),
+            _ => None,
+        }
+    }
+
+    /// Accepted event of the same sn, if it differs from `event`.
+    fn conflicting(
+        &self,
+        event: &SignedEventMessage,
+    ) -> Option<SignedEventMessage> {
+        let data = &event.event_message.data;
+        let accepted = self
+            .storage
+            .get_event_at_sn(&data.get_prefix(), data.get_sn())?
+            .signed_event_message;
+        (accepted.event_message.digest().ok()
+            != event.event_message.digest().ok())
+        .then_some(accepted)
+    }
+}
+
+impl<D: EventDatabase> Notifier for HookObserver<D> {
+    fn notify(
+        &self,
+        notification: &Notification,
+        _bus: &NotificationBus,
+    ) -> Result<(), Error> {
+        match (&self.hook, notification) {
+            (
+                LifecycleHook::Rotation(hook),
+                Notification::KeyEventAccepted(accepted),
+            ) => {
+                if let Some(context) = self.rotation(accepted) {
+                    hook(&context);
+                }
+            }
+            (
+                LifecycleHook::WitnessReceipt(hook),
+                Notification::ReceiptOutOfOrder(rct),
+            ) => {
+                let witnesses = rct
+                    .signatures
+                    .iter()
+                    .flat_map(|signature| match signature {
+                        Nontransferable::Couplet(couplets) => couplets
+                            .iter()
+                            .map(|(witness, _)| witness.clone())
+                            .collect(),
+                        Nontransferable::Indexed(_) => vec![],
+                    })
+                    .collect();
+                hook(&WitnessReceiptContext {
+                    id: rct.body.prefix.clone(),
+                    sn: rct.body.sn,
+                    digest: rct.body.receipted_event_digest.clone(),
+                    witnesses,
+                    receipt: rct.clone(),
+                });
+            }
+            (
+                LifecycleHook::DelegationRequest(hook),
+                Notification::MissingDelegatingEvent(event),
+            ) => {
+                if let Some(delegator) = self.delegator(event) {
+                    hook(&DelegationRequestContext {
+                        delegator,
+                        delegate: event.event_message.data.get_prefix(),
+                        sn: event.event_message.data.get_sn(),
+                        event: event.clone(),
+                    });
+                }
+            }
+            (
+                LifecycleHook::Duplicity(hook),
+                Notification::DupliciousEvent(event),
+            ) => {
+                if let Some(accepted) = self.conflicting(event) {
+                    hook(&DuplicityContext {
+                        id: event.event_message.data.get_prefix(),
+                        sn: event.event_message.data.get_sn(),
+                        accepted,
+                        conflicting: event.clone(),
+                    });
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    fn register_hook(
+        &self,
+        hook: LifecycleHook,
+        notification: JustNotification,
+    ) {
+        let observer = HookObserver {
+            storage: self.kel.storage.clone(),
+            hook,
+        };
+        self.kel
+            .notification_bus
+            .register_observer(Arc::new(observer), vec![notification]);
+    }
+
+    /// Registers hook called whenever rotation of any identifier is
+    /// accepted.
+    pub fn on_rotation(
+        &self,
+        hook: impl Fn(&RotationContext) + Send + Sync + 'static,
+    ) {
+        self.register_hook(
+            LifecycleHook::Rotation(Box::new(hook)),
+            JustNotification::KeyEventAccepted,
+        );
+    }
+
+    /// Registers hook called with witness receipts of events that wait
+    /// for receipts. Receipts of already accepted events don't call it.
+    pub fn on_witness_receipt(
+        &self,
+        hook: impl Fn(&WitnessReceiptContext) + Send + Sync + 'static,
+    ) {
+        self.register_hook(
+            LifecycleHook::WitnessReceipt(Box::new(hook)),
+            JustNotification::ReceiptOutOfOrder,
+        );
+    }
+
+    /// Registers hook called when delegated event waits for approval of
+    /// its delegator, e.g. one fetched from delegator's mailbox.
+    pub fn on_delegation_request(
+        &self,
+        hook: impl Fn(&DelegationRequestContext) + Send + Sync + 'static,
+    ) {
+        self.register_hook(
+            LifecycleHook::DelegationRequest(Box::new(hook)),
+            JustNotification::MissingDelegatingEvent,
+        );
+    }
+
+    /// Registers hook called when event conflicting with accepted one is
+    /// received.
+    pub fn on_duplicity(
+        &self,
+        hook: impl Fn(&DuplicityContext) + Send + Sync + 'static,
+    ) {
+        self.register_hook(
+            LifecycleHook::Duplicity(Box::new(hook)),
+            JustNotification::DuplicitousEvent,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use keri_core::{
+        actor::prelude::SerializationFormats,
+        event::receipt::Receipt,
+        event_message::{signed_event_message::Notice, EventTypeTag},
+        prefix::SelfSigningPrefix,
+        processor::Processor,
+        signer::Signer,
+    };
+    use said::derivation::{HashFunction, HashFunctionCode};
+
+    use super::*;
+    use crate::{
+        test_utils::{incept, setup, sign},
+        EncryptedFileKeyStore, IdentifierKeys, KeyStore,
+    };
+
+    #[test]
+    fn test_lifecycle_hooks() {
+        let (root, controller) = setup("test-db");
+        let rotations = Arc::new(Mutex::new(vec![]));
+        let receipts = Arc::new(Mutex::new(vec![]));
+        let requests = Arc::new(Mutex::new(vec![]));
+        let duplicities = Arc::new(Mutex::new(vec![]));
+        let (rot, rct, req, dup) = (
+            rotations.clone(),
+            receipts.clone(),
+            requests.clone(),
+            duplicities.clone(),
+        );
+        controller.on_rotation(move |context| {
+            rot.lock().unwrap().push(context.clone())
+        });
+        controller.on_witness_receipt(move |context| {
+            rct.lock().unwrap().push(context.clone())
+        });
+        controller.on_delegation_request(move |context| {
+            req.lock().unwrap().push(context.clone())
+        });
+        controller.on_duplicity(move |context| {
+            dup.lock().unwrap().push(context.clone())
+        });
+
+        let store = EncryptedFileKeyStore::new(root.path().join("keys.json"));
+        store.unlock("passphrase").unwrap();
+        let keys = IdentifierKeys::generate(1);
+        let sign_with = |keys: &IdentifierKeys, data: &[u8]| {
+            SelfSigningPrefix::Ed25519Sha512(
+                keys.signers().unwrap()[0].sign(data).unwrap(),
+            )
+        };
+        let icp = controller
+            .incept(
+                keys.public_keys().unwrap(),
+                keys.next_public_keys().unwrap(),
+            )
+            .unwrap();
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &sign_with(&keys, icp.as_bytes()))
+            .unwrap();
+        store.save(&identifier.id, &keys).unwrap();
+
+        identifier.rotate(&store).unwrap();
+        let rotated = store.load(&identifier.id).unwrap().unwrap();
+        {
+            let rotations = rotations.lock().unwrap();
+            assert_eq!(rotations.len(), 1);
+            assert_eq!(rotations[0].id, identifier.id);
+            assert_eq!(rotations[0].sn, 1);
+            assert_eq!(rotations[0].previous_keys, keys.public_keys().unwrap());
+            assert_eq!(rotations[0].keys, rotated.public_keys().unwrap());
+        }
+
+        // Two different interactions of the same sn.
+        let first = identifier.anchor(&[]).unwrap();
+        let digest =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"other");
+        let second = identifier.anchor(&[digest]).unwrap();
+        identifier
+            .finalize_anchor(
+                first.as_bytes(),
+                sign_with(&rotated, first.as_bytes()),
+            )
+            .unwrap();
+        let _ = identifier.finalize_anchor(
+            first.as_bytes(),
+            sign_with(&rotated, first.as_bytes()),
+        );
+        assert!(duplicities.lock().unwrap().is_empty());
+        let _ = identifier.finalize_anchor(
+            second.as_bytes(),
+            sign_with(&rotated, second.as_bytes()),
+        );
+        {
+            let duplicities = duplicities.lock().unwrap();
+            assert_eq!(duplicities.len(), 1);
+            assert_eq!(duplicities[0].sn, 2);
+            assert_ne!(duplicities[0].accepted, duplicities[0].conflicting);
+        }
+
+        // Delegated inception waiting for delegator's approval.
+        let delegator = incept(&controller, &Signer::new());
+        let signer = Signer::new();
+        let dip = controller
+            .incept_delegated(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+                &delegator.id,
+            )
+            .unwrap();
+        let (delegate, _request) = controller
+            .finalize_incept_delegated(
+                dip.as_bytes(),
+                &sign(&signer, dip.as_bytes()),
+            )
+            .unwrap();
+        {
+            let requests = requests.lock().unwrap();
+            assert_eq!(requests.len(), 1);
+            assert_eq!(requests[0].delegator, delegator.id);
+            assert_eq!(requests[0].delegate, delegate.id);
+        }
+
+        // Receipt of event that isn't accepted yet.
+        let witness = Signer::new();
+        let witness_key = BasicPrefix::Ed25519NT(witness.public_key());
+        let event_digest =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"event");
+        let receipt = Receipt::new(
+            SerializationFormats::JSON,
+            event_digest.clone(),
+            delegate.id.clone(),
+            1,
+        );
+        let signature = sign(&witness, &receipt.encode().unwrap());
+        let signed = SignedNontransferableReceipt::new(
+            &receipt,
+            vec![Nontransferable::Couplet(vec![(
+                witness_key.clone(),
+                signature,
+            )])],
+        );
+        let _ = controller
+            .kel
+            .processor
+            .process_notice(&Notice::NontransferableRct(signed));
+        let receipts = receipts.lock().unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].id, delegate.id);
+        assert_eq!(receipts[0].digest, event_digest);
+        assert_eq!(receipts[0].witnesses, vec![witness_key]);
+        assert_eq!(receipts[0].receipt.body.event_type, EventTypeTag::Rct);
+    }
+}
@@ -1,30 +1,78 @@
 use keri_core::{
     actor::{
-        event_generator,
+        event_generator, parse_event_stream, parse_reply_stream,
         prelude::{
             EventStorage, HashFunctionCode, Message, SerializationFormats,
         },
     },
     database::EventDatabase,
+    error::Error,
+    event::{
+        event_data::EventData,
+        sections::{
+            seal::{DigestSeal, EventSeal, Seal},
+            threshold::SignatureThreshold,
+            RotationWitnessConfig,
+        },
+    },
     event_message::{
         cesr_adapter::{parse_event_type, EventType},
         msg::KeriEvent,
-        signed_event_message::{Notice, Op},
+        signed_event_message::{Notice, Op, SignedEventMessage},
         timestamped::Timestamped,
     },
-    oobi::Role,
-    prefix::{IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
+    oobi::{Oobi, Role, Scheme},
+    prefix::{
+        BasicPrefix, CesrPrimitive, IdentifierPrefix, IndexedSignature,
+        SelfSigningPrefix,
+    },
+    processor::{
+        basic_processor::BasicProcessor, validator::EventValidator, Processor,
+    },
     query::{
-        query_event::{LogsQueryArgs, QueryEvent, QueryRoute},
+        query_event::{
+            QueryEvent, QueryRoute, SignedKelQuery, SignedQueryMessage,
+        },
         reply_event::{ReplyEvent, ReplyRoute, SignedReply},
+        QueryError,
     },
+    state::IdentifierState,
+};
+use said::{derivation::HashFunction, SelfAddressingIdentifier};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use teliox::query::{
+    SignedTelQuery, TelQueryArgs, TelQueryEvent, TelQueryRoute,
+};
+use url::Url;
+
+#[cfg(feature = "mailbox")]
+use keri_core::{
+    actor::possible_response::{parse_mailbox_response, PossibleResponse},
+    query::mailbox::SignedMailboxQuery,
+};
+
+#[cfg(feature = "mailbox")]
+use crate::mailbox::{
+    mailbox_items, MailboxCursor, MailboxItem, MailboxTopic, MailboxTransport,
+};
+use crate::{
+    query::{QuerySigner, QueryTransport, KEL_QUERY_PAGE_SIZE},
+    watcher::WatcherTransport,
+    witness::{WitnessPool, WitnessPublisher},
 };
-use std::sync::Arc;
-use teliox::query::{TelQueryArgs, TelQueryEvent, TelQueryRoute};
 
 pub struct Identifier<D: EventDatabase> {
     pub id: IdentifierPrefix,
-    event_storage: Arc<EventStorage<D>>,
+    pub(crate) event_storage: Arc<EventStorage<D>>,
+    pub(crate) processor: Option<Arc<BasicProcessor<D>>>,
+    witness_pool: WitnessPool,
+    #[cfg(feature = "mailbox")]
+    mailbox_cursors: RwLock<HashMap<IdentifierPrefix, MailboxCursor>>,
+    ksn_subscriptions: RwLock<HashMap<IdentifierPrefix, Vec<IdentifierPrefix>>>,
+    watchers: RwLock<Vec<IdentifierPrefix>>,
 }
 
 impl<D: EventDatabase> Identifier<D> {
@@ -32,7 +80,22 @@ impl<D: EventDatabase> Identifier<D> {
         id: IdentifierPrefix,
         event_storage: Arc<EventStorage<D>>,
     ) -> Self {
-        Self { id, event_storage }
+        Self {
+            id,
+            event_storage,
+            processor: None,
+            witness_pool: WitnessPool::new(),
+            #[cfg(feature = "mailbox")]
+            mailbox_cursors: RwLock::new(HashMap::new()),
+            ksn_subscriptions: RwLock::new(HashMap::new()),
+            watchers: RwLock::new(vec![]),
+        }
+    }
+
+    /// Sets processor used to apply events obtained by queries.
+    pub fn with_processor(mut self, processor: Arc<BasicProcessor<D>>) -> Self {
+        self.processor = Some(processor);
+        self
     }
 
     pub fn get_prefix(&self) -> &IdentifierPrefix {
@@ -45,23 +108,157 @@ impl<D: EventDatabase> Identifier<D> {
             .unwrap()
     }
 
-    pub fn add_watcher(
+    /// Returns next keys commitments of the last establishment event.
+    pub fn next_keys_hashes(
         &self,
-        watcher_id: IdentifierPrefix,
+    ) -> Result<Vec<SelfAddressingIdentifier>, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        Ok(state.current.next_keys_data.next_keys_hashes())
+    }
+
+    /// Returns next keys commitments which stay unexposed when rotating to
+    /// `exposed` keys. They can be carried over as reserve keys.
+    pub fn reserved_next_keys(
+        &self,
+        exposed: &[BasicPrefix],
+    ) -> Result<Vec<SelfAddressingIdentifier>, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        Ok(state.current.next_keys_data.reserved_hashes(exposed))
+    }
+
+    /// Generates partial rotation event. Only `exposed_keys` of pre-rotated
+    /// keys become current keys. New next keys list consists of commitments
+    /// to `new_next_keys` followed by commitments of keys that stay reserved.
+    pub fn partial_rotate(
+        &self,
+        exposed_keys: Vec<BasicPrefix>,
+        threshold: SignatureThreshold,
+        new_next_keys: &[BasicPrefix],
+        next_threshold: SignatureThreshold,
     ) -> Result<String, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let witness_config = RotationWitnessConfig {
+            tally: state.witness_config.tally,
+            prune: vec![],
+            graft: vec![],
+        };
+        self.rotate_witnesses(
+            exposed_keys,
+            threshold,
+            new_next_keys,
+            next_threshold,
+            witness_config,
+        )
+    }
+
+    /// Returns witnesses known to identifier. Configured witnesses are
+    /// always in the pool.
+    pub fn witness_pool(&self) -> &WitnessPool {
+        if let Some(state) = self.event_storage.get_state(&self.id) {
+            for witness in state.witness_config.witnesses {
+                self.witness_pool.add(witness);
+            }
+        }
+        &self.witness_pool
+    }
+
+    /// Computes witness changes to be applied with `rotate_witnesses`.
+    /// Witness threshold is adjusted to the new number of witnesses unless
+    /// provided explicitly.
+    pub fn witness_rotation(
+        &self,
+        to_add: &[BasicPrefix],
+        to_remove: &[BasicPrefix],
+        threshold: Option<u64>,
+    ) -> Result<RotationWitnessConfig, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        self.witness_pool()
+            .rotation_config(&state, to_add, to_remove, threshold)
+    }
+
+    /// Generates partial rotation event, which also changes witnesses as
+    /// described in `witness_config`.
+    pub fn rotate_witnesses(
+        &self,
+        exposed_keys: Vec<BasicPrefix>,
+        threshold: SignatureThreshold,
+        new_next_keys: &[BasicPrefix],
+        next_threshold: SignatureThreshold,
+        witness_config: RotationWitnessConfig,
+    ) -> Result<String, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let derivation: HashFunction = HashFunctionCode::Blake3_256.into();
+        let next_keys_hashes = new_next_keys
+            .iter()
+            .map(|key| derivation.derive(key.to_str().as_bytes()))
+            .chain(state.current.next_keys_data.reserved_hashes(&exposed_keys))
+            .collect();
+        let rot = event_generator::partial_rotate_with_witnesses(
+            state,
+            exposed_keys,
+            &threshold,
+            next_keys_hashes,
+            &next_threshold,
+            witness_config,
+        )
+        .map_err(|e| e.to_string())?;
         String::from_utf8(
-            event_generator::generate_end_role(
-                &self.id,
-                &watcher_id,
-                Role::Watcher,
-                true,
-            )
-            .encode()
-            .map_err(|_| "Event encoding error".to_string())?,
+            rot.encode()
+                .map_err(|_| "Event encoding error".to_string())?,
         )
         .map_err(|_| "Event format error".to_string())
     }
 
+    /// Attaches indexes to signature of rotation event made with `key`: its
+    /// position in `rotation_keys` and, if it was pre-rotated, its position
+    /// in previous next keys list.
+    pub fn rotation_signature(
+        &self,
+        rotation_keys: &[BasicPrefix],
+        key: &BasicPrefix,
+        signature: SelfSigningPrefix,
+    ) -> Result<IndexedSignature, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let current = rotation_keys
+            .iter()
+            .position(|k| k == key)
+            .ok_or("Key is not in rotation keys".to_string())?
+            as u16;
+        Ok(match state.current.next_keys_data.key_position(key) {
+            Some(prev_next) => IndexedSignature::new_both_diffrent(
+                signature,
+                current,
+                prev_next as u16,
+            ),
+            None => IndexedSignature::new_current_only(signature, current),
+        })
+    }
+
+    pub fn add_watcher(
+        &self,
+        watcher_id: IdentifierPrefix,
+    ) -> Result<String, String> {
+        self.add_end_role(watcher_id, Role::Watcher)
+    }
+
     pub fn finalize_add_watcher(
         &self,
         event: &[u8],
@@ -71,16 +268,93 @@ impl<D: EventDatabase> Identifier<D> {
             .map_err(|_| "Event parsing error".to_string())?;
         match parsed_event {
             EventType::Rpy(rpy) => match rpy.get_route() {
-                ReplyRoute::EndRoleAdd(_) => Ok(self
-                    .finalize_add_role(&self.id, rpy, vec![sig])
-                    .unwrap()),
-                ReplyRoute::EndRoleCut(_) => todo!(),
+                ReplyRoute::EndRoleAdd(_) | ReplyRoute::EndRoleCut(_) => {
+                    self.finalize_add_role(&self.id, rpy, vec![sig])
+                }
                 _ => Err("Wrong reply route".to_string()),
             },
             _ => Err("Event is not a reply".to_string()),
         }
     }
 
+    /// Generates reply authorizing `eid` to act as identifier's endpoint
+    /// of given role.
+    pub fn add_end_role(
+        &self,
+        eid: IdentifierPrefix,
+        role: Role,
+    ) -> Result<String, String> {
+        encode_reply(event_generator::generate_end_role(
+            &self.id, &eid, role, true,
+        ))
+    }
+
+    /// Generates reply revoking authorization of `eid` endpoint.
+    pub fn cut_end_role(
+        &self,
+        eid: IdentifierPrefix,
+        role: Role,
+    ) -> Result<String, String> {
+        encode_reply(event_generator::generate_end_role(
+            &self.id, &eid, role, false,
+        ))
+    }
+
+    /// Generates reply advertising location of identifier itself, e.g.
+    /// when it serves its own KEL.
+    pub fn add_loc_scheme(
+        &self,
+        scheme: Scheme,
+        url: Url,
+    ) -> Result<String, String> {
+        encode_reply(event_generator::generate_loc_scheme(
+            &self.id, scheme, url,
+        ))
+    }
+
+    /// Generates reply with key state notice of identifier itself. Signed
+    /// reply can be published to witnesses and watchers, so verifiers can
+    /// learn current key state without fetching KEL.
+    pub fn ksn_reply(&self) -> Result<String, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier state".to_string())?;
+        encode_reply(event_generator::generate_ksn_reply(&self.id, state))
+    }
+
+    /// Attaches identifier's signature to end role, location scheme or key
+    /// state notice reply made by identifier.
+    pub fn finalize_reply(
+        &self,
+        event: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<SignedReply, String> {
+        let rpy = match parse_event_type(event)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::Rpy(rpy) => rpy,
+            _ => return Err("Event is not a reply".to_string()),
+        };
+        let signer = match rpy.get_route() {
+            ReplyRoute::LocScheme(loc) => loc.eid,
+            ReplyRoute::EndRoleAdd(er) | ReplyRoute::EndRoleCut(er) => er.cid,
+            ReplyRoute::Ksn(signer, _) => signer,
+        };
+        if signer != self.id {
+            return Err("Reply wasn't made by identifier".to_string());
+        }
+        let seal = self
+            .event_storage
+            .get_last_establishment_event_seal(&self.id)
+            .ok_or("Failed to get last establishment event seal".to_string())?;
+        Ok(SignedReply::new_trans(
+            rpy,
+            seal,
+            vec![IndexedSignature::new_both_same(sig, 0)],
+        ))
+    }
+
     fn finalize_add_role(
         &self,
         signer_prefix: &IdentifierPrefix,
@@ -147,19 +421,88 @@ impl<D: EventDatabase> Identifier<D> {
         from_sn: Option<u64>,
         limit: Option<u64>,
     ) -> QueryEvent {
-        QueryEvent::new_query(
-            QueryRoute::Logs {
-                reply_route: "".to_string(),
-                args: LogsQueryArgs {
-                    s: from_sn,
-                    limit,
-                    i: identifier,
-                    src: Some(witness),
-                },
-            },
-            SerializationFormats::JSON,
-            HashFunctionCode::Blake3_256,
+        event_generator::logs_query(&identifier, &witness, from_sn, limit)
+    }
+
+    /// Returns cursor of mailbox kept by `witness`.
+    #[cfg(feature = "mailbox")]
+    pub fn mailbox_cursor(&self, witness: &IdentifierPrefix) -> MailboxCursor {
+        self.mailbox_cursors
+            .read()
+            .unwrap()
+            .get(witness)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Generates query of own mailbox kept by `witness`. Only messages
+    /// that weren't fetched yet are requested, at most `limit` of each
+    /// topic.
+    #[cfg(feature = "mailbox")]
+    pub fn query_mailbox(
+        &self,
+        witness: &IdentifierPrefix,
+        limit: Option<usize>,
+    ) -> Result<String, String> {
+        let qry = event_generator::mailbox_query(
+            &self.id,
+            &self.id,
+            witness,
+            self.mailbox_cursor(witness).to_query_topics(),
+            limit,
+        );
+        String::from_utf8(
+            qry.encode()
+                .map_err(|_| "Event encoding error".to_string())?,
         )
+        .map_err(|_| "Event format error".to_string())
+    }
+
+    /// Signs mailbox query, sends it with `transport` and returns fetched
+    /// messages of provided topics. Cursor of these topics is moved past
+    /// returned messages, so next query asks only for new ones.
+    #[cfg(feature = "mailbox")]
+    pub fn fetch_mailbox(
+        &self,
+        query: &[u8],
+        sig: SelfSigningPrefix,
+        topics: &[MailboxTopic],
+        transport: &dyn MailboxTransport,
+    ) -> Result<Vec<MailboxItem>, String> {
+        let qry = match parse_event_type(query)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::MailboxQry(qry) => qry,
+            _ => return Err("Event is not a mailbox query".to_string()),
+        };
+        let args = qry.get_args();
+        if args.pre != self.id {
+            return Err("Query wasn't made by identifier".to_string());
+        }
+        let signed = SignedMailboxQuery::new_trans(
+            qry,
+            self.id.clone(),
+            vec![IndexedSignature::new_both_same(sig, 0)],
+        );
+        let stream =
+            Message::Op(Op::Query(SignedQueryMessage::MailboxQuery(signed)))
+                .to_cesr()
+                .map_err(|e| e.to_string())?;
+        let response = transport.query(&args.src, &stream)?;
+        let response = match parse_mailbox_response(&response)
+            .map_err(|e| e.to_string())?
+        {
+            PossibleResponse::Mbx(mbx) => mbx,
+            _ => return Err("Unexpected mailbox response".to_string()),
+        };
+
+        self.mailbox_cursors
+            .write()
+            .unwrap()
+            .entry(args.src)
+            .or_default()
+            .advance(&args.topics, &response, topics);
+        Ok(mailbox_items(response, topics))
     }
 
     pub fn get_tel_query(
@@ -174,11 +517,749 @@ impl<D: EventDatabase> Identifier<D> {
                 ri: Some(registry_id),
             },
         };
-        let env = Timestamped::new(route);
-        Ok(KeriEvent::new(
-            SerializationFormats::JSON,
-            HashFunctionCode::Blake3_256.into(),
-            env,
-        ))
+        Ok(tel_query_event(route))
+    }
+
+    /// Generates query for TEL state notice of credential `vc_identifier`
+    /// issued in `registry_id`. Responder replies with current registry
+    /// and credential state instead of their TEL.
+    pub fn get_tel_state_query(
+        &self,
+        registry_id: IdentifierPrefix,
+        vc_identifier: IdentifierPrefix,
+    ) -> Result<TelQueryEvent, String> {
+        let route = TelQueryRoute::Tsn {
+            reply_route: "".into(),
+            args: TelQueryArgs {
+                i: Some(vc_identifier),
+                ri: Some(registry_id),
+            },
+        };
+        Ok(tel_query_event(route))
+    }
+
+    /// Signs TEL query made by identifier, sends it to `recipient` witness
+    /// or watcher and returns the TEL stream it responded with.
+    pub fn finalize_tel_query(
+        &self,
+        query: &TelQueryEvent,
+        sig: SelfSigningPrefix,
+        recipient: &IdentifierPrefix,
+        transport: &dyn QueryTransport,
+    ) -> Result<Vec<u8>, String> {
+        let signed = SignedTelQuery::new_trans(
+            query.clone(),
+            self.id.clone(),
+            vec![IndexedSignature::new_both_same(sig, 0)],
+        );
+        let stream = signed.to_cesr().map_err(|e| e.to_string())?;
+        transport.query(recipient, &stream)
+    }
+}
+
+impl<D: EventDatabase + 'static> Identifier<D> {
+    /// Generates interaction event anchoring digest seals of provided
+    /// `digests`, e.g. SAIDs of documents.
+    pub fn anchor(
+        &self,
+        digests: &[SelfAddressingIdentifier],
+    ) -> Result<String, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier state".to_string())?;
+        event_generator::anchor(state, digests).map_err(|e| e.to_string())
+    }
+
+    /// Signs and processes anchoring interaction event. Returns signed
+    /// event, which should be published to identifier's witnesses, e.g.
+    /// with `ReceiptCollector::collect`. Until they receipt it, the event
+    /// waits in partially witnessed escrow.
+    pub fn finalize_anchor(
+        &self,
+        event: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<SignedEventMessage, String> {
+        let ixn = match parse_event_type(event)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::KeyEvent(ixn)
+                if matches!(ixn.data.get_event_data(), EventData::Ixn(_)) =>
+            {
+                ixn
+            }
+            _ => return Err("Event is not an interaction".to_string()),
+        };
+        if ixn.data.get_prefix() != self.id {
+            return Err("Event is not identifier's interaction".to_string());
+        }
+        let signed =
+            ixn.sign(vec![IndexedSignature::new_both_same(sig, 0)], None, None);
+        self.processor()?
+            .process_notice(&Notice::Event(signed.clone()))
+            .map_err(|e| e.to_string())?;
+        Ok(signed)
+    }
+
+    /// Searches identifier's KEL for event anchoring `digest`. Returns seal
+    /// of the anchoring event.
+    pub fn find_anchor(
+        &self,
+        digest: &SelfAddressingIdentifier,
+    ) -> Result<Option<EventSeal>, String> {
+        let expected = Seal::Digest(DigestSeal::new(digest.clone()));
+        let kel = self
+            .event_storage
+            .get_kel_messages_with_receipts_all(&self.id)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default();
+        for notice in kel {
+            let Notice::Event(event) = notice else {
+                continue;
+            };
+            let data = match event.event_message.data.get_event_data() {
+                EventData::Ixn(ixn) => ixn.data,
+                EventData::Rot(rot) => rot.data,
+                EventData::Drt(drt) => drt.data,
+                EventData::Icp(icp) => icp.data,
+                EventData::Dip(dip) => dip.inception_data.data,
+            };
+            if data.contains(&expected) {
+                return Ok(Some(EventSeal::new(
+                    self.id.clone(),
+                    event.event_message.data.get_sn(),
+                    event.event_message.digest().map_err(|e| e.to_string())?,
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Generates abandonment rotation, which exposes `exposed_keys` and
+    /// commits to no next keys. After it is accepted, identifier can't be
+    /// rotated nor anchor anything anymore.
+    pub fn abandon(
+        &self,
+        exposed_keys: Vec<BasicPrefix>,
+        threshold: SignatureThreshold,
+    ) -> Result<String, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let rot = event_generator::abandon(state, exposed_keys, &threshold)
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(
+            rot.encode()
+                .map_err(|_| "Event encoding error".to_string())?,
+        )
+        .map_err(|_| "Event format error".to_string())
+    }
+
+    /// Processes abandonment rotation generated by `abandon` and publishes
+    /// it to all identifier's witnesses. Witnesses that can't be reached
+    /// are skipped, but at least one of them has to receive the event.
+    pub fn finalize_abandon(
+        &self,
+        event: &[u8],
+        signatures: Vec<IndexedSignature>,
+        publisher: &dyn WitnessPublisher,
+    ) -> Result<SignedEventMessage, String> {
+        let rot = match parse_event_type(event)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::KeyEvent(rot) => rot,
+            _ => return Err("Event is not a key event".to_string()),
+        };
+        let next_keys = match rot.data.get_event_data() {
+            EventData::Rot(rot) | EventData::Drt(rot) => {
+                rot.key_config.next_keys_data
+            }
+            _ => return Err("Event is not a rotation".to_string()),
+        };
+        if rot.data.get_prefix() != self.id {
+            return Err("Event is not identifier's rotation".to_string());
+        }
+        if !next_keys.next_keys_hashes().is_empty() {
+            return Err("Rotation commits to next keys".to_string());
+        }
+        let witnesses = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?
+            .witness_config
+            .witnesses;
+        let signed = rot.sign(signatures, None, None);
+        self.processor()?
+            .process_notice(&Notice::Event(signed.clone()))
+            .map_err(|e| e.to_string())?;
+
+        let stream = Message::Notice(Notice::Event(signed.clone()))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+        let published = witnesses
+            .iter()
+            .filter(|witness| match publisher.publish(witness, &stream) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to publish abandonment of {} to {}: {}",
+                        self.id,
+                        witness.to_str(),
+                        e
+                    );
+                    false
+                }
+            })
+            .count();
+        if !witnesses.is_empty() && published == 0 {
+            return Err("No witness received abandonment".to_string());
+        }
+        Ok(signed)
+    }
+
+    /// Signs query made by identifier, sends it to the witness or watcher
+    /// it is addressed to and returns the response.
+    pub fn finalize_query(
+        &self,
+        query: &[u8],
+        sig: SelfSigningPrefix,
+        transport: &dyn QueryTransport,
+    ) -> Result<Vec<u8>, String> {
+        let (recipient, stream) = self.signed_query(query, sig)?;
+        transport.query(&recipient, &stream)
+    }
+
+    /// Returns recipient of query along with the query signed by
+    /// identifier, as CESR stream.
+    pub(crate) fn signed_query(
+        &self,
+        query: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<(IdentifierPrefix, Vec<u8>), String> {
+        let qry = match parse_event_type(query)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::Qry(qry) => qry,
+            _ => return Err("Event is not a query".to_string()),
+        };
+        let recipient = match qry.get_route() {
+            QueryRoute::Logs { args, .. } | QueryRoute::Ksn { args, .. } => {
+                args.src
+                    .clone()
+                    .ok_or("Unknown query recipient".to_string())?
+            }
+        };
+        let signed = SignedKelQuery::new_trans(
+            qry,
+            self.id.clone(),
+            vec![IndexedSignature::new_both_same(sig, 0)],
+        );
+        let stream =
+            Message::Op(Op::Query(SignedQueryMessage::KelQuery(signed)))
+                .to_cesr()
+                .map_err(|e| e.to_string())?;
+        Ok((recipient, stream))
+    }
+
+    /// Queries `witness` for KEL of `of` identifier, starting at `from_sn`,
+    /// and processes returned events. KEL is requested in pages of
+    /// `page_size` events, until witness returns incomplete page. Every
+    /// query is signed with `signer`. Returns number of received events.
+    pub fn query_kel(
+        &self,
+        of: &IdentifierPrefix,
+        from_sn: u64,
+        witness: &IdentifierPrefix,
+        page_size: u64,
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<u64, String> {
+        let mut next_sn = from_sn;
+        let mut received = 0;
+        loop {
+            let query = event_generator::logs_query(
+                of,
+                witness,
+                Some(next_sn),
+                Some(page_size),
+            )
+            .encode()
+            .map_err(|_| "Event encoding error".to_string())?;
+            let response =
+                self.finalize_query(&query, signer.sign(&query)?, transport)?;
+            let page = self.process_kel_page(of, &response, &mut next_sn)?;
+            received += page;
+            if page < page_size {
+                return Ok(received);
+            }
+        }
+    }
+
+    /// Processes page of KEL returned for logs query. Returns number of
+    /// `of` identifier's events in the page and advances `next_sn` past
+    /// them.
+    pub(crate) fn process_kel_page(
+        &self,
+        of: &IdentifierPrefix,
+        response: &[u8],
+        next_sn: &mut u64,
+    ) -> Result<u64, String> {
+        let processor = self.processor()?;
+        let messages =
+            parse_event_stream(response).map_err(|e| e.to_string())?;
+        let mut page = 0;
+        for message in messages {
+            let Message::Notice(notice) = message else {
+                return Err("Unexpected message in KEL".to_string());
+            };
+            if let Notice::Event(event) = &notice {
+                if event.event_message.data.get_prefix() == *of {
+                    *next_sn = event.event_message.data.get_sn() + 1;
+                    page += 1;
+                }
+            }
+            processor
+                .process_notice(&notice)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(page)
+    }
+
+    /// Generates query of `of` identifier's key state kept by `witness`.
+    pub fn query_ksn(
+        &self,
+        of: &IdentifierPrefix,
+        witness: &IdentifierPrefix,
+    ) -> Result<String, String> {
+        String::from_utf8(
+            event_generator::ksn_query(of, witness)
+                .encode()
+                .map_err(|_| "Event encoding error".to_string())?,
+        )
+        .map_err(|_| "Event format error".to_string())
+    }
+
+    /// Sends signed key state query and processes returned key state
+    /// notice. Returns key state reported by witness.
+    pub fn finalize_query_ksn(
+        &self,
+        query: &[u8],
+        sig: SelfSigningPrefix,
+        transport: &dyn QueryTransport,
+    ) -> Result<IdentifierState, String> {
+        let response = self.finalize_query(query, sig, transport)?;
+        let (reply, state) = parse_ksn_response(&response)?;
+        self.processor()?
+            .process_op_reply(&reply)
+            .map_err(|e| e.to_string())?;
+        Ok(state)
+    }
+
+    /// Subscribes to key state notices of `of` identifier kept by
+    /// `watcher`. Notices are fetched by `refresh_ksn`.
+    pub fn subscribe_ksn(
+        &self,
+        of: IdentifierPrefix,
+        watcher: IdentifierPrefix,
+    ) {
+        let mut subscriptions = self.ksn_subscriptions.write().unwrap();
+        let watchers = subscriptions.entry(of).or_default();
+        if !watchers.contains(&watcher) {
+            watchers.push(watcher);
+        }
+    }
+
+    pub fn unsubscribe_ksn(
+        &self,
+        of: &IdentifierPrefix,
+        watcher: &IdentifierPrefix,
+    ) {
+        let mut subscriptions = self.ksn_subscriptions.write().unwrap();
+        if let Some(watchers) = subscriptions.get_mut(of) {
+            watchers.retain(|w| w != watcher);
+            if watchers.is_empty() {
+                subscriptions.remove(of);
+            }
+        }
+    }
+
+    /// Queries watchers of all subscriptions for key state notices and
+    /// returns key states that were accepted. Notices older than already
+    /// accepted ones are skipped, according to BADA-RUN rules. Notices of
+    /// identifiers with unknown KEL are escrowed until the KEL is obtained,
+    /// e.g. with `query_kel`. Listeners registered with
+    /// `KeriRuntime::register_ksn_listener` are called for every accepted
+    /// notice.
+    pub fn refresh_ksn(
+        &self,
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<Vec<IdentifierState>, String> {
+        let processor = self.processor()?;
+        let subscriptions = self.ksn_subscriptions.read().unwrap().clone();
+        let mut updated = vec![];
+        for (of, watchers) in subscriptions {
+            for watcher in watchers {
+                let query = self.query_ksn(&of, &watcher)?;
+                let response = self.finalize_query(
+                    query.as_bytes(),
+                    signer.sign(query.as_bytes())?,
+                    transport,
+                )?;
+                let (reply, state) = parse_ksn_response(&response)?;
+                match processor.process_op_reply(&reply) {
+                    Ok(()) => (),
+                    Err(Error::QueryError(
+                        QueryError::StaleKsn | QueryError::StaleRpy,
+                    )) => continue,
+                    Err(e) => return Err(e.to_string()),
+                };
+                let signer = reply
+                    .signature
+                    .get_signer()
+                    .ok_or("Missing signer".to_string())?;
+                // Notice could be escrowed instead of accepted.
+                let accepted = self
+                    .event_storage
+                    .get_last_ksn_reply(&of, &signer)
+                    .map(|accepted| accepted.reply.digest())
+                    .transpose()
+                    .map_err(|e| e.to_string())?;
+                let digest =
+                    reply.reply.digest().map_err(|e| e.to_string())?;
+                if accepted == Some(digest) {
+                    updated.push(state);
+                }
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Signs end role reply generated by `add_watcher` and sends it, along
+    /// with identifier's KEL, to the watcher. Reply removing the role
+    /// unregisters the watcher. Returns the watcher.
+    pub fn register_watcher(
+        &self,
+        rpy: &[u8],
+        sig: SelfSigningPrefix,
+        transport: &dyn WatcherTransport,
+    ) -> Result<IdentifierPrefix, String> {
+        let adds = match parse_event_type(rpy)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::Rpy(rpy) => match rpy.get_route() {
+                ReplyRoute::EndRoleAdd(er) if er.role == Role::Watcher => true,
+                ReplyRoute::EndRoleCut(er) if er.role == Role::Watcher => false,
+                _ => return Err("Reply is not a watcher end role".to_string()),
+            },
+            _ => return Err("Event is not a reply".to_string()),
+        };
+        let (watcher, messages) = self.finalize_add_watcher(rpy, sig)?;
+        let stream = messages
+            .iter()
+            .map(|msg| msg.to_cesr())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+            .concat();
+        transport.send(&watcher, &stream)?;
+
+        let mut watchers = self.watchers.write().unwrap();
+        watchers.retain(|w| w != &watcher);
+        if adds {
+            watchers.push(watcher.clone());
+        }
+        Ok(watcher)
+    }
+
+    /// Returns watchers registered with `register_watcher`.
+    pub fn watchers(&self) -> Vec<IdentifierPrefix> {
+        self.watchers.read().unwrap().clone()
+    }
+
+    /// Asks all registered watchers to resolve `oobi` and observe
+    /// identifier it points to.
+    pub fn observe(
+        &self,
+        oobi: &Oobi,
+        transport: &dyn WatcherTransport,
+    ) -> Result<(), String> {
+        let watchers = self.watchers();
+        if watchers.is_empty() {
+            return Err("No watchers registered".to_string());
+        }
+        watchers
+            .iter()
+            .try_for_each(|watcher| transport.resolve_oobi(watcher, oobi))
+    }
+
+    /// Queries all registered watchers for key state of `of` identifier.
+    /// Key state is trusted only if at least `threshold` watchers report
+    /// the same one. Missing part of the KEL is then fetched from one of
+    /// agreeing watchers and their notices are processed. Unreachable
+    /// watchers and ones returning invalid notices are skipped. Returns
+    /// agreed key state.
+    pub fn query_watchers(
+        &self,
+        of: &IdentifierPrefix,
+        threshold: usize,
+        signer: &dyn QuerySigner,
+        transport: &dyn QueryTransport,
+    ) -> Result<IdentifierState, String> {
+        if threshold == 0 {
+            return Err("Threshold has to be positive".to_string());
+        }
+        let validator =
+            EventValidator::new(self.event_storage.events_db.clone());
+        let mut reports: Vec<(IdentifierPrefix, SignedReply, IdentifierState)> =
+            vec![];
+        for watcher in self.watchers() {
+            let query = self.query_ksn(of, &watcher)?;
+            let response = match self.finalize_query(
+                query.as_bytes(),
+                signer.sign(query.as_bytes())?,
+                transport,
+            ) {
+                Ok(response) => response,
+                Err(e) => {
+                    log::warn!("Watcher {} unreachable: {}", watcher, e);
+                    continue;
+                }
+            };
+            let verified =
+                parse_ksn_response(&response).and_then(|(reply, state)| {
+                    if reply.signature.get_signer().as_ref() != Some(&watcher)
+                        || &state.prefix != of
+                    {
+                        return Err("Unexpected notice".to_string());
+                    }
+                    let data =
+                        reply.reply.encode().map_err(|e| e.to_string())?;
+                    validator
+                        .verify(&data, &reply.signature)
+                        .map_err(|e| e.to_string())?;
+                    Ok((reply, state))
+                });
+            match verified {
+                Ok((reply, state)) => reports.push((watcher, reply, state)),
+                Err(e) => log::warn!("Wrong notice from {}: {}", watcher, e),
+            }
+        }
+
+        let agreeing = |state: &IdentifierState| {
+            reports
+                .iter()
+                .filter(|(_, _, other)| {
+                    other.sn == state.sn
+                        && other.last_event_digest == state.last_event_digest
+                })
+                .collect::<Vec<_>>()
+        };
+        let agreed = reports
+            .iter()
+            .map(|(_, _, state)| agreeing(state))
+            .max_by_key(|agreeing| agreeing.len())
+            .unwrap_or_default();
+        if agreed.len() < threshold {
+            return Err(format!(
+                "Only {} of required {} watchers agree on key state of {}",
+                agreed.len(),
+                threshold,
+                of
+            ));
+        }
+        let (source, _, state) = agreed[0];
+
+        let known_sn = self.event_storage.get_state(of).map(|state| state.sn);
+        if known_sn.is_none_or(|sn| sn < state.sn) {
+            self.query_kel(
+                of,
+                known_sn.map_or(0, |sn| sn + 1),
+                source,
+                KEL_QUERY_PAGE_SIZE,
+                signer,
+                transport,
+            )?;
+        }
+        let matches_kel = self
+            .event_storage
+            .get_event_at_sn(of, state.sn)
+            .map(|event| {
+                event
+                    .signed_event_message
+                    .event_message
+                    .compare_digest(&state.last_event_digest.clone().into())
+            })
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        if matches_kel != Some(true) {
+            return Err(format!(
+                "KEL of {} doesn't match agreed key state",
+                of
+            ));
+        }
+
+        let processor = self.processor()?;
+        for (_, reply, _) in &agreed {
+            match processor.process_op_reply(reply) {
+                Ok(())
+                | Err(Error::QueryError(
+                    QueryError::StaleKsn | QueryError::StaleRpy,
+                )) => (),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Ok(state.clone())
+    }
+
+    pub(crate) fn processor(&self) -> Result<&BasicProcessor<D>, String> {
+        self.processor
+            .as_deref()
+            .ok_or("Identifier has no processor".to_string())
+    }
+}
+
+/// Returns key state notice reply from witness or watcher response.
+fn parse_ksn_response(
+    response: &[u8],
+) -> Result<(SignedReply, IdentifierState), String> {
+    let reply = parse_reply_stream(response)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+        .ok_or("Empty response".to_string())?;
+    let ReplyRoute::Ksn(_, ksn) = reply.reply.get_route() else {
+        return Err("Response is not a key state notice".to_string());
+    };
+    Ok((reply, ksn.state))
+}
+
+fn encode_reply(rpy: ReplyEvent) -> Result<String, String> {
+    String::from_utf8(
+        rpy.encode()
+            .map_err(|_| "Event encoding error".to_string())?,
+    )
+    .map_err(|_| "Event format error".to_string())
+}
+
+fn tel_query_event(route: TelQueryRoute) -> TelQueryEvent {
+    KeriEvent::new(
+        SerializationFormats::JSON,
+        HashFunctionCode::Blake3_256.into(),
+        Timestamped::new(route),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::signer::Signer;
+
+    use super::*;
+    use crate::test_utils::{setup, sign};
+
+    #[test]
+    fn test_anchor() {
+        let (_root, controller) = setup("test-db");
+
+        let signer = Signer::new();
+        let icp = controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            )
+            .unwrap();
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &sign(&signer, icp.as_bytes()))
+            .unwrap();
+
+        let hash: HashFunction = HashFunctionCode::Blake3_256.into();
+        let documents = [hash.derive(b"first"), hash.derive(b"second")];
+        let ixn = identifier.anchor(&documents).unwrap();
+        let signed = identifier
+            .finalize_anchor(ixn.as_bytes(), sign(&signer, ixn.as_bytes()))
+            .unwrap();
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 1);
+
+        let expected = EventSeal::new(
+            identifier.id.clone(),
+            1,
+            signed.event_message.digest().unwrap(),
+        );
+        for document in &documents {
+            assert_eq!(
+                identifier.find_anchor(document).unwrap(),
+                Some(expected.clone())
+            );
+        }
+        assert_eq!(
+            identifier.find_anchor(&hash.derive(b"other")).unwrap(),
+            None
+        );
+
+        // Only identifier's own interaction events can be finalized.
+        assert!(identifier
+            .finalize_anchor(icp.as_bytes(), sign(&signer, icp.as_bytes()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_abandon() {
+        let (_root, controller) = setup("test-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+
+        let (signer, next_signer) = (Signer::new(), Signer::new());
+        let next_key = BasicPrefix::Ed25519(next_signer.public_key());
+        let icp = controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![next_key.clone()],
+            )
+            .unwrap();
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &sign(&signer, icp.as_bytes()))
+            .unwrap();
+
+        // Rotation committing to next keys isn't an abandonment.
+        let rot = identifier
+            .partial_rotate(
+                vec![next_key.clone()],
+                SignatureThreshold::Simple(1),
+                &[BasicPrefix::Ed25519(Signer::new().public_key())],
+                SignatureThreshold::Simple(1),
+            )
+            .unwrap();
+        let signature = IndexedSignature::new_both_same(
+            sign(&next_signer, rot.as_bytes()),
+            0,
+        );
+        assert!(identifier
+            .finalize_abandon(rot.as_bytes(), vec![signature], &publisher)
+            .is_err());
+
+        let rot = identifier
+            .abandon(vec![next_key], SignatureThreshold::Simple(1))
+            .unwrap();
+        let signature = IndexedSignature::new_both_same(
+            sign(&next_signer, rot.as_bytes()),
+            0,
+        );
+        identifier
+            .finalize_abandon(rot.as_bytes(), vec![signature], &publisher)
+            .unwrap();
+        let state = controller.get_state(&identifier.id).unwrap();
+        assert_eq!(state.sn, 1);
+        assert!(state.is_abandoned());
+
+        // Abandoned identifier can't anchor nor rotate anymore.
+        let ixn = identifier.anchor(&[]).unwrap();
+        assert!(identifier
+            .finalize_anchor(ixn.as_bytes(), sign(&next_signer, ixn.as_bytes()))
+            .is_err());
+        assert!(identifier
+            .abandon(vec![], SignatureThreshold::Simple(0))
+            .is_err());
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 1);
     }
 }
@@ -0,0 +1,321 @@
+use cesrox::cesr_proof::MaterialPath;
+use keri_core::{
+    actor::{event_generator, parse_event_stream, parse_exchange_stream},
+    database::{EscrowCreator, EventDatabase},
+    event_message::{
+        cesr_adapter::{parse_event_type, EventType},
+        signature::{Signature, SignerData},
+        signed_event_message::{Message, Op},
+    },
+    mailbox::exchange::{ExchangeRoute, Ipex, IpexData, SignedExchange},
+    prefix::{IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
+};
+use said::SelfAddressingIdentifier;
+use teliox::database::TelEventDatabase;
+
+use crate::{acdc::Acdc, Controller, Identifier};
+
+impl<D: EventDatabase> Identifier<D> {
+    /// Generates IPEX message of given route to `recipient`, e.g. apply for
+    /// credential of some schema, or admit presented one. `prior` is SAID
+    /// of IPEX message it responds to. Signed with `finalize_ipex`.
+    pub fn ipex(
+        &self,
+        route: ExchangeRoute,
+        recipient: &IdentifierPrefix,
+        prior: Option<SelfAddressingIdentifier>,
+        data: IpexData,
+    ) -> Result<String, String> {
+        let exn = event_generator::ipex_exchange(recipient, route, prior, data)
+            .map_err(|e| e.to_string())?
+            .encode()
+            .map_err(|_| "Event encoding error".to_string())?;
+        String::from_utf8(exn).map_err(|_| "Event format error".to_string())
+    }
+
+    /// Attaches identifier's signature to IPEX message. Returns CESR
+    /// stream, which should be sent to its recipient.
+    pub fn finalize_ipex(
+        &self,
+        exn: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<Vec<u8>, String> {
+        let exn = match parse_event_type(exn)
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::Exn(exn) => exn,
+            _ => return Err("Event is not an exchange".to_string()),
+        };
+        if exn.data.data.ipex_payload().is_none() {
+            return Err("Exchange is not an IPEX message".to_string());
+        }
+        let signed = SignedExchange {
+            exchange_message: exn,
+            signature: vec![Signature::Transferable(
+                SignerData::LastEstablishment(self.id.clone()),
+                vec![IndexedSignature::new_both_same(sig, 0)],
+            )],
+            data_signature: (MaterialPath::to_path("-a".into()), vec![]),
+        };
+        Message::Op(Op::Exchange(signed))
+            .to_cesr()
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Generates IPEX grant presenting `credential`, signed by its issuer
+    /// with `Identifier::sign_data`, to `verifier`. Grant carries issuer's
+    /// KEL and credential's TEL, so verifier can check it without querying
+    /// witnesses. Signed with `Identifier::finalize_ipex`.
+    pub fn present_credential(
+        &self,
+        holder: &Identifier<D>,
+        verifier: &IdentifierPrefix,
+        credential: &[u8],
+        prior: Option<SelfAddressingIdentifier>,
+    ) -> Result<String, String> {
+        let acdc = self.verify_credential(credential)?;
+        let kel = self
+            .kel
+            .storage
+            .get_kel(&acdc.issuer)
+            .map_err(|e| e.to_string())?
+            .ok_or("Unknown issuer KEL".to_string())?;
+        let tel = match &acdc.registry {
+            Some(_) => Some(self.credential_tel(&acdc.said()?)?),
+            None => None,
+        };
+        let data = IpexData {
+            message: String::new(),
+            schema: Some(acdc.schema.clone()),
+            acdc: Some(utf8(credential.to_vec())?),
+            kel: Some(utf8(kel)?),
+            tel,
+        };
+        holder.ipex(ExchangeRoute::IpexGrant, verifier, prior, data)
+    }
+
+    /// Verifies IPEX message received as CESR stream. Signer's KEL needs
+    /// to be known. Returns signer, route and payload of the message.
+    pub fn process_ipex(
+        &self,
+        stream: &[u8],
+    ) -> Result<(IdentifierPrefix, ExchangeRoute, Ipex), String> {
+        let exn = parse_exchange_stream(stream)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .next()
+            .ok_or("Empty IPEX message".to_string())?;
+        let exchange = &exn.exchange_message.data.data;
+        let ipex = exchange
+            .ipex_payload()
+            .ok_or("Exchange is not an IPEX message".to_string())?;
+        let signer = match exn.signature.as_slice() {
+            [signature] => signature
+                .get_signer()
+                .ok_or("Unknown IPEX message signer".to_string())?,
+            _ => return Err("Expected single IPEX signature".to_string()),
+        };
+        if !exn
+            .verify(self.kel.storage.as_ref())
+            .map_err(|e| e.to_string())?
+        {
+            return Err("Wrong IPEX message signature".to_string());
+        }
+        Ok((signer, exchange.route(), ipex.clone()))
+    }
+
+    /// Verifies credential presented with IPEX grant. Processes issuer's
+    /// KEL and credential's TEL carried by grant, then checks credential
+    /// as `verify_credential` does. Holder's KEL needs to be known.
+    /// Returns holder and presented credential.
+    pub fn verify_presentation(
+        &self,
+        stream: &[u8],
+    ) -> Result<(IdentifierPrefix, Acdc), String> {
+        let (holder, route, ipex) = self.process_ipex(stream)?;
+        if route != ExchangeRoute::IpexGrant {
+            return Err("IPEX message is not a grant".to_string());
+        }
+        if let Some(kel) = &ipex.data.kel {
+            let kel = parse_event_stream(kel.as_bytes())
+                .map_err(|e| e.to_string())?;
+            self.process_kel(&kel)?;
+        }
+        if let Some(tel) = &ipex.data.tel {
+            self.process_tel(tel.as_bytes())?;
+        }
+        let credential = ipex
+            .data
+            .acdc
+            .as_ref()
+            .ok_or("Grant carries no credential".to_string())?;
+        let acdc = self.verify_credential(credential.as_bytes())?;
+        if ipex.data.schema.as_ref() != Some(&acdc.schema) {
+            return Err("Credential schema doesn't match grant".to_string());
+        }
+        Ok((holder, acdc))
+    }
+
+    /// Returns registry TEL followed by TEL of credential `said`, as CESR
+    /// stream.
+    fn credential_tel(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<String, String> {
+        let tel = self.tel.get_tel(said).map_err(|e| e.to_string())?;
+        let tel = tel
+            .iter()
+            .map(|event| event.serialize().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        utf8(tel)
+    }
+}
+
+fn utf8(stream: Vec<u8>) -> Result<String, String> {
+    String::from_utf8(stream).map_err(|_| "CESR format error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{prefix::BasicPrefix, signer::Signer};
+    use said::derivation::{HashFunction, HashFunctionCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_present_credential() {
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let (_holder_root, holder_controller) = setup("holder-db");
+        let (issuer_signer, holder_signer) = (Signer::new(), Signer::new());
+        let issuer = incept(&holder_controller, &issuer_signer);
+        let holder = incept(&holder_controller, &holder_signer);
+        let (ixn, vcp) = holder_controller.incept_registry(&issuer).unwrap();
+        let registry = holder_controller
+            .finalize_incept_registry(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&issuer_signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let schema =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"schema");
+        let acdc = Acdc::new(
+            issuer.id.clone(),
+            Some(registry),
+            schema.clone(),
+            json!({"name": "John Doe"}).as_object().unwrap().clone(),
+        )
+        .unwrap();
+        let (ixn, iss) = holder_controller.issue_acdc(&issuer, &acdc).unwrap();
+        holder_controller
+            .finalize_issue_credential(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&issuer_signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let encoded = acdc.encode().unwrap();
+        let credential = issuer
+            .sign_data(&encoded, sign(&issuer_signer, &encoded))
+            .unwrap();
+
+        // Verifier knows only holder's KEL.
+        let (_root, controller) = setup("verifier-db");
+        let verifier_signer = Signer::new();
+        let verifier = incept(&controller, &verifier_signer);
+        let holder_kel = parse_event_stream(
+            &holder_controller
+                .kel
+                .storage
+                .get_kel(&holder.id)
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        controller.process_kel(&holder_kel).unwrap();
+
+        // Verifier applies for credential of the schema.
+        let apply = verifier
+            .ipex(
+                ExchangeRoute::IpexApply,
+                &holder.id,
+                None,
+                IpexData {
+                    schema: Some(schema.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let apply_digest = match parse_event_type(apply.as_bytes()).unwrap() {
+            EventType::Exn(exn) => exn.digest().unwrap(),
+            _ => unreachable!(),
+        };
+
+        // Holder grants it.
+        let grant = holder_controller
+            .present_credential(
+                &holder,
+                &verifier.id,
+                &credential,
+                Some(apply_digest),
+            )
+            .unwrap();
+        let signed_grant = holder
+            .finalize_ipex(
+                grant.as_bytes(),
+                sign(&holder_signer, grant.as_bytes()),
+            )
+            .unwrap();
+        let (presenter, presented) =
+            controller.verify_presentation(&signed_grant).unwrap();
+        assert_eq!(presenter, holder.id);
+        assert_eq!(presented, acdc);
+
+        // Grant signed with other keys is rejected.
+        let forged = holder
+            .finalize_ipex(
+                grant.as_bytes(),
+                sign(&Signer::new(), grant.as_bytes()),
+            )
+            .unwrap();
+        assert!(controller.verify_presentation(&forged).is_err());
+
+        // Only grant presents credential.
+        let admit = verifier
+            .ipex(
+                ExchangeRoute::IpexAdmit,
+                &holder.id,
+                None,
+                IpexData::default(),
+            )
+            .unwrap();
+        let signed_admit = verifier
+            .finalize_ipex(
+                admit.as_bytes(),
+                sign(&verifier_signer, admit.as_bytes()),
+            )
+            .unwrap();
+        let (signer, route, _) =
+            controller.process_ipex(&signed_admit).unwrap();
+        assert_eq!(
+            (signer, route),
+            (verifier.id.clone(), ExchangeRoute::IpexAdmit)
+        );
+        assert!(controller.verify_presentation(&signed_admit).is_err());
+    }
+}
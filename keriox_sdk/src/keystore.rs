@@ -0,0 +1,588 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use keri_core::{
+    database::{EscrowCreator, EventDatabase},
+    event::sections::RotationWitnessConfig,
+    event_message::{
+        cesr_adapter::{parse_event_type, EventType},
+        signed_event_message::{Notice, SignedEventMessage},
+    },
+    keys::{KeySource, OsKeySource, SecretSeed},
+    prefix::{BasicPrefix, IdentifierPrefix, SelfSigningPrefix},
+    processor::Processor,
+    signer::{KeyType, Signer},
+};
+use serde::{Deserialize, Serialize};
+use teliox::database::TelEventDatabase;
+use zeroize::Zeroizing;
+
+use crate::{Controller, Identifier};
+
+pub(crate) const SALT_LEN: usize = 16;
+
+/// Seeds of identifier's keys: current signing keys and next keys, whose
+/// digests are committed in the last establishment event. Seeds of keys
+/// rotated out are dropped, only their public keys are kept, so that they
+/// are never committed to again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdentifierKeys {
+    pub current: Vec<SecretSeed>,
+    pub next: Vec<SecretSeed>,
+    #[serde(default)]
+    pub retired: Vec<BasicPrefix>,
+}
+
+impl IdentifierKeys {
+    /// Generates `count` current and `count` next Ed25519 keys.
+    pub fn generate(count: usize) -> Self {
+        Self::generate_from(count, &OsKeySource)
+    }
+
+    /// Generates keys with bytes of `source`.
+    pub fn generate_from(count: usize, source: &dyn KeySource) -> Self {
+        Self {
+            current: (0..count).map(|_| random_seed(source)).collect(),
+            next: (0..count).map(|_| random_seed(source)).collect(),
+            retired: vec![],
+        }
+    }
+
+    pub fn signers(&self) -> Result<Vec<Signer>, String> {
+        signers(&self.current)
+    }
+
+    pub fn public_keys(&self) -> Result<Vec<BasicPrefix>, String> {
+        public_keys(&self.current)
+    }
+
+    pub fn next_public_keys(&self) -> Result<Vec<BasicPrefix>, String> {
+        public_keys(&self.next)
+    }
+
+    /// Makes next keys current ones, retires current keys and generates
+    /// new next keys. Should be saved once rotation event is accepted.
+    pub fn rotate(&mut self) -> Result<(), String> {
+        self.rotate_from(&OsKeySource)
+    }
+
+    /// Rotates keys, generating new next keys with bytes of `source`.
+    /// Fails if any of them was already exposed.
+    pub fn rotate_from(
+        &mut self,
+        source: &dyn KeySource,
+    ) -> Result<(), String> {
+        let next = (0..self.next.len())
+            .map(|_| random_seed(source))
+            .collect::<Vec<_>>();
+        let current = self.public_keys()?;
+        if public_keys(&next)?
+            .iter()
+            .any(|key| current.contains(key) || self.retired.contains(key))
+        {
+            return Err("New next key was already exposed".to_string());
+        }
+        self.retired.extend(current);
+        self.current = std::mem::replace(&mut self.next, next);
+        Ok(())
+    }
+}
+
+pub(crate) fn random_seed(source: &dyn KeySource) -> SecretSeed {
+    SecretSeed::generate_from(KeyType::Ed25519, source)
+}
+
+fn signers(seeds: &[SecretSeed]) -> Result<Vec<Signer>, String> {
+    seeds
+        .iter()
+        .map(|seed| Signer::new_with_seed(seed).map_err(|e| e.to_string()))
+        .collect()
+}
+
+pub(crate) fn public_keys(
+    seeds: &[SecretSeed],
+) -> Result<Vec<BasicPrefix>, String> {
+    Ok(signers(seeds)?
+        .iter()
+        .map(|signer| BasicPrefix::Ed25519(signer.public_key()))
+        .collect())
+}
+
+fn sign_with_first(
+    keys: &IdentifierKeys,
+    data: &[u8],
+) -> Result<SelfSigningPrefix, String> {
+    let signer = keys
+        .signers()?
+        .into_iter()
+        .next()
+        .ok_or("No signing key".to_string())?;
+    let signature = signer.sign(data).map_err(|e| e.to_string())?;
+    Ok(SelfSigningPrefix::Ed25519Sha512(signature))
+}
+
+/// Storage of identifiers' key seeds.
+pub trait KeyStore: Send + Sync {
+    /// Gives access to stored seeds. Stores which don't need passphrase
+    /// ignore it.
+    fn unlock(&self, passphrase: &str) -> Result<(), String>;
+
+    fn save(
+        &self,
+        id: &IdentifierPrefix,
+        keys: &IdentifierKeys,
+    ) -> Result<(), String>;
+
+    fn load(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<IdentifierKeys>, String>;
+
+    /// Returns identifiers whose seeds are stored.
+    fn identifiers(&self) -> Result<Vec<IdentifierPrefix>, String>;
+}
+
+/// Content of keystore file. Seeds of all identifiers are encrypted
+/// together, with ChaCha20-Poly1305 key derived from passphrase with
+/// Argon2id.
+#[derive(Serialize, Deserialize)]
+struct KeyStoreFile {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+struct Unlocked {
+    key: Zeroizing<[u8; 32]>,
+    salt: Vec<u8>,
+    keys: BTreeMap<String, IdentifierKeys>,
+}
+
+/// Key store keeping seeds in a file encrypted with passphrase derived key.
+/// File is created on first `save`.
+pub struct EncryptedFileKeyStore {
+    path: PathBuf,
+    unlocked: RwLock<Option<Unlocked>>,
+}
+
+impl EncryptedFileKeyStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            unlocked: RwLock::new(None),
+        }
+    }
+
+    /// Forgets passphrase derived key and decrypted seeds.
+    pub fn lock(&self) {
+        if let Ok(mut unlocked) = self.unlocked.write() {
+            *unlocked = None;
+        }
+    }
+
+    fn write(&self, unlocked: &Unlocked) -> Result<(), String> {
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(&unlocked.keys).map_err(|e| e.to_string())?,
+        );
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext =
+            ChaCha20Poly1305::new(Key::from_slice(unlocked.key.as_slice()))
+                .encrypt(&nonce, plaintext.as_slice())
+                .map_err(|_| "Keystore encryption error".to_string())?;
+        let file = KeyStoreFile {
+            salt: base64::encode(&unlocked.salt),
+            nonce: base64::encode(nonce),
+            ciphertext: base64::encode(ciphertext),
+        };
+        let content = serde_json::to_vec(&file).map_err(|e| e.to_string())?;
+        // Replace file at once, so it's never left partially written.
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+pub(crate) fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+) -> Result<Zeroizing<[u8; 32]>, String> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key[..])
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+fn read_file(path: &Path) -> Result<Option<KeyStoreFile>, String> {
+    match std::fs::read(path) {
+        Ok(content) => {
+            serde_json::from_slice(&content).map_err(|e| e.to_string())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+impl KeyStore for EncryptedFileKeyStore {
+    fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        let unlocked = match read_file(&self.path)? {
+            Some(file) => {
+                let decode = |field: &str| {
+                    base64::decode(field)
+                        .map_err(|_| "Keystore format error".to_string())
+                };
+                let salt = decode(&file.salt)?;
+                let nonce = decode(&file.nonce)?;
+                if nonce.len() != 12 {
+                    return Err("Keystore format error".to_string());
+                }
+                let key = derive_key(passphrase, &salt)?;
+                let plaintext = Zeroizing::new(
+                    ChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+                        .decrypt(
+                            Nonce::from_slice(&nonce),
+                            decode(&file.ciphertext)?.as_slice(),
+                        )
+                        .map_err(|_| "Wrong keystore passphrase".to_string())?,
+                );
+                let keys = serde_json::from_slice(&plaintext)
+                    .map_err(|_| "Keystore format error".to_string())?;
+                Unlocked { key, salt, keys }
+            }
+            None => {
+                let mut salt = vec![0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                Unlocked {
+                    key: derive_key(passphrase, &salt)?,
+                    salt,
+                    keys: BTreeMap::new(),
+                }
+            }
+        };
+        *self
+            .unlocked
+            .write()
+            .map_err(|_| "Keystore lock poisoned".to_string())? =
+            Some(unlocked);
+        Ok(())
+    }
+
+    fn save(
+        &self,
+        id: &IdentifierPrefix,
+        keys: &IdentifierKeys,
+    ) -> Result<(), String> {
+        let mut unlocked = self
+            .unlocked
+            .write()
+            .map_err(|_| "Keystore lock poisoned".to_string())?;
+        let unlocked =
+            unlocked.as_mut().ok_or("Keystore is locked".to_string())?;
+        unlocked.keys.insert(id.to_string(), keys.clone());
+        self.write(unlocked)
+    }
+
+    fn load(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<IdentifierKeys>, String> {
+        let unlocked = self
+            .unlocked
+            .read()
+            .map_err(|_| "Keystore lock poisoned".to_string())?;
+        let unlocked =
+            unlocked.as_ref().ok_or("Keystore is locked".to_string())?;
+        Ok(unlocked.keys.get(&id.to_string()).cloned())
+    }
+
+    fn identifiers(&self) -> Result<Vec<IdentifierPrefix>, String> {
+        let unlocked = self
+            .unlocked
+            .read()
+            .map_err(|_| "Keystore lock poisoned".to_string())?;
+        let unlocked =
+            unlocked.as_ref().ok_or("Keystore is locked".to_string())?;
+        unlocked
+            .keys
+            .keys()
+            .map(|id| id.parse().map_err(|_| "Invalid identifier".to_string()))
+            .collect()
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Unlocks key store set with `with_keystore`.
+    pub fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        self.keystore_ref()?.unlock(passphrase)
+    }
+
+    /// Returns seeds of identifier's keys from key store.
+    pub fn stored_keys(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<IdentifierKeys, String> {
+        self.keystore_ref()?
+            .load(id)?
+            .ok_or(format!("No keys of {} stored", id))
+    }
+
+    /// Saves seeds of identifier's keys in key store.
+    pub fn store_keys(
+        &self,
+        id: &IdentifierPrefix,
+        keys: &IdentifierKeys,
+    ) -> Result<(), String> {
+        self.keystore_ref()?.save(id, keys)
+    }
+
+    /// Incepts single key identifier with freshly generated keys, which
+    /// are saved in key store.
+    pub fn incept_with_keystore(&self) -> Result<Identifier<D>, String> {
+        let keys = IdentifierKeys::generate(1);
+        let icp = self
+            .incept(keys.public_keys()?, keys.next_public_keys()?)
+            .map_err(|_| "Event generation error".to_string())?;
+        let sig = sign_with_first(&keys, icp.as_bytes())?;
+        let identifier = self
+            .finalize_incept(icp.as_bytes(), &sig)
+            .map_err(|_| "Inception failed".to_string())?;
+        self.store_keys(&identifier.id, &keys)?;
+        Ok(identifier)
+    }
+
+    /// Signs `data` with the first current key of identifier, saved in key
+    /// store. Suits single key identifiers, e.g. the ones incepted with
+    /// `incept_with_keystore`.
+    pub fn sign_with_keystore(
+        &self,
+        id: &IdentifierPrefix,
+        data: &[u8],
+    ) -> Result<SelfSigningPrefix, String> {
+        sign_with_first(&self.stored_keys(id)?, data)
+    }
+
+    fn keystore_ref(&self) -> Result<&dyn KeyStore, String> {
+        self.keystore().ok_or("No keystore configured".to_string())
+    }
+}
+
+impl<D: EventDatabase + 'static> Identifier<D> {
+    /// Rotates identifier to next keys stored in `keystore`, committing to
+    /// freshly generated next keys with the same threshold. Rotated keys are
+    /// saved before the event is processed, so that new next keys are never
+    /// lost. Returns signed rotation, which should be published to
+    /// identifier's witnesses.
+    pub fn rotate(
+        &self,
+        keystore: &dyn KeyStore,
+    ) -> Result<SignedEventMessage, String> {
+        let state = self
+            .event_storage
+            .get_state(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let keys = keystore
+            .load(&self.id)?
+            .ok_or(format!("No keys of {} stored", self.id))?;
+        // Stored keys have to be the ones committed in the last
+        // establishment event, otherwise keystore is out of date.
+        let next_keys = keys.next_public_keys()?;
+        if next_keys.len()
+            != state.current.next_keys_data.next_keys_hashes().len()
+            || next_keys.iter().any(|key| {
+                state.current.next_keys_data.key_position(key).is_none()
+            })
+        {
+            return Err(
+                "Stored next keys are not the committed ones".to_string()
+            );
+        }
+
+        let mut rotated = keys.clone();
+        rotated.rotate()?;
+        let rot = self.rotate_witnesses(
+            next_keys,
+            state.current.threshold.clone(),
+            &rotated.next_public_keys()?,
+            state.current.next_keys_data.threshold.clone(),
+            RotationWitnessConfig {
+                tally: state.witness_config.tally.clone(),
+                prune: vec![],
+                graft: vec![],
+            },
+        )?;
+        self.finalize_key_rotation(keystore, &rot, &keys, &rotated)
+    }
+
+    /// Signs rotation event with current keys of `rotated`, which it
+    /// exposes. Rotated keys are saved before the event is processed, and
+    /// `previous` keys are restored if it's rejected.
+    pub(crate) fn finalize_key_rotation(
+        &self,
+        keystore: &dyn KeyStore,
+        rot: &str,
+        previous: &IdentifierKeys,
+        rotated: &IdentifierKeys,
+    ) -> Result<SignedEventMessage, String> {
+        let rotation_keys = rotated.public_keys()?;
+        let signatures = rotated
+            .signers()?
+            .iter()
+            .zip(&rotation_keys)
+            .map(|(signer, key)| {
+                let signature =
+                    signer.sign(rot.as_bytes()).map_err(|e| e.to_string())?;
+                self.rotation_signature(
+                    &rotation_keys,
+                    key,
+                    SelfSigningPrefix::Ed25519Sha512(signature),
+                )
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let rot = match parse_event_type(rot.as_bytes())
+            .map_err(|_| "Event parsing error".to_string())?
+        {
+            EventType::KeyEvent(rot) => rot.sign(signatures, None, None),
+            _ => return Err("Event is not a key event".to_string()),
+        };
+
+        keystore.save(&self.id, rotated)?;
+        // Rotation waiting for witness receipts is processed as well, only
+        // rejected one restores previous keys.
+        let processed = self.processor().and_then(|processor| {
+            processor
+                .process_notice(&Notice::Event(rot.clone()))
+                .map_err(|e| e.to_string())
+        });
+        if let Err(e) = processed {
+            keystore.save(&self.id, previous)?;
+            return Err(e);
+        }
+        Ok(rot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use keri_core::{
+        keys::SeededKeySource,
+        prefix::{CesrPrimitive, SelfSigningPrefix},
+    };
+
+    use super::*;
+    use crate::test_utils::setup;
+
+    #[test]
+    fn test_encrypted_keystore() {
+        let (root, controller) = setup("test-db");
+        let path = root.path().join("keys.json");
+        let controller = controller
+            .with_keystore(Arc::new(EncryptedFileKeyStore::new(&path)));
+        controller.unlock("passphrase").unwrap();
+
+        let keys = IdentifierKeys::generate(1);
+        let icp = controller
+            .incept(
+                keys.public_keys().unwrap(),
+                keys.next_public_keys().unwrap(),
+            )
+            .unwrap();
+        let signature = SelfSigningPrefix::Ed25519Sha512(
+            keys.signers().unwrap()[0].sign(icp.as_bytes()).unwrap(),
+        );
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &signature)
+            .unwrap();
+        controller.store_keys(&identifier.id, &keys).unwrap();
+
+        // Seeds aren't written in plain text.
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains(&keys.current[0].to_str()));
+
+        // Other store of the same file needs the passphrase.
+        let store = EncryptedFileKeyStore::new(&path);
+        assert!(store.load(&identifier.id).is_err());
+        assert!(store.unlock("wrong").is_err());
+        store.unlock("passphrase").unwrap();
+        assert_eq!(store.load(&identifier.id).unwrap(), Some(keys.clone()));
+        assert_eq!(store.identifiers().unwrap(), vec![identifier.id.clone()]);
+
+        let mut rotated = keys.clone();
+        rotated.rotate().unwrap();
+        assert_eq!(rotated.current, keys.next);
+        assert_ne!(rotated.next, keys.next);
+        store.save(&identifier.id, &rotated).unwrap();
+        controller.unlock("passphrase").unwrap();
+        assert_eq!(controller.stored_keys(&identifier.id).unwrap(), rotated);
+
+        store.lock();
+        assert!(store.load(&identifier.id).is_err());
+    }
+
+    #[test]
+    fn test_identifier_rotate() {
+        let (root, controller) = setup("test-db");
+        let store = EncryptedFileKeyStore::new(root.path().join("keys.json"));
+        store.unlock("passphrase").unwrap();
+
+        let keys = IdentifierKeys::generate(1);
+        let icp = controller
+            .incept(
+                keys.public_keys().unwrap(),
+                keys.next_public_keys().unwrap(),
+            )
+            .unwrap();
+        let signature = SelfSigningPrefix::Ed25519Sha512(
+            keys.signers().unwrap()[0].sign(icp.as_bytes()).unwrap(),
+        );
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &signature)
+            .unwrap();
+        assert!(identifier.rotate(&store).is_err());
+        store.save(&identifier.id, &keys).unwrap();
+
+        identifier.rotate(&store).unwrap();
+        let rotated = store.load(&identifier.id).unwrap().unwrap();
+        let state = controller.get_state(&identifier.id).unwrap();
+        assert_eq!(state.sn, 1);
+        assert_eq!(rotated.current, keys.next);
+        assert_eq!(state.current.public_keys, rotated.public_keys().unwrap());
+        assert_eq!(rotated.retired, keys.public_keys().unwrap());
+
+        identifier.rotate(&store).unwrap();
+        let state = controller.get_state(&identifier.id).unwrap();
+        assert_eq!(state.sn, 2);
+        assert_eq!(
+            store.load(&identifier.id).unwrap().unwrap().retired.len(),
+            2
+        );
+
+        // Keys which are no longer committed can't be used.
+        store.save(&identifier.id, &rotated).unwrap();
+        assert!(identifier.rotate(&store).is_err());
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 2);
+    }
+
+    #[test]
+    fn test_rotate_exposed_key() {
+        let source = SeededKeySource::new(1);
+        let mut keys = IdentifierKeys::generate_from(1, &source);
+        // Source replaying the same bytes would commit to exposed key.
+        let replay = SeededKeySource::new(1);
+        assert!(keys.rotate_from(&replay).is_err());
+        assert!(keys.rotate_from(&source).is_ok());
+    }
+}
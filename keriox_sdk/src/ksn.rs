@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use keri_core::{
+    error::Error,
+    prefix::IdentifierPrefix,
+    processor::notification::{Notification, NotificationBus, Notifier},
+    query::reply_event::ReplyRoute,
+    state::IdentifierState,
+};
+
+/// Receives key states from accepted key state notices.
+pub trait KsnListener: Send + Sync {
+    /// Called with identifier that signed the notice and the state it
+    /// reports.
+    fn ksn_updated(&self, signer: &IdentifierPrefix, state: &IdentifierState);
+}
+
+impl<F> KsnListener for F
+where
+    F: Fn(&IdentifierPrefix, &IdentifierState) + Send + Sync,
+{
+    fn ksn_updated(&self, signer: &IdentifierPrefix, state: &IdentifierState) {
+        self(signer, state)
+    }
+}
+
+/// Observes `KsnUpdated` notifications and passes reported key states to
+/// listener. Notices are validated by processor before notification is
+/// sent, so stale ones never reach the listener.
+pub struct KsnObserver {
+    listener: Arc<dyn KsnListener>,
+}
+
+impl KsnObserver {
+    pub fn new(listener: Arc<dyn KsnListener>) -> Self {
+        Self { listener }
+    }
+}
+
+impl Notifier for KsnObserver {
+    fn notify(
+        &self,
+        notification: &Notification,
+        _bus: &NotificationBus,
+    ) -> Result<(), Error> {
+        if let Notification::KsnUpdated(rpy) = notification {
+            if let ReplyRoute::Ksn(signer, ksn) = rpy.reply.get_route() {
+                self.listener.ksn_updated(&signer, &ksn.state);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, thread::sleep, time::Duration};
+
+    use keri_core::{
+        event_message::signed_event_message::{Message, Op},
+        prefix::{BasicPrefix, SelfSigningPrefix},
+        query::reply_event::SignedReply,
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_ksn_subscription() {
+        // Publisher signs notices of its own key state.
+        let (_publisher_root, publisher) = setup("publisher-db");
+        let publisher_signer = Signer::new();
+        let published = incept(&publisher, &publisher_signer);
+        let signed_ksn = || {
+            let rpy = published.ksn_reply().unwrap();
+            published
+                .finalize_reply(
+                    rpy.as_bytes(),
+                    sign(&publisher_signer, rpy.as_bytes()),
+                )
+                .unwrap()
+        };
+        let older = signed_ksn();
+        sleep(Duration::from_millis(10));
+        let newer = signed_ksn();
+
+        // Watcher returns newer notice first, and then the older one.
+        let responses = Mutex::new(vec![older, newer]);
+        let transport = move |_watcher: &IdentifierPrefix,
+                              _query: &[u8]|
+              -> Result<Vec<u8>, String> {
+            let rpy: SignedReply = responses
+                .lock()
+                .unwrap()
+                .pop()
+                .ok_or("No more notices".to_string())?;
+            Message::Op(Op::Reply(rpy))
+                .to_cesr()
+                .map_err(|e| e.to_string())
+        };
+
+        let (_root, controller) = setup("test-db");
+        let kel = publisher
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(&published.id)
+            .unwrap()
+            .unwrap();
+        controller
+            .process_kel(
+                &kel.into_iter().map(Message::Notice).collect::<Vec<_>>(),
+            )
+            .unwrap();
+        let updates = Arc::new(Mutex::new(vec![]));
+        let listener_updates = updates.clone();
+        controller.kel.register_ksn_listener(Arc::new(
+            move |signer: &IdentifierPrefix, state: &IdentifierState| {
+                listener_updates
+                    .lock()
+                    .unwrap()
+                    .push((signer.clone(), state.clone()));
+            },
+        ));
+
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let watcher = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        identifier.subscribe_ksn(published.id.clone(), watcher.clone());
+        let query_signer = |query: &[u8]| -> Result<SelfSigningPrefix, String> {
+            Ok(sign(&signer, query))
+        };
+
+        let updated =
+            identifier.refresh_ksn(&query_signer, &transport).unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].prefix, published.id);
+        assert_eq!(updated[0].sn, 0);
+        let (ksn_signer, state) = updates.lock().unwrap()[0].clone();
+        assert_eq!(ksn_signer, published.id);
+        assert_eq!(state.last_event_digest, updated[0].last_event_digest);
+
+        // Older notice is stale and is ignored.
+        let updated =
+            identifier.refresh_ksn(&query_signer, &transport).unwrap();
+        assert!(updated.is_empty());
+        assert_eq!(updates.lock().unwrap().len(), 1);
+
+        identifier.unsubscribe_ksn(&published.id, &watcher);
+        assert!(identifier
+            .refresh_ksn(&query_signer, &transport)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,9 +1,216 @@
+mod acdc;
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "aws-kms")]
+mod aws_kms;
+#[cfg(feature = "azure-keyvault")]
+mod azure_keyvault;
+#[cfg(feature = "oobi-manager")]
+mod builder;
+#[cfg(feature = "oobi-manager")]
+mod bundle;
+#[cfg(feature = "mailbox")]
+mod challenge;
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "storage-redb")]
+mod contacts;
 mod controller;
+mod credential;
+mod delegation;
+#[cfg(feature = "oobi-manager")]
+mod did;
+#[cfg(feature = "oobi-manager")]
+mod did_webs;
+mod edges;
+mod emergency;
+mod events;
+#[cfg(feature = "encryption")]
+mod encryption;
+#[cfg(feature = "gcp-kms")]
+mod gcp_kms;
+#[cfg(feature = "mailbox")]
+mod group;
+#[cfg(feature = "grpc")]
+mod grpc;
+#[cfg(feature = "hd-keys")]
+mod hd_keys;
+mod hooks;
 mod identifier;
+#[cfg(feature = "mailbox")]
+mod ipex;
+mod keystore;
+mod ksn;
+#[cfg(feature = "mailbox")]
+mod mailbox;
+#[cfg(feature = "storage-redb")]
+mod managed;
+#[cfg(feature = "storage-redb")]
+mod metadata;
+#[cfg(feature = "mobile")]
+mod mobile;
+mod network;
+mod next_keys;
+mod oobi;
+#[cfg(feature = "storage-redb")]
+mod outbox;
+#[cfg(feature = "p2p")]
+mod p2p;
+#[cfg(feature = "os-keychain")]
+mod os_keychain;
+#[cfg(feature = "storage-redb")]
+mod pending;
+#[cfg(feature = "piv")]
+mod piv;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+mod query;
+mod receipts;
+#[cfg(feature = "remote-keys")]
+mod remote_key;
+mod remote_signer;
+mod schema;
+mod signing;
+mod signing_policy;
+mod status_cache;
+mod tcp;
+#[cfg(feature = "oobi-manager")]
+mod tel_escrow;
+#[cfg(test)]
+mod test_utils;
+mod threshold;
+mod transport;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod watcher;
+mod witness;
 
+pub use acdc::{Acdc, AcdcSection};
+#[cfg(feature = "async")]
+pub use asynchronous::{
+    AsyncController, AsyncIdentifier, AsyncQueryTransport,
+    AsyncReceiptFetcher, AsyncWitnessPublisher, Blocking, BoxFuture,
+};
+#[cfg(feature = "aws-kms")]
+pub use aws_kms::{AwsKms, KmsSigner};
+#[cfg(feature = "azure-keyvault")]
+pub use azure_keyvault::AzureKeyVault;
+#[cfg(feature = "oobi-manager")]
+pub use builder::ControllerBuilder;
+#[cfg(feature = "oobi-manager")]
+pub use bundle::BundleContents;
+#[cfg(feature = "config")]
+pub use config::{EscrowTimeouts, RuntimeConfig, TransportConfig};
+#[cfg(feature = "storage-redb")]
+pub use contacts::{ChallengeStatus, Contact, ContactBook};
 pub use controller::{Controller, KeriRuntime};
+pub use credential::CredentialStatus;
+pub use delegation::delegating_seal;
+#[cfg(feature = "oobi-manager")]
+pub use delegation::DelegationObserver;
+#[cfg(feature = "oobi-manager")]
+pub use did::{
+    parse_did_keri, DidDocument, DidResolver, PublicKeyJwk, Service,
+    VerificationMethod, DID_KERI_PREFIX,
+};
+#[cfg(feature = "oobi-manager")]
+pub use did_webs::{
+    did_webs, parse_did_webs, DidWebsArtifacts, DID_WEBS_PREFIX,
+};
+pub use edges::{AcdcEdge, CredentialResolver};
+pub use emergency::{EmergencyReport, EmergencyRotation};
+pub use events::{EventHub, IdentifierEvent};
+#[cfg(feature = "encryption")]
+pub use encryption::{encryption_key, seal, SealedKey, SealedPayload};
+#[cfg(feature = "gcp-kms")]
+pub use gcp_kms::GcpKms;
+#[cfg(feature = "mailbox")]
+pub use group::GroupIdentifier;
+#[cfg(feature = "grpc")]
+pub use grpc::{proto as agent_proto, AgentService};
+#[cfg(feature = "hd-keys")]
+pub use hd_keys::HdKeyManager;
+pub use hooks::{
+    DelegationRequestContext, DuplicityContext, RotationContext,
+    WitnessReceiptContext,
+};
 pub use identifier::Identifier;
-pub use keri_core::{database, signer::Signer};
+pub use keri_core::{
+    database,
+    keys::{KeySource, OsKeySource, SecretSeed, SeededKeySource},
+    signer::Signer,
+};
+#[cfg(feature = "mailbox")]
+pub use keri_core::mailbox::exchange::{ExchangeRoute, Ipex, IpexData};
+pub use keystore::{EncryptedFileKeyStore, IdentifierKeys, KeyStore};
+pub use ksn::{KsnListener, KsnObserver};
+#[cfg(feature = "mailbox")]
+pub use mailbox::{
+    mailbox_items, MailboxCursor, MailboxItem, MailboxTopic, MailboxTransport,
+};
+#[cfg(feature = "storage-redb")]
+pub use managed::{IdentifierRegistry, ManagedRecord};
+#[cfg(feature = "storage-redb")]
+pub use metadata::IdentifierMetadata;
+#[cfg(feature = "mobile")]
+pub use mobile::{KeriError, MobileController, VerifiedData};
+pub use network::{NetworkPolicy, PolicyTransport};
+pub use next_keys::NextKeyManager;
+#[cfg(feature = "http")]
+pub use oobi::HttpOobiFetcher;
+pub use oobi::{oobi_identifier, OobiFetcher};
+#[cfg(feature = "oobi-manager")]
+pub use oobi::{KelResolver, OobiResolver};
+#[cfg(feature = "storage-redb")]
+pub use outbox::{
+    Outbox, OutboxEntry, OutboxKind, OutboxTransport, ResponseHandler,
+};
+#[cfg(feature = "p2p")]
+pub use p2p::{peer_id, peer_keypair, P2pTransport, PeerHandler};
+#[cfg(feature = "os-keychain")]
+pub use os_keychain::OsKeychainStore;
+#[cfg(feature = "storage-redb")]
+pub use pending::{PendingKind, PendingOperation, PendingOperations};
+#[cfg(feature = "piv")]
+pub use piv::{PivPrompt, PivSigner};
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::{Pkcs11Signer, TokenSelector};
+pub use query::{QuerySigner, QueryTransport, KEL_QUERY_PAGE_SIZE};
+pub use receipts::{ReceiptCollector, ReceiptFetcher, RetryPolicy};
+#[cfg(any(feature = "azure-keyvault", feature = "gcp-kms"))]
+pub use remote_key::AccessTokenProvider;
+#[cfg(feature = "remote-keys")]
+pub use remote_key::{RemoteKeyManager, RemoteKeyRef, RemoteKeyService};
+#[cfg(feature = "http")]
+pub use remote_signer::RemoteSigner;
+pub use remote_signer::SigningServer;
+#[cfg(feature = "http")]
+pub use schema::HttpSchemaResolver;
+pub use schema::{
+    saidify_schema, FileSchemaResolver, SchemaRegistry, SchemaResolver,
+};
+pub use signing::verify_signed_data;
+pub use signing_policy::{
+    PolicySigner, SigningApproval, SigningPolicy, SigningPurpose,
+};
+pub use status_cache::CredentialStatusCache;
+pub use tcp::TcpTransport;
+#[cfg(feature = "oobi-manager")]
+pub use tel_escrow::MissingAnchorObserver;
 pub use teliox::{
     database::TelEventDatabase, processor::storage::TelEventStorage,
+    query::TelStateNotice,
+};
+pub use threshold::{KeyHolder, ThresholdSigning};
+#[cfg(feature = "http")]
+pub use transport::HttpTransport;
+pub use transport::{Transport, TransportAdapter};
+#[cfg(feature = "wasm")]
+pub use wasm::{AsyncStore, BrowserController, FetchTransport, LocalBoxFuture};
+pub use watcher::WatcherTransport;
+pub use witness::{
+    ample, WitnessEntry, WitnessHealth, WitnessPool, WitnessPublisher,
+    WitnessSubmitter,
 };
+#[cfg(feature = "mobile")]
+uniffi::setup_scaffolding!();
@@ -0,0 +1,276 @@
+use keri_core::{
+    actor::prelude::Message,
+    event_message::signed_event_message::{
+        Notice, SignedEventMessage, SignedNontransferableReceipt,
+    },
+    mailbox::MailboxResponse,
+    prefix::IdentifierPrefix,
+    query::mailbox::QueryTopics,
+};
+
+/// Kinds of messages kept in identifier's mailbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MailboxTopic {
+    Receipt,
+    Multisig,
+    Delegate,
+}
+
+impl MailboxTopic {
+    pub const ALL: [MailboxTopic; 3] = [
+        MailboxTopic::Receipt,
+        MailboxTopic::Multisig,
+        MailboxTopic::Delegate,
+    ];
+}
+
+/// Indexes of the first not yet fetched messages of each topic. Messages
+/// aren't removed from mailbox after fetching, so cursor prevents getting
+/// them again.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MailboxCursor {
+    pub receipt: usize,
+    pub multisig: usize,
+    pub delegate: usize,
+}
+
+impl MailboxCursor {
+    pub fn to_query_topics(&self) -> QueryTopics {
+        QueryTopics {
+            receipt: self.receipt,
+            multisig: self.multisig,
+            delegate: self.delegate,
+            ..Default::default()
+        }
+    }
+
+    /// Moves cursor of provided topics past messages of `response`, which
+    /// were returned for query starting at `queried` indexes.
+    pub(crate) fn advance(
+        &mut self,
+        queried: &QueryTopics,
+        response: &MailboxResponse,
+        topics: &[MailboxTopic],
+    ) {
+        for topic in topics {
+            match topic {
+                MailboxTopic::Receipt => {
+                    self.receipt = queried.receipt + response.receipt.len()
+                }
+                MailboxTopic::Multisig => {
+                    self.multisig = queried.multisig + response.multisig.len()
+                }
+                MailboxTopic::Delegate => {
+                    self.delegate = queried.delegate + response.delegate.len()
+                }
+            }
+        }
+    }
+}
+
+/// Message fetched from mailbox.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MailboxItem {
+    /// Witness receipt of identifier's event.
+    Receipt(SignedNontransferableReceipt),
+    /// Group event proposed by other participant.
+    MultisigRequest(SignedEventMessage),
+    /// Delegated event waiting for delegator's approval.
+    DelegationRequest(SignedEventMessage),
+}
+
+impl MailboxItem {
+    pub fn topic(&self) -> MailboxTopic {
+        match self {
+            MailboxItem::Receipt(_) => MailboxTopic::Receipt,
+            MailboxItem::MultisigRequest(_) => MailboxTopic::Multisig,
+            MailboxItem::DelegationRequest(_) => MailboxTopic::Delegate,
+        }
+    }
+
+    /// Returns message that can be processed with `Controller::process_kel`.
+    pub fn to_message(&self) -> Message {
+        Message::Notice(match self {
+            MailboxItem::Receipt(rct) => {
+                Notice::NontransferableRct(rct.clone())
+            }
+            MailboxItem::MultisigRequest(event)
+            | MailboxItem::DelegationRequest(event) => {
+                Notice::Event(event.clone())
+            }
+        })
+    }
+}
+
+/// Sends signed mailbox query to witness or agent and returns its
+/// response.
+pub trait MailboxTransport: Send + Sync {
+    fn query(
+        &self,
+        witness: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String>;
+}
+
+impl<F> MailboxTransport for F
+where
+    F: Fn(&IdentifierPrefix, &[u8]) -> Result<String, String> + Send + Sync,
+{
+    fn query(
+        &self,
+        witness: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String> {
+        self(witness, query)
+    }
+}
+
+/// Splits mailbox response into items of provided topics.
+pub fn mailbox_items(
+    response: MailboxResponse,
+    topics: &[MailboxTopic],
+) -> Vec<MailboxItem> {
+    let mut items = vec![];
+    if topics.contains(&MailboxTopic::Receipt) {
+        items.extend(response.receipt.into_iter().map(MailboxItem::Receipt));
+    }
+    if topics.contains(&MailboxTopic::Multisig) {
+        items.extend(
+            response
+                .multisig
+                .into_iter()
+                .map(MailboxItem::MultisigRequest),
+        );
+    }
+    if topics.contains(&MailboxTopic::Delegate) {
+        items.extend(
+            response
+                .delegate
+                .into_iter()
+                .map(MailboxItem::DelegationRequest),
+        );
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        actor::{
+            event_generator, parse_event_stream,
+            possible_response::PossibleResponse,
+        },
+        event_message::{
+            cesr_adapter::{parse_event_type, EventType},
+            signed_event_message::Op,
+        },
+        prefix::{BasicPrefix, IndexedSignature},
+        query::query_event::SignedQueryMessage,
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{setup, sign};
+
+    #[test]
+    fn test_fetch_mailbox() {
+        let (_root, controller) = setup("test-db");
+        let incept = |signer: &Signer| {
+            controller
+                .incept(
+                    vec![BasicPrefix::Ed25519(signer.public_key())],
+                    vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+                )
+                .unwrap()
+        };
+
+        let signer = Signer::new();
+        let icp = incept(&signer);
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &sign(&signer, icp.as_bytes()))
+            .unwrap();
+
+        // Events proposed by other group participants.
+        let requests: Vec<SignedEventMessage> = (0..3)
+            .map(|_| {
+                let participant = Signer::new();
+                let icp = incept(&participant);
+                let EventType::KeyEvent(event) =
+                    parse_event_type(icp.as_bytes()).unwrap()
+                else {
+                    unreachable!()
+                };
+                let signature = IndexedSignature::new_both_same(
+                    sign(&participant, icp.as_bytes()),
+                    0,
+                );
+                event.sign(vec![signature], None, None)
+            })
+            .collect();
+
+        // Witness mailbox, which returns requested page of messages.
+        let mailbox = requests.clone();
+        let transport = move |_witness: &IdentifierPrefix, query: &[u8]| {
+            let Message::Op(Op::Query(SignedQueryMessage::MailboxQuery(qry))) =
+                parse_event_stream(query).unwrap().remove(0)
+            else {
+                return Err("Not a mailbox query".to_string());
+            };
+            let args = qry.query.get_args();
+            let from = args.topics.multisig.min(mailbox.len());
+            let to = args
+                .limit
+                .map_or(mailbox.len(), |limit| from + limit)
+                .min(mailbox.len());
+            let response = MailboxResponse {
+                receipt: vec![],
+                multisig: mailbox[from..to].to_vec(),
+                delegate: vec![],
+            };
+            Ok(PossibleResponse::Mbx(response).to_string())
+        };
+
+        let witness = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let mut fetched = vec![];
+        for expected in [2, 1, 0] {
+            let qry = identifier.query_mailbox(&witness, Some(2)).unwrap();
+            let items = identifier
+                .fetch_mailbox(
+                    qry.as_bytes(),
+                    sign(&signer, qry.as_bytes()),
+                    &MailboxTopic::ALL,
+                    &transport,
+                )
+                .unwrap();
+            assert_eq!(items.len(), expected);
+            fetched.extend(items);
+        }
+        let expected: Vec<_> = requests
+            .into_iter()
+            .map(MailboxItem::MultisigRequest)
+            .collect();
+        assert_eq!(fetched, expected);
+        assert_eq!(identifier.mailbox_cursor(&witness).multisig, 3);
+
+        // Query made by other identifier is rejected.
+        let qry = event_generator::mailbox_query(
+            &witness,
+            &identifier.id,
+            &witness,
+            QueryTopics::default(),
+            None,
+        )
+        .encode()
+        .unwrap();
+        assert!(identifier
+            .fetch_mailbox(
+                &qry,
+                sign(&signer, &qry),
+                &MailboxTopic::ALL,
+                &transport,
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,431 @@
+#[cfg(feature = "mailbox")]
+use std::collections::{HashMap, HashSet};
+use std::{path::Path, sync::Arc};
+
+use keri_core::{
+    database::{EscrowCreator, EventDatabase},
+    prefix::{BasicPrefix, IdentifierPrefix},
+};
+#[cfg(feature = "mailbox")]
+use keri_core::{
+    event::event_data::EventData,
+    event_message::signed_event_message::SignedEventMessage,
+};
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use teliox::database::TelEventDatabase;
+
+#[cfg(feature = "mailbox")]
+use crate::mailbox::MailboxItem;
+use crate::{metadata::IdentifierMetadata, Controller, Identifier};
+
+/// Identifiers store identifier -> JSON serialized record
+const IDENTIFIERS: TableDefinition<&str, &[u8]> =
+    TableDefinition::new("identifiers");
+
+/// Settings of identifier managed by controller.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ManagedRecord {
+    /// Witnesses used instead of the ones configured for controller.
+    pub witnesses: Option<Vec<BasicPrefix>>,
+    #[serde(default)]
+    pub metadata: IdentifierMetadata,
+}
+
+/// Persistent store of identifiers managed by controller, see
+/// `Controller::with_identifier_registry`.
+pub struct IdentifierRegistry {
+    db: Database,
+}
+
+impl IdentifierRegistry {
+    pub fn new(path: &Path) -> Result<Self, String> {
+        let db = Database::create(path).map_err(|e| e.to_string())?;
+        // Create table
+        let write_txn = db.begin_write().map_err(|e| e.to_string())?;
+        write_txn
+            .open_table(IDENTIFIERS)
+            .map_err(|e| e.to_string())?;
+        write_txn.commit().map_err(|e| e.to_string())?;
+        Ok(Self { db })
+    }
+
+    pub fn save(
+        &self,
+        id: &IdentifierPrefix,
+        record: &ManagedRecord,
+    ) -> Result<(), String> {
+        let value = serde_json::to_vec(record).map_err(|e| e.to_string())?;
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table = write_txn
+                .open_table(IDENTIFIERS)
+                .map_err(|e| e.to_string())?;
+            table
+                .insert(id.to_string().as_str(), value.as_slice())
+                .map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())
+    }
+
+    pub fn get(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<ManagedRecord>, String> {
+        let read_txn = self.db.begin_read().map_err(|e| e.to_string())?;
+        let table = read_txn
+            .open_table(IDENTIFIERS)
+            .map_err(|e| e.to_string())?;
+        let record = table
+            .get(id.to_string().as_str())
+            .map_err(|e| e.to_string())?
+            .map(|value| {
+                serde_json::from_slice(value.value()).map_err(|e| e.to_string())
+            })
+            .transpose();
+        record
+    }
+
+    /// Removes identifier and returns its record, if it was managed.
+    pub fn remove(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<ManagedRecord>, String> {
+        let removed = self.get(id)?;
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table = write_txn
+                .open_table(IDENTIFIERS)
+                .map_err(|e| e.to_string())?;
+            table
+                .remove(id.to_string().as_str())
+                .map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())?;
+        Ok(removed)
+    }
+
+    /// Returns all managed identifiers with their records.
+    pub fn list(
+        &self,
+    ) -> Result<Vec<(IdentifierPrefix, ManagedRecord)>, String> {
+        let read_txn = self.db.begin_read().map_err(|e| e.to_string())?;
+        let table = read_txn
+            .open_table(IDENTIFIERS)
+            .map_err(|e| e.to_string())?;
+        let records = table
+            .iter()
+            .map_err(|e| e.to_string())?
+            .map(|entry| {
+                let (id, value) = entry.map_err(|e| e.to_string())?;
+                let id = id.value().parse().map_err(|_| {
+                    format!("Invalid identifier {} in registry", id.value())
+                })?;
+                let record = serde_json::from_slice(value.value())
+                    .map_err(|e| e.to_string())?;
+                Ok((id, record))
+            })
+            .collect();
+        records
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    fn registry(&self) -> Result<&IdentifierRegistry, String> {
+        self.registry
+            .as_deref()
+            .ok_or("Identifier registry not set".to_string())
+    }
+
+    /// Adds identifier to the ones managed by controller and returns it,
+    /// so that it can be shared. Its settings are kept, if it was managed
+    /// before.
+    pub fn manage(
+        &self,
+        identifier: Identifier<D>,
+    ) -> Result<Arc<Identifier<D>>, String> {
+        let registry = self.registry()?;
+        if registry.get(&identifier.id)?.is_none() {
+            registry.save(&identifier.id, &ManagedRecord::default())?;
+        }
+        self.track_pending(&identifier.id)?;
+        let identifier = Arc::new(identifier);
+        self.managed
+            .write()
+            .map_err(|_| "Controller lock poisoned".to_string())?
+            .insert(identifier.id.clone(), identifier.clone());
+        Ok(identifier)
+    }
+
+    /// Stops managing identifier. Its KEL is kept.
+    pub fn unmanage(&self, id: &IdentifierPrefix) -> Result<(), String> {
+        self.registry()?.remove(id)?;
+        self.managed
+            .write()
+            .map_err(|_| "Controller lock poisoned".to_string())?
+            .remove(id);
+        Ok(())
+    }
+
+    /// Returns managed identifiers, ordered by prefix.
+    pub fn identifiers(&self) -> Result<Vec<IdentifierPrefix>, String> {
+        let mut ids = self
+            .registry()?
+            .list()?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        ids.sort_by_key(|id| id.to_string());
+        Ok(ids)
+    }
+
+    /// Returns managed identifier. Identifiers managed before restart are
+    /// loaded on first use.
+    pub fn identifier(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Arc<Identifier<D>>, String> {
+        if let Some(identifier) = self
+            .managed
+            .read()
+            .map_err(|_| "Controller lock poisoned".to_string())?
+            .get(id)
+        {
+            return Ok(identifier.clone());
+        }
+        if self.registry()?.get(id)?.is_none() {
+            return Err(format!("Identifier {} is not managed", id));
+        }
+        let identifier = Arc::new(self.load_identifier(id)?);
+        Ok(self
+            .managed
+            .write()
+            .map_err(|_| "Controller lock poisoned".to_string())?
+            .entry(id.clone())
+            .or_insert(identifier)
+            .clone())
+    }
+
+    /// Sets witnesses of managed identifier, used instead of the ones
+    /// configured for controller.
+    pub fn set_identifier_witnesses(
+        &self,
+        id: &IdentifierPrefix,
+        witnesses: Vec<BasicPrefix>,
+    ) -> Result<(), String> {
+        self.update_record(id, |record| record.witnesses = Some(witnesses))
+    }
+
+    pub(crate) fn managed_record(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<ManagedRecord, String> {
+        self.registry()?
+            .get(id)?
+            .ok_or(format!("Identifier {} is not managed", id))
+    }
+
+    pub(crate) fn managed_records(
+        &self,
+    ) -> Result<Vec<(IdentifierPrefix, ManagedRecord)>, String> {
+        self.registry()?.list()
+    }
+
+    pub(crate) fn update_record(
+        &self,
+        id: &IdentifierPrefix,
+        change: impl FnOnce(&mut ManagedRecord),
+    ) -> Result<(), String> {
+        let mut record = self.managed_record(id)?;
+        change(&mut record);
+        self.registry()?.save(id, &record)
+    }
+
+    /// Returns witnesses to be used by managed identifier, e.g. on
+    /// inception or witness rotation.
+    pub fn identifier_witnesses(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Vec<BasicPrefix>, String> {
+        let record = self.managed_record(id)?;
+        Ok(record.witnesses.unwrap_or_else(|| self.witnesses.clone()))
+    }
+
+    /// Groups mailbox items by managed identifier they are addressed to:
+    /// receipts to receipted identifier, delegation requests to delegator
+    /// and multisig requests to group members. Items not addressed to
+    /// managed identifier are skipped.
+    #[cfg(feature = "mailbox")]
+    pub fn route_mailbox(
+        &self,
+        items: Vec<MailboxItem>,
+    ) -> Result<HashMap<IdentifierPrefix, Vec<MailboxItem>>, String> {
+        let managed: HashSet<_> = self.identifiers()?.into_iter().collect();
+        let mut routed: HashMap<IdentifierPrefix, Vec<MailboxItem>> =
+            HashMap::new();
+        for item in items {
+            let recipients = match &item {
+                MailboxItem::Receipt(rct) => vec![rct.body.prefix.clone()],
+                MailboxItem::DelegationRequest(event) => {
+                    self.delegator_of(&event.event_message).into_iter().collect()
+                }
+                MailboxItem::MultisigRequest(event) => {
+                    self.members_of(event, &managed)
+                }
+            };
+            let recipients: Vec<_> = recipients
+                .into_iter()
+                .filter(|recipient| managed.contains(recipient))
+                .collect();
+            if recipients.is_empty() {
+                log::debug!("Skipping mailbox item of unmanaged identifier");
+            }
+            for recipient in recipients {
+                routed.entry(recipient).or_default().push(item.clone());
+            }
+        }
+        Ok(routed)
+    }
+
+    /// Managed identifiers, whose current key is one of group keys.
+    #[cfg(feature = "mailbox")]
+    fn members_of(
+        &self,
+        event: &SignedEventMessage,
+        managed: &HashSet<IdentifierPrefix>,
+    ) -> Vec<IdentifierPrefix> {
+        let keys = match &event.event_message.data.event_data {
+            EventData::Icp(icp) => icp.key_config.public_keys.clone(),
+            EventData::Dip(dip) => {
+                dip.inception_data.key_config.public_keys.clone()
+            }
+            EventData::Rot(rot) | EventData::Drt(rot) => {
+                rot.key_config.public_keys.clone()
+            }
+            EventData::Ixn(_) => self
+                .kel
+                .storage
+                .get_state(&event.event_message.data.get_prefix())
+                .map(|state| state.current.public_keys)
+                .unwrap_or_default(),
+        };
+        managed
+            .iter()
+            .filter(|id| {
+                self.kel.storage.get_state(id).is_some_and(|state| {
+                    state
+                        .current
+                        .public_keys
+                        .iter()
+                        .any(|key| keys.contains(key))
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::signer::Signer;
+
+    use super::*;
+    use crate::test_utils::{incept, setup};
+
+    #[test]
+    fn test_managed_identifiers() {
+        let (root, controller) = setup("test-db");
+        let path = root.path().join("identifiers");
+        let controller = controller.with_identifier_registry(Arc::new(
+            IdentifierRegistry::new(&path).unwrap(),
+        ));
+        let first = controller
+            .manage(incept(&controller, &Signer::new()))
+            .unwrap();
+        let second_signer = Signer::new();
+        let second = controller
+            .manage(incept(&controller, &second_signer))
+            .unwrap();
+        let other = incept(&controller, &Signer::new());
+
+        let mut expected = vec![first.id.clone(), second.id.clone()];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(controller.identifiers().unwrap(), expected);
+        assert!(Arc::ptr_eq(
+            &controller.identifier(&first.id).unwrap(),
+            &first
+        ));
+        assert!(controller.identifier(&other.id).is_err());
+
+        let witness = BasicPrefix::Ed25519NT(Signer::new().public_key());
+        controller
+            .set_identifier_witnesses(&second.id, vec![witness.clone()])
+            .unwrap();
+        assert!(controller
+            .identifier_witnesses(&first.id)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            controller.identifier_witnesses(&second.id).unwrap(),
+            vec![witness]
+        );
+        assert!(controller
+            .set_identifier_witnesses(&other.id, vec![])
+            .is_err());
+
+        #[cfg(feature = "mailbox")]
+        {
+            use keri_core::event_message::{
+                cesr_adapter::{parse_event_type, EventType},
+                signed_event_message::Notice,
+            };
+
+            // Group of second identifier and unmanaged one.
+            let icp = controller
+                .incept(
+                    vec![
+                        BasicPrefix::Ed25519(second_signer.public_key()),
+                        BasicPrefix::Ed25519(Signer::new().public_key()),
+                    ],
+                    vec![],
+                )
+                .unwrap();
+            let group_icp = match parse_event_type(icp.as_bytes()).unwrap() {
+                EventType::KeyEvent(event) => event.sign(vec![], None, None),
+                _ => unreachable!(),
+            };
+            let own_icp = match other.get_own_kel().unwrap().remove(0) {
+                Notice::Event(icp) => icp,
+                _ => unreachable!(),
+            };
+            let routed = controller
+                .route_mailbox(vec![
+                    MailboxItem::MultisigRequest(group_icp.clone()),
+                    MailboxItem::MultisigRequest(own_icp),
+                ])
+                .unwrap();
+            assert_eq!(routed.len(), 1);
+            assert_eq!(
+                routed[&second.id],
+                vec![MailboxItem::MultisigRequest(group_icp)]
+            );
+        }
+
+        // Managed identifiers and their settings survive restart.
+        controller.unmanage(&first.id).unwrap();
+        drop(controller);
+        let (_root, controller) = setup("test-db");
+        let controller = controller.with_identifier_registry(Arc::new(
+            IdentifierRegistry::new(&path).unwrap(),
+        ));
+        assert_eq!(controller.identifiers().unwrap(), vec![second.id.clone()]);
+        assert_eq!(
+            controller.identifier_witnesses(&second.id).unwrap().len(),
+            1
+        );
+    }
+}
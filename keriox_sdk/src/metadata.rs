@@ -0,0 +1,215 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use keri_core::{
+    database::{EscrowCreator, EventDatabase},
+    prefix::IdentifierPrefix,
+};
+use serde::{Deserialize, Serialize};
+use teliox::database::TelEventDatabase;
+
+use crate::Controller;
+
+/// Labels of managed identifier, kept in identifier registry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdentifierMetadata {
+    /// Display name, unique among managed identifiers.
+    pub name: Option<String>,
+    /// Purpose tags, e.g. `signing` or `issuer`.
+    pub tags: BTreeSet<String>,
+    /// Context identifier was created in, e.g. application or device.
+    pub context: BTreeMap<String, String>,
+}
+
+impl IdentifierMetadata {
+    fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.name
+            .iter()
+            .chain(&self.tags)
+            .chain(self.context.values())
+            .any(|value| value.to_lowercase().contains(&query))
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    pub fn identifier_metadata(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<IdentifierMetadata, String> {
+        Ok(self.managed_record(id)?.metadata)
+    }
+
+    /// Replaces metadata of managed identifier. Fails if its name is
+    /// taken by other identifier.
+    pub fn set_identifier_metadata(
+        &self,
+        id: &IdentifierPrefix,
+        metadata: IdentifierMetadata,
+    ) -> Result<(), String> {
+        if let Some(name) = &metadata.name {
+            let owner = self.identifier_by_name(name)?;
+            if owner.is_some_and(|owner| &owner != id) {
+                return Err(format!("Name {} already exists", name));
+            }
+        }
+        self.update_record(id, |record| record.metadata = metadata)
+    }
+
+    pub fn set_identifier_name(
+        &self,
+        id: &IdentifierPrefix,
+        name: &str,
+    ) -> Result<(), String> {
+        let mut metadata = self.identifier_metadata(id)?;
+        metadata.name = Some(name.to_string());
+        self.set_identifier_metadata(id, metadata)
+    }
+
+    pub fn tag_identifier(
+        &self,
+        id: &IdentifierPrefix,
+        tag: &str,
+    ) -> Result<(), String> {
+        self.update_record(id, |record| {
+            record.metadata.tags.insert(tag.to_string());
+        })
+    }
+
+    pub fn untag_identifier(
+        &self,
+        id: &IdentifierPrefix,
+        tag: &str,
+    ) -> Result<(), String> {
+        self.update_record(id, |record| {
+            record.metadata.tags.remove(tag);
+        })
+    }
+
+    /// Returns managed identifier of given display name.
+    pub fn identifier_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<IdentifierPrefix>, String> {
+        Ok(self
+            .managed_records()?
+            .into_iter()
+            .find(|(_, record)| record.metadata.name.as_deref() == Some(name))
+            .map(|(id, _)| id))
+    }
+
+    /// Returns managed identifiers tagged with `tag`, ordered by prefix.
+    pub fn identifiers_with_tag(
+        &self,
+        tag: &str,
+    ) -> Result<Vec<IdentifierPrefix>, String> {
+        self.find_identifiers(|metadata| metadata.tags.contains(tag))
+    }
+
+    /// Returns managed identifiers whose name, tags or context values
+    /// contain `query`, ignoring case.
+    pub fn search_identifiers(
+        &self,
+        query: &str,
+    ) -> Result<Vec<IdentifierPrefix>, String> {
+        self.find_identifiers(|metadata| metadata.matches(query))
+    }
+
+    fn find_identifiers(
+        &self,
+        filter: impl Fn(&IdentifierMetadata) -> bool,
+    ) -> Result<Vec<IdentifierPrefix>, String> {
+        let mut ids = self
+            .managed_records()?
+            .into_iter()
+            .filter(|(_, record)| filter(&record.metadata))
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        ids.sort_by_key(|id| id.to_string());
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use keri_core::signer::Signer;
+
+    use super::*;
+    use crate::{
+        test_utils::{incept, setup},
+        IdentifierRegistry,
+    };
+
+    #[test]
+    fn test_identifier_metadata() {
+        let (root, controller) = setup("test-db");
+        let path = root.path().join("identifiers");
+        let controller = controller.with_identifier_registry(Arc::new(
+            IdentifierRegistry::new(&path).unwrap(),
+        ));
+        let issuer = controller
+            .manage(incept(&controller, &Signer::new()))
+            .unwrap();
+        let signer = controller
+            .manage(incept(&controller, &Signer::new()))
+            .unwrap();
+        let other = incept(&controller, &Signer::new());
+
+        controller
+            .set_identifier_metadata(
+                &issuer.id,
+                IdentifierMetadata {
+                    name: Some("Issuer".to_string()),
+                    tags: ["issuer".to_string(), "signing".to_string()].into(),
+                    context: [("app".to_string(), "Wallet".to_string())].into(),
+                },
+            )
+            .unwrap();
+        controller.tag_identifier(&signer.id, "signing").unwrap();
+        assert!(controller
+            .set_identifier_name(&signer.id, "Issuer")
+            .is_err());
+        controller
+            .set_identifier_name(&signer.id, "Signer")
+            .unwrap();
+        // Renaming to own name is fine.
+        controller
+            .set_identifier_name(&signer.id, "Signer")
+            .unwrap();
+        assert!(controller.tag_identifier(&other.id, "signing").is_err());
+
+        let mut both = vec![issuer.id.clone(), signer.id.clone()];
+        both.sort_by_key(|id| id.to_string());
+        assert_eq!(controller.identifiers_with_tag("signing").unwrap(), both);
+        assert_eq!(
+            controller.identifiers_with_tag("issuer").unwrap(),
+            vec![issuer.id.clone()]
+        );
+        assert_eq!(
+            controller.identifier_by_name("Signer").unwrap(),
+            Some(signer.id.clone())
+        );
+        assert_eq!(
+            controller.search_identifiers("wallet").unwrap(),
+            vec![issuer.id.clone()]
+        );
+
+        controller.untag_identifier(&issuer.id, "signing").unwrap();
+        assert_eq!(
+            controller.identifiers_with_tag("signing").unwrap(),
+            vec![signer.id.clone()]
+        );
+
+        // Metadata is persisted with managed identifiers.
+        drop(controller);
+        let registry = IdentifierRegistry::new(&path).unwrap();
+        let metadata = registry.get(&issuer.id).unwrap().unwrap().metadata;
+        assert_eq!(metadata.name.as_deref(), Some("Issuer"));
+        assert_eq!(metadata.context["app"], "Wallet");
+    }
+}
@@ -0,0 +1,277 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use keri_core::{
+    database::redb::RedbDatabase,
+    oobi_manager::OobiManager,
+    prefix::{BasicPrefix, CesrPrimitive, IdentifierPrefix, SelfSigningPrefix},
+};
+use said::SelfAddressingIdentifier;
+use serde_json::{Map, Value};
+use teliox::database::redb::RedbTelDatabase;
+
+use crate::{
+    acdc::Acdc,
+    keystore::EncryptedFileKeyStore,
+    oobi::{HttpOobiFetcher, OobiResolver},
+    witness::WitnessPublisher,
+    Controller, Identifier,
+};
+
+/// Error returned to foreign code, carrying SDK error message.
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum KeriError {
+    Failed(String),
+}
+
+impl Display for KeriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeriError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for KeriError {
+    fn from(message: String) -> Self {
+        KeriError::Failed(message)
+    }
+}
+
+/// Data verified with `MobileController::verify`.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct VerifiedData {
+    pub signer: String,
+    pub data: Vec<u8>,
+}
+
+/// Controller for Kotlin and Swift apps. Identifiers are single key
+/// identifiers without witnesses, whose seeds are kept in encrypted key
+/// store, so that apps pass identifiers around as strings and never see
+/// keys or events.
+#[derive(uniffi::Object)]
+pub struct MobileController {
+    inner: Controller<RedbDatabase, RedbTelDatabase>,
+}
+
+#[uniffi::export]
+impl MobileController {
+    /// Opens controller whose databases are kept in `db_path` and key
+    /// store, unlocked with `passphrase`, in `keystore_path`.
+    #[uniffi::constructor]
+    pub fn new(
+        db_path: String,
+        keystore_path: String,
+        passphrase: String,
+    ) -> Result<Arc<Self>, KeriError> {
+        let inner = Controller::builder()
+            .with_db_path(db_path)
+            .with_keystore(Arc::new(EncryptedFileKeyStore::new(keystore_path)))
+            .with_oobi_fetcher(Arc::new(HttpOobiFetcher::new()))
+            .build()?;
+        inner.unlock(&passphrase)?;
+        Ok(Arc::new(Self { inner }))
+    }
+
+    /// Incepts identifier with freshly generated keys and returns its
+    /// prefix.
+    pub fn incept(&self) -> Result<String, KeriError> {
+        let identifier = self.inner.incept_with_keystore()?;
+        Ok(self.inner.manage(identifier)?.id.to_string())
+    }
+
+    /// Rotates identifier to its next keys.
+    pub fn rotate(&self, id: String) -> Result<(), KeriError> {
+        let identifier = self.identifier(&id)?;
+        let keystore = self
+            .inner
+            .keystore()
+            .ok_or("No keystore configured".to_string())?;
+        identifier.rotate(keystore)?;
+        Ok(())
+    }
+
+    /// Signs JSON `data` with identifier's current key. Returns CESR
+    /// stream of data followed by the signature.
+    pub fn sign(
+        &self,
+        id: String,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, KeriError> {
+        let identifier = self.identifier(&id)?;
+        let sig = self.sign_as(&identifier, &data)?;
+        Ok(identifier.sign_data(&data, sig)?)
+    }
+
+    /// Verifies data signed with `sign` by identifier whose KEL is known.
+    pub fn verify(&self, stream: Vec<u8>) -> Result<VerifiedData, KeriError> {
+        let (signer, data) = self.inner.verify_signed_data(&stream)?;
+        Ok(VerifiedData {
+            signer: signer.to_string(),
+            data,
+        })
+    }
+
+    /// Resolves OOBI URL and returns identifier it is about.
+    pub fn resolve_oobi(&self, url: String) -> Result<String, KeriError> {
+        let resolver = OobiResolver::new(
+            self.inner.kel.processor.clone(),
+            self.inner.kel.storage.clone(),
+            Arc::new(OobiManager::new(
+                self.inner.kel.storage.events_db.clone(),
+            )),
+            Arc::new(HttpOobiFetcher::new()),
+        );
+        Ok(resolver.resolve_oobi(&url)?.to_string())
+    }
+
+    /// Incepts credential registry managed by identifier and returns its
+    /// identifier.
+    pub fn incept_registry(&self, id: String) -> Result<String, KeriError> {
+        let identifier = self.identifier(&id)?;
+        let (ixn, vcp) = self.inner.incept_registry(&identifier)?;
+        let sig = self.sign_as(&identifier, ixn.as_bytes())?;
+        let registry = self.inner.finalize_incept_registry(
+            &identifier,
+            ixn.as_bytes(),
+            sig,
+            vcp.as_bytes(),
+            &no_witnesses(),
+        )?;
+        Ok(registry.to_string())
+    }
+
+    /// Issues ACDC of `schema` with JSON object `attributes` in
+    /// `registry`. Returns signed credential, to be handed to holder.
+    pub fn issue_credential(
+        &self,
+        id: String,
+        registry: String,
+        schema: String,
+        attributes: String,
+    ) -> Result<Vec<u8>, KeriError> {
+        let identifier = self.identifier(&id)?;
+        let attributes: Map<String, Value> =
+            serde_json::from_str(&attributes).map_err(|e| e.to_string())?;
+        let acdc = Acdc::new(
+            identifier.id.clone(),
+            Some(parse_prefix(&registry)?),
+            SelfAddressingIdentifier::from_str(&schema)
+                .map_err(|e| e.to_string())?,
+            attributes,
+        )?;
+        let (ixn, iss) = self.inner.issue_acdc(&identifier, &acdc)?;
+        let sig = self.sign_as(&identifier, ixn.as_bytes())?;
+        self.inner.finalize_issue_credential(
+            &identifier,
+            ixn.as_bytes(),
+            sig,
+            iss.as_bytes(),
+            &no_witnesses(),
+        )?;
+        let data = acdc.encode()?;
+        let sig = self.sign_as(&identifier, &data)?;
+        Ok(identifier.sign_data(&data, sig)?)
+    }
+
+    /// Verifies signed credential, see `Controller::verify_credential`.
+    /// Returns the ACDC as JSON.
+    pub fn verify_credential(
+        &self,
+        stream: Vec<u8>,
+    ) -> Result<String, KeriError> {
+        let acdc = self.inner.verify_credential(&stream)?;
+        Ok(String::from_utf8(acdc.encode()?).map_err(|e| e.to_string())?)
+    }
+
+    /// Returns managed identifiers.
+    pub fn identifiers(&self) -> Result<Vec<String>, KeriError> {
+        Ok(self
+            .inner
+            .identifiers()?
+            .iter()
+            .map(|id| id.to_string())
+            .collect())
+    }
+}
+
+impl MobileController {
+    fn identifier(
+        &self,
+        id: &str,
+    ) -> Result<Arc<Identifier<RedbDatabase>>, String> {
+        self.inner.identifier(&parse_prefix(id)?)
+    }
+
+    fn sign_as(
+        &self,
+        identifier: &Identifier<RedbDatabase>,
+        data: &[u8],
+    ) -> Result<SelfSigningPrefix, String> {
+        self.inner.sign_with_keystore(&identifier.id, data)
+    }
+}
+
+fn parse_prefix(id: &str) -> Result<IdentifierPrefix, String> {
+    id.parse().map_err(|_| format!("Invalid identifier {}", id))
+}
+
+/// Identifiers of mobile controller have no witnesses to publish to.
+fn no_witnesses() -> impl WitnessPublisher {
+    |witness: &BasicPrefix, _stream: &[u8]| -> Result<(), String> {
+        Err(format!("No transport to witness {}", witness.to_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::test_utils::person_schema;
+
+    #[test]
+    fn test_mobile_controller() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let open = || {
+            MobileController::new(
+                root.path().join("db").to_string_lossy().to_string(),
+                root.path().join("keys.json").to_string_lossy().to_string(),
+                "passphrase".to_string(),
+            )
+            .unwrap()
+        };
+        let controller = open();
+        let id = controller.incept().unwrap();
+        assert_eq!(controller.identifiers().unwrap(), vec![id.clone()]);
+
+        let signed = controller
+            .sign(id.clone(), br#"{"hello":"world"}"#.to_vec())
+            .unwrap();
+        controller.rotate(id.clone()).unwrap();
+        // Signature stays valid after rotation.
+        let verified = controller.verify(signed).unwrap();
+        assert_eq!(verified.signer, id);
+        assert_eq!(verified.data, br#"{"hello":"world"}"#.to_vec());
+        assert!(controller.sign(id.clone(), b"not json".to_vec()).is_err());
+
+        let registry = controller.incept_registry(id.clone()).unwrap();
+        let schema = person_schema()["$id"].as_str().unwrap().to_string();
+        let credential = controller
+            .issue_credential(
+                id.clone(),
+                registry,
+                schema,
+                r#"{"name":"Alice","age":30}"#.to_string(),
+            )
+            .unwrap();
+
+        // Identifiers and their keys survive restart.
+        drop(controller);
+        let controller = open();
+        assert_eq!(controller.identifiers().unwrap(), vec![id.clone()]);
+        let acdc = controller.verify_credential(credential).unwrap();
+        assert!(acdc.contains("Alice"));
+        controller.rotate(id).unwrap();
+    }
+}
@@ -0,0 +1,459 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use keri_core::prefix::{BasicPrefix, IdentifierPrefix};
+use rand_core::{OsRng, RngCore};
+use url::Url;
+
+use crate::{receipts::RetryPolicy, transport::Transport};
+
+/// How network operations of controller are retried and limited.
+/// Recipient that failed `failure_threshold` times in a row isn't
+/// contacted for `cooldown`, then single trial request decides whether it
+/// is used again.
+#[derive(Debug, Clone)]
+pub struct NetworkPolicy {
+    pub retry: RetryPolicy,
+    /// Part of backoff that is randomized, between 0 and 1, so that
+    /// controllers don't retry in lockstep.
+    pub jitter: f64,
+    /// How long single request may take, unless set for recipient.
+    pub timeout: Duration,
+    pub recipient_timeouts: HashMap<IdentifierPrefix, Duration>,
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+    /// How many requests may be in progress at once. Requests that timed
+    /// out count until wrapped transport returns, so hanging endpoints
+    /// can't exhaust threads.
+    pub max_pending_requests: usize,
+}
+
+impl Default for NetworkPolicy {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            jitter: 0.5,
+            timeout: Duration::from_secs(30),
+            recipient_timeouts: HashMap::new(),
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(60),
+            max_pending_requests: 16,
+        }
+    }
+}
+
+impl NetworkPolicy {
+    /// Sets how long requests to `recipient`, e.g. slow witness, may take.
+    pub fn with_recipient_timeout(
+        mut self,
+        recipient: IdentifierPrefix,
+        timeout: Duration,
+    ) -> Self {
+        self.recipient_timeouts.insert(recipient, timeout);
+        self
+    }
+
+    fn timeout_of(&self, recipient: &IdentifierPrefix) -> Duration {
+        self.recipient_timeouts
+            .get(recipient)
+            .copied()
+            .unwrap_or(self.timeout)
+    }
+
+    /// Backoff randomly shortened by up to `jitter` of it.
+    fn jittered(&self, backoff: Duration) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let random = OsRng.next_u32() as f64 / u32::MAX as f64;
+        backoff.mul_f64(1.0 - jitter * random)
+    }
+}
+
+/// Consecutive failures of endpoint.
+#[derive(Default)]
+struct Circuit {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Fixed set of threads running requests of wrapped transport, so that
+/// caller can stop waiting for them after timeout. Threads exit when pool
+/// is dropped.
+struct RequestPool {
+    jobs: mpsc::Sender<Job>,
+    size: usize,
+    pending: Arc<AtomicUsize>,
+}
+
+impl RequestPool {
+    fn new(size: usize) -> Self {
+        let size = size.max(1);
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..size {
+            let receiver = receiver.clone();
+            std::thread::spawn(move || loop {
+                let job = match receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => return,
+                };
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => return,
+                }
+            });
+        }
+        Self {
+            jobs,
+            size,
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Runs `request` on pool thread and waits for its result up to
+    /// `timeout`. Fails immediately if all threads are busy.
+    fn run<R, F>(
+        &self,
+        transport: Arc<dyn Transport>,
+        timeout: Duration,
+        request: F,
+    ) -> Result<Result<R, String>, String>
+    where
+        R: Send + 'static,
+        F: FnOnce(&dyn Transport) -> Result<R, String> + Send + 'static,
+    {
+        let size = self.size;
+        self.pending
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+                (pending < size).then_some(pending + 1)
+            })
+            .map_err(|_| "Too many pending requests".to_string())?;
+        let (sender, receiver) = mpsc::channel();
+        let pending = self.pending.clone();
+        let job: Job = Box::new(move || {
+            let response = request(transport.as_ref());
+            pending.fetch_sub(1, Ordering::SeqCst);
+            let _ = sender.send(response);
+        });
+        if self.jobs.send(job).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return Err("Request pool stopped".to_string());
+        }
+        Ok(receiver
+            .recv_timeout(timeout)
+            .map_err(|_| format!("Request timed out after {:?}", timeout))
+            .and_then(|response| response))
+    }
+}
+
+/// Transport applying `NetworkPolicy` to requests of wrapped transport.
+/// Endpoints are recipients, and origins of OOBI URLs.
+pub struct PolicyTransport {
+    inner: Arc<dyn Transport>,
+    policy: NetworkPolicy,
+    circuits: Mutex<HashMap<String, Circuit>>,
+    pool: RequestPool,
+}
+
+impl PolicyTransport {
+    pub fn new(inner: Arc<dyn Transport>, policy: NetworkPolicy) -> Self {
+        let pool = RequestPool::new(policy.max_pending_requests);
+        Self {
+            inner,
+            policy,
+            circuits: Mutex::new(HashMap::new()),
+            pool,
+        }
+    }
+
+    pub fn policy(&self) -> &NetworkPolicy {
+        &self.policy
+    }
+
+    /// Checks whether `endpoint` is currently not contacted because of
+    /// repeated failures.
+    pub fn is_open(&self, endpoint: &str) -> bool {
+        let now = Instant::now();
+        self.circuits
+            .lock()
+            .map(|circuits| {
+                circuits
+                    .get(endpoint)
+                    .and_then(|circuit| circuit.open_until)
+                    .is_some_and(|until| until > now)
+            })
+            .unwrap_or(false)
+    }
+
+    fn record(&self, endpoint: &str, success: bool) {
+        if let Ok(mut circuits) = self.circuits.lock() {
+            if success {
+                circuits.remove(endpoint);
+                return;
+            }
+            let circuit = circuits.entry(endpoint.to_string()).or_default();
+            circuit.failures += 1;
+            // Failed trial request after cooldown opens circuit again.
+            if circuit.failures >= self.policy.failure_threshold {
+                log::warn!("Circuit of {} opened", endpoint);
+                circuit.open_until =
+                    Some(Instant::now() + self.policy.cooldown);
+            }
+        }
+    }
+
+    /// Calls `request` on wrapped transport until it succeeds, retries are
+    /// exhausted or circuit of `endpoint` opens. Requests that can't start
+    /// because too many are pending aren't retried.
+    fn call<R, F>(
+        &self,
+        endpoint: &str,
+        timeout: Duration,
+        request: F,
+    ) -> Result<R, String>
+    where
+        R: Send + 'static,
+        F: Fn(&dyn Transport) -> Result<R, String> + Clone + Send + 'static,
+    {
+        let mut backoff = self.policy.retry.initial_backoff;
+        let mut last_error = format!("No attempts made to {}", endpoint);
+        for attempt in 0..self.policy.retry.max_attempts {
+            if self.is_open(endpoint) {
+                return Err(format!("Circuit of {} is open", endpoint));
+            }
+            match self
+                .pool
+                .run(self.inner.clone(), timeout, request.clone())?
+            {
+                Ok(response) => {
+                    self.record(endpoint, true);
+                    return Ok(response);
+                }
+                Err(e) => {
+                    log::debug!("Request to {} failed: {}", endpoint, e);
+                    self.record(endpoint, false);
+                    last_error = e;
+                }
+            }
+            if attempt + 1 < self.policy.retry.max_attempts {
+                std::thread::sleep(self.policy.jittered(backoff));
+                backoff =
+                    std::cmp::min(backoff * 2, self.policy.retry.max_backoff);
+            }
+        }
+        Err(last_error)
+    }
+}
+
+impl Transport for PolicyTransport {
+    fn send_event(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        let (to, stream) = (recipient.clone(), stream.to_vec());
+        self.call(
+            &recipient.to_string(),
+            self.policy.timeout_of(recipient),
+            move |transport| transport.send_event(&to, &stream),
+        )
+    }
+
+    fn send_query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let (to, query) = (recipient.clone(), query.to_vec());
+        self.call(
+            &recipient.to_string(),
+            self.policy.timeout_of(recipient),
+            move |transport| transport.send_query(&to, &query),
+        )
+    }
+
+    fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+        let target = url.clone();
+        self.call(
+            &url.origin().ascii_serialization(),
+            self.policy.timeout,
+            move |transport| transport.resolve_oobi(&target),
+        )
+    }
+
+    fn fetch_mailbox(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String> {
+        let (to, query) = (recipient.clone(), query.to_vec());
+        self.call(
+            &recipient.to_string(),
+            self.policy.timeout_of(recipient),
+            move |transport| transport.fetch_mailbox(&to, &query),
+        )
+    }
+
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        sn: u64,
+    ) -> Result<Vec<u8>, String> {
+        let recipient = IdentifierPrefix::Basic(witness.clone());
+        let (witness, id) = (witness.clone(), id.clone());
+        self.call(
+            &recipient.to_string(),
+            self.policy.timeout_of(&recipient),
+            move |transport| transport.fetch_receipts(&witness, &id, sn),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use keri_core::{prefix::BasicPrefix, signer::Signer};
+
+    use super::*;
+
+    /// Fails first `failures` requests, answers with number of request.
+    struct FlakyTransport {
+        requests: AtomicU32,
+        failures: u32,
+        delay: Duration,
+    }
+
+    impl FlakyTransport {
+        fn request(&self) -> Result<Vec<u8>, String> {
+            let request = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+            std::thread::sleep(self.delay);
+            if request <= self.failures {
+                Err("Connection refused".to_string())
+            } else {
+                Ok(request.to_string().into_bytes())
+            }
+        }
+    }
+
+    impl Transport for FlakyTransport {
+        fn send_event(
+            &self,
+            _recipient: &IdentifierPrefix,
+            _stream: &[u8],
+        ) -> Result<(), String> {
+            self.request().map(|_| ())
+        }
+
+        fn send_query(
+            &self,
+            _recipient: &IdentifierPrefix,
+            _query: &[u8],
+        ) -> Result<Vec<u8>, String> {
+            self.request()
+        }
+
+        fn resolve_oobi(&self, _url: &Url) -> Result<Vec<u8>, String> {
+            self.request()
+        }
+
+        fn fetch_mailbox(
+            &self,
+            _recipient: &IdentifierPrefix,
+            _query: &[u8],
+        ) -> Result<String, String> {
+            String::from_utf8(self.request()?).map_err(|e| e.to_string())
+        }
+    }
+
+    fn flaky(failures: u32, delay: Duration) -> Arc<FlakyTransport> {
+        Arc::new(FlakyTransport {
+            requests: AtomicU32::new(0),
+            failures,
+            delay,
+        })
+    }
+
+    #[test]
+    fn test_policy_transport() {
+        let witness = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let policy = NetworkPolicy {
+            retry: RetryPolicy {
+                max_attempts: 3,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(2),
+            },
+            failure_threshold: 4,
+            cooldown: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        // Failures are retried.
+        let inner = flaky(2, Duration::ZERO);
+        let transport = PolicyTransport::new(inner.clone(), policy.clone());
+        assert_eq!(transport.send_query(&witness, b"qry").unwrap(), b"3");
+        assert!(!transport.is_open(&witness.to_string()));
+
+        // Circuit opens after failure threshold and fails fast.
+        let inner = flaky(5, Duration::ZERO);
+        let transport = PolicyTransport::new(inner.clone(), policy.clone());
+        assert!(transport.send_event(&witness, b"icp").is_err());
+        assert!(transport.send_event(&witness, b"icp").is_err());
+        assert!(transport.is_open(&witness.to_string()));
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 4);
+        assert!(transport.fetch_mailbox(&witness, b"qry").is_err());
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 4);
+
+        // Failed trial request after cooldown opens circuit again, the
+        // successful one closes it.
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(transport.send_event(&witness, b"icp").is_err());
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 5);
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(transport.send_query(&witness, b"qry").unwrap(), b"6");
+        assert!(!transport.is_open(&witness.to_string()));
+
+        // Slow recipient times out.
+        let transport = PolicyTransport::new(
+            flaky(0, Duration::from_millis(200)),
+            policy.clone().with_recipient_timeout(
+                witness.clone(),
+                Duration::from_millis(10),
+            ),
+        );
+        assert!(transport.send_query(&witness, b"qry").is_err());
+        let url = Url::parse("http://127.0.0.1:3232/oobi").unwrap();
+        assert!(transport.resolve_oobi(&url).is_ok());
+
+        // Timed out requests keep their threads busy until they return, and
+        // no more requests are started meanwhile.
+        let inner = flaky(0, Duration::from_millis(200));
+        let transport = PolicyTransport::new(
+            inner.clone(),
+            NetworkPolicy {
+                timeout: Duration::from_millis(10),
+                max_pending_requests: 1,
+                ..policy
+            },
+        );
+        assert!(transport.send_query(&witness, b"qry").is_err());
+        assert_eq!(
+            transport.send_event(&witness, b"icp"),
+            Err("Too many pending requests".to_string())
+        );
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 1);
+        std::thread::sleep(Duration::from_millis(250));
+        assert!(transport.resolve_oobi(&url).is_err());
+        assert_eq!(inner.requests.load(Ordering::SeqCst), 2);
+    }
+}
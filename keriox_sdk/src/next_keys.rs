@@ -0,0 +1,334 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use keri_core::{
+    actor::{event_generator, prelude::HashFunctionCode},
+    database::{EscrowCreator, EventDatabase},
+    event::sections::{threshold::SignatureThreshold, RotationWitnessConfig},
+    keys::{KeySource, OsKeySource},
+    prefix::{
+        BasicPrefix, CesrPrimitive, IdentifierPrefix, IndexedSignature,
+        SelfSigningPrefix,
+    },
+    signer::Signer,
+};
+use said::derivation::HashFunction;
+use teliox::database::TelEventDatabase;
+
+use crate::{Controller, SigningPolicy};
+
+/// Keys established by single event.
+struct KeySet {
+    signers: Vec<Signer>,
+}
+
+impl KeySet {
+    fn generate(count: usize, source: &dyn KeySource) -> Self {
+        Self {
+            signers: (0..count).map(|_| Signer::from_source(source)).collect(),
+        }
+    }
+
+    fn public_keys(&self) -> Vec<BasicPrefix> {
+        self.signers
+            .iter()
+            .map(|signer| BasicPrefix::Ed25519(signer.public_key()))
+            .collect()
+    }
+
+    fn sign(
+        &self,
+        data: &[u8],
+        policy: Option<&SigningPolicy>,
+    ) -> Result<Vec<IndexedSignature>, String> {
+        self.signers
+            .iter()
+            .enumerate()
+            .map(|(i, signer)| {
+                if let Some(policy) = policy {
+                    policy.check(&signer.public_key(), data)?;
+                }
+                let sig = signer.sign(data).map_err(|e| e.to_string())?;
+                Ok(IndexedSignature::new_both_same(
+                    SelfSigningPrefix::Ed25519Sha512(sig),
+                    i as u16,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Pre-generates key sets of identifier and builds its rotations from
+/// them.
+///
+/// Manager keeps current keys and a reserve of `depth` future key sets.
+/// The first set of the reserve is the one committed to as next keys in
+/// the last establishment event. Each rotation exposes it, commits to the
+/// following one and tops the reserve up. Keys are kept in memory only.
+pub struct NextKeyManager {
+    current: KeySet,
+    reserve: VecDeque<KeySet>,
+    exposed: Vec<BasicPrefix>,
+    key_count: usize,
+    threshold: u64,
+    depth: usize,
+    source: Arc<dyn KeySource>,
+    policy: Option<Arc<SigningPolicy>>,
+}
+
+impl NextKeyManager {
+    /// Generates current keys and `depth` future key sets, each of
+    /// `key_count` keys, of which `threshold` need to sign.
+    pub fn new(
+        key_count: usize,
+        threshold: u64,
+        depth: usize,
+    ) -> Result<Self, String> {
+        Self::with_source(key_count, threshold, depth, Arc::new(OsKeySource))
+    }
+
+    /// Same as `new`, but all keys are generated with bytes of `source`.
+    pub fn with_source(
+        key_count: usize,
+        threshold: u64,
+        depth: usize,
+        source: Arc<dyn KeySource>,
+    ) -> Result<Self, String> {
+        if key_count == 0 || threshold == 0 || threshold > key_count as u64 {
+            return Err(format!(
+                "Threshold {} can't be met with {} keys",
+                threshold, key_count
+            ));
+        }
+        if depth == 0 {
+            return Err("Reserve needs at least one key set".to_string());
+        }
+        let current = KeySet::generate(key_count, source.as_ref());
+        let exposed = current.public_keys();
+        let reserve = (0..depth)
+            .map(|_| KeySet::generate(key_count, source.as_ref()))
+            .collect();
+        Ok(Self {
+            current,
+            reserve,
+            exposed,
+            key_count,
+            threshold,
+            depth,
+            source,
+            policy: None,
+        })
+    }
+
+    /// Subjects all signatures made by manager to `policy`.
+    pub fn with_policy(mut self, policy: Arc<SigningPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Current keys, e.g. to be used in inception event.
+    pub fn current_keys(&self) -> Vec<BasicPrefix> {
+        self.current.public_keys()
+    }
+
+    /// Keys committed to as next keys, e.g. in inception event.
+    pub fn next_keys(&self) -> Vec<BasicPrefix> {
+        self.reserve[0].public_keys()
+    }
+
+    pub fn threshold(&self) -> SignatureThreshold {
+        SignatureThreshold::Simple(self.threshold)
+    }
+
+    /// Number of pre-generated key sets waiting in reserve.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Changes number of pre-generated key sets. Shrinking the reserve
+    /// drops the most distant sets, so the committed one is kept.
+    pub fn set_depth(&mut self, depth: usize) -> Result<(), String> {
+        if depth == 0 {
+            return Err("Reserve needs at least one key set".to_string());
+        }
+        self.depth = depth;
+        self.reserve.truncate(depth);
+        self.refill();
+        Ok(())
+    }
+
+    /// Returns true if key has ever been current key, so it was published
+    /// in establishment event.
+    pub fn is_exposed(&self, key: &BasicPrefix) -> bool {
+        self.exposed.contains(key)
+    }
+
+    /// Returns all keys exposed so far, in order of exposure.
+    pub fn exposed_keys(&self) -> &[BasicPrefix] {
+        &self.exposed
+    }
+
+    /// Signs data with current keys.
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<IndexedSignature>, String> {
+        self.current.sign(data, self.policy.as_deref())
+    }
+
+    /// Builds rotation of identifier which exposes committed next keys and
+    /// commits to the following key set of the reserve. Witnesses are
+    /// kept unchanged. Returns rotation event and its signatures.
+    pub fn rotation<D, T>(
+        &mut self,
+        controller: &Controller<D, T>,
+        id: &IdentifierPrefix,
+    ) -> Result<(String, Vec<IndexedSignature>), String>
+    where
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    {
+        let state = controller
+            .get_state(id)
+            .ok_or("Unknown identifier".to_string())?;
+        // Set following the exposed one is needed even if reserve is only
+        // one set deep.
+        if self.reserve.len() < 2 {
+            self.reserve.push_back(KeySet::generate(
+                self.key_count,
+                self.source.as_ref(),
+            ));
+        }
+        let exposed = &self.reserve[0];
+        let derivation: HashFunction = HashFunctionCode::Blake3_256.into();
+        let next_keys_hashes = self.reserve[1]
+            .public_keys()
+            .iter()
+            .map(|key| derivation.derive(key.to_str().as_bytes()))
+            .collect();
+        let witness_config = RotationWitnessConfig {
+            tally: state.witness_config.tally.clone(),
+            prune: vec![],
+            graft: vec![],
+        };
+        let rot = event_generator::partial_rotate_with_witnesses(
+            state,
+            exposed.public_keys(),
+            &self.threshold(),
+            next_keys_hashes,
+            &self.threshold(),
+            witness_config,
+        )
+        .map_err(|e| e.to_string())?;
+        let rot = String::from_utf8(
+            rot.encode()
+                .map_err(|_| "Event encoding error".to_string())?,
+        )
+        .map_err(|_| "Event format error".to_string())?;
+        let signatures =
+            exposed.sign(rot.as_bytes(), self.policy.as_deref())?;
+        Ok((rot, signatures))
+    }
+
+    /// Builds rotation with `rotation`, processes it and advances the
+    /// reserve. Returns rotation event, which should be published to
+    /// identifier's witnesses.
+    pub fn rotate<D, T>(
+        &mut self,
+        controller: &Controller<D, T>,
+        id: &IdentifierPrefix,
+    ) -> Result<String, String>
+    where
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    {
+        let (rot, signatures) = self.rotation(controller, id)?;
+        controller.finalize_rotate(rot.as_bytes(), signatures)?;
+        self.advance();
+        Ok(rot)
+    }
+
+    /// Makes committed next keys current, after rotation exposing them was
+    /// processed.
+    fn advance(&mut self) {
+        if let Some(next) = self.reserve.pop_front() {
+            self.exposed.extend(next.public_keys());
+            self.current = next;
+        }
+        self.refill();
+    }
+
+    fn refill(&mut self) {
+        while self.reserve.len() < self.depth {
+            self.reserve.push_back(KeySet::generate(
+                self.key_count,
+                self.source.as_ref(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::keys::SeededKeySource;
+
+    use super::*;
+    use crate::test_utils::setup;
+
+    #[test]
+    fn test_next_key_manager() {
+        assert!(NextKeyManager::new(2, 3, 1).is_err());
+        assert!(NextKeyManager::new(1, 1, 0).is_err());
+
+        let (_root, controller) = setup("test-db");
+
+        let mut manager = NextKeyManager::new(1, 1, 3).unwrap();
+        let icp = controller
+            .incept(manager.current_keys(), manager.next_keys())
+            .unwrap();
+        let sig = manager.sign(icp.as_bytes()).unwrap().remove(0).signature;
+        let identifier =
+            controller.finalize_incept(icp.as_bytes(), &sig).unwrap();
+
+        for sn in 1..=4 {
+            let next_keys = manager.next_keys();
+            assert!(!manager.is_exposed(&next_keys[0]));
+            manager.rotate(&controller, &identifier.id).unwrap();
+
+            let state = controller.get_state(&identifier.id).unwrap();
+            assert_eq!(state.sn, sn);
+            assert_eq!(state.current.public_keys, next_keys);
+            assert_eq!(manager.current_keys(), next_keys);
+            assert!(manager.is_exposed(&next_keys[0]));
+            assert_eq!(manager.exposed_keys().len() as u64, sn + 1);
+            // The new next keys commitment is made of reserved keys.
+            assert_eq!(
+                state
+                    .current
+                    .next_keys_data
+                    .key_position(&manager.next_keys()[0]),
+                Some(0)
+            );
+        }
+
+        // Reserve depth can change between rotations.
+        manager.set_depth(1).unwrap();
+        assert_eq!(manager.depth(), 1);
+        manager.rotate(&controller, &identifier.id).unwrap();
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 5);
+
+        // Other manager's keys weren't committed to.
+        let mut other = NextKeyManager::new(1, 1, 1).unwrap();
+        assert!(other.rotate(&controller, &identifier.id).is_err());
+        assert_eq!(controller.get_state(&identifier.id).unwrap().sn, 5);
+    }
+
+    #[test]
+    fn test_seeded_key_source() {
+        let manager = |seed| {
+            let source = Arc::new(SeededKeySource::new(seed));
+            NextKeyManager::with_source(2, 1, 2, source).unwrap()
+        };
+        let (first, second) = (manager(7), manager(7));
+        assert_eq!(first.current_keys(), second.current_keys());
+        assert_eq!(first.next_keys(), second.next_keys());
+        assert_ne!(first.current_keys(), first.next_keys());
+        assert_ne!(first.current_keys(), manager(8).current_keys());
+    }
+}
@@ -0,0 +1,471 @@
+#[cfg(feature = "oobi-manager")]
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use keri_core::prefix::IdentifierPrefix;
+#[cfg(feature = "oobi-manager")]
+use keri_core::{
+    actor::{parse_event_stream, prelude::EventStorage, process_reply},
+    database::{EscrowCreator, EventDatabase},
+    event_message::signed_event_message::{Message, Op},
+    oobi::{EndRole, LocationScheme, Oobi, Role},
+    oobi_manager::OobiManager,
+    processor::{basic_processor::BasicProcessor, Processor},
+    query::reply_event::{ReplyRoute, SignedReply},
+};
+use url::Url;
+
+/// Dereferences OOBI URL and returns the served CESR stream.
+pub trait OobiFetcher: Send + Sync {
+    fn fetch(&self, url: &Url) -> Result<Vec<u8>, String>;
+}
+
+impl<F> OobiFetcher for F
+where
+    F: Fn(&Url) -> Result<Vec<u8>, String> + Send + Sync,
+{
+    fn fetch(&self, url: &Url) -> Result<Vec<u8>, String> {
+        self(url)
+    }
+}
+
+/// Fetches OOBIs with blocking HTTP GET requests.
+#[cfg(feature = "http")]
+#[derive(Default)]
+pub struct HttpOobiFetcher {
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "http")]
+impl HttpOobiFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates fetcher whose requests fail after `timeout`.
+    pub fn with_timeout(timeout: std::time::Duration) -> Result<Self, String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { client })
+    }
+}
+
+#[cfg(feature = "http")]
+impl OobiFetcher for HttpOobiFetcher {
+    fn fetch(&self, url: &Url) -> Result<Vec<u8>, String> {
+        let response = self
+            .client
+            .get(url.clone())
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        response
+            .bytes()
+            .map(|body| body.to_vec())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Resolves OOBIs: fetches KELs and endpoint replies they point to,
+/// processes them and saves verified endpoint information.
+#[cfg(feature = "oobi-manager")]
+pub struct OobiResolver<D: EventDatabase + EscrowCreator + 'static> {
+    processor: Arc<BasicProcessor<D>>,
+    storage: Arc<EventStorage<D>>,
+    oobi_manager: Arc<OobiManager>,
+    fetcher: Arc<dyn OobiFetcher>,
+}
+
+#[cfg(feature = "oobi-manager")]
+impl<D: EventDatabase + EscrowCreator + 'static> OobiResolver<D> {
+    pub fn new(
+        processor: Arc<BasicProcessor<D>>,
+        storage: Arc<EventStorage<D>>,
+        oobi_manager: Arc<OobiManager>,
+        fetcher: Arc<dyn OobiFetcher>,
+    ) -> Self {
+        Self {
+            processor,
+            storage,
+            oobi_manager,
+            fetcher,
+        }
+    }
+
+    /// Fetches and processes OOBI URL of form `{url}/oobi/{aid}` or
+    /// `{url}/oobi/{cid}/{role}/{eid}`. Returns identifier the OOBI is
+    /// about.
+    pub fn resolve_oobi(&self, url: &str) -> Result<IdentifierPrefix, String> {
+        let url = Url::parse(url).map_err(|e| e.to_string())?;
+        let id = oobi_identifier(&url)?;
+        let stream = self.fetcher.fetch(&url)?;
+        self.process_stream(&stream)?;
+        Ok(id)
+    }
+
+    /// Resolves location scheme or end role OOBI. Location of end role
+    /// provider needs to be known.
+    pub fn resolve(&self, oobi: &Oobi) -> Result<(), String> {
+        let url = match oobi {
+            // {url}/oobi/{eid}
+            Oobi::Location(loc) => {
+                join_url(&loc.url, &["oobi/", &loc.eid.to_string()])?
+            }
+            // {url}/oobi/{cid}/{role}/{eid}
+            Oobi::EndRole(er) => {
+                let loc = self
+                    .get_loc_schemes(&er.eid)?
+                    .into_iter()
+                    .next()
+                    .ok_or(format!("Unknown location of {}", er.eid))?;
+                join_url(
+                    &loc.url,
+                    &[
+                        "oobi/",
+                        &format!("{}/", er.cid),
+                        role_segment(&er.role),
+                        &er.eid.to_string(),
+                    ],
+                )?
+            }
+        };
+        let stream = self.fetcher.fetch(&url)?;
+        self.process_stream(&stream)
+    }
+
+    /// Processes KEL events and endpoint replies of OOBI response. Events
+    /// should precede replies, because reply signatures are verified
+    /// against signer's KEL.
+    pub fn process_stream(&self, stream: &[u8]) -> Result<(), String> {
+        let messages = parse_event_stream(stream).map_err(|e| e.to_string())?;
+        for message in messages {
+            match message {
+                Message::Notice(notice) => self
+                    .processor
+                    .process_notice(&notice)
+                    .map_err(|e| e.to_string())?,
+                Message::Op(Op::Reply(reply)) => process_reply(
+                    reply,
+                    &self.oobi_manager,
+                    self.processor.as_ref(),
+                    &self.storage,
+                )
+                .map_err(|e| e.to_string())?,
+                Message::Op(_) => {
+                    log::warn!("Unexpected message in OOBI response skipped")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies signature of end role or location scheme reply against
+    /// signer's KEL and saves it, unless older than the one already known
+    /// (BADA rules).
+    pub fn save_reply(&self, reply: SignedReply) -> Result<(), String> {
+        match reply.reply.get_route() {
+            ReplyRoute::LocScheme(_)
+            | ReplyRoute::EndRoleAdd(_)
+            | ReplyRoute::EndRoleCut(_) => process_reply(
+                reply,
+                &self.oobi_manager,
+                self.processor.as_ref(),
+                &self.storage,
+            )
+            .map_err(|e| e.to_string()),
+            _ => Err("Reply is not an OOBI".to_string()),
+        }
+    }
+
+    /// Returns verified locations of identifier.
+    pub fn get_loc_schemes(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Vec<LocationScheme>, String> {
+        Ok(self
+            .oobi_manager
+            .get_loc_scheme(id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter_map(|rpy| match rpy.data.data {
+                ReplyRoute::LocScheme(loc) => Some(loc),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Returns endpoints currently authorized by `cid` for `role`.
+    pub fn get_end_roles(
+        &self,
+        cid: &IdentifierPrefix,
+        role: Role,
+    ) -> Result<Vec<EndRole>, String> {
+        Ok(self
+            .oobi_manager
+            .get_end_role(cid, role)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|rpy| match rpy.reply.get_route() {
+                ReplyRoute::EndRoleAdd(er) => Some(er),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+/// Fetches KELs of identifiers from their locations known from OOBIs and
+/// from locations of configured watchers.
+///
+/// Observers of missing KEL events only `request` resolution, because
+/// event processing can't be re-entered while notifications are
+/// dispatched. Requested KELs are fetched by `resolve_pending`.
+#[cfg(feature = "oobi-manager")]
+pub struct KelResolver<D: EventDatabase + EscrowCreator + 'static> {
+    oobi_resolver: OobiResolver<D>,
+    watchers: Vec<IdentifierPrefix>,
+    pending: Mutex<HashSet<IdentifierPrefix>>,
+}
+
+#[cfg(feature = "oobi-manager")]
+impl<D: EventDatabase + EscrowCreator + 'static> KelResolver<D> {
+    pub fn new(
+        oobi_resolver: OobiResolver<D>,
+        watchers: Vec<IdentifierPrefix>,
+    ) -> Self {
+        Self {
+            oobi_resolver,
+            watchers,
+            pending: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Schedules KEL of `id` to be fetched by `resolve_pending`.
+    pub fn request(&self, id: &IdentifierPrefix) {
+        self.pending.lock().unwrap().insert(id.clone());
+    }
+
+    /// Returns identifiers whose KELs were requested, but not resolved yet.
+    pub fn pending(&self) -> Vec<IdentifierPrefix> {
+        self.pending.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Fetches requested KELs. Identifiers that couldn't be resolved stay
+    /// pending.
+    pub fn resolve_pending(&self) {
+        let pending: Vec<_> = self.pending.lock().unwrap().drain().collect();
+        for id in pending {
+            if let Err(e) = self.resolve(&id) {
+                log::warn!("Failed to resolve KEL of {}: {}", id, e);
+                self.request(&id);
+            }
+        }
+    }
+
+    /// Resolves location OOBI of `id` at all its known locations and at
+    /// locations of watchers. Unreachable sources are skipped. Fails only
+    /// if there is no source to ask.
+    pub fn resolve(&self, id: &IdentifierPrefix) -> Result<(), String> {
+        let mut sources = self.oobi_resolver.get_loc_schemes(id)?;
+        for watcher in &self.watchers {
+            sources.extend(self.oobi_resolver.get_loc_schemes(watcher)?);
+        }
+        if sources.is_empty() {
+            return Err(format!("Unknown location of {}", id));
+        }
+        for LocationScheme { scheme, url, .. } in sources {
+            let oobi = Oobi::Location(LocationScheme::new(
+                id.clone(),
+                scheme,
+                url.clone(),
+            ));
+            if let Err(e) = self.oobi_resolver.resolve(&oobi) {
+                log::warn!("Failed to resolve {} from {}: {}", id, url, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extracts identifier from path of OOBI URL, which is the segment
+/// following `oobi`.
+pub fn oobi_identifier(url: &Url) -> Result<IdentifierPrefix, String> {
+    url.path_segments()
+        .and_then(|mut segments| {
+            segments.by_ref().find(|segment| *segment == "oobi")?;
+            segments.next()
+        })
+        .ok_or(format!("{} is not an OOBI URL", url))?
+        .parse()
+        .map_err(|_| format!("Improper identifier in OOBI URL {}", url))
+}
+
+#[cfg(feature = "oobi-manager")]
+fn join_url(base: &Url, segments: &[&str]) -> Result<Url, String> {
+    segments.iter().try_fold(base.clone(), |url, segment| {
+        url.join(segment).map_err(|e| e.to_string())
+    })
+}
+
+#[cfg(feature = "oobi-manager")]
+fn role_segment(role: &Role) -> &'static str {
+    match role {
+        Role::Witness => "witness/",
+        Role::Watcher => "watcher/",
+        Role::Controller => "controller/",
+        Role::Messagebox => "messagebox/",
+    }
+}
+
+#[cfg(all(test, feature = "oobi-manager"))]
+mod tests {
+    use keri_core::{
+        actor::event_generator, database::redb::RedbDatabase, oobi::Scheme,
+        prefix::BasicPrefix, signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_resolve_oobi() {
+        // Identifier that advertises its watcher.
+        let (_root, controller) = setup("test-db");
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let watcher_id = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let rpy = identifier.add_watcher(watcher_id.clone()).unwrap();
+        let (_, messages) = identifier
+            .finalize_add_watcher(rpy.as_bytes(), sign(&signer, rpy.as_bytes()))
+            .unwrap();
+        let served: Vec<u8> = messages
+            .iter()
+            .flat_map(|msg| msg.to_cesr().unwrap())
+            .collect();
+
+        let (_other_root, other) = setup("other-db");
+        let oobi_manager =
+            Arc::new(OobiManager::new(other.kel.storage.events_db.clone()));
+        let resolver = other.kel.oobi_resolver(
+            oobi_manager,
+            Arc::new(move |_url: &Url| -> Result<Vec<u8>, String> {
+                Ok(served.clone())
+            }),
+        );
+
+        let url = format!(
+            "http://127.0.0.1:3232/oobi/{}/watcher/{}",
+            identifier.id, watcher_id
+        );
+        let resolved = resolver.resolve_oobi(&url).unwrap();
+        assert_eq!(resolved, identifier.id);
+        assert!(other.get_state(&identifier.id).is_some());
+        let end_roles = resolver
+            .get_end_roles(&identifier.id, Role::Watcher)
+            .unwrap();
+        assert_eq!(end_roles.len(), 1);
+        assert_eq!(end_roles[0].eid, watcher_id);
+
+        assert!(resolver.resolve_oobi("http://127.0.0.1:3232/").is_err());
+    }
+
+    #[test]
+    fn test_end_role_management() {
+        let (_root, controller) = setup("test-db");
+        let resolver = controller.kel.oobi_resolver(
+            Arc::new(OobiManager::new(
+                controller.kel.storage.events_db.clone(),
+            )),
+            Arc::new(|url: &Url| -> Result<Vec<u8>, String> {
+                Err(format!("{} is unreachable", url))
+            }),
+        );
+
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let finalize = |rpy: String| {
+            identifier
+                .finalize_reply(rpy.as_bytes(), sign(&signer, rpy.as_bytes()))
+                .unwrap()
+        };
+
+        let url = Url::parse("http://127.0.0.1:3232/").unwrap();
+        let loc = finalize(
+            identifier
+                .add_loc_scheme(Scheme::Http, url.clone())
+                .unwrap(),
+        );
+        resolver.save_reply(loc).unwrap();
+        let locations = resolver.get_loc_schemes(&identifier.id).unwrap();
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].url, url);
+
+        let (first, second) = (
+            IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+                Signer::new().public_key(),
+            )),
+            IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+                Signer::new().public_key(),
+            )),
+        );
+        let first_add = finalize(
+            identifier
+                .add_end_role(first.clone(), Role::Messagebox)
+                .unwrap(),
+        );
+        resolver.save_reply(first_add.clone()).unwrap();
+        resolver
+            .save_reply(finalize(
+                identifier
+                    .add_end_role(second.clone(), Role::Messagebox)
+                    .unwrap(),
+            ))
+            .unwrap();
+        let eids = |resolver: &OobiResolver<RedbDatabase>| {
+            resolver
+                .get_end_roles(&identifier.id, Role::Messagebox)
+                .unwrap()
+                .into_iter()
+                .map(|er| er.eid)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(eids(&resolver).len(), 2);
+
+        // Make sure cut reply is newer than add reply.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        resolver
+            .save_reply(finalize(
+                identifier
+                    .cut_end_role(first.clone(), Role::Messagebox)
+                    .unwrap(),
+            ))
+            .unwrap();
+        assert_eq!(eids(&resolver), vec![second]);
+
+        // Stale authorization is rejected.
+        assert!(resolver.save_reply(first_add).is_err());
+        assert!(!eids(&resolver).contains(&first));
+
+        // Identifier can't sign replies about other identifiers.
+        let other = String::from_utf8(
+            event_generator::generate_end_role(
+                &first,
+                &identifier.id,
+                Role::Witness,
+                true,
+            )
+            .encode()
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(identifier
+            .finalize_reply(other.as_bytes(), sign(&signer, other.as_bytes()))
+            .is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use keri_core::prefix::IdentifierPrefix;
+use keyring::Entry;
+use zeroize::Zeroizing;
+
+use crate::keystore::{IdentifierKeys, KeyStore};
+
+/// Account of entry listing identifiers whose seeds are stored, as
+/// keychains can't be enumerated portably.
+const INDEX_ACCOUNT: &str = "identifiers";
+
+/// Key store keeping seeds in platform keychain: macOS Keychain, Windows
+/// Credential Manager or Secret Service on Linux. Seeds of every
+/// identifier are kept in separate entry of `service`, whose account is
+/// the identifier. Keychain is unlocked by the platform, so passphrase is
+/// ignored.
+pub struct OsKeychainStore {
+    service: String,
+}
+
+impl OsKeychainStore {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+
+    fn entry(&self, account: &str) -> Result<Entry, String> {
+        Entry::new(&self.service, account).map_err(|e| e.to_string())
+    }
+
+    fn read(&self, account: &str) -> Result<Option<Zeroizing<String>>, String> {
+        match self.entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(Zeroizing::new(secret))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl KeyStore for OsKeychainStore {
+    fn unlock(&self, _passphrase: &str) -> Result<(), String> {
+        // Fails early if keychain isn't available.
+        self.read(INDEX_ACCOUNT).map(|_| ())
+    }
+
+    fn save(
+        &self,
+        id: &IdentifierPrefix,
+        keys: &IdentifierKeys,
+    ) -> Result<(), String> {
+        let secret = Zeroizing::new(
+            serde_json::to_string(keys).map_err(|e| e.to_string())?,
+        );
+        self.entry(&id.to_string())?
+            .set_password(&secret)
+            .map_err(|e| e.to_string())?;
+        let mut identifiers = self.identifiers()?;
+        if !identifiers.contains(id) {
+            identifiers.push(id.clone());
+            let index = serde_json::to_string(&identifiers)
+                .map_err(|e| e.to_string())?;
+            self.entry(INDEX_ACCOUNT)?
+                .set_password(&index)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn load(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<IdentifierKeys>, String> {
+        self.read(&id.to_string())?
+            .map(|secret| {
+                serde_json::from_str(&secret).map_err(|e| e.to_string())
+            })
+            .transpose()
+    }
+
+    fn identifiers(&self) -> Result<Vec<IdentifierPrefix>, String> {
+        match self.read(INDEX_ACCOUNT)? {
+            Some(index) => {
+                serde_json::from_str(&index).map_err(|e| e.to_string())
+            }
+            None => Ok(vec![]),
+        }
+    }
+}
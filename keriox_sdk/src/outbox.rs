@@ -0,0 +1,376 @@
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::{Arc, Weak},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use keri_core::prefix::{BasicPrefix, IdentifierPrefix};
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::transport::Transport;
+
+/// Outbox store sequence number -> JSON serialized entry
+const OUTBOX: TableDefinition<u64, &[u8]> = TableDefinition::new("outbox");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxKind {
+    /// Events, receipts or replies, sent with `Transport::send_event`.
+    Event,
+    /// Query, whose response is passed to flush callback.
+    Query,
+}
+
+/// Message waiting to be sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub recipient: IdentifierPrefix,
+    pub kind: OutboxKind,
+    pub stream: Vec<u8>,
+}
+
+/// Called with query sent on flush and its response.
+pub type ResponseHandler = dyn Fn(&OutboxEntry, &[u8]) + Send + Sync;
+
+/// Persistent queue of messages for recipients that couldn't be reached,
+/// so that controllers keep working offline. Messages are sent in order
+/// they were queued, message to recipient is never sent before the ones
+/// queued for it earlier.
+pub struct Outbox {
+    db: Database,
+}
+
+impl Outbox {
+    pub fn new(path: &Path) -> Result<Self, String> {
+        let db = Database::create(path).map_err(|e| e.to_string())?;
+        // Create table
+        let write_txn = db.begin_write().map_err(|e| e.to_string())?;
+        write_txn.open_table(OUTBOX).map_err(|e| e.to_string())?;
+        write_txn.commit().map_err(|e| e.to_string())?;
+        Ok(Self { db })
+    }
+
+    /// Queues message and returns its sequence number.
+    pub fn enqueue(&self, entry: &OutboxEntry) -> Result<u64, String> {
+        let value = serde_json::to_vec(entry).map_err(|e| e.to_string())?;
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        let sn = {
+            let mut table =
+                write_txn.open_table(OUTBOX).map_err(|e| e.to_string())?;
+            let sn = table
+                .last()
+                .map_err(|e| e.to_string())?
+                .map_or(0, |(sn, _)| sn.value() + 1);
+            table
+                .insert(sn, value.as_slice())
+                .map_err(|e| e.to_string())?;
+            sn
+        };
+        write_txn.commit().map_err(|e| e.to_string())?;
+        Ok(sn)
+    }
+
+    /// Returns queued messages in order they were queued.
+    pub fn pending(&self) -> Result<Vec<(u64, OutboxEntry)>, String> {
+        let read_txn = self.db.begin_read().map_err(|e| e.to_string())?;
+        let table = read_txn.open_table(OUTBOX).map_err(|e| e.to_string())?;
+        let entries = table
+            .iter()
+            .map_err(|e| e.to_string())?
+            .map(|entry| {
+                let (sn, value) = entry.map_err(|e| e.to_string())?;
+                let entry = serde_json::from_slice(value.value())
+                    .map_err(|e| e.to_string())?;
+                Ok((sn.value(), entry))
+            })
+            .collect();
+        entries
+    }
+
+    /// Checks whether messages for `recipient` are waiting.
+    pub fn has_pending(
+        &self,
+        recipient: &IdentifierPrefix,
+    ) -> Result<bool, String> {
+        Ok(self
+            .pending()?
+            .iter()
+            .any(|(_, entry)| &entry.recipient == recipient))
+    }
+
+    fn remove(&self, sn: u64) -> Result<(), String> {
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table =
+                write_txn.open_table(OUTBOX).map_err(|e| e.to_string())?;
+            table.remove(sn).map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())
+    }
+
+    /// Sends queued messages over `transport` and returns how many were
+    /// sent. Once sending to recipient fails, its later messages are kept
+    /// for next flush. Responses to queries are passed to `on_response`.
+    pub fn flush(
+        &self,
+        transport: &dyn Transport,
+        on_response: &ResponseHandler,
+    ) -> Result<usize, String> {
+        let mut unreachable = HashSet::new();
+        let mut sent = 0;
+        for (sn, entry) in self.pending()? {
+            if unreachable.contains(&entry.recipient) {
+                continue;
+            }
+            let result = match entry.kind {
+                OutboxKind::Event => transport
+                    .send_event(&entry.recipient, &entry.stream)
+                    .map(|_| vec![]),
+                OutboxKind::Query => {
+                    transport.send_query(&entry.recipient, &entry.stream)
+                }
+            };
+            match result {
+                Ok(response) => {
+                    self.remove(sn)?;
+                    if entry.kind == OutboxKind::Query {
+                        on_response(&entry, &response);
+                    }
+                    sent += 1;
+                }
+                Err(e) => {
+                    log::debug!("{} still unreachable: {}", entry.recipient, e);
+                    unreachable.insert(entry.recipient);
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Flushes outbox every `interval` in background thread, until outbox
+    /// is dropped.
+    pub fn spawn_flusher(
+        self: &Arc<Self>,
+        transport: Arc<dyn Transport>,
+        interval: Duration,
+        on_response: Arc<ResponseHandler>,
+    ) -> JoinHandle<()> {
+        let outbox: Weak<Self> = Arc::downgrade(self);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            let Some(outbox) = outbox.upgrade() else {
+                return;
+            };
+            if let Err(e) = outbox.flush(transport.as_ref(), &*on_response) {
+                log::warn!("Failed to flush outbox: {}", e);
+            }
+        })
+    }
+}
+
+/// Transport that queues messages in outbox when recipient can't be
+/// reached, or has messages queued already, so that they are sent in
+/// order. Sending event then succeeds, sending query fails, as its
+/// response comes on flush.
+pub struct OutboxTransport {
+    inner: Arc<dyn Transport>,
+    outbox: Arc<Outbox>,
+}
+
+impl OutboxTransport {
+    pub fn new(inner: Arc<dyn Transport>, outbox: Arc<Outbox>) -> Self {
+        Self { inner, outbox }
+    }
+
+    pub fn outbox(&self) -> &Arc<Outbox> {
+        &self.outbox
+    }
+
+    fn send(
+        &self,
+        recipient: &IdentifierPrefix,
+        kind: OutboxKind,
+        stream: &[u8],
+    ) -> Result<Option<Vec<u8>>, String> {
+        if !self.outbox.has_pending(recipient)? {
+            let result = match kind {
+                OutboxKind::Event => {
+                    self.inner.send_event(recipient, stream).map(|_| vec![])
+                }
+                OutboxKind::Query => self.inner.send_query(recipient, stream),
+            };
+            match result {
+                Ok(response) => return Ok(Some(response)),
+                Err(e) => {
+                    log::debug!("Queueing message to {}: {}", recipient, e)
+                }
+            }
+        }
+        self.outbox.enqueue(&OutboxEntry {
+            recipient: recipient.clone(),
+            kind,
+            stream: stream.to_vec(),
+        })?;
+        Ok(None)
+    }
+}
+
+impl Transport for OutboxTransport {
+    fn send_event(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self.send(recipient, OutboxKind::Event, stream).map(|_| ())
+    }
+
+    fn send_query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.send(recipient, OutboxKind::Query, query)?
+            .ok_or(format!("{} unreachable, query queued in outbox", recipient))
+    }
+
+    fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+        self.inner.resolve_oobi(url)
+    }
+
+    fn fetch_mailbox(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String> {
+        self.inner.fetch_mailbox(recipient, query)
+    }
+
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        sn: u64,
+    ) -> Result<Vec<u8>, String> {
+        self.inner.fetch_receipts(witness, id, sn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    };
+
+    use keri_core::{prefix::BasicPrefix, signer::Signer};
+    use tempfile::Builder;
+
+    use super::*;
+
+    /// Records messages while online and echoes queries.
+    #[derive(Default)]
+    struct SwitchedTransport {
+        online: AtomicBool,
+        sent: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl SwitchedTransport {
+        fn send(&self, stream: &[u8]) -> Result<Vec<u8>, String> {
+            if !self.online.load(Ordering::SeqCst) {
+                return Err("Network unreachable".to_string());
+            }
+            self.sent.lock().unwrap().push(stream.to_vec());
+            Ok(stream.to_vec())
+        }
+    }
+
+    impl Transport for SwitchedTransport {
+        fn send_event(
+            &self,
+            _recipient: &IdentifierPrefix,
+            stream: &[u8],
+        ) -> Result<(), String> {
+            self.send(stream).map(|_| ())
+        }
+
+        fn send_query(
+            &self,
+            _recipient: &IdentifierPrefix,
+            query: &[u8],
+        ) -> Result<Vec<u8>, String> {
+            self.send(query)
+        }
+
+        fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+            Err(format!("{} is unreachable", url))
+        }
+
+        fn fetch_mailbox(
+            &self,
+            _recipient: &IdentifierPrefix,
+            query: &[u8],
+        ) -> Result<String, String> {
+            String::from_utf8(self.send(query)?).map_err(|e| e.to_string())
+        }
+    }
+
+    #[test]
+    fn test_outbox() {
+        let root = Builder::new().prefix("test-db").tempdir().unwrap();
+        let path = root.path().join("outbox");
+        let witness = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let network = Arc::new(SwitchedTransport::default());
+        let outbox = Arc::new(Outbox::new(&path).unwrap());
+        let transport = OutboxTransport::new(network.clone(), outbox.clone());
+
+        transport.send_event(&witness, b"icp").unwrap();
+        assert!(transport.send_query(&witness, b"qry").is_err());
+        // Queued messages are sent first, even once recipient is reachable.
+        network.online.store(true, Ordering::SeqCst);
+        transport.send_event(&witness, b"rct").unwrap();
+        assert!(network.sent.lock().unwrap().is_empty());
+        assert_eq!(outbox.pending().unwrap().len(), 3);
+
+        // Outbox survives restart.
+        drop(transport);
+        drop(outbox);
+        let outbox = Arc::new(Outbox::new(&path).unwrap());
+        let responses = Arc::new(Mutex::new(vec![]));
+        let received = responses.clone();
+        let on_response = move |entry: &OutboxEntry, response: &[u8]| {
+            assert_eq!(entry.kind, OutboxKind::Query);
+            received.lock().unwrap().push(response.to_vec());
+        };
+        let flusher = outbox.spawn_flusher(
+            network.clone(),
+            Duration::from_millis(10),
+            Arc::new(on_response),
+        );
+        while !outbox.pending().unwrap().is_empty() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            *network.sent.lock().unwrap(),
+            vec![b"icp".to_vec(), b"qry".to_vec(), b"rct".to_vec()]
+        );
+        assert_eq!(*responses.lock().unwrap(), vec![b"qry".to_vec()]);
+
+        network.online.store(false, Ordering::SeqCst);
+        let entry = OutboxEntry {
+            recipient: witness.clone(),
+            kind: OutboxKind::Event,
+            stream: b"ixn".to_vec(),
+        };
+        outbox.enqueue(&entry).unwrap();
+        assert_eq!(outbox.flush(network.as_ref(), &|_, _| ()).unwrap(), 0);
+        assert_eq!(outbox.pending().unwrap()[0].1, entry);
+
+        drop(outbox);
+        flusher.join().unwrap();
+    }
+}
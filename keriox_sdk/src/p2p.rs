@@ -0,0 +1,387 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use keri_core::{
+    keys::SecretSeed,
+    prefix::{BasicPrefix, IdentifierPrefix, SeedPrefix},
+};
+use libp2p::{
+    identity::{self, Keypair},
+    noise,
+    request_response::{self, cbor, OutboundRequestId, ProtocolSupport},
+    swarm::SwarmEvent,
+    tcp, yamux, Multiaddr, PeerId, StreamProtocol, Swarm,
+};
+use tokio::{
+    runtime::Runtime,
+    sync::{mpsc, oneshot},
+};
+use url::Url;
+use zeroize::Zeroizing;
+
+use crate::transport::Transport;
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/keri/cesr/1.0.0");
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+type Behaviour = cbor::Behaviour<Vec<u8>, Vec<u8>>;
+
+/// Answers CESR streams received from peers, e.g. by processing exn
+/// messages or answering KEL queries. Returned stream is sent back.
+pub trait PeerHandler: Send + Sync {
+    fn handle(&self, stream: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+impl<F> PeerHandler for F
+where
+    F: Fn(&[u8]) -> Result<Vec<u8>, String> + Send + Sync,
+{
+    fn handle(&self, stream: &[u8]) -> Result<Vec<u8>, String> {
+        self(stream)
+    }
+}
+
+/// Returns libp2p identity of Ed25519 key with `seed`, e.g. current key of
+/// identifier, so that peers can tell identifier by its key.
+pub fn peer_keypair(seed: &SecretSeed) -> Result<Keypair, String> {
+    match seed.seed() {
+        SeedPrefix::RandomSeed256Ed25519(seed) => {
+            let mut bytes = Zeroizing::new(seed.clone());
+            Keypair::ed25519_from_bytes(&mut bytes[..])
+                .map_err(|e| e.to_string())
+        }
+        _ => Err("Only Ed25519 keys can be used as peer identity".into()),
+    }
+}
+
+/// Returns peer id of libp2p node, whose identity is Ed25519 `key`.
+pub fn peer_id(key: &BasicPrefix) -> Result<PeerId, String> {
+    match key {
+        BasicPrefix::Ed25519(key) | BasicPrefix::Ed25519NT(key) => {
+            let key = identity::ed25519::PublicKey::try_from_bytes(&key.key())
+                .map_err(|e| e.to_string())?;
+            Ok(identity::PublicKey::from(key).to_peer_id())
+        }
+        _ => Err("Only Ed25519 keys can be used as peer identity".into()),
+    }
+}
+
+enum Command {
+    AddAddress(PeerId, Multiaddr),
+    Request {
+        peer: PeerId,
+        stream: Vec<u8>,
+        reply: oneshot::Sender<Result<Vec<u8>, String>>,
+    },
+}
+
+/// Transport exchanging CESR streams directly with other identifiers over
+/// libp2p, e.g. exn messages or KEL sync, without witnesses in between.
+/// Node runs in background until transport is dropped. Its identity is
+/// derived from identifier's key with `peer_keypair`, and peers are added
+/// with `add_peer` using their current key.
+///
+/// Methods block, so they mustn't be called from async context.
+pub struct P2pTransport {
+    runtime: Runtime,
+    commands: mpsc::UnboundedSender<Command>,
+    peers: RwLock<HashMap<IdentifierPrefix, PeerId>>,
+    local_peer_id: PeerId,
+    timeout: Duration,
+}
+
+impl P2pTransport {
+    /// Starts node listening on `address`, e.g.
+    /// `/ip4/0.0.0.0/tcp/5631`. Streams received from peers are answered
+    /// by `handler`.
+    pub fn start(
+        keypair: Keypair,
+        address: Multiaddr,
+        handler: Arc<dyn PeerHandler>,
+    ) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+        let local_peer_id = keypair.public().to_peer_id();
+        let swarm = runtime.block_on(async {
+            let mut swarm =
+                libp2p::SwarmBuilder::with_existing_identity(keypair)
+                    .with_tokio()
+                    .with_tcp(
+                        tcp::Config::default(),
+                        noise::Config::new,
+                        yamux::Config::default,
+                    )
+                    .map_err(|e| e.to_string())?
+                    .with_behaviour(|_| {
+                        Behaviour::new(
+                            [(PROTOCOL, ProtocolSupport::Full)],
+                            request_response::Config::default(),
+                        )
+                    })
+                    .map_err(|e| e.to_string())?
+                    // Connection without open streams is closed at once by
+                    // default, even while peer is preparing response.
+                    .with_swarm_config(|config| {
+                        config.with_idle_connection_timeout(IDLE_TIMEOUT)
+                    })
+                    .build();
+            swarm.listen_on(address).map_err(|e| e.to_string())?;
+            Ok::<_, String>(swarm)
+        })?;
+        let (commands, receiver) = mpsc::unbounded_channel();
+        runtime.spawn(run(swarm, receiver, handler));
+        Ok(Self {
+            runtime,
+            commands,
+            peers: RwLock::new(HashMap::new()),
+            local_peer_id,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Sets how long to wait for response of peer.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn local_peer_id(&self) -> PeerId {
+        self.local_peer_id
+    }
+
+    /// Sets `address` of identifier `id`, whose node identity is its
+    /// current `key`.
+    pub fn add_peer(
+        &self,
+        id: &IdentifierPrefix,
+        key: &BasicPrefix,
+        address: Multiaddr,
+    ) -> Result<(), String> {
+        let peer = peer_id(key)?;
+        self.commands
+            .send(Command::AddAddress(peer, address))
+            .map_err(|_| "Peer node stopped".to_string())?;
+        self.peers
+            .write()
+            .map_err(|_| "Transport lock poisoned".to_string())?
+            .insert(id.clone(), peer);
+        Ok(())
+    }
+
+    fn exchange(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let peer = *self
+            .peers
+            .read()
+            .map_err(|_| "Transport lock poisoned".to_string())?
+            .get(recipient)
+            .ok_or(format!("Unknown peer {}", recipient))?;
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(Command::Request {
+                peer,
+                stream: stream.to_vec(),
+                reply,
+            })
+            .map_err(|_| "Peer node stopped".to_string())?;
+        self.runtime.block_on(async {
+            tokio::time::timeout(self.timeout, response)
+                .await
+                .map_err(|_| format!("Request to {} timed out", recipient))?
+                .map_err(|_| "Peer node stopped".to_string())?
+        })
+    }
+}
+
+/// Drives the node: sends requests of transport and answers requests of
+/// peers, until transport is dropped.
+async fn run(
+    mut swarm: Swarm<Behaviour>,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    handler: Arc<dyn PeerHandler>,
+) {
+    let mut pending: HashMap<
+        OutboundRequestId,
+        oneshot::Sender<Result<Vec<u8>, String>>,
+    > = HashMap::new();
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(Command::AddAddress(peer, address)) => {
+                    swarm.add_peer_address(peer, address);
+                }
+                Some(Command::Request { peer, stream, reply }) => {
+                    let id = swarm.behaviour_mut().send_request(&peer, stream);
+                    pending.insert(id, reply);
+                }
+                None => return,
+            },
+            event = swarm.select_next_some() => match event {
+                SwarmEvent::Behaviour(request_response::Event::Message {
+                    peer,
+                    message,
+                }) => match message {
+                    request_response::Message::Request {
+                        request, channel, ..
+                    } => {
+                        let response = handler
+                            .handle(&request)
+                            .unwrap_or_else(|e| {
+                                log::warn!(
+                                    "Failed to handle stream of {}: {}",
+                                    peer,
+                                    e
+                                );
+                                vec![]
+                            });
+                        let _ = swarm
+                            .behaviour_mut()
+                            .send_response(channel, response);
+                    }
+                    request_response::Message::Response {
+                        request_id,
+                        response,
+                    } => {
+                        if let Some(reply) = pending.remove(&request_id) {
+                            let _ = reply.send(Ok(response));
+                        }
+                    }
+                },
+                SwarmEvent::Behaviour(
+                    request_response::Event::OutboundFailure {
+                        request_id,
+                        error,
+                        ..
+                    },
+                ) => {
+                    if let Some(reply) = pending.remove(&request_id) {
+                        let _ = reply.send(Err(error.to_string()));
+                    }
+                }
+                _ => (),
+            },
+        }
+    }
+}
+
+impl Transport for P2pTransport {
+    fn send_event(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self.exchange(recipient, stream).map(|_| ())
+    }
+
+    fn send_query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.exchange(recipient, query)
+    }
+
+    fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+        Err(format!("Can't resolve {} over libp2p", url))
+    }
+
+    fn fetch_mailbox(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String> {
+        String::from_utf8(self.exchange(recipient, query)?)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use keri_core::signer::{KeyType, Signer};
+
+    use super::*;
+
+    /// Returns loopback addresses with ports that were free a moment ago.
+    fn loopback_addresses() -> [Multiaddr; 2] {
+        let listeners = [
+            TcpListener::bind("127.0.0.1:0").unwrap(),
+            TcpListener::bind("127.0.0.1:0").unwrap(),
+        ];
+        listeners.map(|listener| {
+            format!(
+                "/ip4/127.0.0.1/tcp/{}",
+                listener.local_addr().unwrap().port()
+            )
+            .parse()
+            .unwrap()
+        })
+    }
+
+    /// Starts node answering streams prefixed with `name`.
+    fn start(
+        seed: &SecretSeed,
+        address: Multiaddr,
+        name: &str,
+    ) -> P2pTransport {
+        let name = name.as_bytes().to_vec();
+        let handler = move |stream: &[u8]| -> Result<Vec<u8>, String> {
+            if stream == b"fail" {
+                return Err("Unexpected stream".to_string());
+            }
+            Ok([name.as_slice(), b":", stream].concat())
+        };
+        P2pTransport::start(
+            peer_keypair(seed).unwrap(),
+            address,
+            Arc::new(handler),
+        )
+        .unwrap()
+        .with_timeout(Duration::from_secs(10))
+    }
+
+    #[test]
+    fn test_loopback_exchange() {
+        let seeds = [
+            SecretSeed::generate(KeyType::Ed25519),
+            SecretSeed::generate(KeyType::Ed25519),
+        ];
+        let keys = [&seeds[0], &seeds[1]].map(|seed| {
+            Signer::new_with_seed(seed).unwrap().public_prefix(true)
+        });
+        let ids = keys.clone().map(IdentifierPrefix::Basic);
+        let addresses = loopback_addresses();
+        let alice = start(&seeds[0], addresses[0].clone(), "alice");
+        let bob = start(&seeds[1], addresses[1].clone(), "bob");
+        assert_eq!(alice.local_peer_id(), peer_id(&keys[0]).unwrap());
+
+        // Address of bob isn't known yet.
+        assert!(alice.send_query(&ids[1], b"ping").is_err());
+
+        alice
+            .add_peer(&ids[1], &keys[1], addresses[1].clone())
+            .unwrap();
+        bob.add_peer(&ids[0], &keys[0], addresses[0].clone())
+            .unwrap();
+        assert_eq!(alice.send_query(&ids[1], b"ping").unwrap(), b"bob:ping");
+        assert_eq!(bob.send_query(&ids[0], b"pong").unwrap(), b"alice:pong");
+        assert_eq!(
+            alice.fetch_mailbox(&ids[1], b"mbx").unwrap(),
+            "bob:mbx".to_string()
+        );
+        // Stream that couldn't be handled is answered with empty stream.
+        assert!(bob.send_query(&ids[0], b"fail").unwrap().is_empty());
+    }
+}
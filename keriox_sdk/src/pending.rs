@@ -0,0 +1,427 @@
+use std::{path::Path, sync::Arc};
+
+use keri_core::{
+    actor::parse_event_stream,
+    database::{EscrowCreator, EventDatabase},
+    error::Error,
+    event::event_data::EventData,
+    event_message::signed_event_message::{
+        Message, Notice, SignedEventMessage,
+    },
+    prefix::IdentifierPrefix,
+    processor::{
+        notification::{
+            JustNotification, Notification, NotificationBus, Notifier,
+        },
+        Processor,
+    },
+};
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use teliox::database::TelEventDatabase;
+
+#[cfg(feature = "oobi-manager")]
+use crate::oobi::KelResolver;
+use crate::{controller::KeriRuntime, receipts::ReceiptCollector, Controller};
+
+/// Pending operations store event digest -> JSON serialized operation
+const OPERATIONS: TableDefinition<&str, &[u8]> =
+    TableDefinition::new("pending_operations");
+/// Identifiers whose operations are recorded
+const TRACKED: TableDefinition<&str, &[u8]> = TableDefinition::new("tracked");
+
+/// What event is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PendingKind {
+    WitnessReceipts,
+    /// Signatures of other members of group.
+    CoSignatures,
+    DelegatorAnchor,
+}
+
+/// Event of tracked identifier that isn't accepted yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingOperation {
+    pub kind: PendingKind,
+    pub id: IdentifierPrefix,
+    pub sn: u64,
+    pub delegator: Option<IdentifierPrefix>,
+    /// Signed event as CESR stream.
+    pub event: String,
+}
+
+impl PendingOperation {
+    pub fn event(&self) -> Result<SignedEventMessage, String> {
+        match parse_event_stream(self.event.as_bytes())
+            .map_err(|e| e.to_string())?
+            .pop()
+        {
+            Some(Message::Notice(Notice::Event(event))) => Ok(event),
+            _ => Err("Pending operation without event".to_string()),
+        }
+    }
+}
+
+/// Persistent record of events of tracked identifiers that wait for
+/// witness receipts, co-signatures or delegator's anchor. Operations are
+/// recorded and removed from notifications, once registered with
+/// `KeriRuntime::register_pending_operations`, and can be resumed with
+/// `KeriRuntime::resume_pending` after restart.
+pub struct PendingOperations {
+    db: Database,
+}
+
+impl PendingOperations {
+    pub fn new(path: &Path) -> Result<Self, String> {
+        let db = Database::create(path).map_err(|e| e.to_string())?;
+        // Create tables
+        let write_txn = db.begin_write().map_err(|e| e.to_string())?;
+        write_txn
+            .open_table(OPERATIONS)
+            .map_err(|e| e.to_string())?;
+        write_txn.open_table(TRACKED).map_err(|e| e.to_string())?;
+        write_txn.commit().map_err(|e| e.to_string())?;
+        Ok(Self { db })
+    }
+
+    /// Records operations of identifier from now on, e.g. own identifier
+    /// or group it is member of.
+    pub fn track(&self, id: &IdentifierPrefix) -> Result<(), String> {
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table =
+                write_txn.open_table(TRACKED).map_err(|e| e.to_string())?;
+            table
+                .insert(id.to_string().as_str(), [].as_slice())
+                .map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())
+    }
+
+    pub fn is_tracked(&self, id: &IdentifierPrefix) -> Result<bool, String> {
+        let read_txn = self.db.begin_read().map_err(|e| e.to_string())?;
+        let table = read_txn.open_table(TRACKED).map_err(|e| e.to_string())?;
+        let tracked = table
+            .get(id.to_string().as_str())
+            .map_err(|e| e.to_string())?
+            .is_some();
+        Ok(tracked)
+    }
+
+    /// Returns pending operations with digests of their events.
+    pub fn list(&self) -> Result<Vec<(String, PendingOperation)>, String> {
+        let read_txn = self.db.begin_read().map_err(|e| e.to_string())?;
+        let table =
+            read_txn.open_table(OPERATIONS).map_err(|e| e.to_string())?;
+        let operations = table
+            .iter()
+            .map_err(|e| e.to_string())?
+            .map(|entry| {
+                let (digest, value) = entry.map_err(|e| e.to_string())?;
+                let operation = serde_json::from_slice(value.value())
+                    .map_err(|e| e.to_string())?;
+                Ok((digest.value().to_string(), operation))
+            })
+            .collect();
+        operations
+    }
+
+    fn save(
+        &self,
+        digest: &str,
+        operation: &PendingOperation,
+    ) -> Result<(), String> {
+        let value = serde_json::to_vec(operation).map_err(|e| e.to_string())?;
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table = write_txn
+                .open_table(OPERATIONS)
+                .map_err(|e| e.to_string())?;
+            table
+                .insert(digest, value.as_slice())
+                .map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())
+    }
+
+    fn remove(&self, digest: &str) -> Result<(), String> {
+        let write_txn = self.db.begin_write().map_err(|e| e.to_string())?;
+        {
+            let mut table = write_txn
+                .open_table(OPERATIONS)
+                .map_err(|e| e.to_string())?;
+            table.remove(digest).map_err(|e| e.to_string())?;
+        }
+        write_txn.commit().map_err(|e| e.to_string())
+    }
+
+    fn record(
+        &self,
+        kind: PendingKind,
+        event: &SignedEventMessage,
+        delegator: Option<IdentifierPrefix>,
+    ) -> Result<(), String> {
+        let data = &event.event_message.data;
+        let id = data.get_prefix();
+        if !self.is_tracked(&id)? {
+            return Ok(());
+        }
+        let digest = event.event_message.digest().map_err(|e| e.to_string())?;
+        let stream = Message::Notice(Notice::Event(event.clone()))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+        self.save(
+            &digest.to_string(),
+            &PendingOperation {
+                kind,
+                id,
+                sn: data.get_sn(),
+                delegator,
+                event: String::from_utf8(stream).map_err(|e| e.to_string())?,
+            },
+        )
+    }
+}
+
+impl Notifier for PendingOperations {
+    fn notify(
+        &self,
+        notification: &Notification,
+        _bus: &NotificationBus,
+    ) -> Result<(), Error> {
+        let result = match notification {
+            Notification::PartiallyWitnessed(event) => {
+                self.record(PendingKind::WitnessReceipts, event, None)
+            }
+            Notification::PartiallySigned(event) => {
+                self.record(PendingKind::CoSignatures, event, None)
+            }
+            Notification::MissingDelegatingEvent(event) => {
+                let delegator = match &event.event_message.data.event_data {
+                    EventData::Dip(dip) => Some(dip.delegator.clone()),
+                    _ => None,
+                };
+                self.record(PendingKind::DelegatorAnchor, event, delegator)
+            }
+            Notification::KeyEventAccepted(accepted) => accepted
+                .event
+                .event_message
+                .digest()
+                .map_err(|e| e.to_string())
+                .and_then(|digest| self.remove(&digest.to_string())),
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to update pending operations: {}", e);
+        }
+        Ok(())
+    }
+}
+
+impl<D: EventDatabase + EscrowCreator + Send + Sync + 'static> KeriRuntime<D> {
+    /// Registers store recording operations of tracked identifiers until
+    /// their events are accepted.
+    pub fn register_pending_operations(
+        &self,
+        operations: Arc<PendingOperations>,
+    ) {
+        self.notification_bus.register_observer(
+            operations,
+            vec![
+                JustNotification::PartiallyWitnessed,
+                JustNotification::PartiallySigned,
+                JustNotification::MissingDelegatingEvent,
+                JustNotification::KeyEventAccepted,
+            ],
+        );
+    }
+
+    /// Resumes operations interrupted by restart: pending events are put
+    /// back to escrow, their receipts are collected again with `collector`
+    /// and KELs of delegators are fetched with `resolver`. Returns number
+    /// of operations still pending. Without `oobi-manager` feature there is
+    /// no resolver, and delegated events wait for delegator's KEL to be
+    /// processed otherwise.
+    pub fn resume_pending(
+        &self,
+        operations: &PendingOperations,
+        collector: Option<&ReceiptCollector<D>>,
+        #[cfg(feature = "oobi-manager")] resolver: Option<&KelResolver<D>>,
+    ) -> Result<usize, String> {
+        let mut delegated = vec![];
+        for (digest, operation) in operations.list()? {
+            let event = operation.event()?;
+            let accepted = self
+                .storage
+                .get_event_at_sn(&operation.id, operation.sn)
+                .and_then(|accepted| {
+                    accepted.signed_event_message.event_message.digest().ok()
+                })
+                .map(|accepted| accepted.to_string());
+            if accepted.as_deref() == Some(digest.as_str()) {
+                operations.remove(&digest)?;
+                continue;
+            }
+            // Escrow may not have survived restart, put event back first.
+            if let Err(e) =
+                self.processor.process_notice(&Notice::Event(event.clone()))
+            {
+                log::warn!("Failed to process pending event: {}", e);
+            }
+            match operation.kind {
+                PendingKind::WitnessReceipts => {
+                    if let Some(collector) = collector {
+                        if let Err(e) = collector.collect(&event) {
+                            log::warn!(
+                                "Receipts of {} still missing: {}",
+                                operation.id,
+                                e
+                            );
+                        }
+                    }
+                }
+                // Collected signatures are added by further exchanges.
+                PendingKind::CoSignatures => (),
+                PendingKind::DelegatorAnchor => {
+                    #[cfg(feature = "oobi-manager")]
+                    let delegator = operation.delegator.clone().or_else(|| {
+                        self.storage
+                            .get_state(&operation.id)
+                            .and_then(|state| state.delegator)
+                    });
+                    #[cfg(feature = "oobi-manager")]
+                    if let (Some(resolver), Some(delegator)) =
+                        (resolver, delegator)
+                    {
+                        resolver.request(&delegator);
+                    }
+                    delegated.push(operation.id);
+                }
+            }
+        }
+        #[cfg(feature = "oobi-manager")]
+        if let Some(resolver) = resolver {
+            resolver.resolve_pending();
+        }
+        for id in delegated {
+            self.reprocess_escrows(&id)?;
+        }
+        Ok(operations.list()?.len())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Records operations of `id`, if controller keeps pending operations.
+    pub(crate) fn track_pending(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<(), String> {
+        match self.pending_operations() {
+            Some(pending) => pending.track(id),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "oobi-manager"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use keri_core::{
+        database::redb::RedbDatabase, prefix::BasicPrefix, signer::Signer,
+    };
+    use teliox::database::redb::RedbTelDatabase;
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign, watcher_kel_resolver};
+
+    #[test]
+    fn test_resume_pending_delegation() {
+        let (_delegator_root, delegator_controller) = setup("delegator-db");
+        let (root, delegate_controller) = setup("delegate-db");
+        let path = root.path().join("pending");
+        let delegate_controller = delegate_controller.with_pending_operations(
+            Arc::new(PendingOperations::new(&path).unwrap()),
+        );
+
+        let delegator_signer = Signer::new();
+        let delegator = incept(&delegator_controller, &delegator_signer);
+        let signer = Signer::new();
+        let dip = delegate_controller
+            .incept_delegated(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+                &delegator.id,
+            )
+            .unwrap();
+        let (delegate, request) = delegate_controller
+            .finalize_incept_delegated(
+                dip.as_bytes(),
+                &sign(&signer, dip.as_bytes()),
+            )
+            .unwrap();
+        let operations = delegate_controller
+            .pending_operations()
+            .unwrap()
+            .list()
+            .unwrap();
+        assert_eq!(operations.len(), 1);
+        let operation = &operations[0].1;
+        assert_eq!(operation.kind, PendingKind::DelegatorAnchor);
+        assert_eq!(operation.id, delegate.id);
+        assert_eq!(operation.delegator, Some(delegator.id.clone()));
+
+        // Delegator approves while delegate is offline.
+        delegator_controller
+            .process_delegation_request(&delegator, &request)
+            .unwrap();
+        let ixn = delegator_controller
+            .approve_delegation(&delegator, dip.as_bytes())
+            .unwrap();
+        delegator_controller
+            .finalize_approve_delegation(
+                &delegator,
+                ixn.as_bytes(),
+                sign(&delegator_signer, ixn.as_bytes()),
+            )
+            .unwrap();
+        let served = Arc::new(Mutex::new(
+            delegator
+                .get_own_kel()
+                .unwrap()
+                .into_iter()
+                .flat_map(|notice| Message::Notice(notice).to_cesr().unwrap())
+                .collect(),
+        ));
+
+        // Restarted controller completes the inception. Identifier holds the
+        // database too, so it's closed only when both are dropped.
+        let delegate_id = delegate.id.clone();
+        drop(delegate);
+        drop(delegate_controller);
+        let delegate_controller = Controller::new(
+            Arc::new(RedbDatabase::new(&root.path().join("events")).unwrap()),
+            Arc::new(RedbTelDatabase::new(root.path().join("tel")).unwrap()),
+        )
+        .with_pending_operations(Arc::new(
+            PendingOperations::new(&path).unwrap(),
+        ));
+        let pending = delegate_controller.pending_operations().unwrap();
+        assert_eq!(pending.list().unwrap().len(), 1);
+        let resolver =
+            watcher_kel_resolver(&delegate_controller, &delegator.id, served);
+        assert_eq!(
+            delegate_controller
+                .kel
+                .resume_pending(&pending, None, Some(&resolver))
+                .unwrap(),
+            0
+        );
+        let state = delegate_controller.get_state(&delegate_id).unwrap();
+        assert_eq!(state.delegator, Some(delegator.id));
+    }
+}
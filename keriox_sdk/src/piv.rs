@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use cryptoki::{
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use keri_core::{error::Error, keys::PublicKey, signer::KeyManager};
+
+use crate::pkcs11::{
+    find_or_generate, generate, open_session, pkcs11_error, TokenSelector,
+};
+
+/// Number of retired key management PIV slots, `82` to `95`, which hold
+/// keys of `PivSigner`.
+const RETIRED_SLOTS: u64 = 20;
+/// PKCS#11 object id of first retired key management slot, as assigned by
+/// YKCS11.
+const FIRST_RETIRED_ID: u64 = 5;
+
+/// Interacts with operator holding PIV token.
+pub trait PivPrompt: Send + Sync {
+    /// Asks operator for PIN of the token.
+    fn pin(&self) -> Result<String, String>;
+
+    /// Notifies operator that token may wait for touch to sign, depending
+    /// on touch policy of the key.
+    fn touch(&self);
+}
+
+/// Ed25519 key manager keeping keys in PIV slots of YubiKey, used with
+/// YKCS11 PKCS#11 module of yubico-piv-tool. Requires YubiKey firmware 5.7
+/// or newer, which supports Ed25519 keys.
+///
+/// Keys are held in retired key management slots: key `n` of sequence of
+/// rotated keys is in slot `n mod 20`, so current and next keys occupy
+/// adjacent slots. Rotation generates new next key, overwriting key
+/// rotated out 18 rotations earlier.
+pub struct PivSigner {
+    session: Session,
+    prompt: Box<dyn PivPrompt>,
+    index: u64,
+    current: (ObjectHandle, PublicKey),
+    next: (ObjectHandle, PublicKey),
+}
+
+impl PivSigner {
+    /// Opens session with selected token of YKCS11 `module`, asking
+    /// `prompt` for PIN. Uses keys of slots of key `index` and `index + 1`,
+    /// generating the ones missing.
+    pub fn open(
+        module: impl AsRef<Path>,
+        token: TokenSelector,
+        prompt: Box<dyn PivPrompt>,
+        index: u64,
+    ) -> Result<Self, Error> {
+        let pin = prompt.pin().map_err(Error::SemanticError)?;
+        let session = open_session(module.as_ref(), token, &pin)?;
+        let current = find_or_generate(&session, &slot_id(index))?;
+        let next = find_or_generate(&session, &slot_id(index + 1))?;
+        Ok(Self {
+            session,
+            prompt,
+            index,
+            current,
+            next,
+        })
+    }
+
+    /// Number of current key in sequence of rotated keys. It has to be
+    /// persisted by application, so that signer can be opened again with
+    /// the same keys.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Checks whether key requires PIN before every signing, which is PIN
+    /// policy `always`.
+    fn always_authenticate(&self, key: ObjectHandle) -> Result<bool, Error> {
+        Ok(self
+            .session
+            .get_attributes(key, &[AttributeType::AlwaysAuthenticate])
+            .map_err(pkcs11_error)?
+            .into_iter()
+            .any(|attribute| {
+                matches!(attribute, Attribute::AlwaysAuthenticate(true))
+            }))
+    }
+}
+
+impl KeyManager for PivSigner {
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        let key = self.current.0;
+        if self.always_authenticate(key)? {
+            let pin = self.prompt.pin().map_err(Error::SemanticError)?;
+            self.session
+                .login(UserType::ContextSpecific, Some(&AuthPin::new(pin)))
+                .map_err(pkcs11_error)?;
+        }
+        self.prompt.touch();
+        self.session.sign(&Mechanism::Eddsa, key, msg).map_err(|e| {
+            log::warn!("PIV signing failed: {}", e);
+            Error::SigningError
+        })
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.current.1.clone()
+    }
+
+    fn next_public_key(&self) -> PublicKey {
+        self.next.1.clone()
+    }
+
+    fn rotate(&mut self) -> Result<(), Error> {
+        let next = generate(&self.session, &slot_id(self.index + 2))?;
+        self.current = std::mem::replace(&mut self.next, next);
+        self.index += 1;
+        Ok(())
+    }
+}
+
+/// Returns PKCS#11 object id of slot holding key `index`.
+fn slot_id(index: u64) -> Attribute {
+    let id = FIRST_RETIRED_ID + index % RETIRED_SLOTS;
+    Attribute::Id(vec![id as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_id() {
+        let id = |index| match slot_id(index) {
+            Attribute::Id(id) => id,
+            _ => unreachable!(),
+        };
+        assert_eq!(id(0), vec![5]);
+        assert_eq!(id(19), vec![24]);
+        // Slots are reused after 20 keys.
+        assert_eq!(id(20), id(0));
+    }
+}
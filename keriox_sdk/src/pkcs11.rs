@@ -0,0 +1,245 @@
+use std::path::Path;
+
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    slot::Slot,
+    types::AuthPin,
+};
+use keri_core::{error::Error, keys::PublicKey, signer::KeyManager};
+
+/// DER encoded OID of Ed25519 curve, used as `CKA_EC_PARAMS` of generated
+/// keys.
+const ED25519_PARAMS: [u8; 5] = [0x06, 0x03, 0x2b, 0x65, 0x70];
+
+/// Selects token of PKCS#11 module.
+#[derive(Debug, Clone)]
+pub enum TokenSelector {
+    /// Token in slot of given index, among slots with tokens present.
+    Index(usize),
+    /// Token of given label.
+    Label(String),
+}
+
+/// Ed25519 key manager keeping keys in PKCS#11 token, e.g. HSM. Keys never
+/// leave the token: they are generated by it and referenced by labels
+/// `<prefix>-<n>`, where `n` is number of key in sequence of rotated keys.
+/// Current key is `n`, next key, whose digest is committed in the last
+/// establishment event, is `n + 1`.
+pub struct Pkcs11Signer {
+    session: Session,
+    label_prefix: String,
+    index: u64,
+    current: (ObjectHandle, PublicKey),
+    next: (ObjectHandle, PublicKey),
+}
+
+impl Pkcs11Signer {
+    /// Opens session with selected token of PKCS#11 module loaded from
+    /// `module` library and logs in as user with `pin`. Uses keys of
+    /// `label_prefix` labels, starting with key `index`. Keys missing from
+    /// token are generated.
+    pub fn open(
+        module: impl AsRef<Path>,
+        token: TokenSelector,
+        pin: &str,
+        label_prefix: &str,
+        index: u64,
+    ) -> Result<Self, Error> {
+        let session = open_session(module.as_ref(), token, pin)?;
+        let current =
+            find_or_generate(&session, &key_label(label_prefix, index))?;
+        let next =
+            find_or_generate(&session, &key_label(label_prefix, index + 1))?;
+        Ok(Self {
+            session,
+            label_prefix: label_prefix.to_string(),
+            index,
+            current,
+            next,
+        })
+    }
+
+    /// Number of current key in sequence of rotated keys. It has to be
+    /// persisted by application, so that signer can be opened again with
+    /// the same keys.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+}
+
+impl KeyManager for Pkcs11Signer {
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        self.session
+            .sign(&Mechanism::Eddsa, self.current.0, msg)
+            .map_err(|e| {
+                log::warn!("PKCS#11 signing failed: {}", e);
+                Error::SigningError
+            })
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.current.1.clone()
+    }
+
+    fn next_public_key(&self) -> PublicKey {
+        self.next.1.clone()
+    }
+
+    /// Makes next key the current one and generates new next key. Key
+    /// pair exposed by rotation is kept in token, so that signatures made
+    /// with it can be repeated if needed.
+    fn rotate(&mut self) -> Result<(), Error> {
+        let next = find_or_generate(
+            &self.session,
+            &key_label(&self.label_prefix, self.index + 2),
+        )?;
+        self.current = std::mem::replace(&mut self.next, next);
+        self.index += 1;
+        Ok(())
+    }
+}
+
+fn key_label(prefix: &str, index: u64) -> Attribute {
+    Attribute::Label(format!("{}-{}", prefix, index).into_bytes())
+}
+
+pub(crate) fn pkcs11_error(e: cryptoki::error::Error) -> Error {
+    Error::SemanticError(format!("PKCS#11 error: {}", e))
+}
+
+/// Opens session with selected token of PKCS#11 module loaded from
+/// `module` library and logs in as user with `pin`.
+pub(crate) fn open_session(
+    module: &Path,
+    token: TokenSelector,
+    pin: &str,
+) -> Result<Session, Error> {
+    let pkcs11 = Pkcs11::new(module).map_err(pkcs11_error)?;
+    pkcs11
+        .initialize(CInitializeArgs::OsThreads)
+        .map_err(pkcs11_error)?;
+    let slot = select_slot(&pkcs11, token)?;
+    let session = pkcs11.open_rw_session(slot).map_err(pkcs11_error)?;
+    session
+        .login(UserType::User, Some(&AuthPin::new(pin.to_string())))
+        .map_err(pkcs11_error)?;
+    Ok(session)
+}
+
+fn select_slot(pkcs11: &Pkcs11, token: TokenSelector) -> Result<Slot, Error> {
+    let slots = pkcs11.get_slots_with_token().map_err(pkcs11_error)?;
+    match token {
+        TokenSelector::Index(index) => slots.get(index).copied(),
+        TokenSelector::Label(label) => slots.into_iter().find(|slot| {
+            pkcs11
+                .get_token_info(*slot)
+                .map(|info| info.label().trim_end() == label)
+                .unwrap_or(false)
+        }),
+    }
+    .ok_or(Error::SemanticError("PKCS#11 token not found".to_string()))
+}
+
+/// Returns private key identified by `key` attribute, e.g. its label,
+/// along with its public key, generating the key pair if token doesn't
+/// hold it yet.
+pub(crate) fn find_or_generate(
+    session: &Session,
+    key: &Attribute,
+) -> Result<(ObjectHandle, PublicKey), Error> {
+    let find = |class| {
+        session
+            .find_objects(&[Attribute::Class(class), key.clone()])
+            .map(|handles| handles.first().copied())
+            .map_err(pkcs11_error)
+    };
+    match (
+        find(ObjectClass::PUBLIC_KEY)?,
+        find(ObjectClass::PRIVATE_KEY)?,
+    ) {
+        (Some(public), Some(private)) => {
+            Ok((private, public_key(session, public)?))
+        }
+        (None, None) => generate(session, key),
+        _ => Err(Error::SemanticError(format!(
+            "Incomplete key pair {:?} in PKCS#11 token",
+            key
+        ))),
+    }
+}
+
+/// Generates Ed25519 key pair identified by `key` attribute. Returns its
+/// private key along with public key.
+pub(crate) fn generate(
+    session: &Session,
+    key: &Attribute,
+) -> Result<(ObjectHandle, PublicKey), Error> {
+    let (public, private) = session
+        .generate_key_pair(
+            &Mechanism::EccEdwardsKeyPairGen,
+            &[
+                Attribute::Token(true),
+                Attribute::Verify(true),
+                Attribute::EcParams(ED25519_PARAMS.to_vec()),
+                key.clone(),
+            ],
+            &[
+                Attribute::Token(true),
+                Attribute::Private(true),
+                Attribute::Sensitive(true),
+                Attribute::Extractable(false),
+                Attribute::KeyType(KeyType::EC_EDWARDS),
+                Attribute::Sign(true),
+                key.clone(),
+            ],
+        )
+        .map_err(pkcs11_error)?;
+    Ok((private, public_key(session, public)?))
+}
+
+fn public_key(
+    session: &Session,
+    public: ObjectHandle,
+) -> Result<PublicKey, Error> {
+    let point = session
+        .get_attributes(public, &[AttributeType::EcPoint])
+        .map_err(pkcs11_error)?
+        .into_iter()
+        .find_map(|attribute| match attribute {
+            Attribute::EcPoint(point) => Some(point),
+            _ => None,
+        })
+        .ok_or(Error::SemanticError(
+            "Missing PKCS#11 public key".to_string(),
+        ))?;
+    Ok(PublicKey::new(ed25519_point(&point)?))
+}
+
+/// Extracts Ed25519 public key from `CKA_EC_POINT`, which tokens return
+/// either raw or wrapped in DER octet string.
+fn ed25519_point(point: &[u8]) -> Result<Vec<u8>, Error> {
+    match point {
+        [0x04, 0x20, key @ ..] if key.len() == 32 => Ok(key.to_vec()),
+        key if key.len() == 32 => Ok(key.to_vec()),
+        _ => Err(Error::SemanticError(
+            "Unsupported PKCS#11 public key format".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_point() {
+        let key = [7u8; 32];
+        let wrapped = [[0x04, 0x20].as_slice(), &key].concat();
+        assert_eq!(ed25519_point(&wrapped).unwrap(), key);
+        assert_eq!(ed25519_point(&key).unwrap(), key);
+        assert!(ed25519_point(&key[1..]).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use keri_core::prefix::{IdentifierPrefix, SelfSigningPrefix};
+
+/// Default number of events requested with single KEL query.
+pub const KEL_QUERY_PAGE_SIZE: u64 = 100;
+
+/// Sends signed query to witness or watcher and returns its response.
+pub trait QueryTransport: Send + Sync {
+    fn query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String>;
+}
+
+impl<F> QueryTransport for F
+where
+    F: Fn(&IdentifierPrefix, &[u8]) -> Result<Vec<u8>, String> + Send + Sync,
+{
+    fn query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self(recipient, query)
+    }
+}
+
+/// Signs queries generated while paginating over KEL.
+pub trait QuerySigner {
+    fn sign(&self, query: &[u8]) -> Result<SelfSigningPrefix, String>;
+}
+
+impl<F> QuerySigner for F
+where
+    F: Fn(&[u8]) -> Result<SelfSigningPrefix, String>,
+{
+    fn sign(&self, query: &[u8]) -> Result<SelfSigningPrefix, String> {
+        self(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        actor::{event_generator, parse_query_stream},
+        event_message::{
+            cesr_adapter::{parse_event_type, EventType},
+            signed_event_message::{Message, Notice},
+        },
+        prefix::{BasicPrefix, IndexedSignature},
+        query::query_event::{QueryRoute, SignedQueryMessage},
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_query_kel() {
+        // Witness side, which knows KEL of 5 events.
+        let (_witness_root, witness) = setup("witness-db");
+        let signer = Signer::new();
+        let queried = incept(&witness, &signer);
+        for _ in 0..4 {
+            let state = witness.get_state(&queried.id).unwrap();
+            let ixn = event_generator::anchor(state, &[]).unwrap();
+            let EventType::KeyEvent(event) =
+                parse_event_type(ixn.as_bytes()).unwrap()
+            else {
+                unreachable!()
+            };
+            let signature = IndexedSignature::new_both_same(
+                sign(&signer, ixn.as_bytes()),
+                0,
+            );
+            witness
+                .process_kel(&[Message::Notice(Notice::Event(event.sign(
+                    vec![signature],
+                    None,
+                    None,
+                )))])
+                .unwrap();
+        }
+        let witness_storage = witness.kel.storage.clone();
+        let transport = move |_recipient: &IdentifierPrefix,
+                              query: &[u8]|
+              -> Result<Vec<u8>, String> {
+            // Only KEL queries exist without `mailbox` feature.
+            #[allow(irrefutable_let_patterns)]
+            let SignedQueryMessage::KelQuery(qry) =
+                parse_query_stream(query).unwrap().remove(0)
+            else {
+                return Err("Not a KEL query".to_string());
+            };
+            let QueryRoute::Logs { args, .. } = qry.query.get_route() else {
+                return Err("Not a logs query".to_string());
+            };
+            Ok(witness_storage
+                .get_kel_messages_with_receipts_range(
+                    &args.i,
+                    args.s.unwrap_or_default(),
+                    args.limit.unwrap_or(KEL_QUERY_PAGE_SIZE),
+                )
+                .unwrap()
+                .unwrap_or_default()
+                .into_iter()
+                .flat_map(|notice| Message::Notice(notice).to_cesr().unwrap())
+                .collect())
+        };
+
+        let (_root, controller) = setup("test-db");
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let witness_id = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let query_signer = |query: &[u8]| -> Result<SelfSigningPrefix, String> {
+            Ok(sign(&signer, query))
+        };
+
+        let received = identifier
+            .query_kel(
+                &queried.id,
+                0,
+                &witness_id,
+                2,
+                &query_signer,
+                &transport,
+            )
+            .unwrap();
+        assert_eq!(received, 5);
+        assert_eq!(controller.get_state(&queried.id).unwrap().sn, 4);
+
+        let received = identifier
+            .query_kel(
+                &queried.id,
+                5,
+                &witness_id,
+                2,
+                &query_signer,
+                &transport,
+            )
+            .unwrap();
+        assert_eq!(received, 0);
+    }
+}
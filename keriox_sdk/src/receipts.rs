@@ -0,0 +1,511 @@
+use std::{
+    collections::HashSet, sync::Arc, thread::JoinHandle, time::Duration,
+};
+
+use keri_core::{
+    actor::{parse_event_stream, prelude::EventStorage},
+    database::{EscrowCreator, EventDatabase},
+    event_message::{
+        signature::Nontransferable,
+        signed_event_message::{Message, Notice, SignedEventMessage},
+    },
+    prefix::{BasicPrefix, CesrPrimitive, IdentifierPrefix},
+    processor::{basic_processor::BasicProcessor, Processor},
+    state::IdentifierState,
+};
+
+use crate::witness::WitnessPublisher;
+
+/// Source of receipts made by witness.
+///
+/// Implementations are expected to query witness, e.g. its mailbox, for
+/// receipts of identifier's event and return them as CESR stream.
+pub trait ReceiptFetcher: Send + Sync {
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        sn: u64,
+    ) -> Result<Vec<u8>, String>;
+}
+
+impl<F> ReceiptFetcher for F
+where
+    F: Fn(&BasicPrefix, &IdentifierPrefix, u64) -> Result<Vec<u8>, String>
+        + Send
+        + Sync,
+{
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        sn: u64,
+    ) -> Result<Vec<u8>, String> {
+        self(witness, id, sn)
+    }
+}
+
+/// How witnesses that didn't receipt event yet are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Publishes events to witnesses and collects their receipts until event
+/// is fully witnessed.
+pub struct ReceiptCollector<D: EventDatabase + EscrowCreator + 'static> {
+    processor: Arc<BasicProcessor<D>>,
+    storage: Arc<EventStorage<D>>,
+    publisher: Arc<dyn WitnessPublisher>,
+    fetcher: Arc<dyn ReceiptFetcher>,
+    retry: RetryPolicy,
+}
+
+impl<D: EventDatabase + EscrowCreator + Send + Sync + 'static>
+    ReceiptCollector<D>
+{
+    pub fn new(
+        processor: Arc<BasicProcessor<D>>,
+        storage: Arc<EventStorage<D>>,
+        publisher: Arc<dyn WitnessPublisher>,
+        fetcher: Arc<dyn ReceiptFetcher>,
+    ) -> Self {
+        Self {
+            processor,
+            storage,
+            publisher,
+            fetcher,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry_policy(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// Sends event to all its witnesses and processes receipts they
+    /// return. Witnesses that didn't receipt the event are retried with
+    /// exponential backoff, until witness threshold is met and the event
+    /// is accepted. Collected receipts are then shared with all witnesses.
+    pub fn collect(&self, event: &SignedEventMessage) -> Result<(), String> {
+        let id = event.event_message.data.get_prefix();
+        let sn = event.event_message.data.get_sn();
+        let state = self.state_after(event)?;
+        let witnesses = state.witness_config.witnesses;
+        let stream = Message::Notice(Notice::Event(event.clone()))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+
+        let mut receipted = HashSet::new();
+        let mut backoff = self.retry.initial_backoff;
+        for attempt in 0..self.retry.max_attempts {
+            for witness in &witnesses {
+                if receipted.contains(witness) {
+                    continue;
+                }
+                if let Err(e) = self.publisher.publish(witness, &stream) {
+                    log::warn!(
+                        "Failed to publish to {}: {}",
+                        witness.to_str(),
+                        e
+                    );
+                    continue;
+                }
+                match self.fetcher.fetch_receipts(witness, &id, sn) {
+                    Ok(receipts) => self.process_receipts(
+                        &receipts,
+                        event,
+                        &witnesses,
+                        &mut receipted,
+                    )?,
+                    Err(e) => log::warn!(
+                        "Failed to fetch receipts from {}: {}",
+                        witness.to_str(),
+                        e
+                    ),
+                }
+            }
+            if self.is_witnessed(event)? {
+                self.share_receipts(&id, sn, &witnesses)?;
+                return Ok(());
+            }
+            if attempt + 1 < self.retry.max_attempts {
+                std::thread::sleep(backoff);
+                backoff = std::cmp::min(backoff * 2, self.retry.max_backoff);
+            }
+        }
+
+        let stragglers: Vec<String> = witnesses
+            .iter()
+            .filter(|witness| !receipted.contains(*witness))
+            .map(|witness| witness.to_str())
+            .collect();
+        Err(format!(
+            "Event not witnessed, missing receipts from: {}",
+            stragglers.join(", ")
+        ))
+    }
+
+    /// Collects receipts in background thread and calls `callback` with
+    /// the result once event is fully witnessed or retries are exhausted.
+    pub fn collect_with_callback<F>(
+        self: &Arc<Self>,
+        event: SignedEventMessage,
+        callback: F,
+    ) -> JoinHandle<()>
+    where
+        F: FnOnce(Result<(), String>) + Send + 'static,
+    {
+        let collector = self.clone();
+        std::thread::spawn(move || callback(collector.collect(&event)))
+    }
+
+    /// Witness configuration in force after the event is accepted.
+    fn state_after(
+        &self,
+        event: &SignedEventMessage,
+    ) -> Result<IdentifierState, String> {
+        let id = event.event_message.data.get_prefix();
+        let sn = event.event_message.data.get_sn();
+        if let Some(state) = self
+            .storage
+            .get_state_at(&id, sn)
+            .map_err(|e| e.to_string())?
+        {
+            return Ok(state);
+        }
+        match self.storage.get_state(&id) {
+            Some(state) => state.apply(&event.event_message),
+            None => IdentifierState::default().apply(&event.event_message),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    fn process_receipts(
+        &self,
+        stream: &[u8],
+        event: &SignedEventMessage,
+        witnesses: &[BasicPrefix],
+        receipted: &mut HashSet<BasicPrefix>,
+    ) -> Result<(), String> {
+        let digest = event.event_message.digest().map_err(|e| e.to_string())?;
+        for message in parse_event_stream(stream).map_err(|e| e.to_string())? {
+            let Message::Notice(notice) = message else {
+                continue;
+            };
+            if let Notice::NontransferableRct(rct) = &notice {
+                if rct.body.receipted_event_digest == digest {
+                    receipted
+                        .extend(receipt_signers(&rct.signatures, witnesses));
+                }
+            }
+            if let Err(e) = self.processor.process_notice(&notice) {
+                log::debug!("Witness message not accepted: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_witnessed(&self, event: &SignedEventMessage) -> Result<bool, String> {
+        let digest = event.event_message.digest().map_err(|e| e.to_string())?;
+        Ok(self
+            .storage
+            .get_event_at_sn(
+                &event.event_message.data.get_prefix(),
+                event.event_message.data.get_sn(),
+            )
+            .and_then(|ev| ev.signed_event_message.event_message.digest().ok())
+            == Some(digest))
+    }
+
+    /// Sends all collected receipts to witnesses, so each of them can
+    /// serve fully witnessed event.
+    fn share_receipts(
+        &self,
+        id: &IdentifierPrefix,
+        sn: u64,
+        witnesses: &[BasicPrefix],
+    ) -> Result<(), String> {
+        let Some(receipt) = self
+            .storage
+            .get_nt_receipts(id, sn)
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(());
+        };
+        let stream = Message::Notice(Notice::NontransferableRct(receipt))
+            .to_cesr()
+            .map_err(|e| e.to_string())?;
+        for witness in witnesses {
+            if let Err(e) = self.publisher.publish(witness, &stream) {
+                log::warn!(
+                    "Failed to send receipts to {}: {}",
+                    witness.to_str(),
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn receipt_signers(
+    signatures: &[Nontransferable],
+    witnesses: &[BasicPrefix],
+) -> Vec<BasicPrefix> {
+    signatures
+        .iter()
+        .flat_map(|signature| match signature {
+            Nontransferable::Couplet(couplets) => {
+                couplets.iter().map(|(w, _)| w.clone()).collect()
+            }
+            Nontransferable::Indexed(sigs) => sigs
+                .iter()
+                .filter_map(|sig| {
+                    witnesses.get(sig.index.current() as usize).cloned()
+                })
+                .collect::<Vec<_>>(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use keri_core::{
+        actor::{event_generator, prelude::SerializationFormats},
+        event::receipt::Receipt,
+        event_message::{
+            cesr_adapter::{parse_event_type, EventType},
+            signed_event_message::SignedNontransferableReceipt,
+        },
+        prefix::IndexedSignature,
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{setup, sign};
+
+    #[test]
+    fn test_collect_receipts() {
+        let (_root, controller) = setup("test-db");
+
+        let witness_signers = Arc::new(vec![Signer::new(), Signer::new()]);
+        let witnesses: Vec<BasicPrefix> = witness_signers
+            .iter()
+            .map(|signer| BasicPrefix::Ed25519NT(signer.public_key()))
+            .collect();
+        let signer = Signer::new();
+        let icp = event_generator::incept(
+            vec![BasicPrefix::Ed25519(signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            witnesses.clone(),
+            2,
+            None,
+        )
+        .unwrap();
+        let EventType::KeyEvent(icp) =
+            parse_event_type(icp.as_bytes()).unwrap()
+        else {
+            unreachable!()
+        };
+        let sig = sign(&signer, &icp.encode().unwrap());
+        let signed_icp =
+            icp.sign(vec![IndexedSignature::new_both_same(sig, 0)], None, None);
+
+        // Event is escrowed until witnesses receipt it.
+        controller
+            .kel
+            .processor
+            .process_notice(&Notice::Event(signed_icp.clone()))
+            .unwrap();
+        assert!(controller.get_state(&icp.data.get_prefix()).is_none());
+
+        // First witness is unreachable on first attempt.
+        let attempts = Arc::new(AtomicU32::new(0));
+        let fetcher = {
+            let (attempts, witness_signers) =
+                (attempts.clone(), witness_signers.clone());
+            let icp = icp.clone();
+            move |witness: &BasicPrefix, id: &IdentifierPrefix, sn: u64| {
+                let position = witness_signers
+                    .iter()
+                    .position(|s| {
+                        &BasicPrefix::Ed25519NT(s.public_key()) == witness
+                    })
+                    .unwrap();
+                if position == 0 && attempts.fetch_add(1, Ordering::SeqCst) == 0
+                {
+                    return Err("Witness unreachable".to_string());
+                }
+                let receipt = Receipt::new(
+                    SerializationFormats::JSON,
+                    icp.digest().unwrap(),
+                    id.clone(),
+                    sn,
+                );
+                let signature =
+                    sign(&witness_signers[position], &icp.encode().unwrap());
+                Message::Notice(Notice::NontransferableRct(
+                    SignedNontransferableReceipt::new(
+                        &receipt,
+                        vec![Nontransferable::Couplet(vec![(
+                            witness.clone(),
+                            signature,
+                        )])],
+                    ),
+                ))
+                .to_cesr()
+                .map_err(|e| e.to_string())
+            }
+        };
+
+        // Receipt of the first witness never comes, so event stays in
+        // escrow.
+        let failing_fetcher = {
+            let (fetcher, unreachable) =
+                (fetcher.clone(), witnesses[0].clone());
+            move |witness: &BasicPrefix, id: &IdentifierPrefix, sn: u64| {
+                if witness == &unreachable {
+                    return Err("Witness unreachable".to_string());
+                }
+                fetcher(witness, id, sn)
+            }
+        };
+        let collector = controller
+            .kel
+            .receipt_collector(
+                Arc::new(|_: &BasicPrefix, _: &[u8]| Ok(())),
+                Arc::new(failing_fetcher),
+            )
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            });
+        let err = collector.collect(&signed_icp).unwrap_err();
+        assert!(err.contains(&witnesses[0].to_str()));
+        assert!(!err.contains(&witnesses[1].to_str()));
+        assert!(controller.get_state(&icp.data.get_prefix()).is_none());
+        assert_eq!(attempts.load(Ordering::SeqCst), 0);
+
+        let published = Arc::new(AtomicU32::new(0));
+        let publisher = {
+            let published = published.clone();
+            move |_witness: &BasicPrefix, _stream: &[u8]| {
+                published.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        };
+
+        let collector = controller
+            .kel
+            .receipt_collector(Arc::new(publisher), Arc::new(fetcher))
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            });
+        collector.collect(&signed_icp).unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        // Event sent three times, then receipts shared with both witnesses.
+        assert_eq!(published.load(Ordering::SeqCst), 5);
+        assert!(controller.get_state(&icp.data.get_prefix()).is_some());
+    }
+
+    #[test]
+    fn test_state_after_accepted_event() {
+        let (_root, controller) = setup("test-db");
+
+        let (first_witness, second_witness) = (
+            BasicPrefix::Ed25519NT(Signer::new().public_key()),
+            BasicPrefix::Ed25519NT(Signer::new().public_key()),
+        );
+        let (signer, next_signer) = (Signer::new(), Signer::new());
+        let icp = event_generator::incept(
+            vec![BasicPrefix::Ed25519(signer.public_key())],
+            vec![BasicPrefix::Ed25519(next_signer.public_key())],
+            vec![first_witness.clone()],
+            0,
+            None,
+        )
+        .unwrap();
+        let EventType::KeyEvent(icp) =
+            parse_event_type(icp.as_bytes()).unwrap()
+        else {
+            unreachable!()
+        };
+        let sig = sign(&signer, &icp.encode().unwrap());
+        let signed_icp =
+            icp.sign(vec![IndexedSignature::new_both_same(sig, 0)], None, None);
+        controller
+            .kel
+            .processor
+            .process_notice(&Notice::Event(signed_icp.clone()))
+            .unwrap();
+        let id = icp.data.get_prefix();
+
+        // Witness is replaced by rotation.
+        let rot = event_generator::rotate(
+            controller.get_state(&id).unwrap(),
+            vec![BasicPrefix::Ed25519(next_signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            1,
+            vec![second_witness.clone()],
+            vec![first_witness.clone()],
+            0,
+        )
+        .unwrap();
+        let EventType::KeyEvent(rot) =
+            parse_event_type(rot.as_bytes()).unwrap()
+        else {
+            unreachable!()
+        };
+        let sig = sign(&next_signer, &rot.encode().unwrap());
+        let signed_rot =
+            rot.sign(vec![IndexedSignature::new_both_same(sig, 0)], None, None);
+        controller
+            .kel
+            .processor
+            .process_notice(&Notice::Event(signed_rot.clone()))
+            .unwrap();
+        assert_eq!(controller.get_state(&id).unwrap().sn, 1);
+
+        let collector = controller.kel.receipt_collector(
+            Arc::new(|_: &BasicPrefix, _: &[u8]| Ok(())),
+            Arc::new(|_: &BasicPrefix, _: &IdentifierPrefix, _: u64| {
+                Ok(vec![])
+            }),
+        );
+        assert_eq!(
+            collector
+                .state_after(&signed_icp)
+                .unwrap()
+                .witness_config
+                .witnesses,
+            vec![first_witness]
+        );
+        assert_eq!(
+            collector
+                .state_after(&signed_rot)
+                .unwrap()
+                .witness_config
+                .witnesses,
+            vec![second_witness]
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use k256::ecdsa::{Signature, VerifyingKey};
+use keri_core::{error::Error, keys::PublicKey, signer::KeyManager};
+use sha2::{Digest, Sha256};
+
+/// Length of uncompressed secp256k1 point, which ends DER encoded public
+/// key.
+const UNCOMPRESSED_POINT_LEN: usize = 65;
+
+/// Key held by remote key management service. `id` is service specific
+/// name of the key, e.g. AWS KMS key id, Azure Key Vault key identifier or
+/// GCP crypto key version resource name. Public key is compressed, to be
+/// used as `BasicPrefix::ECDSAsecp256k1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteKeyRef {
+    pub id: String,
+    pub public_key: PublicKey,
+}
+
+/// Key management service holding ECDSA secp256k1 keys, which never leave
+/// it.
+pub trait RemoteKeyService {
+    /// Creates new signing key.
+    fn create_key(&self) -> Result<RemoteKeyRef, Error>;
+
+    /// Returns key of provided id.
+    fn get_key(&self, id: &str) -> Result<RemoteKeyRef, Error>;
+
+    /// Signs SHA-256 digest with key. Returns signature as `r || s`.
+    fn sign_digest(
+        &self,
+        key: &RemoteKeyRef,
+        digest: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+}
+
+/// Key manager keeping keys in remote key management service. Messages
+/// are hashed locally and only their digests are sent to the service, so
+/// events of any size can be signed.
+///
+/// Next key is created up front, so its digest can be committed in
+/// establishment event before it's used. Rotation promotes it to current
+/// key and creates new next key. Rotated out keys are left in the service,
+/// it's up to the operator to disable them or schedule their deletion.
+pub struct RemoteKeyManager<S: RemoteKeyService> {
+    service: S,
+    current: RemoteKeyRef,
+    next: RemoteKeyRef,
+}
+
+impl<S: RemoteKeyService> RemoteKeyManager<S> {
+    /// Creates current and next keys in the service.
+    pub fn create(service: S) -> Result<Self, Error> {
+        let current = service.create_key()?;
+        let next = service.create_key()?;
+        Ok(Self {
+            service,
+            current,
+            next,
+        })
+    }
+
+    /// Uses existing keys of provided ids, e.g. ones returned by `key_ids`
+    /// before.
+    pub fn open(
+        service: S,
+        current_key_id: &str,
+        next_key_id: &str,
+    ) -> Result<Self, Error> {
+        let current = service.get_key(current_key_id)?;
+        let next = service.get_key(next_key_id)?;
+        Ok(Self {
+            service,
+            current,
+            next,
+        })
+    }
+
+    /// Returns ids of current and next keys. They have to be persisted by
+    /// application, so that key manager can be opened again.
+    pub fn key_ids(&self) -> (&str, &str) {
+        (&self.current.id, &self.next.id)
+    }
+}
+
+impl<S: RemoteKeyService> KeyManager for RemoteKeyManager<S> {
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        let digest = Sha256::digest(msg);
+        let signature = self.service.sign_digest(&self.current, &digest)?;
+        low_s(&signature)
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.current.public_key.clone()
+    }
+
+    fn next_public_key(&self) -> PublicKey {
+        self.next.public_key.clone()
+    }
+
+    fn rotate(&mut self) -> Result<(), Error> {
+        let next = self.service.create_key()?;
+        self.current = std::mem::replace(&mut self.next, next);
+        Ok(())
+    }
+}
+
+pub(crate) fn remote_key_error(e: impl std::fmt::Display) -> Error {
+    Error::SemanticError(format!("Remote key error: {}", e))
+}
+
+/// Compresses SEC1 encoded secp256k1 public key.
+pub(crate) fn compressed_key(point: &[u8]) -> Result<PublicKey, Error> {
+    let key = VerifyingKey::from_sec1_bytes(point)
+        .map_err(|_| remote_key_error("invalid public key"))?;
+    Ok(PublicKey::new(key.to_bytes().to_vec()))
+}
+
+/// Compresses secp256k1 public key encoded as DER SubjectPublicKeyInfo.
+pub(crate) fn spki_key(spki: &[u8]) -> Result<PublicKey, Error> {
+    let point = spki
+        .len()
+        .checked_sub(UNCOMPRESSED_POINT_LEN)
+        .map(|start| &spki[start..])
+        .ok_or(remote_key_error("invalid public key"))?;
+    compressed_key(point)
+}
+
+/// Converts DER encoded ECDSA signature to `r || s` form.
+pub(crate) fn der_signature(der: &[u8]) -> Result<Vec<u8>, Error> {
+    let (r, rest) = match der {
+        [0x30, len, rest @ ..] if *len as usize == rest.len() => {
+            der_integer(rest)?
+        }
+        _ => return Err(Error::SigningError),
+    };
+    let (s, rest) = der_integer(rest)?;
+    if !rest.is_empty() {
+        return Err(Error::SigningError);
+    }
+    Ok([r, s].concat())
+}
+
+/// Parses DER integer into 32 bytes big endian scalar. Returns it along
+/// with remaining bytes.
+fn der_integer(der: &[u8]) -> Result<([u8; 32], &[u8]), Error> {
+    let (value, rest) = match der {
+        [0x02, len, rest @ ..] if (*len as usize) <= rest.len() => {
+            rest.split_at(*len as usize)
+        }
+        _ => return Err(Error::SigningError),
+    };
+    // Leading zero is added by DER to integers with high bit set.
+    let value = match value {
+        [0x00, value @ ..] => value,
+        value => value,
+    };
+    let mut scalar = [0u8; 32];
+    let start = scalar
+        .len()
+        .checked_sub(value.len())
+        .ok_or(Error::SigningError)?;
+    scalar[start..].copy_from_slice(value);
+    Ok((scalar, rest))
+}
+
+/// Normalizes `r || s` signature to low `s` form, which is the only one
+/// accepted by verifiers.
+fn low_s(signature: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut signature =
+        Signature::try_from(signature).map_err(|_| Error::SigningError)?;
+    signature.normalize_s().map_err(|_| Error::SigningError)?;
+    Ok(signature.as_ref().to_vec())
+}
+
+/// Sends JSON request to REST API of key management service, authorized
+/// with bearer token. Returns JSON response.
+#[cfg(any(feature = "azure-keyvault", feature = "gcp-kms"))]
+pub(crate) fn request_json(
+    request: reqwest::blocking::RequestBuilder,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> Result<serde_json::Value, Error> {
+    let request = request.bearer_auth(token);
+    let request = match body {
+        Some(body) => request
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string()),
+        None => request,
+    };
+    let response = request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.bytes())
+        .map_err(remote_key_error)?;
+    serde_json::from_slice(&response).map_err(remote_key_error)
+}
+
+/// Provides OAuth 2.0 access tokens for REST APIs of cloud key management
+/// services.
+#[cfg(any(feature = "azure-keyvault", feature = "gcp-kms"))]
+pub trait AccessTokenProvider: Send + Sync {
+    fn access_token(&self) -> Result<String, String>;
+}
+
+#[cfg(any(feature = "azure-keyvault", feature = "gcp-kms"))]
+impl<F> AccessTokenProvider for F
+where
+    F: Fn() -> Result<String, String> + Send + Sync,
+{
+    fn access_token(&self) -> Result<String, String> {
+        self()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::keys::PrivateKey;
+
+    use super::*;
+
+    fn der_encode(raw: &[u8]) -> Vec<u8> {
+        let integer = |value: &[u8]| {
+            let mut value = value.to_vec();
+            if value[0] & 0x80 != 0 {
+                value.insert(0, 0);
+            }
+            [vec![0x02, value.len() as u8], value].concat()
+        };
+        let body = [integer(&raw[..32]), integer(&raw[32..])].concat();
+        [vec![0x30, body.len() as u8], body].concat()
+    }
+
+    fn hex(encoded: &str) -> Vec<u8> {
+        (0..encoded.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&encoded[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_der_signature() {
+        let private_key = PrivateKey::new(vec![1u8; 32]);
+        let public_key = PublicKey::new(
+            k256::ecdsa::SigningKey::from_bytes(&[1u8; 32])
+                .unwrap()
+                .verifying_key()
+                .to_bytes()
+                .to_vec(),
+        );
+        let raw = private_key.sign_ecdsa(b"message").unwrap();
+
+        let parsed = der_signature(&der_encode(&raw)).unwrap();
+        assert_eq!(parsed, raw);
+        assert!(public_key.verify_ecdsa(b"message", &low_s(&parsed).unwrap()));
+        assert!(der_signature(&raw).is_err());
+    }
+
+    #[test]
+    fn test_spki_key() {
+        // SubjectPublicKeyInfo of secp256k1 generator point.
+        let spki = hex(concat!(
+            "3056301006072a8648ce3d020106052b8104000a034200",
+            "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        ));
+        assert_eq!(
+            spki_key(&spki).unwrap().key(),
+            hex(
+                "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            )
+        );
+        assert!(spki_key(&spki[..40]).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use std::sync::Mutex;
+
+#[cfg(feature = "http")]
+use keri_core::keys::PublicKey;
+use keri_core::{error::Error, signer::KeyManager};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "http")]
+use url::Url;
+use zeroize::Zeroizing;
+
+/// Operation requested from signing service. Requests are JSON bodies of
+/// POST requests authorized with bearer token.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum SigningRequest {
+    Keys,
+    /// Signs base64 encoded `data`.
+    Sign {
+        data: String,
+    },
+    Rotate,
+}
+
+/// Response of signing service. Keys are returned with every response, so
+/// that client always knows which key signed.
+#[derive(Debug, Serialize, Deserialize)]
+struct SigningResponse {
+    public_key: String,
+    next_public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Server side of remote signing, meant to run in a separate process
+/// holding the keys. It's independent of HTTP server: application routes
+/// POST requests to `handle` and sends back returned status and body.
+pub struct SigningServer {
+    keys: Mutex<Box<dyn KeyManager + Send>>,
+    token: Zeroizing<String>,
+}
+
+impl SigningServer {
+    /// Serves `keys` to clients authorized with `token`.
+    pub fn new(keys: Box<dyn KeyManager + Send>, token: &str) -> Self {
+        Self {
+            keys: Mutex::new(keys),
+            token: Zeroizing::new(token.to_string()),
+        }
+    }
+
+    /// Handles request with given value of `Authorization` header. Returns
+    /// HTTP status code and JSON body of response.
+    pub fn handle(
+        &self,
+        authorization: Option<&str>,
+        body: &[u8],
+    ) -> (u16, Vec<u8>) {
+        let authorized = authorization
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .is_some_and(|token| {
+                constant_time_eq(token.as_bytes(), self.token.as_bytes())
+            });
+        let result = if !authorized {
+            Err((401, "Unauthorized".to_string()))
+        } else {
+            serde_json::from_slice(body)
+                .map_err(|e| (400, e.to_string()))
+                .and_then(|request| {
+                    self.process(request).map_err(|e| (500, e.to_string()))
+                })
+        };
+        match result {
+            Ok(response) => (200, serde_json::to_vec(&response).unwrap()),
+            Err((status, error)) => (
+                status,
+                serde_json::to_vec(&ErrorResponse { error }).unwrap(),
+            ),
+        }
+    }
+
+    fn process(
+        &self,
+        request: SigningRequest,
+    ) -> Result<SigningResponse, Error> {
+        let mut keys = self
+            .keys
+            .lock()
+            .map_err(|_| Error::SemanticError("Signer lock poisoned".into()))?;
+        let signature = match request {
+            SigningRequest::Keys => None,
+            SigningRequest::Sign { data } => {
+                let data = base64::decode(data).map_err(|_| {
+                    Error::SemanticError("Invalid data encoding".into())
+                })?;
+                Some(base64::encode(keys.sign(&data)?))
+            }
+            SigningRequest::Rotate => {
+                keys.rotate()?;
+                None
+            }
+        };
+        Ok(SigningResponse {
+            public_key: base64::encode(keys.public_key().key()),
+            next_public_key: base64::encode(keys.next_public_key().key()),
+            signature,
+        })
+    }
+}
+
+/// Compares secrets in time independent of position of first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(feature = "http")]
+fn remote_signer_error(e: impl std::fmt::Display) -> Error {
+    Error::SemanticError(format!("Remote signer error: {}", e))
+}
+
+#[cfg(feature = "http")]
+fn decode_key(key: &str) -> Result<PublicKey, Error> {
+    base64::decode(key)
+        .map(PublicKey::new)
+        .map_err(remote_signer_error)
+}
+
+/// Key manager whose keys are held by `SigningServer` reached over HTTP.
+/// Public keys are fetched on connection and updated on rotation.
+#[cfg(feature = "http")]
+pub struct RemoteSigner {
+    client: reqwest::blocking::Client,
+    url: Url,
+    token: Zeroizing<String>,
+    public_key: PublicKey,
+    next_public_key: PublicKey,
+}
+
+#[cfg(feature = "http")]
+impl RemoteSigner {
+    /// Connects to signing service at `url`, authorized with `token`.
+    pub fn connect(url: Url, token: &str) -> Result<Self, Error> {
+        let mut signer = Self {
+            client: reqwest::blocking::Client::default(),
+            url,
+            token: Zeroizing::new(token.to_string()),
+            public_key: PublicKey::default(),
+            next_public_key: PublicKey::default(),
+        };
+        signer.request(&SigningRequest::Keys)?;
+        Ok(signer)
+    }
+
+    /// Sends request and updates keys with ones from response.
+    fn request(
+        &mut self,
+        request: &SigningRequest,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let response = self.send(request)?;
+        self.public_key = decode_key(&response.public_key)?;
+        self.next_public_key = decode_key(&response.next_public_key)?;
+        response
+            .signature
+            .map(|signature| {
+                base64::decode(signature).map_err(remote_signer_error)
+            })
+            .transpose()
+    }
+
+    fn send(&self, request: &SigningRequest) -> Result<SigningResponse, Error> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .bearer_auth(self.token.as_str())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(request).map_err(remote_signer_error)?)
+            .send()
+            .map_err(remote_signer_error)?;
+        let status = response.status();
+        let body = response.bytes().map_err(remote_signer_error)?;
+        if !status.is_success() {
+            let error = serde_json::from_slice::<ErrorResponse>(&body)
+                .map(|response| response.error)
+                .unwrap_or(status.to_string());
+            return Err(remote_signer_error(error));
+        }
+        serde_json::from_slice(&body).map_err(remote_signer_error)
+    }
+}
+
+#[cfg(feature = "http")]
+impl KeyManager for RemoteSigner {
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        let response = self
+            .send(&SigningRequest::Sign {
+                data: base64::encode(msg),
+            })
+            .map_err(|e| {
+                log::warn!("Remote signing failed: {}", e);
+                Error::SigningError
+            })?;
+        // Keys could be rotated by other client in the meantime.
+        if decode_key(&response.public_key)? != self.public_key {
+            return Err(remote_signer_error("signing key has changed"));
+        }
+        response
+            .signature
+            .ok_or(Error::SigningError)
+            .and_then(|signature| {
+                base64::decode(signature).map_err(|_| Error::SigningError)
+            })
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    fn next_public_key(&self) -> PublicKey {
+        self.next_public_key.clone()
+    }
+
+    fn rotate(&mut self) -> Result<(), Error> {
+        self.request(&SigningRequest::Rotate).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{keys::PublicKey, signer::CryptoBox};
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_signing_server() {
+        let server =
+            SigningServer::new(Box::new(CryptoBox::new().unwrap()), "secret");
+        let request = |authorization, body: Value| {
+            let (status, response) =
+                server.handle(authorization, body.to_string().as_bytes());
+            (status, serde_json::from_slice::<Value>(&response).unwrap())
+        };
+        let key = |response: &Value, field: &str| {
+            let key = response[field].as_str().unwrap();
+            PublicKey::new(base64::decode(key).unwrap())
+        };
+
+        let (status, _) = request(None, json!({ "op": "keys" }));
+        assert_eq!(status, 401);
+        let (status, _) =
+            request(Some("Bearer wrong"), json!({ "op": "keys" }));
+        assert_eq!(status, 401);
+        let (status, _) = request(Some("Bearer secret"), json!({ "op": "x" }));
+        assert_eq!(status, 400);
+
+        let (status, keys) =
+            request(Some("Bearer secret"), json!({ "op": "keys" }));
+        assert_eq!(status, 200);
+        assert!(keys.get("signature").is_none());
+
+        let data = b"data to sign";
+        let (status, signed) = request(
+            Some("Bearer secret"),
+            json!({ "op": "sign", "data": base64::encode(data) }),
+        );
+        assert_eq!(status, 200);
+        let signature =
+            base64::decode(signed["signature"].as_str().unwrap()).unwrap();
+        assert!(key(&signed, "public_key").verify_ed(data, &signature));
+
+        let (status, rotated) =
+            request(Some("Bearer secret"), json!({ "op": "rotate" }));
+        assert_eq!(status, 200);
+        assert_eq!(key(&rotated, "public_key"), key(&keys, "next_public_key"));
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use keri_core::actor::prelude::HashFunctionCode;
+use said::{derivation::HashFunction, SelfAddressingIdentifier};
+use serde_json::{Map, Value};
+#[cfg(feature = "http")]
+use url::Url;
+
+use crate::acdc::Acdc;
+
+/// Field of JSON Schema holding its SAID.
+const SCHEMA_ID: &str = "$id";
+
+/// Fetches JSON Schema identified by its SAID.
+pub trait SchemaResolver: Send + Sync {
+    fn resolve(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<Vec<u8>, String>;
+}
+
+impl<F> SchemaResolver for F
+where
+    F: Fn(&SelfAddressingIdentifier) -> Result<Vec<u8>, String> + Send + Sync,
+{
+    fn resolve(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<Vec<u8>, String> {
+        self(said)
+    }
+}
+
+/// Reads schemas from `<said>.json` files of a local directory.
+pub struct FileSchemaResolver {
+    dir: PathBuf,
+}
+
+impl FileSchemaResolver {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl SchemaResolver for FileSchemaResolver {
+    fn resolve(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<Vec<u8>, String> {
+        std::fs::read(self.dir.join(format!("{}.json", said)))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Fetches schemas from `<base>/oobi/<said>` with blocking HTTP GET
+/// requests.
+#[cfg(feature = "http")]
+pub struct HttpSchemaResolver {
+    client: reqwest::blocking::Client,
+    base: Url,
+}
+
+#[cfg(feature = "http")]
+impl HttpSchemaResolver {
+    pub fn new(base: Url) -> Self {
+        Self {
+            client: reqwest::blocking::Client::default(),
+            base,
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl SchemaResolver for HttpSchemaResolver {
+    fn resolve(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<Vec<u8>, String> {
+        let url = self
+            .base
+            .join(&format!("oobi/{}", said))
+            .map_err(|e| e.to_string())?;
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        response
+            .bytes()
+            .map(|body| body.to_vec())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Cache of JSON Schemas keyed by their SAIDs. Schemas missing from cache
+/// are fetched with resolvers, in order they were added. Every schema is
+/// checked against its SAID before it's cached.
+#[derive(Default)]
+pub struct SchemaRegistry {
+    resolvers: Vec<Arc<dyn SchemaResolver>>,
+    cache: RwLock<HashMap<SelfAddressingIdentifier, Value>>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_resolver(mut self, resolver: Arc<dyn SchemaResolver>) -> Self {
+        self.resolvers.push(resolver);
+        self
+    }
+
+    /// Adds schema to cache. Returns its SAID.
+    pub fn add(
+        &self,
+        schema: &[u8],
+    ) -> Result<SelfAddressingIdentifier, String> {
+        let schema: Value =
+            serde_json::from_slice(schema).map_err(|e| e.to_string())?;
+        let said = verify_schema_said(&schema)?;
+        self.cache
+            .write()
+            .map_err(|_| "Schema cache lock poisoned".to_string())?
+            .insert(said.clone(), schema);
+        Ok(said)
+    }
+
+    /// Returns schema of provided SAID, fetching it if it isn't cached.
+    pub fn get(
+        &self,
+        said: &SelfAddressingIdentifier,
+    ) -> Result<Value, String> {
+        if let Some(schema) = self
+            .cache
+            .read()
+            .map_err(|_| "Schema cache lock poisoned".to_string())?
+            .get(said)
+        {
+            return Ok(schema.clone());
+        }
+        for resolver in &self.resolvers {
+            match resolver.resolve(said).and_then(|schema| self.add(&schema)) {
+                Ok(resolved) if &resolved == said => return self.get(said),
+                Ok(resolved) => log::warn!(
+                    "Resolver returned schema {} instead of {}",
+                    resolved,
+                    said
+                ),
+                Err(e) => log::debug!("Schema {} not resolved: {}", said, e),
+            }
+        }
+        Err(format!("Unknown schema {}", said))
+    }
+
+    /// Validates ACDC against its schema, which describes its attributes
+    /// block, among others.
+    pub fn validate(&self, acdc: &Acdc) -> Result<(), String> {
+        let mut schema = self.get(&acdc.schema)?;
+        // `$id` holds SAID, not URI the schema could be referenced with.
+        if let Some(schema) = schema.as_object_mut() {
+            schema.remove(SCHEMA_ID);
+        }
+        let validator =
+            jsonschema::validator_for(&schema).map_err(|e| e.to_string())?;
+        let acdc = serde_json::to_value(acdc).map_err(|e| e.to_string())?;
+        let errors = validator
+            .iter_errors(&acdc)
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "ACDC doesn't match its schema: {}",
+                errors.join(", ")
+            ))
+        }
+    }
+}
+
+/// Computes SAID of JSON Schema and sets it as its `$id`.
+pub fn saidify_schema(
+    mut schema: Map<String, Value>,
+) -> Result<Map<String, Value>, String> {
+    let dummy =
+        "#".repeat(HashFunction::from(HashFunctionCode::Blake3_256).get_len());
+    schema.insert(SCHEMA_ID.to_string(), Value::String(dummy));
+    let serialized = serde_json::to_vec(&schema).map_err(|e| e.to_string())?;
+    let said =
+        HashFunction::from(HashFunctionCode::Blake3_256).derive(&serialized);
+    schema.insert(SCHEMA_ID.to_string(), Value::String(said.to_string()));
+    Ok(schema)
+}
+
+/// Checks that `$id` of schema is SAID of its content.
+fn verify_schema_said(
+    schema: &Value,
+) -> Result<SelfAddressingIdentifier, String> {
+    let said: SelfAddressingIdentifier = schema
+        .get(SCHEMA_ID)
+        .and_then(Value::as_str)
+        .ok_or("Schema has no SAID".to_string())?
+        .parse()
+        .map_err(|_| "Invalid schema SAID".to_string())?;
+    let mut dummy = schema.clone();
+    dummy[SCHEMA_ID] = Value::String("#".repeat(said.to_string().len()));
+    let serialized = serde_json::to_vec(&dummy).map_err(|e| e.to_string())?;
+    if said.verify_binding(&serialized) {
+        Ok(said)
+    } else {
+        Err("Schema SAID doesn't match its content".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::person_schema;
+
+    #[test]
+    fn test_schema_registry() {
+        let schema = serde_json::to_vec(&person_schema()).unwrap();
+        let served = schema.clone();
+        let registry = SchemaRegistry::new()
+            .with_resolver(Arc::new(
+                |_said: &SelfAddressingIdentifier| -> Result<Vec<u8>, String> {
+                    Err("Not found".to_string())
+                },
+            ))
+            .with_resolver(Arc::new(
+                move |_said: &SelfAddressingIdentifier| Ok(served.clone()),
+            ));
+        let said: SelfAddressingIdentifier = person_schema()[SCHEMA_ID]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        // Schema is fetched by second resolver.
+        assert_eq!(
+            registry.get(&said).unwrap(),
+            serde_json::from_slice::<Value>(&schema).unwrap()
+        );
+
+        let issuer = "EC61gZ9lCKmHAS7U5ehUfEbGId5rcY0D7MirFZHDQcE2"
+            .parse()
+            .unwrap();
+        let valid = Acdc::new(
+            issuer,
+            None,
+            said.clone(),
+            json!({"name": "John Doe", "age": 42})
+                .as_object()
+                .unwrap()
+                .clone(),
+        )
+        .unwrap();
+        registry.validate(&valid).unwrap();
+
+        let malformed = Acdc::new(
+            valid.issuer.clone(),
+            None,
+            said,
+            json!({"name": "John Doe", "age": "unknown"})
+                .as_object()
+                .unwrap()
+                .clone(),
+        )
+        .unwrap();
+        assert!(registry.validate(&malformed).is_err());
+
+        // Schema not matching its SAID isn't accepted.
+        let mut tampered = person_schema();
+        tampered.insert("title".to_string(), json!("Animal"));
+        assert!(registry
+            .add(&serde_json::to_vec(&tampered).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_file_schema_resolver() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = person_schema();
+        let said: SelfAddressingIdentifier =
+            schema[SCHEMA_ID].as_str().unwrap().parse().unwrap();
+        std::fs::write(
+            dir.path().join(format!("{}.json", said)),
+            serde_json::to_vec(&schema).unwrap(),
+        )
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .with_resolver(Arc::new(FileSchemaResolver::new(dir.path())));
+        assert_eq!(registry.get(&said).unwrap(), Value::Object(schema));
+    }
+}
@@ -0,0 +1,194 @@
+use cesrox::{parse_many, payload::Payload, ParsedData};
+use keri_core::{
+    actor::prelude::EventStorage,
+    database::{EscrowCreator, EventDatabase},
+    event_message::signature::{get_signatures, Signature, SignerData},
+    prefix::{IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
+    processor::validator::EventValidator,
+};
+use teliox::database::TelEventDatabase;
+
+use crate::{Controller, Identifier};
+
+impl<D: EventDatabase> Identifier<D> {
+    /// Attaches identifier's signature of JSON `data` to it. Signature is
+    /// bound to the current establishment event, so it can be verified
+    /// with `verify_signed_data` even after keys are rotated. Returns CESR
+    /// stream of data followed by the signature.
+    pub fn sign_data(
+        &self,
+        data: &[u8],
+        sig: SelfSigningPrefix,
+    ) -> Result<Vec<u8>, String> {
+        serde_json::from_slice::<serde_json::Value>(data)
+            .map_err(|_| "Data is not a JSON".to_string())?;
+        let seal = self
+            .event_storage
+            .get_last_establishment_event_seal(&self.id)
+            .ok_or("Unknown identifier".to_string())?;
+        let signature = Signature::Transferable(
+            SignerData::EventSeal(seal),
+            vec![IndexedSignature::new_both_same(sig, 0)],
+        );
+        ParsedData {
+            payload: Payload::JSON(data.to_vec()),
+            attachments: vec![signature.into()],
+        }
+        .to_cesr()
+        .map_err(|_| "CESR format error".to_string())
+    }
+}
+
+/// Verifies data signed with `Identifier::sign_data`. Signatures are
+/// checked against keys of establishment event they are bound to, so
+/// signer's KEL has to be known up to that event. Returns signer and
+/// signed data.
+pub fn verify_signed_data<D: EventDatabase>(
+    storage: &EventStorage<D>,
+    stream: &[u8],
+) -> Result<(IdentifierPrefix, Vec<u8>), String> {
+    let (_rest, parsed) =
+        parse_many(stream).map_err(|_| "CESR format error".to_string())?;
+    let [signed] = parsed.as_slice() else {
+        return Err("Expected single signed payload".to_string());
+    };
+    let Payload::JSON(data) = &signed.payload else {
+        return Err("Unsupported payload format".to_string());
+    };
+    let signatures = signed
+        .attachments
+        .iter()
+        .map(|group| get_signatures(group.clone()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .concat();
+    let signer = match signatures.first() {
+        Some(Signature::Transferable(SignerData::EventSeal(seal), _)) => {
+            seal.prefix.clone()
+        }
+        Some(_) => {
+            return Err(
+                "Signature isn't bound to establishment event".to_string()
+            )
+        }
+        None => return Err("Missing signature".to_string()),
+    };
+    let validator = EventValidator::new(storage.events_db.clone());
+    for signature in &signatures {
+        match signature {
+            Signature::Transferable(SignerData::EventSeal(seal), _)
+                if seal.prefix == signer => {}
+            _ => return Err("Data signed by multiple signers".to_string()),
+        }
+        validator
+            .verify(data, signature)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok((signer, data.clone()))
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Verifies signed data against KELs known to controller. See
+    /// `verify_signed_data`.
+    pub fn verify_signed_data(
+        &self,
+        stream: &[u8],
+    ) -> Result<(IdentifierPrefix, Vec<u8>), String> {
+        verify_signed_data(self.kel.storage.as_ref(), stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        actor::event_generator, event_message::signed_event_message::Message,
+        prefix::BasicPrefix, signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{setup, sign};
+
+    #[test]
+    fn test_sign_data() {
+        let (_signer_root, signer_controller) = setup("signer-db");
+        let signer = Signer::new();
+        let next_signer = Signer::new();
+        let icp = signer_controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(next_signer.public_key())],
+            )
+            .unwrap();
+        let identifier = signer_controller
+            .finalize_incept(icp.as_bytes(), &sign(&signer, icp.as_bytes()))
+            .unwrap();
+
+        let data = br#"{"message":"hello"}"#;
+        let signed = identifier.sign_data(data, sign(&signer, data)).unwrap();
+        assert!(identifier
+            .sign_data(b"hello", sign(&signer, b"hello"))
+            .is_err());
+
+        // Verifier doesn't know signer's KEL yet.
+        let (_root, controller) = setup("test-db");
+        assert!(controller.verify_signed_data(&signed).is_err());
+
+        // Rotate keys, so verifier gets KEL with signing keys no longer
+        // current.
+        let state = signer_controller.get_state(&identifier.id).unwrap();
+        let rot = event_generator::rotate(
+            state,
+            vec![BasicPrefix::Ed25519(next_signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            1,
+            vec![],
+            vec![],
+            0,
+        )
+        .unwrap();
+        signer_controller
+            .finalize_rotate(
+                rot.as_bytes(),
+                vec![IndexedSignature::new_both_same(
+                    sign(&next_signer, rot.as_bytes()),
+                    0,
+                )],
+            )
+            .unwrap();
+        let kel = signer_controller
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(&identifier.id)
+            .unwrap()
+            .unwrap();
+        controller
+            .process_kel(
+                &kel.into_iter().map(Message::Notice).collect::<Vec<_>>(),
+            )
+            .unwrap();
+
+        // Data signed before rotation is still valid.
+        assert_eq!(
+            controller.verify_signed_data(&signed),
+            Ok((identifier.id.clone(), data.to_vec()))
+        );
+
+        // Data signed after rotation is bound to rotation event.
+        let rotated = identifier
+            .sign_data(data, sign(&next_signer, data))
+            .unwrap();
+        assert_eq!(
+            controller.verify_signed_data(&rotated),
+            Ok((identifier.id.clone(), data.to_vec()))
+        );
+
+        // Signature made with keys of other establishment event is
+        // rejected.
+        let forged = identifier.sign_data(data, sign(&signer, data)).unwrap();
+        assert!(controller.verify_signed_data(&forged).is_err());
+    }
+}
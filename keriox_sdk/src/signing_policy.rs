@@ -0,0 +1,235 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use keri_core::{
+    error::Error,
+    event::event_data::EventData,
+    event_message::cesr_adapter::{parse_event_type, EventType},
+    keys::PublicKey,
+    signer::KeyManager,
+};
+
+use crate::QuerySigner;
+
+/// Kind of data to be signed, recognized from its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningPurpose {
+    Inception,
+    Rotation,
+    Interaction,
+    Query,
+    Reply,
+    Exchange,
+    /// Data that isn't KERI message, e.g. ACDC or arbitrary payload.
+    Other,
+}
+
+impl SigningPurpose {
+    pub fn of(data: &[u8]) -> Self {
+        match parse_event_type(data) {
+            Ok(EventType::KeyEvent(event)) => {
+                match event.data.get_event_data() {
+                    EventData::Icp(_) | EventData::Dip(_) => Self::Inception,
+                    EventData::Rot(_) | EventData::Drt(_) => Self::Rotation,
+                    EventData::Ixn(_) => Self::Interaction,
+                }
+            }
+            Ok(EventType::Qry(_)) => Self::Query,
+            #[cfg(feature = "mailbox")]
+            Ok(EventType::MailboxQry(_)) => Self::Query,
+            Ok(EventType::Rpy(_)) => Self::Reply,
+            #[cfg(feature = "mailbox")]
+            Ok(EventType::Exn(_)) => Self::Exchange,
+            Ok(EventType::Receipt(_)) | Err(_) => Self::Other,
+        }
+    }
+}
+
+/// Decides whether data may be signed, e.g. by asking user for
+/// confirmation.
+pub trait SigningApproval: Send + Sync {
+    fn approve(&self, purpose: SigningPurpose, data: &[u8]) -> bool;
+}
+
+impl<F> SigningApproval for F
+where
+    F: Fn(SigningPurpose, &[u8]) -> bool + Send + Sync,
+{
+    fn approve(&self, purpose: SigningPurpose, data: &[u8]) -> bool {
+        self(purpose, data)
+    }
+}
+
+/// Rules applied before any signature is made: approvals required for
+/// given kinds of data and limit of signatures every key can make within a
+/// period of time.
+#[derive(Default)]
+pub struct SigningPolicy {
+    approvals: Vec<(SigningPurpose, Arc<dyn SigningApproval>)>,
+    rate_limit: Option<(usize, Duration)>,
+    history: Mutex<HashMap<PublicKey, VecDeque<Instant>>>,
+}
+
+impl SigningPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires `approval` before signing data of given purpose. All
+    /// approvals registered for the purpose have to agree.
+    pub fn with_approval(
+        mut self,
+        purpose: SigningPurpose,
+        approval: Arc<dyn SigningApproval>,
+    ) -> Self {
+        self.approvals.push((purpose, approval));
+        self
+    }
+
+    /// Allows every key to make at most `max` signatures within `period`.
+    pub fn with_rate_limit(mut self, max: usize, period: Duration) -> Self {
+        self.rate_limit = Some((max, period));
+        self
+    }
+
+    /// Checks whether `key` may sign `data`. Signature is counted towards
+    /// rate limit of the key only if it's allowed.
+    pub fn check(&self, key: &PublicKey, data: &[u8]) -> Result<(), String> {
+        let purpose = SigningPurpose::of(data);
+        if self
+            .approvals
+            .iter()
+            .filter(|(approved, _)| *approved == purpose)
+            .any(|(_, approval)| !approval.approve(purpose, data))
+        {
+            return Err(format!("Signing of {:?} not approved", purpose));
+        }
+        if let Some((max, period)) = self.rate_limit {
+            let mut history = self
+                .history
+                .lock()
+                .map_err(|_| "Signing history lock poisoned".to_string())?;
+            let signed = history.entry(key.clone()).or_default();
+            let now = Instant::now();
+            while signed
+                .front()
+                .is_some_and(|time| now.duration_since(*time) >= period)
+            {
+                signed.pop_front();
+            }
+            if signed.len() >= max {
+                return Err("Signing rate limit exceeded".to_string());
+            }
+            signed.push_back(now);
+        }
+        Ok(())
+    }
+
+    /// Wraps query signer of `key`, so that its signatures are subject to
+    /// the policy.
+    pub fn query_signer<'a>(
+        &'a self,
+        key: PublicKey,
+        signer: &'a dyn QuerySigner,
+    ) -> impl QuerySigner + 'a {
+        move |query: &[u8]| {
+            self.check(&key, query)?;
+            signer.sign(query)
+        }
+    }
+}
+
+/// Key manager whose signatures are subject to signing policy.
+pub struct PolicySigner<K: KeyManager> {
+    inner: K,
+    policy: Arc<SigningPolicy>,
+}
+
+impl<K: KeyManager> PolicySigner<K> {
+    pub fn new(inner: K, policy: Arc<SigningPolicy>) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn into_inner(self) -> K {
+        self.inner
+    }
+}
+
+impl<K: KeyManager> KeyManager for PolicySigner<K> {
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Error> {
+        self.policy
+            .check(&self.inner.public_key(), msg)
+            .map_err(Error::SemanticError)?;
+        self.inner.sign(msg)
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.inner.public_key()
+    }
+
+    fn next_public_key(&self) -> PublicKey {
+        self.inner.next_public_key()
+    }
+
+    fn rotate(&mut self) -> Result<(), Error> {
+        self.inner.rotate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        prefix::{BasicPrefix, SelfSigningPrefix},
+        signer::CryptoBox,
+    };
+
+    use super::*;
+    use crate::test_utils::setup;
+
+    #[test]
+    fn test_signing_policy() {
+        let (_root, controller) = setup("test-db");
+        let keys = CryptoBox::new().unwrap();
+        let icp = controller
+            .incept(
+                vec![BasicPrefix::Ed25519(keys.public_key())],
+                vec![BasicPrefix::Ed25519(keys.next_public_key())],
+            )
+            .unwrap();
+        assert_eq!(
+            SigningPurpose::of(icp.as_bytes()),
+            SigningPurpose::Inception
+        );
+        assert_eq!(SigningPurpose::of(b"payload"), SigningPurpose::Other);
+
+        // Only inception is refused, everything else is limited to two
+        // signatures.
+        let refuse = |purpose: SigningPurpose, _: &[u8]| {
+            purpose != SigningPurpose::Inception
+        };
+        let policy = SigningPolicy::new()
+            .with_approval(SigningPurpose::Inception, Arc::new(refuse))
+            .with_rate_limit(2, Duration::from_secs(60));
+        let signer = PolicySigner::new(keys, Arc::new(policy));
+        assert!(signer.sign(icp.as_bytes()).is_err());
+        assert!(signer.sign(b"payload").is_ok());
+        assert!(signer.sign(b"payload").is_ok());
+        assert!(signer.sign(b"payload").is_err());
+
+        let policy =
+            SigningPolicy::new().with_rate_limit(1, Duration::from_millis(10));
+        let keys = signer.into_inner();
+        let sign = |query: &[u8]| -> Result<SelfSigningPrefix, String> {
+            let signature = keys.sign(query).map_err(|e| e.to_string())?;
+            Ok(SelfSigningPrefix::Ed25519Sha512(signature))
+        };
+        let query_signer = policy.query_signer(keys.public_key(), &sign);
+        assert!(query_signer.sign(b"query").is_ok());
+        assert!(query_signer.sign(b"query").is_err());
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(query_signer.sign(b"query").is_ok());
+    }
+}
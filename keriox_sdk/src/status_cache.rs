@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use keri_core::prefix::IdentifierPrefix;
+use said::SelfAddressingIdentifier;
+use teliox::{
+    error::Error,
+    event::Event,
+    processor::notification::{
+        TelNotification, TelNotificationBus, TelNotifier,
+    },
+};
+
+use crate::CredentialStatus;
+
+/// Identifiers of registry and credential.
+type CacheKey = (IdentifierPrefix, IdentifierPrefix);
+
+/// Cache of credential statuses keyed by registry and credential SAID.
+/// Entries expire after `ttl`. Entry of credential is dropped whenever
+/// event of its TEL, e.g. revocation, is accepted, so cached status is
+/// never older than locally known TEL.
+pub struct CredentialStatusCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<CacheKey, (CredentialStatus, Instant)>>,
+}
+
+impl CredentialStatusCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns cached status of credential, unless it expired.
+    pub fn get(
+        &self,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+    ) -> Option<CredentialStatus> {
+        let key = (
+            registry.clone(),
+            IdentifierPrefix::self_addressing(said.clone()),
+        );
+        self.entries
+            .read()
+            .ok()?
+            .get(&key)
+            .filter(|(_, cached_at)| cached_at.elapsed() < self.ttl)
+            .map(|(status, _)| *status)
+    }
+
+    pub fn insert(
+        &self,
+        registry: &IdentifierPrefix,
+        said: &SelfAddressingIdentifier,
+        status: CredentialStatus,
+    ) {
+        if let Ok(mut entries) = self.entries.write() {
+            // Drop expired entries, so that cache doesn't grow unbounded.
+            entries.retain(|_, (_, cached_at)| cached_at.elapsed() < self.ttl);
+            entries.insert(
+                (
+                    registry.clone(),
+                    IdentifierPrefix::self_addressing(said.clone()),
+                ),
+                (status, Instant::now()),
+            );
+        }
+    }
+
+    fn invalidate(&self, registry: IdentifierPrefix, vc_id: IdentifierPrefix) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.remove(&(registry, vc_id));
+        }
+    }
+}
+
+impl TelNotifier for CredentialStatusCache {
+    fn notify(
+        &self,
+        notification: &TelNotification,
+        _bus: &TelNotificationBus,
+    ) -> Result<(), Error> {
+        if let TelNotification::TelEventAdded(event) = notification {
+            if let Event::Vc(vc) = &event.event {
+                self.invalidate(
+                    vc.data.data.registry_id()?,
+                    vc.data.data.prefix.clone(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{prefix::BasicPrefix, signer::Signer};
+    use said::derivation::{HashFunction, HashFunctionCode};
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    #[test]
+    fn test_status_cache() {
+        let (_root, controller) = setup("test-db");
+        let controller = controller
+            .with_status_cache(Duration::from_secs(3600))
+            .unwrap();
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let (ixn, vcp) = controller.incept_registry(&identifier).unwrap();
+        let registry = controller
+            .finalize_incept_registry(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let said =
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"vc");
+        let cache = controller.status_cache().unwrap();
+
+        // Unknown status is cached too, until issuance is accepted.
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Unknown
+        );
+        assert_eq!(
+            cache.get(&registry, &said),
+            Some(CredentialStatus::Unknown)
+        );
+        let (ixn, iss) = controller
+            .issue_credential(&identifier, &registry, b"vc")
+            .unwrap();
+        controller
+            .finalize_issue_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(cache.get(&registry, &said), None);
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Issued
+        );
+        assert_eq!(cache.get(&registry, &said), Some(CredentialStatus::Issued));
+
+        let (ixn, rev) = controller
+            .revoke_credential(&identifier, &registry, &said)
+            .unwrap();
+        controller
+            .finalize_revoke_credential(
+                &identifier,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                rev.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        assert_eq!(cache.get(&registry, &said), None);
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Revoked
+        );
+
+        // Expired entries aren't returned.
+        let expiring = CredentialStatusCache::new(Duration::ZERO);
+        expiring.insert(&registry, &said, CredentialStatus::Issued);
+        assert_eq!(expiring.get(&registry, &said), None);
+    }
+}
@@ -0,0 +1,313 @@
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Mutex, RwLock},
+    time::Duration,
+};
+
+use keri_core::{
+    event_message::cesr_stream::CesrStreamParser,
+    oobi::{LocationScheme, Scheme},
+    prefix::IdentifierPrefix,
+};
+use url::Url;
+
+use crate::transport::Transport;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Transport sending CESR streams over plain TCP, to locations of `tcp`
+/// scheme, e.g. keripy witnesses listening on TCP ports. CESR is
+/// self-framing, so response is complete once all received bytes are
+/// split into complete messages by `CesrStreamParser`.
+///
+/// Connections are kept open and reused. Broken connection is replaced
+/// with new one and the request is sent again once.
+pub struct TcpTransport {
+    locations: RwLock<HashMap<IdentifierPrefix, String>>,
+    connections: Mutex<HashMap<IdentifierPrefix, TcpStream>>,
+    timeout: Duration,
+}
+
+impl Default for TcpTransport {
+    fn default() -> Self {
+        Self {
+            locations: RwLock::new(HashMap::new()),
+            connections: Mutex::new(HashMap::new()),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl TcpTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long connecting, sending and waiting for response may take.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets location of identifier, e.g. one obtained with
+    /// `OobiResolver::get_loc_schemes`. Only TCP locations are supported.
+    pub fn add_location(
+        &self,
+        location: &LocationScheme,
+    ) -> Result<(), String> {
+        if location.scheme != Scheme::Tcp {
+            return Err(format!("Unsupported scheme of {}", location.eid));
+        }
+        let address = match (location.url.host_str(), location.url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            _ => return Err(format!("Invalid location of {}", location.eid)),
+        };
+        self.locations
+            .write()
+            .map_err(|_| "Transport lock poisoned".to_string())?
+            .insert(location.eid.clone(), address);
+        Ok(())
+    }
+
+    fn connect(
+        &self,
+        recipient: &IdentifierPrefix,
+    ) -> Result<TcpStream, String> {
+        let address = self
+            .locations
+            .read()
+            .map_err(|_| "Transport lock poisoned".to_string())?
+            .get(recipient)
+            .cloned()
+            .ok_or(format!("Unknown location of {}", recipient))?;
+        let mut last_error = format!("Can't resolve {}", address);
+        for socket in address.to_socket_addrs().map_err(|e| e.to_string())? {
+            match TcpStream::connect_timeout(&socket, self.timeout) {
+                Ok(connection) => {
+                    connection
+                        .set_write_timeout(Some(self.timeout))
+                        .map_err(|e| e.to_string())?;
+                    return Ok(connection);
+                }
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Sends stream over pooled connection, reconnecting once if it's
+    /// broken. Returns response, if `response` is expected.
+    fn exchange(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+        response: bool,
+    ) -> Result<Vec<u8>, String> {
+        // Connection is taken out of pool for the time of exchange, so
+        // that other recipients can be reached meanwhile.
+        let pooled = self
+            .connections
+            .lock()
+            .map_err(|_| "Transport lock poisoned".to_string())?
+            .remove(recipient);
+        let (connection, (body, open)) = match pooled {
+            Some(mut connection) => {
+                match self.send(&mut connection, stream, response) {
+                    Ok(result) => (connection, result),
+                    Err(e) => {
+                        log::debug!("Reconnecting to {}: {}", recipient, e);
+                        let mut connection = self.connect(recipient)?;
+                        let result = self
+                            .send(&mut connection, stream, response)
+                            .map_err(|e| e.to_string())?;
+                        (connection, result)
+                    }
+                }
+            }
+            None => {
+                let mut connection = self.connect(recipient)?;
+                let result = self
+                    .send(&mut connection, stream, response)
+                    .map_err(|e| e.to_string())?;
+                (connection, result)
+            }
+        };
+        // Connection closed by recipient is dropped.
+        if open {
+            if let Ok(mut connections) = self.connections.lock() {
+                connections.insert(recipient.clone(), connection);
+            }
+        }
+        Ok(body)
+    }
+
+    /// Writes stream and reads response. Returns it together with
+    /// information whether connection is still open.
+    fn send(
+        &self,
+        connection: &mut TcpStream,
+        stream: &[u8],
+        response: bool,
+    ) -> std::io::Result<(Vec<u8>, bool)> {
+        connection.write_all(stream)?;
+        connection.flush()?;
+        if !response {
+            return Ok((vec![], true));
+        }
+        let mut received = vec![];
+        let mut parser = CesrStreamParser::new();
+        let mut messages = 0;
+        let mut chunk = [0u8; 4096];
+        connection.set_read_timeout(Some(self.timeout))?;
+        loop {
+            let read = match connection.read(&mut chunk) {
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            received.extend_from_slice(&chunk[..read]);
+            parser.push(&chunk[..read]);
+            let end = read == 0;
+            while parser
+                .next_message(end)
+                .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?
+                .is_some()
+            {
+                messages += 1;
+            }
+            if end && messages == 0 {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            // Response ends with complete message, or connection closed.
+            if messages > 0 && (end || parser.buffered() == 0) {
+                return Ok((received, !end));
+            }
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send_event(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self.exchange(recipient, stream, false).map(|_| ())
+    }
+
+    fn send_query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.exchange(recipient, query, true)
+    }
+
+    fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+        Err(format!("Can't resolve {} over TCP", url))
+    }
+
+    fn fetch_mailbox(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String> {
+        String::from_utf8(self.exchange(recipient, query, true)?)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use keri_core::{
+        actor::event_generator, event_message::signed_event_message::Message,
+        prefix::BasicPrefix, signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{incept, setup};
+
+    #[test]
+    fn test_tcp_transport() {
+        let (_root, controller) = setup("test-db");
+        let identifier = incept(&controller, &Signer::new());
+        let kel = identifier
+            .get_own_kel()
+            .unwrap()
+            .into_iter()
+            .flat_map(|notice| Message::Notice(notice).to_cesr().unwrap())
+            .collect::<Vec<_>>();
+
+        // Witness answering queries with KEL. It drops first connection
+        // after answering, so that transport has to reconnect.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        let response = kel.clone();
+        std::thread::spawn(move || {
+            for mut connection in listener.incoming().flatten() {
+                let first = accepted.fetch_add(1, Ordering::SeqCst) == 0;
+                let mut chunk = [0u8; 4096];
+                while let Ok(read) = connection.read(&mut chunk) {
+                    if read == 0 {
+                        break;
+                    }
+                    let received = String::from_utf8_lossy(&chunk[..read]);
+                    if received.contains("\"t\":\"qry\"") {
+                        // Response arrives in parts, the first one ending
+                        // in the middle of message.
+                        let (head, tail) = response.split_at(100);
+                        connection.write_all(head).unwrap();
+                        std::thread::sleep(Duration::from_millis(50));
+                        connection.write_all(tail).unwrap();
+                        if first {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        let witness = IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(
+            Signer::new().public_key(),
+        ));
+        let transport = TcpTransport::new();
+        assert!(transport.send_event(&witness, &kel).is_err());
+        assert!(transport
+            .add_location(&LocationScheme::new(
+                witness.clone(),
+                Scheme::Http,
+                Url::parse(&format!("http://{}/", address)).unwrap(),
+            ))
+            .is_err());
+        transport
+            .add_location(&LocationScheme::new(
+                witness.clone(),
+                Scheme::Tcp,
+                Url::parse(&format!("tcp://{}/", address)).unwrap(),
+            ))
+            .unwrap();
+
+        let query =
+            event_generator::logs_query(&identifier.id, &witness, None, None)
+                .encode()
+                .unwrap();
+        transport.send_event(&witness, &kel).unwrap();
+        assert_eq!(transport.send_query(&witness, &query).unwrap(), kel);
+        assert_eq!(transport.send_query(&witness, &query).unwrap(), kel);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+        assert_eq!(transport.send_query(&witness, &query).unwrap(), kel);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use keri_core::{
+    database::{EscrowCreator, EventDatabase},
+    prefix::IdentifierPrefix,
+};
+use teliox::{
+    database::TelEventDatabase,
+    error::Error,
+    event::{
+        manager_event::ManagerEventType, verifiable_event::VerifiableEvent,
+        Event,
+    },
+    processor::{
+        notification::{
+            TelNotification, TelNotificationBus, TelNotificationKind,
+            TelNotifier,
+        },
+        storage::TelEventStorage,
+    },
+};
+
+use crate::{oobi::KelResolver, Controller};
+
+/// Observes `MissingIssuer` notifications, sent for TEL events anchored in
+/// unknown KEL events, and requests issuer's KEL from resolver. Once the
+/// anchoring event is processed, missing issuer escrow accepts the
+/// escrowed TEL event.
+pub struct MissingAnchorObserver<
+    D: EventDatabase + EscrowCreator + 'static,
+    T: TelEventDatabase,
+> {
+    tel_storage: Arc<TelEventStorage<T>>,
+    resolver: Arc<KelResolver<D>>,
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > MissingAnchorObserver<D, T>
+{
+    pub fn new(
+        tel_storage: Arc<TelEventStorage<T>>,
+        resolver: Arc<KelResolver<D>>,
+    ) -> Self {
+        Self {
+            tel_storage,
+            resolver,
+        }
+    }
+
+    /// Returns identifier whose KEL anchors TEL event.
+    fn issuer(
+        &self,
+        event: &VerifiableEvent,
+    ) -> Result<IdentifierPrefix, String> {
+        let registry = match &event.event {
+            Event::Management(man) => match &man.data.event_type {
+                ManagerEventType::Vcp(vcp) => return Ok(vcp.issuer_id.clone()),
+                ManagerEventType::Vrt(_) => man.data.prefix.clone(),
+            },
+            Event::Vc(vc) => {
+                vc.data.data.registry_id().map_err(|e| e.to_string())?
+            }
+        };
+        let state = self
+            .tel_storage
+            .compute_management_tel_state(&registry)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Unknown registry {}", registry))?;
+        Ok(state.issuer)
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > TelNotifier for MissingAnchorObserver<D, T>
+{
+    fn notify(
+        &self,
+        notification: &TelNotification,
+        _bus: &TelNotificationBus,
+    ) -> Result<(), Error> {
+        if let TelNotification::MissingIssuer(event) = notification {
+            match self.issuer(event) {
+                Ok(issuer) => self.resolver.request(&issuer),
+                Err(e) => log::warn!("Unknown issuer of TEL event: {}", e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase + Send + Sync + 'static,
+    > Controller<D, T>
+{
+    /// Registers observer that requests issuer's KEL from `resolver`
+    /// whenever TEL event is anchored in KEL event that isn't known yet.
+    /// Requested KELs are fetched with `KelResolver::resolve_pending`. TEL
+    /// events wait for their anchors only if controller was created with
+    /// `Controller::with_tel_escrow`.
+    pub fn register_issuer_resolver(
+        &self,
+        resolver: Arc<KelResolver<D>>,
+    ) -> Result<(), String> {
+        let observer = Arc::new(MissingAnchorObserver::new(
+            self.tel.processor.tel_reference.clone(),
+            resolver,
+        ));
+        self.tel
+            .processor
+            .register_observer(
+                observer,
+                vec![TelNotificationKind::MissingIssuer],
+            )
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use keri_core::{
+        event_message::signed_event_message::Message, prefix::BasicPrefix,
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::{
+        test_utils::{
+            incept, setup_with_tel_escrow, sign, watcher_kel_resolver,
+        },
+        CredentialStatus,
+    };
+
+    #[test]
+    fn test_missing_anchor_resolution() {
+        let (_issuer_root, issuer_controller) =
+            setup_with_tel_escrow("issuer-db");
+        let publisher = |_witness: &BasicPrefix,
+                         _stream: &[u8]|
+         -> Result<(), String> { Ok(()) };
+        let signer = Signer::new();
+        let issuer = incept(&issuer_controller, &signer);
+        let (ixn, vcp) = issuer_controller.incept_registry(&issuer).unwrap();
+        let registry = issuer_controller
+            .finalize_incept_registry(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                vcp.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+        let (ixn, iss) = issuer_controller
+            .issue_credential(&issuer, &registry, b"credential")
+            .unwrap();
+        let said = issuer_controller
+            .finalize_issue_credential(
+                &issuer,
+                ixn.as_bytes(),
+                sign(&signer, ixn.as_bytes()),
+                iss.as_bytes(),
+                &publisher,
+            )
+            .unwrap();
+
+        let issuer_tel = &issuer_controller.tel.processor.tel_reference;
+        let mut tel = issuer_tel
+            .get_management_events(&registry)
+            .unwrap()
+            .unwrap();
+        let vc_tel: Vec<u8> = issuer_tel
+            .get_events(&IdentifierPrefix::self_addressing(said.clone()))
+            .unwrap()
+            .into_iter()
+            .flat_map(|event| event.serialize().unwrap())
+            .collect();
+        tel.extend(&vc_tel);
+        let issuer_kel: Vec<Message> = issuer_controller
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(&issuer.id)
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(Message::Notice)
+            .collect();
+
+        // TEL waits in escrow until issuer's KEL is processed. KEL without
+        // event anchoring the issuance accepts only the registry.
+        let (_root, controller) = setup_with_tel_escrow("verifier-db");
+        controller.process_tel(&tel).unwrap();
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Unknown
+        );
+        controller.process_kel(&issuer_kel[..2]).unwrap();
+        assert!(controller
+            .tel
+            .get_management_tel_state(&registry)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Unknown
+        );
+        controller.process_kel(&issuer_kel[2..]).unwrap();
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Issued
+        );
+
+        // Registered resolver fetches missing KEL from watcher.
+        let (_root, controller) =
+            setup_with_tel_escrow("resolving-verifier-db");
+        let to_cesr = |messages: &[Message]| -> Vec<u8> {
+            messages
+                .iter()
+                .flat_map(|message| message.to_cesr().unwrap())
+                .collect()
+        };
+        let kel = Arc::new(Mutex::new(to_cesr(&issuer_kel[..2])));
+        let resolver =
+            watcher_kel_resolver(&controller, &issuer.id, kel.clone());
+        controller
+            .register_issuer_resolver(resolver.clone())
+            .unwrap();
+
+        // Issuer of credential from unknown registry can't be found, so
+        // nothing is requested.
+        controller.process_tel(&vc_tel).unwrap();
+        assert!(resolver.pending().is_empty());
+
+        controller.process_tel(&tel).unwrap();
+        assert_eq!(resolver.pending(), vec![issuer.id.clone()]);
+
+        // Watcher doesn't know the anchoring event yet.
+        resolver.resolve_pending();
+        assert_eq!(controller.get_state(&issuer.id).unwrap().sn, 1);
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Unknown
+        );
+
+        *kel.lock().unwrap() = to_cesr(&issuer_kel);
+        resolver.request(&issuer.id);
+        resolver.resolve_pending();
+        assert_eq!(controller.get_state(&issuer.id).unwrap().sn, 2);
+        assert_eq!(
+            controller.credential_status(&registry, &said).unwrap(),
+            CredentialStatus::Issued
+        );
+    }
+}
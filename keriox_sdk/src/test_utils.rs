@@ -0,0 +1,136 @@
+use std::sync::Arc;
+#[cfg(feature = "oobi-manager")]
+use std::sync::Mutex;
+
+#[cfg(feature = "oobi-manager")]
+use keri_core::{
+    actor::event_generator, oobi::Scheme, oobi_manager::OobiManager,
+    prefix::IdentifierPrefix, query::reply_event::SignedReply,
+};
+use keri_core::{
+    database::redb::RedbDatabase,
+    prefix::{BasicPrefix, SelfSigningPrefix},
+    signer::Signer,
+};
+use serde_json::{json, Map, Value};
+#[cfg(feature = "oobi-manager")]
+use teliox::database::EscrowDatabase;
+use teliox::database::{redb::RedbTelDatabase, TelEventDatabase};
+use tempfile::{Builder, TempDir};
+#[cfg(feature = "oobi-manager")]
+use url::Url;
+
+#[cfg(feature = "oobi-manager")]
+use crate::KelResolver;
+use crate::{saidify_schema, Controller, Identifier};
+
+pub(crate) type TestController = Controller<RedbDatabase, RedbTelDatabase>;
+
+/// Creates controller backed by databases in new temporary directory.
+/// The directory is removed when returned `TempDir` is dropped.
+pub(crate) fn setup(name: &str) -> (TempDir, TestController) {
+    let root = Builder::new().prefix(name).tempdir().unwrap();
+    let event_database =
+        Arc::new(RedbDatabase::new(&root.path().join("events")).unwrap());
+    let tel_database =
+        Arc::new(RedbTelDatabase::new(root.path().join("tel")).unwrap());
+    (root, Controller::new(event_database, tel_database))
+}
+
+/// Same as `setup`, but TEL events wait in escrow for missing anchors.
+#[cfg(feature = "oobi-manager")]
+pub(crate) fn setup_with_tel_escrow(name: &str) -> (TempDir, TestController) {
+    let root = Builder::new().prefix(name).tempdir().unwrap();
+    let event_database =
+        Arc::new(RedbDatabase::new(&root.path().join("events")).unwrap());
+    let tel_database =
+        Arc::new(RedbTelDatabase::new(root.path().join("tel")).unwrap());
+    let tel_escrow =
+        EscrowDatabase::new(&root.path().join("tel_escrow")).unwrap();
+    let controller =
+        Controller::with_tel_escrow(event_database, tel_database, tel_escrow)
+            .unwrap();
+    (root, controller)
+}
+
+pub(crate) fn sign(signer: &Signer, data: &[u8]) -> SelfSigningPrefix {
+    SelfSigningPrefix::Ed25519Sha512(signer.sign(data).unwrap())
+}
+
+/// Incepts single key identifier controlled by `signer`.
+pub(crate) fn incept(
+    controller: &TestController,
+    signer: &Signer,
+) -> Identifier<RedbDatabase> {
+    let icp = controller
+        .incept(
+            vec![BasicPrefix::Ed25519(signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+        )
+        .unwrap();
+    controller
+        .finalize_incept(icp.as_bytes(), &sign(signer, icp.as_bytes()))
+        .unwrap()
+}
+
+/// Creates KEL resolver of `controller` with single watcher, whose location
+/// is known. The watcher serves current content of `kel` as KEL of `id`.
+#[cfg(feature = "oobi-manager")]
+pub(crate) fn watcher_kel_resolver(
+    controller: &TestController,
+    id: &IdentifierPrefix,
+    kel: Arc<Mutex<Vec<u8>>>,
+) -> Arc<KelResolver<RedbDatabase>> {
+    let watcher_signer = Signer::new();
+    let watcher_key = BasicPrefix::Ed25519NT(watcher_signer.public_key());
+    let watcher_id = IdentifierPrefix::Basic(watcher_key.clone());
+    let watcher_url = Url::parse("http://127.0.0.1:3236/").unwrap();
+    let oobi_url = watcher_url.join(&format!("oobi/{}", id)).unwrap();
+    let fetcher = move |url: &Url| -> Result<Vec<u8>, String> {
+        if url == &oobi_url {
+            Ok(kel.lock().unwrap().clone())
+        } else {
+            Err(format!("{} is unreachable", url))
+        }
+    };
+    let oobi_manager =
+        Arc::new(OobiManager::new(controller.kel.storage.events_db.clone()));
+    let rpy = event_generator::generate_loc_scheme(
+        &watcher_id,
+        Scheme::Http,
+        watcher_url,
+    );
+    let sig = sign(&watcher_signer, &rpy.encode().unwrap());
+    let oobi_resolver = controller
+        .kel
+        .oobi_resolver(oobi_manager, Arc::new(fetcher));
+    oobi_resolver
+        .save_reply(SignedReply::new_nontrans(rpy, watcher_key, sig))
+        .unwrap();
+    Arc::new(KelResolver::new(oobi_resolver, vec![watcher_id]))
+}
+
+/// Schema of ACDC with `name` and `age` attributes, with its SAID set.
+pub(crate) fn person_schema() -> Map<String, Value> {
+    saidify_schema(
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Person",
+            "type": "object",
+            "properties": {
+                "a": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "age": {"type": "integer"}
+                    },
+                    "required": ["name", "age"]
+                }
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+    )
+    .unwrap()
+}
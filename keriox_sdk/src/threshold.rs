@@ -0,0 +1,245 @@
+use keri_core::{
+    database::{EscrowCreator, EventDatabase},
+    event::{event_data::EventData, sections::key_config::KeyConfig, KeyEvent},
+    event_message::{
+        msg::KeriEvent,
+        signed_event_message::{Notice, SignedEventMessage},
+    },
+    prefix::{BasicPrefix, IndexedSignature, SelfSigningPrefix},
+    processor::Processor,
+};
+use teliox::database::TelEventDatabase;
+
+use crate::{controller::parse_key_event, Controller};
+
+/// Holder of some of identifier's keys, e.g. remote device or operator
+/// reached through exchange messages.
+pub trait KeyHolder: Send + Sync {
+    /// Returns signature of `event` made with `key`, or `None` if key isn't
+    /// held or holder declines to sign.
+    fn sign(
+        &self,
+        key: &BasicPrefix,
+        event: &[u8],
+    ) -> Result<Option<SelfSigningPrefix>, String>;
+}
+
+impl<F> KeyHolder for F
+where
+    F: Fn(&BasicPrefix, &[u8]) -> Result<Option<SelfSigningPrefix>, String>
+        + Send
+        + Sync,
+{
+    fn sign(
+        &self,
+        key: &BasicPrefix,
+        event: &[u8],
+    ) -> Result<Option<SelfSigningPrefix>, String> {
+        self(key, event)
+    }
+}
+
+/// Signing session of single identifier's event whose keys are held apart,
+/// e.g. by different devices. Signatures are collected until threshold of
+/// signing keys is met, so no process needs all of them.
+pub struct ThresholdSigning {
+    event: KeriEvent<KeyEvent>,
+    encoded: Vec<u8>,
+    key_config: KeyConfig,
+    signatures: Vec<IndexedSignature>,
+}
+
+impl ThresholdSigning {
+    /// Serialized event to be signed.
+    pub fn event(&self) -> &[u8] {
+        &self.encoded
+    }
+
+    /// Keys whose signatures are collected, in order of their indexes.
+    pub fn keys(&self) -> &[BasicPrefix] {
+        &self.key_config.public_keys
+    }
+
+    pub fn signatures(&self) -> &[IndexedSignature] {
+        &self.signatures
+    }
+
+    pub fn is_complete(&self) -> bool {
+        let indexes = self
+            .signatures
+            .iter()
+            .map(|sig| sig.index.current() as usize)
+            .collect::<Vec<_>>();
+        self.key_config.threshold.is_satisfied(&indexes)
+    }
+
+    /// Adds signature of key `index`, replacing previous one of the same
+    /// key. Returns whether threshold is met.
+    pub fn add_signature(
+        &mut self,
+        index: usize,
+        sig: SelfSigningPrefix,
+    ) -> Result<bool, String> {
+        let key = self
+            .keys()
+            .get(index)
+            .ok_or(format!("No key of index {}", index))?;
+        if !key.verify(&self.encoded, &sig).map_err(|e| e.to_string())? {
+            return Err(format!("Wrong signature of key {}", index));
+        }
+        self.signatures
+            .retain(|signature| signature.index.current() as usize != index);
+        self.signatures
+            .push(IndexedSignature::new_both_same(sig, index as u16));
+        Ok(self.is_complete())
+    }
+
+    /// Asks `holder` for signatures of keys not signed yet, until threshold
+    /// is met. Holder failing for some key doesn't stop the session, as
+    /// other keys may still satisfy the threshold. Returns whether
+    /// threshold is met.
+    pub fn circulate(
+        &mut self,
+        holder: &dyn KeyHolder,
+    ) -> Result<bool, String> {
+        for index in 0..self.keys().len() {
+            if self.is_complete() {
+                break;
+            }
+            if self
+                .signatures
+                .iter()
+                .any(|sig| sig.index.current() as usize == index)
+            {
+                continue;
+            }
+            let key = self.keys()[index].clone();
+            match holder.sign(&key, &self.encoded) {
+                Ok(Some(sig)) => {
+                    self.add_signature(index, sig)?;
+                }
+                Ok(None) => (),
+                Err(e) => log::warn!("Holder of key {} failed: {}", index, e),
+            }
+        }
+        Ok(self.is_complete())
+    }
+}
+
+impl<
+        D: EventDatabase + EscrowCreator + Send + Sync + 'static,
+        T: TelEventDatabase,
+    > Controller<D, T>
+{
+    /// Starts collecting signatures of identifier's key event. Keys of
+    /// establishment event are the ones it sets, interaction is signed with
+    /// current keys of the identifier.
+    pub fn threshold_signing(
+        &self,
+        event: &[u8],
+    ) -> Result<ThresholdSigning, String> {
+        let event = parse_key_event(event)?;
+        let key_config = match event.data.get_event_data() {
+            EventData::Icp(icp) => icp.key_config,
+            EventData::Dip(dip) => dip.inception_data.key_config,
+            EventData::Rot(rot) | EventData::Drt(rot) => rot.key_config,
+            EventData::Ixn(_) => {
+                self.kel
+                    .storage
+                    .get_state(&event.data.get_prefix())
+                    .ok_or("Unknown identifier".to_string())?
+                    .current
+            }
+        };
+        let encoded = event.encode().map_err(|e| e.to_string())?;
+        Ok(ThresholdSigning {
+            event,
+            encoded,
+            key_config,
+            signatures: vec![],
+        })
+    }
+
+    /// Processes event signed with signatures collected by `signing`, once
+    /// they meet the threshold. Returns signed event, which should be
+    /// published to identifier's witnesses.
+    pub fn finalize_threshold_signing(
+        &self,
+        signing: ThresholdSigning,
+    ) -> Result<SignedEventMessage, String> {
+        if !signing.is_complete() {
+            return Err("Signature threshold not met".to_string());
+        }
+        let signed = signing.event.sign(signing.signatures, None, None);
+        self.kel
+            .processor
+            .process_notice(&Notice::Event(signed.clone()))
+            .map_err(|e| e.to_string())?;
+        Ok(signed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::{
+        event::sections::threshold::SignatureThreshold,
+        event_message::key_event_builder::InceptionBuilder, signer::Signer,
+    };
+
+    use super::*;
+    use crate::{
+        test_utils::{setup, sign},
+        Identifier,
+    };
+
+    #[test]
+    fn test_threshold_signing() {
+        let (_root, controller) = setup("test-db");
+        let signers = (0..3).map(|_| Signer::new()).collect::<Vec<_>>();
+        let keys = signers
+            .iter()
+            .map(|signer| BasicPrefix::Ed25519(signer.public_key()))
+            .collect::<Vec<_>>();
+        let next_keys = (0..3)
+            .map(|_| BasicPrefix::Ed25519(Signer::new().public_key()))
+            .collect::<Vec<_>>();
+        // Holder of the second key is offline.
+        let holder = |key: &BasicPrefix,
+                      event: &[u8]|
+         -> Result<Option<SelfSigningPrefix>, String> {
+            let index = keys.iter().position(|k| k == key).unwrap();
+            Ok((index != 1).then(|| sign(&signers[index], event)))
+        };
+
+        let icp = InceptionBuilder::new(keys.clone())
+            .with_threshold(SignatureThreshold::Simple(2))
+            .with_next_keys(next_keys)
+            .with_next_threshold(SignatureThreshold::Simple(2))
+            .build()
+            .unwrap()
+            .encode()
+            .unwrap();
+        let mut signing = controller.threshold_signing(&icp).unwrap();
+        assert!(signing.add_signature(1, sign(&signers[0], &icp)).is_err());
+        assert!(!signing.add_signature(0, sign(&signers[0], &icp)).unwrap());
+        assert!(controller
+            .finalize_threshold_signing(
+                controller.threshold_signing(&icp).unwrap()
+            )
+            .is_err());
+        assert!(signing.circulate(&holder).unwrap());
+        assert_eq!(signing.signatures().len(), 2);
+        let icp = controller.finalize_threshold_signing(signing).unwrap();
+        let id = icp.event_message.data.get_prefix();
+        let state = || controller.kel.storage.get_state(&id).unwrap();
+        assert_eq!(state().sn, 0);
+
+        let identifier =
+            Identifier::new(id.clone(), controller.kel.storage.clone());
+        let ixn = identifier.anchor(&[]).unwrap();
+        let mut signing = controller.threshold_signing(ixn.as_bytes()).unwrap();
+        assert!(signing.circulate(&holder).unwrap());
+        controller.finalize_threshold_signing(signing).unwrap();
+        assert_eq!(state().sn, 1);
+    }
+}
@@ -0,0 +1,339 @@
+use std::sync::Arc;
+#[cfg(feature = "http")]
+use std::{collections::HashMap, sync::RwLock};
+
+#[cfg(feature = "http")]
+use keri_core::oobi::{LocationScheme, Scheme};
+use keri_core::prefix::{BasicPrefix, CesrPrimitive, IdentifierPrefix};
+use url::Url;
+
+#[cfg(feature = "mailbox")]
+use crate::mailbox::MailboxTransport;
+use crate::{
+    oobi::OobiFetcher, query::QueryTransport, receipts::ReceiptFetcher,
+    witness::WitnessPublisher,
+};
+
+/// Network interaction of controller. Implementations are expected to
+/// resolve locations of recipients, e.g. from their OOBIs.
+pub trait Transport: Send + Sync {
+    /// Sends CESR stream of events, receipts or replies to `recipient`.
+    fn send_event(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String>;
+
+    /// Sends signed query to `recipient` and returns its response.
+    fn send_query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String>;
+
+    /// Dereferences OOBI URL and returns the served CESR stream.
+    fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String>;
+
+    /// Sends signed mailbox query to `recipient` and returns its response.
+    fn fetch_mailbox(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String>;
+
+    /// Returns CESR stream with receipts of `id`'s event at `sn` made by
+    /// `witness`, e.g. KEL of `id` served by the witness. Transports that
+    /// can't reach witness's receipts return error.
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        _sn: u64,
+    ) -> Result<Vec<u8>, String> {
+        Err(format!(
+            "Can't fetch receipts of {} from {}",
+            id,
+            witness.to_str()
+        ))
+    }
+}
+
+/// Makes `Transport` usable wherever SDK operations take narrower
+/// transport traits, e.g. `WitnessPublisher`, `ReceiptFetcher` or
+/// `OobiFetcher`.
+#[derive(Clone)]
+pub struct TransportAdapter(pub Arc<dyn Transport>);
+
+impl WitnessPublisher for TransportAdapter {
+    fn publish(
+        &self,
+        witness: &BasicPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self.0
+            .send_event(&IdentifierPrefix::Basic(witness.clone()), stream)
+    }
+}
+
+impl ReceiptFetcher for TransportAdapter {
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        sn: u64,
+    ) -> Result<Vec<u8>, String> {
+        self.0.fetch_receipts(witness, id, sn)
+    }
+}
+
+impl QueryTransport for TransportAdapter {
+    fn query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.0.send_query(recipient, query)
+    }
+}
+
+impl OobiFetcher for TransportAdapter {
+    fn fetch(&self, url: &Url) -> Result<Vec<u8>, String> {
+        self.0.resolve_oobi(url)
+    }
+}
+
+#[cfg(feature = "mailbox")]
+impl MailboxTransport for TransportAdapter {
+    fn query(
+        &self,
+        witness: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String> {
+        self.0.fetch_mailbox(witness, query)
+    }
+}
+
+/// Transport speaking KERI HTTP endpoints of witnesses and watchers:
+/// events are posted to `/process`, queries to `/query`. Locations of
+/// recipients have to be added with `add_location`.
+#[cfg(feature = "http")]
+#[derive(Default)]
+pub struct HttpTransport {
+    client: reqwest::blocking::Client,
+    locations: RwLock<HashMap<IdentifierPrefix, Url>>,
+}
+
+#[cfg(feature = "http")]
+impl HttpTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates transport whose requests fail after `timeout`.
+    pub fn with_timeout(timeout: std::time::Duration) -> Result<Self, String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            client,
+            locations: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Sets location of identifier, e.g. one obtained with
+    /// `OobiResolver::get_loc_schemes`. Only HTTP locations are supported.
+    pub fn add_location(
+        &self,
+        location: &LocationScheme,
+    ) -> Result<(), String> {
+        if location.scheme != Scheme::Http {
+            return Err(format!("Unsupported scheme of {}", location.eid));
+        }
+        self.locations
+            .write()
+            .map_err(|_| "Transport lock poisoned".to_string())?
+            .insert(location.eid.clone(), location.url.clone());
+        Ok(())
+    }
+
+    fn url(
+        &self,
+        recipient: &IdentifierPrefix,
+        endpoint: &str,
+    ) -> Result<Url, String> {
+        self.locations
+            .read()
+            .map_err(|_| "Transport lock poisoned".to_string())?
+            .get(recipient)
+            .ok_or(format!("Unknown location of {}", recipient))?
+            .join(endpoint)
+            .map_err(|e| e.to_string())
+    }
+
+    fn post(
+        &self,
+        recipient: &IdentifierPrefix,
+        endpoint: &str,
+        body: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let url = self.url(recipient, endpoint)?;
+        self.client
+            .post(url)
+            .body(body.to_vec())
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+            .map(|body| body.to_vec())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "http")]
+impl Transport for HttpTransport {
+    fn send_event(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self.post(recipient, "process", stream).map(|_| ())
+    }
+
+    fn send_query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.post(recipient, "query", query)
+    }
+
+    fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+        self.client
+            .get(url.clone())
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+            .map(|body| body.to_vec())
+            .map_err(|e| e.to_string())
+    }
+
+    fn fetch_mailbox(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<String, String> {
+        String::from_utf8(self.post(recipient, "query", query)?)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Witness serves KEL of `id` with receipts of all its events.
+    fn fetch_receipts(
+        &self,
+        witness: &BasicPrefix,
+        id: &IdentifierPrefix,
+        _sn: u64,
+    ) -> Result<Vec<u8>, String> {
+        let url = self.url(
+            &IdentifierPrefix::Basic(witness.clone()),
+            &format!("kel/{}", id),
+        )?;
+        self.resolve_oobi(&url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use keri_core::{
+        event_message::signed_event_message::{Message, Notice},
+        prefix::SelfSigningPrefix,
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::test_utils::{incept, setup, sign};
+
+    /// Records sent messages and answers queries with nothing.
+    #[derive(Default)]
+    struct MockTransport {
+        sent: Mutex<Vec<(IdentifierPrefix, Vec<u8>)>>,
+    }
+
+    impl Transport for MockTransport {
+        fn send_event(
+            &self,
+            recipient: &IdentifierPrefix,
+            stream: &[u8],
+        ) -> Result<(), String> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((recipient.clone(), stream.to_vec()));
+            Ok(())
+        }
+
+        fn send_query(
+            &self,
+            recipient: &IdentifierPrefix,
+            query: &[u8],
+        ) -> Result<Vec<u8>, String> {
+            self.send_event(recipient, query)?;
+            Ok(vec![])
+        }
+
+        fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+            Err(format!("{} is unreachable", url))
+        }
+
+        fn fetch_mailbox(
+            &self,
+            recipient: &IdentifierPrefix,
+            query: &[u8],
+        ) -> Result<String, String> {
+            self.send_event(recipient, query)?;
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_transport_adapter() {
+        let (_root, controller) = setup("test-db");
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let witness = BasicPrefix::Ed25519NT(Signer::new().public_key());
+        let transport = Arc::new(MockTransport::default());
+        let adapter = TransportAdapter(transport.clone());
+
+        let icp = match identifier.get_own_kel().unwrap().remove(0) {
+            Notice::Event(icp) => icp,
+            _ => unreachable!(),
+        };
+        let stream = Message::Notice(Notice::Event(icp)).to_cesr().unwrap();
+        adapter.publish(&witness, &stream).unwrap();
+
+        let query_signer = |query: &[u8]| -> Result<SelfSigningPrefix, String> {
+            Ok(sign(&signer, query))
+        };
+        let received = identifier
+            .query_kel(
+                &identifier.id,
+                0,
+                &IdentifierPrefix::Basic(witness.clone()),
+                10,
+                &query_signer,
+                &adapter,
+            )
+            .unwrap();
+        assert_eq!(received, 0);
+
+        let sent = transport.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|(recipient, _)| recipient
+            == &IdentifierPrefix::Basic(witness.clone())));
+        assert_eq!(sent[0].1, stream);
+        assert!(adapter
+            .fetch(&Url::parse("http://127.0.0.1:3232/oobi").unwrap())
+            .is_err());
+    }
+}
@@ -0,0 +1,352 @@
+use std::{
+    cell::RefCell, collections::HashMap, future::Future, pin::Pin, rc::Rc,
+    sync::Arc,
+};
+
+use gloo_net::http::{Request, Response};
+use js_sys::Uint8Array;
+use keri_core::{
+    actor::{event_generator, parse_event_stream},
+    database::memory::MemoryDatabase,
+    event_message::{
+        cesr_adapter::{parse_event_type, EventType},
+        signed_event_message::{Message, Notice},
+    },
+    prefix::{BasicPrefix, IdentifierPrefix, IndexedSignature},
+    processor::Processor,
+};
+use url::Url;
+
+use crate::{controller::KeriRuntime, oobi::oobi_identifier};
+
+/// Future of browser APIs, which can't be sent between threads.
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Async key-value storage injected by the page, e.g. backed by
+/// IndexedDB.
+pub trait AsyncStore {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> LocalBoxFuture<'a, Result<Option<Vec<u8>>, String>>;
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        value: Vec<u8>,
+    ) -> LocalBoxFuture<'a, Result<(), String>>;
+
+    /// Returns keys starting with `prefix`.
+    fn keys<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> LocalBoxFuture<'a, Result<Vec<String>, String>>;
+}
+
+/// Transport using `fetch` of the browser. Endpoints are the ones of
+/// `HttpTransport`. Locations are kept in `RefCell`, as browser runs it on
+/// single thread.
+#[derive(Default)]
+pub struct FetchTransport {
+    locations: RefCell<HashMap<IdentifierPrefix, Url>>,
+}
+
+impl FetchTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets HTTP location of identifier.
+    pub fn add_location(&self, id: IdentifierPrefix, url: Url) {
+        self.locations.borrow_mut().insert(id, url);
+    }
+
+    pub async fn send_event(
+        &self,
+        recipient: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self.post(recipient, "process", stream).await.map(|_| ())
+    }
+
+    pub async fn send_query(
+        &self,
+        recipient: &IdentifierPrefix,
+        query: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.post(recipient, "query", query).await
+    }
+
+    pub async fn resolve_oobi(&self, url: &Url) -> Result<Vec<u8>, String> {
+        let response = Request::get(url.as_str())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        body(response).await
+    }
+
+    async fn post(
+        &self,
+        recipient: &IdentifierPrefix,
+        endpoint: &str,
+        body_bytes: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let url = self
+            .locations
+            .borrow()
+            .get(recipient)
+            .ok_or(format!("Unknown location of {}", recipient))?
+            .join(endpoint)
+            .map_err(|e| e.to_string())?;
+        let response = Request::post(url.as_str())
+            .body(Uint8Array::from(body_bytes))
+            .map_err(|e| e.to_string())?
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        body(response).await
+    }
+}
+
+async fn body(response: Response) -> Result<Vec<u8>, String> {
+    if !response.ok() {
+        return Err(format!(
+            "Request to {} failed with status {}",
+            response.url(),
+            response.status()
+        ));
+    }
+    response.binary().await.map_err(|e| e.to_string())
+}
+
+/// Key prefix of KELs kept in `AsyncStore`.
+const KEL_KEY: &str = "kel/";
+
+/// Controller running in browser. Events are processed in memory, KELs
+/// are saved in injected `AsyncStore` once processed and loaded from it
+/// on start. Network is reached with `FetchTransport`.
+pub struct BrowserController {
+    pub kel: KeriRuntime<MemoryDatabase>,
+    pub transport: FetchTransport,
+    store: Rc<dyn AsyncStore>,
+}
+
+impl BrowserController {
+    /// Creates controller with KELs saved in `store`.
+    pub async fn load(store: Rc<dyn AsyncStore>) -> Result<Self, String> {
+        let controller = Self {
+            kel: KeriRuntime::new(Arc::new(MemoryDatabase::new())),
+            transport: FetchTransport::new(),
+            store,
+        };
+        for key in controller.store.keys(KEL_KEY).await? {
+            if let Some(stream) = controller.store.get(&key).await? {
+                controller.process(&stream)?;
+            }
+        }
+        Ok(controller)
+    }
+
+    pub fn incept(
+        &self,
+        public_keys: Vec<BasicPrefix>,
+        next_pub_keys: Vec<BasicPrefix>,
+        witnesses: Vec<BasicPrefix>,
+        witness_threshold: u64,
+    ) -> Result<String, String> {
+        event_generator::incept(
+            public_keys,
+            next_pub_keys,
+            witnesses,
+            witness_threshold,
+            None,
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// Processes key event signed with `signatures` and saves KEL of its
+    /// identifier. Returns the identifier.
+    pub async fn finalize_event(
+        &self,
+        event: &[u8],
+        signatures: Vec<IndexedSignature>,
+    ) -> Result<IdentifierPrefix, String> {
+        let EventType::KeyEvent(event) =
+            parse_event_type(event).map_err(|e| e.to_string())?
+        else {
+            return Err("Event is not a key event".to_string());
+        };
+        let id = event.data.get_prefix();
+        self.kel
+            .processor
+            .process_notice(&Notice::Event(event.sign(signatures, None, None)))
+            .map_err(|e| e.to_string())?;
+        self.save(&id).await?;
+        Ok(id)
+    }
+
+    /// Sends KEL of `id` to its witnesses.
+    pub async fn publish(&self, id: &IdentifierPrefix) -> Result<(), String> {
+        let state = self
+            .kel
+            .storage
+            .get_state(id)
+            .ok_or("Unknown identifier".to_string())?;
+        let kel = self.kel_stream(id)?;
+        for witness in state.witness_config.witnesses {
+            self.transport
+                .send_event(&IdentifierPrefix::Basic(witness), &kel)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Fetches and processes KEL served at OOBI URL of form
+    /// `{url}/oobi/{aid}`, and saves it. Returns identifier the OOBI is
+    /// about.
+    pub async fn resolve_oobi(
+        &self,
+        url: &str,
+    ) -> Result<IdentifierPrefix, String> {
+        let url = Url::parse(url).map_err(|e| e.to_string())?;
+        let id = oobi_identifier(&url)?;
+        let stream = self.transport.resolve_oobi(&url).await?;
+        self.process(&stream)?;
+        self.save(&id).await?;
+        Ok(id)
+    }
+
+    /// Processes CESR stream of events and receipts, e.g. response to
+    /// query, and saves KELs it updated.
+    pub async fn process_stream(&self, stream: &[u8]) -> Result<(), String> {
+        let ids = self.process(stream)?;
+        for id in ids {
+            self.save(&id).await?;
+        }
+        Ok(())
+    }
+
+    fn process(&self, stream: &[u8]) -> Result<Vec<IdentifierPrefix>, String> {
+        let mut ids = vec![];
+        for message in parse_event_stream(stream).map_err(|e| e.to_string())? {
+            let Message::Notice(notice) = message else {
+                continue;
+            };
+            let id = match &notice {
+                Notice::Event(event) => event.event_message.data.get_prefix(),
+                Notice::NontransferableRct(rct) => rct.body.prefix.clone(),
+                Notice::TransferableRct(rct) => rct.body.prefix.clone(),
+            };
+            self.kel
+                .processor
+                .process_notice(&notice)
+                .map_err(|e| e.to_string())?;
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    fn kel_stream(&self, id: &IdentifierPrefix) -> Result<Vec<u8>, String> {
+        let kel = self
+            .kel
+            .storage
+            .get_kel_messages_with_receipts_all(id)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("No KEL of {}", id))?;
+        kel.into_iter()
+            .map(|notice| Message::Notice(notice).to_cesr())
+            .collect::<Result<Vec<_>, _>>()
+            .map(|streams| streams.concat())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Saves KEL of `id`, if it is accepted.
+    async fn save(&self, id: &IdentifierPrefix) -> Result<(), String> {
+        if self.kel.storage.get_state(id).is_none() {
+            return Ok(());
+        }
+        let kel = self.kel_stream(id)?;
+        self.store.put(&format!("{}{}", KEL_KEY, id), kel).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use keri_core::signer::Signer;
+
+    use super::*;
+    use crate::test_utils::sign;
+
+    #[derive(Default)]
+    struct MemoryStore(RefCell<BTreeMap<String, Vec<u8>>>);
+
+    impl AsyncStore for MemoryStore {
+        fn get<'a>(
+            &'a self,
+            key: &'a str,
+        ) -> LocalBoxFuture<'a, Result<Option<Vec<u8>>, String>> {
+            Box::pin(async move { Ok(self.0.borrow().get(key).cloned()) })
+        }
+
+        fn put<'a>(
+            &'a self,
+            key: &'a str,
+            value: Vec<u8>,
+        ) -> LocalBoxFuture<'a, Result<(), String>> {
+            Box::pin(async move {
+                self.0.borrow_mut().insert(key.to_string(), value);
+                Ok(())
+            })
+        }
+
+        fn keys<'a>(
+            &'a self,
+            prefix: &'a str,
+        ) -> LocalBoxFuture<'a, Result<Vec<String>, String>> {
+            Box::pin(async move {
+                Ok(self
+                    .0
+                    .borrow()
+                    .keys()
+                    .filter(|key| key.starts_with(prefix))
+                    .cloned()
+                    .collect())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_browser_controller() {
+        let store = Rc::new(MemoryStore::default());
+        let controller = BrowserController::load(store.clone()).await.unwrap();
+        let signer = Signer::new();
+        let icp = controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+                vec![],
+                0,
+            )
+            .unwrap();
+        let sig = sign(&signer, icp.as_bytes());
+        let id = controller
+            .finalize_event(
+                icp.as_bytes(),
+                vec![IndexedSignature::new_both_same(sig, 0)],
+            )
+            .await
+            .unwrap();
+        assert!(controller.transport.send_event(&id, b"").await.is_err());
+
+        // KEL is loaded from store on start.
+        let restored = BrowserController::load(store).await.unwrap();
+        assert_eq!(
+            restored.kel.storage.get_state(&id),
+            controller.kel.storage.get_state(&id)
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use keri_core::{oobi::Oobi, prefix::IdentifierPrefix};
+
+/// Delivers messages to watchers.
+///
+/// Implementations are expected to resolve watcher location, e.g. from its
+/// OOBI, and send messages using chosen transport.
+pub trait WatcherTransport: Send + Sync {
+    /// Sends CESR stream, e.g. end role reply registering the watcher.
+    fn send(
+        &self,
+        watcher: &IdentifierPrefix,
+        stream: &[u8],
+    ) -> Result<(), String>;
+
+    /// Asks watcher to resolve `oobi` and start observing identifier it
+    /// points to.
+    fn resolve_oobi(
+        &self,
+        watcher: &IdentifierPrefix,
+        oobi: &Oobi,
+    ) -> Result<(), String>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use keri_core::{
+        actor::{event_generator, parse_query_stream},
+        event_message::signed_event_message::{Message, Op},
+        oobi::{EndRole, Role},
+        prefix::{BasicPrefix, SelfSigningPrefix},
+        query::{
+            query_event::{QueryRoute, SignedQueryMessage},
+            reply_event::SignedReply,
+        },
+        signer::Signer,
+    };
+
+    use super::*;
+    use crate::{
+        test_utils::{incept, setup, sign},
+        KEL_QUERY_PAGE_SIZE,
+    };
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<IdentifierPrefix>>,
+        oobis: Mutex<Vec<(IdentifierPrefix, Oobi)>>,
+    }
+
+    impl WatcherTransport for RecordingTransport {
+        fn send(
+            &self,
+            watcher: &IdentifierPrefix,
+            _stream: &[u8],
+        ) -> Result<(), String> {
+            self.sent.lock().unwrap().push(watcher.clone());
+            Ok(())
+        }
+
+        fn resolve_oobi(
+            &self,
+            watcher: &IdentifierPrefix,
+            oobi: &Oobi,
+        ) -> Result<(), String> {
+            self.oobis
+                .lock()
+                .unwrap()
+                .push((watcher.clone(), oobi.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_query_watchers() {
+        // Observed identifier with KEL of 3 events.
+        let (_subject_root, subject_controller) = setup("subject-db");
+        let subject_signer = Signer::new();
+        let subject = incept(&subject_controller, &subject_signer);
+        let stale_state = subject_controller.get_state(&subject.id).unwrap();
+        for _ in 0..2 {
+            let ixn = subject.anchor(&[]).unwrap();
+            subject
+                .finalize_anchor(
+                    ixn.as_bytes(),
+                    sign(&subject_signer, ixn.as_bytes()),
+                )
+                .unwrap();
+        }
+        let state = subject_controller.get_state(&subject.id).unwrap();
+
+        // Two watchers report current key state, the third one is behind.
+        let watcher_signers: Vec<Signer> =
+            (0..3).map(|_| Signer::new()).collect();
+        let mut notices = HashMap::new();
+        for (i, watcher_signer) in watcher_signers.iter().enumerate() {
+            let bp = BasicPrefix::Ed25519NT(watcher_signer.public_key());
+            let watcher = IdentifierPrefix::Basic(bp.clone());
+            let reported = if i < 2 {
+                state.clone()
+            } else {
+                stale_state.clone()
+            };
+            let rpy = event_generator::generate_ksn_reply(&watcher, reported);
+            let sig = sign(watcher_signer, &rpy.encode().unwrap());
+            notices.insert(watcher, SignedReply::new_nontrans(rpy, bp, sig));
+        }
+        let watchers: Vec<IdentifierPrefix> = notices.keys().cloned().collect();
+        let subject_storage = subject_controller.kel.storage.clone();
+        let query_transport = move |recipient: &IdentifierPrefix,
+                                    query: &[u8]|
+              -> Result<Vec<u8>, String> {
+            // Only KEL queries exist without `mailbox` feature.
+            #[allow(irrefutable_let_patterns)]
+            let SignedQueryMessage::KelQuery(qry) =
+                parse_query_stream(query).unwrap().remove(0)
+            else {
+                return Err("Not a KEL query".to_string());
+            };
+            match qry.query.get_route() {
+                QueryRoute::Ksn { .. } => {
+                    Message::Op(Op::Reply(notices[recipient].clone()))
+                        .to_cesr()
+                        .map_err(|e| e.to_string())
+                }
+                QueryRoute::Logs { args, .. } => Ok(subject_storage
+                    .get_kel_messages_with_receipts_range(
+                        &args.i,
+                        args.s.unwrap_or_default(),
+                        args.limit.unwrap_or(KEL_QUERY_PAGE_SIZE),
+                    )
+                    .unwrap()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .flat_map(|notice| {
+                        Message::Notice(notice).to_cesr().unwrap()
+                    })
+                    .collect()),
+            }
+        };
+
+        let (_root, controller) = setup("test-db");
+        let signer = Signer::new();
+        let identifier = incept(&controller, &signer);
+        let transport = RecordingTransport::default();
+        for watcher in &watchers {
+            let rpy = identifier.add_watcher(watcher.clone()).unwrap();
+            identifier
+                .register_watcher(
+                    rpy.as_bytes(),
+                    sign(&signer, rpy.as_bytes()),
+                    &transport,
+                )
+                .unwrap();
+        }
+        assert_eq!(identifier.watchers(), watchers);
+        assert_eq!(*transport.sent.lock().unwrap(), watchers);
+
+        let oobi = Oobi::EndRole(EndRole {
+            cid: subject.id.clone(),
+            role: Role::Witness,
+            eid: watchers[0].clone(),
+        });
+        identifier.observe(&oobi, &transport).unwrap();
+        assert_eq!(transport.oobis.lock().unwrap().len(), 3);
+
+        let query_signer = |query: &[u8]| -> Result<SelfSigningPrefix, String> {
+            Ok(sign(&signer, query))
+        };
+        assert!(identifier
+            .query_watchers(&subject.id, 3, &query_signer, &query_transport)
+            .is_err());
+        assert!(controller.get_state(&subject.id).is_none());
+
+        let agreed = identifier
+            .query_watchers(&subject.id, 2, &query_signer, &query_transport)
+            .unwrap();
+        assert_eq!(agreed.sn, 2);
+        assert_eq!(agreed.last_event_digest, state.last_event_digest);
+        assert_eq!(controller.get_state(&subject.id).unwrap().sn, 2);
+
+        // Removing end role unregisters watcher.
+        let rpy = identifier
+            .cut_end_role(watchers[0].clone(), Role::Watcher)
+            .unwrap();
+        identifier
+            .register_watcher(
+                rpy.as_bytes(),
+                sign(&signer, rpy.as_bytes()),
+                &transport,
+            )
+            .unwrap();
+        assert_eq!(identifier.watchers(), watchers[1..].to_vec());
+    }
+}
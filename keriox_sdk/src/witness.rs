@@ -0,0 +1,382 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+
+use keri_core::{
+    error::Error,
+    event::sections::{threshold::SignatureThreshold, RotationWitnessConfig},
+    event_message::signed_event_message::{Message, Notice},
+    oobi::LocationScheme,
+    prefix::{BasicPrefix, CesrPrimitive, IdentifierPrefix},
+    processor::notification::{Notification, NotificationBus, Notifier},
+    state::IdentifierState,
+};
+
+/// Delivers CESR stream to a witness.
+///
+/// Implementations are expected to resolve witness location, e.g. from its
+/// OOBI, and send the stream using chosen transport.
+pub trait WitnessPublisher: Send + Sync {
+    fn publish(
+        &self,
+        witness: &BasicPrefix,
+        stream: &[u8],
+    ) -> Result<(), String>;
+
+    /// Delivers TEL events, which witnesses process separately from KEL
+    /// events.
+    fn publish_tel(
+        &self,
+        _witness: &BasicPrefix,
+        _stream: &[u8],
+    ) -> Result<(), String> {
+        Err("Publishing TEL events is not supported".to_string())
+    }
+}
+
+impl<F> WitnessPublisher for F
+where
+    F: Fn(&BasicPrefix, &[u8]) -> Result<(), String> + Send + Sync,
+{
+    fn publish(
+        &self,
+        witness: &BasicPrefix,
+        stream: &[u8],
+    ) -> Result<(), String> {
+        self(witness, stream)
+    }
+}
+
+/// Sends events of watched identifiers to their witnesses as soon as they
+/// are accepted, e.g. when group event collected enough signatures.
+pub struct WitnessSubmitter {
+    watched: RwLock<HashSet<IdentifierPrefix>>,
+    publisher: Arc<dyn WitnessPublisher>,
+}
+
+impl WitnessSubmitter {
+    pub fn new(publisher: Arc<dyn WitnessPublisher>) -> Self {
+        Self {
+            watched: RwLock::new(HashSet::new()),
+            publisher,
+        }
+    }
+
+    /// Starts submitting accepted events of `id` to its witnesses.
+    pub fn watch(&self, id: IdentifierPrefix) {
+        self.watched.write().unwrap().insert(id);
+    }
+
+    pub fn unwatch(&self, id: &IdentifierPrefix) {
+        self.watched.write().unwrap().remove(id);
+    }
+}
+
+impl Notifier for WitnessSubmitter {
+    fn notify(
+        &self,
+        notification: &Notification,
+        _bus: &NotificationBus,
+    ) -> Result<(), Error> {
+        if let Notification::KeyEventAccepted(accepted) = notification {
+            let id = accepted.event.event_message.data.get_prefix();
+            if !self.watched.read().unwrap().contains(&id) {
+                return Ok(());
+            }
+            let stream = Message::Notice(Notice::Event(accepted.event.clone()))
+                .to_cesr()?;
+            for witness in &accepted.state.witness_config.witnesses {
+                if let Err(e) = self.publisher.publish(witness, &stream) {
+                    log::warn!(
+                        "Failed to publish event of {} to witness {}: {}",
+                        id,
+                        witness.to_str(),
+                        e
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Health of witness, as observed when communicating with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitnessHealth {
+    Unknown,
+    Healthy,
+    /// Number of consecutive failed attempts.
+    Unreachable(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WitnessEntry {
+    pub id: BasicPrefix,
+    pub oobis: Vec<LocationScheme>,
+    pub health: WitnessHealth,
+}
+
+/// Witnesses known to identifier: configured ones and candidates, with
+/// their locations and health.
+#[derive(Default)]
+pub struct WitnessPool {
+    entries: RwLock<Vec<WitnessEntry>>,
+}
+
+impl WitnessPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds witness to the pool, if it isn't there yet.
+    pub fn add(&self, id: BasicPrefix) {
+        let mut entries = self.entries.write().unwrap();
+        if !entries.iter().any(|entry| entry.id == id) {
+            entries.push(WitnessEntry {
+                id,
+                oobis: vec![],
+                health: WitnessHealth::Unknown,
+            });
+        }
+    }
+
+    /// Saves witness location. Witness is added to the pool if needed.
+    pub fn add_oobi(&self, oobi: LocationScheme) -> Result<(), String> {
+        let id = match &oobi.eid {
+            IdentifierPrefix::Basic(bp) => bp.clone(),
+            _ => return Err("Witness identifier must be basic".to_string()),
+        };
+        self.add(id.clone());
+        let mut entries = self.entries.write().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|entry| entry.id == id) {
+            if !entry.oobis.contains(&oobi) {
+                entry.oobis.push(oobi);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&self, id: &BasicPrefix) {
+        self.entries
+            .write()
+            .unwrap()
+            .retain(|entry| &entry.id != id);
+    }
+
+    pub fn get(&self, id: &BasicPrefix) -> Option<WitnessEntry> {
+        self.entries
+            .read()
+            .unwrap()
+            .iter()
+            .find(|entry| &entry.id == id)
+            .cloned()
+    }
+
+    pub fn entries(&self) -> Vec<WitnessEntry> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// Returns witnesses that didn't fail since last successful contact.
+    pub fn healthy(&self) -> Vec<BasicPrefix> {
+        self.entries
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.health == WitnessHealth::Healthy)
+            .map(|entry| entry.id.clone())
+            .collect()
+    }
+
+    pub fn unreachable(&self) -> Vec<BasicPrefix> {
+        self.entries
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|entry| {
+                matches!(entry.health, WitnessHealth::Unreachable(_))
+            })
+            .map(|entry| entry.id.clone())
+            .collect()
+    }
+
+    pub fn record_success(&self, id: &BasicPrefix) {
+        self.set_health(id, |_| WitnessHealth::Healthy)
+    }
+
+    pub fn record_failure(&self, id: &BasicPrefix) {
+        self.set_health(id, |health| match health {
+            WitnessHealth::Unreachable(failures) => {
+                WitnessHealth::Unreachable(failures + 1)
+            }
+            _ => WitnessHealth::Unreachable(1),
+        })
+    }
+
+    /// Computes witness changes of rotation event. Removed witnesses must
+    /// be configured and added ones must not. If `threshold` isn't
+    /// provided, it is adjusted to the new number of witnesses with
+    /// `ample`.
+    pub fn rotation_config(
+        &self,
+        state: &IdentifierState,
+        to_add: &[BasicPrefix],
+        to_remove: &[BasicPrefix],
+        threshold: Option<u64>,
+    ) -> Result<RotationWitnessConfig, String> {
+        let current = &state.witness_config.witnesses;
+        if let Some(unknown) = to_remove.iter().find(|w| !current.contains(w)) {
+            return Err(format!("{} is not a witness", unknown.to_str()));
+        }
+        if let Some(known) = to_add.iter().find(|w| current.contains(w)) {
+            return Err(format!("{} is already a witness", known.to_str()));
+        }
+        if to_add.iter().any(|w| to_remove.contains(w)) {
+            return Err("Witness can't be added and removed".to_string());
+        }
+        let dedup = |witnesses: &[BasicPrefix]| {
+            let mut unique: Vec<BasicPrefix> = vec![];
+            for witness in witnesses {
+                if !unique.contains(witness) {
+                    unique.push(witness.clone());
+                }
+            }
+            unique
+        };
+        let (graft, prune) = (dedup(to_add), dedup(to_remove));
+        let count = current.len() - prune.len() + graft.len();
+        let tally = match threshold {
+            Some(threshold) if threshold > count as u64 => {
+                return Err("Improper witness threshold".to_string())
+            }
+            Some(threshold) => threshold,
+            None => ample(count),
+        };
+        for witness in &graft {
+            self.add(witness.clone());
+        }
+        Ok(RotationWitnessConfig {
+            tally: SignatureThreshold::Simple(tally),
+            prune,
+            graft,
+        })
+    }
+
+    fn set_health(
+        &self,
+        id: &BasicPrefix,
+        update: impl Fn(WitnessHealth) -> WitnessHealth,
+    ) {
+        if let Some(entry) = self
+            .entries
+            .write()
+            .unwrap()
+            .iter_mut()
+            .find(|entry| &entry.id == id)
+        {
+            entry.health = update(entry.health);
+        }
+    }
+}
+
+/// Sufficient witness threshold for `n` witnesses, which tolerates
+/// `f = (n - 1) / 3` faulty ones while keeping receipts of any two
+/// agreeing thresholds overlapping.
+pub fn ample(n: usize) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    let f1 = std::cmp::max(1, (n - 1) / 3);
+    let f2 = std::cmp::max(1, (n - 1).div_ceil(3));
+    [n, (n + f1 + 1).div_ceil(2), (n + f2 + 1).div_ceil(2)]
+        .into_iter()
+        .min()
+        .unwrap_or(n) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use keri_core::signer::Signer;
+
+    use super::*;
+    use crate::test_utils::{setup, sign};
+
+    #[test]
+    fn test_ample() {
+        let thresholds: Vec<u64> = (0..8).map(ample).collect();
+        assert_eq!(thresholds, vec![0, 1, 2, 3, 3, 4, 4, 5]);
+    }
+
+    #[test]
+    fn test_witness_rotation() {
+        let (_root, controller) = setup("test-db");
+
+        let (signer, next_signer) = (Signer::new(), Signer::new());
+        let icp = controller
+            .incept(
+                vec![BasicPrefix::Ed25519(signer.public_key())],
+                vec![BasicPrefix::Ed25519(next_signer.public_key())],
+            )
+            .unwrap();
+        let identifier = controller
+            .finalize_incept(icp.as_bytes(), &sign(&signer, icp.as_bytes()))
+            .unwrap();
+        let witnesses: Vec<BasicPrefix> = (0..3)
+            .map(|_| BasicPrefix::Ed25519NT(Signer::new().public_key()))
+            .collect();
+
+        let config =
+            identifier.witness_rotation(&witnesses, &[], None).unwrap();
+        assert_eq!(config.tally, SignatureThreshold::Simple(3));
+        assert!(identifier
+            .witness_rotation(&[], &witnesses[..1], None)
+            .is_err());
+        assert!(identifier
+            .witness_rotation(&witnesses, &[], Some(4))
+            .is_err());
+
+        // Witness threshold of 0 lets rotation be accepted without receipts.
+        let config = identifier
+            .witness_rotation(&witnesses[..2], &[], Some(0))
+            .unwrap();
+        let next_key = BasicPrefix::Ed25519(next_signer.public_key());
+        let rot = identifier
+            .rotate_witnesses(
+                vec![next_key.clone()],
+                SignatureThreshold::Simple(1),
+                &[BasicPrefix::Ed25519(Signer::new().public_key())],
+                SignatureThreshold::Simple(1),
+                config,
+            )
+            .unwrap();
+        let signature = identifier
+            .rotation_signature(
+                std::slice::from_ref(&next_key),
+                &next_key,
+                sign(&next_signer, rot.as_bytes()),
+            )
+            .unwrap();
+        controller
+            .finalize_rotate(rot.as_bytes(), vec![signature])
+            .unwrap();
+        let state = controller.get_state(&identifier.id).unwrap();
+        assert_eq!(state.witness_config.witnesses, witnesses[..2].to_vec());
+
+        let config = identifier
+            .witness_rotation(&[], &witnesses[..1], None)
+            .unwrap();
+        assert_eq!(config.prune, witnesses[..1].to_vec());
+        assert_eq!(config.tally, SignatureThreshold::Simple(1));
+
+        let pool = identifier.witness_pool();
+        assert_eq!(pool.entries().len(), 3);
+        pool.record_failure(&witnesses[0]);
+        pool.record_failure(&witnesses[0]);
+        pool.record_success(&witnesses[1]);
+        assert_eq!(
+            pool.get(&witnesses[0]).unwrap().health,
+            WitnessHealth::Unreachable(2)
+        );
+        assert_eq!(pool.healthy(), vec![witnesses[1].clone()]);
+    }
+}
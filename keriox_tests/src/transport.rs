@@ -27,7 +27,7 @@ impl TelTestActor {
                 .parse_and_process_tel_queries(&input_stream)
                 .unwrap()
                 .into_iter()
-                .map(|msg| msg.to_string())
+                .map(|msg| String::from_utf8(msg.encode().unwrap()).unwrap())
                 .collect::<Vec<_>>()
                 .join(""),
             TelTestActor::Watcher(wat) => wat
@@ -35,7 +35,7 @@ impl TelTestActor {
                 .await
                 .unwrap()
                 .into_iter()
-                .map(|msg| msg.to_string())
+                .map(|msg| String::from_utf8(msg.encode().unwrap()).unwrap())
                 .collect::<Vec<_>>()
                 .join(""),
         };
@@ -16,6 +16,15 @@ pub trait TelEventDatabase {
 
     fn add_new_event(&self, event: VerifiableEvent, id: &IdentifierPrefix) -> Result<(), Error>;
 
+    /// Adds all events at once. Implementations should save them
+    /// atomically, so that either all or none of them are stored.
+    fn add_new_events(&self, events: Vec<VerifiableEvent>) -> Result<(), Error> {
+        events.into_iter().try_for_each(|event| {
+            let id = event.get_event().get_prefix();
+            self.add_new_event(event, &id)
+        })
+    }
+
     fn get_events(
         &self,
         id: &IdentifierPrefix,
@@ -25,6 +34,15 @@ pub trait TelEventDatabase {
         &self,
         id: &IdentifierPrefix,
     ) -> Option<impl DoubleEndedIterator<Item = VerifiableEvent>>;
+
+    /// Returns identifiers of credentials issued in registry.
+    fn get_registry_vcs(
+        &self,
+        registry_id: &IdentifierPrefix,
+    ) -> Result<Vec<IdentifierPrefix>, Error>;
+
+    /// Returns identifiers of all known registries.
+    fn get_registries(&self) -> Result<Vec<IdentifierPrefix>, Error>;
 }
 
 #[cfg(feature = "storage-redb")]
@@ -10,7 +10,7 @@ use keri_core::{
     database::redb::{execute_in_transaction, WriteTxnMode},
     prefix::IdentifierPrefix,
 };
-use redb::{Database, ReadTransaction, TableDefinition};
+use redb::{Database, MultimapTableDefinition, ReadTransaction, ReadableTable, TableDefinition};
 use std::{fs, path::Path, sync::Arc};
 
 /// Events store. (event digest) -> tel event
@@ -28,6 +28,17 @@ const VC_TELS: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("kels"
 /// referencing the actual event stored in the `EVENTS` table.
 const MANAGEMENT_TELS: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("kels");
 
+/// Credentials of registries. registry identifier -> VC identifier
+/// The `REGISTRY_VCS` table links registry identifier to identifiers of all credentials
+/// issued in it.
+const REGISTRY_VCS: MultimapTableDefinition<&str, &str> =
+    MultimapTableDefinition::new("registry_vcs");
+
+/// Registries. registry identifier -> ()
+/// The `REGISTRIES` table lists identifiers of all registries with accepted
+/// management events.
+const REGISTRIES: TableDefinition<&str, ()> = TableDefinition::new("registries");
+
 pub struct RedbTelDatabase {
     events_log: Arc<LogTelDb>,
     tel_digests: Arc<TelEventsDb>,
@@ -45,6 +56,8 @@ impl TelEventsDb {
         {
             write_txn.open_table(VC_TELS)?;
             write_txn.open_table(MANAGEMENT_TELS)?;
+            write_txn.open_multimap_table(REGISTRY_VCS)?;
+            write_txn.open_table(REGISTRIES)?;
         }
         write_txn.commit()?;
         Ok(Self { db })
@@ -57,6 +70,7 @@ impl TelEventsDb {
     ) -> Result<(), Error> {
         let id = vc_event.data.data.prefix.clone();
         let sn = vc_event.data.data.sn.clone();
+        let registry_id = vc_event.data.data.registry_id().ok();
         let said = vc_event
             .digest()
             .map_err(|_e| Error::Generic("Event does not have a digest".to_string()))?;
@@ -65,6 +79,10 @@ impl TelEventsDb {
                 let mut man_tel_table = write_txn.open_table(VC_TELS)?;
                 man_tel_table.insert((id.to_string().as_str(), sn), said.to_string().as_bytes())?;
             };
+            if let Some(registry_id) = registry_id {
+                let mut registry_table = write_txn.open_multimap_table(REGISTRY_VCS)?;
+                registry_table.insert(registry_id.to_string().as_str(), id.to_string().as_str())?;
+            };
             Ok(())
         })
         .map_err(|e| Error::Generic(format!("Failed to insert digest: {}", e)))
@@ -85,6 +103,10 @@ impl TelEventsDb {
                 let mut man_tel_table = write_txn.open_table(MANAGEMENT_TELS)?;
                 man_tel_table.insert((id.to_string().as_str(), sn), said.to_string().as_bytes())?;
             };
+            {
+                let mut registries_table = write_txn.open_table(REGISTRIES)?;
+                registries_table.insert(id.to_string().as_str(), ())?;
+            };
             Ok(())
         })
         .map_err(|e| Error::Generic(format!("Failed to insert digest: {}", e)))
@@ -105,6 +127,37 @@ impl TelEventsDb {
             })
     }
 
+    pub fn get_registry_vcs(
+        &self,
+        registry_id: &IdentifierPrefix,
+        txn: &ReadTransaction,
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        let table = txn.open_multimap_table(REGISTRY_VCS)?;
+        table
+            .get(registry_id.to_string().as_str())?
+            .map(|entry| {
+                entry?
+                    .value()
+                    .parse()
+                    .map_err(|_e| Error::Generic("Improper VC identifier".to_string()))
+            })
+            .collect()
+    }
+
+    pub fn get_registries(&self, txn: &ReadTransaction) -> Result<Vec<IdentifierPrefix>, Error> {
+        let table = txn.open_table(REGISTRIES)?;
+        table
+            .iter()?
+            .map(|entry| {
+                entry?
+                    .0
+                    .value()
+                    .parse()
+                    .map_err(|_e| Error::Generic("Improper registry identifier".to_string()))
+            })
+            .collect()
+    }
+
     pub fn get_management_events(
         &self,
         id: &IdentifierPrefix,
@@ -208,19 +261,25 @@ impl TelEventDatabase for RedbTelDatabase {
         })
     }
 
-    fn add_new_event(&self, event: VerifiableEvent, id: &IdentifierPrefix) -> Result<(), Error> {
+    fn add_new_event(&self, event: VerifiableEvent, _id: &IdentifierPrefix) -> Result<(), Error> {
+        self.add_new_events(vec![event])
+    }
+
+    fn add_new_events(&self, events: Vec<VerifiableEvent>) -> Result<(), Error> {
         let write_txn = self.db.begin_write()?;
         let txn_mode = WriteTxnMode::UseExisting(&write_txn);
-        self.events_log.log_event(&event, &txn_mode)?;
+        for event in events {
+            self.events_log.log_event(&event, &txn_mode)?;
 
-        match event.event {
-            Event::Management(typed_event) => {
-                self.tel_digests
-                    .add_management_event_digest(typed_event, &txn_mode)?;
-            }
-            Event::Vc(typed_event) => {
-                self.tel_digests
-                    .add_vc_event_digest(typed_event, &txn_mode)?;
+            match event.event {
+                Event::Management(typed_event) => {
+                    self.tel_digests
+                        .add_management_event_digest(typed_event, &txn_mode)?;
+                }
+                Event::Vc(typed_event) => {
+                    self.tel_digests
+                        .add_vc_event_digest(typed_event, &txn_mode)?;
+                }
             }
         }
         write_txn.commit()?;
@@ -261,4 +320,17 @@ impl TelEventDatabase for RedbTelDatabase {
             Some(out_iter.collect::<Vec<_>>().into_iter())
         }
     }
+
+    fn get_registry_vcs(
+        &self,
+        registry_id: &IdentifierPrefix,
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        let read_txn = self.db.begin_read()?;
+        self.tel_digests.get_registry_vcs(registry_id, &read_txn)
+    }
+
+    fn get_registries(&self) -> Result<Vec<IdentifierPrefix>, Error> {
+        let read_txn = self.db.begin_read()?;
+        self.tel_digests.get_registries(&read_txn)
+    }
 }
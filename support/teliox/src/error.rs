@@ -1,4 +1,4 @@
-use keri_core::error::Error as KeriError;
+use keri_core::{error::Error as KeriError, prefix::IdentifierPrefix};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -19,8 +19,8 @@ pub enum Error {
     #[error("Escrow database error: {0}")]
     EscrowDatabaseError(String),
 
-    #[error("Error")]
-    MissingSealError,
+    #[error("KEL event {sn} of {issuer} doesn't anchor TEL event")]
+    AnchorMismatchError { issuer: IdentifierPrefix, sn: u64 },
 
     #[error("Missing issuer event")]
     MissingIssuerEventError,
@@ -40,6 +40,16 @@ pub enum Error {
     #[error("Event is already accepted in TEL")]
     EventAlreadySavedError,
 
+    #[error("Not enough backer receipts")]
+    NotEnoughReceiptsError,
+
+    #[error("Event {sn} of {id} TEL is inconsistent: {reason}")]
+    InconsistentTelError {
+        id: IdentifierPrefix,
+        sn: u64,
+        reason: Box<Error>,
+    },
+
     #[error("Locking error")]
     RwLockingError,
 }
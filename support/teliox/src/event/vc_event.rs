@@ -137,6 +137,16 @@ impl VCEvent {
                 .clone(),
         })
     }
+
+    /// Returns seal of management TEL event the event is anchored to, if
+    /// registry has backers.
+    pub fn registry_anchor(&self) -> Option<&EventSeal> {
+        match &self.event_type {
+            VCEventType::Bis(iss) => Some(&iss.registry_anchor),
+            VCEventType::Brv(rev) => rev.registry_anchor.as_ref(),
+            VCEventType::Iss(_) | VCEventType::Rev(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1,6 +1,7 @@
 use crate::error::Error;
 use crate::seal::AttachedSourceSeal;
-use cesrox::payload::Payload;
+use cesrox::{group::Group, payload::Payload};
+use keri_core::prefix::{BasicPrefix, SelfSigningPrefix};
 use serde::{Deserialize, Serialize};
 
 use super::Event;
@@ -9,18 +10,47 @@ use super::Event;
 pub struct VerifiableEvent {
     pub event: Event,
     pub seal: AttachedSourceSeal,
+    /// Receipts of registry backers, i.e. their signatures of encoded event.
+    #[serde(default)]
+    pub receipts: Vec<(BasicPrefix, SelfSigningPrefix)>,
 }
 
 impl VerifiableEvent {
     pub fn new(event: Event, seal: AttachedSourceSeal) -> Self {
-        Self { event, seal }
+        Self {
+            event,
+            seal,
+            receipts: vec![],
+        }
+    }
+
+    /// Attaches backer receipts, skipping the ones already attached.
+    pub fn add_receipts(&mut self, receipts: Vec<(BasicPrefix, SelfSigningPrefix)>) {
+        for receipt in receipts {
+            if !self.receipts.contains(&receipt) {
+                self.receipts.push(receipt);
+            }
+        }
     }
 
     pub fn serialize(&self) -> Result<Vec<u8>, Error> {
-        Ok(match &self.event {
+        let mut serialized = match &self.event {
             Event::Management(man) => [man.encode()?, self.seal.serialize()?].join("".as_bytes()),
             Event::Vc(vc) => [vc.encode()?, self.seal.serialize()?].join("".as_bytes()),
-        })
+        };
+        if !self.receipts.is_empty() {
+            let couples = self
+                .receipts
+                .iter()
+                .map(|(bp, sp)| (bp.clone().into(), sp.clone().into()))
+                .collect();
+            serialized.extend(
+                Group::NontransReceiptCouples(couples)
+                    .to_cesr_str()
+                    .as_bytes(),
+            );
+        }
+        Ok(serialized)
     }
 
     pub fn get_event(&self) -> Event {
@@ -28,22 +58,38 @@ impl VerifiableEvent {
     }
 
     pub fn parse(stream: &[u8]) -> Result<Vec<Self>, Error> {
-        let events = cesrox::parse_many(stream).unwrap().1;
+        let (_rest, events) = cesrox::parse_many(stream)
+            .map_err(|_e| Error::Generic("Can't parse TEL stream".into()))?;
         events
             .into_iter()
             .map(|ev| -> Result<Self, Error> {
                 let event: Event = match ev.payload {
-                    Payload::JSON(json) => serde_json::from_slice(&json).unwrap(),
-                    _ => todo!(),
+                    Payload::JSON(json) => serde_json::from_slice(&json)
+                        .map_err(|e| Error::EncodingError(e.to_string()))?,
+                    _ => return Err(Error::Generic("Unsupported TEL event format".into())),
                 };
-                let seal = match &ev.attachments[0] {
-                    cesrox::group::Group::SourceSealCouples(seal) => {
-                        let (sn, digest) = seal[0].clone();
-                        Ok(AttachedSourceSeal::new(sn, (digest.clone()).into()))
+                let mut seal = None;
+                let mut receipts = vec![];
+                for attachment in ev.attachments {
+                    match attachment {
+                        Group::SourceSealCouples(couples) => {
+                            let (sn, digest) = couples
+                                .first()
+                                .cloned()
+                                .ok_or(Error::Generic("Empty source seal".into()))?;
+                            seal = Some(AttachedSourceSeal::new(sn, digest.into()));
+                        }
+                        Group::NontransReceiptCouples(couples) => receipts
+                            .extend(couples.into_iter().map(|(bp, sp)| (bp.into(), sp.into()))),
+                        _ => return Err(Error::Generic("Unexpected attachment".into())),
                     }
-                    _ => Err(Error::Generic("Unexpected attachment".into())),
-                }?;
-                Ok(Self { event, seal })
+                }
+                let seal = seal.ok_or(Error::Generic("Missing source seal".into()))?;
+                Ok(Self {
+                    event,
+                    seal,
+                    receipts,
+                })
             })
             .collect()
     }
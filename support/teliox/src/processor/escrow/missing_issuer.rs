@@ -14,7 +14,6 @@ use crate::{
         digest_key_database::DigestKeyDatabase, EscrowDatabase, TelEventDatabase, TelLogDatabase,
     },
     error::Error,
-    event::Event,
     processor::{
         notification::{TelNotification, TelNotificationBus, TelNotifier},
         storage::TelEventStorage,
@@ -105,11 +104,7 @@ impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> MissingIssuerEscrow
                 let kel_event_digest = event.event.get_digest()?;
                 let validator =
                     TelEventValidator::new(self.tel_reference.clone(), self.kel_reference.clone());
-                let result = match &event.event {
-                    Event::Management(man) => validator.validate_management(&man, &event.seal),
-                    Event::Vc(vc) => validator.validate_vc(&vc, &event.seal),
-                };
-                match result {
+                match validator.validate(&event) {
                     Ok(_) => {
                         // remove from escrow
                         self.escrowed_missing_issuer
@@ -121,7 +116,7 @@ impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> MissingIssuerEscrow
                         self.publisher
                             .notify(&TelNotification::TelEventAdded(event))?;
                     }
-                    Err(Error::MissingSealError) => {
+                    Err(Error::AnchorMismatchError { .. }) => {
                         // remove from escrow
                         self.escrowed_missing_issuer
                             .remove(said, &kel_event_digest)
@@ -140,6 +135,13 @@ impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> MissingIssuerEscrow
                         self.publisher
                             .notify(&TelNotification::MissingRegistry(event))?;
                     }
+                    Err(Error::NotEnoughReceiptsError) => {
+                        self.escrowed_missing_issuer
+                            .remove(said, &kel_event_digest)
+                            .unwrap();
+                        self.publisher
+                            .notify(&TelNotification::PartiallyReceipted(event))?;
+                    }
                     Err(_e) => (), // keep in escrow,
                 }
             }
@@ -93,7 +93,7 @@ impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> MissingRegistryEscr
                         // stop processing the escrow if tel was updated. It needs to start again.
                         break;
                     }
-                    Err(Error::MissingSealError) => {
+                    Err(Error::AnchorMismatchError { .. }) => {
                         // remove from escrow
                         self.escrowed_missing_registry.remove(id, &digest).unwrap();
                     }
@@ -101,6 +101,10 @@ impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> MissingRegistryEscr
                         self.escrowed_missing_registry.remove(id, &digest).unwrap();
                         bus.notify(&TelNotification::MissingIssuer(event.clone()))?;
                     }
+                    Err(Error::NotEnoughReceiptsError) => {
+                        self.escrowed_missing_registry.remove(id, &digest).unwrap();
+                        bus.notify(&TelNotification::PartiallyReceipted(event.clone()))?;
+                    }
                     Err(_e) => {} // keep in escrow,
                 }
             }
@@ -10,7 +10,7 @@ use crate::{
 
 use self::{
     missing_issuer::MissingIssuerEscrow, missing_registry::MissingRegistryEscrow,
-    out_of_order::OutOfOrderEscrow,
+    out_of_order::OutOfOrderEscrow, partially_receipted::PartiallyReceiptedEscrow,
 };
 
 use super::notification::TelNotificationBus;
@@ -18,6 +18,7 @@ use super::notification::TelNotificationBus;
 pub mod missing_issuer;
 pub mod missing_registry;
 pub mod out_of_order;
+pub mod partially_receipted;
 
 pub fn default_escrow_bus<D: TelEventDatabase + TelLogDatabase + Send + Sync + 'static, K: EventDatabase + Send + Sync + 'static>(
     tel_storage: Arc<D>,
@@ -29,6 +30,7 @@ pub fn default_escrow_bus<D: TelEventDatabase + TelLogDatabase + Send + Sync + '
         Arc<MissingIssuerEscrow<D, K>>,
         Arc<OutOfOrderEscrow<D, K>>,
         Arc<MissingRegistryEscrow<D, K>>,
+        Arc<PartiallyReceiptedEscrow<D, K>>,
     ),
     Error,
 > {
@@ -44,6 +46,11 @@ pub fn default_escrow_bus<D: TelEventDatabase + TelLogDatabase + Send + Sync + '
         &tel_escrow_db,
         Duration::from_secs(100),
     ));
+    let partially_receipted_escrow = Arc::new(PartiallyReceiptedEscrow::new(
+        tel_storage.clone(),
+        kel_storage.clone(),
+        &tel_escrow_db,
+    ));
     let tel_bus = TelNotificationBus::new();
 
     let missing_issuer_escrow = Arc::new(MissingIssuerEscrow::new(
@@ -72,10 +79,15 @@ pub fn default_escrow_bus<D: TelEventDatabase + TelLogDatabase + Send + Sync + '
         missing_issuer_escrow.clone(),
         vec![TelNotificationKind::MissingIssuer],
     )?;
+    tel_bus.register_observer(
+        partially_receipted_escrow.clone(),
+        vec![TelNotificationKind::PartiallyReceipted],
+    )?;
     Ok((
         tel_bus,
         missing_issuer_escrow,
         out_of_order_escrow,
         missing_registry_escrow,
+        partially_receipted_escrow,
     ))
 }
@@ -104,12 +104,18 @@ impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> OutOfOrderEscrow<D,
                         // stop processing the escrow if tel was updated. It needs to start again.
                         break;
                     }
-                    Err(Error::MissingSealError) => {
+                    Err(Error::AnchorMismatchError { .. }) => {
                         // remove from escrow
                         self.escrowed_out_of_order
                             .remove(id, sn, &said)
                             .map_err(|e| Error::EscrowDatabaseError(e.to_string()))?;
                     }
+                    Err(Error::NotEnoughReceiptsError) => {
+                        self.escrowed_out_of_order
+                            .remove(id, sn, &said)
+                            .map_err(|e| Error::EscrowDatabaseError(e.to_string()))?;
+                        bus.notify(&TelNotification::PartiallyReceipted(event))?;
+                    }
                     Err(_e) => {} // keep in escrow,
                 }
             }
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use keri_core::{
+    database::{redb::WriteTxnMode, EventDatabase},
+    processor::event_storage::EventStorage,
+};
+
+use crate::{
+    database::{
+        digest_key_database::DigestKeyDatabase, EscrowDatabase, TelEventDatabase, TelLogDatabase,
+    },
+    error::Error,
+    event::verifiable_event::VerifiableEvent,
+    processor::{
+        notification::{TelNotification, TelNotificationBus, TelNotifier},
+        storage::TelEventStorage,
+        validator::TelEventValidator,
+    },
+};
+
+/// Escrow for TEL events which are valid, but not receipted by enough
+/// registry backers yet. Receipts of the same event received later are
+/// merged with the escrowed ones, and the event is accepted as soon as
+/// backer threshold is met.
+pub struct PartiallyReceiptedEscrow<D: TelEventDatabase, K: EventDatabase> {
+    tel_reference: Arc<TelEventStorage<D>>,
+    kel_reference: Arc<EventStorage<K>>,
+    // Key is the tel event identifier, value is the escrowed tel events digests
+    escrowed_partially_receipted: DigestKeyDatabase,
+}
+
+impl<D: TelEventDatabase, K: EventDatabase> PartiallyReceiptedEscrow<D, K> {
+    pub fn new(
+        tel_reference: Arc<D>,
+        kel_reference: Arc<EventStorage<K>>,
+        escrow_db: &EscrowDatabase,
+    ) -> Self {
+        let escrow = DigestKeyDatabase::new(escrow_db.0.clone(), "partially_receipted_escrow");
+        let tel_event_storage = Arc::new(TelEventStorage::new(tel_reference.clone()));
+        Self {
+            tel_reference: tel_event_storage,
+            kel_reference,
+            escrowed_partially_receipted: escrow,
+        }
+    }
+}
+
+impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> TelNotifier
+    for PartiallyReceiptedEscrow<D, K>
+{
+    fn notify(
+        &self,
+        notification: &TelNotification,
+        bus: &TelNotificationBus,
+    ) -> Result<(), Error> {
+        match notification {
+            TelNotification::PartiallyReceipted(event) => self.process_receipts(bus, event),
+            _ => Err(Error::Generic("Wrong notification".into())),
+        }
+    }
+}
+
+impl<D: TelEventDatabase + TelLogDatabase, K: EventDatabase> PartiallyReceiptedEscrow<D, K> {
+    /// Merges receipts of `event` with receipts of its escrowed copy and
+    /// accepts it if there are enough of them. Otherwise keeps it in escrow.
+    pub fn process_receipts(
+        &self,
+        bus: &TelNotificationBus,
+        event: &VerifiableEvent,
+    ) -> Result<(), Error> {
+        let id = event.event.get_prefix();
+        let digest = event.event.get_digest()?;
+        // Escrow table doesn't exist until first event is escrowed.
+        let escrowed = self
+            .escrowed_partially_receipted
+            .get(&id.to_string().as_str())
+            .map(|escrowed| escrowed.contains(&digest))
+            .unwrap_or(false);
+        let mut event = event.clone();
+        if escrowed {
+            if let Some(saved) = self.tel_reference.db.get(&digest)? {
+                event.add_receipts(saved.receipts);
+            }
+        }
+
+        let validator =
+            TelEventValidator::new(self.tel_reference.clone(), self.kel_reference.clone());
+        match validator.validate(&event) {
+            Ok(_) => {
+                if escrowed {
+                    self.escrowed_partially_receipted
+                        .remove(&id, &digest)
+                        .map_err(|e| Error::EscrowDatabaseError(e.to_string()))?;
+                }
+                self.tel_reference.add_event(event.clone())?;
+                bus.notify(&TelNotification::TelEventAdded(event))
+            }
+            Err(Error::NotEnoughReceiptsError) => {
+                self.tel_reference
+                    .db
+                    .log_event(&event, &WriteTxnMode::CreateNew)?;
+                if !escrowed {
+                    self.escrowed_partially_receipted
+                        .insert(&id.to_string().as_str(), &digest)
+                        .map_err(|e| Error::EscrowDatabaseError(e.to_string()))?;
+                }
+                Ok(())
+            }
+            Err(_e) => {
+                // Event can't be accepted anymore, e.g. other event of the
+                // same sn was accepted in the meantime.
+                if escrowed {
+                    self.escrowed_partially_receipted
+                        .remove(&id, &digest)
+                        .map_err(|e| Error::EscrowDatabaseError(e.to_string()))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,12 +1,14 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use keri_core::{database::EventDatabase, processor::event_storage::EventStorage};
+use keri_core::{
+    database::EventDatabase, prefix::IdentifierPrefix, processor::event_storage::EventStorage,
+};
 
 use crate::{
     database::TelEventDatabase,
     error::Error,
     event::{verifiable_event::VerifiableEvent, Event},
-    query::SignedTelQuery,
+    query::{SignedTelQuery, TelStateNotice},
 };
 
 use self::{
@@ -41,7 +43,7 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventProcessor<D, K> {
     }
 
     pub fn register_observer(
-        &mut self,
+        &self,
         observer: Arc<dyn TelNotifier + Send + Sync>,
         notifications: Vec<TelNotificationKind>,
     ) -> Result<(), Error> {
@@ -49,12 +51,22 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventProcessor<D, K> {
         Ok(())
     }
 
+    /// Replays registry TEL and TELs of its credentials, see
+    /// `TelEventValidator::verify_tel`.
+    pub fn verify_tel(&self, registry_id: &IdentifierPrefix) -> Result<(), Error> {
+        TelEventValidator::new(self.tel_reference.clone(), self.kel_reference.clone())
+            .verify_tel(registry_id)
+    }
+
     // Checks verifiable event and adds it to database.
     pub fn process(&self, event: VerifiableEvent) -> Result<(), Error> {
         let validator =
             TelEventValidator::new(self.tel_reference.clone(), self.kel_reference.clone());
         match &event.event.clone() {
-            Event::Management(ref man) => match validator.validate_management(man, &event.seal) {
+            Event::Management(ref man) => match validator
+                .validate_management(man, &event.seal)
+                .and_then(|_| validator.check_backer_receipts(&event))
+            {
                 Ok(_) => {
                     self.tel_reference
                         .db
@@ -74,6 +86,9 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventProcessor<D, K> {
                     Error::MissingRegistryError => self
                         .publisher
                         .notify(&TelNotification::MissingRegistry(event)),
+                    Error::NotEnoughReceiptsError => self
+                        .publisher
+                        .notify(&TelNotification::PartiallyReceipted(event)),
                     Error::EventAlreadySavedError => {
                         // Means that vc of given registry is already accepted
                         Ok(())
@@ -82,7 +97,10 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventProcessor<D, K> {
                 },
             },
             Event::Vc(ref vc_ev) => {
-                match validator.validate_vc(vc_ev, &event.seal) {
+                match validator
+                    .validate_vc(vc_ev, &event.seal)
+                    .and_then(|_| validator.check_backer_receipts(&event))
+                {
                     Ok(_) => {
                         self.tel_reference
                             .db
@@ -100,6 +118,9 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventProcessor<D, K> {
                     Err(Error::OutOfOrderError) => {
                         self.publisher.notify(&TelNotification::OutOfOrder(event))
                     }
+                    Err(Error::NotEnoughReceiptsError) => self
+                        .publisher
+                        .notify(&TelNotification::PartiallyReceipted(event)),
                     Err(Error::EventAlreadySavedError) => {
                         // Means that vc of given id is already accepted
                         Ok(())
@@ -110,6 +131,61 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventProcessor<D, K> {
         }
     }
 
+    /// Finds event anchoring tel event in issuer's KEL and processes tel
+    /// event with source seal pointing to it, so the seal doesn't need to
+    /// be known by caller. Fails with `MissingIssuerEventError` if
+    /// anchoring event isn't known yet.
+    pub fn process_event(&self, event: Event) -> Result<(), Error> {
+        let seal = TelEventValidator::new(self.tel_reference.clone(), self.kel_reference.clone())
+            .locate_anchor(&event)?;
+        self.process(VerifiableEvent::new(event, seal))
+    }
+
+    /// Checks credential events and adds all of them to database in one
+    /// transaction. Nothing is saved if any of them isn't valid. Events
+    /// not receipted by enough backers yet are escrowed instead, like in
+    /// `process`. Batch can't hold management events or more than one
+    /// event of the same credential, since they would depend on each other.
+    pub fn process_batch(&self, events: Vec<VerifiableEvent>) -> Result<(), Error> {
+        let validator =
+            TelEventValidator::new(self.tel_reference.clone(), self.kel_reference.clone());
+        let mut credentials = HashSet::new();
+        for event in &events {
+            let vc_ev = match &event.event {
+                Event::Vc(vc_ev) => vc_ev,
+                Event::Management(_) => {
+                    return Err(Error::Generic(
+                        "Management events can't be processed in batch".to_string(),
+                    ))
+                }
+            };
+            if !credentials.insert(vc_ev.data.data.prefix.clone()) {
+                return Err(Error::Generic(format!(
+                    "Batch holds more than one event of {}",
+                    vc_ev.data.data.prefix
+                )));
+            }
+            validator.validate_vc(vc_ev, &event.seal)?;
+        }
+        let (mut receipted, mut partially_receipted) = (vec![], vec![]);
+        for event in events {
+            match validator.check_backer_receipts(&event) {
+                Ok(_) => receipted.push(event),
+                Err(Error::NotEnoughReceiptsError) => partially_receipted.push(event),
+                Err(e) => return Err(e),
+            }
+        }
+        self.tel_reference.db.add_new_events(receipted.clone())?;
+        receipted.into_iter().try_for_each(|event| {
+            self.publisher
+                .notify(&TelNotification::TelEventAdded(event))
+        })?;
+        partially_receipted.into_iter().try_for_each(|event| {
+            self.publisher
+                .notify(&TelNotification::PartiallyReceipted(event))
+        })
+    }
+
     pub fn process_signed_query(&self, qr: SignedTelQuery) -> Result<TelReplyType, Error> {
         let signature = qr.signature;
         // check signatures
@@ -126,12 +202,17 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventProcessor<D, K> {
 
 pub enum TelReplyType {
     Tel(Vec<u8>),
+    State(Box<TelStateNotice>),
 }
 
-impl ToString for TelReplyType {
-    fn to_string(&self) -> String {
+impl TelReplyType {
+    /// Encodes reply into bytes sent back to the querying party.
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
         match self {
-            TelReplyType::Tel(tel) => String::from_utf8(tel.to_vec()).unwrap(),
+            TelReplyType::Tel(tel) => Ok(tel.clone()),
+            TelReplyType::State(notice) => {
+                serde_json::to_vec(notice).map_err(|e| Error::EncodingError(e.to_string()))
+            }
         }
     }
 }
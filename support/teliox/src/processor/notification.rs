@@ -62,6 +62,7 @@ pub enum TelNotification {
     MissingRegistry(VerifiableEvent),
     MissingIssuer(VerifiableEvent),
     OutOfOrder(VerifiableEvent),
+    PartiallyReceipted(VerifiableEvent),
     TelEventAdded(VerifiableEvent),
 }
 
@@ -70,6 +71,7 @@ pub enum TelNotificationKind {
     MissingRegistry,
     MissingIssuer,
     OutOfOrder,
+    PartiallyReceipted,
     TelEventAdded,
 }
 
@@ -79,6 +81,7 @@ impl From<&TelNotification> for TelNotificationKind {
             TelNotification::MissingRegistry(_) => Self::MissingRegistry,
             TelNotification::MissingIssuer(_) => Self::MissingIssuer,
             TelNotification::OutOfOrder(_) => Self::OutOfOrder,
+            TelNotification::PartiallyReceipted(_) => Self::PartiallyReceipted,
             TelNotification::TelEventAdded(_) => Self::TelEventAdded,
         }
     }
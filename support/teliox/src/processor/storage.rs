@@ -6,7 +6,7 @@ use crate::{
     database::TelEventDatabase,
     error::Error,
     event::{verifiable_event::VerifiableEvent, Event},
-    query::TelQueryRoute,
+    query::{TelQueryRoute, TelStateNotice},
     state::{vc_state::TelState, ManagerTelState},
 };
 
@@ -42,6 +42,20 @@ impl<D: TelEventDatabase> TelEventStorage<D> {
             .transpose()
     }
 
+    /// Returns current backers of registry and number of their receipts
+    /// required for its events, or None if registry has no backers.
+    pub fn get_backers(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<(Vec<IdentifierPrefix>, u64)>, Error> {
+        let state = self
+            .compute_management_tel_state(id)?
+            .ok_or(Error::MissingRegistryError)?;
+        Ok(state
+            .backers
+            .map(|backers| (backers, state.backer_threshold)))
+    }
+
     pub fn compute_vc_state(&self, vc_id: &IdentifierPrefix) -> Result<Option<TelState>, Error> {
         self.db
             .get_events(vc_id)
@@ -59,6 +73,71 @@ impl<D: TelEventDatabase> TelEventStorage<D> {
             .transpose()
     }
 
+    /// Returns identifiers of known registries, skipping first `start` of
+    /// them and returning at most `limit` of the rest.
+    pub fn list_registries(
+        &self,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        Ok(self
+            .db
+            .get_registries()?
+            .into_iter()
+            .skip(start)
+            .take(limit)
+            .collect())
+    }
+
+    /// Returns identifiers of credentials currently issued in registry,
+    /// paginated as in `list_registries`.
+    pub fn list_issued(
+        &self,
+        registry_id: &IdentifierPrefix,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        self.list_vcs(registry_id, start, limit, |state| {
+            matches!(state, TelState::Issued(_))
+        })
+    }
+
+    /// Returns identifiers of credentials revoked in registry, paginated as
+    /// in `list_registries`.
+    pub fn list_revoked(
+        &self,
+        registry_id: &IdentifierPrefix,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        self.list_vcs(registry_id, start, limit, |state| {
+            matches!(state, TelState::Revoked)
+        })
+    }
+
+    fn list_vcs(
+        &self,
+        registry_id: &IdentifierPrefix,
+        start: usize,
+        limit: usize,
+        selected: impl Fn(&TelState) -> bool,
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        if self.db.get_management_events(registry_id).is_none() {
+            return Err(Error::MissingRegistryError);
+        }
+        self.db
+            .get_registry_vcs(registry_id)?
+            .into_iter()
+            .filter_map(|vc_id| match self.compute_vc_state(&vc_id) {
+                Ok(Some(state)) if selected(&state) => Some(Ok(vc_id)),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .skip(start)
+            .take(limit)
+            .collect()
+    }
+
     pub fn get_management_events(&self, id: &IdentifierPrefix) -> Result<Option<Vec<u8>>, Error> {
         match self.db.get_management_events(id) {
             Some(events) => Ok(Some(
@@ -73,6 +152,21 @@ impl<D: TelEventDatabase> TelEventStorage<D> {
         }
     }
 
+    /// Returns CESR stream of all registry events, followed by events of
+    /// all credentials issued in it, with their seals and receipts
+    /// attached.
+    pub fn export_registry(&self, registry_id: &IdentifierPrefix) -> Result<Vec<u8>, Error> {
+        let mut stream = self
+            .get_management_events(registry_id)?
+            .ok_or(Error::MissingRegistryError)?;
+        for vc_id in self.db.get_registry_vcs(registry_id)? {
+            for event in self.get_events(&vc_id)? {
+                stream.extend(event.serialize()?);
+            }
+        }
+        Ok(stream)
+    }
+
     pub fn get_events(&self, vc_id: &IdentifierPrefix) -> Result<Vec<VerifiableEvent>, Error> {
         match self.db.get_events(vc_id) {
             Some(events) => Ok(events.collect()),
@@ -127,6 +221,110 @@ impl<D: TelEventDatabase> TelEventStorage<D> {
                     Ok(TelReplyType::Tel(management_tel))
                 }
             }
+            TelQueryRoute::Tsn {
+                reply_route: _,
+                args,
+            } => {
+                let registry_id = args
+                    .ri
+                    .as_ref()
+                    .ok_or(Error::Generic("Missing registry identifier".into()))?;
+                let state = self
+                    .compute_management_tel_state(registry_id)?
+                    .ok_or(Error::MissingRegistryError)?;
+                let vc_state = args
+                    .i
+                    .as_ref()
+                    .map(|vc_id| Ok::<_, Error>(self.compute_vc_state(vc_id)?.unwrap_or_default()))
+                    .transpose()?;
+                Ok(TelReplyType::State(Box::new(TelStateNotice::new(
+                    state,
+                    args.i.clone(),
+                    vc_state,
+                ))))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use keri_core::{
+        prefix::{BasicPrefix, IdentifierPrefix},
+        signer::Signer,
+    };
+    use said::derivation::{HashFunction, HashFunctionCode};
+    use tempfile::Builder;
+
+    use crate::{
+        database::{redb::RedbTelDatabase, TelEventDatabase},
+        error::Error,
+        event::verifiable_event::VerifiableEvent,
+        processor::storage::TelEventStorage,
+        seal::AttachedSourceSeal,
+        tel::event_generator,
+    };
+
+    #[test]
+    pub fn test_registry_inventory() -> Result<(), Error> {
+        let tel_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let storage = TelEventStorage::new(Arc::new(RedbTelDatabase::new(tel_root.path())?));
+        // Events are saved without validation, so their seals don't matter.
+        let seal = AttachedSourceSeal::new(
+            1,
+            HashFunction::from(HashFunctionCode::Blake3_256).derive(b"ixn"),
+        );
+        let save = |event| storage.add_event(VerifiableEvent::new(event, seal.clone()));
+
+        let mut registries = vec![];
+        for _ in 0..2 {
+            let issuer = IdentifierPrefix::Basic(BasicPrefix::Ed25519(Signer::new().public_key()));
+            let vcp = event_generator::make_inception_event(issuer, vec![], 0, vec![], None, None)?;
+            registries.push(vcp.get_prefix());
+            save(vcp)?;
+        }
+        let mut listed = storage.list_registries(0, 10)?;
+        listed.sort_by_key(|id| id.to_string());
+        registries.sort_by_key(|id| id.to_string());
+        assert_eq!(listed, registries);
+        assert_eq!(storage.list_registries(1, 10)?.len(), 1);
+        assert_eq!(storage.list_registries(0, 1)?.len(), 1);
+
+        let registry = &registries[0];
+        let state = storage.compute_management_tel_state(registry)?.unwrap();
+        let mut credentials = vec![];
+        for name in ["first", "second", "third"] {
+            let vc = HashFunction::from(HashFunctionCode::Blake3_256).derive(name.as_bytes());
+            let iss = event_generator::make_issuance_event(&state, vc.clone(), None, None)?;
+            credentials.push((vc, iss.get_digest()?));
+            save(iss)?;
+        }
+        let (revoked, iss_digest) = &credentials[1];
+        save(event_generator::make_revoke_event(
+            revoked,
+            iss_digest.clone(),
+            &state,
+            None,
+            None,
+        )?)?;
+
+        let issued = storage.list_issued(registry, 0, 10)?;
+        assert_eq!(issued.len(), 2);
+        assert!(!issued.contains(&IdentifierPrefix::self_addressing(revoked.clone())));
+        assert_eq!(storage.list_issued(registry, 1, 10)?, issued[1..]);
+        assert_eq!(storage.list_issued(registry, 0, 1)?, issued[..1]);
+        assert_eq!(
+            storage.list_revoked(registry, 0, 10)?,
+            vec![IdentifierPrefix::self_addressing(revoked.clone())]
+        );
+        assert!(storage.list_issued(&registries[1], 0, 10)?.is_empty());
+        assert!(matches!(
+            storage.list_issued(&state.issuer, 0, 10),
+            Err(Error::MissingRegistryError)
+        ));
+
+        Ok(())
+    }
+}
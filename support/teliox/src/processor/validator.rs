@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
 use keri_core::{
-    database::EventDatabase,
-    event::{event_data::EventData, sections::seal::Seal},
+    database::{EventDatabase, QueryParameters},
+    event::{event_data::EventData, sections::seal::Seal, KeyEvent},
+    event_message::msg::KeriEvent,
     prefix::IdentifierPrefix,
     processor::event_storage::EventStorage,
 };
@@ -18,6 +19,7 @@ use crate::{
         Event,
     },
     seal::AttachedSourceSeal,
+    state::{vc_state::TelState, ManagerTelState},
 };
 
 use super::TelEventStorage;
@@ -36,6 +38,8 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventValidator<D, K> {
     }
 
     /// Checks if kel event pointed by seal has seal to tel event inside.
+    /// Fails with `AnchorMismatchError` if the event exists, but doesn't
+    /// anchor tel event.
     pub fn check_kel_event(
         kel_reference: Arc<EventStorage<K>>,
         seal: &AttachedSourceSeal,
@@ -45,49 +49,69 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventValidator<D, K> {
         let reference_kel_event = kel_reference
             .get_event_at_sn(issuer_id, seal.seal.sn)
             .ok_or(Error::MissingIssuerEventError)?;
-        // Check if digest of found event matches digest from seal
-        match &reference_kel_event
-            .signed_event_message
-            .event_message
-            .digest()
-        {
-            Ok(dig) if dig == &seal.seal.digest => Ok(()),
-            _ => Err(Error::DigestsNotMatchError),
-        }?;
-        // Check if found event has tel event anchored
-        let event_type = reference_kel_event
-            .signed_event_message
-            .event_message
-            .data
-            .event_data;
-        if let EventData::Ixn(ixn) = event_type {
-            if ixn.data.into_iter().any(|seal| match seal {
-                Seal::Event(es) => es.event_digest().eq(&expected_digest),
-                _ => false,
-            }) {
-                Ok(())
-            } else {
-                Err(Error::MissingSealError)
-            }
-        } else {
-            Err(Error::Generic("Wrong event type".to_string()))
+        let message = &reference_kel_event.signed_event_message.event_message;
+        // Check if digest of found event matches digest from seal and if it
+        // has tel event anchored
+        match message.digest() {
+            Ok(dig) if dig == seal.seal.digest && anchors(message, &expected_digest) => Ok(()),
+            _ => Err(Error::AnchorMismatchError {
+                issuer: issuer_id.clone(),
+                sn: seal.seal.sn,
+            }),
+        }
+    }
+
+    /// Finds event of issuer's KEL which anchors tel event and returns
+    /// seal pointing to it. Fails with `MissingIssuerEventError` if no
+    /// known KEL event anchors it.
+    pub fn locate_anchor(&self, event: &Event) -> Result<AttachedSourceSeal, Error> {
+        let issuer_id = self.issuer(event)?;
+        let expected_digest = event.get_digest()?;
+        let seal = self
+            .kel_reference
+            .events_db
+            .get_kel_finalized_events(QueryParameters::All { id: &issuer_id })
+            .into_iter()
+            .flatten()
+            .map(|kel_event| kel_event.signed_event_message.event_message)
+            .find(|message| anchors(message, &expected_digest))
+            .and_then(|message| {
+                let digest = message.digest().ok()?;
+                Some(AttachedSourceSeal::new(message.data.get_sn(), digest))
+            })
+            .ok_or(Error::MissingIssuerEventError);
+        seal
+    }
+
+    /// Returns identifier whose KEL anchors tel event.
+    fn issuer(&self, event: &Event) -> Result<IdentifierPrefix, Error> {
+        match event {
+            Event::Management(man) => self.management_issuer(man),
+            Event::Vc(vc) => self.registry_issuer(&vc.data.data.registry_id()?),
         }
     }
 
+    fn management_issuer(&self, event: &ManagerTelEventMessage) -> Result<IdentifierPrefix, Error> {
+        match &event.data.event_type {
+            ManagerEventType::Vcp(vcp) => Ok(vcp.issuer_id.clone()),
+            ManagerEventType::Vrt(_vrt) => self.registry_issuer(&event.data.prefix),
+        }
+    }
+
+    fn registry_issuer(&self, registry_id: &IdentifierPrefix) -> Result<IdentifierPrefix, Error> {
+        Ok(self
+            .db
+            .compute_management_tel_state(registry_id)?
+            .ok_or(Error::MissingRegistryError)?
+            .issuer)
+    }
+
     pub fn validate_management(
         &self,
         event: &ManagerTelEventMessage,
         seal: &AttachedSourceSeal,
     ) -> Result<(), Error> {
-        let id = match &event.data.event_type {
-            ManagerEventType::Vcp(vcp) => vcp.issuer_id.clone(),
-            ManagerEventType::Vrt(_vrt) => {
-                self.db
-                    .compute_management_tel_state(&event.data.prefix)?
-                    .ok_or(Error::MissingRegistryError)?
-                    .issuer
-            }
-        };
+        let id = self.management_issuer(event)?;
 
         Self::check_kel_event(
             self.kel_reference.clone(),
@@ -111,12 +135,7 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventValidator<D, K> {
         vc_event: &VCEventMessage,
         seal: &AttachedSourceSeal,
     ) -> Result<(), Error> {
-        let registry_id = vc_event.data.data.registry_id()?;
-        let issuer_id = self
-            .db
-            .compute_management_tel_state(&registry_id)?
-            .ok_or(Error::MissingRegistryError)?
-            .issuer;
+        let issuer_id = self.registry_issuer(&vc_event.data.data.registry_id()?)?;
         Self::check_kel_event(
             self.kel_reference.clone(),
             seal,
@@ -130,10 +149,533 @@ impl<D: TelEventDatabase, K: EventDatabase> TelEventValidator<D, K> {
 
         Ok(())
     }
+
+    /// Checks if event is receipted by enough backers of its registry.
+    /// Backers are taken from registry state the event results in, so
+    /// registry rotation needs to be receipted by the new backers.
+    pub fn check_backer_receipts(&self, verifiable_event: &VerifiableEvent) -> Result<(), Error> {
+        let state = match &verifiable_event.event {
+            Event::Management(man) => self
+                .db
+                .compute_management_tel_state(&man.data.prefix)?
+                .unwrap_or_default()
+                .apply(man)?,
+            Event::Vc(vc) => self
+                .db
+                .compute_management_tel_state(&vc.data.data.registry_id()?)?
+                .ok_or(Error::MissingRegistryError)?,
+        };
+        Self::check_receipts(&state, verifiable_event)
+    }
+
+    /// Checks if event is receipted by enough backers of given registry
+    /// state.
+    fn check_receipts(
+        state: &ManagerTelState,
+        verifiable_event: &VerifiableEvent,
+    ) -> Result<(), Error> {
+        let backers = match &state.backers {
+            Some(backers) => backers,
+            None => return Ok(()),
+        };
+        let data = verifiable_event.event.serialize()?;
+        let mut receipted = vec![];
+        for (backer, signature) in &verifiable_event.receipts {
+            if backers.contains(&IdentifierPrefix::Basic(backer.clone()))
+                && !receipted.contains(&backer)
+                && matches!(backer.verify(&data, signature), Ok(true))
+            {
+                receipted.push(backer);
+            }
+        }
+        if (receipted.len() as u64) < state.backer_threshold {
+            return Err(Error::NotEnoughReceiptsError);
+        }
+        Ok(())
+    }
+
     pub fn validate(&self, verifiable_event: &VerifiableEvent) -> Result<(), Error> {
         match verifiable_event.event {
             Event::Management(ref man) => self.validate_management(man, &verifiable_event.seal),
             Event::Vc(ref vc) => self.validate_vc(vc, &verifiable_event.seal),
+        }?;
+        self.check_backer_receipts(verifiable_event)
+    }
+
+    /// Replays all saved events of registry and its credentials from
+    /// scratch. Checks digest chaining of events, their anchors in
+    /// issuer's KEL and backer receipts. Returns error pointing to the
+    /// first inconsistent event.
+    pub fn verify_tel(&self, registry_id: &IdentifierPrefix) -> Result<(), Error> {
+        // Registry states after each management event, indexed by sn.
+        let mut states: Vec<ManagerTelState> = vec![];
+        let management_events = self
+            .db
+            .db
+            .get_management_events(registry_id)
+            .ok_or(Error::MissingRegistryError)?;
+        for verifiable_event in management_events {
+            let sn = verifiable_event.event.get_sn();
+            let state = self
+                .replay_management(states.last(), &verifiable_event)
+                .map_err(|e| inconsistent(registry_id, sn, e))?;
+            states.push(state);
         }
+        if states.is_empty() {
+            return Err(Error::MissingRegistryError);
+        }
+
+        for vc_id in self.db.db.get_registry_vcs(registry_id)? {
+            let mut vc_state = TelState::default();
+            for (sn, verifiable_event) in self.db.get_events(&vc_id)?.into_iter().enumerate() {
+                vc_state = self
+                    .replay_vc(
+                        &vc_state,
+                        &states,
+                        registry_id,
+                        sn as u64,
+                        &verifiable_event,
+                    )
+                    .map_err(|e| inconsistent(&vc_id, sn as u64, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn replay_management(
+        &self,
+        state: Option<&ManagerTelState>,
+        verifiable_event: &VerifiableEvent,
+    ) -> Result<ManagerTelState, Error> {
+        let Event::Management(man) = &verifiable_event.event else {
+            return Err(Error::Generic("Wrong event type".to_string()));
+        };
+        let issuer_id = match (&man.data.event_type, state) {
+            (ManagerEventType::Vcp(vcp), _) => vcp.issuer_id.clone(),
+            (ManagerEventType::Vrt(_), Some(state)) => state.issuer.clone(),
+            (ManagerEventType::Vrt(_), None) => return Err(Error::OutOfOrderError),
+        };
+        Self::check_kel_event(
+            self.kel_reference.clone(),
+            &verifiable_event.seal,
+            &issuer_id,
+            man.digest()?,
+        )?;
+        let new_state = state.cloned().unwrap_or_default().apply(man)?;
+        Self::check_receipts(&new_state, verifiable_event)?;
+        Ok(new_state)
+    }
+
+    fn replay_vc(
+        &self,
+        vc_state: &TelState,
+        registry_states: &[ManagerTelState],
+        registry_id: &IdentifierPrefix,
+        sn: u64,
+        verifiable_event: &VerifiableEvent,
+    ) -> Result<TelState, Error> {
+        let Event::Vc(vc) = &verifiable_event.event else {
+            return Err(Error::Generic("Wrong event type".to_string()));
+        };
+        if vc.data.data.sn != sn {
+            return Err(Error::OutOfOrderError);
+        }
+        if &vc.data.data.registry_id()? != registry_id {
+            return Err(Error::Generic("Registry doesn't match".to_string()));
+        }
+        // Backed events point to registry state they were issued in.
+        // Otherwise current registry state is used.
+        let registry_state = match vc.data.data.registry_anchor() {
+            Some(anchor) => {
+                let state = registry_states
+                    .get(anchor.sn as usize)
+                    .ok_or(Error::OutOfOrderError)?;
+                if state.last != anchor.event_digest() {
+                    return Err(Error::DigestsNotMatchError);
+                }
+                state
+            }
+            None => registry_states.last().ok_or(Error::MissingRegistryError)?,
+        };
+        Self::check_kel_event(
+            self.kel_reference.clone(),
+            &verifiable_event.seal,
+            &registry_state.issuer,
+            vc.digest()?,
+        )?;
+        Self::check_receipts(registry_state, verifiable_event)?;
+        vc_state.apply(vc)
+    }
+}
+
+/// Checks if KEL event is interaction event holding seal of tel event.
+fn anchors(message: &KeriEvent<KeyEvent>, expected_digest: &SelfAddressingIdentifier) -> bool {
+    match &message.data.event_data {
+        EventData::Ixn(ixn) => ixn.data.iter().any(|seal| match seal {
+            Seal::Event(es) => es.event_digest().eq(expected_digest),
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+fn inconsistent(id: &IdentifierPrefix, sn: u64, reason: Error) -> Error {
+    Error::InconsistentTelError {
+        id: id.clone(),
+        sn,
+        reason: Box::new(reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use keri_core::{
+        actor::{event_generator as kel_generator, prelude::Message},
+        database::redb::RedbDatabase,
+        event::{
+            sections::seal::{EventSeal, Seal},
+            KeyEvent,
+        },
+        event_message::{
+            cesr_adapter::{parse_event_type, EventType},
+            msg::KeriEvent,
+            signed_event_message::Notice,
+        },
+        prefix::{BasicPrefix, IdentifierPrefix, IndexedSignature, SelfSigningPrefix},
+        processor::{basic_processor::BasicProcessor, event_storage::EventStorage, Processor},
+        signer::Signer,
+    };
+    use said::derivation::{HashFunction, HashFunctionCode};
+    use tempfile::Builder;
+
+    use crate::{
+        database::{redb::RedbTelDatabase, EscrowDatabase, TelEventDatabase},
+        error::Error,
+        event::{verifiable_event::VerifiableEvent, Event},
+        processor::{
+            escrow::default_escrow_bus, TelEventProcessor, TelEventStorage, TelEventValidator,
+        },
+        seal::AttachedSourceSeal,
+        state::vc_state::TelState,
+        tel::event_generator,
+    };
+
+    fn process_kel_event(
+        processor: &BasicProcessor<RedbDatabase>,
+        signer: &Signer,
+        event: KeriEvent<KeyEvent>,
+    ) {
+        let signature =
+            SelfSigningPrefix::Ed25519Sha512(signer.sign(event.encode().unwrap()).unwrap());
+        let signed = event.sign(
+            vec![IndexedSignature::new_both_same(signature, 0)],
+            None,
+            None,
+        );
+        processor
+            .process(&Message::Notice(Notice::Event(signed)))
+            .unwrap();
+    }
+
+    /// Anchors TEL event in issuer's KEL and attaches source seal to it.
+    fn anchor(
+        processor: &BasicProcessor<RedbDatabase>,
+        storage: &EventStorage<RedbDatabase>,
+        signer: &Signer,
+        issuer: &IdentifierPrefix,
+        event: Event,
+    ) -> VerifiableEvent {
+        let seal = Seal::Event(EventSeal::new(
+            event.get_prefix(),
+            event.get_sn(),
+            event.get_digest().unwrap(),
+        ));
+        let ixn =
+            kel_generator::anchor_with_seal(storage.get_state(issuer).unwrap(), &[seal]).unwrap();
+        let source_seal = AttachedSourceSeal::new(ixn.data.get_sn(), ixn.digest().unwrap());
+        process_kel_event(processor, signer, ixn);
+        VerifiableEvent::new(event, source_seal)
+    }
+
+    fn receipt(backer: &Signer, event: &VerifiableEvent) -> (BasicPrefix, SelfSigningPrefix) {
+        let data = event.event.serialize().unwrap();
+        (
+            BasicPrefix::Ed25519NT(backer.public_key()),
+            SelfSigningPrefix::Ed25519Sha512(backer.sign(data).unwrap()),
+        )
+    }
+
+    #[test]
+    pub fn test_backer_receipts() -> Result<(), Error> {
+        let keri_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let keri_db = Arc::new(RedbDatabase::new(keri_root.path()).unwrap());
+        let keri_processor = BasicProcessor::new(keri_db.clone(), None);
+        let keri_storage = Arc::new(EventStorage::new(keri_db));
+
+        let signer = Signer::new();
+        let icp = kel_generator::incept(
+            vec![BasicPrefix::Ed25519(signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            vec![],
+            0,
+            None,
+        )
+        .unwrap();
+        let EventType::KeyEvent(icp) = parse_event_type(icp.as_bytes()).unwrap() else {
+            unreachable!()
+        };
+        let issuer = icp.data.get_prefix();
+        process_kel_event(&keri_processor, &signer, icp);
+
+        let tel_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let tel_escrow_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let tel_db = Arc::new(RedbTelDatabase::new(tel_root.path()).unwrap());
+        let (tel_bus, _, _, _, _) = default_escrow_bus(
+            tel_db.clone(),
+            keri_storage.clone(),
+            EscrowDatabase::new(tel_escrow_root.path()).unwrap(),
+        )?;
+        let tel_storage = Arc::new(TelEventStorage::new(tel_db));
+        let processor =
+            TelEventProcessor::new(keri_storage.clone(), tel_storage.clone(), Some(tel_bus));
+
+        let backers: Vec<Signer> = (0..3).map(|_| Signer::new()).collect();
+        let backer_ids: Vec<IdentifierPrefix> = backers
+            .iter()
+            .map(|backer| IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(backer.public_key())))
+            .collect();
+        assert!(event_generator::make_inception_event(
+            issuer.clone(),
+            vec![],
+            4,
+            backer_ids.clone(),
+            None,
+            None
+        )
+        .is_err());
+        let vcp = event_generator::make_inception_event(
+            issuer.clone(),
+            vec![],
+            2,
+            backer_ids,
+            None,
+            None,
+        )?;
+        let registry = vcp.get_prefix();
+        let mut vcp = anchor(&keri_processor, &keri_storage, &signer, &issuer, vcp);
+
+        // Receipts of non-backers don't count to the threshold. Event is
+        // escrowed until enough receipts arrive.
+        let receipts = vec![receipt(&backers[0], &vcp), receipt(&Signer::new(), &vcp)];
+        vcp.add_receipts(receipts);
+        processor.process(vcp.clone())?;
+        assert_eq!(tel_storage.compute_management_tel_state(&registry)?, None);
+
+        // Missing receipt can arrive alone. It's merged with escrowed ones.
+        let mut late_receipt = VerifiableEvent::new(vcp.event.clone(), vcp.seal.clone());
+        late_receipt.add_receipts(vec![receipt(&backers[1], &vcp)]);
+        vcp.add_receipts(late_receipt.receipts.clone());
+        assert_eq!(
+            VerifiableEvent::parse(&vcp.serialize()?)?,
+            vec![vcp.clone()]
+        );
+        processor.process(late_receipt)?;
+        let state = tel_storage
+            .compute_management_tel_state(&registry)?
+            .unwrap();
+        assert_eq!(state.backer_threshold, 2);
+        assert_eq!(state.backers.as_ref().map(|backers| backers.len()), Some(3));
+
+        // Credential events need backer receipts too.
+        let vc = HashFunction::from(HashFunctionCode::Blake3_256).derive(b"credential");
+        let iss = event_generator::make_issuance_event(&state, vc.clone(), None, None)?;
+        let mut iss = anchor(&keri_processor, &keri_storage, &signer, &issuer, iss);
+        let vc_id = IdentifierPrefix::self_addressing(vc.clone());
+        processor.process(iss.clone())?;
+        assert_eq!(tel_storage.compute_vc_state(&vc_id)?, None);
+        let receipts = vec![receipt(&backers[1], &iss), receipt(&backers[2], &iss)];
+        iss.add_receipts(receipts);
+        processor.process(iss)?;
+        assert!(matches!(
+            tel_storage.compute_vc_state(&vc_id)?,
+            Some(TelState::Issued(_))
+        ));
+
+        // Rotation replacing backer needs to be receipted by new backers.
+        let new_backer = Signer::new();
+        let new_backer_id =
+            IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(new_backer.public_key()));
+        let removed_backer_id =
+            IdentifierPrefix::Basic(BasicPrefix::Ed25519NT(backers[0].public_key()));
+        let vrt = event_generator::make_rotation_event(
+            &state,
+            std::slice::from_ref(&new_backer_id),
+            std::slice::from_ref(&removed_backer_id),
+            None,
+            None,
+        )?;
+        let mut vrt = anchor(&keri_processor, &keri_storage, &signer, &issuer, vrt);
+        let receipts = vec![receipt(&backers[0], &vrt), receipt(&backers[1], &vrt)];
+        vrt.add_receipts(receipts);
+        processor.process(vrt.clone())?;
+        assert_eq!(
+            tel_storage.compute_management_tel_state(&registry)?,
+            Some(state)
+        );
+        let receipts = vec![receipt(&new_backer, &vrt)];
+        vrt.add_receipts(receipts);
+        processor.process(vrt)?;
+
+        let (current_backers, threshold) = tel_storage.get_backers(&registry)?.unwrap();
+        assert_eq!(threshold, 2);
+        assert_eq!(current_backers.len(), 3);
+        assert!(current_backers.contains(&new_backer_id));
+        assert!(!current_backers.contains(&removed_backer_id));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_verify_tel() -> Result<(), Error> {
+        let keri_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let keri_db = Arc::new(RedbDatabase::new(keri_root.path()).unwrap());
+        let keri_processor = BasicProcessor::new(keri_db.clone(), None);
+        let keri_storage = Arc::new(EventStorage::new(keri_db));
+
+        let signer = Signer::new();
+        let icp = kel_generator::incept(
+            vec![BasicPrefix::Ed25519(signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            vec![],
+            0,
+            None,
+        )
+        .unwrap();
+        let EventType::KeyEvent(icp) = parse_event_type(icp.as_bytes()).unwrap() else {
+            unreachable!()
+        };
+        let issuer = icp.data.get_prefix();
+        process_kel_event(&keri_processor, &signer, icp);
+
+        let tel_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let tel_storage = Arc::new(TelEventStorage::new(Arc::new(
+            RedbTelDatabase::new(tel_root.path()).unwrap(),
+        )));
+        let processor = TelEventProcessor::new(keri_storage.clone(), tel_storage.clone(), None);
+
+        let vcp =
+            event_generator::make_inception_event(issuer.clone(), vec![], 0, vec![], None, None)?;
+        let registry = vcp.get_prefix();
+        let vcp = anchor(&keri_processor, &keri_storage, &signer, &issuer, vcp);
+        processor.process(vcp)?;
+        let state = tel_storage
+            .compute_management_tel_state(&registry)?
+            .unwrap();
+
+        let vc = HashFunction::from(HashFunctionCode::Blake3_256).derive(b"credential");
+        let iss = event_generator::make_issuance_event(&state, vc.clone(), None, None)?;
+        let iss_digest = iss.get_digest()?;
+        let iss = anchor(&keri_processor, &keri_storage, &signer, &issuer, iss);
+        processor.process(iss)?;
+        let rev = event_generator::make_revoke_event(&vc, iss_digest, &state, None, None)?;
+        let rev = anchor(&keri_processor, &keri_storage, &signer, &issuer, rev);
+        processor.process(rev.clone())?;
+        processor.verify_tel(&registry)?;
+
+        // Event saved without validation is anchored in KEL event that
+        // doesn't contain its seal.
+        let other_vc = HashFunction::from(HashFunctionCode::Blake3_256).derive(b"other credential");
+        let other_iss = event_generator::make_issuance_event(&state, other_vc.clone(), None, None)?;
+        tel_storage.add_event(VerifiableEvent::new(other_iss, rev.seal.clone()))?;
+        match processor.verify_tel(&registry) {
+            Err(Error::InconsistentTelError { id, sn, reason }) => {
+                assert_eq!(id, IdentifierPrefix::self_addressing(other_vc));
+                assert_eq!(sn, 0);
+                assert!(matches!(*reason, Error::AnchorMismatchError { .. }));
+            }
+            other => panic!("Unexpected verification result: {:?}", other),
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_locate_anchor() -> Result<(), Error> {
+        let keri_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let keri_db = Arc::new(RedbDatabase::new(keri_root.path()).unwrap());
+        let keri_processor = BasicProcessor::new(keri_db.clone(), None);
+        let keri_storage = Arc::new(EventStorage::new(keri_db));
+
+        let signer = Signer::new();
+        let icp = kel_generator::incept(
+            vec![BasicPrefix::Ed25519(signer.public_key())],
+            vec![BasicPrefix::Ed25519(Signer::new().public_key())],
+            vec![],
+            0,
+            None,
+        )
+        .unwrap();
+        let EventType::KeyEvent(icp) = parse_event_type(icp.as_bytes()).unwrap() else {
+            unreachable!()
+        };
+        let issuer = icp.data.get_prefix();
+        process_kel_event(&keri_processor, &signer, icp);
+
+        let tel_root = Builder::new().prefix("test-db").tempfile().unwrap();
+        let tel_storage = Arc::new(TelEventStorage::new(Arc::new(
+            RedbTelDatabase::new(tel_root.path()).unwrap(),
+        )));
+        let processor = TelEventProcessor::new(keri_storage.clone(), tel_storage.clone(), None);
+
+        let vcp =
+            event_generator::make_inception_event(issuer.clone(), vec![], 0, vec![], None, None)?;
+        let registry = vcp.get_prefix();
+        // Registry isn't anchored yet.
+        assert!(matches!(
+            processor.process_event(vcp.clone()),
+            Err(Error::MissingIssuerEventError)
+        ));
+        let anchored_vcp = anchor(
+            &keri_processor,
+            &keri_storage,
+            &signer,
+            &issuer,
+            vcp.clone(),
+        );
+        processor.process_event(vcp)?;
+        let state = tel_storage
+            .compute_management_tel_state(&registry)?
+            .unwrap();
+
+        let vc = HashFunction::from(HashFunctionCode::Blake3_256).derive(b"credential");
+        let iss = event_generator::make_issuance_event(&state, vc.clone(), None, None)?;
+        let anchored_iss = anchor(
+            &keri_processor,
+            &keri_storage,
+            &signer,
+            &issuer,
+            iss.clone(),
+        );
+        // Seal pointing to KEL event that doesn't anchor the event.
+        match processor.process(VerifiableEvent::new(iss.clone(), anchored_vcp.seal)) {
+            Err(Error::AnchorMismatchError { issuer: id, sn }) => {
+                assert_eq!((id, sn), (issuer.clone(), 1));
+            }
+            other => panic!("Unexpected processing result: {:?}", other),
+        };
+        assert_eq!(
+            TelEventValidator::new(tel_storage.clone(), keri_storage.clone())
+                .locate_anchor(&iss)?,
+            anchored_iss.seal
+        );
+        processor.process_event(iss)?;
+        assert!(matches!(
+            tel_storage.compute_vc_state(&IdentifierPrefix::self_addressing(vc))?,
+            Some(TelState::Issued(_))
+        ));
+
+        Ok(())
     }
 }
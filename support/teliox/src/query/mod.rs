@@ -3,7 +3,15 @@ use keri_core::{
     prefix::IdentifierPrefix,
     query::query_event::SignedQuery,
 };
+use said::SelfAddressingIdentifier;
 use serde::{Deserialize, Serialize};
+use serde_hex::{Compact, SerHex};
+
+use crate::{
+    error::Error,
+    event::{verifiable_event::VerifiableEvent, Event},
+    state::{vc_state::TelState, ManagerTelState},
+};
 
 pub type QueryEvent = KeriEvent<Timestamped<TelQueryRoute>>;
 
@@ -17,6 +25,14 @@ pub enum TelQueryRoute {
         #[serde(rename = "q")]
         args: TelQueryArgs,
     },
+    /// Asks for TEL state notice of registry `ri` and credential `i`.
+    #[serde(rename = "tsn")]
+    Tsn {
+        #[serde(rename = "rr")]
+        reply_route: String,
+        #[serde(rename = "q")]
+        args: TelQueryArgs,
+    },
 }
 
 impl Typeable for TelQueryRoute {
@@ -34,6 +50,69 @@ pub struct TelQueryArgs {
     pub ri: Option<IdentifierPrefix>,
 }
 
+/// TEL state notice, i.e. current state of registry and, if it was asked
+/// for, of credential issued in it, as known to responder.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TelStateNotice {
+    #[serde(rename = "ri")]
+    pub registry_id: IdentifierPrefix,
+    #[serde(rename = "s", with = "SerHex::<Compact>")]
+    pub sn: u64,
+    #[serde(rename = "d")]
+    pub last: SelfAddressingIdentifier,
+    #[serde(rename = "b", skip_serializing_if = "Option::is_none")]
+    pub backers: Option<Vec<IdentifierPrefix>>,
+    #[serde(rename = "i", skip_serializing_if = "Option::is_none")]
+    pub vc_id: Option<IdentifierPrefix>,
+    #[serde(rename = "vs", skip_serializing_if = "Option::is_none")]
+    pub vc_state: Option<TelState>,
+}
+
+impl TelStateNotice {
+    pub fn new(
+        state: ManagerTelState,
+        vc_id: Option<IdentifierPrefix>,
+        vc_state: Option<TelState>,
+    ) -> Self {
+        Self {
+            registry_id: state.prefix,
+            sn: state.sn,
+            last: state.last,
+            backers: state.backers,
+            vc_id,
+            vc_state,
+        }
+    }
+
+    /// Computes state notice of registry `registry_id` (and of credential
+    /// `vc_id`, if provided) from not yet verified TEL events, e.g. the ones
+    /// collected from backers. Events of other registries and credentials are
+    /// skipped.
+    pub fn from_events(
+        registry_id: &IdentifierPrefix,
+        vc_id: Option<&IdentifierPrefix>,
+        events: impl IntoIterator<Item = VerifiableEvent>,
+    ) -> Result<Self, Error> {
+        let mut state = ManagerTelState::default();
+        let mut vc_state = TelState::default();
+        for event in events {
+            match event.event {
+                Event::Management(man) if &man.data.prefix == registry_id => {
+                    state = state.apply(&man)?
+                }
+                Event::Vc(vc) if Some(&vc.data.data.prefix) == vc_id => {
+                    vc_state = vc_state.apply(&vc)?
+                }
+                _ => (),
+            }
+        }
+        if state == ManagerTelState::default() {
+            return Err(Error::MissingRegistryError);
+        }
+        Ok(Self::new(state, vc_id.cloned(), vc_id.map(|_| vc_state)))
+    }
+}
+
 pub type SignedTelQuery = SignedQuery<TelQueryEvent>;
 pub type TelQueryEvent = KeriEvent<Timestamped<TelQueryRoute>>;
 
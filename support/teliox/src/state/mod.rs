@@ -16,13 +16,15 @@ pub enum State {
     Tel(TelState),
 }
 
-#[derive(Default, PartialEq, Eq, Debug)]
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
 pub struct ManagerTelState {
     pub prefix: IdentifierPrefix,
     pub sn: u64,
     pub last: SelfAddressingIdentifier,
     pub issuer: IdentifierPrefix,
     pub backers: Option<Vec<IdentifierPrefix>>,
+    /// Number of backer receipts required for registry events.
+    pub backer_threshold: u64,
 }
 
 impl ManagerTelState {
@@ -36,10 +38,10 @@ impl ManagerTelState {
                 if self != &ManagerTelState::default() {
                     Err(Error::EventAlreadySavedError)
                 } else {
-                    let backers = if vcp.config.contains(&Config::NoBackers) {
-                        None
+                    let (backers, backer_threshold) = if vcp.config.contains(&Config::NoBackers) {
+                        (None, 0)
                     } else {
-                        Some(vcp.backers.clone())
+                        (Some(vcp.backers.clone()), vcp.backer_threshold)
                     };
                     Ok(ManagerTelState {
                         prefix: event_content.prefix.to_owned(),
@@ -47,34 +49,23 @@ impl ManagerTelState {
                         last: event.digest()?,
                         issuer: vcp.issuer_id.clone(),
                         backers,
+                        backer_threshold,
                     })
                 }
             }
             ManagerEventType::Vrt(ref vrt) => {
                 if self.sn + 1 == event_content.sn {
                     if vrt.prev_event.eq(&self.last) {
-                        match self.backers {
-                            Some(ref backers) => {
-                                let mut new_backers: Vec<IdentifierPrefix> = backers
-                                    .iter()
-                                    .filter(|backer| !backers.contains(backer))
-                                    .map(|x| x.to_owned())
-                                    .collect();
-                                vrt.backers_to_add
-                                    .iter()
-                                    .for_each(|ba| new_backers.push(ba.to_owned()));
-                                Ok(ManagerTelState {
-                                    prefix: self.prefix.to_owned(),
-                                    sn: self.sn + 1,
-                                    last: event.digest()?,
-                                    backers: Some(new_backers),
-                                    issuer: self.issuer.clone(),
-                                })
-                            }
-                            None => Err(Error::Generic(
-                                "Trying to update backers of backerless state".into(),
-                            )),
-                        }
+                        Ok(ManagerTelState {
+                            prefix: self.prefix.to_owned(),
+                            sn: self.sn + 1,
+                            last: event.digest()?,
+                            backers: Some(
+                                self.rotate_backers(&vrt.backers_to_add, &vrt.backers_to_remove)?,
+                            ),
+                            issuer: self.issuer.clone(),
+                            backer_threshold: self.backer_threshold,
+                        })
                     } else {
                         Err(Error::Generic("Previous event doesn't match".to_string()))
                     }
@@ -84,6 +75,38 @@ impl ManagerTelState {
             }
         }
     }
+
+    /// Returns backers of registry after removing `br` and adding `ba`.
+    /// Only current backers can be removed and added ones can't be
+    /// backers already. Enough backers need to be left to meet the
+    /// backer threshold.
+    pub fn rotate_backers(
+        &self,
+        ba: &[IdentifierPrefix],
+        br: &[IdentifierPrefix],
+    ) -> Result<Vec<IdentifierPrefix>, Error> {
+        let backers = self.backers.as_ref().ok_or(Error::Generic(
+            "Trying to update backers of backerless state".into(),
+        ))?;
+        if br.iter().any(|backer| !backers.contains(backer)) {
+            return Err(Error::Generic("Removed backer is not a backer".into()));
+        }
+        let mut new_backers: Vec<IdentifierPrefix> = backers
+            .iter()
+            .filter(|backer| !br.contains(backer))
+            .map(|x| x.to_owned())
+            .collect();
+        for backer in ba {
+            if new_backers.contains(backer) || br.contains(backer) {
+                return Err(Error::Generic("Added backer is already a backer".into()));
+            }
+            new_backers.push(backer.to_owned());
+        }
+        if self.backer_threshold > new_backers.len() as u64 {
+            return Err(Error::Generic("Improper backer threshold".into()));
+        }
+        Ok(new_backers)
+    }
 }
 
 #[cfg(test)]
@@ -131,6 +154,36 @@ mod tests {
         assert_eq!(state.sn, 1);
         assert_eq!(state.prefix, rct.get_prefix());
         assert_eq!(state.last, rct.get_digest()?);
+        assert_eq!(state.backers, Some(backers_to_add.clone()));
+
+        // Backers need to be known to be removed and can't be added twice.
+        let other_backer: IdentifierPrefix = "EHvR3p8V95W8J7Ui4-mEzZ79S-A1esAnJo1Kmzq80Jkc"
+            .parse()
+            .unwrap();
+        assert!(event_generator::make_rotation_event(
+            &state,
+            &[],
+            std::slice::from_ref(&other_backer),
+            None,
+            None
+        )
+        .is_err());
+        assert!(
+            event_generator::make_rotation_event(&state, &backers_to_add, &[], None, None).is_err()
+        );
+
+        let rct = event_generator::make_rotation_event(
+            &state,
+            std::slice::from_ref(&other_backer),
+            &backers_to_add,
+            None,
+            None,
+        )?;
+        if let Event::Management(event) = &rct {
+            state = state.apply(event)?;
+        }
+        assert_eq!(state.sn, 2);
+        assert_eq!(state.backers, Some(vec![other_backer]));
 
         Ok(())
     }
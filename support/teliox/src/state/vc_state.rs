@@ -36,7 +36,7 @@ impl TelState {
                     }
                 }
                 TelState::NotIssued => Err(Error::OutOfOrderError),
-                TelState::Revoked => Err(Error::Generic("Already revoked".into())),
+                TelState::Revoked => Err(Error::EventAlreadySavedError),
             },
             VCEventType::Iss(_iss) => match self {
                 TelState::NotIssued => Ok(TelState::Issued(event.digest()?)),
@@ -51,7 +51,7 @@ impl TelState {
                     }
                 }
                 TelState::NotIssued => Err(Error::OutOfOrderError),
-                TelState::Revoked => Err(Error::Generic("Already revoked".into())),
+                TelState::Revoked => Err(Error::EventAlreadySavedError),
             },
         }
     }
@@ -20,6 +20,9 @@ pub fn make_inception_event(
     derivation: Option<&HashFunctionCode>,
     serialization_format: Option<&SerializationFormats>,
 ) -> Result<Event, Error> {
+    if !config.contains(&Config::NoBackers) && backer_threshold > backers.len() as u64 {
+        return Err(Error::Generic("Improper backer threshold".into()));
+    }
     let event_type = Inc {
         issuer_id: issuer_prefix,
         config,
@@ -53,6 +56,7 @@ pub fn make_rotation_event(
     derivation: Option<&HashFunctionCode>,
     serialization_format: Option<&SerializationFormats>,
 ) -> Result<Event, Error> {
+    state.rotate_backers(ba, br)?;
     let rot_data = Rot {
         prev_event: state.last.clone(),
         backers_to_add: ba.to_vec(),
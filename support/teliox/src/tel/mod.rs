@@ -147,6 +147,26 @@ impl<D: TelEventDatabase, K: EventDatabase> Tel<D, K> {
         Ok(())
     }
 
+    /// Returns complete TEL of registry as CESR stream, see
+    /// `TelEventStorage::export_registry`.
+    pub fn export_tel(&self, registry_id: &IdentifierPrefix) -> Result<Vec<u8>, Error> {
+        self.processor.tel_reference.export_registry(registry_id)
+    }
+
+    /// Processes TEL exported with `export_tel`. Registry events are
+    /// processed before credential events, so that credential events
+    /// don't wait in escrow for their registry. Events anchored in
+    /// unknown KEL events are escrowed, as in `parse_and_process_tel_stream`.
+    pub fn import_tel(&self, stream: &[u8]) -> Result<(), Error> {
+        let (management, vc): (Vec<_>, Vec<_>) = VerifiableEvent::parse(stream)?
+            .into_iter()
+            .partition(|event| matches!(event.event, Event::Management(_)));
+        management
+            .into_iter()
+            .chain(vc)
+            .try_for_each(|event| self.processor.process(event))
+    }
+
     pub fn get_vc_state(
         &self,
         vc_hash: &SelfAddressingIdentifier,
@@ -196,4 +216,19 @@ impl<D: TelEventDatabase, K: EventDatabase> Tel<D, K> {
             .tel_reference
             .compute_management_tel_state(id)
     }
+
+    /// Returns current backers of registry along with backer threshold.
+    pub fn get_backers(
+        &self,
+        id: &IdentifierPrefix,
+    ) -> Result<Option<(Vec<IdentifierPrefix>, u64)>, Error> {
+        self.processor.tel_reference.get_backers(id)
+    }
+
+    /// Re-verifies all events of registry and its credentials. Returns
+    /// `Error::InconsistentTelError` pointing to the first event that
+    /// doesn't verify.
+    pub fn verify_tel(&self, registry_id: &IdentifierPrefix) -> Result<(), Error> {
+        self.processor.verify_tel(registry_id)
+    }
 }